@@ -0,0 +1,27 @@
+import { readFileSync } from "node:fs";
+import { loadKundera } from "./kundera-wasm.mjs";
+
+const wasmPath = new URL(
+	"../../target/wasm32-unknown-unknown/release/starknet_crypto_ffi.wasm",
+	import.meta.url,
+);
+const kundera = await loadKundera(readFileSync(wasmPath));
+
+const hex = (bytes) => `0x${Buffer.from(bytes).toString("hex")}`;
+const felt = (value) => {
+	const out = new Uint8Array(32);
+	new DataView(out.buffer).setBigUint64(24, BigInt(value));
+	return out;
+};
+
+console.log("random      ", hex(kundera.randomBytes(16)));
+console.log("1 / 2       ", hex(kundera.feltInverse(felt(2))));
+console.log("pedersen    ", hex(kundera.pedersenHash(felt(1), felt(2))));
+console.log("public key  ", hex(kundera.getPublicKey(felt(1))));
+console.log("keccak256   ", hex(kundera.keccak256(new TextEncoder().encode("transfer"))));
+
+try {
+	kundera.feltInverse(felt(0));
+} catch (error) {
+	console.log("error       ", error.message);
+}
@@ -0,0 +1,97 @@
+/**
+ * Minimal JS glue for the wasm32-unknown-unknown build of the crypto FFI
+ *
+ * Every pointer argument of the exported functions is an offset into the
+ * module's linear memory: inputs are copied into space reserved with
+ * stark_wasm_alloc, outputs read back from it, and the space released with
+ * stark_buffer_free. The host provides the module's randomness
+ * (kundera.random_fill).
+ */
+
+const FELT_SIZE = 32;
+
+export async function loadKundera(wasmBytes) {
+	let exports;
+
+	const imports = {
+		kundera: {
+			random_fill(ptr, len) {
+				try {
+					crypto.getRandomValues(new Uint8Array(exports.memory.buffer, ptr, len));
+					return 0;
+				} catch {
+					return 1;
+				}
+			},
+		},
+	};
+
+	// A dependency (lambdaworks-math, through getrandom 0.2) links
+	// wasm-bindgen, whose placeholder imports no export ever calls: stub them
+	const module = await WebAssembly.compile(wasmBytes);
+	for (const { module: name, name: field } of WebAssembly.Module.imports(module)) {
+		imports[name] ??= {};
+		imports[name][field] ??= () => {
+			throw new Error(`unexpected call to ${name}.${field}`);
+		};
+	}
+
+	const instance = await WebAssembly.instantiate(module, imports);
+	exports = instance.exports;
+
+	// Views are recreated on each access: memory.grow detaches old buffers
+	const bytes = (ptr, len) => new Uint8Array(exports.memory.buffer, ptr, len);
+
+	function readCString(ptr) {
+		const memory = new Uint8Array(exports.memory.buffer);
+		let end = ptr;
+		while (memory[end] !== 0) end++;
+		return new TextDecoder().decode(memory.subarray(ptr, end));
+	}
+
+	function check(code) {
+		if (code !== 0) {
+			throw new Error(readCString(exports.stark_last_error_message()));
+		}
+	}
+
+	/**
+	 * Run fn with linear-memory copies of inputs and `outLen` bytes of
+	 * output space; returns a copy of the output
+	 */
+	function call(inputs, outLen, fn) {
+		const allocated = [];
+		const reserve = (len) => {
+			const ptr = exports.stark_wasm_alloc(len);
+			allocated.push([ptr, len]);
+			return ptr;
+		};
+		try {
+			const ptrs = inputs.map((input) => {
+				const ptr = reserve(input.length);
+				bytes(ptr, input.length).set(input);
+				return ptr;
+			});
+			const out = reserve(outLen);
+			check(fn(...ptrs, out));
+			return bytes(out, outLen).slice();
+		} finally {
+			for (const [ptr, len] of allocated) exports.stark_buffer_free(ptr, len);
+		}
+	}
+
+	return {
+		exports,
+		randomBytes: (len) =>
+			call([], len, (out) => exports.stark_random_bytes(out, len)),
+		feltInverse: (a) => call([a], FELT_SIZE, exports.felt_inverse),
+		pedersenHash: (a, b) =>
+			call([a, b], FELT_SIZE, exports.starknet_pedersen_hash),
+		getPublicKey: (privateKey) =>
+			call([privateKey], FELT_SIZE, exports.starknet_get_public_key),
+		keccak256: (data) =>
+			call([data], FELT_SIZE, (ptr, out) =>
+				exports.starknet_keccak256(ptr, data.length, out),
+			),
+	};
+}
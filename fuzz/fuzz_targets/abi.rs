@@ -0,0 +1,80 @@
+//! Contract ABI: arbitrary ABI JSON, and arbitrary call arguments and
+//! results against the loaded ABI (a fixed one when the input is not an ABI)
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use starknet_crypto_ffi::abi::{abi_decode_result, abi_encode_call, abi_free, abi_load};
+use starknet_crypto_ffi::{FeltBytes, StarkResult};
+
+#[path = "common.rs"]
+mod common;
+use common::{bytes_out, check};
+
+const FALLBACK_ABI: &str = r#"[
+  { "type": "struct", "name": "core::integer::u256", "members": [
+      { "name": "low", "type": "core::integer::u128" },
+      { "name": "high", "type": "core::integer::u128" } ] },
+  { "type": "enum", "name": "core::bool", "variants": [
+      { "name": "False", "type": "()" }, { "name": "True", "type": "()" } ] },
+  { "type": "struct", "name": "demo::Order", "members": [
+      { "name": "owner", "type": "core::starknet::contract_address::ContractAddress" },
+      { "name": "amount", "type": "core::integer::u256" },
+      { "name": "tags", "type": "core::array::Span::<core::felt252>" },
+      { "name": "note", "type": "core::byte_array::ByteArray" },
+      { "name": "delta", "type": "core::integer::i32" } ] },
+  { "type": "enum", "name": "core::option::Option::<demo::Order>", "variants": [
+      { "name": "Some", "type": "demo::Order" }, { "name": "None", "type": "()" } ] },
+  { "type": "function", "name": "f",
+    "inputs": [{ "name": "order", "type": "core::option::Option::<demo::Order>" }],
+    "outputs": [{ "type": "core::array::Array::<demo::Order>" }],
+    "state_mutability": "external" }
+]"#;
+
+#[derive(Arbitrary, Debug)]
+struct Input<'a> {
+    abi_json: &'a [u8],
+    function_name: &'a str,
+    args_json: &'a [u8],
+    result: Vec<FeltBytes>,
+}
+
+fuzz_target!(|input: Input| {
+    let mut abi = std::ptr::null_mut();
+    unsafe {
+        let loaded = abi_load(input.abi_json.as_ptr(), input.abi_json.len(), &mut abi);
+        let function_name = if check(loaded) == StarkResult::Success {
+            input.function_name
+        } else {
+            let result = abi_load(FALLBACK_ABI.as_ptr(), FALLBACK_ABI.len(), &mut abi);
+            assert_eq!(result, StarkResult::Success);
+            "f"
+        };
+        let name = (function_name.as_ptr(), function_name.len());
+
+        bytes_out(|out, count| {
+            abi_encode_call(
+                abi,
+                name.0,
+                name.1,
+                input.args_json.as_ptr(),
+                input.args_json.len(),
+                out,
+                count,
+            )
+        });
+        bytes_out(|out, len| {
+            abi_decode_result(
+                abi,
+                name.0,
+                name.1,
+                input.result.as_ptr(),
+                input.result.len(),
+                out,
+                len,
+            )
+        });
+        abi_free(abi);
+    }
+});
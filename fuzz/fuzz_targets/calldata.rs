@@ -0,0 +1,69 @@
+//! Calldata reader: arbitrary felts read back as an arbitrary sequence of
+//! felts, u256s, arrays and byte arrays
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use starknet_crypto_ffi::byte_array::bytearray_from_felts;
+use starknet_crypto_ffi::calldata::{
+    calldata_reader_free, calldata_reader_new, calldata_reader_read_array,
+    calldata_reader_read_bytearray, calldata_reader_read_felt, calldata_reader_read_u256,
+    calldata_reader_remaining,
+};
+use starknet_crypto_ffi::{FeltBytes, StarkResult};
+
+#[path = "common.rs"]
+mod common;
+use common::{bytes_out, check};
+
+#[derive(Arbitrary, Debug)]
+enum Read {
+    Felt,
+    U256,
+    Array,
+    ByteArray,
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    felts: Vec<FeltBytes>,
+    reads: Vec<Read>,
+}
+
+fuzz_target!(|input: Input| {
+    let mut reader = std::ptr::null_mut();
+    unsafe {
+        let result = calldata_reader_new(input.felts.as_ptr(), input.felts.len(), &mut reader);
+        assert_eq!(check(result), StarkResult::Success);
+
+        let mut remaining = input.felts.len();
+        for read in &input.reads {
+            let mut out = [0u8; 32];
+            match read {
+                Read::Felt => {
+                    check(calldata_reader_read_felt(reader, &mut out));
+                }
+                Read::U256 => {
+                    check(calldata_reader_read_u256(reader, &mut out));
+                }
+                Read::Array => {
+                    // A failed read (too small) does not consume anything
+                    bytes_out(|out, count| calldata_reader_read_array(reader, out, count));
+                }
+                Read::ByteArray => {
+                    bytes_out(|out, len| calldata_reader_read_bytearray(reader, out, len));
+                }
+            }
+            let mut left = 0;
+            check(calldata_reader_remaining(reader, &mut left));
+            assert!(left <= remaining);
+            remaining = left;
+        }
+        calldata_reader_free(reader);
+
+        bytes_out(|out, len| {
+            bytearray_from_felts(input.felts.as_ptr(), input.felts.len(), out, len)
+        });
+    }
+});
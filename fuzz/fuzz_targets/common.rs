@@ -0,0 +1,51 @@
+//! Helpers shared by the fuzz targets
+
+#![allow(dead_code)]
+
+use std::ffi::CStr;
+use std::ptr::null_mut;
+
+use starknet_crypto_ffi::error::stark_last_error_message;
+use starknet_crypto_ffi::{FeltBytes, StarkResult};
+
+/// Fail the run on a caught panic (`ffi_guard` reports it as a result code)
+pub fn check(result: StarkResult) -> StarkResult {
+    if result == StarkResult::InternalPanic {
+        let message = unsafe { CStr::from_ptr(stark_last_error_message()) };
+        panic!("{}", message.to_string_lossy());
+    }
+    result
+}
+
+/// `data` as whole felts (trailing bytes dropped)
+pub fn felts(data: &[u8]) -> Vec<FeltBytes> {
+    data.chunks_exact(32)
+        .map(|chunk| chunk.try_into().unwrap())
+        .collect()
+}
+
+/// Run a variable-length output function the way a C caller does: query
+/// the length with a null buffer, then call again with that capacity.
+/// `None` if the function failed.
+pub fn bytes_out<T: Copy + Default>(
+    f: impl Fn(*mut T, *mut usize) -> StarkResult,
+) -> Option<Vec<T>> {
+    let mut len = 0;
+    match check(f(null_mut(), &mut len)) {
+        StarkResult::Success => return Some(Vec::new()),
+        StarkResult::BufferTooSmall => {}
+        _ => return None,
+    }
+    let mut out = vec![T::default(); len];
+    let mut out_len = len;
+    assert_eq!(
+        check(f(out.as_mut_ptr(), &mut out_len)),
+        StarkResult::Success
+    );
+    assert!(
+        out_len <= len,
+        "wrote {out_len} items after asking for {len}"
+    );
+    out.truncate(out_len);
+    Some(out)
+}
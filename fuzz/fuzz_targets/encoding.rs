@@ -0,0 +1,57 @@
+//! Hex, base64 and base58: arbitrary bytes round-trip, arbitrary text
+//! decodes without panicking
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use starknet_crypto_ffi::encoding::{
+    base58_decode, base58_encode, base64_decode, base64_encode, hex_decode, hex_encode,
+};
+
+#[path = "common.rs"]
+mod common;
+use common::bytes_out;
+
+#[derive(Arbitrary, Debug)]
+struct Input<'a> {
+    data: &'a [u8],
+    text: &'a [u8],
+    url_safe: bool,
+    checksum: bool,
+}
+
+fuzz_target!(|input: Input| {
+    let Input {
+        data,
+        text,
+        url_safe,
+        checksum,
+    } = input;
+    let (ptr, len) = (data.as_ptr(), data.len());
+
+    unsafe {
+        let hex = bytes_out(|out, out_len| hex_encode(ptr, len, out, out_len)).unwrap();
+        let decoded = bytes_out(|out, out_len| hex_decode(hex.as_ptr(), hex.len(), out, out_len));
+        assert_eq!(decoded.as_deref(), Some(data));
+
+        let b64 =
+            bytes_out(|out, out_len| base64_encode(ptr, len, url_safe, out, out_len)).unwrap();
+        let decoded = bytes_out(|out, out_len| {
+            base64_decode(b64.as_ptr(), b64.len(), url_safe, out, out_len)
+        });
+        assert_eq!(decoded.as_deref(), Some(data));
+
+        let b58 =
+            bytes_out(|out, out_len| base58_encode(ptr, len, checksum, out, out_len)).unwrap();
+        let decoded = bytes_out(|out, out_len| {
+            base58_decode(b58.as_ptr(), b58.len(), checksum, out, out_len)
+        });
+        assert_eq!(decoded.as_deref(), Some(data));
+
+        let (ptr, len) = (text.as_ptr(), text.len());
+        bytes_out(|out, out_len| hex_decode(ptr, len, out, out_len));
+        bytes_out(|out, out_len| base64_decode(ptr, len, url_safe, out, out_len));
+        bytes_out(|out, out_len| base58_decode(ptr, len, checksum, out, out_len));
+    }
+});
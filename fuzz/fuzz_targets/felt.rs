@@ -0,0 +1,53 @@
+//! Felt arithmetic on arbitrary 32-byte inputs (reduced modulo P)
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use starknet_crypto_ffi::{
+    felt_add, felt_div, felt_inverse, felt_mul, felt_neg, felt_pow, felt_sqrt, felt_sub, FeltBytes,
+    StarkResult,
+};
+
+#[path = "common.rs"]
+mod common;
+use common::check;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    a: FeltBytes,
+    b: FeltBytes,
+}
+
+fuzz_target!(|input: Input| {
+    let Input { a, b } = input;
+    let zero = [0u8; 32];
+    let (mut a_reduced, mut out, mut back) = ([0u8; 32], [0u8; 32], [0u8; 32]);
+
+    unsafe {
+        assert_eq!(
+            check(felt_add(&a, &zero, &mut a_reduced)),
+            StarkResult::Success
+        );
+
+        assert_eq!(check(felt_add(&a, &b, &mut out)), StarkResult::Success);
+        assert_eq!(check(felt_sub(&out, &b, &mut back)), StarkResult::Success);
+        assert_eq!(back, a_reduced);
+
+        check(felt_mul(&a, &b, &mut out));
+        if check(felt_div(&a, &b, &mut out)) == StarkResult::Success {
+            check(felt_mul(&out, &b, &mut back));
+            assert_eq!(back, a_reduced);
+        }
+        if check(felt_inverse(&a, &mut out)) == StarkResult::Success {
+            check(felt_mul(&out, &a, &mut back));
+            assert_eq!(back[31], 1);
+        }
+        if check(felt_sqrt(&a, &mut out)) == StarkResult::Success {
+            check(felt_mul(&out, &out, &mut back));
+            assert_eq!(back, a_reduced);
+        }
+        check(felt_neg(&a, &mut out));
+        check(felt_pow(&a, &b, &mut out));
+    }
+});
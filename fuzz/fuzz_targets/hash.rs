@@ -0,0 +1,44 @@
+//! Hashes over arbitrary inputs: Poseidon over any felt count, Pedersen,
+//! keccak over any byte length
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use starknet_crypto_ffi::{
+    keccak256, starknet_keccak256, starknet_pedersen_hash, starknet_poseidon_hash,
+    starknet_poseidon_hash_many, StarkResult,
+};
+
+#[path = "common.rs"]
+mod common;
+use common::{check, felts};
+
+fuzz_target!(|data: &[u8]| {
+    let felts = felts(data);
+    let mut out = [0u8; 32];
+    let mut pair = [0u8; 32];
+
+    unsafe {
+        let result = starknet_poseidon_hash_many(felts.as_ptr(), felts.len(), &mut out);
+        let expected = if felts.is_empty() {
+            StarkResult::InvalidInput
+        } else {
+            StarkResult::Success
+        };
+        assert_eq!(check(result), expected);
+        if let [a, b] = felts[..] {
+            check(starknet_poseidon_hash(&a, &b, &mut pair));
+            check(starknet_pedersen_hash(&a, &b, &mut pair));
+        }
+
+        assert_eq!(
+            check(keccak256(data.as_ptr(), data.len(), &mut out)),
+            StarkResult::Success
+        );
+        assert_eq!(
+            check(starknet_keccak256(data.as_ptr(), data.len(), &mut out)),
+            StarkResult::Success
+        );
+        assert!(out[0] < 0x04, "starknet_keccak256 is not 250 bits");
+    }
+});
@@ -0,0 +1,65 @@
+//! Signature parsing and verification on arbitrary inputs: STARK ECDSA
+//! verify/recover/normalize, DER decoding and secp256k1 recovery
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use starknet_crypto_ffi::secp256k1::secp256k1_recover;
+use starknet_crypto_ffi::signature::{
+    signature_from_der, signature_to_der, starknet_signature_normalize,
+};
+use starknet_crypto_ffi::{starknet_recover, starknet_verify, FeltBytes, StarkResult};
+
+#[path = "common.rs"]
+mod common;
+use common::{bytes_out, check};
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    public_key: FeltBytes,
+    message_hash: FeltBytes,
+    r: FeltBytes,
+    s: FeltBytes,
+    v: u8,
+    der: Vec<u8>,
+}
+
+fuzz_target!(|input: Input| {
+    let Input {
+        public_key,
+        message_hash,
+        r,
+        s,
+        v,
+        der,
+    } = input;
+    let mut v_felt = [0u8; 32];
+    v_felt[31] = v;
+    let mut out = [0u8; 32];
+    let mut point = [0u8; 64];
+
+    unsafe {
+        check(starknet_verify(&public_key, &message_hash, &r, &s));
+        check(starknet_recover(&message_hash, &r, &s, &v_felt, &mut out));
+        check(starknet_signature_normalize(&r, &s, &mut out));
+        check(secp256k1_recover(&message_hash, &r, &s, v, &mut point));
+
+        // Whatever DER decodes re-encodes to the same signature
+        let (mut der_r, mut der_s) = ([0u8; 32], [0u8; 32]);
+        if check(signature_from_der(
+            der.as_ptr(),
+            der.len(),
+            &mut der_r,
+            &mut der_s,
+        )) == StarkResult::Success
+        {
+            let encoded = bytes_out(|out, out_len| signature_to_der(&der_r, &der_s, out, out_len))
+                .expect("a decoded signature must encode");
+            let (mut r2, mut s2) = ([0u8; 32], [0u8; 32]);
+            let result = signature_from_der(encoded.as_ptr(), encoded.len(), &mut r2, &mut s2);
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!((r2, s2), (der_r, der_s));
+        }
+    }
+});
@@ -0,0 +1,23 @@
+//! SNIP-12 typed data hashing of arbitrary JSON
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use starknet_crypto_ffi::typed_data::starknet_typed_data_hash;
+
+#[path = "common.rs"]
+mod common;
+use common::check;
+
+fuzz_target!(|data: &[u8]| {
+    let account = [0x12u8; 32];
+    let mut out = [0u8; 32];
+    unsafe {
+        check(starknet_typed_data_hash(
+            data.as_ptr(),
+            data.len(),
+            &account,
+            &mut out,
+        ));
+    }
+});
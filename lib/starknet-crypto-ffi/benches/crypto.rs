@@ -0,0 +1,422 @@
+//! Benchmarks of the hot exported functions, called through the C ABI as a
+//! host would call them
+//!
+//! `cargo bench` (or `cargo bench -- poseidon` to filter). The batch groups
+//! compare one batch call with the same work done call by call, which is
+//! what decides whether a batch API pays off.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use starknet_crypto_ffi::arena::{stark_arena_free, stark_arena_new};
+use starknet_crypto_ffi::felt_array::{
+    stark_felt_array_free, stark_felt_array_new, starknet_poseidon_hash_felt_array,
+};
+use starknet_crypto_ffi::felt_batch::{felt_inner_product, felt_vec_mul};
+use starknet_crypto_ffi::rescue::rescue_prime_hash;
+use starknet_crypto_ffi::sign_ct::starknet_sign_ct;
+use starknet_crypto_ffi::transaction::{
+    starknet_sign_invoke_v3, StarknetInvokeV3, StarknetResourceBounds, DA_MODE_L1,
+};
+use starknet_crypto_ffi::typed_data::{
+    starknet_merkle_root, starknet_typed_data_hash, MERKLE_HASH_POSEIDON,
+};
+use starknet_crypto_ffi::verify::{
+    starknet_verify_batch, starknet_verify_batch_soa, starknet_verify_context_free,
+    starknet_verify_context_new, starknet_verify_context_verify, StarknetVerifyItem,
+};
+use starknet_crypto_ffi::{
+    felt_add, felt_inverse, felt_mul, keccak256, starknet_get_public_key, starknet_pedersen_hash,
+    starknet_poseidon_hash, starknet_poseidon_hash_many, starknet_sign, starknet_verify, FeltBytes,
+    StarkResult,
+};
+
+/// A felt below P whose low bytes are `n`
+fn felt(n: u64) -> FeltBytes {
+    let mut bytes = [0u8; 32];
+    bytes[0] = 0x04;
+    bytes[24..].copy_from_slice(&n.to_be_bytes());
+    bytes
+}
+
+struct Signed {
+    private_key: FeltBytes,
+    public_key: FeltBytes,
+    message_hash: FeltBytes,
+    r: FeltBytes,
+    s: FeltBytes,
+}
+
+fn signed(n: u64) -> Signed {
+    let private_key = felt(n + 1);
+    let message_hash = felt(n + 1000);
+    let (mut public_key, mut r, mut s) = ([0u8; 32], [0u8; 32], [0u8; 32]);
+    unsafe {
+        assert_eq!(
+            starknet_get_public_key(&private_key, &mut public_key),
+            StarkResult::Success
+        );
+        assert_eq!(
+            starknet_sign(&private_key, &message_hash, &mut r, &mut s),
+            StarkResult::Success
+        );
+    }
+    Signed {
+        private_key,
+        public_key,
+        message_hash,
+        r,
+        s,
+    }
+}
+
+fn felt_arith(c: &mut Criterion) {
+    let (a, b) = (felt(12345), felt(67890));
+    let mut out = [0u8; 32];
+    c.bench_function("felt_mul", |bench| {
+        bench.iter(|| unsafe { felt_mul(black_box(&a), black_box(&b), &mut out) })
+    });
+    c.bench_function("felt_inverse", |bench| {
+        bench.iter(|| unsafe { felt_inverse(black_box(&a), &mut out) })
+    });
+
+    let mut group = c.benchmark_group("felt_vec_mul");
+    for count in [16u64, 1024] {
+        let a: Vec<FeltBytes> = (0..count).map(felt).collect();
+        let b: Vec<FeltBytes> = (0..count).map(|n| felt(n + count)).collect();
+        let mut outs = vec![[0u8; 32]; a.len()];
+        group.throughput(Throughput::Elements(count));
+        group.bench_function(BenchmarkId::new("batch", count), |bench| {
+            bench.iter(|| unsafe {
+                felt_vec_mul(a.as_ptr(), b.as_ptr(), a.len(), outs.as_mut_ptr())
+            })
+        });
+        group.bench_function(BenchmarkId::new("sequential", count), |bench| {
+            bench.iter(|| {
+                for ((a, b), out) in a.iter().zip(&b).zip(&mut outs) {
+                    unsafe { felt_mul(a, b, out) };
+                }
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("felt_inner_product");
+    for count in [16u64, 1024] {
+        let a: Vec<FeltBytes> = (0..count).map(felt).collect();
+        let b: Vec<FeltBytes> = (0..count).map(|n| felt(n + count)).collect();
+        group.throughput(Throughput::Elements(count));
+        group.bench_function(BenchmarkId::new("batch", count), |bench| {
+            bench.iter(|| unsafe { felt_inner_product(a.as_ptr(), b.as_ptr(), a.len(), &mut out) })
+        });
+        group.bench_function(BenchmarkId::new("sequential", count), |bench| {
+            bench.iter(|| {
+                let mut product = [0u8; 32];
+                out = [0u8; 32];
+                for (a, b) in a.iter().zip(&b) {
+                    unsafe {
+                        felt_mul(a, b, &mut product);
+                        felt_add(&out, &product, &mut out);
+                    }
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+/// A felt with every byte set (the cost of some functions grows with the
+/// number of non-zero bits)
+fn dense_felt(byte: u8) -> FeltBytes {
+    let mut bytes = [byte; 32];
+    bytes[0] = 0x04;
+    bytes
+}
+
+fn hashing(c: &mut Criterion) {
+    let (a, b) = (dense_felt(0x5a), dense_felt(0xc3));
+    let mut out = [0u8; 32];
+    c.bench_function("pedersen_hash", |bench| {
+        bench.iter(|| unsafe { starknet_pedersen_hash(black_box(&a), black_box(&b), &mut out) })
+    });
+    #[cfg(feature = "pedersen-tables")]
+    for bits in [8, 12] {
+        use starknet_crypto_ffi::pedersen::stark_set_pedersen_table_size;
+
+        assert_eq!(stark_set_pedersen_table_size(bits), StarkResult::Success);
+        c.bench_function(&format!("pedersen_hash_table_{bits}"), |bench| {
+            bench.iter(|| unsafe { starknet_pedersen_hash(black_box(&a), black_box(&b), &mut out) })
+        });
+        assert_eq!(stark_set_pedersen_table_size(0), StarkResult::Success);
+    }
+    c.bench_function("poseidon_hash", |bench| {
+        bench.iter(|| unsafe { starknet_poseidon_hash(black_box(&a), black_box(&b), &mut out) })
+    });
+
+    let mut group = c.benchmark_group("poseidon_hash_many");
+    for count in [2, 16, 256] {
+        let inputs: Vec<FeltBytes> = (0..count).map(felt).collect();
+        group.throughput(Throughput::Elements(count));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(count),
+            &inputs,
+            |bench, inputs| {
+                bench.iter(|| unsafe {
+                    starknet_poseidon_hash_many(inputs.as_ptr(), inputs.len(), &mut out)
+                })
+            },
+        );
+
+        let mut array = std::ptr::null_mut();
+        unsafe {
+            assert_eq!(
+                stark_felt_array_new(inputs.as_ptr(), inputs.len(), &mut array),
+                StarkResult::Success
+            );
+        }
+        group.bench_function(BenchmarkId::new("felt_array", count), |bench| {
+            bench.iter(|| unsafe {
+                starknet_poseidon_hash_felt_array(array, 0, count as usize, &mut out)
+            })
+        });
+        unsafe { stark_felt_array_free(array) };
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("rescue_prime_hash");
+    for count in [2, 16, 256] {
+        let inputs: Vec<FeltBytes> = (0..count).map(felt).collect();
+        group.throughput(Throughput::Elements(count));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(count),
+            &inputs,
+            |bench, inputs| {
+                bench.iter(|| unsafe { rescue_prime_hash(inputs.as_ptr(), inputs.len(), &mut out) })
+            },
+        );
+    }
+    group.finish();
+
+    let mut arena = std::ptr::null_mut();
+    unsafe { assert_eq!(stark_arena_new(0, &mut arena), StarkResult::Success) };
+    let mut group = c.benchmark_group("merkle_root");
+    for count in [16u64, 1024] {
+        let leaves: Vec<FeltBytes> = (0..count).map(felt).collect();
+        group.throughput(Throughput::Elements(count));
+        for (name, arena) in [
+            ("arena", arena.cast_const()),
+            ("no_arena", std::ptr::null()),
+        ] {
+            group.bench_function(BenchmarkId::new(name, count), |bench| {
+                bench.iter(|| unsafe {
+                    starknet_merkle_root(
+                        leaves.as_ptr(),
+                        leaves.len(),
+                        MERKLE_HASH_POSEIDON,
+                        arena,
+                        &mut out,
+                    )
+                })
+            });
+        }
+    }
+    group.finish();
+    unsafe { stark_arena_free(arena) };
+
+    let mut group = c.benchmark_group("keccak256");
+    for len in [32, 1024] {
+        let data = vec![0xa5u8; len];
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(len), &data, |bench, data| {
+            bench.iter(|| unsafe { keccak256(data.as_ptr(), data.len(), &mut out) })
+        });
+    }
+    group.finish();
+}
+
+fn ecdsa(c: &mut Criterion) {
+    let sig = signed(0);
+    let (mut public_key, mut r, mut s) = ([0u8; 32], [0u8; 32], [0u8; 32]);
+    c.bench_function("get_public_key", |bench| {
+        bench.iter(|| unsafe {
+            starknet_get_public_key(black_box(&sig.private_key), &mut public_key)
+        })
+    });
+    c.bench_function("sign", |bench| {
+        bench.iter(|| unsafe {
+            starknet_sign(
+                &sig.private_key,
+                black_box(&sig.message_hash),
+                &mut r,
+                &mut s,
+            )
+        })
+    });
+    c.bench_function("sign_ct", |bench| {
+        bench.iter(|| unsafe {
+            starknet_sign_ct(
+                &sig.private_key,
+                black_box(&sig.message_hash),
+                &mut r,
+                &mut s,
+            )
+        })
+    });
+    c.bench_function("verify", |bench| {
+        bench.iter(|| unsafe {
+            starknet_verify(
+                &sig.public_key,
+                black_box(&sig.message_hash),
+                &sig.r,
+                &sig.s,
+            )
+        })
+    });
+
+    let mut ctx = std::ptr::null_mut();
+    unsafe {
+        assert_eq!(
+            starknet_verify_context_new(&sig.public_key, &mut ctx),
+            StarkResult::Success
+        );
+    }
+    c.bench_function("verify_context_verify", |bench| {
+        bench.iter(|| unsafe {
+            starknet_verify_context_verify(ctx, black_box(&sig.message_hash), &sig.r, &sig.s)
+        })
+    });
+    unsafe { starknet_verify_context_free(ctx) };
+}
+
+/// One `starknet_verify_batch` (or `_soa`) call against `count`
+/// `starknet_verify` calls
+fn verify_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify_batch");
+    for count in [1u64, 16, 128] {
+        let items: Vec<StarknetVerifyItem> = (0..count)
+            .map(|n| {
+                let sig = signed(n);
+                StarknetVerifyItem {
+                    public_key: sig.public_key,
+                    message_hash: sig.message_hash,
+                    r: sig.r,
+                    s: sig.s,
+                }
+            })
+            .collect();
+        let mut results = vec![StarkResult::Success; items.len()];
+        let fields: [Vec<FeltBytes>; 4] = [
+            items.iter().map(|item| item.public_key).collect(),
+            items.iter().map(|item| item.message_hash).collect(),
+            items.iter().map(|item| item.r).collect(),
+            items.iter().map(|item| item.s).collect(),
+        ];
+        let mut bitmap = vec![0u8; items.len().div_ceil(8)];
+        group.throughput(Throughput::Elements(count));
+        group.bench_with_input(BenchmarkId::new("batch", count), &items, |bench, items| {
+            bench.iter(|| unsafe {
+                starknet_verify_batch(
+                    items.as_ptr(),
+                    items.len(),
+                    std::ptr::null(),
+                    results.as_mut_ptr(),
+                )
+            })
+        });
+        group.bench_function(BenchmarkId::new("soa", count), |bench| {
+            bench.iter(|| unsafe {
+                starknet_verify_batch_soa(
+                    fields[0].as_ptr(),
+                    fields[1].as_ptr(),
+                    fields[2].as_ptr(),
+                    fields[3].as_ptr(),
+                    items.len(),
+                    std::ptr::null(),
+                    bitmap.as_mut_ptr(),
+                )
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("sequential", count),
+            &items,
+            |bench, items| {
+                bench.iter(|| {
+                    for item in items {
+                        unsafe {
+                            starknet_verify(&item.public_key, &item.message_hash, &item.r, &item.s);
+                        }
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+const TYPED_DATA: &str = r#"{
+  "types": {
+    "StarknetDomain": [
+      { "name": "name", "type": "shortstring" },
+      { "name": "version", "type": "shortstring" },
+      { "name": "chainId", "type": "shortstring" },
+      { "name": "revision", "type": "shortstring" }
+    ],
+    "Transfer": [
+      { "name": "recipient", "type": "ContractAddress" },
+      { "name": "amount", "type": "u128" },
+      { "name": "memo", "type": "string" }
+    ]
+  },
+  "primaryType": "Transfer",
+  "domain": { "name": "Bench", "version": "1", "chainId": "SN_MAIN", "revision": "1" },
+  "message": { "recipient": "0x1234", "amount": "1000", "memo": "benchmark transfer" }
+}"#;
+
+fn transactions(c: &mut Criterion) {
+    let account = felt(0x1234);
+    let mut out = [0u8; 32];
+    c.bench_function("typed_data_hash", |bench| {
+        bench.iter(|| unsafe {
+            starknet_typed_data_hash(TYPED_DATA.as_ptr(), TYPED_DATA.len(), &account, &mut out)
+        })
+    });
+
+    let calldata: Vec<FeltBytes> = (0..8).map(felt).collect();
+    let bounds = StarknetResourceBounds {
+        max_amount: 100_000,
+        max_price_per_unit: [0u8; 32],
+    };
+    let tx = StarknetInvokeV3 {
+        sender_address: account,
+        calldata: calldata.as_ptr(),
+        calldata_len: calldata.len(),
+        nonce: felt(1),
+        tip: 0,
+        l1_gas: bounds,
+        l2_gas: bounds,
+        l1_data_gas: bounds,
+        paymaster_data: std::ptr::null(),
+        paymaster_data_len: 0,
+        account_deployment_data: std::ptr::null(),
+        account_deployment_data_len: 0,
+        nonce_data_availability_mode: DA_MODE_L1,
+        fee_data_availability_mode: DA_MODE_L1,
+    };
+    let (private_key, chain_id) = (felt(1), felt(0x534e5f4d41494e));
+    let (mut r, mut s) = ([0u8; 32], [0u8; 32]);
+    c.bench_function("sign_invoke_v3", |bench| {
+        bench.iter(|| unsafe {
+            starknet_sign_invoke_v3(&private_key, &tx, &chain_id, &mut r, &mut s, &mut out)
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    felt_arith,
+    hashing,
+    ecdsa,
+    verify_batch,
+    transactions
+);
+criterion_main!(benches);
@@ -0,0 +1,144 @@
+//! Generates the C header (and, with the `zig` feature, Zig bindings) from
+//! the extern signatures
+//!
+//! The files are written to `OUT_DIR` and copied to `include/` next to the
+//! static and shared libraries (`target/<profile>/include/`), so they are
+//! installed together with them and always match the build.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "zig")]
+#[path = "build/zig.rs"]
+mod zig;
+
+const HEADER: &str = "starknet_crypto_ffi.h";
+/// Prefix of every exported function with the `symbol-prefix` feature
+#[cfg(feature = "symbol-prefix")]
+const SYMBOL_PREFIX: &str = "kundera_";
+/// Subsystem features, each guarding its declarations with `STARK_FEATURE_*`
+const SUBSYSTEMS: &[&str] = &[
+    "hashing",
+    "ecdsa",
+    "secp",
+    "merkle",
+    "keystore",
+    "encoding",
+    "bls",
+    "kzg",
+    "ed25519",
+    "x25519",
+    "aead",
+    "sealed_box",
+    "ecies",
+    "mnemonic",
+    "abi",
+    "typed_data",
+];
+#[cfg(feature = "zig")]
+const ZIG_BINDINGS: &str = "starknet_crypto.zig";
+
+fn main() {
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let ffi_dir = crate_dir.join("lib/starknet-crypto-ffi");
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+    #[cfg(feature = "napi")]
+    napi_build::setup();
+
+    println!("cargo:rerun-if-changed=lib/starknet-crypto-ffi/src");
+    println!("cargo:rerun-if-changed=lib/starknet-crypto-ffi/cbindgen.toml");
+
+    let mut config =
+        cbindgen::Config::from_file(ffi_dir.join("cbindgen.toml")).expect("invalid cbindgen.toml");
+    // cbindgen ignores the `cfg` on `mod allocator` and `mod wasm`: drop
+    // them from builds that do not compile them
+    if env::var_os("CARGO_FEATURE_STD").is_some() {
+        config.export.exclude.extend(
+            [
+                "stark_set_allocator",
+                "StarkMallocFn",
+                "StarkFreeFn",
+                "ALLOCATOR_ALIGN",
+            ]
+            .map(String::from),
+        );
+    }
+    // Define the guards of the subsystems this build exports
+    for feature in SUBSYSTEMS {
+        if env::var_os(format!("CARGO_FEATURE_{}", feature.to_uppercase())).is_some() {
+            config.after_includes.get_or_insert_with(String::new).push_str(&format!(
+                "\n#define STARK_FEATURE_{}",
+                feature.to_uppercase()
+            ));
+        }
+    }
+    let wasm_target = env::var("CARGO_CFG_TARGET_ARCH").is_ok_and(|arch| arch == "wasm32");
+    if env::var_os("CARGO_FEATURE_WASM").is_none() || !wasm_target {
+        config.export.exclude.push("stark_wasm_alloc".into());
+    }
+    #[allow(unused_mut)]
+    let mut bindings = cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(ffi_dir.join("src/lib.rs"))
+        .generate()
+        .expect("failed to generate the C header");
+    write_arguments(&out_dir, &bindings);
+    // cbindgen does not see the `export_name` inside `cfg_attr` (imports
+    // from the host keep their names)
+    #[cfg(feature = "symbol-prefix")]
+    for function in bindings.functions.iter_mut().filter(|f| !f.extern_decl) {
+        function.path = cbindgen::ir::Path::new(format!("{SYMBOL_PREFIX}{}", function.path.name()));
+    }
+    let header = out_dir.join(HEADER);
+    bindings.write_to_file(&header);
+    install(&out_dir, &header);
+
+    #[cfg(feature = "zig")]
+    {
+        let path = out_dir.join(ZIG_BINDINGS);
+        fs::write(&path, zig::generate(&bindings)).expect("failed to write the Zig bindings");
+        install(&out_dir, &path);
+    }
+}
+
+/// Write the parameter names of every extern function, for the argument
+/// index of `StarkErrorInfo` (included by `error.rs`)
+fn write_arguments(out_dir: &Path, bindings: &cbindgen::Bindings) {
+    let mut functions: Vec<_> = bindings
+        .functions
+        .iter()
+        .filter(|f| !f.extern_decl)
+        .map(|f| {
+            let args: Vec<_> = f
+                .args
+                .iter()
+                .map(|arg| format!("{:?}", arg.name.as_deref().unwrap_or("")))
+                .collect();
+            format!("    ({:?}, &[{}]),\n", f.path.name(), args.join(", "))
+        })
+        .collect();
+    functions.sort();
+    fs::write(
+        out_dir.join("arguments.rs"),
+        format!(
+            "/// Parameter names of each extern function, sorted by name\n\
+             static ARGUMENTS: &[(&str, &[&str])] = &[\n{}];\n",
+            functions.concat()
+        ),
+    )
+    .expect("failed to write the argument table");
+}
+
+/// Copy a generated file to `include/` next to the libraries
+fn install(out_dir: &Path, path: &Path) {
+    // OUT_DIR is target/<profile>/build/<pkg>-<hash>/out
+    let Some(profile_dir) = out_dir.ancestors().nth(3) else {
+        return;
+    };
+    let include_dir = profile_dir.join("include");
+    fs::create_dir_all(&include_dir).expect("failed to create include directory");
+    fs::copy(path, include_dir.join(path.file_name().unwrap()))
+        .expect("failed to install the generated bindings");
+}
@@ -0,0 +1,715 @@
+//! Zig bindings (`starknet_crypto.zig`), generated from the cbindgen IR
+//!
+//! The generated module declares the raw C ABI in a `c` namespace, then one
+//! wrapper per function:
+//! - a `StarkResult` return becomes an `Error!T` union (`check`);
+//! - a pointer followed by a `len`/`count` argument becomes a slice;
+//! - `out*` pointers become the return value (a struct if there are
+//!   several), and an `out` buffer with its `out_len`/`out_count` capacity
+//!   becomes a caller slice whose written prefix is returned (a library
+//!   allocated `out` buffer is returned as a slice to free with
+//!   `bufferFree`).
+
+use std::collections::HashSet;
+use std::env;
+use std::fmt::Write;
+
+use cbindgen::ir::{
+    Cfg, ConstExpr, Documentation, Function, IntKind, ItemContainer, Literal, PrimitiveType, Type,
+};
+use cbindgen::Bindings;
+
+/// Pointer arguments documented as optional (null is accepted)
+const OPTIONAL_ARGS: &[(&str, &str)] =
+    &[("bip340_sign", "aux_rand"), ("starknet_rfc6979_k", "seed")];
+
+const KEYWORDS: &[&str] = &[
+    "addrspace",
+    "align",
+    "allowzero",
+    "and",
+    "anyframe",
+    "anytype",
+    "asm",
+    "break",
+    "callconv",
+    "catch",
+    "comptime",
+    "const",
+    "continue",
+    "defer",
+    "else",
+    "enum",
+    "errdefer",
+    "error",
+    "export",
+    "extern",
+    "fn",
+    "for",
+    "if",
+    "inline",
+    "linksection",
+    "noalias",
+    "noinline",
+    "nosuspend",
+    "opaque",
+    "or",
+    "orelse",
+    "packed",
+    "pub",
+    "resume",
+    "return",
+    "struct",
+    "suspend",
+    "switch",
+    "test",
+    "threadlocal",
+    "try",
+    "union",
+    "unreachable",
+    "var",
+    "volatile",
+    "while",
+    "type",
+    "void",
+    "bool",
+    "anyopaque",
+    "anyerror",
+    "noreturn",
+    "usize",
+    "isize",
+];
+
+/// Where a type is used: the raw `extern fn` declarations take C pointers,
+/// the wrappers take Zig pointers
+#[derive(Clone, Copy, PartialEq)]
+enum Ctx {
+    Raw,
+    RawReturn,
+    Wrapper,
+}
+
+/// A wrapper output: a single value, or a caller or library buffer and its
+/// length
+enum Output {
+    Value {
+        var: String,
+        field: String,
+        ty: String,
+        handle: bool,
+    },
+    Buffer {
+        param: String,
+        len_var: String,
+        field: String,
+        elem: String,
+    },
+}
+
+struct Generator {
+    opaque: HashSet<String>,
+    /// Container-level names, which parameters must not shadow
+    reserved: HashSet<String>,
+    out: String,
+}
+
+pub fn generate(bindings: &Bindings) -> String {
+    let mut gen = Generator {
+        opaque: HashSet::new(),
+        reserved: ["std", "c", "Error", "check"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        out: String::new(),
+    };
+    for item in &bindings.items {
+        if let ItemContainer::OpaqueItem(o) = item {
+            gen.opaque.insert(o.export_name.clone());
+        }
+        gen.reserved.insert(item_name(item).to_owned());
+    }
+    for constant in &bindings.constants {
+        gen.reserved.insert(constant.export_name.clone());
+    }
+    for function in &bindings.functions {
+        gen.reserved.insert(camel_case(unprefixed(function)));
+    }
+
+    gen.out.push_str(
+        "//! Zig bindings for the starknet-crypto FFI library\n\
+         //!\n\
+         //! Generated by build.rs from the Rust sources. Do not edit.\n\
+         //!\n\
+         //! `c` declares the raw C ABI. The wrappers turn result codes into `Error`\n\
+         //! unions, (pointer, length) pairs into slices and output pointers into\n\
+         //! return values. A wrapper writing to a caller buffer returns the written\n\
+         //! prefix; `error.InvalidInput` can mean the buffer is too small (the raw\n\
+         //! function reports the required length). `stark_last_error_message`\n\
+         //! describes the last error.\n\n\
+         const std = @import(\"std\");\n",
+    );
+    gen.constants(bindings);
+    gen.items(bindings);
+    gen.errors(bindings);
+    gen.raw_functions(bindings);
+    for function in &bindings.functions {
+        if enabled(&function.cfg) {
+            gen.wrapper(function);
+        }
+    }
+    gen.out
+}
+
+fn item_name(item: &ItemContainer) -> &str {
+    match item {
+        ItemContainer::Constant(i) => &i.export_name,
+        ItemContainer::Static(i) => &i.export_name,
+        ItemContainer::OpaqueItem(i) => &i.export_name,
+        ItemContainer::Struct(i) => &i.export_name,
+        ItemContainer::Union(i) => &i.export_name,
+        ItemContainer::Enum(i) => &i.export_name,
+        ItemContainer::Typedef(i) => &i.export_name,
+    }
+}
+
+/// Function name without the `symbol-prefix` prefix (wrappers live in the
+/// module's namespace and keep the short name)
+fn unprefixed(function: &Function) -> &str {
+    let name = function.path.name();
+    #[cfg(feature = "symbol-prefix")]
+    let name = name.strip_prefix(super::SYMBOL_PREFIX).unwrap_or(name);
+    name
+}
+
+/// `felt_add` -> `feltAdd`, `bls12_381_sign` -> `bls12381Sign`
+fn camel_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, part) in name.split('_').filter(|p| !p.is_empty()).enumerate() {
+        let mut chars = part.chars();
+        if i > 0 {
+            out.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+        }
+        out.extend(chars);
+    }
+    out
+}
+
+/// Lowercase words of an enum variant, undoing the config's renaming
+/// (`STARK_RESULT_INVALID_INPUT` or `InvalidInput` -> `invalid`, `input`)
+fn variant_words(enum_name: &str, variant: &str) -> Vec<String> {
+    let mut prefix = String::new();
+    for (i, c) in enum_name.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            prefix.push('_');
+        }
+        prefix.push(c.to_ascii_uppercase());
+    }
+    prefix.push('_');
+    let variant = variant.strip_prefix(&prefix).unwrap_or(variant);
+
+    if variant.contains('_') || !variant.chars().any(|c| c.is_ascii_lowercase()) {
+        return variant.split('_').map(str::to_ascii_lowercase).collect();
+    }
+    let mut words: Vec<String> = Vec::new();
+    for c in variant.chars() {
+        if c.is_ascii_uppercase() || words.is_empty() {
+            words.push(String::new());
+        }
+        words.last_mut().unwrap().push(c.to_ascii_lowercase());
+    }
+    words
+}
+
+/// `invalid`, `input` -> `InvalidInput`
+fn pascal_case(words: &[String]) -> String {
+    words
+        .iter()
+        .flat_map(|w| {
+            let mut chars = w.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+        })
+        .collect()
+}
+
+fn is_len(ty: &Type, name: &str) -> bool {
+    matches!(
+        ty,
+        Type::Primitive(PrimitiveType::Integer {
+            signed: false,
+            kind: IntKind::SizeT | IntKind::Size,
+            ..
+        })
+    ) && (name == "len" || name == "count" || name.ends_with("_len") || name.ends_with("_count"))
+}
+
+fn is_len_out(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Ptr {
+            ty,
+            is_const: false,
+            ..
+        } => is_len(ty, name) && name.starts_with("out"),
+        _ => false,
+    }
+}
+
+fn is_result(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.export_name() == "StarkResult")
+}
+
+fn const_expr(expr: &ConstExpr) -> String {
+    match expr {
+        ConstExpr::Value(value) => value.clone(),
+        ConstExpr::Path(path) => path.export_name().to_owned(),
+    }
+}
+
+fn primitive(p: &PrimitiveType) -> &'static str {
+    match p {
+        PrimitiveType::Void => "void",
+        PrimitiveType::Bool => "bool",
+        PrimitiveType::Char | PrimitiveType::UChar => "u8",
+        PrimitiveType::SChar => "i8",
+        PrimitiveType::Char32 => "u32",
+        PrimitiveType::Float => "f32",
+        PrimitiveType::Double => "f64",
+        PrimitiveType::VaList => panic!("varargs are not supported in the Zig bindings"),
+        PrimitiveType::PtrDiffT => "isize",
+        PrimitiveType::Integer { signed, kind, .. } => match (kind, signed) {
+            (IntKind::Short, true) => "c_short",
+            (IntKind::Short, false) => "c_ushort",
+            (IntKind::Int, true) => "c_int",
+            (IntKind::Int, false) => "c_uint",
+            (IntKind::Long, true) => "c_long",
+            (IntKind::Long, false) => "c_ulong",
+            (IntKind::LongLong, true) => "c_longlong",
+            (IntKind::LongLong, false) => "c_ulonglong",
+            (IntKind::SizeT | IntKind::Size, true) => "isize",
+            (IntKind::SizeT | IntKind::Size, false) => "usize",
+            (IntKind::B8, true) => "i8",
+            (IntKind::B8, false) => "u8",
+            (IntKind::B16, true) => "i16",
+            (IntKind::B16, false) => "u16",
+            (IntKind::B32, true) => "i32",
+            (IntKind::B32, false) => "u32",
+            (IntKind::B64, true) => "i64",
+            (IntKind::B64, false) => "u64",
+        },
+    }
+}
+
+/// Whether this build compiles an item with the given `cfg`
+fn enabled(cfg: &Option<Cfg>) -> bool {
+    fn eval(cfg: &Cfg) -> bool {
+        match cfg {
+            Cfg::Boolean(key) => env::var_os(format!("CARGO_CFG_{}", key.to_uppercase())).is_some(),
+            Cfg::Named(key, value) if key == "feature" => env::var_os(format!(
+                "CARGO_FEATURE_{}",
+                value.to_uppercase().replace('-', "_")
+            ))
+            .is_some(),
+            Cfg::Named(key, value) => env::var(format!("CARGO_CFG_{}", key.to_uppercase()))
+                .is_ok_and(|values| values.split(',').any(|v| v == value)),
+            Cfg::Any(cfgs) => cfgs.iter().any(eval),
+            Cfg::All(cfgs) => cfgs.iter().all(eval),
+            Cfg::Not(cfg) => !eval(cfg),
+        }
+    }
+    cfg.as_ref().is_none_or(eval)
+}
+
+fn literal(lit: &Literal) -> Option<String> {
+    Some(match lit {
+        Literal::Expr(expr) => {
+            let number = expr.strip_prefix("0x").unwrap_or(expr);
+            if !number.chars().all(|c| c.is_ascii_hexdigit()) || number.is_empty() {
+                return None;
+            }
+            expr.clone()
+        }
+        Literal::Path { name, .. } => name.clone(),
+        Literal::BinOp { left, op, right } => {
+            format!("({} {} {})", literal(left)?, op, literal(right)?)
+        }
+        _ => return None,
+    })
+}
+
+impl Generator {
+    fn doc(&mut self, doc: &Documentation, indent: &str) {
+        for line in &doc.doc_comment {
+            let line = line.strip_prefix(' ').unwrap_or(line);
+            if line.is_empty() {
+                writeln!(self.out, "{indent}///").unwrap();
+            } else {
+                writeln!(self.out, "{indent}/// {line}").unwrap();
+            }
+        }
+    }
+
+    /// Parameter or local name that shadows nothing
+    fn ident(&self, name: &str) -> String {
+        if KEYWORDS.contains(&name) || self.reserved.contains(name) {
+            format!("{name}_arg")
+        } else {
+            name.to_owned()
+        }
+    }
+
+    fn ty(&self, ty: &Type, ctx: Ctx) -> String {
+        match ty {
+            Type::Primitive(p) => primitive(p).to_owned(),
+            Type::Path(path) => path.export_name().to_owned(),
+            Type::Array(elem, len) => format!("[{}]{}", const_expr(len), self.ty(elem, ctx)),
+            Type::FuncPtr { ret, args, .. } => {
+                let args: Vec<_> = args.iter().map(|(_, ty)| self.ty(ty, Ctx::Raw)).collect();
+                format!(
+                    "?*const fn ({}) callconv(.c) {}",
+                    args.join(", "),
+                    self.ty(ret, Ctx::RawReturn)
+                )
+            }
+            Type::Ptr {
+                ty: pointee,
+                is_const,
+                ..
+            } => {
+                let constness = if *is_const { "const " } else { "" };
+                let opaque =
+                    matches!(&**pointee, Type::Path(p) if self.opaque.contains(p.export_name()));
+                match (&**pointee, ctx) {
+                    (Type::Primitive(PrimitiveType::Char), Ctx::Raw) => "?[*:0]const u8".to_owned(),
+                    (Type::Primitive(PrimitiveType::Char), _) => "[*:0]const u8".to_owned(),
+                    // Untyped pointers are caller contexts, which may be null
+                    (Type::Primitive(PrimitiveType::Void), _) => format!("?*{constness}anyopaque"),
+                    (_, Ctx::Wrapper) => format!("*{constness}{}", self.ty(pointee, ctx)),
+                    _ if opaque => format!("?*{constness}{}", self.ty(pointee, ctx)),
+                    _ => format!("[*c]{constness}{}", self.ty(pointee, Ctx::Raw)),
+                }
+            }
+        }
+    }
+
+    fn constants(&mut self, bindings: &Bindings) {
+        for constant in &bindings.constants {
+            let Some(value) = literal(&constant.value) else {
+                continue;
+            };
+            if !enabled(&constant.cfg) {
+                continue;
+            }
+            self.out.push('\n');
+            self.doc(&constant.documentation, "");
+            let ty = self.ty(&constant.ty, Ctx::Raw);
+            writeln!(
+                self.out,
+                "pub const {}: {ty} = {value};",
+                constant.export_name
+            )
+            .unwrap();
+        }
+    }
+
+    fn items(&mut self, bindings: &Bindings) {
+        for item in &bindings.items {
+            match item {
+                ItemContainer::OpaqueItem(o) if enabled(&o.cfg) => {
+                    self.out.push('\n');
+                    self.doc(&o.documentation, "");
+                    writeln!(self.out, "pub const {} = opaque {{}};", o.export_name).unwrap();
+                }
+                ItemContainer::Typedef(t) if enabled(&t.cfg) => {
+                    self.out.push('\n');
+                    self.doc(&t.documentation, "");
+                    let ty = self.ty(&t.aliased, Ctx::Raw);
+                    writeln!(self.out, "pub const {} = {ty};", t.export_name).unwrap();
+                }
+                ItemContainer::Struct(s) if enabled(&s.cfg) => {
+                    self.out.push('\n');
+                    self.doc(&s.documentation, "");
+                    writeln!(self.out, "pub const {} = extern struct {{", s.export_name).unwrap();
+                    for field in &s.fields {
+                        self.doc(&field.documentation, "    ");
+                        let ty = self.ty(&field.ty, Ctx::Raw);
+                        writeln!(self.out, "    {}: {ty},", self.ident(&field.name)).unwrap();
+                    }
+                    self.out.push_str("};\n");
+                }
+                ItemContainer::Enum(e) if enabled(&e.cfg) => {
+                    self.out.push('\n');
+                    self.doc(&e.documentation, "");
+                    writeln!(self.out, "pub const {} = enum(c_int) {{", e.export_name).unwrap();
+                    for variant in &e.variants {
+                        let mut name = variant_words(&e.export_name, &variant.name).join("_");
+                        if KEYWORDS.contains(&name.as_str()) {
+                            // A field named after a keyword is written `@"error"`
+                            name = format!("@\"{name}\"");
+                        }
+                        match variant.discriminant.as_ref().and_then(literal) {
+                            Some(value) => writeln!(self.out, "    {name} = {value},").unwrap(),
+                            None => writeln!(self.out, "    {name},").unwrap(),
+                        }
+                    }
+                    self.out.push_str("    _,\n};\n");
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// `Error` and `check`, from the `StarkResult` variants
+    fn errors(&mut self, bindings: &Bindings) {
+        let Some(result) = bindings.items.iter().find_map(|item| match item {
+            ItemContainer::Enum(e) if e.export_name == "StarkResult" => Some(e),
+            _ => None,
+        }) else {
+            panic!("StarkResult is missing from the bindings");
+        };
+        let failures: Vec<_> = result
+            .variants
+            .iter()
+            .map(|v| variant_words(&result.export_name, &v.name))
+            .filter(|words| words != &["success"])
+            .collect();
+
+        self.out
+            .push_str("\n/// Failure result codes (`Unknown` for a code this module predates)\n");
+        self.out.push_str("pub const Error = error{\n");
+        for words in &failures {
+            writeln!(self.out, "    {},", pascal_case(words)).unwrap();
+        }
+        self.out.push_str("    Unknown,\n};\n");
+
+        self.out.push_str(
+            "\n/// Map a result code to `Error`\n\
+             pub fn check(result: StarkResult) Error!void {\n\
+             \x20   return switch (result) {\n\
+             \x20       .success => {},\n",
+        );
+        for words in &failures {
+            let (tag, error) = (words.join("_"), pascal_case(words));
+            writeln!(self.out, "        .{tag} => error.{error},").unwrap();
+        }
+        self.out
+            .push_str("        _ => error.Unknown,\n    };\n}\n");
+    }
+
+    fn raw_functions(&mut self, bindings: &Bindings) {
+        self.out
+            .push_str("\n/// The raw C ABI\npub const c = struct {\n");
+        for function in &bindings.functions {
+            if !enabled(&function.cfg) {
+                continue;
+            }
+            let args: Vec<_> = function
+                .args
+                .iter()
+                .enumerate()
+                .map(|(i, arg)| {
+                    let name = arg.name.clone().unwrap_or_else(|| format!("arg{i}"));
+                    format!("{}: {}", self.ident(&name), self.ty(&arg.ty, Ctx::Raw))
+                })
+                .collect();
+            writeln!(
+                self.out,
+                "    pub extern fn {}({}) {};",
+                function.path.name(),
+                args.join(", "),
+                self.ty(&function.ret, Ctx::RawReturn)
+            )
+            .unwrap();
+        }
+        self.out.push_str("};\n");
+    }
+
+    fn wrapper(&mut self, function: &Function) {
+        let symbol = function.path.name();
+        let name = unprefixed(function);
+        let mut params = Vec::new();
+        let mut call_args = Vec::new();
+        let mut outputs = Vec::new();
+        let mut locals = Vec::new();
+
+        let args = &function.args;
+        let mut i = 0;
+        while i < args.len() {
+            let arg_name = args[i].name.clone().unwrap_or_else(|| format!("arg{i}"));
+            let ident = self.ident(&arg_name);
+            let next = args
+                .get(i + 1)
+                .and_then(|a| Some((&a.ty, a.name.as_deref()?)));
+            let returns_result = is_result(&function.ret);
+
+            if let Type::Ptr {
+                ty: pointee,
+                is_const,
+                ..
+            } = &args[i].ty
+            {
+                let elem = self.ty(pointee, Ctx::Raw);
+                let constness = if *is_const { "const " } else { "" };
+
+                // (ptr, len): a slice
+                if matches!(next, Some((ty, n)) if is_len(ty, n)) {
+                    params.push(format!("{ident}: []{constness}{elem}"));
+                    call_args.push(format!("{ident}.ptr"));
+                    call_args.push(format!("{ident}.len"));
+                    i += 2;
+                    continue;
+                }
+                if returns_result && !is_const && arg_name.starts_with("out") {
+                    // (out, out_len) with `out` a pointer to a pointer: a library
+                    // buffer, returned as a slice the caller frees with `bufferFree`
+                    if let Type::Ptr { ty: inner, .. } = &**pointee {
+                        if let Some((_, len_name)) = next.filter(|(ty, n)| is_len_out(ty, n)) {
+                            let len_var = self.ident(len_name);
+                            locals.push(format!("var {ident}: {elem} = null;"));
+                            locals.push(format!("var {len_var}: usize = 0;"));
+                            call_args.push(format!("&{ident}"));
+                            call_args.push(format!("&{len_var}"));
+                            outputs.push(Output::Buffer {
+                                param: ident,
+                                len_var,
+                                field: field_name(&arg_name),
+                                elem: self.ty(inner, Ctx::Raw),
+                            });
+                            i += 2;
+                            continue;
+                        }
+                    }
+                    // (out, out_len): a caller buffer, the written prefix is returned
+                    if let Some((_, len_name)) = next.filter(|(ty, n)| is_len_out(ty, n)) {
+                        let len_var = self.ident(len_name);
+                        params.push(format!("{ident}: []{elem}"));
+                        locals.push(format!("var {len_var}: usize = {ident}.len;"));
+                        call_args.push(format!("{ident}.ptr"));
+                        call_args.push(format!("&{len_var}"));
+                        outputs.push(Output::Buffer {
+                            param: ident,
+                            len_var,
+                            field: field_name(&arg_name),
+                            elem,
+                        });
+                        i += 2;
+                        continue;
+                    }
+                    // A single output value
+                    let handle = matches!(&**pointee, Type::Ptr { .. });
+                    let ty = if handle {
+                        self.ty(pointee, Ctx::Wrapper)
+                    } else {
+                        elem.clone()
+                    };
+                    if handle {
+                        locals.push(format!("var {ident}: {elem} = null;"));
+                    } else {
+                        locals.push(format!("var {ident}: {elem} = undefined;"));
+                    }
+                    call_args.push(format!("&{ident}"));
+                    outputs.push(Output::Value {
+                        var: ident,
+                        field: field_name(&arg_name),
+                        ty,
+                        handle,
+                    });
+                    i += 1;
+                    continue;
+                }
+            }
+
+            let mut ty = self.ty(&args[i].ty, Ctx::Wrapper);
+            if OPTIONAL_ARGS.contains(&(name, arg_name.as_str())) {
+                ty = format!("?{ty}");
+            }
+            params.push(format!("{ident}: {ty}"));
+            call_args.push(ident);
+            i += 1;
+        }
+
+        let call = format!("c.{symbol}({})", call_args.join(", "));
+        let ret_ty;
+        let mut body = Vec::new();
+        body.extend(locals);
+        if is_result(&function.ret) {
+            let (value_ty, value) = match outputs.as_slice() {
+                [] => ("void".to_owned(), None),
+                [output] => (output_type(output), Some(output_value(output))),
+                outputs => {
+                    let fields: Vec<_> = outputs
+                        .iter()
+                        .map(|o| format!("{}: {}", output_field(o), output_type(o)))
+                        .collect();
+                    let values: Vec<_> = outputs
+                        .iter()
+                        .map(|o| format!(".{} = {}", output_field(o), output_value(o)))
+                        .collect();
+                    (
+                        format!("struct {{ {} }}", fields.join(", ")),
+                        Some(format!(".{{ {} }}", values.join(", "))),
+                    )
+                }
+            };
+            ret_ty = format!("Error!{value_ty}");
+            match value {
+                Some(value) => {
+                    body.push(format!("try check({call});"));
+                    body.push(format!("return {value};"));
+                }
+                None => body.push(format!("return check({call});")),
+            }
+        } else if matches!(&function.ret, Type::Ptr { ty, .. } if matches!(**ty, Type::Primitive(PrimitiveType::Char)))
+        {
+            ret_ty = "[:0]const u8".to_owned();
+            body.push(format!("return std.mem.span({call});"));
+        } else {
+            ret_ty = self.ty(&function.ret, Ctx::RawReturn);
+            body.push(format!("return {call};"));
+        }
+
+        self.out.push('\n');
+        self.doc(&function.documentation, "");
+        writeln!(
+            self.out,
+            "pub fn {}({}) {ret_ty} {{",
+            camel_case(name),
+            params.join(", ")
+        )
+        .unwrap();
+        for line in body {
+            writeln!(self.out, "    {line}").unwrap();
+        }
+        self.out.push_str("}\n");
+    }
+}
+
+/// `out_r` -> `r`, `out` -> `out`
+fn field_name(arg: &str) -> String {
+    arg.strip_prefix("out_").unwrap_or(arg).to_owned()
+}
+
+fn output_field(output: &Output) -> &str {
+    match output {
+        Output::Value { field, .. } | Output::Buffer { field, .. } => field,
+    }
+}
+
+fn output_type(output: &Output) -> String {
+    match output {
+        Output::Value { ty, .. } => ty.clone(),
+        Output::Buffer { elem, .. } => format!("[]{elem}"),
+    }
+}
+
+fn output_value(output: &Output) -> String {
+    match output {
+        Output::Value {
+            var, handle: true, ..
+        } => format!("{var}.?"),
+        Output::Value { var, .. } => var.clone(),
+        Output::Buffer { param, len_var, .. } => format!("{param}[0..{len_var}]"),
+    }
+}
@@ -3,9 +3,26 @@
 
 #include <stdint.h>
 #include <stddef.h>
+#include <stdbool.h>
 
 /**
  * Result codes for FFI functions
+ * Pointer arguments must be non-NULL unless documented otherwise (buffers
+ * with a zero length, optional arguments, the _free functions); a NULL
+ * pointer is reported as STARK_NULL_POINTER.
+ * Variable-length outputs take a buffer and its capacity (out_len, or
+ * out_count for felt arrays) and write the output length back. If the
+ * output does not fit, the required length is still written and
+ * STARK_BUFFER_TOO_SMALL is returned, so a call with a NULL buffer and a
+ * zero capacity queries the size to allocate.
+ * Opaque handles (ABI, calldata builder/reader, secret buffer, verify
+ * context, cancel token) are created by a *_new/*_load function and released by the
+ * matching *_free function (NULL is a no-op). Debug builds report a freed,
+ * double-freed or wrong-type handle as STARK_INVALID_HANDLE; release builds
+ * do not check.
+ * Builds with the "symbol-prefix" cargo feature export every function below
+ * as kundera_<name> (kundera_keccak256, ...); use the generated header
+ * (target/<profile>/include/starknet_crypto_ffi.h) with them.
  */
 typedef enum {
     STARK_SUCCESS = 0,
@@ -15,6 +32,16 @@ typedef enum {
     STARK_DIVISION_BY_ZERO = 4,
     STARK_NO_INVERSE = 5,
     STARK_NO_SQUARE_ROOT = 6,
+    STARK_INVALID_PRIVATE_KEY = 7,
+    STARK_RNG_FAILED = 8,
+    STARK_DECRYPTION_FAILED = 9,
+    STARK_MEMORY_LOCK_FAILED = 10,
+    STARK_INTERNAL_PANIC = 11,
+    STARK_NULL_POINTER = 12,
+    STARK_INVALID_HANDLE = 13,
+    STARK_BUFFER_TOO_SMALL = 14,
+    STARK_CANCELLED = 15,
+    STARK_SELF_TEST_FAILED = 16,
 } StarkResult;
 
 /**
@@ -23,6 +50,236 @@ typedef enum {
  */
 typedef uint8_t FeltBytes[32];
 
+/* ============ ERRORS ============ */
+
+/**
+ * Human-readable description of the last failed call on this thread,
+ * naming the argument at fault where known (e.g. "null pointer: `out` is
+ * null"). A successful call leaves the message unchanged.
+ * Returns a NUL-terminated UTF-8 string, empty if no call has failed yet;
+ * never NULL. Valid until the next failing call on the same thread.
+ */
+const char* stark_last_error_message(void);
+
+#define STARK_ERROR_DETAIL_LEN 128
+
+/**
+ * The last failure on this thread, for callers that branch on it
+ * argument_index: zero-based position of the argument at fault in the
+ * failed function's parameter list, -1 if no argument is to blame.
+ * detail: the stark_last_error_message text, truncated to fit and
+ * NUL-terminated; detail_len is the length of the full text.
+ */
+typedef struct {
+    StarkResult code;
+    int32_t argument_index;
+    size_t detail_len;
+    char detail[STARK_ERROR_DETAIL_LEN];
+} StarkErrorInfo;
+
+/**
+ * Fill *out_info with the last failed call on this thread (code
+ * STARK_SUCCESS, index -1 and an empty detail if none failed yet).
+ */
+StarkResult stark_last_error_info(StarkErrorInfo* out_info);
+
+/**
+ * Static description of a result code (e.g. "invalid input"), or
+ * "unknown error" for a value this build does not define.
+ * The string lives for the whole program; do not free it.
+ */
+const char* stark_result_to_string(int result);
+
+/* ============ BUFFERS ============ */
+
+/**
+ * Functions with an _alloc suffix return a buffer allocated by the library
+ * (a pointer and its length) instead of writing to a caller buffer. The
+ * pointer is non-NULL on success, even when the length is 0.
+ */
+
+/**
+ * Free a buffer returned by an _alloc function, passing the length written
+ * with it (NULL is a no-op). Debug builds ignore and report a buffer that
+ * is not live or a wrong length.
+ */
+void stark_buffer_free(uint8_t* ptr, size_t len);
+
+/* ============ STREAMING ============ */
+
+/**
+ * Functions with a _stream suffix deliver their output to a callback in
+ * chunks of at most STREAM_CHUNK_MAX_LEN bytes instead of writing a caller
+ * buffer. Felt outputs arrive as consecutive 32-byte big-endian felts, never
+ * split across chunks. chunk is only valid during the callback; ctx is
+ * passed through unchanged. The callback must not free the handle being
+ * streamed from.
+ */
+#define STREAM_CHUNK_MAX_LEN 2048
+
+typedef void (*StarkChunkCallback)(void* ctx, const uint8_t* chunk, size_t len);
+
+/* ============ LOGGING ============ */
+
+/**
+ * Severity of a log message (lower is more severe)
+ */
+typedef enum {
+    STARK_LOG_ERROR = 1,  /* internal failures (a caught panic) */
+    STARK_LOG_WARN = 2,   /* degraded operation (platform fallback, test RNG) */
+    STARK_LOG_INFO = 3,   /* configuration changes */
+    STARK_LOG_DEBUG = 4,  /* failed calls and their reason */
+    STARK_LOG_TRACE = 5,  /* progress of long operations */
+} StarkLogLevel;
+
+/**
+ * Receives one log message: len bytes of UTF-8 at msg (not NUL-terminated),
+ * only valid during the call. ctx is the pointer given to
+ * stark_set_log_callback.
+ */
+typedef void (*StarkLogCallback)(void* ctx, StarkLogLevel level, const uint8_t* msg, size_t len);
+
+/**
+ * Route library diagnostics (the library never writes to stdout/stderr) to
+ * callback. Replaces any previous callback; NULL turns logging off. The
+ * callback runs on the calling thread, possibly from several threads at
+ * once; ctx must stay valid until the callback is replaced. Messages logged
+ * while the callback itself calls into the library are dropped.
+ */
+StarkResult stark_set_log_callback(StarkLogCallback callback, void* ctx);
+
+/* ============ THREAD POOL ============ */
+
+/**
+ * Set the number of threads batch operations (starknet_verify_batch,
+ * starknet_verify_batch_soa and stark_grind_nonce) run on. The pool belongs to the library,
+ * never rayon's global pool. 0 restores the default: the STARK_THREADS
+ * environment variable, else one thread per core; 1 runs batches on the
+ * calling thread. Batches already running finish on the old pool. Ignored
+ * by no_std builds.
+ */
+StarkResult stark_set_thread_count(size_t count);
+
+/* Number of threads the next batch operation will use */
+size_t stark_get_thread_count(void);
+
+/* ============ CANCELLATION ============ */
+
+/**
+ * Cancellation flag for long-running operations (starknet_verify_batch,
+ * stark_grind_nonce).
+ * Any thread may cancel it while an operation uses it; the operation stops
+ * at its next poll and returns STARK_CANCELLED without writing its outputs.
+ * A token stays cancelled until reset. Operations accept NULL (never
+ * cancelled).
+ */
+typedef struct StarkCancelToken StarkCancelToken;
+
+/* Create a token (not cancelled); release it with stark_cancel_token_free */
+StarkResult stark_cancel_token_new(StarkCancelToken** out_token);
+
+/* Cancel the operations using token (callable from any thread) */
+StarkResult stark_cancel_token_cancel(const StarkCancelToken* token);
+
+/* Clear the cancellation so the token can be reused */
+StarkResult stark_cancel_token_reset(const StarkCancelToken* token);
+
+/* Whether token is cancelled (false for NULL) */
+bool stark_cancel_token_is_cancelled(const StarkCancelToken* token);
+
+/* Free a token (NULL is a no-op) */
+void stark_cancel_token_free(StarkCancelToken* token);
+
+/* ============ ARENAS ============ */
+
+/**
+ * Scratch buffer reused across calls, so batch hashing
+ * (starknet_poseidon_hash_many_arena) and starknet_merkle_root stop
+ * allocating once it has grown to the workload. Calls sharing an arena take
+ * turns on it: use one arena per thread. Functions accept NULL (allocate
+ * as usual).
+ */
+typedef struct StarkArena StarkArena;
+
+/* Create an arena with room for capacity felts (0: grow on first use);
+ * release it with stark_arena_free */
+StarkResult stark_arena_new(size_t capacity, StarkArena** out_arena);
+
+/* Number of felts the arena has room for (0 for NULL) */
+size_t stark_arena_capacity(const StarkArena* arena);
+
+/* Release the memory the arena holds (it grows again on next use) */
+StarkResult stark_arena_shrink(const StarkArena* arena);
+
+/* Free an arena (NULL is a no-op) */
+void stark_arena_free(StarkArena* arena);
+
+/* ============ ALLOCATOR (no_std builds) ============ */
+
+/* Alignment that every block returned by a StarkMallocFn must have */
+#define ALLOCATOR_ALIGN 8
+
+/**
+ * Allocate size bytes (never 0), aligned to ALLOCATOR_ALIGN; NULL when out
+ * of memory
+ */
+typedef void* (*StarkMallocFn)(size_t size);
+
+/* Release a block returned by the StarkMallocFn */
+typedef void (*StarkFreeFn)(void* ptr);
+
+/**
+ * Give the library its heap. Only exported by builds without the default
+ * `std` cargo feature (bare-metal firmware), which have no allocator of
+ * their own: call it once, before any other function. A second call
+ * returns STARK_INVALID_INPUT and keeps the first allocator. Such builds
+ * must not be called from two threads (or an interrupt handler) at once,
+ * and a panic reports STARK_LOG_ERROR to the log callback and halts instead
+ * of returning STARK_INTERNAL_PANIC.
+ */
+StarkResult stark_set_allocator(StarkMallocFn malloc_fn, StarkFreeFn free_fn);
+
+/* ============ VERSION ============ */
+
+/**
+ * Library version (major.minor.patch). Within a major version the ABI only
+ * grows: minor releases add functions and result codes.
+ */
+StarkResult stark_ffi_version(uint32_t* out_major, uint32_t* out_minor, uint32_t* out_patch);
+
+/**
+ * Whether a subsystem is compiled in. `name` is NUL-terminated; known
+ * names: "abi", "address", "aead", "bip340", "bip39", "bls", "byte_array",
+ * "calldata", "derivation", "ecdsa", "ecies", "ed25519", "encoding", "eth",
+ * "hashing", "keystore", "kzg", "merkle", "outside_execution", "schnorr",
+ * "sealed_box", "secret", "secp256k1", "secp256r1", "session",
+ * "transaction", "typed_data", "webauthn", "x25519" and (only in builds
+ * with the cargo feature of that name) "pedersen-tables", "profiling",
+ * "test-rng" and "test-vectors".
+ * Returns false for unknown names and for NULL.
+ *
+ * Slimmed-down builds leave out the cargo features "hashing" (Pedersen,
+ * Poseidon, keccak), "ecdsa" (STARK signing and verification, V3
+ * transactions), "secp" (secp256k1/r1, BIP-340, Ethereum, WebAuthn, key
+ * derivation), "merkle" (sessions, merkletree typed data), "keystore",
+ * "encoding", "bls", "kzg", "ed25519", "x25519", "aead", "sealed_box",
+ * "ecies", "mnemonic" (BIP-39), "abi" and "typed_data" (SNIP-12, outside
+ * execution); their functions are then not exported. The generated header
+ * guards them with STARK_FEATURE_* and defines the ones the build has.
+ */
+bool stark_ffi_has_feature(const char* name);
+
+/* ============ SELF-TEST ============ */
+
+/**
+ * Run built-in known-answer tests (Pedersen, Poseidon, keccak, STARK
+ * sign/verify, secp256k1) through the exported functions. Call once at
+ * startup: STARK_SELF_TEST_FAILED (the last-error message names the
+ * failing function) means the binary is miscompiled or corrupted and must
+ * not be used.
+ */
+StarkResult stark_self_test(void);
+
 /* ============ FELT ARITHMETIC ============ */
 
 /**
@@ -59,15 +316,97 @@ StarkResult felt_inverse(const FeltBytes* a, FeltBytes* out);
 
 /**
  * Power: base^exp mod P
+ * exp is read as a felt (reduced mod P), then used as an integer in
+ * [0, P); 0^0 = 1. An exp of P or more is therefore not the integer power
+ * (see felt_pow_reduced).
  */
 StarkResult felt_pow(const FeltBytes* base, const FeltBytes* exp, FeltBytes* out);
 
+/**
+ * Power with the exponent reduced mod P - 1: base^(exp mod (P - 1)) mod P
+ * exp is the full 256-bit big-endian integer. Equals the integer power
+ * base^exp for any non-zero base (Fermat); for base 0 the result is 1 when
+ * exp is a multiple of P - 1 (0 included), 0 otherwise.
+ */
+StarkResult felt_pow_reduced(const FeltBytes* base, const FeltBytes* exp, FeltBytes* out);
+
 /**
  * Square root (Tonelli-Shanks): returns sqrt if exists
  * Returns STARK_NO_SQUARE_ROOT if a is not a quadratic residue
  */
 StarkResult felt_sqrt(const FeltBytes* a, FeltBytes* out);
 
+/**
+ * In-place variants: a_inout = a_inout op b, through a single pointer.
+ * b may point to a_inout. felt_div_assign returns STARK_DIVISION_BY_ZERO
+ * with a_inout untouched if b == 0.
+ */
+StarkResult felt_add_assign(FeltBytes* a_inout, const FeltBytes* b);
+StarkResult felt_sub_assign(FeltBytes* a_inout, const FeltBytes* b);
+StarkResult felt_mul_assign(FeltBytes* a_inout, const FeltBytes* b);
+StarkResult felt_div_assign(FeltBytes* a_inout, const FeltBytes* b);
+
+/* In-place negation: a_inout = -a_inout mod P */
+StarkResult felt_neg_assign(FeltBytes* a_inout);
+
+/**
+ * Element-wise batch operations: out[i] = a[i] op b[i] for i < count
+ * One call for a whole array (AVX2 inner loop on x86 CPUs that have it).
+ * The arrays may be NULL when count == 0; out may be a or b (in place) but
+ * must not partially overlap them.
+ */
+StarkResult felt_vec_add(const FeltBytes* a, const FeltBytes* b, size_t count, FeltBytes* out);
+StarkResult felt_vec_sub(const FeltBytes* a, const FeltBytes* b, size_t count, FeltBytes* out);
+StarkResult felt_vec_mul(const FeltBytes* a, const FeltBytes* b, size_t count, FeltBytes* out);
+
+/**
+ * Scale a vector by one felt: out[i] = a[i] * scalar for i < count
+ * The arrays may be NULL when count == 0; out may be a.
+ */
+StarkResult felt_vec_scale(const FeltBytes* a, const FeltBytes* scalar, size_t count, FeltBytes* out);
+
+/**
+ * Inner product: out = sum of a[i] * b[i] for i < count, reduced mod P once
+ * The arrays may be NULL when count == 0 (out is then 0).
+ */
+StarkResult felt_inner_product(const FeltBytes* a, const FeltBytes* b, size_t count, FeltBytes* out);
+
+/**
+ * Matrix-vector product: out[r] = sum of matrix[r * cols + c] * vector[c]
+ * for r < rows, matrix row-major, each row reduced mod P once
+ * matrix and vector may be NULL when cols == 0, out when rows == 0; out may
+ * be vector. Returns STARK_INVALID_INPUT if rows * cols overflows.
+ */
+StarkResult felt_mat_vec_mul(
+    const FeltBytes* matrix,
+    size_t rows,
+    size_t cols,
+    const FeltBytes* vector,
+    FeltBytes* out
+);
+
+/* ============ FELT ARRAYS ============ */
+
+/**
+ * Immutable array of felts converted once, so functions taking it
+ * (starknet_poseidon_hash_felt_array) skip parsing the 32-byte buffers on
+ * every call. Safe to share between threads.
+ */
+typedef struct StarkFeltArray StarkFeltArray;
+
+/* Convert count felts into a new array (felts may be NULL when count == 0);
+ * release it with stark_felt_array_free */
+StarkResult stark_felt_array_new(const FeltBytes* felts, size_t count, StarkFeltArray** out_array);
+
+/* Number of felts in the array (0 for NULL) */
+size_t stark_felt_array_len(const StarkFeltArray* array);
+
+/* Read the felt at index (STARK_INVALID_INPUT if out of bounds) */
+StarkResult stark_felt_array_get(const StarkFeltArray* array, size_t index, FeltBytes* out);
+
+/* Free an array (NULL is a no-op) */
+void stark_felt_array_free(StarkFeltArray* array);
+
 /* ============ HASHING ============ */
 
 /**
@@ -99,6 +438,41 @@ StarkResult starknet_poseidon_hash_many(
     FeltBytes* out
 );
 
+/**
+ * starknet_poseidon_hash_many using arena's buffer (arena may be NULL)
+ */
+StarkResult starknet_poseidon_hash_many_arena(
+    const StarkArena* arena,
+    const FeltBytes* inputs,
+    size_t count,
+    FeltBytes* out
+);
+
+/**
+ * Poseidon hash of N felts in the hash domain domain_tag: the sponge
+ * capacity starts at domain_tag instead of 0 (Poseidon paper domain
+ * separation). Tag 0 gives starknet_poseidon_hash_many; inputs may be NULL
+ * when count is 0
+ */
+StarkResult poseidon_hash_with_domain(
+    const FeltBytes* domain_tag,
+    const FeltBytes* inputs,
+    size_t count,
+    FeltBytes* out
+);
+
+/**
+ * Poseidon hash of array[start .. start + count], without converting the
+ * felts again
+ * Returns STARK_INVALID_INPUT if count is 0 or the range is out of bounds
+ */
+StarkResult starknet_poseidon_hash_felt_array(
+    const StarkFeltArray* array,
+    size_t start,
+    size_t count,
+    FeltBytes* out
+);
+
 /**
  * Keccak256 hash of arbitrary data, truncated to 250 bits (Starknet selector format)
  * The output is keccak256(data) with the top 6 bits masked to zero.
@@ -109,6 +483,111 @@ StarkResult starknet_keccak256(
     FeltBytes* out
 );
 
+/**
+ * Rescue-Prime hash of count felts (state of 3, rate 2, x^3 S-box, 18
+ * rounds, constants from the specification's SHAKE256 derivation), for
+ * comparing against Poseidon; no Cairo builtin computes it
+ * inputs may be NULL when count is 0
+ */
+StarkResult rescue_prime_hash(const FeltBytes* inputs, size_t count, FeltBytes* out);
+
+/**
+ * MiMC sponge hash of count felts: circomlib's MiMCSponge (220 rounds,
+ * x^5, key 0, one output) with its keccak-derived constants reduced mod the
+ * Stark prime
+ * Returns STARK_INVALID_INPUT if count is 0
+ */
+StarkResult mimc_hash(const FeltBytes* inputs, size_t count, FeltBytes* out);
+
+/* ============ VECTOR COMMITMENTS ============ */
+
+/*
+ * Poseidon vector commitments with positional openings: the values are
+ * the leaves of a binary tree zero-padded to the next power of two, a node
+ * is poseidon(left, right) and the commitment is poseidon(tree_root, count).
+ * An opening is the tree depth's sibling hashes from the leaf up.
+ */
+
+/**
+ * Commit to count values
+ * Returns STARK_INVALID_INPUT if count is 0
+ */
+StarkResult vector_commit(const FeltBytes* values, size_t count, FeltBytes* out_root);
+
+/**
+ * Write the opening of values[index]
+ * *out_count: capacity of out_path in felts, set to the tree depth
+ * Returns STARK_INVALID_INPUT if index >= count
+ */
+StarkResult vector_open(
+    const FeltBytes* values,
+    size_t count,
+    size_t index,
+    FeltBytes* out_path,
+    size_t* out_count
+);
+
+/**
+ * Verify that root commits to count values with value at index
+ * Returns STARK_SUCCESS if the opening holds, STARK_INVALID_SIGNATURE if not,
+ * STARK_INVALID_INPUT if index >= count or path_len is not the tree depth
+ */
+StarkResult vector_verify(
+    const FeltBytes* root,
+    size_t count,
+    size_t index,
+    const FeltBytes* value,
+    const FeltBytes* path,
+    size_t path_len
+);
+
+/* ============ FIAT-SHAMIR TRANSCRIPTS ============ */
+
+/**
+ * Fiat-Shamir transcript over Poseidon (opaque): digest starts at 0;
+ * appending felt x sets digest = poseidon_hash(digest, x); challenge i since
+ * the last append is poseidon_hash(digest, i). These two rules follow stwo's
+ * Poseidon252Channel (mix_root, draw_felt252). Appending bytes sets
+ * digest = poseidon_hash_many([digest, len, 31-byte big-endian chunks...]),
+ * this library's own encoding. Stone's channel is not supported.
+ */
+typedef struct Transcript Transcript;
+
+/**
+ * Create an empty transcript
+ * Writes an owned handle to out_transcript; release it with transcript_free.
+ */
+StarkResult transcript_new(Transcript** out_transcript);
+
+/** Append a felt */
+StarkResult transcript_append_felt(Transcript* transcript, const FeltBytes* value);
+
+/**
+ * Append len bytes
+ * data may be NULL when len == 0.
+ */
+StarkResult transcript_append_bytes(Transcript* transcript, const uint8_t* data, size_t len);
+
+/** Draw the next challenge felt */
+StarkResult transcript_challenge_felt(Transcript* transcript, FeltBytes* out);
+
+/** Free a transcript (NULL is a no-op) */
+void transcript_free(Transcript* transcript);
+
+/* ============ PROOF OF WORK ============ */
+
+/**
+ * Find the smallest nonce proving difficulty_bits (1 to 64) of keccak work
+ * on a STARK channel seed (Stone's proof-of-work), on the thread pool
+ * cancel may be NULL; returns STARK_CANCELLED if it is cancelled
+ */
+StarkResult stark_grind_nonce(
+    const uint8_t seed[32],
+    uint32_t difficulty_bits,
+    const StarkCancelToken* cancel,
+    uint64_t* out_nonce
+);
+
 /* ============ ECDSA (STARK Curve) ============ */
 
 /**
@@ -119,6 +598,19 @@ StarkResult starknet_get_public_key(
     FeltBytes* out
 );
 
+/**
+ * Generate a random keypair from OS randomness
+ * The private key is sampled uniformly from [1, n).
+ * Returns STARK_RNG_FAILED if the OS random source is unavailable
+ */
+StarkResult starknet_keypair_generate(FeltBytes* out_private, FeltBytes* out_public);
+
+/**
+ * Check that a private key is usable for signing: 0 < key < curve order
+ * Returns STARK_INVALID_PRIVATE_KEY otherwise
+ */
+StarkResult starknet_private_key_is_valid(const FeltBytes* private_key);
+
 /**
  * Sign a message hash with private key
  * Returns signature (r, s)
@@ -130,6 +622,87 @@ StarkResult starknet_sign(
     FeltBytes* out_s
 );
 
+/**
+ * Sign a message hash with private key
+ * Returns signature (r, s) and the recovery id v accepted by starknet_recover
+ */
+StarkResult starknet_sign_recoverable(
+    const FeltBytes* private_key,
+    const FeltBytes* message_hash,
+    FeltBytes* out_r,
+    FeltBytes* out_s,
+    FeltBytes* out_v
+);
+
+/**
+ * Constant-time variant of starknet_sign (same signature, RFC 6979 nonce):
+ * no branch, memory access or loop count depends on the private key or the
+ * nonce, including nonce generation, k*G (fixed windows, complete addition
+ * formulas, full table scans) and the inversions (Fermat). Slower than
+ * starknet_sign.
+ * Returns STARK_INVALID_PRIVATE_KEY unless 0 < private_key < n, and
+ * STARK_INVALID_INPUT if message_hash >= 2^251
+ */
+StarkResult starknet_sign_ct(
+    const FeltBytes* private_key,
+    const FeltBytes* message_hash,
+    FeltBytes* out_r,
+    FeltBytes* out_s
+);
+
+/**
+ * Sign a message hash with a caller-provided nonce k
+ * k must be secret, uniformly random and never reused; prefer starknet_sign.
+ * Returns STARK_INVALID_INPUT if k is zero or yields an invalid signature
+ */
+StarkResult starknet_sign_with_k(
+    const FeltBytes* private_key,
+    const FeltBytes* message_hash,
+    const FeltBytes* k,
+    FeltBytes* out_r,
+    FeltBytes* out_s
+);
+
+/**
+ * Derive the RFC6979 nonce k used by starknet_sign
+ * seed is optional extra entropy and may be NULL; with a NULL seed the result
+ * is the k starknet_sign uses for the same key and message
+ */
+StarkResult starknet_rfc6979_k(
+    const FeltBytes* message_hash,
+    const FeltBytes* private_key,
+    const FeltBytes* seed,
+    FeltBytes* out
+);
+
+/**
+ * RFC 6979 nonce sequence (opaque): successive nonces of the HMAC-DRBG
+ * behind starknet_rfc6979_k, for signing loops that reject some nonces
+ * (low s, a chosen R) and must stay deterministic. Each next applies the
+ * RFC's retry step and skips candidates outside [1, n). The state is wiped
+ * when the iterator is freed.
+ */
+typedef struct StarkRfc6979KIter StarkRfc6979KIter;
+
+/**
+ * Start the sequence for a key and message; seed may be NULL, as for
+ * starknet_rfc6979_k
+ * Writes an owned handle to out_iter; release it with
+ * starknet_rfc6979_k_iter_free.
+ */
+StarkResult starknet_rfc6979_k_iter_new(
+    const FeltBytes* message_hash,
+    const FeltBytes* private_key,
+    const FeltBytes* seed,
+    StarkRfc6979KIter** out_iter
+);
+
+/** Next nonce; the first is starknet_rfc6979_k's */
+StarkResult starknet_rfc6979_k_iter_next(StarkRfc6979KIter* iter, FeltBytes* out_k);
+
+/** Free a nonce iterator (NULL is a no-op) */
+void starknet_rfc6979_k_iter_free(StarkRfc6979KIter* iter);
+
 /**
  * Verify a signature
  * Returns STARK_SUCCESS if valid, STARK_INVALID_SIGNATURE if not
@@ -153,4 +726,1684 @@ StarkResult starknet_recover(
     FeltBytes* out
 );
 
+/* ============ BLOCK HASH ============ */
+
+#define STARK_L1_DA_MODE_CALLDATA 0
+#define STARK_L1_DA_MODE_BLOB 1
+
+/**
+ * Header fields committed to by the block hash.
+ * Gas prices are big-endian felts; starknet_version is the version string
+ * encoded as a Cairo short string (e.g. "0.13.4").
+ */
+typedef struct {
+    uint64_t block_number;
+    FeltBytes state_root;
+    FeltBytes sequencer_address;
+    uint64_t timestamp;
+    uint64_t transaction_count;
+    uint64_t event_count;
+    uint64_t state_diff_length;
+    uint8_t l1_da_mode;
+    FeltBytes state_diff_commitment;
+    FeltBytes transaction_commitment;
+    FeltBytes event_commitment;
+    FeltBytes receipt_commitment;
+    FeltBytes l1_gas_price_wei;
+    FeltBytes l1_gas_price_fri;
+    FeltBytes l1_data_gas_price_wei;
+    FeltBytes l1_data_gas_price_fri;
+    FeltBytes l2_gas_price_wei;
+    FeltBytes l2_gas_price_fri;
+    FeltBytes starknet_version;
+    FeltBytes parent_block_hash;
+} StarknetBlockHeader;
+
+/**
+ * Compute the hash of a Starknet block header (0.13.2+ formula)
+ * The formula variant (0.13.2 or 0.13.4+) is selected from starknet_version.
+ * Returns STARK_INVALID_INPUT for versions before 0.13.2 or an unknown l1_da_mode
+ */
+StarkResult starknet_block_hash(
+    const StarknetBlockHeader* header,
+    FeltBytes* out
+);
+
+/* ============ CONTRACT ADDRESS ============ */
+
+/**
+ * Compute the address of a contract deployed by `deployer`
+ * pedersen_array(PREFIX, deployer, salt, class_hash, pedersen_array(calldata)) mod (2^251 - 256)
+ * Use a zero deployer for DEPLOY_ACCOUNT (counterfactual account addresses).
+ * constructor_calldata may be NULL when calldata_len == 0.
+ */
+StarkResult starknet_contract_address(
+    const FeltBytes* deployer,
+    const FeltBytes* salt,
+    const FeltBytes* class_hash,
+    const FeltBytes* constructor_calldata,
+    size_t calldata_len,
+    FeltBytes* out
+);
+
+/**
+ * Predict the address of a contract deployed through the Universal Deployer
+ * Contract (0x041a78e7...ad02bf).
+ * unique = true:  salt becomes pedersen(deployer, salt) and the UDC is the deployer
+ * unique = false: zero deployer, address independent of the caller
+ * calldata may be NULL when len == 0.
+ */
+StarkResult starknet_udc_address(
+    const FeltBytes* deployer,
+    const FeltBytes* salt,
+    const FeltBytes* class_hash,
+    const FeltBytes* calldata,
+    size_t len,
+    bool unique,
+    FeltBytes* out
+);
+
+/* Account presets for starknet_account_address_preset */
+#define STARK_ACCOUNT_PRESET_OZ 0        /* OpenZeppelin Account 0.8.1, calldata [public_key] */
+#define STARK_ACCOUNT_PRESET_ARGENT_X 1  /* Argent X 0.4.0, calldata [0, public_key, 1] */
+#define STARK_ACCOUNT_PRESET_BRAAVOS 2   /* Braavos base account, calldata [public_key] */
+
+/**
+ * Compute the counterfactual (DEPLOY_ACCOUNT) address of a wallet account
+ * The preset supplies the class hash and constructor calldata for
+ * public_key. Wallets use the public key as salt.
+ * Returns STARK_INVALID_INPUT for an unknown kind
+ */
+StarkResult starknet_account_address_preset(
+    uint32_t kind,
+    const FeltBytes* public_key,
+    const FeltBytes* salt,
+    FeltBytes* out
+);
+
+/* ============ EVENTS ============ */
+
+/**
+ * Selector of an event variant: sn_keccak(event_name) (not NUL-terminated)
+ * Returns STARK_INVALID_INPUT if event_name is empty
+ */
+StarkResult starknet_event_selector(const uint8_t* event_name, size_t len, FeltBytes* out);
+
+/**
+ * Selector keys of an event as the compiler emits them, before the #[key]
+ * members: one sn_keccak(variant) per segment of path, the variants from
+ * the contract's event enum down to the event separated by "::"
+ * ("ERC20Event::Transfer"), except the segments whose bit is set in
+ * flat_mask (#[flat] variants)
+ * *out_count is the capacity of out_keys in felts, set to the key count.
+ * Returns STARK_INVALID_INPUT for an empty segment, more than 64 segments or
+ * a mask bit past the last segment
+ */
+StarkResult starknet_event_keys(
+    const uint8_t* path,
+    size_t len,
+    uint64_t flat_mask,
+    FeltBytes* out_keys,
+    size_t* out_count
+);
+
+/* ============ CAIRO PROGRAM HASH ============ */
+
+/**
+ * Compute the bootloader (SHARP) hash of a compiled Cairo 0 program:
+ * cairo-lang's compute_program_hash_chain, a Pedersen hash chain over the
+ * bootloader version, main's pc, the builtins and the bytecode
+ * program_json is the cairo-compile output (not NUL-terminated); version is
+ * the bootloader version (0)
+ * Returns STARK_INVALID_INPUT if the JSON is malformed, has no main, or is
+ * compiled for another prime
+ */
+StarkResult cairo_program_hash(
+    const uint8_t* program_json,
+    size_t len,
+    uint64_t version,
+    FeltBytes* out
+);
+
+/* ============ TYPED DATA (SNIP-12) ============ */
+
+/**
+ * Compute the SNIP-12 message hash of a typed data document
+ * json is the UTF-8 document (types, primaryType, domain, message), not
+ * NUL-terminated. Supports structs, enums, arrays, merkletree, selector and
+ * the u256 / TokenAmount / NftId preset types (revision 1 only).
+ * The revision follows the domain type: "StarknetDomain" (revision 1,
+ * Poseidon) or the legacy "StarkNetDomain" (revision 0, Pedersen).
+ * Returns STARK_INVALID_INPUT if the document is malformed
+ */
+StarkResult starknet_typed_data_hash(
+    const uint8_t* json,
+    size_t json_len,
+    const FeltBytes* account_address,
+    FeltBytes* out
+);
+
+#define STARK_MERKLE_HASH_PEDERSEN 0
+#define STARK_MERKLE_HASH_POSEIDON 1
+
+/**
+ * Merkle root of count leaves, as SNIP-12 merkletree values hash them:
+ * pairs are sorted before hashing and an odd node is hashed with zero.
+ * hash is STARK_MERKLE_HASH_PEDERSEN (revision 0) or
+ * STARK_MERKLE_HASH_POSEIDON (revision 1); arena may be NULL.
+ * Returns STARK_INVALID_INPUT if count is 0 or hash is unknown
+ */
+StarkResult starknet_merkle_root(
+    const FeltBytes* leaves,
+    size_t count,
+    uint32_t hash,
+    const StarkArena* arena,
+    FeltBytes* out
+);
+
+/* ============ ETHEREUM ============ */
+
+/**
+ * EIP-191 personal message hash (the digest signed by personal_sign)
+ * out = keccak256("\x19Ethereum Signed Message:\n" || decimal(len) || msg)
+ * msg may be NULL when len is 0.
+ */
+StarkResult eth_personal_message_hash(
+    const uint8_t* msg,
+    size_t len,
+    uint8_t out[32]
+);
+
+/**
+ * Ethereum address of an uncompressed secp256k1 public key (x || y)
+ * out = last 20 bytes of keccak256(x || y)
+ */
+StarkResult eth_address_from_public_key(const uint8_t public_key[64], uint8_t out[20]);
+
+/**
+ * EIP-55 checksummed hex string of a 20-byte address
+ * Writes "0x" + 40 hex digits + NUL terminator (43 bytes)
+ */
+StarkResult eth_address_checksum(const uint8_t address[20], char out[43]);
+
+/* ============ OUTSIDE EXECUTION (SNIP-9) ============ */
+
+/**
+ * A contract call. calldata may be NULL when calldata_len is 0.
+ */
+typedef struct {
+    FeltBytes to;
+    FeltBytes selector;
+    const FeltBytes* calldata;
+    size_t calldata_len;
+} StarknetCall;
+
+/**
+ * Compute the SNIP-9 OutsideExecution message hash signed by the account
+ * revision selects the SNIP-12 revision: 0 for SNIP-9 v1
+ * (execute_from_outside, Pedersen), 1 for SNIP-9 v2
+ * (execute_from_outside_v2, Poseidon).
+ * caller may be the short string 'ANY_CALLER'.
+ * Returns STARK_INVALID_INPUT for an unknown revision
+ */
+StarkResult starknet_outside_execution_hash(
+    const FeltBytes* caller,
+    const FeltBytes* nonce,
+    uint64_t execute_after,
+    uint64_t execute_before,
+    const StarknetCall* calls,
+    size_t calls_len,
+    const FeltBytes* chain_id,
+    const FeltBytes* account_address,
+    uint8_t revision,
+    FeltBytes* out
+);
+
+/* ============ SESSION KEYS ============ */
+
+/* Argent session format only; Braavos session keys are not supported. */
+
+/**
+ * A (contract, selector) pair a session key is allowed to call
+ */
+typedef struct {
+    FeltBytes contract_address;
+    FeltBytes selector;
+} StarknetSessionPolicy;
+
+/**
+ * Merkle root committing to a session's allowed methods (Argent session format)
+ * Returns STARK_INVALID_INPUT if policies is empty
+ */
+StarkResult starknet_session_policies_root(
+    const StarknetSessionPolicy* policies,
+    size_t policies_len,
+    FeltBytes* out
+);
+
+/**
+ * SNIP-12 session message hash signed by the account owner (Argent
+ * SessionAccount.session domain, revision 1)
+ * metadata is the UTF-8 metadata string and may be NULL when metadata_len is 0.
+ * session_key is the session signer GUID (see starknet_session_signer_guid).
+ * Returns STARK_INVALID_INPUT if policies is empty
+ */
+StarkResult starknet_session_hash(
+    uint64_t expires_at,
+    const StarknetSessionPolicy* policies,
+    size_t policies_len,
+    const uint8_t* metadata,
+    size_t metadata_len,
+    const FeltBytes* session_key,
+    const FeltBytes* chain_id,
+    const FeltBytes* account_address,
+    FeltBytes* out
+);
+
+/**
+ * GUID of a Stark-curve session signer: poseidon('Starknet Signer', public_key)
+ */
+StarkResult starknet_session_signer_guid(
+    const FeltBytes* public_key,
+    FeltBytes* out
+);
+
+/* ============ MULTISIG SIGNATURES ============ */
+
+/*
+ * Transaction signatures of k-of-n multisig accounts, in the layout the
+ * account's __validate__ expects (signer order included)
+ */
+
+/* Argent multisig 0.2+: [count, (0, public_key, r, s)...] by signer GUID */
+#define STARK_MULTISIG_LAYOUT_ARGENT 0
+/* Argent multisig 0.1: [(public_key, r, s)...] by public key */
+#define STARK_MULTISIG_LAYOUT_ARGENT_LEGACY 1
+
+/**
+ * Encode count signatures (r[i], s[i]) by public_keys[i], sorted as the
+ * layout requires
+ * *out_count: capacity of out in felts, set to the payload length
+ * Returns STARK_INVALID_INPUT for an unknown layout or a duplicate signer
+ */
+StarkResult multisig_signature_encode(
+    uint32_t layout,
+    const FeltBytes* public_keys,
+    const FeltBytes* r,
+    const FeltBytes* s,
+    size_t count,
+    FeltBytes* out,
+    size_t* out_count
+);
+
+/**
+ * Split a multisig signature into signers and (r, s) pairs
+ * *out_count: capacity of each output array in signatures, set to the
+ * number of signatures
+ * Returns STARK_INVALID_INPUT if the payload does not follow the layout
+ * (signers out of order included)
+ */
+StarkResult multisig_signature_decode(
+    uint32_t layout,
+    const FeltBytes* signature,
+    size_t len,
+    FeltBytes* out_public_keys,
+    FeltBytes* out_r,
+    FeltBytes* out_s,
+    size_t* out_count
+);
+
+/* ============ SIGNATURE ENCODING ============ */
+
+/**
+ * Convert a signature to canonical low-s form (s <= n / 2)
+ * (r, s) and (r, n - s) both verify; this writes the low-s value.
+ * Returns STARK_INVALID_SIGNATURE if (r, s) is out of range or has no
+ * canonical equivalent
+ */
+StarkResult starknet_signature_normalize(
+    const FeltBytes* r,
+    const FeltBytes* s,
+    FeltBytes* out_s
+);
+
+/**
+ * Check that a signature is canonical: r, s in [1, 2^251), s^-1 mod n in
+ * range and s <= n / 2
+ * Returns STARK_SUCCESS if canonical, STARK_INVALID_SIGNATURE otherwise
+ */
+StarkResult starknet_signature_is_canonical(
+    const FeltBytes* r,
+    const FeltBytes* s
+);
+
+/** Largest DER-encoded signature, in bytes */
+#define SIGNATURE_DER_MAX_LEN 72
+
+/**
+ * Encode a signature (r, s) of 32-byte big-endian scalars as ASN.1 DER
+ * Works for STARK, secp256k1 and secp256r1 signatures alike.
+ * out_len holds the capacity of out on input and the encoded length on
+ * output. If out is too small, the required length is written to out_len and
+ * STARK_BUFFER_TOO_SMALL is returned
+ */
+StarkResult signature_to_der(
+    const uint8_t r[32],
+    const uint8_t s[32],
+    uint8_t* out,
+    size_t* out_len
+);
+
+/**
+ * Decode a strict ASN.1 DER signature into 32-byte big-endian (r, s)
+ * Returns STARK_INVALID_INPUT for non-DER input or values wider than 32 bytes
+ */
+StarkResult signature_from_der(
+    const uint8_t* der,
+    size_t der_len,
+    uint8_t out_r[32],
+    uint8_t out_s[32]
+);
+
+/* ============ VERIFY CONTEXT ============ */
+
+/**
+ * Verification context for a fixed public key (opaque)
+ * Caches the decompressed public key point and a table of its multiples.
+ */
+typedef struct StarkVerifyContext StarkVerifyContext;
+
+/**
+ * Create a verification context for public_key (x coordinate)
+ * Writes an owned handle to out_ctx; release it with starknet_verify_context_free.
+ * Returns STARK_INVALID_INPUT if public_key is not on the curve
+ */
+StarkResult starknet_verify_context_new(
+    const FeltBytes* public_key,
+    StarkVerifyContext** out_ctx
+);
+
+/**
+ * Verify a signature against the context's public key
+ * Same result codes as starknet_verify
+ */
+StarkResult starknet_verify_context_verify(
+    const StarkVerifyContext* ctx,
+    const FeltBytes* message_hash,
+    const FeltBytes* r,
+    const FeltBytes* s
+);
+
+/**
+ * Free a verification context (NULL is a no-op)
+ */
+void starknet_verify_context_free(StarkVerifyContext* ctx);
+
+/**
+ * Verify a signature against a full (x, y) public key
+ * Only the given point is accepted, not its negation (starknet_verify accepts
+ * both since it only knows x).
+ * Returns STARK_INVALID_INPUT if (x, y) is not on the curve, otherwise the
+ * same result codes as starknet_verify
+ */
+StarkResult starknet_verify_full(
+    const FeltBytes* public_key_x,
+    const FeltBytes* public_key_y,
+    const FeltBytes* message_hash,
+    const FeltBytes* r,
+    const FeltBytes* s
+);
+
+/* One signature of a starknet_verify_batch call */
+typedef struct {
+    FeltBytes public_key;
+    FeltBytes message_hash;
+    FeltBytes r;
+    FeltBytes s;
+} StarknetVerifyItem;
+
+/**
+ * Verify count signatures on the library's thread pool (see
+ * stark_set_thread_count), writing each starknet_verify result to
+ * out_results[i]
+ * Returns STARK_SUCCESS if every signature is valid, otherwise
+ * STARK_INVALID_SIGNATURE. cancel may be NULL; once it is cancelled the
+ * remaining items are skipped and STARK_CANCELLED is returned with
+ * out_results left unwritten.
+ */
+StarkResult starknet_verify_batch(
+    const StarknetVerifyItem* items,
+    size_t count,
+    const StarkCancelToken* cancel,
+    StarkResult* out_results
+);
+
+/**
+ * Verify count signatures given as one array per field (signature i is
+ * public_keys[i], message_hashes[i], r[i], s[i]) on the library's thread
+ * pool. Sets bit i % 8 of out_bitmap[i / 8] if signature i is valid and
+ * clears it otherwise; out_bitmap holds (count + 7) / 8 bytes and the
+ * unused bits of its last byte are cleared.
+ * Returns STARK_SUCCESS if every signature is valid, otherwise
+ * STARK_INVALID_SIGNATURE. cancel behaves as for starknet_verify_batch.
+ */
+StarkResult starknet_verify_batch_soa(
+    const FeltBytes* public_keys,
+    const FeltBytes* message_hashes,
+    const FeltBytes* r,
+    const FeltBytes* s,
+    size_t count,
+    const StarkCancelToken* cancel,
+    uint8_t* out_bitmap
+);
+
+/* ============ SECP256K1 (ETHEREUM) ============ */
+
+/*
+ * Messages are 32-byte hashes. Public keys are 64 bytes: uncompressed
+ * x || y without the SEC1 0x04 prefix. Signatures are low-s normalized and
+ * high-s signatures are rejected (EIP-2).
+ */
+
+/**
+ * Derive the uncompressed public key (x || y) from a private key
+ * Returns STARK_INVALID_PRIVATE_KEY unless 0 < key < n
+ */
+StarkResult secp256k1_get_public_key(const uint8_t private_key[32], uint8_t out[64]);
+
+/**
+ * Sign a 32-byte message hash (deterministic RFC6979, low-s)
+ * Returns signature (r, s) and the recovery id v (0 or 1)
+ */
+StarkResult secp256k1_sign_recoverable(
+    const uint8_t private_key[32],
+    const uint8_t message_hash[32],
+    uint8_t out_r[32],
+    uint8_t out_s[32],
+    uint8_t* out_v
+);
+
+/**
+ * Verify a signature over a 32-byte message hash
+ * Returns STARK_INVALID_SIGNATURE for a wrong or high-s signature
+ */
+StarkResult secp256k1_verify(
+    const uint8_t public_key[64],
+    const uint8_t message_hash[32],
+    const uint8_t r[32],
+    const uint8_t s[32]
+);
+
+/**
+ * Recover the public key (x || y) from a signature
+ * v is the recovery id: 0/1, or 27/28 as used by Ethereum
+ * Returns STARK_RECOVERY_FAILED on error
+ */
+StarkResult secp256k1_recover(
+    const uint8_t message_hash[32],
+    const uint8_t r[32],
+    const uint8_t s[32],
+    uint8_t v,
+    uint8_t out[64]
+);
+
+/* ============ SECP256R1 (P-256) ============ */
+
+/*
+ * Same conventions as secp256k1: 32-byte message hashes, 64-byte x || y
+ * public keys. Signatures are low-s normalized; verification accepts both
+ * s forms.
+ */
+
+/**
+ * Derive the uncompressed public key (x || y) from a private key
+ * Returns STARK_INVALID_PRIVATE_KEY unless 0 < key < n
+ */
+StarkResult secp256r1_get_public_key(const uint8_t private_key[32], uint8_t out[64]);
+
+/**
+ * Sign a 32-byte message hash (deterministic RFC6979, low-s)
+ * Returns signature (r, s)
+ */
+StarkResult secp256r1_sign(
+    const uint8_t private_key[32],
+    const uint8_t message_hash[32],
+    uint8_t out_r[32],
+    uint8_t out_s[32]
+);
+
+/**
+ * Verify a signature over a 32-byte message hash
+ */
+StarkResult secp256r1_verify(
+    const uint8_t public_key[64],
+    const uint8_t message_hash[32],
+    const uint8_t r[32],
+    const uint8_t s[32]
+);
+
+/* ============ WEBAUTHN ============ */
+
+/*
+ * Passkey assertions: the authenticator signs
+ * sha256(authenticator_data || sha256(client_data_json)) with P-256.
+ * Both functions check that client_data_json is a "webauthn.get" assertion
+ * whose challenge is base64url(challenge) and that the user-present flag is
+ * set, returning STARK_INVALID_INPUT otherwise.
+ */
+
+/**
+ * Compute the P-256 message hash signed by a WebAuthn assertion
+ */
+StarkResult webauthn_assertion_hash(
+    const uint8_t* authenticator_data,
+    size_t authenticator_data_len,
+    const uint8_t* client_data_json,
+    size_t client_data_json_len,
+    const uint8_t* challenge,
+    size_t challenge_len,
+    uint8_t out[32]
+);
+
+/**
+ * Verify a WebAuthn assertion signature against a P-256 public key (x || y)
+ */
+StarkResult webauthn_verify(
+    const uint8_t public_key[64],
+    const uint8_t* authenticator_data,
+    size_t authenticator_data_len,
+    const uint8_t* client_data_json,
+    size_t client_data_json_len,
+    const uint8_t* challenge,
+    size_t challenge_len,
+    const uint8_t r[32],
+    const uint8_t s[32]
+);
+
+/* ============ ED25519 ============ */
+
+/*
+ * RFC 8032 Ed25519. Private keys are the 32-byte seed (libsodium's 64-byte
+ * secret key is seed || public_key). Verification uses strict rules, like
+ * libsodium's crypto_sign_verify_detached.
+ */
+
+/**
+ * Generate a random keypair (private seed, public key)
+ * Returns STARK_RNG_FAILED if the OS random number generator is unavailable
+ */
+StarkResult ed25519_keypair(uint8_t out_private[32], uint8_t out_public[32]);
+
+/**
+ * Derive the public key from a private seed
+ */
+StarkResult ed25519_get_public_key(const uint8_t private_key[32], uint8_t out[32]);
+
+/**
+ * Sign a message
+ * Returns the 64-byte signature R || S
+ */
+StarkResult ed25519_sign(
+    const uint8_t private_key[32],
+    const uint8_t* msg,
+    size_t len,
+    uint8_t out[64]
+);
+
+/**
+ * Verify a signature over a message
+ */
+StarkResult ed25519_verify(
+    const uint8_t public_key[32],
+    const uint8_t* msg,
+    size_t len,
+    const uint8_t signature[64]
+);
+
+/* ============ X25519 ============ */
+
+/*
+ * RFC 7748 key agreement. Keys are 32-byte little-endian u-coordinates;
+ * private keys are clamped on use. Pass the shared secret through a KDF
+ * before using it as a symmetric key.
+ */
+
+/**
+ * Generate a random keypair
+ * Returns STARK_RNG_FAILED if the OS random number generator is unavailable
+ */
+StarkResult x25519_keypair(uint8_t out_private[32], uint8_t out_public[32]);
+
+/**
+ * Derive the public key from a private key
+ */
+StarkResult x25519_get_public_key(const uint8_t private_key[32], uint8_t out[32]);
+
+/**
+ * Compute the shared secret with a peer's public key
+ * Returns STARK_INVALID_INPUT if the peer key is a low-order point
+ */
+StarkResult x25519(
+    const uint8_t private_key[32],
+    const uint8_t peer_public[32],
+    uint8_t out_shared[32]
+);
+
+/* ============ SCHNORR (STARK Curve) ============ */
+
+/*
+ * x-only Schnorr signatures with a Poseidon challenge
+ * e = poseidon_hash_many([r, public_key, message_hash]) mod n.
+ * Keys are the same as for STARK ECDSA (public key = x coordinate).
+ */
+
+/**
+ * Sign a message hash (deterministic nonce)
+ * Returns signature (r, s), or STARK_INVALID_PRIVATE_KEY unless 0 < key < n
+ */
+StarkResult stark_schnorr_sign(
+    const FeltBytes* private_key,
+    const FeltBytes* message_hash,
+    FeltBytes* out_r,
+    FeltBytes* out_s
+);
+
+/**
+ * Verify a Schnorr signature against a public key (x coordinate)
+ */
+StarkResult stark_schnorr_verify(
+    const FeltBytes* public_key,
+    const FeltBytes* message_hash,
+    const FeltBytes* r,
+    const FeltBytes* s
+);
+
+/* ============ BLS12-381 ============ */
+
+/*
+ * Minimal-pubkey-size BLS signatures (Ethereum consensus style): 32-byte
+ * big-endian secret keys, 48-byte compressed G1 public keys, 96-byte
+ * compressed G2 signatures, ciphersuite
+ * BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_.
+ */
+
+/**
+ * Derive a secret key from input keying material (IETF KeyGen)
+ * ikm_len must be at least 32
+ */
+StarkResult bls12_381_keygen(const uint8_t* ikm, size_t ikm_len, uint8_t out_secret[32]);
+
+/**
+ * Derive the compressed G1 public key from a secret key
+ * Returns STARK_INVALID_PRIVATE_KEY unless 0 < key < r
+ */
+StarkResult bls12_381_get_public_key(const uint8_t secret_key[32], uint8_t out[48]);
+
+/**
+ * Sign a message
+ */
+StarkResult bls12_381_sign(
+    const uint8_t secret_key[32],
+    const uint8_t* msg,
+    size_t len,
+    uint8_t out[96]
+);
+
+/**
+ * Verify a signature (or a same-message aggregate against an aggregated key)
+ */
+StarkResult bls12_381_verify(
+    const uint8_t public_key[48],
+    const uint8_t* msg,
+    size_t len,
+    const uint8_t signature[96]
+);
+
+/**
+ * Verify an aggregate signature: signer i holds public_keys[i] and signed
+ * msg_lens[i] bytes at msgs[i] (NULL when the length is 0)
+ */
+StarkResult bls12_381_aggregate_verify(
+    const uint8_t (*public_keys)[48],
+    const uint8_t* const* msgs,
+    const size_t* msg_lens,
+    size_t count,
+    const uint8_t signature[96]
+);
+
+/**
+ * Prove possession of a secret key: sign the compressed public key with
+ * BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_
+ */
+StarkResult bls12_381_pop_prove(const uint8_t secret_key[32], uint8_t out[96]);
+
+/**
+ * Verify a proof of possession before aggregating the key
+ */
+StarkResult bls12_381_pop_verify(const uint8_t public_key[48], const uint8_t proof[96]);
+
+/**
+ * Aggregate count signatures into one
+ */
+StarkResult bls12_381_aggregate_signatures(
+    const uint8_t (*signatures)[96],
+    size_t count,
+    uint8_t out[96]
+);
+
+/**
+ * Aggregate count public keys into one
+ * Only aggregate keys whose proof of possession has been verified
+ */
+StarkResult bls12_381_aggregate_public_keys(
+    const uint8_t (*public_keys)[48],
+    size_t count,
+    uint8_t out[48]
+);
+
+/**
+ * Hash a message to a compressed G2 point
+ * dst may be NULL to use the signature ciphersuite tag
+ */
+StarkResult bls12_381_hash_to_g2(
+    const uint8_t* msg,
+    size_t len,
+    const uint8_t* dst,
+    size_t dst_len,
+    uint8_t out[96]
+);
+
+/* ============ KZG (EIP-4844) ============ */
+
+/*
+ * KZG commitments to data-availability blobs: a blob is 4096 32-byte
+ * big-endian BLS12-381 scalars (each below r), commitments and proofs are
+ * 48-byte compressed G1 points. Load the Ethereum trusted setup first.
+ */
+
+#define STARK_KZG_BYTES_PER_BLOB 131072
+
+/**
+ * Load the trusted setup (content of c-kzg-4844's trusted_setup.txt, not
+ * NUL-terminated, 4096 G1 points)
+ * Replaces any setup loaded before
+ * Returns STARK_INVALID_INPUT if the text is malformed or a point is invalid
+ */
+StarkResult kzg_load_trusted_setup(const uint8_t* text, size_t len);
+
+/**
+ * Compute the commitment of a blob
+ * Returns STARK_INVALID_INPUT if no setup is loaded or an element is not below r
+ */
+StarkResult kzg_blob_to_commitment(
+    const uint8_t blob[STARK_KZG_BYTES_PER_BLOB],
+    uint8_t out_commitment[48]
+);
+
+/**
+ * Compute the proof that a blob's polynomial takes value y at z
+ * Writes the proof to out_proof and y to out_y
+ */
+StarkResult kzg_compute_proof(
+    const uint8_t blob[STARK_KZG_BYTES_PER_BLOB],
+    const uint8_t z[32],
+    uint8_t out_proof[48],
+    uint8_t out_y[32]
+);
+
+/**
+ * Verify a proof that the committed polynomial takes value y at z
+ * Returns STARK_SUCCESS if it holds, STARK_INVALID_SIGNATURE if not,
+ * STARK_INVALID_INPUT for an invalid point or scalar or no loaded setup
+ */
+StarkResult kzg_verify_proof(
+    const uint8_t commitment[48],
+    const uint8_t z[32],
+    const uint8_t y[32],
+    const uint8_t proof[48]
+);
+
+/* ============ BIP-340 SCHNORR (TAPROOT) ============ */
+
+/*
+ * secp256k1 Schnorr signatures with 32-byte x-only public keys and 64-byte
+ * signatures (r || s). Private keys are the same as for secp256k1 ECDSA.
+ */
+
+/**
+ * Derive the x-only public key from a private key
+ * Returns STARK_INVALID_PRIVATE_KEY unless 0 < key < n
+ */
+StarkResult bip340_get_public_key(const uint8_t private_key[32], uint8_t out[32]);
+
+/**
+ * Sign a message
+ * aux_rand may be NULL to draw auxiliary randomness from the OS
+ */
+StarkResult bip340_sign(
+    const uint8_t private_key[32],
+    const uint8_t* msg,
+    size_t len,
+    const uint8_t aux_rand[32],
+    uint8_t out[64]
+);
+
+/**
+ * Verify a signature against an x-only public key
+ */
+StarkResult bip340_verify(
+    const uint8_t public_key[32],
+    const uint8_t* msg,
+    size_t len,
+    const uint8_t signature[64]
+);
+
+/* ============ TRANSACTIONS (V3) ============ */
+
+/* Data availability modes for nonce/fee */
+#define STARK_DA_MODE_L1 0
+#define STARK_DA_MODE_L2 1
+
+/**
+ * Bounds for one resource
+ * max_price_per_unit is a big-endian felt that must fit in 128 bits
+ */
+typedef struct {
+    uint64_t max_amount;
+    FeltBytes max_price_per_unit;
+} StarknetResourceBounds;
+
+/**
+ * INVOKE v3 transaction fields (everything but the signature)
+ * Empty arrays may pass NULL with a zero length
+ */
+typedef struct {
+    FeltBytes sender_address;
+    const FeltBytes* calldata;
+    size_t calldata_len;
+    FeltBytes nonce;
+    uint64_t tip;
+    StarknetResourceBounds l1_gas;
+    StarknetResourceBounds l2_gas;
+    StarknetResourceBounds l1_data_gas;
+    const FeltBytes* paymaster_data;
+    size_t paymaster_data_len;
+    const FeltBytes* account_deployment_data;
+    size_t account_deployment_data_len;
+    uint32_t nonce_data_availability_mode;
+    uint32_t fee_data_availability_mode;
+} StarknetInvokeV3;
+
+/**
+ * Hash and sign an INVOKE v3 transaction in one call
+ * Returns the transaction hash and its signature (r, s), as starknet_sign
+ * would sign that hash. All three resource bounds are hashed (Starknet 0.13.4+).
+ */
+StarkResult starknet_sign_invoke_v3(
+    const FeltBytes* private_key,
+    const StarknetInvokeV3* tx,
+    const FeltBytes* chain_id,
+    FeltBytes* out_r,
+    FeltBytes* out_s,
+    FeltBytes* out_tx_hash
+);
+
+/* ============ RANDOMNESS ============ */
+
+/**
+ * Fill out with len cryptographically secure random bytes (OS CSPRNG)
+ * out may be NULL when len is 0. Returns STARK_RNG_FAILED if the OS random
+ * source is unavailable.
+ */
+StarkResult stark_random_bytes(uint8_t* out, size_t len);
+
+/* ============ FELT DERIVATION ============ */
+
+/**
+ * Derive the felt for domain_tag from a secret seed: HKDF-SHA256 (no salt,
+ * info = domain_tag) expanded to 64 bytes, reduced mod P
+ * Same seed and tag give the same felt; domain_tag may be NULL when
+ * tag_len is 0
+ * Returns STARK_INVALID_INPUT if seed_len is 0
+ */
+StarkResult felt_derive(
+    const uint8_t* seed,
+    size_t seed_len,
+    const uint8_t* domain_tag,
+    size_t tag_len,
+    FeltBytes* out
+);
+
+/**
+ * Reduce a 64-byte big-endian digest (SHA-512, BLAKE2b, ...) mod P
+ * Uniform up to a bias below 2^-250 for a uniform digest
+ */
+StarkResult felt_from_hash512(const uint8_t digest[64], FeltBytes* out);
+
+/* ============ SECRETS ============ */
+
+/**
+ * Overwrite len bytes at ptr with zeros, in a way the compiler cannot elide
+ * Use it to wipe private keys, seeds and plaintexts once they are no longer
+ * needed. ptr may be NULL when len is 0.
+ */
+StarkResult stark_secure_zero(uint8_t* ptr, size_t len);
+
+/**
+ * Locked secret buffer (opaque)
+ * Dedicated pages locked into RAM with mlock (never swapped), excluded from
+ * core dumps on Linux and FreeBSD, and wiped on free. Not locked off Unix.
+ */
+typedef struct SecretBuffer SecretBuffer;
+
+/**
+ * Allocate a locked buffer of len zeroed bytes
+ * Writes an owned handle to out_secret; release it with secret_free.
+ * Returns STARK_INVALID_INPUT if len is 0 and STARK_MEMORY_LOCK_FAILED if
+ * the pages cannot be locked (usually RLIMIT_MEMLOCK).
+ */
+StarkResult secret_new(size_t len, SecretBuffer** out_secret);
+
+/**
+ * Copy len bytes of data into the buffer at offset
+ * Returns STARK_INVALID_INPUT if the write does not fit. Wipe the source
+ * afterwards with stark_secure_zero.
+ */
+StarkResult secret_write(
+    SecretBuffer* secret,
+    size_t offset,
+    const uint8_t* data,
+    size_t len
+);
+
+/**
+ * Sign a message hash with the Stark private key held in a 32-byte buffer
+ * Returns STARK_INVALID_INPUT for a buffer of another length, otherwise the
+ * same result codes as starknet_sign
+ */
+StarkResult secret_use_for_sign(
+    const SecretBuffer* secret,
+    const FeltBytes* message_hash,
+    FeltBytes* out_r,
+    FeltBytes* out_s
+);
+
+/**
+ * Wipe, unlock and free a secret buffer (NULL is a no-op)
+ */
+void secret_free(SecretBuffer* secret);
+
+/* ============ TEST RNG ============ */
+
+/**
+ * Make all library randomness deterministic, derived from seed
+ * Pass NULL to go back to the OS CSPRNG. Only exported by builds with the
+ * `test-rng` cargo feature; never ship such a build.
+ */
+StarkResult stark_set_test_rng(const uint8_t seed[32]);
+
+/* ============ TEST VECTORS ============ */
+
+/**
+ * Generate JSON test vectors (inputs and expected outputs) for the hash,
+ * signature and V3 transaction hash functions, keyed by function name
+ * Inputs are deterministic, so the output only changes between versions.
+ * Writes a library-allocated UTF-8 buffer (not NUL-terminated); release it
+ * with stark_buffer_free(*out_json, *out_len). Only exported by builds with
+ * the `test-vectors` cargo feature (which also builds the
+ * kundera-test-vectors binary printing the same JSON).
+ */
+StarkResult stark_test_vectors_json_alloc(uint8_t** out_json, size_t* out_len);
+
+/* ============ PERF COUNTERS ============ */
+
+typedef struct {
+    uint64_t calls;
+    /** Cumulative time spent in the calls */
+    uint64_t nanos;
+} StarkPerfCounter;
+
+/**
+ * Calls and time per operation class. A call counts from entry to return,
+ * failed calls included.
+ */
+typedef struct {
+    /** felt_add, felt_sub, felt_mul, felt_div, felt_neg, felt_inverse, felt_pow(_reduced), felt_sqrt, felt_*_assign, felt_vec_*, felt_inner_product, felt_mat_vec_mul */
+    StarkPerfCounter felt_arith;
+    StarkPerfCounter pedersen;
+    /** starknet_poseidon_hash, starknet_poseidon_hash_many(_arena), poseidon_hash_with_domain, starknet_poseidon_hash_felt_array, Poseidon merkle roots, vector commitments, transcripts */
+    StarkPerfCounter poseidon;
+    /** keccak256, starknet_keccak256, stark_grind_nonce, starknet_event_selector, starknet_event_keys */
+    StarkPerfCounter keccak;
+    /** starknet_sign and its variants */
+    StarkPerfCounter sign;
+    /** starknet_verify, verify contexts, starknet_verify_full */
+    StarkPerfCounter verify;
+    /** starknet_verify_batch and starknet_verify_batch_soa (one call per batch) */
+    StarkPerfCounter verify_batch;
+    StarkPerfCounter recover;
+    StarkPerfCounter typed_data;
+    /** starknet_sign_invoke_v3 */
+    StarkPerfCounter transaction;
+} StarkPerfCounters;
+
+/**
+ * Read the process-wide counters. Only exported by builds with the
+ * `profiling` cargo feature (other builds do not time anything).
+ */
+StarkResult stark_perf_counters(StarkPerfCounters* out_counters);
+
+/**
+ * Set every counter back to zero (`profiling` builds only)
+ */
+StarkResult stark_perf_counters_reset(void);
+
+/* ============ PEDERSEN TABLES ============ */
+
+/**
+ * Hash Pedersen with bits-bit lookup windows instead of the default 4-bit
+ * ones: fewer point additions per hash (about 2.3x faster with 12 bits)
+ * for more memory (8 bits: 1 MB, 12: 11 MB, 16: 134 MB). bits must be 0
+ * (default tables, frees the large ones) or 4 to 16. The tables are built
+ * before returning (under a second for 12 bits). Only exported by builds
+ * with the `pedersen-tables` cargo feature.
+ */
+StarkResult stark_set_pedersen_table_size(uint32_t bits);
+
+/**
+ * Window size of the current Pedersen tables (0: the default ones)
+ */
+uint32_t stark_get_pedersen_table_size(void);
+
+/* ============ KEY DERIVATION ============ */
+
+/**
+ * Derive a Stark private key from 32 bytes of seed material (StarkWare grindKey)
+ * Candidates are sha256(seed || i); the first one below the largest multiple
+ * of the curve order under 2^256 is reduced mod n. For keys derived from an
+ * Ethereum wallet, the seed is the r value of the signature.
+ */
+StarkResult stark_grind_key(const uint8_t seed[32], FeltBytes* out_private_key);
+
+/**
+ * Derive a Stark private key along an EIP-2645 path
+ * m/2645'/layer'/application'/eth_address_1'/eth_address_2'/index from a
+ * BIP-39 seed (16 to 64 bytes). The BIP-32 key at the path is ground with
+ * stark_grind_key. Returns InvalidInput for a bad seed length or index >= 2^31.
+ */
+StarkResult stark_derive_eip2645(const uint8_t* seed, size_t seed_len,
+                                 const uint8_t* layer, size_t layer_len,
+                                 const uint8_t* application, size_t application_len,
+                                 const uint8_t eth_address[20], uint32_t index,
+                                 FeltBytes* out_private_key);
+
+/**
+ * Derive a secp256k1 private key from a BIP-39 seed (16 to 64 bytes) along a
+ * BIP-32 path string such as "m/44'/60'/0'/0/0" (UTF-8, not NUL-terminated;
+ * hardened components take a ' or h suffix)
+ */
+StarkResult bip32_derive(const uint8_t* seed, size_t seed_len,
+                         const uint8_t* path, size_t path_len,
+                         uint8_t out_private_key[32]);
+
+/**
+ * Derive a Stark private key along a BIP-32 path: the secp256k1 key at the
+ * path is ground with stark_grind_key (Argent X / Braavos: m/44'/9004'/0'/0/i)
+ */
+StarkResult stark_derive_path(const uint8_t* seed, size_t seed_len,
+                              const uint8_t* path, size_t path_len,
+                              FeltBytes* out_private_key);
+
+/* ============ BIP-39 MNEMONICS ============ */
+
+/* Phrases are UTF-8 bytes with an explicit length (no NUL terminator) */
+#define BIP39_PHRASE_MAX_LEN 215
+
+/**
+ * Generate a random English mnemonic from 128, 160, 192, 224 or 256 bits of
+ * entropy. out_len holds the capacity of out_phrase on input and the phrase
+ * length on output; if too small, the required length is written and
+ * STARK_BUFFER_TOO_SMALL is returned.
+ */
+StarkResult bip39_generate(uint32_t entropy_bits, uint8_t* out_phrase, size_t* out_len);
+
+/**
+ * Check word count, wordlist membership and checksum of a phrase
+ */
+StarkResult bip39_validate(const uint8_t* phrase, size_t len);
+
+/**
+ * Derive the 64-byte BIP-39 seed (PBKDF2-HMAC-SHA512, 2048 rounds) from a
+ * valid phrase and an optional passphrase (null when passphrase_len is 0)
+ */
+StarkResult bip39_to_seed(const uint8_t* phrase, size_t phrase_len,
+                          const uint8_t* passphrase, size_t passphrase_len,
+                          uint8_t out[64]);
+
+/* ============ KEYSTORE V3 ============ */
+
+/* Upper bound on the JSON written by keystore_encrypt */
+#define KEYSTORE_JSON_MAX_LEN 512
+
+/**
+ * Encrypt a 32-byte private key into a Web3 Secret Storage (keystore v3)
+ * JSON file: scrypt (n = 2^18, r = 8, p = 1), AES-128-CTR, keccak MAC.
+ * out_len holds the capacity of out_json on input and the JSON length on
+ * output; if too small, the required length is written and
+ * STARK_BUFFER_TOO_SMALL is returned.
+ */
+StarkResult keystore_encrypt(const uint8_t private_key[32],
+                             const uint8_t* password, size_t password_len,
+                             uint8_t* out_json, size_t* out_len);
+
+/**
+ * Decrypt a keystore v3 JSON file (scrypt or PBKDF2-HMAC-SHA256, AES-128-CTR)
+ * Returns STARK_DECRYPTION_FAILED on a MAC mismatch (wrong password) and
+ * InvalidInput for malformed files or KDF costs above the limits.
+ */
+StarkResult keystore_decrypt(const uint8_t* json, size_t json_len,
+                             const uint8_t* password, size_t password_len,
+                             uint8_t out_private_key[32]);
+
+/* ============ AEAD ============ */
+
+/*
+ * Ciphertexts are data || 16-byte tag. out_len holds the capacity of out on
+ * input and the written length on output; if too small, the required length
+ * is written and STARK_BUFFER_TOO_SMALL is returned. aad may be null when
+ * aad_len is 0.
+ */
+#define AEAD_TAG_LEN 16
+#define XCHACHA20POLY1305_NONCE_LEN 24
+#define AES256GCM_NONCE_LEN 12
+/* version (1) || nonce (24) || ciphertext || tag (16) */
+#define WALLET_BLOB_OVERHEAD 41
+
+/**
+ * Encrypt with XChaCha20-Poly1305. Never reuse a nonce under the same key.
+ */
+StarkResult aead_xchacha20poly1305_encrypt(const uint8_t key[32], const uint8_t nonce[24],
+                                           const uint8_t* aad, size_t aad_len,
+                                           const uint8_t* data, size_t len,
+                                           uint8_t* out, size_t* out_len);
+
+/**
+ * Decrypt XChaCha20-Poly1305 ciphertext || tag
+ * Returns STARK_DECRYPTION_FAILED if authentication fails.
+ */
+StarkResult aead_xchacha20poly1305_decrypt(const uint8_t key[32], const uint8_t nonce[24],
+                                           const uint8_t* aad, size_t aad_len,
+                                           const uint8_t* data, size_t len,
+                                           uint8_t* out, size_t* out_len);
+
+/**
+ * Encrypt with AES-256-GCM. Never reuse a nonce under the same key; 12-byte
+ * nonces should come from a counter rather than the RNG.
+ */
+StarkResult aes256gcm_encrypt(const uint8_t key[32], const uint8_t nonce[12],
+                              const uint8_t* aad, size_t aad_len,
+                              const uint8_t* data, size_t len,
+                              uint8_t* out, size_t* out_len);
+
+/**
+ * Decrypt AES-256-GCM ciphertext || tag
+ * Returns STARK_DECRYPTION_FAILED if authentication fails.
+ */
+StarkResult aes256gcm_decrypt(const uint8_t key[32], const uint8_t nonce[12],
+                              const uint8_t* aad, size_t aad_len,
+                              const uint8_t* data, size_t len,
+                              uint8_t* out, size_t* out_len);
+
+/**
+ * Seal data into a wallet blob (XChaCha20-Poly1305 with a random nonce)
+ * The blob is WALLET_BLOB_OVERHEAD bytes longer than the data.
+ */
+StarkResult wallet_blob_seal(const uint8_t key[32],
+                             const uint8_t* aad, size_t aad_len,
+                             const uint8_t* data, size_t len,
+                             uint8_t* out, size_t* out_len);
+
+/**
+ * Open a wallet blob (same aad as when sealing)
+ * Returns STARK_DECRYPTION_FAILED if authentication fails.
+ */
+StarkResult wallet_blob_open(const uint8_t key[32],
+                             const uint8_t* aad, size_t aad_len,
+                             const uint8_t* blob, size_t blob_len,
+                             uint8_t* out, size_t* out_len);
+
+/* ============ ECIES ============ */
+
+/* ephemeral public key x (32) || ciphertext || tag (16) */
+#define STARK_ECIES_OVERHEAD 48
+
+/**
+ * Encrypt a message to a Stark public key (ephemeral ECDH on the STARK
+ * curve, HKDF-SHA256, XChaCha20-Poly1305). The output is
+ * STARK_ECIES_OVERHEAD bytes longer than the plaintext; out_len holds the
+ * capacity of out on input and the written length on output.
+ */
+StarkResult stark_ecies_encrypt(const FeltBytes* recipient_public_key,
+                                const uint8_t* plaintext, size_t len,
+                                uint8_t* out, size_t* out_len);
+
+/**
+ * Decrypt a message produced by stark_ecies_encrypt
+ * Returns STARK_DECRYPTION_FAILED for a wrong key or tampered ciphertext.
+ */
+StarkResult stark_ecies_decrypt(const FeltBytes* private_key,
+                                const uint8_t* ciphertext, size_t len,
+                                uint8_t* out, size_t* out_len);
+
+/* ============ SEALED BOXES ============ */
+
+/* ephemeral public key (32) || tag (16) || ciphertext */
+#define SEALED_BOX_OVERHEAD 48
+
+/**
+ * Encrypt a message anonymously to an X25519 public key (libsodium
+ * crypto_box_seal). The output is SEALED_BOX_OVERHEAD bytes longer than the
+ * message; out_len holds the capacity of out on input and the written
+ * length on output.
+ */
+StarkResult sealed_box_seal(const uint8_t recipient_public_key[32],
+                            const uint8_t* msg, size_t len,
+                            uint8_t* out, size_t* out_len);
+
+/**
+ * Open a sealed box with the recipient's X25519 private key (libsodium
+ * crypto_box_seal_open). Returns STARK_DECRYPTION_FAILED on failure.
+ */
+StarkResult sealed_box_open(const uint8_t recipient_private_key[32],
+                            const uint8_t* sealed, size_t len,
+                            uint8_t* out, size_t* out_len);
+
+/* ============ CAIRO SERIALIZATION ============ */
+
+/* Bytes in a full ByteArray word (bytes31) */
+#define BYTES31_LEN 31
+
+/**
+ * Serialize bytes as a Cairo 1 ByteArray
+ * [n, word_0..word_{n-1}, pending_word, pending_word_len], 31-byte words.
+ * A len-byte string takes len / 31 + 3 felts. out_count holds the capacity
+ * of out_felts on input and the felt count on output; if too small, the
+ * required count is written and STARK_BUFFER_TOO_SMALL is returned.
+ * data may be NULL when len == 0.
+ */
+StarkResult bytearray_to_felts(
+    const uint8_t* data,
+    size_t len,
+    FeltBytes* out_felts,
+    size_t* out_count
+);
+
+/**
+ * Deserialize exactly one Cairo 1 ByteArray into bytes
+ * out_len follows the usual capacity convention. Returns STARK_INVALID_INPUT
+ * for a truncated or non-canonical serialization, or trailing felts.
+ */
+StarkResult bytearray_from_felts(
+    const FeltBytes* felts,
+    size_t count,
+    uint8_t* out,
+    size_t* out_len
+);
+
+/**
+ * Pack bytes into ceil(len / 31) big-endian 31-byte words, the storage
+ * layout of byte data (no length prefix or pending word); the last word
+ * holds the remaining bytes, right-aligned
+ */
+StarkResult bytes_pack_31(
+    const uint8_t* data,
+    size_t len,
+    FeltBytes* out_felts,
+    size_t* out_count
+);
+
+/**
+ * Unpack len bytes from 31-byte words (inverse of bytes_pack_31)
+ * count must be ceil(len / 31) and each word must fit in its chunk,
+ * otherwise STARK_INVALID_INPUT.
+ */
+StarkResult bytes_unpack_31(
+    const FeltBytes* felts,
+    size_t count,
+    size_t len,
+    uint8_t* out,
+    size_t* out_len
+);
+
+/**
+ * Split a 32-byte big-endian u256 into its Cairo (low, high) u128 felts
+ * Cairo serializes u256 as [low, high].
+ */
+StarkResult u256_to_felts(
+    const uint8_t value[32],
+    FeltBytes* out_low,
+    FeltBytes* out_high
+);
+
+/**
+ * Join Cairo (low, high) felts into a 32-byte big-endian u256
+ * Returns STARK_INVALID_INPUT unless both halves are below 2^128
+ */
+StarkResult u256_from_felts(
+    const FeltBytes* low,
+    const FeltBytes* high,
+    uint8_t out[32]
+);
+
+/**
+ * Calldata under construction (opaque)
+ * Values are appended with the Cairo serde rules: felt252 / ContractAddress /
+ * small integers as one felt, bool as 0 or 1, u256 as [low, high],
+ * Array<felt252> as [len, elements...], ByteArray as above. Structs and
+ * tuples are their members in order.
+ */
+typedef struct CalldataBuilder CalldataBuilder;
+
+/**
+ * Create an empty calldata builder
+ * Writes an owned handle to out_builder; release it with calldata_builder_free.
+ */
+StarkResult calldata_builder_new(CalldataBuilder** out_builder);
+
+/** Append a single felt */
+StarkResult calldata_builder_push_felt(CalldataBuilder* builder, const FeltBytes* value);
+
+/** Append a u256 given as 32 big-endian bytes */
+StarkResult calldata_builder_push_u256(CalldataBuilder* builder, const uint8_t value[32]);
+
+/** Append a bool */
+StarkResult calldata_builder_push_bool(CalldataBuilder* builder, bool value);
+
+/**
+ * Append an Array<felt252> / Span<felt252> (length, then elements)
+ * values may be NULL when len == 0.
+ */
+StarkResult calldata_builder_push_array(
+    CalldataBuilder* builder,
+    const FeltBytes* values,
+    size_t len
+);
+
+/**
+ * Append a ByteArray (typically a UTF-8 string)
+ * data may be NULL when len == 0.
+ */
+StarkResult calldata_builder_push_bytearray(
+    CalldataBuilder* builder,
+    const uint8_t* data,
+    size_t len
+);
+
+/**
+ * Copy the calldata built so far to out_felts
+ * out_count holds the capacity on input and the felt count on output; if
+ * too small, the required count is written and STARK_BUFFER_TOO_SMALL is
+ * returned. The builder is left unchanged.
+ */
+StarkResult calldata_builder_finish(
+    const CalldataBuilder* builder,
+    FeltBytes* out_felts,
+    size_t* out_count
+);
+
+/**
+ * Stream the calldata built so far to callback (see STREAMING)
+ * The builder is left unchanged.
+ */
+StarkResult calldata_builder_finish_stream(
+    const CalldataBuilder* builder,
+    StarkChunkCallback callback,
+    void* ctx
+);
+
+/**
+ * Free a calldata builder (NULL is a no-op)
+ */
+void calldata_builder_free(CalldataBuilder* builder);
+
+/**
+ * Cursor over serialized calldata (opaque)
+ * Reads are bounds-checked; a read that fails leaves the cursor in place.
+ */
+typedef struct CalldataReader CalldataReader;
+
+/**
+ * Create a reader over count felts (copied)
+ * felts may be NULL when count == 0. Writes an owned handle to out_reader;
+ * release it with calldata_reader_free.
+ */
+StarkResult calldata_reader_new(
+    const FeltBytes* felts,
+    size_t count,
+    CalldataReader** out_reader
+);
+
+/** Number of felts left to read */
+StarkResult calldata_reader_remaining(const CalldataReader* reader, size_t* out_count);
+
+/** Read a single felt. Returns STARK_INVALID_INPUT at the end of the data */
+StarkResult calldata_reader_read_felt(CalldataReader* reader, FeltBytes* out);
+
+/**
+ * Read a u256 ([low, high]) as 32 big-endian bytes
+ * Returns STARK_INVALID_INPUT if fewer than two felts are left or a half is
+ * not below 2^128
+ */
+StarkResult calldata_reader_read_u256(CalldataReader* reader, uint8_t out[32]);
+
+/**
+ * Read an Array<felt252> / Span<felt252>
+ * out_count holds the capacity on input and the array length on output; if
+ * too small, the required count is written, STARK_BUFFER_TOO_SMALL is
+ * returned and the cursor does not move. STARK_INVALID_INPUT if the length
+ * prefix runs past the end of the data.
+ */
+StarkResult calldata_reader_read_array(
+    CalldataReader* reader,
+    FeltBytes* out_felts,
+    size_t* out_count
+);
+
+/**
+ * Read a ByteArray
+ * out_len follows the usual capacity convention; the cursor only moves on
+ * success. Returns STARK_INVALID_INPUT for a truncated or non-canonical
+ * ByteArray.
+ */
+StarkResult calldata_reader_read_bytearray(
+    CalldataReader* reader,
+    uint8_t* out,
+    size_t* out_len
+);
+
+/**
+ * Free a calldata reader (NULL is a no-op)
+ */
+void calldata_reader_free(CalldataReader* reader);
+
+/**
+ * Encode a multicall as account __execute__ calldata
+ * Cairo 1 (legacy = false): [n, to, selector, calldata_len, calldata..., ...]
+ * Cairo 0 (legacy = true):  [n, to, selector, offset, len, ..., total_len,
+ *                            flattened calldata...]
+ * calls may be NULL when calls_len == 0. out_count holds the capacity of
+ * out_felts on input and the felt count on output; if too small, the
+ * required count is written and STARK_BUFFER_TOO_SMALL is returned.
+ */
+StarkResult starknet_build_execute_calldata(
+    const StarknetCall* calls,
+    size_t calls_len,
+    bool legacy,
+    FeltBytes* out_felts,
+    size_t* out_count
+);
+
+/**
+ * Same as starknet_build_execute_calldata, streamed to callback (see
+ * STREAMING)
+ */
+StarkResult starknet_build_execute_calldata_stream(
+    const StarknetCall* calls,
+    size_t calls_len,
+    bool legacy,
+    StarkChunkCallback callback,
+    void* ctx
+);
+
+/* ============ CONTRACT ABI ============ */
+
+/**
+ * Parsed Cairo 1 contract ABI (opaque)
+ * JSON values: felt252 / ContractAddress / ClassHash as "0x..." strings,
+ * u8..u32 and i8..i32 as numbers, wider integers and u256 as decimal
+ * strings (hex or decimal strings accepted on input), bool as true/false,
+ * ByteArray as a string, arrays and tuples as arrays, structs as objects,
+ * enums as {"Variant": value} with null for unit variants.
+ */
+typedef struct ContractAbi ContractAbi;
+
+/**
+ * Parse a contract ABI (the "abi" array of a Sierra contract class)
+ * Writes an owned handle to out_abi; release it with abi_free.
+ */
+StarkResult abi_load(const uint8_t* json, size_t len, ContractAbi** out_abi);
+
+/**
+ * Encode the calldata of a call to function_name
+ * args_json is a JSON array of positional arguments or an object keyed by
+ * parameter name. out_count holds the capacity of out_felts on input and
+ * the felt count on output; if too small, the required count is written
+ * and STARK_BUFFER_TOO_SMALL is returned.
+ */
+StarkResult abi_encode_call(
+    const ContractAbi* abi,
+    const uint8_t* function_name,
+    size_t function_name_len,
+    const uint8_t* args_json,
+    size_t args_len,
+    FeltBytes* out_felts,
+    size_t* out_count
+);
+
+/**
+ * Same as abi_encode_call, streamed to callback (see STREAMING)
+ */
+StarkResult abi_encode_call_stream(
+    const ContractAbi* abi,
+    const uint8_t* function_name,
+    size_t function_name_len,
+    const uint8_t* args_json,
+    size_t args_len,
+    StarkChunkCallback callback,
+    void* ctx
+);
+
+/**
+ * Decode the result of a call to function_name into JSON (not
+ * NUL-terminated): the single output, null if none, an array if several.
+ * out_len follows the usual capacity convention. Returns
+ * STARK_INVALID_INPUT if the felts do not match the output types exactly.
+ */
+StarkResult abi_decode_result(
+    const ContractAbi* abi,
+    const uint8_t* function_name,
+    size_t function_name_len,
+    const FeltBytes* felts,
+    size_t count,
+    uint8_t* out_json,
+    size_t* out_len
+);
+
+/**
+ * Same as abi_decode_result, but the library allocates the JSON buffer:
+ * *out_json and *out_len receive it; release it with
+ * stark_buffer_free(*out_json, *out_len).
+ */
+StarkResult abi_decode_result_alloc(
+    const ContractAbi* abi,
+    const uint8_t* function_name,
+    size_t function_name_len,
+    const FeltBytes* felts,
+    size_t count,
+    uint8_t** out_json,
+    size_t* out_len
+);
+
+/**
+ * Free a contract ABI (NULL is a no-op)
+ */
+void abi_free(ContractAbi* abi);
+
+/* ============ ENCODING ============ */
+
+/*
+ * Encoded strings are ASCII and not NUL-terminated. Output lengths hold the
+ * capacity on input and the length on output; if too small, the required
+ * length is written and STARK_BUFFER_TOO_SMALL is returned.
+ */
+
+/**
+ * Encode bytes as lowercase hex (2 * len characters, no 0x prefix)
+ */
+StarkResult hex_encode(const uint8_t* data, size_t len, uint8_t* out_str, size_t* out_len);
+
+/**
+ * Decode hex, with or without a 0x / 0X prefix, in either case
+ * Returns STARK_INVALID_INPUT for an odd digit count or a non-hex character.
+ */
+StarkResult hex_decode(const uint8_t* str, size_t str_len, uint8_t* out, size_t* out_len);
+
+/**
+ * Encode bytes as base64
+ * url_safe: "-_" alphabet without padding; otherwise "+/" with "=" padding
+ */
+StarkResult base64_encode(
+    const uint8_t* data,
+    size_t len,
+    bool url_safe,
+    uint8_t* out_str,
+    size_t* out_len
+);
+
+/**
+ * Decode base64 in the selected alphabet; padding is optional
+ */
+StarkResult base64_decode(
+    const uint8_t* str,
+    size_t str_len,
+    bool url_safe,
+    uint8_t* out,
+    size_t* out_len
+);
+
+/**
+ * Encode bytes as base58 (Bitcoin alphabet)
+ * checksum: append the 4-byte Base58Check checksum (double SHA-256) first
+ */
+StarkResult base58_encode(
+    const uint8_t* data,
+    size_t len,
+    bool checksum,
+    uint8_t* out_str,
+    size_t* out_len
+);
+
+/**
+ * Decode base58 (Bitcoin alphabet)
+ * checksum: verify and strip the trailing Base58Check checksum; returns
+ * STARK_INVALID_INPUT if it is missing or wrong
+ */
+StarkResult base58_decode(
+    const uint8_t* str,
+    size_t str_len,
+    bool checksum,
+    uint8_t* out,
+    size_t* out_len
+);
+
 #endif /* STARKNET_CRYPTO_H */
@@ -0,0 +1,968 @@
+//! Contract ABI encoding and decoding
+//!
+//! Loads a Cairo 1 (Sierra) contract ABI and converts between JSON values
+//! and calldata following the types it declares. Functions are looked up by
+//! name at the top level and inside `interface` entries; `constructor` and
+//! `l1_handler` entries are included. Cairo 0 ABIs are not supported.
+//!
+//! JSON representation of Cairo values:
+//!
+//! | Cairo type                               | JSON                                  |
+//! |------------------------------------------|---------------------------------------|
+//! | `felt252`, `ContractAddress`, `ClassHash`, `EthAddress`, `bytes31` | `"0x…"` (input: number, hex or decimal string) |
+//! | `u8`..`u32`, `i8`..`i32`                 | number                                |
+//! | `u64`, `u128`, `u256`, `i64`, `i128`     | decimal string (input: number, hex or decimal string) |
+//! | `bool`                                   | `true` / `false`                      |
+//! | `ByteArray`                              | string (UTF-8)                        |
+//! | `Array<T>`, `Span<T>`, tuples            | array                                 |
+//! | structs                                  | object keyed by member name           |
+//! | enums (`Option`, `Result`, ...)          | `{"Variant": value}`, `null` for `()` |
+//!
+//! Call arguments are a JSON array (positional) or an object keyed by
+//! parameter name. A decoded result is the function's single output, `null`
+//! if it has none, or an array for several.
+
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ffi::c_void;
+
+use num_bigint::{BigInt, BigUint, Sign};
+use serde_json::{Map, Value};
+use starknet_crypto::Felt;
+
+use crate::buffer::alloc_bytes_out;
+use crate::byte_array::{decode_byte_array, encode_byte_array};
+use crate::handle::StarkHandle;
+use crate::stream::{stream_felts, StarkChunkCallback};
+use crate::{
+    bytes_from_raw,
+    error::{invalid_input, null_pointer},
+    felts_from_raw, ffi_guard, ffi_guard_void, write_bytes_out, write_felts_out, FeltBytes,
+    StarkResult,
+};
+
+/// Nesting limit for types, so self-referencing ABI types cannot recurse
+/// without bound
+const MAX_TYPE_DEPTH: usize = 64;
+
+/// (name, type) pairs of function parameters, struct members or enum
+/// variants
+type Members = Vec<(String, String)>;
+
+struct Function {
+    inputs: Members,
+    outputs: Vec<String>,
+}
+
+/// Parsed contract ABI (opaque)
+pub struct ContractAbi {
+    functions: BTreeMap<String, Function>,
+    structs: BTreeMap<String, Members>,
+    enums: BTreeMap<String, Members>,
+}
+
+impl StarkHandle for ContractAbi {
+    const NAME: &'static str = "ContractAbi";
+}
+
+/// A Cairo type resolved against the ABI
+enum Ty<'a> {
+    Unit,
+    /// A felt below 2^bits (also below P), shown as hex
+    Felt(u64),
+    Bool,
+    Uint(u64),
+    Int(u64),
+    U256,
+    ByteArray,
+    Array(&'a str),
+    Tuple(Vec<&'a str>),
+    Struct(&'a Members),
+    Enum(&'a Members),
+}
+
+fn members(entry: &Value, key: &str) -> Option<Members> {
+    entry
+        .get(key)?
+        .as_array()?
+        .iter()
+        .map(|m| {
+            Some((
+                m.get("name")?.as_str()?.to_owned(),
+                m.get("type")?.as_str()?.to_owned(),
+            ))
+        })
+        .collect()
+}
+
+/// `T` in `base::<T>`
+fn generic_arg<'a>(ty: &'a str, base: &str) -> Option<&'a str> {
+    ty.strip_prefix(base)?
+        .strip_prefix("::<")?
+        .strip_suffix('>')
+}
+
+/// Split a tuple body on its top-level commas
+fn tuple_elements(body: &str) -> Vec<&str> {
+    let mut elements = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in body.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                elements.push(body[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    elements.push(body[start..].trim());
+    // "(T,)" is a one-element tuple
+    elements.retain(|e| !e.is_empty());
+    elements
+}
+
+fn integer_bits(name: &str) -> Option<(bool, u64)> {
+    let (signed, bits) = match name.strip_prefix('u') {
+        Some(bits) => (false, bits),
+        None => (true, name.strip_prefix('i')?),
+    };
+    match bits {
+        "8" | "16" | "32" | "64" | "128" => Some((signed, bits.parse().ok()?)),
+        "size" if !signed => Some((false, 32)),
+        _ => None,
+    }
+}
+
+fn felt_modulus() -> BigUint {
+    Felt::MAX.to_biguint() + 1u32
+}
+
+fn parse_biguint(value: &Value) -> Option<BigUint> {
+    match value {
+        Value::Number(n) => n.as_u64().map(BigUint::from),
+        Value::String(s) => match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) if !hex.is_empty() => BigUint::parse_bytes(hex.as_bytes(), 16),
+            Some(_) => None,
+            None if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) => {
+                BigUint::parse_bytes(s.as_bytes(), 10)
+            }
+            None => None,
+        },
+        _ => None,
+    }
+}
+
+fn parse_bigint(value: &Value) -> Option<BigInt> {
+    match value {
+        Value::Number(n) => n.as_i64().map(BigInt::from),
+        Value::String(s) => match s.strip_prefix('-') {
+            Some(magnitude) => {
+                let magnitude = parse_biguint(&Value::String(magnitude.to_owned()))?;
+                Some(BigInt::from_biguint(Sign::Minus, magnitude))
+            }
+            None => parse_biguint(value).map(BigInt::from),
+        },
+        _ => None,
+    }
+}
+
+/// Small integers as JSON numbers, wide ones as decimal strings
+fn integer_json(value: BigInt, bits: u64) -> Value {
+    match i64::try_from(&value) {
+        Ok(n) if bits <= 32 => Value::from(n),
+        _ => Value::String(value.to_string()),
+    }
+}
+
+impl ContractAbi {
+    fn parse(json: &[u8]) -> Option<Self> {
+        let entries: Vec<Value> = serde_json::from_slice(json).ok()?;
+        let mut abi = ContractAbi {
+            functions: BTreeMap::new(),
+            structs: BTreeMap::new(),
+            enums: BTreeMap::new(),
+        };
+        for entry in &entries {
+            abi.add_entry(entry)?;
+        }
+        Some(abi)
+    }
+
+    fn add_entry(&mut self, entry: &Value) -> Option<()> {
+        match entry.get("type")?.as_str()? {
+            "function" | "constructor" | "l1_handler" => {
+                let outputs = match entry.get("outputs") {
+                    Some(outputs) => outputs
+                        .as_array()?
+                        .iter()
+                        .map(|o| Some(o.get("type")?.as_str()?.to_owned()))
+                        .collect::<Option<_>>()?,
+                    None => Vec::new(),
+                };
+                let function = Function {
+                    inputs: members(entry, "inputs")?,
+                    outputs,
+                };
+                let name = entry.get("name")?.as_str()?.to_owned();
+                self.functions.insert(name, function);
+            }
+            "interface" => {
+                for item in entry.get("items")?.as_array()? {
+                    self.add_entry(item)?;
+                }
+            }
+            "struct" => {
+                let name = entry.get("name")?.as_str()?.to_owned();
+                self.structs.insert(name, members(entry, "members")?);
+            }
+            "enum" => {
+                let name = entry.get("name")?.as_str()?.to_owned();
+                self.enums.insert(name, members(entry, "variants")?);
+            }
+            // impl, event
+            _ => {}
+        }
+        Some(())
+    }
+
+    fn resolve<'a>(&'a self, ty: &'a str) -> Option<Ty<'a>> {
+        let ty = ty.trim();
+        if ty == "()" {
+            return Some(Ty::Unit);
+        }
+        if let Some(body) = ty.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+            return Some(Ty::Tuple(tuple_elements(body)));
+        }
+        if let Some(inner) =
+            generic_arg(ty, "core::array::Array").or_else(|| generic_arg(ty, "core::array::Span"))
+        {
+            return Some(Ty::Array(inner));
+        }
+        if let Some((signed, bits)) = ty.strip_prefix("core::integer::").and_then(integer_bits) {
+            return Some(if signed {
+                Ty::Int(bits)
+            } else {
+                Ty::Uint(bits)
+            });
+        }
+
+        match ty {
+            "core::felt252" => Some(Ty::Felt(252)),
+            "core::starknet::contract_address::ContractAddress"
+            | "core::starknet::class_hash::ClassHash"
+            | "core::starknet::storage_access::StorageAddress" => Some(Ty::Felt(251)),
+            "core::starknet::eth_address::EthAddress" => Some(Ty::Felt(160)),
+            "core::bytes_31::bytes31" => Some(Ty::Felt(248)),
+            "core::bool" => Some(Ty::Bool),
+            "core::integer::u256" => Some(Ty::U256),
+            "core::byte_array::ByteArray" => Some(Ty::ByteArray),
+            _ => self
+                .structs
+                .get(ty)
+                .map(Ty::Struct)
+                .or_else(|| self.enums.get(ty).map(Ty::Enum)),
+        }
+    }
+
+    fn encode(&self, ty: &str, value: &Value, out: &mut Vec<Felt>, depth: usize) -> Option<()> {
+        if depth > MAX_TYPE_DEPTH {
+            return None;
+        }
+        match self.resolve(ty)? {
+            Ty::Unit => {
+                if !value.is_null() {
+                    return None;
+                }
+            }
+            Ty::Felt(bits) => {
+                let n = parse_biguint(value)?;
+                if n.bits() > bits || n >= felt_modulus() {
+                    return None;
+                }
+                out.push(Felt::from(&n));
+            }
+            Ty::Bool => out.push(Felt::from(value.as_bool()?)),
+            Ty::Uint(bits) => {
+                let n = parse_biguint(value)?;
+                if n.bits() > bits {
+                    return None;
+                }
+                out.push(Felt::from(&n));
+            }
+            Ty::Int(bits) => {
+                let n = parse_bigint(value)?;
+                let bound = BigInt::from(1) << (bits - 1);
+                if n < -bound.clone() || n >= bound {
+                    return None;
+                }
+                out.push(Felt::from(&n));
+            }
+            Ty::U256 => {
+                let n = parse_biguint(value)?;
+                if n.bits() > 256 {
+                    return None;
+                }
+                let low = &n & ((BigUint::from(1u32) << 128) - 1u32);
+                out.extend([Felt::from(&low), Felt::from(&(n >> 128))]);
+            }
+            Ty::ByteArray => out.extend(encode_byte_array(value.as_str()?.as_bytes())),
+            Ty::Array(inner) => {
+                let items = value.as_array()?;
+                out.push(Felt::from(items.len()));
+                for item in items {
+                    self.encode(inner, item, out, depth + 1)?;
+                }
+            }
+            Ty::Tuple(types) => {
+                let items = value.as_array()?;
+                if items.len() != types.len() {
+                    return None;
+                }
+                for (ty, item) in types.iter().zip(items) {
+                    self.encode(ty, item, out, depth + 1)?;
+                }
+            }
+            Ty::Struct(members) => self.encode_named(members, value.as_object()?, out, depth)?,
+            Ty::Enum(variants) => {
+                let object = value.as_object()?;
+                let (name, value) = match object.iter().next() {
+                    Some(entry) if object.len() == 1 => entry,
+                    _ => return None,
+                };
+                let index = variants.iter().position(|(n, _)| n == name)?;
+                out.push(Felt::from(index));
+                self.encode(&variants[index].1, value, out, depth + 1)?;
+            }
+        }
+        Some(())
+    }
+
+    /// Encode members from an object that has exactly those keys
+    fn encode_named(
+        &self,
+        members: &Members,
+        object: &Map<String, Value>,
+        out: &mut Vec<Felt>,
+        depth: usize,
+    ) -> Option<()> {
+        if object.len() != members.len() {
+            return None;
+        }
+        for (name, ty) in members {
+            self.encode(ty, object.get(name)?, out, depth + 1)?;
+        }
+        Some(())
+    }
+
+    fn decode(&self, ty: &str, felts: &[Felt], pos: &mut usize, depth: usize) -> Option<Value> {
+        if depth > MAX_TYPE_DEPTH {
+            return None;
+        }
+        let mut next = || {
+            let felt = *felts.get(*pos)?;
+            *pos += 1;
+            Some(felt)
+        };
+
+        let value = match self.resolve(ty)? {
+            Ty::Unit => Value::Null,
+            Ty::Felt(bits) => {
+                let felt = next()?;
+                if felt.bits() as u64 > bits {
+                    return None;
+                }
+                Value::String(format!("{felt:#x}"))
+            }
+            Ty::Bool => match next()? {
+                f if f == Felt::ZERO => Value::Bool(false),
+                f if f == Felt::ONE => Value::Bool(true),
+                _ => return None,
+            },
+            Ty::Uint(bits) => {
+                let felt = next()?;
+                if felt.bits() as u64 > bits {
+                    return None;
+                }
+                integer_json(felt.to_bigint(), bits)
+            }
+            Ty::Int(bits) => {
+                let felt = next()?;
+                // Negative values are encoded as P - |x|
+                let n = match felt.to_biguint() {
+                    n if n > Felt::MAX.to_biguint() >> 1 => {
+                        BigInt::from(n) - BigInt::from(felt_modulus())
+                    }
+                    n => BigInt::from(n),
+                };
+                let bound = BigInt::from(1) << (bits - 1);
+                if n < -bound.clone() || n >= bound {
+                    return None;
+                }
+                integer_json(n, bits)
+            }
+            Ty::U256 => {
+                let (low, high) = (next()?, next()?);
+                if low.bits() > 128 || high.bits() > 128 {
+                    return None;
+                }
+                let n: BigUint = (high.to_biguint() << 128) + low.to_biguint();
+                Value::String(n.to_string())
+            }
+            Ty::ByteArray => {
+                let (data, consumed) = decode_byte_array(&felts[*pos..])?;
+                *pos += consumed;
+                Value::String(String::from_utf8(data).ok()?)
+            }
+            Ty::Array(inner) => {
+                let len: usize = next()?.try_into().ok()?;
+                // Every element takes at least one felt, except `()`
+                if len > felts.len() - *pos {
+                    return None;
+                }
+                let items = (0..len)
+                    .map(|_| self.decode(inner, felts, pos, depth + 1))
+                    .collect::<Option<_>>()?;
+                Value::Array(items)
+            }
+            Ty::Tuple(types) => Value::Array(
+                types
+                    .iter()
+                    .map(|ty| self.decode(ty, felts, pos, depth + 1))
+                    .collect::<Option<_>>()?,
+            ),
+            Ty::Struct(members) => {
+                let mut object = Map::new();
+                for (name, ty) in members {
+                    object.insert(name.clone(), self.decode(ty, felts, pos, depth + 1)?);
+                }
+                Value::Object(object)
+            }
+            Ty::Enum(variants) => {
+                let index: usize = next()?.try_into().ok()?;
+                let (name, ty) = variants.get(index)?;
+                let mut object = Map::new();
+                object.insert(name.clone(), self.decode(ty, felts, pos, depth + 1)?);
+                Value::Object(object)
+            }
+        };
+        Some(value)
+    }
+
+    fn encode_call(&self, function: &str, args: &Value) -> Option<Vec<Felt>> {
+        let function = self.functions.get(function)?;
+        let mut out = Vec::new();
+        match args {
+            Value::Array(items) => {
+                if items.len() != function.inputs.len() {
+                    return None;
+                }
+                for ((_, ty), item) in function.inputs.iter().zip(items) {
+                    self.encode(ty, item, &mut out, 0)?;
+                }
+            }
+            Value::Object(object) => self.encode_named(&function.inputs, object, &mut out, 0)?,
+            _ => return None,
+        }
+        Some(out)
+    }
+
+    fn decode_result(&self, function: &str, felts: &[Felt]) -> Option<Value> {
+        let function = self.functions.get(function)?;
+        let mut pos = 0;
+        let mut values = function
+            .outputs
+            .iter()
+            .map(|ty| self.decode(ty, felts, &mut pos, 0))
+            .collect::<Option<Vec<_>>>()?;
+        if pos != felts.len() {
+            return None;
+        }
+        Some(match values.len() {
+            0 => Value::Null,
+            1 => values.remove(0),
+            _ => Value::Array(values),
+        })
+    }
+}
+
+/// Parse a contract ABI (the `abi` array of a Sierra contract class)
+///
+/// Writes an owned handle to `out_abi`; release it with `abi_free`. Returns
+/// `InvalidInput` if the JSON is not an ABI array.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_abi_load")]
+pub unsafe extern "C" fn abi_load(
+    json: *const u8,
+    len: usize,
+    out_abi: *mut *mut ContractAbi,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_abi);
+        let json = match bytes_from_raw(json, len) {
+            Some(j) => j,
+            None => return null_pointer("json"),
+        };
+
+        match ContractAbi::parse(json) {
+            Some(abi) => {
+                *out_abi = abi.into_handle();
+                StarkResult::Success
+            }
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+/// Calldata of a call to `function_name`, for `abi_encode_call` and
+/// `abi_encode_call_stream`
+unsafe fn encode_call_felts(
+    abi: *const ContractAbi,
+    function_name: *const u8,
+    function_name_len: usize,
+    args_json: *const u8,
+    args_len: usize,
+) -> Result<Vec<Felt>, StarkResult> {
+    if abi.is_null() {
+        return Err(null_pointer("abi"));
+    }
+    let abi = ContractAbi::from_handle(abi)?;
+    let function_name = match bytes_from_raw(function_name, function_name_len) {
+        Some(n) => n,
+        None => return Err(null_pointer("function_name")),
+    };
+    let function_name = match core::str::from_utf8(function_name) {
+        Ok(n) => n,
+        Err(_) => return Err(invalid_input("`function_name` is not valid UTF-8")),
+    };
+    let args_json = match bytes_from_raw(args_json, args_len) {
+        Some(a) => a,
+        None => return Err(null_pointer("args_json")),
+    };
+    let args: Value = match serde_json::from_slice(args_json) {
+        Ok(a) => a,
+        Err(e) => return Err(invalid_input(format!("`args_json`: {e}"))),
+    };
+
+    match abi.encode_call(function_name, &args) {
+        Some(felts) => Ok(felts),
+        None => Err(StarkResult::InvalidInput),
+    }
+}
+
+/// Encode the calldata of a call to `function_name`
+///
+/// `args_json` is a JSON array of positional arguments or an object keyed
+/// by parameter name. `out_count` holds the capacity of `out_felts` on input
+/// and the number of felts on output; if the array is too small, the
+/// required count is written and `BufferTooSmall` is returned. Returns
+/// `InvalidInput` for an unknown function, an unsupported type or an
+/// argument that does not match its type.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_abi_encode_call")]
+pub unsafe extern "C" fn abi_encode_call(
+    abi: *const ContractAbi,
+    function_name: *const u8,
+    function_name_len: usize,
+    args_json: *const u8,
+    args_len: usize,
+    out_felts: *mut FeltBytes,
+    out_count: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_count);
+        match encode_call_felts(abi, function_name, function_name_len, args_json, args_len) {
+            Ok(felts) => write_felts_out(&felts, out_felts, out_count),
+            Err(e) => e,
+        }
+    })
+}
+
+/// Encode the calldata of a call to `function_name` and stream it to
+/// `callback`
+///
+/// Same as `abi_encode_call`, but the felts are delivered to `callback`
+/// (with `ctx`) in chunks; see `stream`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_abi_encode_call_stream")]
+pub unsafe extern "C" fn abi_encode_call_stream(
+    abi: *const ContractAbi,
+    function_name: *const u8,
+    function_name_len: usize,
+    args_json: *const u8,
+    args_len: usize,
+    callback: StarkChunkCallback,
+    ctx: *mut c_void,
+) -> StarkResult {
+    ffi_guard(|| {
+        match encode_call_felts(abi, function_name, function_name_len, args_json, args_len) {
+            Ok(felts) => stream_felts(&felts, callback, ctx),
+            Err(e) => e,
+        }
+    })
+}
+
+/// JSON of the result of a call to `function_name`, for `abi_decode_result`
+/// and `abi_decode_result_alloc`
+unsafe fn decode_result_json(
+    abi: *const ContractAbi,
+    function_name: *const u8,
+    function_name_len: usize,
+    felts: *const FeltBytes,
+    count: usize,
+) -> Result<String, StarkResult> {
+    if abi.is_null() {
+        return Err(null_pointer("abi"));
+    }
+    let abi = ContractAbi::from_handle(abi)?;
+    let function_name = match bytes_from_raw(function_name, function_name_len) {
+        Some(n) => n,
+        None => return Err(null_pointer("function_name")),
+    };
+    let function_name = match core::str::from_utf8(function_name) {
+        Ok(n) => n,
+        Err(_) => return Err(invalid_input("`function_name` is not valid UTF-8")),
+    };
+    let felts = match felts_from_raw(felts, count) {
+        Some(f) => f,
+        None => return Err(null_pointer("felts")),
+    };
+
+    match abi.decode_result(function_name, &felts) {
+        Some(value) => Ok(value.to_string()),
+        None => Err(StarkResult::InvalidInput),
+    }
+}
+
+/// Decode the result of a call to `function_name` into JSON
+///
+/// `felts` may be null when `count == 0`. `out_len` holds the capacity of
+/// `out_json` on input and the JSON length on output (UTF-8, not
+/// NUL-terminated); if the buffer is too small, the required length is
+/// written and `BufferTooSmall` is returned. Returns `InvalidInput` for an
+/// unknown function, data that does not match the output types, or
+/// trailing felts.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_abi_decode_result")]
+pub unsafe extern "C" fn abi_decode_result(
+    abi: *const ContractAbi,
+    function_name: *const u8,
+    function_name_len: usize,
+    felts: *const FeltBytes,
+    count: usize,
+    out_json: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_len);
+        match decode_result_json(abi, function_name, function_name_len, felts, count) {
+            Ok(json) => write_bytes_out(json.as_bytes(), out_json, out_len),
+            Err(e) => e,
+        }
+    })
+}
+
+/// Decode the result of a call to `function_name` into a library-allocated
+/// JSON buffer
+///
+/// Same as `abi_decode_result`, but writes the JSON (UTF-8, not
+/// NUL-terminated) to a new buffer in `out_json` and its length to
+/// `out_len`; release it with `stark_buffer_free(*out_json, *out_len)`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_abi_decode_result_alloc")]
+pub unsafe extern "C" fn abi_decode_result_alloc(
+    abi: *const ContractAbi,
+    function_name: *const u8,
+    function_name_len: usize,
+    felts: *const FeltBytes,
+    count: usize,
+    out_json: *mut *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_json, out_len);
+        match decode_result_json(abi, function_name, function_name_len, felts, count) {
+            Ok(json) => alloc_bytes_out(json.into_bytes(), out_json, out_len),
+            Err(e) => e,
+        }
+    })
+}
+
+/// Free a contract ABI (null is a no-op)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_abi_free")]
+pub unsafe extern "C" fn abi_free(abi: *mut ContractAbi) {
+    ffi_guard_void(|| ContractAbi::free_handle(abi))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::felt_to_bytes;
+    use crate::stream::tests::collect_chunks;
+    use serde_json::json;
+
+    const ABI: &str = r#"[
+      {
+        "type": "impl",
+        "name": "TokenImpl",
+        "interface_name": "demo::IToken"
+      },
+      {
+        "type": "struct",
+        "name": "core::integer::u256",
+        "members": [
+          { "name": "low", "type": "core::integer::u128" },
+          { "name": "high", "type": "core::integer::u128" }
+        ]
+      },
+      {
+        "type": "enum",
+        "name": "core::bool",
+        "variants": [
+          { "name": "False", "type": "()" },
+          { "name": "True", "type": "()" }
+        ]
+      },
+      {
+        "type": "struct",
+        "name": "demo::Order",
+        "members": [
+          { "name": "owner", "type": "core::starknet::contract_address::ContractAddress" },
+          { "name": "amount", "type": "core::integer::u256" },
+          { "name": "tags", "type": "core::array::Span::<core::felt252>" },
+          { "name": "delta", "type": "core::integer::i32" }
+        ]
+      },
+      {
+        "type": "enum",
+        "name": "core::option::Option::<core::integer::u64>",
+        "variants": [
+          { "name": "Some", "type": "core::integer::u64" },
+          { "name": "None", "type": "()" }
+        ]
+      },
+      {
+        "type": "interface",
+        "name": "demo::IToken",
+        "items": [
+          {
+            "type": "function",
+            "name": "transfer",
+            "inputs": [
+              { "name": "recipient", "type": "core::starknet::contract_address::ContractAddress" },
+              { "name": "amount", "type": "core::integer::u256" }
+            ],
+            "outputs": [{ "type": "core::bool" }],
+            "state_mutability": "external"
+          },
+          {
+            "type": "function",
+            "name": "place",
+            "inputs": [
+              { "name": "order", "type": "demo::Order" },
+              { "name": "memo", "type": "core::byte_array::ByteArray" },
+              { "name": "expiry", "type": "core::option::Option::<core::integer::u64>" },
+              { "name": "pair", "type": "(core::integer::u8, core::bool)" }
+            ],
+            "outputs": [],
+            "state_mutability": "external"
+          },
+          {
+            "type": "function",
+            "name": "get_order",
+            "inputs": [],
+            "outputs": [{ "type": "(demo::Order, core::byte_array::ByteArray)" }],
+            "state_mutability": "view"
+          }
+        ]
+      },
+      {
+        "type": "event",
+        "name": "demo::Event",
+        "kind": "enum",
+        "variants": []
+      }
+    ]"#;
+
+    fn abi() -> ContractAbi {
+        ContractAbi::parse(ABI.as_bytes()).unwrap()
+    }
+
+    fn felts(values: &[u64]) -> Vec<Felt> {
+        values.iter().map(|&v| Felt::from(v)).collect()
+    }
+
+    #[test]
+    fn test_abi_encode_transfer() {
+        let abi = abi();
+        let amount = "340282366920938463463374607431768211457"; // 2^128 + 1
+        let expected = felts(&[0xabc, 1, 1]);
+
+        let positional = json!(["0xabc", amount]);
+        assert_eq!(
+            abi.encode_call("transfer", &positional),
+            Some(expected.clone())
+        );
+        let named = json!({ "amount": amount, "recipient": 2748 });
+        assert_eq!(abi.encode_call("transfer", &named), Some(expected));
+
+        assert!(abi.encode_call("transfer", &json!(["0xabc"])).is_none());
+        assert!(abi
+            .encode_call("transfer", &json!({ "recipient": 1 }))
+            .is_none());
+        assert!(abi
+            .encode_call("transfer", &json!(["0xabc", "-1"]))
+            .is_none());
+        assert!(abi.encode_call("mint", &json!([])).is_none());
+    }
+
+    #[test]
+    fn test_abi_encode_nested() {
+        let args = json!({
+            "order": { "owner": "0x1", "amount": 5, "tags": ["0x7", "8"], "delta": -2 },
+            "memo": "hi",
+            "expiry": { "Some": "1000" },
+            "pair": [255, true]
+        });
+        let minus_two = Felt::ZERO - Felt::TWO;
+
+        let mut expected = felts(&[1, 5, 0, 2, 7, 8]);
+        expected.push(minus_two);
+        expected.extend(felts(&[0, 0x6869, 2, 0, 1000, 255, 1]));
+        assert_eq!(abi().encode_call("place", &args), Some(expected));
+
+        let mut args = args;
+        args["expiry"] = json!({ "None": null });
+        args["pair"] = json!([256, true]);
+        assert!(abi().encode_call("place", &args).is_none());
+        args["pair"] = json!([1, false]);
+        let encoded = abi().encode_call("place", &args).unwrap();
+        assert_eq!(&encoded[10..], &felts(&[1, 1, 0]));
+    }
+
+    #[test]
+    fn test_abi_decode_result() {
+        let abi = abi();
+        assert_eq!(
+            abi.decode_result("transfer", &felts(&[1])),
+            Some(json!(true))
+        );
+        assert_eq!(abi.decode_result("place", &[]), Some(Value::Null));
+        assert!(abi.decode_result("transfer", &felts(&[2])).is_none());
+        assert!(abi.decode_result("transfer", &felts(&[1, 0])).is_none());
+
+        let mut data = felts(&[0x1, 0, 1, 1, 9]);
+        data.push(Felt::ZERO - Felt::from(40u64));
+        data.extend(felts(&[0, 0x6f6b, 2]));
+        assert_eq!(
+            abi.decode_result("get_order", &data),
+            Some(json!([
+                {
+                    "owner": "0x1",
+                    "amount": "340282366920938463463374607431768211456",
+                    "tags": ["0x9"],
+                    "delta": -40
+                },
+                "ok"
+            ]))
+        );
+
+        // Array length beyond the data
+        data[3] = Felt::from(1000u64);
+        assert!(abi.decode_result("get_order", &data).is_none());
+    }
+
+    #[test]
+    fn test_abi_ffi() {
+        let args = br#"["0x2", "10"]"#;
+        let mut calldata = [[0u8; 32]; 3];
+        let mut json = [0u8; 16];
+
+        unsafe {
+            let mut abi = core::ptr::null_mut();
+            assert_eq!(
+                abi_load(ABI.as_ptr(), ABI.len(), &mut abi),
+                StarkResult::Success
+            );
+
+            let mut count = calldata.len();
+            let result = abi_encode_call(
+                abi,
+                b"transfer".as_ptr(),
+                8,
+                args.as_ptr(),
+                args.len(),
+                calldata.as_mut_ptr(),
+                &mut count,
+            );
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(count, 3);
+
+            let mut chunks: Vec<Vec<u8>> = Vec::new();
+            let result = abi_encode_call_stream(
+                abi,
+                b"transfer".as_ptr(),
+                8,
+                args.as_ptr(),
+                args.len(),
+                Some(collect_chunks),
+                (&mut chunks as *mut Vec<Vec<u8>>).cast(),
+            );
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(chunks, [calldata.concat()]);
+
+            let result_felts = [felt_to_bytes(&Felt::ZERO)];
+            let mut len = json.len();
+            let result = abi_decode_result(
+                abi,
+                b"transfer".as_ptr(),
+                8,
+                result_felts.as_ptr(),
+                1,
+                json.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(&json[..len], b"false");
+
+            let mut out = core::ptr::null_mut();
+            let result = abi_decode_result_alloc(
+                abi,
+                b"transfer".as_ptr(),
+                8,
+                result_felts.as_ptr(),
+                1,
+                &mut out,
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(core::slice::from_raw_parts(out, len), b"false");
+            crate::buffer::stark_buffer_free(out, len);
+            abi_free(abi);
+
+            let not_abi = b"{}";
+            assert_eq!(
+                abi_load(not_abi.as_ptr(), not_abi.len(), &mut abi),
+                StarkResult::InvalidInput
+            );
+            abi_free(core::ptr::null_mut());
+        }
+        assert_eq!(calldata[0], felt_to_bytes(&Felt::TWO));
+        assert_eq!(calldata[1], felt_to_bytes(&Felt::from(10u64)));
+    }
+
+    #[test]
+    fn test_abi_self_referencing_type() {
+        let abi = r#"[
+          { "type": "struct", "name": "demo::Loop", "members": [{ "name": "next", "type": "demo::Loop" }] },
+          { "type": "function", "name": "f", "inputs": [], "outputs": [{ "type": "demo::Loop" }] }
+        ]"#;
+        let abi = ContractAbi::parse(abi.as_bytes()).unwrap();
+        assert!(abi.decode_result("f", &[]).is_none());
+    }
+}
@@ -0,0 +1,461 @@
+//! Contract address computation
+//!
+//! `address = pedersen_array(PREFIX, deployer, salt, class_hash,
+//! pedersen_array(calldata)) mod (2^251 - 256)`, where `PREFIX` is the short
+//! string `STARKNET_CONTRACT_ADDRESS`.
+//!
+//! Also models the Universal Deployer Contract (UDC), which mangles the salt
+//! with the caller's address for "unique" deployments.
+//!
+//! Account presets bake in the class hash and constructor calldata of the
+//! common wallets, for counterfactual addresses from a public key:
+//!
+//! - OpenZeppelin Account 0.8.1: `[public_key]`
+//! - Argent X 0.4.0: `[0, public_key, 1]` (Starknet signer, no guardian)
+//! - Braavos base account (upgraded to 1.0 on deployment): `[public_key]`
+//!
+//! Wallets use the public key as salt, but any salt is accepted.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use starknet_crypto::Felt;
+
+use crate::pedersen::pedersen_hash;
+use crate::{
+    error::null_pointer, felt_from_bytes, felt_to_bytes, felts_from_raw, ffi_guard,
+    pedersen_hash_array, FeltBytes, StarkResult,
+};
+
+/// Upper bound of the contract address space: 2^251 - 256
+const ADDR_BOUND: Felt =
+    Felt::from_hex_unchecked("0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00");
+
+/// Address of the Universal Deployer Contract (same on mainnet and sepolia)
+const UDC_ADDRESS: Felt =
+    Felt::from_hex_unchecked("0x041a78e741e5af2fec34b695679bc6891742439f7afb8484ecd7766661ad02bf");
+
+/// Account preset: OpenZeppelin account
+pub const ACCOUNT_PRESET_OZ: u32 = 0;
+/// Account preset: Argent X account
+pub const ACCOUNT_PRESET_ARGENT_X: u32 = 1;
+/// Account preset: Braavos account
+pub const ACCOUNT_PRESET_BRAAVOS: u32 = 2;
+
+/// OpenZeppelin Account 0.8.1
+const OZ_ACCOUNT_CLASS_HASH: Felt =
+    Felt::from_hex_unchecked("0x061dac032f228abef9c6626f995015233097ae253a7f72d68552db02f2971b8f");
+
+/// Argent X Account 0.4.0
+const ARGENT_X_ACCOUNT_CLASS_HASH: Felt =
+    Felt::from_hex_unchecked("0x036078334509b514626504edc9fb252328d1a240e4e948bef8d0c08dff45927f");
+
+/// Braavos base account. Braavos deploys this class and replaces it with the
+/// account implementation during `DEPLOY_ACCOUNT`, so the address only
+/// depends on the base class.
+const BRAAVOS_BASE_ACCOUNT_CLASS_HASH: Felt =
+    Felt::from_hex_unchecked("0x013bfe114fb1cf405bfc3a7f8dbe2d91db146c17521d40dcf57e16d6b59fa8e6");
+
+/// Reduce into `[0, 2^251 - 256)`. P < 2 * ADDR_BOUND, so one subtraction suffices.
+fn normalize_address(address: Felt) -> Felt {
+    if address >= ADDR_BOUND {
+        address - ADDR_BOUND
+    } else {
+        address
+    }
+}
+
+pub(crate) fn contract_address(
+    deployer: Felt,
+    salt: Felt,
+    class_hash: Felt,
+    constructor_calldata: &[Felt],
+) -> Felt {
+    normalize_address(pedersen_hash_array(&[
+        Felt::from_bytes_be_slice(b"STARKNET_CONTRACT_ADDRESS"),
+        deployer,
+        salt,
+        class_hash,
+        pedersen_hash_array(constructor_calldata),
+    ]))
+}
+
+pub(crate) fn udc_address(
+    deployer: Felt,
+    salt: Felt,
+    class_hash: Felt,
+    constructor_calldata: &[Felt],
+    unique: bool,
+) -> Felt {
+    if unique {
+        // Origin-dependent: salt is bound to the caller, the UDC is the deployer
+        let unique_salt = pedersen_hash(&deployer, &salt);
+        contract_address(UDC_ADDRESS, unique_salt, class_hash, constructor_calldata)
+    } else {
+        // Origin-independent: anyone deploying with this salt gets the same address
+        contract_address(Felt::ZERO, salt, class_hash, constructor_calldata)
+    }
+}
+
+/// Class hash and constructor calldata of an account preset
+fn account_preset(kind: u32, public_key: Felt) -> Option<(Felt, Vec<Felt>)> {
+    match kind {
+        ACCOUNT_PRESET_OZ => Some((OZ_ACCOUNT_CLASS_HASH, vec![public_key])),
+        // owner: Signer::Starknet(public_key), guardian: Option::None
+        ACCOUNT_PRESET_ARGENT_X => Some((
+            ARGENT_X_ACCOUNT_CLASS_HASH,
+            vec![Felt::ZERO, public_key, Felt::ONE],
+        )),
+        ACCOUNT_PRESET_BRAAVOS => Some((BRAAVOS_BASE_ACCOUNT_CLASS_HASH, vec![public_key])),
+        _ => None,
+    }
+}
+
+/// Compute the address of a contract deployed by `deployer`
+///
+/// Use a zero deployer for `DEPLOY_ACCOUNT` transactions (counterfactual
+/// account addresses). `constructor_calldata` may be null when
+/// `calldata_len == 0`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_contract_address")]
+pub unsafe extern "C" fn starknet_contract_address(
+    deployer: *const FeltBytes,
+    salt: *const FeltBytes,
+    class_hash: *const FeltBytes,
+    constructor_calldata: *const FeltBytes,
+    calldata_len: usize,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(deployer, salt, class_hash, out);
+        let deployer = match felt_from_bytes(&*deployer) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let salt = match felt_from_bytes(&*salt) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let class_hash = match felt_from_bytes(&*class_hash) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let calldata = match felts_from_raw(constructor_calldata, calldata_len) {
+            Some(c) => c,
+            None => return null_pointer("constructor_calldata"),
+        };
+
+        let address = contract_address(deployer, salt, class_hash, &calldata);
+        *out = felt_to_bytes(&address);
+        StarkResult::Success
+    })
+}
+
+/// Predict the address of a contract deployed through the UDC's `deployContract`
+///
+/// `deployer` is the account calling the UDC. With `unique == true` the salt
+/// becomes `pedersen(deployer, salt)` and the UDC acts as deployer; with
+/// `unique == false` the address is computed with a zero deployer and does
+/// not depend on the caller. `calldata` may be null when `len == 0`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_udc_address")]
+pub unsafe extern "C" fn starknet_udc_address(
+    deployer: *const FeltBytes,
+    salt: *const FeltBytes,
+    class_hash: *const FeltBytes,
+    calldata: *const FeltBytes,
+    len: usize,
+    unique: bool,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(deployer, salt, class_hash, out);
+        let deployer = match felt_from_bytes(&*deployer) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let salt = match felt_from_bytes(&*salt) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let class_hash = match felt_from_bytes(&*class_hash) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let calldata = match felts_from_raw(calldata, len) {
+            Some(c) => c,
+            None => return null_pointer("calldata"),
+        };
+
+        let address = udc_address(deployer, salt, class_hash, &calldata, unique);
+        *out = felt_to_bytes(&address);
+        StarkResult::Success
+    })
+}
+
+/// Compute the counterfactual address of a wallet account
+///
+/// `kind` is one of the `ACCOUNT_PRESET_*` constants; the preset supplies the
+/// class hash and constructor calldata for `public_key`, and the account is
+/// deployed with `DEPLOY_ACCOUNT` (zero deployer). Wallets use the public key
+/// as `salt`. Returns `InvalidInput` for an unknown `kind`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_account_address_preset")]
+pub unsafe extern "C" fn starknet_account_address_preset(
+    kind: u32,
+    public_key: *const FeltBytes,
+    salt: *const FeltBytes,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(public_key, salt, out);
+        let public_key = match felt_from_bytes(&*public_key) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let salt = match felt_from_bytes(&*salt) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let (class_hash, calldata) = match account_preset(kind, public_key) {
+            Some(preset) => preset,
+            None => return StarkResult::InvalidInput,
+        };
+
+        let address = contract_address(Felt::ZERO, salt, class_hash, &calldata);
+        *out = felt_to_bytes(&address);
+        StarkResult::Success
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn felt_bytes_from_hex(hex: &str) -> FeltBytes {
+        felt_to_bytes(&Felt::from_hex(hex).unwrap())
+    }
+
+    #[test]
+    fn test_contract_address() {
+        let deployer = [0u8; 32];
+        let salt = felt_bytes_from_hex(
+            "0x0018a7a329d1d85b621350f2b5fc9c64b2e57dfe708525f0aff2c90de1e5b9c8",
+        );
+        let class_hash = felt_bytes_from_hex(
+            "0x0750cd490a7cd1572411169eaa8be292325990d33c5d4733655fe6b926985062",
+        );
+        let calldata = [felt_bytes_from_hex("0x1")];
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = starknet_contract_address(
+                &deployer,
+                &salt,
+                &class_hash,
+                calldata.as_ptr(),
+                calldata.len(),
+                &mut out,
+            );
+            assert_eq!(result, StarkResult::Success);
+        }
+
+        let expected = felt_bytes_from_hex(
+            "0x00da27ef7c3869c3a6cc6a0f7bf07a51c3e590825adba8a51cae27d815839eec",
+        );
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_contract_address_mainnet_deploy_account() {
+        // DEPLOY_ACCOUNT 0x25aa75f07cf15de60ee669a755f82a0487a474403a4f02dca08911d2d6af6ba
+        // in mainnet block 800000, address from its receipt
+        let salt = felt_bytes_from_hex(
+            "0x05a6e17a2506995bb123633a1ef618efaab485b9695fe622fb85361344b6224f",
+        );
+        let class_hash = felt_bytes_from_hex(
+            "0x01c0bb51e2ce73dc007601a1e7725453627254016c28f118251a71bbb0507fcb",
+        );
+        let calldata = [salt, felt_bytes_from_hex("0x0")];
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = starknet_contract_address(
+                &[0u8; 32],
+                &salt,
+                &class_hash,
+                calldata.as_ptr(),
+                calldata.len(),
+                &mut out,
+            );
+            assert_eq!(result, StarkResult::Success);
+        }
+
+        let expected = felt_bytes_from_hex(
+            "0x004b7be95b563d4dbbaa908b30aca61b6a2fb104c39ef6e45ad2f93dc9ebbd5c",
+        );
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_contract_address_null_calldata() {
+        let deployer = [0u8; 32];
+        let salt = felt_bytes_from_hex("0x1");
+        let class_hash = felt_bytes_from_hex("0x2");
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = starknet_contract_address(
+                &deployer,
+                &salt,
+                &class_hash,
+                core::ptr::null(),
+                0,
+                &mut out,
+            );
+            assert_eq!(result, StarkResult::Success);
+
+            let result = starknet_contract_address(
+                &deployer,
+                &salt,
+                &class_hash,
+                core::ptr::null(),
+                1,
+                &mut out,
+            );
+            assert_eq!(result, StarkResult::NullPointer);
+        }
+    }
+
+    #[test]
+    fn test_udc_address_not_unique() {
+        let deployer = felt_bytes_from_hex("0x1");
+        let salt = felt_bytes_from_hex(
+            "0x06df0e9a9842d97ff3f4c6de7494d6e69d0a107a72150f9c53d59515b91ed9cb",
+        );
+        let class_hash = felt_bytes_from_hex(
+            "0x0562fc1d911530d18a86ea3ef4be50018923898d3c573288c5abb9c2344459ed",
+        );
+        let calldata = [felt_bytes_from_hex("0x1234")];
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = starknet_udc_address(
+                &deployer,
+                &salt,
+                &class_hash,
+                calldata.as_ptr(),
+                calldata.len(),
+                false,
+                &mut out,
+            );
+            assert_eq!(result, StarkResult::Success);
+        }
+
+        let expected = felt_bytes_from_hex(
+            "0x0288e5952d2f2f0e897ea0c5401c6e9f584a89eebfb08b5b26f090a8bbf67eb6",
+        );
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_udc_address_unique() {
+        let deployer = felt_bytes_from_hex(
+            "0x00b1461de04c6a1aa3375bdf9b7723a8779c082ffe21311d683a0b15c078b5dc",
+        );
+        let salt = felt_bytes_from_hex(
+            "0x01f65976b95bf17ae1cb04afc9fc1eeee26d3e1aaa1f30aa535bf261e4322ab8",
+        );
+        let class_hash = felt_bytes_from_hex(
+            "0x0562fc1d911530d18a86ea3ef4be50018923898d3c573288c5abb9c2344459ed",
+        );
+        let calldata = [felt_bytes_from_hex("0x1234")];
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = starknet_udc_address(
+                &deployer,
+                &salt,
+                &class_hash,
+                calldata.as_ptr(),
+                calldata.len(),
+                true,
+                &mut out,
+            );
+            assert_eq!(result, StarkResult::Success);
+        }
+
+        let expected = felt_bytes_from_hex(
+            "0x02406943b25942021f213b047c8765e531dddce3b981722f7aeb2ca137e18dbf",
+        );
+        assert_eq!(out, expected);
+    }
+
+    // No counterfactual address deployed from the preset class hashes is
+    // available to this build, so each preset is checked against
+    // `starknet_contract_address` with the documented calldata; that function
+    // is pinned to a mainnet deployment above.
+    #[test]
+    fn test_account_address_preset() {
+        let public_key = felt_bytes_from_hex(
+            "0x033f45f07e1bd1a51b45fc24ec8c8c9908db9e42191be9e169bfcac0c0d99745",
+        );
+        let argent_calldata = [
+            felt_bytes_from_hex("0x0"),
+            public_key,
+            felt_bytes_from_hex("0x1"),
+        ];
+        let cases: [(u32, Felt, &[FeltBytes]); 3] = [
+            (ACCOUNT_PRESET_OZ, OZ_ACCOUNT_CLASS_HASH, &[public_key]),
+            (
+                ACCOUNT_PRESET_ARGENT_X,
+                ARGENT_X_ACCOUNT_CLASS_HASH,
+                &argent_calldata,
+            ),
+            (
+                ACCOUNT_PRESET_BRAAVOS,
+                BRAAVOS_BASE_ACCOUNT_CLASS_HASH,
+                &[public_key],
+            ),
+        ];
+
+        for (kind, class_hash, calldata) in cases {
+            let mut out = [0u8; 32];
+            let mut expected = [0u8; 32];
+            unsafe {
+                let result =
+                    starknet_account_address_preset(kind, &public_key, &public_key, &mut out);
+                assert_eq!(result, StarkResult::Success);
+                starknet_contract_address(
+                    &[0u8; 32],
+                    &public_key,
+                    &felt_to_bytes(&class_hash),
+                    calldata.as_ptr(),
+                    calldata.len(),
+                    &mut expected,
+                );
+            }
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn test_account_address_preset_unknown_kind() {
+        let public_key = felt_bytes_from_hex("0x1");
+        let mut out = [0u8; 32];
+        unsafe {
+            let result = starknet_account_address_preset(3, &public_key, &public_key, &mut out);
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn test_normalize_address() {
+        assert_eq!(normalize_address(ADDR_BOUND), Felt::ZERO);
+        assert_eq!(normalize_address(ADDR_BOUND + Felt::ONE), Felt::ONE);
+        assert_eq!(
+            normalize_address(ADDR_BOUND - Felt::ONE),
+            ADDR_BOUND - Felt::ONE
+        );
+    }
+}
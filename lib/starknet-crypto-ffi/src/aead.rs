@@ -0,0 +1,495 @@
+//! Authenticated encryption (AEAD)
+//!
+//! Ciphertexts are the encrypted data followed by the 16-byte tag, so an
+//! output buffer needs `len + AEAD_TAG_LEN` bytes when encrypting and
+//! `len - AEAD_TAG_LEN` when decrypting. Output buffers follow the usual
+//! capacity convention: `out_len` holds the capacity on input and the
+//! written length on output.
+//!
+//! Two ciphers share these conventions: XChaCha20-Poly1305 (24-byte nonces)
+//! and AES-256-GCM (12-byte nonces) for environments that mandate AES. GCM
+//! nonces are too short to pick at random for many messages under one key;
+//! use a counter.
+//!
+//! Wallet blobs add nonce handling on top of XChaCha20-Poly1305, whose
+//! 24-byte nonces are safe to draw at random:
+//! `version (1) || nonce (24) || ciphertext || tag (16)`, with the version
+//! byte authenticated along with the caller's associated data.
+
+use alloc::vec::Vec;
+
+use aes_gcm::Aes256Gcm;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+use crate::{bytes_from_raw, error::null_pointer, ffi_guard, random, write_bytes_out, StarkResult};
+
+/// Length of the authentication tag appended to every ciphertext
+pub const AEAD_TAG_LEN: usize = 16;
+/// XChaCha20-Poly1305 nonce length
+pub const XCHACHA20POLY1305_NONCE_LEN: usize = 24;
+/// AES-256-GCM nonce length
+pub const AES256GCM_NONCE_LEN: usize = 12;
+
+const WALLET_BLOB_VERSION: u8 = 1;
+/// Bytes a wallet blob adds to its plaintext (version, nonce and tag)
+pub const WALLET_BLOB_OVERHEAD: usize = 1 + XCHACHA20POLY1305_NONCE_LEN + AEAD_TAG_LEN;
+
+pub(crate) fn xchacha_encrypt(key: &[u8; 32], nonce: &[u8; 24], aad: &[u8], msg: &[u8]) -> Vec<u8> {
+    XChaCha20Poly1305::new(key.into())
+        .encrypt(XNonce::from_slice(nonce), Payload { msg, aad })
+        .expect("plaintext length is within the XChaCha20 limit")
+}
+
+pub(crate) fn xchacha_decrypt(
+    key: &[u8; 32],
+    nonce: &[u8; 24],
+    aad: &[u8],
+    msg: &[u8],
+) -> Option<Vec<u8>> {
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(XNonce::from_slice(nonce), Payload { msg, aad })
+        .ok()
+}
+
+fn aes_gcm_encrypt(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], msg: &[u8]) -> Vec<u8> {
+    Aes256Gcm::new(key.into())
+        .encrypt(nonce.into(), Payload { msg, aad })
+        .expect("plaintext length is within the GCM limit")
+}
+
+fn aes_gcm_decrypt(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], msg: &[u8]) -> Option<Vec<u8>> {
+    Aes256Gcm::new(key.into())
+        .decrypt(nonce.into(), Payload { msg, aad })
+        .ok()
+}
+
+/// Associated data of a wallet blob: the version byte, then the caller's
+fn wallet_blob_aad(aad: &[u8]) -> Vec<u8> {
+    let mut blob_aad = Vec::with_capacity(1 + aad.len());
+    blob_aad.push(WALLET_BLOB_VERSION);
+    blob_aad.extend_from_slice(aad);
+    blob_aad
+}
+
+/// Encrypt with XChaCha20-Poly1305 (writes ciphertext || tag)
+///
+/// `aad` may be null when `aad_len` is 0. Never reuse a nonce with the same
+/// key; `wallet_blob_seal` picks random nonces for you.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_aead_xchacha20poly1305_encrypt")]
+pub unsafe extern "C" fn aead_xchacha20poly1305_encrypt(
+    key: *const [u8; 32],
+    nonce: *const [u8; 24],
+    aad: *const u8,
+    aad_len: usize,
+    data: *const u8,
+    len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(key, nonce, out_len);
+        let aad = match bytes_from_raw(aad, aad_len) {
+            Some(a) => a,
+            None => return null_pointer("aad"),
+        };
+        let data = match bytes_from_raw(data, len) {
+            Some(d) => d,
+            None => return null_pointer("data"),
+        };
+
+        let ciphertext = xchacha_encrypt(&*key, &*nonce, aad, data);
+        write_bytes_out(&ciphertext, out, out_len)
+    })
+}
+
+/// Decrypt ciphertext || tag produced by `aead_xchacha20poly1305_encrypt`
+///
+/// Returns `DecryptionFailed` if the tag does not verify (wrong key, nonce
+/// or associated data, or tampered ciphertext); nothing is written then.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_aead_xchacha20poly1305_decrypt")]
+pub unsafe extern "C" fn aead_xchacha20poly1305_decrypt(
+    key: *const [u8; 32],
+    nonce: *const [u8; 24],
+    aad: *const u8,
+    aad_len: usize,
+    data: *const u8,
+    len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(key, nonce, out_len);
+        let aad = match bytes_from_raw(aad, aad_len) {
+            Some(a) => a,
+            None => return null_pointer("aad"),
+        };
+        let data = match bytes_from_raw(data, len) {
+            Some(d) => d,
+            None => return null_pointer("data"),
+        };
+
+        match xchacha_decrypt(&*key, &*nonce, aad, data) {
+            Some(plaintext) => write_bytes_out(&plaintext, out, out_len),
+            None => StarkResult::DecryptionFailed,
+        }
+    })
+}
+
+/// Encrypt with AES-256-GCM (writes ciphertext || tag)
+///
+/// `aad` may be null when `aad_len` is 0. Never reuse a nonce with the same
+/// key.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_aes256gcm_encrypt")]
+pub unsafe extern "C" fn aes256gcm_encrypt(
+    key: *const [u8; 32],
+    nonce: *const [u8; 12],
+    aad: *const u8,
+    aad_len: usize,
+    data: *const u8,
+    len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(key, nonce, out_len);
+        let aad = match bytes_from_raw(aad, aad_len) {
+            Some(a) => a,
+            None => return null_pointer("aad"),
+        };
+        let data = match bytes_from_raw(data, len) {
+            Some(d) => d,
+            None => return null_pointer("data"),
+        };
+
+        let ciphertext = aes_gcm_encrypt(&*key, &*nonce, aad, data);
+        write_bytes_out(&ciphertext, out, out_len)
+    })
+}
+
+/// Decrypt ciphertext || tag produced by `aes256gcm_encrypt`
+///
+/// Returns `DecryptionFailed` if the tag does not verify; nothing is written
+/// then.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_aes256gcm_decrypt")]
+pub unsafe extern "C" fn aes256gcm_decrypt(
+    key: *const [u8; 32],
+    nonce: *const [u8; 12],
+    aad: *const u8,
+    aad_len: usize,
+    data: *const u8,
+    len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(key, nonce, out_len);
+        let aad = match bytes_from_raw(aad, aad_len) {
+            Some(a) => a,
+            None => return null_pointer("aad"),
+        };
+        let data = match bytes_from_raw(data, len) {
+            Some(d) => d,
+            None => return null_pointer("data"),
+        };
+
+        match aes_gcm_decrypt(&*key, &*nonce, aad, data) {
+            Some(plaintext) => write_bytes_out(&plaintext, out, out_len),
+            None => StarkResult::DecryptionFailed,
+        }
+    })
+}
+
+/// Seal data into a wallet blob under a 32-byte key
+///
+/// The output is `WALLET_BLOB_OVERHEAD` bytes longer than the data. `aad`
+/// (null when `aad_len` is 0) must be passed again to `wallet_blob_open`.
+/// Returns `RngFailed` if the OS random number generator is unavailable.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_wallet_blob_seal")]
+pub unsafe extern "C" fn wallet_blob_seal(
+    key: *const [u8; 32],
+    aad: *const u8,
+    aad_len: usize,
+    data: *const u8,
+    len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(key, out_len);
+        let aad = match bytes_from_raw(aad, aad_len) {
+            Some(a) => a,
+            None => return null_pointer("aad"),
+        };
+        let data = match bytes_from_raw(data, len) {
+            Some(d) => d,
+            None => return null_pointer("data"),
+        };
+        let mut nonce = [0u8; XCHACHA20POLY1305_NONCE_LEN];
+        if random::fill_random(&mut nonce).is_err() {
+            return StarkResult::RngFailed;
+        }
+
+        let mut blob = Vec::with_capacity(data.len() + WALLET_BLOB_OVERHEAD);
+        blob.push(WALLET_BLOB_VERSION);
+        blob.extend_from_slice(&nonce);
+        blob.extend(xchacha_encrypt(&*key, &nonce, &wallet_blob_aad(aad), data));
+        write_bytes_out(&blob, out, out_len)
+    })
+}
+
+/// Open a wallet blob produced by `wallet_blob_seal`
+///
+/// Returns `InvalidInput` for a truncated blob or unknown version and
+/// `DecryptionFailed` if authentication fails.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_wallet_blob_open")]
+pub unsafe extern "C" fn wallet_blob_open(
+    key: *const [u8; 32],
+    aad: *const u8,
+    aad_len: usize,
+    blob: *const u8,
+    blob_len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(key, out_len);
+        let aad = match bytes_from_raw(aad, aad_len) {
+            Some(a) => a,
+            None => return null_pointer("aad"),
+        };
+        let blob = match bytes_from_raw(blob, blob_len) {
+            Some(b) if b.len() >= WALLET_BLOB_OVERHEAD && b[0] == WALLET_BLOB_VERSION => b,
+            _ => return StarkResult::InvalidInput,
+        };
+
+        let (nonce, ciphertext) = blob[1..].split_at(XCHACHA20POLY1305_NONCE_LEN);
+        let nonce: &[u8; 24] = nonce.try_into().unwrap();
+        match xchacha_decrypt(&*key, nonce, &wallet_blob_aad(aad), ciphertext) {
+            Some(plaintext) => write_bytes_out(&plaintext, out, out_len),
+            None => StarkResult::DecryptionFailed,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // draft-irtf-cfrg-xchacha-03, A.3.1
+    const PLAINTEXT: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+    const AAD: &str = "50515253c0c1c2c3c4c5c6c7";
+    const NONCE: &str = "404142434445464748494a4b4c4d4e4f5051525354555657";
+    const CIPHERTEXT: &str = "bd6d179d3e83d43b9576579493c0e939572a1700252bfaccbed2902c21396cbb731c7f1b0b4aa6440bf3a82f4eda7e39ae64c6708c54c216cb96b72e1213b4522f8c9ba40db5d945b11b69b982c1bb9e3f3fac2bc369488f76b2383565d3fff921f9664c97637da9768812f615c68b13b52ec0875924c1c7987947deafd8780acf49";
+
+    fn key() -> [u8; 32] {
+        core::array::from_fn(|i| 0x80 + i as u8)
+    }
+
+    #[test]
+    fn test_xchacha20poly1305_vector() {
+        let nonce: [u8; 24] = hex(NONCE).try_into().unwrap();
+        let aad = hex(AAD);
+        let mut ciphertext = vec![0u8; PLAINTEXT.len() + AEAD_TAG_LEN];
+        let mut plaintext = vec![0u8; PLAINTEXT.len()];
+
+        unsafe {
+            let mut len = ciphertext.len();
+            let result = aead_xchacha20poly1305_encrypt(
+                &key(),
+                &nonce,
+                aad.as_ptr(),
+                aad.len(),
+                PLAINTEXT.as_ptr(),
+                PLAINTEXT.len(),
+                ciphertext.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(ciphertext, hex(CIPHERTEXT));
+
+            let mut len = plaintext.len();
+            let result = aead_xchacha20poly1305_decrypt(
+                &key(),
+                &nonce,
+                aad.as_ptr(),
+                aad.len(),
+                ciphertext.as_ptr(),
+                ciphertext.len(),
+                plaintext.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(plaintext, PLAINTEXT);
+
+            ciphertext[0] ^= 1;
+            let mut len = plaintext.len();
+            let result = aead_xchacha20poly1305_decrypt(
+                &key(),
+                &nonce,
+                aad.as_ptr(),
+                aad.len(),
+                ciphertext.as_ptr(),
+                ciphertext.len(),
+                plaintext.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::DecryptionFailed);
+        }
+    }
+
+    #[test]
+    fn test_aes256gcm_vector() {
+        // GCM spec (McGrew, Viega), test case 16
+        let key: [u8; 32] = hex("feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308")
+            .try_into()
+            .unwrap();
+        let nonce: [u8; 12] = hex("cafebabefacedbaddecaf888").try_into().unwrap();
+        let aad = hex("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let plaintext = hex("d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39");
+        let expected = hex("522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f66276fc6ece0f4e1768cddf8853bb2d551b");
+        let mut ciphertext = vec![0u8; plaintext.len() + AEAD_TAG_LEN];
+        let mut decrypted = vec![0u8; plaintext.len()];
+
+        unsafe {
+            let mut len = ciphertext.len();
+            let result = aes256gcm_encrypt(
+                &key,
+                &nonce,
+                aad.as_ptr(),
+                aad.len(),
+                plaintext.as_ptr(),
+                plaintext.len(),
+                ciphertext.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(ciphertext, expected);
+
+            let mut len = decrypted.len();
+            let result = aes256gcm_decrypt(
+                &key,
+                &nonce,
+                aad.as_ptr(),
+                aad.len(),
+                ciphertext.as_ptr(),
+                ciphertext.len(),
+                decrypted.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(decrypted, plaintext);
+
+            let mut len = decrypted.len();
+            let result = aes256gcm_decrypt(
+                &key,
+                &nonce,
+                core::ptr::null(),
+                0,
+                ciphertext.as_ptr(),
+                ciphertext.len(),
+                decrypted.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::DecryptionFailed);
+        }
+    }
+
+    #[test]
+    fn test_wallet_blob_roundtrip() {
+        let aad = b"account:0";
+        let data = b"wallet secrets";
+        let mut blob = [0u8; 14 + WALLET_BLOB_OVERHEAD];
+        let mut opened = [0u8; 14];
+
+        unsafe {
+            let mut blob_len = blob.len();
+            let result = wallet_blob_seal(
+                &key(),
+                aad.as_ptr(),
+                aad.len(),
+                data.as_ptr(),
+                data.len(),
+                blob.as_mut_ptr(),
+                &mut blob_len,
+            );
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(blob_len, blob.len());
+            assert_eq!(blob[0], WALLET_BLOB_VERSION);
+
+            let mut len = opened.len();
+            let result = wallet_blob_open(
+                &key(),
+                aad.as_ptr(),
+                aad.len(),
+                blob.as_ptr(),
+                blob.len(),
+                opened.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(&opened, data);
+
+            // Different associated data
+            let mut len = opened.len();
+            let result = wallet_blob_open(
+                &key(),
+                core::ptr::null(),
+                0,
+                blob.as_ptr(),
+                blob.len(),
+                opened.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::DecryptionFailed);
+
+            // Truncated blob
+            let mut len = opened.len();
+            let result = wallet_blob_open(
+                &key(),
+                aad.as_ptr(),
+                aad.len(),
+                blob.as_ptr(),
+                WALLET_BLOB_OVERHEAD - 1,
+                opened.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn test_aead_output_too_small() {
+        let nonce = [0u8; 24];
+        let mut out = [0u8; 8];
+        let mut len = out.len();
+
+        unsafe {
+            let result = aead_xchacha20poly1305_encrypt(
+                &key(),
+                &nonce,
+                core::ptr::null(),
+                0,
+                PLAINTEXT.as_ptr(),
+                PLAINTEXT.len(),
+                out.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::BufferTooSmall);
+        }
+        assert_eq!(len, PLAINTEXT.len() + AEAD_TAG_LEN);
+    }
+}
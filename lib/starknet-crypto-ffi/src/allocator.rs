@@ -0,0 +1,108 @@
+//! Heap supplied by the host (`no_std` builds only)
+//!
+//! Without `std` the library has no allocator of its own: the host passes
+//! its `malloc` and `free` to `stark_set_allocator` before any other call,
+//! and every allocation the library makes goes through them. Until then an
+//! allocation fails, which panics (see `ffi_guard`).
+//!
+//! `malloc` must return memory aligned to at least `ALLOCATOR_ALIGN` bytes (or
+//! null when out of memory). Larger alignments (the page-aligned pages of a
+//! `SecretBuffer`) are obtained by over-allocating: the block returned by
+//! `malloc` is then recorded in the word just before the aligned pointer.
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::Cell;
+use core::ffi::c_void;
+use core::mem::size_of;
+use core::ptr;
+
+use crate::{error::invalid_input, ffi_guard, StarkResult};
+
+/// Alignment that every block returned by the host's `malloc` must have
+pub const ALLOCATOR_ALIGN: usize = 8;
+
+/// Allocate `size` bytes (never 0), aligned to `ALLOCATOR_ALIGN`; null on failure
+pub type StarkMallocFn = Option<unsafe extern "C" fn(size: usize) -> *mut c_void>;
+
+/// Release a block returned by the `StarkMallocFn`
+pub type StarkFreeFn = Option<unsafe extern "C" fn(ptr: *mut c_void)>;
+
+#[derive(Clone, Copy)]
+struct Host {
+    malloc: unsafe extern "C" fn(usize) -> *mut c_void,
+    free: unsafe extern "C" fn(*mut c_void),
+}
+
+struct HostAllocator(Cell<Option<Host>>);
+
+// Calls are never concurrent without `std` (see `sync`)
+unsafe impl Sync for HostAllocator {}
+
+#[global_allocator]
+static ALLOCATOR: HostAllocator = HostAllocator(Cell::new(None));
+
+unsafe impl GlobalAlloc for HostAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let host = match self.0.get() {
+            Some(h) => h,
+            None => return ptr::null_mut(),
+        };
+        if layout.align() <= ALLOCATOR_ALIGN {
+            return (host.malloc)(layout.size()).cast();
+        }
+        let size = match layout
+            .size()
+            .checked_add(layout.align() + size_of::<usize>())
+        {
+            Some(s) => s,
+            None => return ptr::null_mut(),
+        };
+        let block = (host.malloc)(size).cast::<u8>();
+        if block.is_null() {
+            return block;
+        }
+        let offset = block.add(size_of::<usize>()).align_offset(layout.align());
+        let aligned = block.add(size_of::<usize>() + offset);
+        aligned.cast::<*mut u8>().sub(1).write_unaligned(block);
+        aligned
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // Only reachable through an allocation, so the host is set
+        let host = match self.0.get() {
+            Some(h) => h,
+            None => return,
+        };
+        let block = if layout.align() <= ALLOCATOR_ALIGN {
+            ptr
+        } else {
+            ptr.cast::<*mut u8>().sub(1).read_unaligned()
+        };
+        (host.free)(block.cast());
+    }
+}
+
+/// Give the library its heap: `malloc_fn` and `free_fn` serve every
+/// allocation from now on
+///
+/// Must be called once, before any other library function; a second call
+/// is `InvalidInput` and keeps the first allocator (blocks already handed
+/// out must go back to the `free` that matches their `malloc`). Only
+/// exported by `no_std` builds.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_set_allocator")]
+pub unsafe extern "C" fn stark_set_allocator(
+    malloc_fn: StarkMallocFn,
+    free_fn: StarkFreeFn,
+) -> StarkResult {
+    if ALLOCATOR.0.get().is_some() {
+        return ffi_guard(|| invalid_input("an allocator is already set"));
+    }
+    // Not in `ffi_guard`: recording the detail would need the missing heap
+    let (malloc, free) = match (malloc_fn, free_fn) {
+        (Some(malloc), Some(free)) => (malloc, free),
+        _ => return StarkResult::NullPointer,
+    };
+    ALLOCATOR.0.set(Some(Host { malloc, free }));
+    StarkResult::Success
+}
@@ -0,0 +1,144 @@
+//! Scratch memory reused across calls
+//!
+//! Functions that need a temporary felt array (batch hashing, Merkle trees)
+//! allocate it on every call. At high request rates the host can instead
+//! create a `StarkArena` once and pass it to the `_arena` variants and to
+//! `starknet_merkle_root`: they work in the arena's buffer, which keeps its
+//! capacity between calls, so a steady workload stops allocating after the
+//! first calls. Arena arguments are optional: null allocates as usual.
+//!
+//! Calls sharing an arena take turns on its buffer, so give each thread its
+//! own arena. `stark_arena_shrink` releases the memory an arena holds.
+
+#![cfg_attr(not(any(feature = "hashing", feature = "merkle")), allow(dead_code))]
+
+use alloc::vec::Vec;
+
+use starknet_crypto::Felt;
+
+use crate::handle::StarkHandle;
+use crate::sync::Mutex;
+use crate::{ffi_guard, ffi_guard_void, StarkResult};
+
+/// Reusable scratch buffer
+pub struct StarkArena {
+    felts: Mutex<Vec<Felt>>,
+}
+
+impl StarkHandle for StarkArena {
+    const NAME: &'static str = "StarkArena";
+}
+
+/// Borrow an optional arena argument (null: no arena)
+pub(crate) unsafe fn optional_arena<'a>(
+    ptr: *const StarkArena,
+) -> Result<Option<&'a StarkArena>, StarkResult> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    StarkArena::from_handle(ptr).map(Some)
+}
+
+/// Run `f` with an empty felt buffer: the arena's if there is one, else a
+/// new one
+pub(crate) fn with_felts<R>(arena: Option<&StarkArena>, f: impl FnOnce(&mut Vec<Felt>) -> R) -> R {
+    match arena {
+        Some(arena) => {
+            let mut felts = arena.felts.lock();
+            felts.clear();
+            f(&mut felts)
+        }
+        None => f(&mut Vec::new()),
+    }
+}
+
+/// Create an arena with room for `capacity` felts (0: grow on first use)
+///
+/// Writes an owned handle to `out_arena`; release it with
+/// `stark_arena_free`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_arena_new")]
+pub unsafe extern "C" fn stark_arena_new(
+    capacity: usize,
+    out_arena: *mut *mut StarkArena,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_arena);
+        *out_arena = StarkArena {
+            felts: Mutex::new(Vec::with_capacity(capacity)),
+        }
+        .into_handle();
+        StarkResult::Success
+    })
+}
+
+/// Number of felts the arena holds room for (0 for null)
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_stark_arena_capacity"
+)]
+pub unsafe extern "C" fn stark_arena_capacity(arena: *const StarkArena) -> usize {
+    let mut capacity = 0;
+    ffi_guard(|| match optional_arena(arena) {
+        Ok(arena) => {
+            capacity = arena.map_or(0, |arena| arena.felts.lock().capacity());
+            StarkResult::Success
+        }
+        Err(e) => e,
+    });
+    capacity
+}
+
+/// Release the memory the arena holds (it grows again on next use)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_arena_shrink")]
+pub unsafe extern "C" fn stark_arena_shrink(arena: *const StarkArena) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(arena);
+        match StarkArena::from_handle(arena) {
+            Ok(arena) => *arena.felts.lock() = Vec::new(),
+            Err(e) => return e,
+        }
+        StarkResult::Success
+    })
+}
+
+/// Free an arena (null is a no-op)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_arena_free")]
+pub unsafe extern "C" fn stark_arena_free(arena: *mut StarkArena) {
+    ffi_guard_void(|| StarkArena::free_handle(arena))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arena_reuses_its_buffer() {
+        let mut arena = core::ptr::null_mut();
+        unsafe {
+            assert_eq!(stark_arena_new(4, &mut arena), StarkResult::Success);
+            assert!(stark_arena_capacity(arena) >= 4);
+
+            let borrowed = optional_arena(arena).unwrap();
+            let first = with_felts(borrowed, |felts| {
+                felts.extend((0..100u64).map(Felt::from));
+                felts.as_ptr()
+            });
+            let second = with_felts(borrowed, |felts| {
+                assert!(felts.is_empty());
+                felts.extend((0..100u64).map(Felt::from));
+                felts.as_ptr()
+            });
+            assert_eq!(first, second);
+            assert!(stark_arena_capacity(arena) >= 100);
+
+            assert_eq!(stark_arena_shrink(arena), StarkResult::Success);
+            assert_eq!(stark_arena_capacity(arena), 0);
+            assert_eq!(stark_arena_capacity(core::ptr::null()), 0);
+            stark_arena_free(arena);
+        }
+    }
+}
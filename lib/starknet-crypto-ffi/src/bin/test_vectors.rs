@@ -0,0 +1,12 @@
+//! Write the library's JSON test vectors to stdout (see `test_vectors`)
+//!
+//! `cargo run --features test-vectors --bin kundera-test-vectors > vectors.json`
+
+use std::io::Write;
+
+fn main() {
+    let vectors = starknet_crypto_ffi::test_vectors::vectors();
+    let json = serde_json::to_string_pretty(&vectors).expect("vectors serialize");
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{json}").expect("cannot write to stdout");
+}
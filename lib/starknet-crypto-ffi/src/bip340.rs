@@ -0,0 +1,197 @@
+//! BIP-340 Schnorr signatures on secp256k1 (Bitcoin Taproot)
+//!
+//! Public keys are 32-byte x-only keys and signatures are 64 bytes
+//! (`r || s`). Challenges and nonces use the BIP-340 tagged hashes. Messages
+//! are signed as-is; Taproot sighashes are already 32 bytes.
+//!
+//! Private keys are the same 32-byte scalars as in the secp256k1 module; the
+//! signer negates the key internally when its point has an odd y.
+
+use k256::schnorr::{Signature, SigningKey, VerifyingKey};
+
+use crate::{bytes_from_raw, error::null_pointer, ffi_guard, random, StarkResult};
+
+/// Derive the x-only public key from a private key
+///
+/// Returns `InvalidPrivateKey` unless 0 < key < n.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bip340_get_public_key")]
+pub unsafe extern "C" fn bip340_get_public_key(
+    private_key: *const [u8; 32],
+    out: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(private_key, out);
+        let key = match SigningKey::from_bytes(&*private_key) {
+            Ok(k) => k,
+            Err(_) => return StarkResult::InvalidPrivateKey,
+        };
+
+        *out = key.verifying_key().to_bytes().into();
+        StarkResult::Success
+    })
+}
+
+/// Sign a message (returns the 64-byte signature r || s)
+///
+/// `aux_rand` is the 32 bytes of auxiliary randomness mixed into the nonce.
+/// Pass null to draw it from the OS random number generator (`RngFailed` if
+/// unavailable), as BIP-340 recommends.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bip340_sign")]
+pub unsafe extern "C" fn bip340_sign(
+    private_key: *const [u8; 32],
+    msg: *const u8,
+    len: usize,
+    aux_rand: *const [u8; 32],
+    out: *mut [u8; 64],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(private_key, out);
+        let key = match SigningKey::from_bytes(&*private_key) {
+            Ok(k) => k,
+            Err(_) => return StarkResult::InvalidPrivateKey,
+        };
+        let msg = match bytes_from_raw(msg, len) {
+            Some(m) => m,
+            None => return null_pointer("msg"),
+        };
+        let aux_rand = if aux_rand.is_null() {
+            let mut bytes = [0u8; 32];
+            if random::fill_random(&mut bytes).is_err() {
+                return StarkResult::RngFailed;
+            }
+            bytes
+        } else {
+            *aux_rand
+        };
+
+        match key.sign_raw(msg, &aux_rand) {
+            Ok(signature) => {
+                *out = signature.to_bytes();
+                StarkResult::Success
+            }
+            Err(_) => StarkResult::InvalidInput,
+        }
+    })
+}
+
+/// Verify a signature against an x-only public key
+///
+/// Returns `InvalidInput` if the public key is not the x coordinate of a
+/// curve point or the signature encoding is out of range.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bip340_verify")]
+pub unsafe extern "C" fn bip340_verify(
+    public_key: *const [u8; 32],
+    msg: *const u8,
+    len: usize,
+    signature: *const [u8; 64],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(public_key, signature);
+        let key = match VerifyingKey::from_bytes(&*public_key) {
+            Ok(k) => k,
+            Err(_) => return StarkResult::InvalidInput,
+        };
+        let signature: [u8; 64] = *signature;
+        let signature = match Signature::try_from(&signature[..]) {
+            Ok(sig) => sig,
+            Err(_) => return StarkResult::InvalidInput,
+        };
+        let msg = match bytes_from_raw(msg, len) {
+            Some(m) => m,
+            None => return null_pointer("msg"),
+        };
+
+        match key.verify_raw(msg, &signature) {
+            Ok(()) => StarkResult::Success,
+            Err(_) => StarkResult::InvalidSignature,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex<const N: usize>(s: &str) -> [u8; N] {
+        let mut out = [0u8; N];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    // BIP-340 test-vectors.csv, index 1
+    const PRIVATE_KEY: &str = "b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef";
+    const PUBLIC_KEY: &str = "dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659";
+    const AUX_RAND: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+    const MESSAGE: &str = "243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89";
+    const SIGNATURE: &str = "6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de33418906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a";
+
+    #[test]
+    fn test_bip340_sign_vector() {
+        let msg = hex::<32>(MESSAGE);
+        let mut public_key = [0u8; 32];
+        let mut signature = [0u8; 64];
+
+        unsafe {
+            let result = bip340_get_public_key(&hex(PRIVATE_KEY), &mut public_key);
+            assert_eq!(result, StarkResult::Success);
+            let result = bip340_sign(
+                &hex(PRIVATE_KEY),
+                msg.as_ptr(),
+                msg.len(),
+                &hex(AUX_RAND),
+                &mut signature,
+            );
+            assert_eq!(result, StarkResult::Success);
+        }
+        assert_eq!(public_key, hex::<32>(PUBLIC_KEY));
+        assert_eq!(signature, hex::<64>(SIGNATURE));
+    }
+
+    #[test]
+    fn test_bip340_verify() {
+        let public_key = hex::<32>(PUBLIC_KEY);
+        let mut msg = hex::<32>(MESSAGE);
+        let signature = hex::<64>(SIGNATURE);
+
+        unsafe {
+            let result = bip340_verify(&public_key, msg.as_ptr(), msg.len(), &signature);
+            assert_eq!(result, StarkResult::Success);
+
+            msg[0] ^= 1;
+            let result = bip340_verify(&public_key, msg.as_ptr(), msg.len(), &signature);
+            assert_eq!(result, StarkResult::InvalidSignature);
+
+            // BIP-340 test vector 5: public key not on the curve
+            let off_curve =
+                hex::<32>("eefdea4cdb677750a420fee807eacf21eb9898ae79b9768766e4faa04a2d4a34");
+            let result = bip340_verify(&off_curve, msg.as_ptr(), msg.len(), &signature);
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn test_bip340_sign_random_aux() {
+        let private_key = hex::<32>(PRIVATE_KEY);
+        let msg = b"taproot";
+        let mut signature = [0u8; 64];
+
+        unsafe {
+            let result = bip340_sign(
+                &private_key,
+                msg.as_ptr(),
+                msg.len(),
+                core::ptr::null(),
+                &mut signature,
+            );
+            assert_eq!(result, StarkResult::Success);
+
+            let result = bip340_verify(&hex(PUBLIC_KEY), msg.as_ptr(), msg.len(), &signature);
+            assert_eq!(result, StarkResult::Success);
+        }
+    }
+}
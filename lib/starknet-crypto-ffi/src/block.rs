@@ -0,0 +1,330 @@
+//! Starknet block hash (0.13.2+ formula)
+//!
+//! Mirrors the sequencer's `calculate_block_hash`: a Poseidon hash chain over
+//! the header fields, the packed `concat_counts` word and the gas prices.
+//! Starting with 0.13.4 the gas prices are folded into a dedicated
+//! `STARKNET_GAS_PRICES0` hash and the chain is tagged `STARKNET_BLOCK_HASH1`.
+
+use alloc::vec;
+
+use starknet_crypto::{poseidon_hash_many, Felt};
+
+use crate::{felt_from_bytes, felt_to_bytes, ffi_guard, FeltBytes, StarkResult};
+
+/// L1 data availability mode: state diffs posted as calldata
+pub const L1_DA_MODE_CALLDATA: u8 = 0;
+/// L1 data availability mode: state diffs posted as EIP-4844 blobs
+pub const L1_DA_MODE_BLOB: u8 = 1;
+
+/// Header fields committed to by the block hash.
+///
+/// Gas prices are big-endian felts (they are u128 on-chain). `starknet_version`
+/// is the version string encoded as a Cairo short string (e.g. "0.13.4").
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct StarknetBlockHeader {
+    pub block_number: u64,
+    pub state_root: FeltBytes,
+    pub sequencer_address: FeltBytes,
+    pub timestamp: u64,
+    pub transaction_count: u64,
+    pub event_count: u64,
+    pub state_diff_length: u64,
+    pub l1_da_mode: u8,
+    pub state_diff_commitment: FeltBytes,
+    pub transaction_commitment: FeltBytes,
+    pub event_commitment: FeltBytes,
+    pub receipt_commitment: FeltBytes,
+    pub l1_gas_price_wei: FeltBytes,
+    pub l1_gas_price_fri: FeltBytes,
+    pub l1_data_gas_price_wei: FeltBytes,
+    pub l1_data_gas_price_fri: FeltBytes,
+    pub l2_gas_price_wei: FeltBytes,
+    pub l2_gas_price_fri: FeltBytes,
+    pub starknet_version: FeltBytes,
+    pub parent_block_hash: FeltBytes,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum BlockHashVersion {
+    V0_13_2,
+    V0_13_4,
+}
+
+/// Parse a short-string encoded version ("0.13.2", "0.13.4.1", ...) and map it
+/// to the block hash formula it uses. Versions before 0.13.2 are rejected.
+fn block_hash_version(version: &Felt) -> Option<BlockHashVersion> {
+    let bytes = version.to_bytes_be();
+    let start = bytes.iter().position(|&b| b != 0)?;
+    let text = core::str::from_utf8(&bytes[start..]).ok()?;
+
+    let mut parts = [0u64; 4];
+    let mut count = 0;
+    for part in text.split('.') {
+        if count == parts.len() {
+            return None;
+        }
+        parts[count] = part.parse().ok()?;
+        count += 1;
+    }
+    if count < 3 {
+        return None;
+    }
+
+    let triple = (parts[0], parts[1], parts[2]);
+    if triple < (0, 13, 2) {
+        None
+    } else if triple < (0, 13, 4) {
+        Some(BlockHashVersion::V0_13_2)
+    } else {
+        Some(BlockHashVersion::V0_13_4)
+    }
+}
+
+/// Pack the transaction/event/state-diff counts and the DA mode into one felt:
+/// `tx_count (64) || event_count (64) || state_diff_length (64) || da_byte || 0 (56)`
+fn concat_counts(
+    transaction_count: u64,
+    event_count: u64,
+    state_diff_length: u64,
+    l1_da_mode: u8,
+) -> Felt {
+    let mut bytes = [0u8; 32];
+    bytes[0..8].copy_from_slice(&transaction_count.to_be_bytes());
+    bytes[8..16].copy_from_slice(&event_count.to_be_bytes());
+    bytes[16..24].copy_from_slice(&state_diff_length.to_be_bytes());
+    if l1_da_mode == L1_DA_MODE_BLOB {
+        bytes[24] = 0b1000_0000;
+    }
+    Felt::from_bytes_be(&bytes)
+}
+
+fn block_hash(header: &StarknetBlockHeader) -> Option<Felt> {
+    if header.l1_da_mode != L1_DA_MODE_CALLDATA && header.l1_da_mode != L1_DA_MODE_BLOB {
+        return None;
+    }
+    let starknet_version = felt_from_bytes(&header.starknet_version)?;
+    let version = block_hash_version(&starknet_version)?;
+
+    let tag: &[u8] = match version {
+        BlockHashVersion::V0_13_2 => b"STARKNET_BLOCK_HASH0",
+        BlockHashVersion::V0_13_4 => b"STARKNET_BLOCK_HASH1",
+    };
+
+    let mut chain = vec![
+        Felt::from_bytes_be_slice(tag),
+        Felt::from(header.block_number),
+        felt_from_bytes(&header.state_root)?,
+        felt_from_bytes(&header.sequencer_address)?,
+        Felt::from(header.timestamp),
+        concat_counts(
+            header.transaction_count,
+            header.event_count,
+            header.state_diff_length,
+            header.l1_da_mode,
+        ),
+        felt_from_bytes(&header.state_diff_commitment)?,
+        felt_from_bytes(&header.transaction_commitment)?,
+        felt_from_bytes(&header.event_commitment)?,
+        felt_from_bytes(&header.receipt_commitment)?,
+    ];
+
+    let l1_prices = [
+        felt_from_bytes(&header.l1_gas_price_wei)?,
+        felt_from_bytes(&header.l1_gas_price_fri)?,
+        felt_from_bytes(&header.l1_data_gas_price_wei)?,
+        felt_from_bytes(&header.l1_data_gas_price_fri)?,
+    ];
+    match version {
+        BlockHashVersion::V0_13_2 => chain.extend_from_slice(&l1_prices),
+        BlockHashVersion::V0_13_4 => {
+            let mut prices = vec![Felt::from_bytes_be_slice(b"STARKNET_GAS_PRICES0")];
+            prices.extend_from_slice(&l1_prices);
+            prices.push(felt_from_bytes(&header.l2_gas_price_wei)?);
+            prices.push(felt_from_bytes(&header.l2_gas_price_fri)?);
+            chain.push(poseidon_hash_many(&prices));
+        }
+    }
+
+    chain.push(starknet_version);
+    chain.push(Felt::ZERO);
+    chain.push(felt_from_bytes(&header.parent_block_hash)?);
+
+    Some(poseidon_hash_many(&chain))
+}
+
+/// Compute the hash of a Starknet block header (0.13.2+ formula)
+///
+/// The formula variant is selected from `header.starknet_version`. Returns
+/// `InvalidInput` for versions before 0.13.2, a malformed version string or
+/// an unknown `l1_da_mode`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_block_hash")]
+pub unsafe extern "C" fn starknet_block_hash(
+    header: *const StarknetBlockHeader,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| match block_hash(&*header) {
+        Some(hash) => {
+            *out = felt_to_bytes(&hash);
+            StarkResult::Success
+        }
+        None => StarkResult::InvalidInput,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn felt_bytes_from_u64(n: u64) -> FeltBytes {
+        felt_to_bytes(&Felt::from(n))
+    }
+
+    fn short_string(s: &str) -> FeltBytes {
+        felt_to_bytes(&Felt::from_bytes_be_slice(s.as_bytes()))
+    }
+
+    fn sample_header(version: &str) -> StarknetBlockHeader {
+        StarknetBlockHeader {
+            block_number: 1,
+            state_root: felt_bytes_from_u64(2),
+            sequencer_address: felt_bytes_from_u64(3),
+            timestamp: 4,
+            transaction_count: 4,
+            event_count: 3,
+            state_diff_length: 2,
+            l1_da_mode: L1_DA_MODE_BLOB,
+            state_diff_commitment: felt_bytes_from_u64(24),
+            transaction_commitment: felt_bytes_from_u64(21),
+            event_commitment: felt_bytes_from_u64(22),
+            receipt_commitment: felt_bytes_from_u64(23),
+            l1_gas_price_wei: felt_bytes_from_u64(7),
+            l1_gas_price_fri: felt_bytes_from_u64(6),
+            l1_data_gas_price_wei: felt_bytes_from_u64(9),
+            l1_data_gas_price_fri: felt_bytes_from_u64(10),
+            l2_gas_price_wei: felt_bytes_from_u64(12),
+            l2_gas_price_fri: felt_bytes_from_u64(11),
+            starknet_version: short_string(version),
+            parent_block_hash: felt_bytes_from_u64(11),
+        }
+    }
+
+    #[test]
+    fn test_concat_counts() {
+        let packed = concat_counts(4, 3, 2, L1_DA_MODE_BLOB);
+        let expected =
+            Felt::from_hex("0x0000000000000004000000000000000300000000000000028000000000000000")
+                .unwrap();
+        assert_eq!(packed, expected);
+    }
+
+    #[test]
+    fn test_block_hash_v0_13_2() {
+        let header = sample_header("0.13.2");
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = starknet_block_hash(&header, &mut out);
+            assert_eq!(result, StarkResult::Success);
+        }
+
+        let expected =
+            Felt::from_hex("0x4b0361c614b5339c51d6cd88082c00e58719067ce25c9c3b5cec86835df2f47")
+                .unwrap();
+        assert_eq!(out, felt_to_bytes(&expected));
+    }
+
+    #[test]
+    fn test_block_hash_v0_13_4() {
+        let header = sample_header("0.13.4");
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = starknet_block_hash(&header, &mut out);
+            assert_eq!(result, StarkResult::Success);
+        }
+
+        let expected =
+            Felt::from_hex("0x9c6d7ce9c0f72395ac40256de1fca2a77af867f55130623e3b02e05adf5f80")
+                .unwrap();
+        assert_eq!(out, felt_to_bytes(&expected));
+    }
+
+    #[test]
+    fn test_block_hash_mainnet_v0_13_2() {
+        let felt = |hex: &str| felt_to_bytes(&Felt::from_hex_unchecked(hex));
+        // Mainnet block 800000 (Starknet 0.13.2.1), header from
+        // starknet_getBlockWithTxHashes
+        let header = StarknetBlockHeader {
+            block_number: 800000,
+            state_root: felt("0xcc0f0615c102a0e8cd4c74a7eba9db4d64cb03b4a1c34db2d1447d61258491"),
+            sequencer_address: felt(
+                "0x1176a1bd84444c89232ec27754698e5d2e7e1a7f1539f12027f28b23ec9f3d8",
+            ),
+            timestamp: 1728897868,
+            transaction_count: 14,
+            event_count: 180,
+            state_diff_length: 171,
+            l1_da_mode: L1_DA_MODE_BLOB,
+            state_diff_commitment: felt(
+                "0x5e09d524e3db4ad3bd7466d287d2a9e010582984efdd93e40dd83bd2cb835ae",
+            ),
+            transaction_commitment: felt(
+                "0x4fa11dbb80890e4d0100b45ff91d28cbb177f59fe191aaa7037dce3a297df",
+            ),
+            event_commitment: felt(
+                "0x8b9cea69959ae77f7b2e97100609cdf8963680c01585f3abc9bafcd95fba2c",
+            ),
+            receipt_commitment: felt(
+                "0x13b1c61bb5bae59dd2fac2c5a5eb99cc3f0a1565c0327c1635445c916d2f6e2",
+            ),
+            l1_gas_price_wei: felt("0x2d5576c55"),
+            l1_gas_price_fri: felt("0x3f4ff003e511"),
+            l1_data_gas_price_wei: felt("0x1"),
+            l1_data_gas_price_fri: felt("0x1658"),
+            l2_gas_price_wei: felt("0x1"),
+            l2_gas_price_fri: felt("0x1"),
+            starknet_version: short_string("0.13.2.1"),
+            parent_block_hash: felt(
+                "0x3b84748bdbf61d4172a8c1bb1d6676e156c8ccc8c22aa31319e1ed8f6f4ac69",
+            ),
+        };
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = starknet_block_hash(&header, &mut out);
+            assert_eq!(result, StarkResult::Success);
+        }
+        assert_eq!(
+            out,
+            felt("0x17614e0ede412d9cd2c3025810fc2655e333a0d11a11c4f04c64eb6bf89cd40")
+        );
+    }
+
+    #[test]
+    fn test_block_hash_l2_gas_ignored_before_v0_13_4() {
+        let a = sample_header("0.13.3");
+        let mut b = a;
+        b.l2_gas_price_wei = felt_bytes_from_u64(999);
+        let mut out_a = [0u8; 32];
+        let mut out_b = [0u8; 32];
+
+        unsafe {
+            starknet_block_hash(&a, &mut out_a);
+            starknet_block_hash(&b, &mut out_b);
+        }
+        assert_eq!(out_a, out_b);
+    }
+
+    #[test]
+    fn test_block_hash_rejects_old_version() {
+        let header = sample_header("0.13.1");
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = starknet_block_hash(&header, &mut out);
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+}
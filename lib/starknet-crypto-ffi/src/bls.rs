@@ -0,0 +1,643 @@
+//! BLS12-381 signatures with aggregation
+//!
+//! Follows the IETF BLS signature draft in the minimal-pubkey-size variant
+//! used by Ethereum consensus: public keys are compressed G1 points (48
+//! bytes), signatures compressed G2 points (96 bytes), and messages are
+//! hashed to G2 with the proof-of-possession ciphersuite
+//! `BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_`. Secret keys are 32-byte
+//! big-endian scalars.
+//!
+//! Aggregating public keys is only safe for keys whose proof of possession
+//! has been checked; otherwise a rogue key can forge an aggregate signature.
+//! A proof of possession signs the compressed public key under the separate
+//! tag `BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_` (`PopProve`), so it
+//! cannot be replayed as a signature.
+
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{
+    multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar,
+};
+use hkdf::Hkdf;
+use sha2::{Digest, Sha256};
+
+use alloc::vec::Vec;
+
+use crate::{bytes_from_raw, error::null_pointer, ffi_guard, StarkResult};
+
+/// Domain separation tag of the proof-of-possession ciphersuite
+const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Domain separation tag of the proofs of possession themselves
+const POP_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+const KEYGEN_SALT: &[u8] = b"BLS-SIG-KEYGEN-SALT-";
+
+fn hash_to_g2(msg: &[u8], dst: &[u8]) -> G2Projective {
+    <G2Projective as HashToCurve<ExpandMsgXmd<sha2_09::Sha256>>>::hash_to_curve(msg, dst)
+}
+
+/// IETF KeyGen with an empty `key_info` (also EIP-2333 `derive_master_SK`)
+fn keygen(ikm: &[u8]) -> Scalar {
+    let mut salt: [u8; 32] = Sha256::digest(KEYGEN_SALT).into();
+    loop {
+        let mut input = ikm.to_vec();
+        input.push(0);
+        let hkdf = Hkdf::<Sha256>::new(Some(&salt), &input);
+
+        // L = 48 bytes, I2OSP(L, 2) as the info suffix
+        let mut okm = [0u8; 48];
+        hkdf.expand(&[0, 48], &mut okm)
+            .expect("48 bytes is a valid HKDF-SHA256 output length");
+
+        let mut wide = [0u8; 64];
+        for (dst, src) in wide.iter_mut().zip(okm.iter().rev()) {
+            *dst = *src;
+        }
+        let secret = Scalar::from_bytes_wide(&wide);
+        if secret != Scalar::zero() {
+            return secret;
+        }
+        salt = Sha256::digest(salt).into();
+    }
+}
+
+fn secret_from_bytes(bytes: &[u8; 32]) -> Option<Scalar> {
+    let mut le = *bytes;
+    le.reverse();
+    let secret = Option::<Scalar>::from(Scalar::from_bytes(&le))?;
+    (secret != Scalar::zero()).then_some(secret)
+}
+
+fn secret_to_bytes(secret: &Scalar) -> [u8; 32] {
+    let mut bytes = secret.to_bytes();
+    bytes.reverse();
+    bytes
+}
+
+/// Decode a public key, rejecting the identity (KeyValidate)
+fn public_key_from_bytes(bytes: &[u8; 48]) -> Option<G1Affine> {
+    let point = Option::<G1Affine>::from(G1Affine::from_compressed(bytes))?;
+    (!bool::from(point.is_identity())).then_some(point)
+}
+
+fn signature_from_bytes(bytes: &[u8; 96]) -> Option<G2Affine> {
+    Option::from(G2Affine::from_compressed(bytes))
+}
+
+/// Check e(pk_1, H(msg_1)) · … · e(pk_n, H(msg_n)) == e(G1, sig)
+fn aggregate_verify(pairs: &[(G1Affine, &[u8])], dst: &[u8], signature: &G2Affine) -> bool {
+    let neg_generator = -G1Affine::generator();
+    let hashes: Vec<G2Prepared> = pairs
+        .iter()
+        .map(|(_, msg)| G2Prepared::from(G2Affine::from(hash_to_g2(msg, dst))))
+        .collect();
+    let signature = G2Prepared::from(*signature);
+
+    let mut terms: Vec<(&G1Affine, &G2Prepared)> =
+        pairs.iter().map(|(pk, _)| pk).zip(hashes.iter()).collect();
+    terms.push((&neg_generator, &signature));
+    multi_miller_loop(&terms).final_exponentiation() == Gt::identity()
+}
+
+/// Check e(pk, H(msg)) == e(G1, sig)
+fn verify(public_key: &G1Affine, msg: &[u8], signature: &G2Affine) -> bool {
+    aggregate_verify(&[(*public_key, msg)], DST, signature)
+}
+
+/// Derive a secret key from at least 32 bytes of input keying material
+///
+/// Deterministic (IETF KeyGen); returns `InvalidInput` if `ikm_len < 32`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bls12_381_keygen")]
+pub unsafe extern "C" fn bls12_381_keygen(
+    ikm: *const u8,
+    ikm_len: usize,
+    out_secret: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_secret);
+        let ikm = match bytes_from_raw(ikm, ikm_len) {
+            Some(s) if s.len() >= 32 => s,
+            _ => return StarkResult::InvalidInput,
+        };
+
+        *out_secret = secret_to_bytes(&keygen(ikm));
+        StarkResult::Success
+    })
+}
+
+/// Derive the compressed G1 public key from a secret key
+///
+/// Returns `InvalidPrivateKey` unless 0 < key < r.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bls12_381_get_public_key")]
+pub unsafe extern "C" fn bls12_381_get_public_key(
+    secret_key: *const [u8; 32],
+    out: *mut [u8; 48],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(secret_key, out);
+        let secret = match secret_from_bytes(&*secret_key) {
+            Some(s) => s,
+            None => return StarkResult::InvalidPrivateKey,
+        };
+
+        *out = G1Affine::from(G1Projective::generator() * secret).to_compressed();
+        StarkResult::Success
+    })
+}
+
+/// Sign a message (returns the compressed G2 signature)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bls12_381_sign")]
+pub unsafe extern "C" fn bls12_381_sign(
+    secret_key: *const [u8; 32],
+    msg: *const u8,
+    len: usize,
+    out: *mut [u8; 96],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(secret_key, out);
+        let secret = match secret_from_bytes(&*secret_key) {
+            Some(s) => s,
+            None => return StarkResult::InvalidPrivateKey,
+        };
+        let msg = match bytes_from_raw(msg, len) {
+            Some(m) => m,
+            None => return null_pointer("msg"),
+        };
+
+        *out = G2Affine::from(hash_to_g2(msg, DST) * secret).to_compressed();
+        StarkResult::Success
+    })
+}
+
+/// Verify a signature over a message
+///
+/// Also verifies an aggregate signature of one message against the
+/// aggregated public key. Returns `InvalidInput` if the public key or
+/// signature is not a valid subgroup point, or the key is the identity.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bls12_381_verify")]
+pub unsafe extern "C" fn bls12_381_verify(
+    public_key: *const [u8; 48],
+    msg: *const u8,
+    len: usize,
+    signature: *const [u8; 96],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(public_key, signature);
+        let public_key = match public_key_from_bytes(&*public_key) {
+            Some(p) => p,
+            None => return StarkResult::InvalidInput,
+        };
+        let signature = match signature_from_bytes(&*signature) {
+            Some(s) => s,
+            None => return StarkResult::InvalidInput,
+        };
+        let msg = match bytes_from_raw(msg, len) {
+            Some(m) => m,
+            None => return null_pointer("msg"),
+        };
+
+        if verify(&public_key, msg, &signature) {
+            StarkResult::Success
+        } else {
+            StarkResult::InvalidSignature
+        }
+    })
+}
+
+/// Verify an aggregate signature over distinct messages
+///
+/// Signer `i` holds `public_keys[i]` and signed the `msg_lens[i]` bytes at
+/// `msgs[i]` (null when its length is 0); `signature` aggregates their
+/// signatures. Messages need not differ: each key is only trusted with a
+/// verified proof of possession, as in the IETF `AggregateVerify` of this
+/// ciphersuite. Returns `InvalidInput` if `count` is 0 or a key or the
+/// signature is invalid.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bls12_381_aggregate_verify")]
+pub unsafe extern "C" fn bls12_381_aggregate_verify(
+    public_keys: *const [u8; 48],
+    msgs: *const *const u8,
+    msg_lens: *const usize,
+    count: usize,
+    signature: *const [u8; 96],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(public_keys, msgs, msg_lens, signature);
+        if count == 0 {
+            return StarkResult::InvalidInput;
+        }
+        let signature = match signature_from_bytes(&*signature) {
+            Some(s) => s,
+            None => return StarkResult::InvalidInput,
+        };
+
+        let public_keys = core::slice::from_raw_parts(public_keys, count);
+        let msgs = core::slice::from_raw_parts(msgs, count);
+        let msg_lens = core::slice::from_raw_parts(msg_lens, count);
+        let mut pairs = Vec::with_capacity(count);
+        for i in 0..count {
+            let Some(public_key) = public_key_from_bytes(&public_keys[i]) else {
+                return StarkResult::InvalidInput;
+            };
+            let Some(msg) = bytes_from_raw(msgs[i], msg_lens[i]) else {
+                return null_pointer("msgs");
+            };
+            pairs.push((public_key, msg));
+        }
+
+        if aggregate_verify(&pairs, DST, &signature) {
+            StarkResult::Success
+        } else {
+            StarkResult::InvalidSignature
+        }
+    })
+}
+
+/// Prove possession of a secret key (`PopProve`)
+///
+/// Signs the compressed public key under the proof-of-possession tag.
+/// Returns `InvalidPrivateKey` unless 0 < key < r.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bls12_381_pop_prove")]
+pub unsafe extern "C" fn bls12_381_pop_prove(
+    secret_key: *const [u8; 32],
+    out: *mut [u8; 96],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(secret_key, out);
+        let secret = match secret_from_bytes(&*secret_key) {
+            Some(s) => s,
+            None => return StarkResult::InvalidPrivateKey,
+        };
+        let public_key = G1Affine::from(G1Projective::generator() * secret).to_compressed();
+
+        *out = G2Affine::from(hash_to_g2(&public_key, POP_DST) * secret).to_compressed();
+        StarkResult::Success
+    })
+}
+
+/// Verify a proof of possession (`PopVerify`)
+///
+/// Returns `InvalidInput` if the public key or proof is not a valid
+/// subgroup point, or the key is the identity.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bls12_381_pop_verify")]
+pub unsafe extern "C" fn bls12_381_pop_verify(
+    public_key: *const [u8; 48],
+    proof: *const [u8; 96],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(public_key, proof);
+        let public_key = &*public_key;
+        let Some(point) = public_key_from_bytes(public_key) else {
+            return StarkResult::InvalidInput;
+        };
+        let Some(proof) = signature_from_bytes(&*proof) else {
+            return StarkResult::InvalidInput;
+        };
+
+        if aggregate_verify(&[(point, &public_key[..])], POP_DST, &proof) {
+            StarkResult::Success
+        } else {
+            StarkResult::InvalidSignature
+        }
+    })
+}
+
+/// Aggregate `count` signatures into one
+///
+/// Returns `InvalidInput` if `count` is 0 or a signature is invalid.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bls12_381_aggregate_signatures")]
+pub unsafe extern "C" fn bls12_381_aggregate_signatures(
+    signatures: *const [u8; 96],
+    count: usize,
+    out: *mut [u8; 96],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(signatures, out);
+        if count == 0 {
+            return StarkResult::InvalidInput;
+        }
+
+        let mut acc = G2Projective::identity();
+        for bytes in core::slice::from_raw_parts(signatures, count) {
+            match signature_from_bytes(bytes) {
+                Some(s) => acc += s,
+                None => return StarkResult::InvalidInput,
+            }
+        }
+
+        *out = G2Affine::from(acc).to_compressed();
+        StarkResult::Success
+    })
+}
+
+/// Aggregate `count` public keys into one
+///
+/// Only aggregate keys whose proof of possession has been verified. Returns
+/// `InvalidInput` if `count` is 0 or a key is invalid.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bls12_381_aggregate_public_keys")]
+pub unsafe extern "C" fn bls12_381_aggregate_public_keys(
+    public_keys: *const [u8; 48],
+    count: usize,
+    out: *mut [u8; 48],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(public_keys, out);
+        if count == 0 {
+            return StarkResult::InvalidInput;
+        }
+
+        let mut acc = G1Projective::identity();
+        for bytes in core::slice::from_raw_parts(public_keys, count) {
+            match public_key_from_bytes(bytes) {
+                Some(p) => acc += p,
+                None => return StarkResult::InvalidInput,
+            }
+        }
+
+        *out = G1Affine::from(acc).to_compressed();
+        StarkResult::Success
+    })
+}
+
+/// Hash a message to a compressed G2 point (hash_to_curve, SSWU, SHA-256)
+///
+/// `dst` is the domain separation tag; pass null to use the signature
+/// ciphersuite tag, which gives the point `bls12_381_sign` multiplies.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bls12_381_hash_to_g2")]
+pub unsafe extern "C" fn bls12_381_hash_to_g2(
+    msg: *const u8,
+    len: usize,
+    dst: *const u8,
+    dst_len: usize,
+    out: *mut [u8; 96],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out);
+        let msg = match bytes_from_raw(msg, len) {
+            Some(m) => m,
+            None => return null_pointer("msg"),
+        };
+        let dst = if dst.is_null() {
+            DST
+        } else {
+            core::slice::from_raw_parts(dst, dst_len)
+        };
+
+        *out = G2Affine::from(hash_to_g2(msg, dst)).to_compressed();
+        StarkResult::Success
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex<const N: usize>(s: &str) -> [u8; N] {
+        let mut out = [0u8; N];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn test_bls_sign_eth_vector() {
+        // Ethereum consensus-spec BLS test vector (sign_case_84d45c9c7cca6b92)
+        let secret_key = hex("263dbd792f5b1be47ed85f8938c0f29586af0d3ac7b977f21c278fe1462040e3");
+        let msg = [0u8; 32];
+        let mut signature = [0u8; 96];
+
+        unsafe {
+            let result = bls12_381_sign(&secret_key, msg.as_ptr(), msg.len(), &mut signature);
+            assert_eq!(result, StarkResult::Success);
+        }
+        assert_eq!(
+            signature,
+            hex::<96>("b6ed936746e01f8ecf281f020953fbf1f01debd5657c4a383940b020b26507f6076334f91e2366c96e9ab279fb5158090352ea1c5b0c9274504f4f0e7053af24802e51e4568d164fe986834f41e55c8e850ce1f98458c0cfc9ab380b55285a55")
+        );
+    }
+
+    #[test]
+    fn test_bls_keygen_eip2333_master() {
+        // EIP-2333 test case 0: derive_master_SK(seed)
+        let seed = hex::<64>("c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04");
+        let mut secret_key = [0u8; 32];
+
+        unsafe {
+            let result = bls12_381_keygen(seed.as_ptr(), seed.len(), &mut secret_key);
+            assert_eq!(result, StarkResult::Success);
+
+            let result = bls12_381_keygen(seed.as_ptr(), 31, &mut secret_key.clone());
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+        // 6083874454709270928345386274498605044986640685124978867557563392430687146096
+        assert_eq!(
+            secret_key,
+            hex::<32>("0d7359d57963ab8fbbde1852dcf553fedbc31f464d80ee7d40ae683122b45070")
+        );
+    }
+
+    #[test]
+    fn test_bls_aggregate_same_message() {
+        let msg = b"block 42";
+        let mut public_keys = [[0u8; 48]; 3];
+        let mut signatures = [[0u8; 96]; 3];
+        let mut aggregate_key = [0u8; 48];
+        let mut aggregate_signature = [0u8; 96];
+
+        unsafe {
+            for i in 0..3 {
+                let ikm = [i as u8 + 1; 32];
+                let mut secret_key = [0u8; 32];
+                bls12_381_keygen(ikm.as_ptr(), ikm.len(), &mut secret_key);
+                bls12_381_get_public_key(&secret_key, &mut public_keys[i]);
+                bls12_381_sign(&secret_key, msg.as_ptr(), msg.len(), &mut signatures[i]);
+
+                let result =
+                    bls12_381_verify(&public_keys[i], msg.as_ptr(), msg.len(), &signatures[i]);
+                assert_eq!(result, StarkResult::Success);
+            }
+
+            let result =
+                bls12_381_aggregate_public_keys(public_keys.as_ptr(), 3, &mut aggregate_key);
+            assert_eq!(result, StarkResult::Success);
+            let result =
+                bls12_381_aggregate_signatures(signatures.as_ptr(), 3, &mut aggregate_signature);
+            assert_eq!(result, StarkResult::Success);
+
+            let result = bls12_381_verify(
+                &aggregate_key,
+                msg.as_ptr(),
+                msg.len(),
+                &aggregate_signature,
+            );
+            assert_eq!(result, StarkResult::Success);
+
+            // Missing one signer
+            bls12_381_aggregate_signatures(signatures.as_ptr(), 2, &mut aggregate_signature);
+            let result = bls12_381_verify(
+                &aggregate_key,
+                msg.as_ptr(),
+                msg.len(),
+                &aggregate_signature,
+            );
+            assert_eq!(result, StarkResult::InvalidSignature);
+
+            let result =
+                bls12_381_aggregate_signatures(signatures.as_ptr(), 0, &mut aggregate_signature);
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+
+    /// Ethereum consensus-spec BLS test keys (`privkeys` of the generator)
+    const ETH_SECRET_KEYS: [&str; 3] = [
+        "263dbd792f5b1be47ed85f8938c0f29586af0d3ac7b977f21c278fe1462040e3",
+        "47b8192d77bf871b62e87859d653922725724a5c031afeabc60bcef5ff665138",
+        "328388aff0d4a5b7dc9205abd374e7e98f3cd9f3418edb4eafda5fb16473d216",
+    ];
+
+    #[test]
+    fn test_bls_aggregate_verify_eth_vector() {
+        // Ethereum consensus-spec BLS test vector (aggregate_verify_valid)
+        let public_keys = [
+            hex::<48>("a491d1b0ecd9bb917989f0e74f0dea0422eac4a873e5e2644f368dffb9a6e20fd6e10c1b77654d067c0618f6e5a7f79a"),
+            hex::<48>("b301803f8b5ac4a1133581fc676dfedc60d891dd5fa99028805e5ea5b08d3491af75d0707adab3b70c6a6a580217bf81"),
+            hex::<48>("b53d21a4cfd562c469cc81514d4ce5a6b577d8403d32a394dc265dd190b47fa9f829fdd7963afdf972e5e77854051f6f"),
+        ];
+        let msgs = [[0u8; 32], [0x56; 32], [0xab; 32]];
+        let signature = hex::<96>("9104e74b9dfd3ad502f25d6a5ef57db0ed7d9a0e00f3500586d8ce44231212542fcfaf87840539b398bf07626705cf1105d246ca1062c6c2e1a53029a0f790ed5e3cb1f52f8234dc5144c45fc847c0cd37a92d68e7c5ba7c648a8a339f171244");
+        let msg_ptrs = msgs.each_ref().map(|m| m.as_ptr());
+        let msg_lens = [32usize; 3];
+
+        unsafe {
+            for (secret_key, public_key) in ETH_SECRET_KEYS.iter().zip(&public_keys) {
+                let mut derived = [0u8; 48];
+                bls12_381_get_public_key(&hex(secret_key), &mut derived);
+                assert_eq!(&derived, public_key);
+            }
+
+            let result = bls12_381_aggregate_verify(
+                public_keys.as_ptr(),
+                msg_ptrs.as_ptr(),
+                msg_lens.as_ptr(),
+                3,
+                &signature,
+            );
+            assert_eq!(result, StarkResult::Success);
+
+            // Messages swapped between signers
+            let swapped = [msg_ptrs[1], msg_ptrs[0], msg_ptrs[2]];
+            let result = bls12_381_aggregate_verify(
+                public_keys.as_ptr(),
+                swapped.as_ptr(),
+                msg_lens.as_ptr(),
+                3,
+                &signature,
+            );
+            assert_eq!(result, StarkResult::InvalidSignature);
+
+            let result = bls12_381_aggregate_verify(
+                public_keys.as_ptr(),
+                msg_ptrs.as_ptr(),
+                msg_lens.as_ptr(),
+                0,
+                &signature,
+            );
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn test_bls_pop() {
+        for secret_key in ETH_SECRET_KEYS {
+            let secret_key = hex::<32>(secret_key);
+            let (mut public_key, mut proof, mut signature) = ([0u8; 48], [0u8; 96], [0u8; 96]);
+
+            unsafe {
+                bls12_381_get_public_key(&secret_key, &mut public_key);
+                let result = bls12_381_pop_prove(&secret_key, &mut proof);
+                assert_eq!(result, StarkResult::Success);
+                assert_eq!(
+                    bls12_381_pop_verify(&public_key, &proof),
+                    StarkResult::Success
+                );
+
+                // PopProve is sk · H(pk) under the POP tag (RFC 9380 hashing,
+                // checked above)
+                let mut hash = [0u8; 96];
+                bls12_381_hash_to_g2(
+                    public_key.as_ptr(),
+                    48,
+                    POP_DST.as_ptr(),
+                    POP_DST.len(),
+                    &mut hash,
+                );
+                let secret = secret_from_bytes(&secret_key).unwrap();
+                let expected = G2Affine::from(G2Affine::from_compressed(&hash).unwrap() * secret);
+                assert_eq!(proof, expected.to_compressed());
+
+                // A signature of the key bytes is not a proof, nor the reverse
+                bls12_381_sign(&secret_key, public_key.as_ptr(), 48, &mut signature);
+                assert_eq!(
+                    bls12_381_pop_verify(&public_key, &signature),
+                    StarkResult::InvalidSignature
+                );
+                let result = bls12_381_verify(&public_key, public_key.as_ptr(), 48, &proof);
+                assert_eq!(result, StarkResult::InvalidSignature);
+
+                assert_eq!(
+                    bls12_381_pop_prove(&[0u8; 32], &mut proof),
+                    StarkResult::InvalidPrivateKey
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_bls_hash_to_g2_rfc9380() {
+        // RFC 9380 J.10.1, BLS12381G2_XMD:SHA-256_SSWU_RO_, msg ""
+        let dst = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+        let mut out = [0u8; 96];
+
+        unsafe {
+            let result =
+                bls12_381_hash_to_g2(core::ptr::null(), 0, dst.as_ptr(), dst.len(), &mut out);
+            assert_eq!(result, StarkResult::Success);
+        }
+
+        let point = G2Affine::from_compressed(&out).unwrap();
+        let uncompressed = point.to_uncompressed();
+        // Zcash serialization orders Fp2 coordinates as (c1, c0)
+        assert_eq!(
+            uncompressed[48..96],
+            hex::<48>("0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a")
+        );
+    }
+
+    #[test]
+    fn test_bls_rejects_invalid_keys() {
+        let mut out = [0u8; 48];
+        let msg = [0u8; 32];
+        let signature = [0u8; 96];
+
+        unsafe {
+            let result = bls12_381_get_public_key(&[0u8; 32], &mut out);
+            assert_eq!(result, StarkResult::InvalidPrivateKey);
+
+            // Compressed identity
+            let mut identity = [0u8; 48];
+            identity[0] = 0xc0;
+            let result = bls12_381_verify(&identity, msg.as_ptr(), msg.len(), &signature);
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+}
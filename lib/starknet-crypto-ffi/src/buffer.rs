@@ -0,0 +1,145 @@
+//! Library-allocated output buffers
+//!
+//! Most functions write to a caller buffer whose capacity is passed in
+//! `out_len` (see `write_bytes_out`). Outputs whose size is not known in
+//! advance can instead be allocated by the library: the function writes a
+//! pointer to `out` and the length to `out_len`, and the caller releases the
+//! buffer with `stark_buffer_free(ptr, len)`, passing back the same length.
+//! These functions carry an `_alloc` suffix.
+//!
+//! The pointer is never null on success, even for an empty buffer (which
+//! must not be dereferenced but may be freed). Debug builds keep a registry
+//! of live buffers, so freeing one twice or with the wrong length is
+//! reported instead of corrupting the heap.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::{ffi_guard_void, StarkResult};
+
+#[cfg(debug_assertions)]
+mod registry {
+    use alloc::collections::BTreeMap;
+
+    use crate::sync::{Mutex, MutexGuard};
+
+    /// Address -> length of every live non-empty buffer (empty buffers all
+    /// share the same dangling address and own no memory)
+    static LIVE: Mutex<BTreeMap<usize, usize>> = Mutex::new(BTreeMap::new());
+
+    fn live() -> MutexGuard<'static, BTreeMap<usize, usize>> {
+        LIVE.lock()
+    }
+
+    pub(super) fn insert(addr: usize, len: usize) {
+        live().insert(addr, len);
+    }
+
+    pub(super) fn remove(addr: usize, len: usize) -> bool {
+        let mut live = live();
+        if live.get(&addr) != Some(&len) {
+            return false;
+        }
+        live.remove(&addr);
+        true
+    }
+}
+
+/// Hand `bytes` to the caller as a library-allocated buffer
+///
+/// A null `out` or `out_len` is `NullPointer`, and nothing is allocated.
+#[cfg_attr(
+    not(any(feature = "abi", feature = "test-vectors", feature = "wasm")),
+    allow(dead_code)
+)]
+pub(crate) unsafe fn alloc_bytes_out(
+    bytes: Vec<u8>,
+    out: *mut *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    check_null!(out, out_len);
+    let len = bytes.len();
+    let ptr = Box::into_raw(bytes.into_boxed_slice()).cast::<u8>();
+    #[cfg(debug_assertions)]
+    if len > 0 {
+        registry::insert(ptr as usize, len);
+    }
+    *out = ptr;
+    *out_len = len;
+    StarkResult::Success
+}
+
+/// Free a buffer returned by an `_alloc` function (null is a no-op)
+///
+/// `len` must be the length written with the buffer.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_buffer_free")]
+pub unsafe extern "C" fn stark_buffer_free(ptr: *mut u8, len: usize) {
+    ffi_guard_void(|| {
+        if ptr.is_null() {
+            return StarkResult::Success;
+        }
+        #[cfg(debug_assertions)]
+        if len > 0 && !registry::remove(ptr as usize, len) {
+            return crate::error::invalid_input(alloc::format!(
+                "`ptr` is not a live library buffer of {len} bytes (already freed, or wrong `len`)"
+            ));
+        }
+        drop(Box::from_raw(core::ptr::slice_from_raw_parts_mut(ptr, len)));
+        StarkResult::Success
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alloc_bytes_out() {
+        let mut ptr = core::ptr::null_mut();
+        let mut len = 0;
+        unsafe {
+            assert_eq!(
+                alloc_bytes_out(b"hello".to_vec(), &mut ptr, &mut len),
+                StarkResult::Success
+            );
+            assert_eq!(core::slice::from_raw_parts(ptr, len), b"hello");
+            stark_buffer_free(ptr, len);
+
+            // Empty buffers are non-null and freeable
+            assert_eq!(
+                alloc_bytes_out(Vec::new(), &mut ptr, &mut len),
+                StarkResult::Success
+            );
+            assert!(!ptr.is_null());
+            assert_eq!(len, 0);
+            stark_buffer_free(ptr, len);
+            stark_buffer_free(core::ptr::null_mut(), 0);
+
+            assert_eq!(
+                alloc_bytes_out(vec![1], core::ptr::null_mut(), &mut len),
+                StarkResult::NullPointer
+            );
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_buffer_misuse_detected() {
+        let mut ptr = core::ptr::null_mut();
+        let mut len = 0;
+        unsafe {
+            alloc_bytes_out(vec![7; 16], &mut ptr, &mut len);
+            // Wrong length: left alone
+            stark_buffer_free(ptr, 8);
+            assert_eq!(*ptr, 7);
+            stark_buffer_free(ptr, len);
+        }
+        let message =
+            unsafe { core::ffi::CStr::from_ptr(crate::error::stark_last_error_message()) };
+        assert!(message
+            .to_str()
+            .unwrap()
+            .contains("not a live library buffer"));
+    }
+}
@@ -0,0 +1,349 @@
+//! Cairo 1 `ByteArray` serialization
+//!
+//! A `ByteArray` is serialized as
+//!
+//! ```text
+//! [n, word_0, ..., word_{n-1}, pending_word, pending_word_len]
+//! ```
+//!
+//! where the `n` full words hold 31 bytes each (big-endian) and the pending
+//! word holds the remaining `pending_word_len < 31` bytes. A string whose
+//! length is a multiple of 31 ends with an empty pending word (`0, 0`), not
+//! with a short final full word. A `len`-byte string takes
+//! `len / 31 + 3` felts.
+//!
+//! `bytes_pack_31` is the plain chunking used for byte data in storage: the
+//! words alone, the last one holding the remaining `1..=31` bytes, without
+//! counts. Its inverse needs the byte length to restore leading zeros of
+//! the last word.
+
+use alloc::vec::Vec;
+
+use starknet_crypto::Felt;
+
+use crate::{
+    bytes_from_raw, error::null_pointer, felts_from_raw, ffi_guard, write_bytes_out,
+    write_felts_out, FeltBytes, StarkResult,
+};
+
+/// Bytes in a full `bytes31` word
+pub const BYTES31_LEN: usize = 31;
+
+/// Serialize bytes as a Cairo `ByteArray`
+pub(crate) fn encode_byte_array(data: &[u8]) -> Vec<Felt> {
+    let chunks = data.chunks_exact(BYTES31_LEN);
+    let pending = chunks.remainder();
+
+    let mut felts = Vec::with_capacity(data.len() / BYTES31_LEN + 3);
+    felts.push(Felt::from(chunks.len()));
+    felts.extend(chunks.map(Felt::from_bytes_be_slice));
+    felts.push(Felt::from_bytes_be_slice(pending));
+    felts.push(Felt::from(pending.len()));
+    felts
+}
+
+/// Big-endian bytes of `word`, which must fit in `len` bytes
+fn word_bytes(word: &Felt, len: usize) -> Option<Vec<u8>> {
+    let bytes = word.to_bytes_be();
+    let (high, low) = bytes.split_at(32 - len);
+    if high.iter().any(|&b| b != 0) {
+        return None;
+    }
+    Some(low.to_vec())
+}
+
+/// Deserialize a Cairo `ByteArray` from the start of `felts`
+///
+/// Returns the bytes and the number of felts consumed, or `None` if the
+/// serialization is truncated or not canonical (a word wider than its
+/// length, a pending length of 31 or more).
+pub(crate) fn decode_byte_array(felts: &[Felt]) -> Option<(Vec<u8>, usize)> {
+    let full_words: usize = (*felts.first()?).try_into().ok()?;
+    let words = felts.get(1..full_words.checked_add(3)?)?;
+    let (full, pending) = words.split_at(full_words);
+    let pending_len: usize = pending[1].try_into().ok()?;
+    if pending_len >= BYTES31_LEN {
+        return None;
+    }
+
+    let mut data = Vec::with_capacity(full_words * BYTES31_LEN + pending_len);
+    for word in full {
+        data.extend(word_bytes(word, BYTES31_LEN)?);
+    }
+    data.extend(word_bytes(&pending[0], pending_len)?);
+    Some((data, full_words + 3))
+}
+
+/// Split bytes into big-endian 31-byte words, the last one possibly shorter
+pub(crate) fn pack_31(data: &[u8]) -> Vec<Felt> {
+    data.chunks(BYTES31_LEN)
+        .map(Felt::from_bytes_be_slice)
+        .collect()
+}
+
+/// Inverse of `pack_31` for `len` bytes, or `None` if the word count does
+/// not match `len` or a word is wider than its chunk
+pub(crate) fn unpack_31(words: &[Felt], len: usize) -> Option<Vec<u8>> {
+    if words.len() != len.div_ceil(BYTES31_LEN) {
+        return None;
+    }
+
+    let mut data = Vec::with_capacity(len);
+    for word in words {
+        data.extend(word_bytes(word, (len - data.len()).min(BYTES31_LEN))?);
+    }
+    Some(data)
+}
+
+/// Serialize bytes (typically a UTF-8 string) as a Cairo `ByteArray`
+///
+/// `data` may be null when `len == 0`. `out_count` holds the capacity of
+/// `out_felts` on input and the number of felts on output (`len / 31 + 3`);
+/// if the array is too small, the required count is written and
+/// `BufferTooSmall` is returned.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bytearray_to_felts")]
+pub unsafe extern "C" fn bytearray_to_felts(
+    data: *const u8,
+    len: usize,
+    out_felts: *mut FeltBytes,
+    out_count: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_count);
+        let data = match bytes_from_raw(data, len) {
+            Some(d) => d,
+            None => return null_pointer("data"),
+        };
+
+        write_felts_out(&encode_byte_array(data), out_felts, out_count)
+    })
+}
+
+/// Deserialize a Cairo `ByteArray` into bytes
+///
+/// `felts` must hold exactly one serialized `ByteArray`. `out_len` follows
+/// the usual capacity convention. Returns `InvalidInput` for a truncated or
+/// non-canonical serialization, or trailing felts.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bytearray_from_felts")]
+pub unsafe extern "C" fn bytearray_from_felts(
+    felts: *const FeltBytes,
+    count: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_len);
+        let felts = match felts_from_raw(felts, count) {
+            Some(f) => f,
+            None => return null_pointer("felts"),
+        };
+
+        match decode_byte_array(&felts) {
+            Some((data, consumed)) if consumed == felts.len() => {
+                write_bytes_out(&data, out, out_len)
+            }
+            _ => StarkResult::InvalidInput,
+        }
+    })
+}
+
+/// Pack bytes into 31-byte felt words (no length prefix or pending word)
+///
+/// Writes `ceil(len / 31)` felts; the last word holds the remaining bytes,
+/// right-aligned. `data` may be null when `len == 0`. `out_count` follows
+/// the usual capacity convention.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bytes_pack_31")]
+pub unsafe extern "C" fn bytes_pack_31(
+    data: *const u8,
+    len: usize,
+    out_felts: *mut FeltBytes,
+    out_count: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_count);
+        let data = match bytes_from_raw(data, len) {
+            Some(d) => d,
+            None => return null_pointer("data"),
+        };
+
+        write_felts_out(&pack_31(data), out_felts, out_count)
+    })
+}
+
+/// Unpack `len` bytes from 31-byte felt words (inverse of `bytes_pack_31`)
+///
+/// `count` must be `ceil(len / 31)`, and every word must fit in its chunk
+/// (31 bytes, or the remaining bytes for the last one); otherwise returns
+/// `InvalidInput`. `out_len` follows the usual capacity convention.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bytes_unpack_31")]
+pub unsafe extern "C" fn bytes_unpack_31(
+    felts: *const FeltBytes,
+    count: usize,
+    len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_len);
+        let felts = match felts_from_raw(felts, count) {
+            Some(f) => f,
+            None => return null_pointer("felts"),
+        };
+
+        match unpack_31(&felts, len) {
+            Some(data) => write_bytes_out(&data, out, out_len),
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::felt_to_bytes;
+
+    fn felts(hex: &[&str]) -> Vec<Felt> {
+        hex.iter().map(|h| Felt::from_hex(h).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_encode_byte_array() {
+        assert_eq!(encode_byte_array(b""), felts(&["0x0", "0x0", "0x0"]));
+        assert_eq!(
+            encode_byte_array(b"hello"),
+            felts(&["0x0", "0x68656c6c6f", "0x5"])
+        );
+        // Same layout as starknet.js byteArrayFromString
+        assert_eq!(
+            encode_byte_array(b"Long string, more than 31 characters."),
+            felts(&[
+                "0x1",
+                "0x4c6f6e6720737472696e672c206d6f7265207468616e203331206368617261",
+                "0x63746572732e",
+                "0x6",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_byte_array_exact_word() {
+        // 31 bytes: one full word and an empty pending word
+        let data = [0x41u8; 31];
+        let encoded = encode_byte_array(&data);
+        assert_eq!(encoded.len(), 4);
+        assert_eq!(encoded[0], Felt::ONE);
+        assert_eq!(&encoded[2..], &[Felt::ZERO, Felt::ZERO]);
+        assert_eq!(decode_byte_array(&encoded), Some((data.to_vec(), 4)));
+    }
+
+    #[test]
+    fn test_byte_array_roundtrip() {
+        let data: Vec<u8> = (0u8..100).collect();
+        let mut out_felts = [[0u8; 32]; 6];
+        let mut decoded = [0u8; 100];
+
+        unsafe {
+            let mut count = 2;
+            let result = bytearray_to_felts(
+                data.as_ptr(),
+                data.len(),
+                out_felts.as_mut_ptr(),
+                &mut count,
+            );
+            assert_eq!(result, StarkResult::BufferTooSmall);
+            assert_eq!(count, 100 / 31 + 3);
+
+            let mut count = out_felts.len();
+            let result = bytearray_to_felts(
+                data.as_ptr(),
+                data.len(),
+                out_felts.as_mut_ptr(),
+                &mut count,
+            );
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(count, 6);
+
+            let mut len = decoded.len();
+            let result =
+                bytearray_from_felts(out_felts.as_ptr(), count, decoded.as_mut_ptr(), &mut len);
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(len, 100);
+        }
+        assert_eq!(decoded.to_vec(), data);
+    }
+
+    #[test]
+    fn test_decode_byte_array_rejects_malformed() {
+        // Truncated
+        assert!(decode_byte_array(&felts(&["0x1", "0x41", "0x0"])).is_none());
+        assert!(decode_byte_array(&[]).is_none());
+        // Pending length out of range, or too short for the word
+        assert!(decode_byte_array(&felts(&["0x0", "0x41", "0x1f"])).is_none());
+        assert!(decode_byte_array(&felts(&["0x0", "0x4142", "0x1"])).is_none());
+        // Full word wider than 31 bytes
+        let wide = Felt::TWO.pow(248u32);
+        assert!(decode_byte_array(&[Felt::ONE, wide, Felt::ZERO, Felt::ZERO]).is_none());
+        // Huge word count
+        assert!(decode_byte_array(&[Felt::MAX, Felt::ZERO, Felt::ZERO]).is_none());
+
+        // Trailing felts are rejected at the FFI
+        let encoded: Vec<FeltBytes> = felts(&["0x0", "0x41", "0x1", "0x0"])
+            .iter()
+            .map(felt_to_bytes)
+            .collect();
+        let mut out = [0u8; 1];
+        let mut len = 1;
+        unsafe {
+            let result = bytearray_from_felts(encoded.as_ptr(), 4, out.as_mut_ptr(), &mut len);
+            assert_eq!(result, StarkResult::InvalidInput);
+            let result = bytearray_from_felts(encoded.as_ptr(), 3, out.as_mut_ptr(), &mut len);
+            assert_eq!(result, StarkResult::Success);
+        }
+        assert_eq!(out, *b"A");
+    }
+
+    #[test]
+    fn test_pack_31() {
+        let data: Vec<u8> = (1u8..=40).collect();
+        let packed = pack_31(&data);
+        assert_eq!(packed.len(), 2);
+        assert_eq!(packed[0], Felt::from_bytes_be_slice(&data[..31]));
+        assert_eq!(packed[1], Felt::from_hex("0x202122232425262728").unwrap());
+        assert_eq!(unpack_31(&packed, 40), Some(data));
+
+        assert!(pack_31(b"").is_empty());
+        assert_eq!(unpack_31(&[], 0), Some(vec![]));
+        // Exactly one word, no empty trailing word
+        assert_eq!(pack_31(&[0x41; 31]).len(), 1);
+    }
+
+    #[test]
+    fn test_unpack_31_leading_zeros() {
+        // The length restores zero bytes the last word's value cannot show
+        let data = [0u8, 0, 0x12];
+        let mut packed = [[0u8; 32]; 1];
+        let mut unpacked = [0xffu8; 3];
+
+        unsafe {
+            let mut count = packed.len();
+            let result = bytes_pack_31(data.as_ptr(), 3, packed.as_mut_ptr(), &mut count);
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(count, 1);
+
+            let mut len = unpacked.len();
+            let result = bytes_unpack_31(packed.as_ptr(), 1, 3, unpacked.as_mut_ptr(), &mut len);
+            assert_eq!(result, StarkResult::Success);
+        }
+        assert_eq!(unpacked, data);
+
+        // Word count mismatch, or a last word wider than the remaining bytes
+        let words = [Felt::from(0x1234u64)];
+        assert!(unpack_31(&words, 32).is_none());
+        assert!(unpack_31(&words, 1).is_none());
+        assert_eq!(unpack_31(&words, 2), Some(vec![0x12, 0x34]));
+    }
+}
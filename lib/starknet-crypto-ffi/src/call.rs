@@ -0,0 +1,279 @@
+//! Contract calls passed across the FFI boundary
+//!
+//! Also encodes a multicall as account `__execute__` calldata. Cairo 1
+//! accounts take `Array<Call>`:
+//!
+//! ```text
+//! [n, to_0, selector_0, calldata_len_0, calldata_0..., to_1, ...]
+//! ```
+//!
+//! Legacy Cairo 0 accounts take the call headers with offsets into one
+//! flattened calldata array:
+//!
+//! ```text
+//! [n, to_0, selector_0, offset_0, len_0, ..., total_len, calldata_0..., calldata_1...]
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::c_void;
+
+use starknet_crypto::Felt;
+
+use crate::stream::{stream_felts, StarkChunkCallback};
+use crate::{
+    error::null_pointer, felt_from_bytes, felts_from_raw, ffi_guard, write_felts_out, FeltBytes,
+    StarkResult,
+};
+
+/// A single contract call: target, entry point selector and calldata.
+///
+/// `calldata` may be null when `calldata_len == 0`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct StarknetCall {
+    pub to: FeltBytes,
+    pub selector: FeltBytes,
+    pub calldata: *const FeltBytes,
+    pub calldata_len: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Call {
+    pub to: Felt,
+    pub selector: Felt,
+    pub calldata: Vec<Felt>,
+}
+
+/// Read `len` calls from `ptr`; null is accepted when `len == 0`.
+/// Returns `None` for a null array or calldata pointer with a non-zero
+/// length (`NullPointer`).
+pub(crate) unsafe fn calls_from_raw(ptr: *const StarknetCall, len: usize) -> Option<Vec<Call>> {
+    if len == 0 {
+        return Some(Vec::new());
+    }
+    if ptr.is_null() {
+        return None;
+    }
+
+    core::slice::from_raw_parts(ptr, len)
+        .iter()
+        .map(|call| {
+            Some(Call {
+                to: felt_from_bytes(&call.to)?,
+                selector: felt_from_bytes(&call.selector)?,
+                calldata: felts_from_raw(call.calldata, call.calldata_len)?,
+            })
+        })
+        .collect()
+}
+
+/// `__execute__` calldata for `calls`, in the Cairo 0 layout if `legacy`
+pub(crate) fn execute_calldata(calls: &[Call], legacy: bool) -> Vec<Felt> {
+    let mut felts = vec![Felt::from(calls.len())];
+    if legacy {
+        let mut offset = 0;
+        for call in calls {
+            felts.extend([
+                call.to,
+                call.selector,
+                Felt::from(offset),
+                Felt::from(call.calldata.len()),
+            ]);
+            offset += call.calldata.len();
+        }
+        felts.push(Felt::from(offset));
+        for call in calls {
+            felts.extend(&call.calldata);
+        }
+    } else {
+        for call in calls {
+            felts.extend([call.to, call.selector, Felt::from(call.calldata.len())]);
+            felts.extend(&call.calldata);
+        }
+    }
+    felts
+}
+
+/// Encode a multicall as account `__execute__` calldata
+///
+/// `legacy` selects the Cairo 0 layout (call headers with offsets, then the
+/// flattened calldata); otherwise the Cairo 1 `Array<Call>` layout is used.
+/// `calls` may be null when `calls_len == 0`. `out_count` holds the capacity
+/// of `out_felts` on input and the number of felts on output; if the array is
+/// too small, the required count is written and `BufferTooSmall` is returned.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_build_execute_calldata")]
+pub unsafe extern "C" fn starknet_build_execute_calldata(
+    calls: *const StarknetCall,
+    calls_len: usize,
+    legacy: bool,
+    out_felts: *mut FeltBytes,
+    out_count: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_count);
+        let calls = match calls_from_raw(calls, calls_len) {
+            Some(c) => c,
+            None => return null_pointer("calls"),
+        };
+
+        write_felts_out(&execute_calldata(&calls, legacy), out_felts, out_count)
+    })
+}
+
+/// Encode a multicall as account `__execute__` calldata and stream it to
+/// `callback`
+///
+/// Same as `starknet_build_execute_calldata`, but the felts are delivered to
+/// `callback` (with `ctx`) in chunks; see `stream`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_build_execute_calldata_stream")]
+pub unsafe extern "C" fn starknet_build_execute_calldata_stream(
+    calls: *const StarknetCall,
+    calls_len: usize,
+    legacy: bool,
+    callback: StarkChunkCallback,
+    ctx: *mut c_void,
+) -> StarkResult {
+    ffi_guard(|| {
+        let calls = match calls_from_raw(calls, calls_len) {
+            Some(c) => c,
+            None => return null_pointer("calls"),
+        };
+
+        stream_felts(&execute_calldata(&calls, legacy), callback, ctx)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::felt_to_bytes;
+    use crate::stream::tests::collect_chunks;
+
+    fn felt_bytes(value: u64) -> FeltBytes {
+        felt_to_bytes(&Felt::from(value))
+    }
+
+    fn felts(values: &[u64]) -> Vec<Felt> {
+        values.iter().map(|&v| Felt::from(v)).collect()
+    }
+
+    fn calls() -> Vec<Call> {
+        vec![
+            Call {
+                to: Felt::from(0x10u64),
+                selector: Felt::from(0x20u64),
+                calldata: felts(&[1, 2]),
+            },
+            Call {
+                to: Felt::from(0x11u64),
+                selector: Felt::from(0x21u64),
+                calldata: Vec::new(),
+            },
+            Call {
+                to: Felt::from(0x12u64),
+                selector: Felt::from(0x22u64),
+                calldata: felts(&[3]),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_execute_calldata_cairo1() {
+        assert_eq!(
+            execute_calldata(&calls(), false),
+            felts(&[3, 0x10, 0x20, 2, 1, 2, 0x11, 0x21, 0, 0x12, 0x22, 1, 3])
+        );
+        assert_eq!(execute_calldata(&[], false), felts(&[0]));
+    }
+
+    #[test]
+    fn test_execute_calldata_legacy() {
+        assert_eq!(
+            execute_calldata(&calls(), true),
+            felts(&[3, 0x10, 0x20, 0, 2, 0x11, 0x21, 2, 0, 0x12, 0x22, 2, 1, 3, 1, 2, 3])
+        );
+        assert_eq!(execute_calldata(&[], true), felts(&[0, 0]));
+    }
+
+    #[test]
+    fn test_build_execute_calldata() {
+        let calldata = [felt_bytes(1), felt_bytes(2)];
+        let raw = [StarknetCall {
+            to: felt_bytes(0x10),
+            selector: felt_bytes(0x20),
+            calldata: calldata.as_ptr(),
+            calldata_len: calldata.len(),
+        }];
+        let mut out = [[0u8; 32]; 6];
+
+        unsafe {
+            let mut count = 2;
+            let result = starknet_build_execute_calldata(
+                raw.as_ptr(),
+                1,
+                false,
+                out.as_mut_ptr(),
+                &mut count,
+            );
+            assert_eq!(result, StarkResult::BufferTooSmall);
+            assert_eq!(count, 6);
+
+            let result = starknet_build_execute_calldata(
+                raw.as_ptr(),
+                1,
+                false,
+                out.as_mut_ptr(),
+                &mut count,
+            );
+            assert_eq!(result, StarkResult::Success);
+
+            let mut count = 0;
+            let result = starknet_build_execute_calldata(
+                core::ptr::null(),
+                1,
+                true,
+                out.as_mut_ptr(),
+                &mut count,
+            );
+            assert_eq!(result, StarkResult::NullPointer);
+        }
+        let expected: Vec<FeltBytes> = [1, 0x10, 0x20, 2, 1, 2]
+            .into_iter()
+            .map(felt_bytes)
+            .collect();
+        assert_eq!(out.to_vec(), expected);
+    }
+
+    #[test]
+    fn test_build_execute_calldata_stream() {
+        let raw = [StarknetCall {
+            to: felt_bytes(0x10),
+            selector: felt_bytes(0x20),
+            calldata: core::ptr::null(),
+            calldata_len: 0,
+        }];
+        let mut chunks: Vec<Vec<u8>> = Vec::new();
+        let ctx = (&mut chunks as *mut Vec<Vec<u8>>).cast();
+
+        unsafe {
+            let result = starknet_build_execute_calldata_stream(
+                raw.as_ptr(),
+                1,
+                false,
+                Some(collect_chunks),
+                ctx,
+            );
+            assert_eq!(result, StarkResult::Success);
+            let result = starknet_build_execute_calldata_stream(raw.as_ptr(), 1, false, None, ctx);
+            assert_eq!(result, StarkResult::NullPointer);
+        }
+        let expected: Vec<u8> = [1, 0x10, 0x20, 0]
+            .into_iter()
+            .flat_map(felt_bytes)
+            .collect();
+        assert_eq!(chunks, [expected]);
+    }
+}
@@ -0,0 +1,634 @@
+//! Cairo calldata encoding and decoding
+//!
+//! A `CalldataBuilder` appends values with the Cairo serde rules and hands
+//! back the flat felt array a contract call expects; a `CalldataReader`
+//! walks such an array (call results, event data) with the same rules:
+//!
+//! - `felt252`, `ContractAddress`, integers up to `u128`: one felt
+//! - `bool`: `0` or `1`
+//! - `u256`: `[low, high]`
+//! - `Array<felt252>` / `Span<felt252>`: `[len, elements...]`
+//! - `ByteArray`: see `byte_array`
+//!
+//! Structs are their fields in order and tuples their elements in order, so
+//! they are built by pushing each member and read by reading each member.
+
+use alloc::vec::Vec;
+use core::ffi::c_void;
+
+use starknet_crypto::Felt;
+
+use crate::byte_array::{decode_byte_array, encode_byte_array};
+use crate::handle::StarkHandle;
+use crate::stream::{stream_felts, StarkChunkCallback};
+use crate::u256::{u256_from_halves, u256_to_halves};
+use crate::{
+    bytes_from_raw, error::null_pointer, felt_from_bytes, felt_to_bytes, felts_from_raw, ffi_guard,
+    ffi_guard_void, write_bytes_out, write_felts_out, FeltBytes, StarkResult,
+};
+
+/// Calldata under construction (opaque)
+pub struct CalldataBuilder {
+    felts: Vec<Felt>,
+}
+
+impl StarkHandle for CalldataBuilder {
+    const NAME: &'static str = "CalldataBuilder";
+}
+
+/// Create an empty calldata builder
+///
+/// Writes an owned handle to `out_builder`; release it with
+/// `calldata_builder_free`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_builder_new")]
+pub unsafe extern "C" fn calldata_builder_new(
+    out_builder: *mut *mut CalldataBuilder,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_builder);
+
+        *out_builder = CalldataBuilder { felts: Vec::new() }.into_handle();
+        StarkResult::Success
+    })
+}
+
+/// Append a single felt (`felt252`, `ContractAddress`, `u8`..`u128`, ...)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_builder_push_felt")]
+pub unsafe extern "C" fn calldata_builder_push_felt(
+    builder: *mut CalldataBuilder,
+    value: *const FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(builder, value);
+        let builder = match CalldataBuilder::from_handle_mut(builder) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+        let value = match felt_from_bytes(&*value) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+
+        builder.felts.push(value);
+        StarkResult::Success
+    })
+}
+
+/// Append a `u256` given as 32 big-endian bytes (`[low, high]`)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_builder_push_u256")]
+pub unsafe extern "C" fn calldata_builder_push_u256(
+    builder: *mut CalldataBuilder,
+    value: *const [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(builder, value);
+        let builder = match CalldataBuilder::from_handle_mut(builder) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+
+        let (low, high) = u256_to_halves(&*value);
+        builder.felts.extend([low, high]);
+        StarkResult::Success
+    })
+}
+
+/// Append a `bool` (`0` or `1`)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_builder_push_bool")]
+pub unsafe extern "C" fn calldata_builder_push_bool(
+    builder: *mut CalldataBuilder,
+    value: bool,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(builder);
+        let builder = match CalldataBuilder::from_handle_mut(builder) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+
+        builder.felts.push(Felt::from(value));
+        StarkResult::Success
+    })
+}
+
+/// Append an `Array<felt252>` or `Span<felt252>` (length, then elements)
+///
+/// `values` may be null when `len == 0`. Arrays of other element types are
+/// built by pushing the length with `calldata_builder_push_felt` and then
+/// each element.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_builder_push_array")]
+pub unsafe extern "C" fn calldata_builder_push_array(
+    builder: *mut CalldataBuilder,
+    values: *const FeltBytes,
+    len: usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(builder);
+        let builder = match CalldataBuilder::from_handle_mut(builder) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+        let values = match felts_from_raw(values, len) {
+            Some(v) => v,
+            None => return null_pointer("values"),
+        };
+
+        let felts = &mut builder.felts;
+        felts.push(Felt::from(values.len()));
+        felts.extend(values);
+        StarkResult::Success
+    })
+}
+
+/// Append a `ByteArray` (typically a UTF-8 string)
+///
+/// `data` may be null when `len == 0`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_builder_push_bytearray")]
+pub unsafe extern "C" fn calldata_builder_push_bytearray(
+    builder: *mut CalldataBuilder,
+    data: *const u8,
+    len: usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(builder);
+        let builder = match CalldataBuilder::from_handle_mut(builder) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+        let data = match bytes_from_raw(data, len) {
+            Some(d) => d,
+            None => return null_pointer("data"),
+        };
+
+        builder.felts.extend(encode_byte_array(data));
+        StarkResult::Success
+    })
+}
+
+/// Copy the calldata built so far to `out_felts`
+///
+/// `out_count` holds the capacity of `out_felts` on input and the number of
+/// felts on output; if the array is too small, the required count is written
+/// and `BufferTooSmall` is returned. The builder is left unchanged, so it can
+/// be queried for its size first and can keep growing afterwards.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_builder_finish")]
+pub unsafe extern "C" fn calldata_builder_finish(
+    builder: *const CalldataBuilder,
+    out_felts: *mut FeltBytes,
+    out_count: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(builder, out_count);
+        let builder = match CalldataBuilder::from_handle(builder) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+
+        write_felts_out(&builder.felts, out_felts, out_count)
+    })
+}
+
+/// Stream the calldata built so far to `callback`
+///
+/// The felts are delivered to `callback` (with `ctx`) in chunks; see
+/// `stream`. The builder is left unchanged.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_builder_finish_stream")]
+pub unsafe extern "C" fn calldata_builder_finish_stream(
+    builder: *const CalldataBuilder,
+    callback: StarkChunkCallback,
+    ctx: *mut c_void,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(builder);
+        let builder = match CalldataBuilder::from_handle(builder) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+
+        stream_felts(&builder.felts, callback, ctx)
+    })
+}
+
+/// Free a calldata builder (null is a no-op)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_builder_free")]
+pub unsafe extern "C" fn calldata_builder_free(builder: *mut CalldataBuilder) {
+    ffi_guard_void(|| CalldataBuilder::free_handle(builder))
+}
+
+/// Cursor over serialized calldata (opaque)
+///
+/// Every read is bounds-checked against the remaining felts. A read that
+/// fails leaves the cursor where it was.
+pub struct CalldataReader {
+    felts: Vec<Felt>,
+    position: usize,
+}
+
+impl StarkHandle for CalldataReader {
+    const NAME: &'static str = "CalldataReader";
+}
+
+impl CalldataReader {
+    fn remaining(&self) -> &[Felt] {
+        &self.felts[self.position..]
+    }
+
+    /// Length prefix and elements of an `Array<felt252>` at the cursor
+    fn peek_array(&self) -> Option<&[Felt]> {
+        let (len, elements) = self.remaining().split_first()?;
+        let len: usize = (*len).try_into().ok()?;
+        elements.get(..len)
+    }
+}
+
+/// Create a reader over `count` serialized felts
+///
+/// The felts are copied, so `felts` can be released afterwards. `felts` may
+/// be null when `count == 0`. Writes an owned handle to `out_reader`;
+/// release it with `calldata_reader_free`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_reader_new")]
+pub unsafe extern "C" fn calldata_reader_new(
+    felts: *const FeltBytes,
+    count: usize,
+    out_reader: *mut *mut CalldataReader,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_reader);
+        let felts = match felts_from_raw(felts, count) {
+            Some(f) => f,
+            None => return null_pointer("felts"),
+        };
+
+        *out_reader = CalldataReader { felts, position: 0 }.into_handle();
+        StarkResult::Success
+    })
+}
+
+/// Number of felts left to read
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_reader_remaining")]
+pub unsafe extern "C" fn calldata_reader_remaining(
+    reader: *const CalldataReader,
+    out_count: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(reader, out_count);
+        let reader = match CalldataReader::from_handle(reader) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+
+        *out_count = reader.remaining().len();
+        StarkResult::Success
+    })
+}
+
+/// Read a single felt
+///
+/// Returns `InvalidInput` if no felts are left.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_reader_read_felt")]
+pub unsafe extern "C" fn calldata_reader_read_felt(
+    reader: *mut CalldataReader,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(reader, out);
+        let reader = match CalldataReader::from_handle_mut(reader) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+        let value = match reader.remaining().first() {
+            Some(f) => *f,
+            None => return StarkResult::InvalidInput,
+        };
+
+        *out = felt_to_bytes(&value);
+        reader.position += 1;
+        StarkResult::Success
+    })
+}
+
+/// Read a `u256` (`[low, high]`) as 32 big-endian bytes
+///
+/// Returns `InvalidInput` if fewer than two felts are left or either half is
+/// not below 2^128.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_reader_read_u256")]
+pub unsafe extern "C" fn calldata_reader_read_u256(
+    reader: *mut CalldataReader,
+    out: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(reader, out);
+        let reader = match CalldataReader::from_handle_mut(reader) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+        let value = match reader.remaining() {
+            [low, high, ..] => u256_from_halves(low, high),
+            _ => None,
+        };
+
+        match value {
+            Some(value) => {
+                *out = value;
+                reader.position += 2;
+                StarkResult::Success
+            }
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+/// Read an `Array<felt252>` / `Span<felt252>` into `out_felts`
+///
+/// `out_count` holds the capacity of `out_felts` on input and the array
+/// length on output; if the array is too small, the required count is
+/// written, `BufferTooSmall` is returned and the cursor does not move, so
+/// the read can be retried. Returns `InvalidInput` if the length prefix
+/// runs past the end of the data.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_reader_read_array")]
+pub unsafe extern "C" fn calldata_reader_read_array(
+    reader: *mut CalldataReader,
+    out_felts: *mut FeltBytes,
+    out_count: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(reader, out_count);
+        let reader = match CalldataReader::from_handle_mut(reader) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+        let elements = match reader.peek_array() {
+            Some(e) => e,
+            None => return StarkResult::InvalidInput,
+        };
+        let consumed = elements.len() + 1;
+
+        let result = write_felts_out(elements, out_felts, out_count);
+        if result == StarkResult::Success {
+            reader.position += consumed;
+        }
+        result
+    })
+}
+
+/// Read a `ByteArray` into `out`
+///
+/// `out_len` follows the usual capacity convention; as with
+/// `calldata_reader_read_array`, the cursor only moves on success. Returns
+/// `InvalidInput` for a truncated or non-canonical `ByteArray`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_reader_read_bytearray")]
+pub unsafe extern "C" fn calldata_reader_read_bytearray(
+    reader: *mut CalldataReader,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(reader, out_len);
+        let reader = match CalldataReader::from_handle_mut(reader) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+        let (data, consumed) = match decode_byte_array(reader.remaining()) {
+            Some(d) => d,
+            None => return StarkResult::InvalidInput,
+        };
+
+        let result = write_bytes_out(&data, out, out_len);
+        if result == StarkResult::Success {
+            reader.position += consumed;
+        }
+        result
+    })
+}
+
+/// Free a calldata reader (null is a no-op)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_calldata_reader_free")]
+pub unsafe extern "C" fn calldata_reader_free(reader: *mut CalldataReader) {
+    ffi_guard_void(|| CalldataReader::free_handle(reader))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::felt_to_bytes;
+    use crate::stream::tests::collect_chunks;
+
+    fn felt_bytes(value: u64) -> FeltBytes {
+        felt_to_bytes(&Felt::from(value))
+    }
+
+    #[test]
+    fn test_calldata_builder() {
+        // recipient, amount, tags, memo, flag and an empty array
+        let recipient = felt_bytes(0xabc);
+        let mut amount = [0u8; 32];
+        amount[15] = 0x02;
+        amount[31] = 0x05;
+        let tags = [felt_bytes(7), felt_bytes(8)];
+        let mut out = [[0u8; 32]; 12];
+
+        unsafe {
+            let mut builder = core::ptr::null_mut();
+            assert_eq!(calldata_builder_new(&mut builder), StarkResult::Success);
+            assert_eq!(
+                calldata_builder_push_felt(builder, &recipient),
+                StarkResult::Success
+            );
+            assert_eq!(
+                calldata_builder_push_u256(builder, &amount),
+                StarkResult::Success
+            );
+            assert_eq!(
+                calldata_builder_push_array(builder, tags.as_ptr(), tags.len()),
+                StarkResult::Success
+            );
+            assert_eq!(
+                calldata_builder_push_bytearray(builder, b"hi".as_ptr(), 2),
+                StarkResult::Success
+            );
+            assert_eq!(
+                calldata_builder_push_bool(builder, true),
+                StarkResult::Success
+            );
+            assert_eq!(
+                calldata_builder_push_array(builder, core::ptr::null(), 0),
+                StarkResult::Success
+            );
+
+            let mut count = 0;
+            let result = calldata_builder_finish(builder, core::ptr::null_mut(), &mut count);
+            assert_eq!(result, StarkResult::BufferTooSmall);
+            assert_eq!(count, 11);
+
+            let mut count = out.len();
+            let result = calldata_builder_finish(builder, out.as_mut_ptr(), &mut count);
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(count, 11);
+
+            let mut chunks: Vec<Vec<u8>> = Vec::new();
+            let ctx = (&mut chunks as *mut Vec<Vec<u8>>).cast();
+            let result = calldata_builder_finish_stream(builder, Some(collect_chunks), ctx);
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(chunks, [out[..11].concat()]);
+            calldata_builder_free(builder);
+        }
+
+        let expected = [
+            0xabc, 5, 2, // recipient, amount low, amount high
+            2, 7, 8, // tags
+            0, 0x6869, 2, // "hi"
+            1, // true
+            0, // empty array
+        ];
+        let expected: Vec<FeltBytes> = expected.into_iter().map(felt_bytes).collect();
+        assert_eq!(&out[..11], &expected[..]);
+    }
+
+    #[test]
+    fn test_calldata_builder_rejects_invalid_input() {
+        unsafe {
+            assert_eq!(
+                calldata_builder_new(core::ptr::null_mut()),
+                StarkResult::NullPointer
+            );
+            assert_eq!(
+                calldata_builder_push_bool(core::ptr::null_mut(), true),
+                StarkResult::NullPointer
+            );
+
+            let mut builder = core::ptr::null_mut();
+            calldata_builder_new(&mut builder);
+            assert_eq!(
+                calldata_builder_push_felt(core::ptr::null_mut(), &[0u8; 32]),
+                StarkResult::NullPointer
+            );
+            assert_eq!(
+                calldata_builder_push_array(builder, core::ptr::null(), 1),
+                StarkResult::NullPointer
+            );
+
+            // Nothing was appended by the failed calls
+            let mut count = 0;
+            let result = calldata_builder_finish(builder, core::ptr::null_mut(), &mut count);
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(count, 0);
+            calldata_builder_free(builder);
+            calldata_builder_free(core::ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_calldata_reader() {
+        // u256 amount, tags, memo "hi", then an address
+        let data: Vec<FeltBytes> = [5, 2, 2, 7, 8, 0, 0x6869, 2, 0xabc]
+            .into_iter()
+            .map(felt_bytes)
+            .collect();
+        let mut amount = [0u8; 32];
+        let mut tags = [[0u8; 32]; 2];
+        let mut memo = [0u8; 2];
+        let mut address = [0u8; 32];
+        let mut remaining = 0;
+
+        unsafe {
+            let mut reader = core::ptr::null_mut();
+            let result = calldata_reader_new(data.as_ptr(), data.len(), &mut reader);
+            assert_eq!(result, StarkResult::Success);
+
+            assert_eq!(
+                calldata_reader_read_u256(reader, &mut amount),
+                StarkResult::Success
+            );
+
+            // Too small: the required count is reported and nothing is consumed
+            let mut count = 1;
+            let result = calldata_reader_read_array(reader, tags.as_mut_ptr(), &mut count);
+            assert_eq!(result, StarkResult::BufferTooSmall);
+            assert_eq!(count, 2);
+            calldata_reader_remaining(reader, &mut remaining);
+            assert_eq!(remaining, 7);
+
+            let result = calldata_reader_read_array(reader, tags.as_mut_ptr(), &mut count);
+            assert_eq!(result, StarkResult::Success);
+
+            let mut len = memo.len();
+            let result = calldata_reader_read_bytearray(reader, memo.as_mut_ptr(), &mut len);
+            assert_eq!(result, StarkResult::Success);
+
+            assert_eq!(
+                calldata_reader_read_felt(reader, &mut address),
+                StarkResult::Success
+            );
+            calldata_reader_remaining(reader, &mut remaining);
+            assert_eq!(remaining, 0);
+            assert_eq!(
+                calldata_reader_read_felt(reader, &mut address),
+                StarkResult::InvalidInput
+            );
+            calldata_reader_free(reader);
+        }
+
+        assert_eq!(amount[15], 2);
+        assert_eq!(amount[31], 5);
+        assert_eq!(tags, [felt_bytes(7), felt_bytes(8)]);
+        assert_eq!(&memo, b"hi");
+        assert_eq!(address, felt_bytes(0xabc));
+    }
+
+    #[test]
+    fn test_calldata_reader_bounds() {
+        // Array length prefix past the end, then a u256 with a 2^128 half
+        let data = [
+            felt_bytes(3),
+            felt_bytes(1),
+            felt_to_bytes(&Felt::TWO.pow(128u32)),
+        ];
+        let mut out = [[0u8; 32]; 3];
+        let mut u256 = [0u8; 32];
+
+        unsafe {
+            let mut reader = core::ptr::null_mut();
+            calldata_reader_new(data.as_ptr(), data.len(), &mut reader);
+
+            let mut count = out.len();
+            let result = calldata_reader_read_array(reader, out.as_mut_ptr(), &mut count);
+            assert_eq!(result, StarkResult::InvalidInput);
+            let mut len = 0;
+            let result = calldata_reader_read_bytearray(reader, core::ptr::null_mut(), &mut len);
+            assert_eq!(result, StarkResult::InvalidInput);
+
+            let mut first = [0u8; 32];
+            calldata_reader_read_felt(reader, &mut first);
+            assert_eq!(
+                calldata_reader_read_u256(reader, &mut u256),
+                StarkResult::InvalidInput
+            );
+            let mut remaining = 0;
+            calldata_reader_remaining(reader, &mut remaining);
+            assert_eq!(remaining, 2);
+            calldata_reader_free(reader);
+
+            assert_eq!(
+                calldata_reader_new(core::ptr::null(), 1, &mut reader),
+                StarkResult::NullPointer
+            );
+            calldata_reader_free(core::ptr::null_mut());
+        }
+    }
+}
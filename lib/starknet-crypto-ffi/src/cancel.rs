@@ -0,0 +1,151 @@
+//! Cancellation of long-running operations
+//!
+//! A `StarkCancelToken` is created by the host and passed to operations that
+//! may run for seconds (such as `starknet_verify_batch`). Any thread may
+//! cancel it while such an operation runs; the operation polls the token
+//! between units of work, stops, and returns `Cancelled` without writing its
+//! outputs. A token stays cancelled until it is reset, so it can abort a
+//! whole sequence of calls. Operations take the token as an optional
+//! argument: null never cancels.
+
+// Cancellable operations so far: ECDSA batch verification, nonce grinding
+#![cfg_attr(not(any(feature = "ecdsa", feature = "hashing")), allow(dead_code))]
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::handle::StarkHandle;
+use crate::{error, ffi_guard, ffi_guard_void, StarkResult};
+
+/// Cancellation flag shared between the host and running operations
+pub struct StarkCancelToken {
+    cancelled: AtomicBool,
+}
+
+impl StarkHandle for StarkCancelToken {
+    const NAME: &'static str = "StarkCancelToken";
+}
+
+impl StarkCancelToken {
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Borrow an optional token argument (null: never cancelled)
+pub(crate) unsafe fn optional_token<'a>(
+    ptr: *const StarkCancelToken,
+) -> Result<Option<&'a StarkCancelToken>, StarkResult> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    StarkCancelToken::from_handle(ptr).map(Some)
+}
+
+/// Whether an operation holding `token` must stop
+pub(crate) fn is_cancelled(token: Option<&StarkCancelToken>) -> bool {
+    token.is_some_and(StarkCancelToken::is_cancelled)
+}
+
+/// Record that the operation was cancelled and return `Cancelled`
+pub(crate) fn cancelled(operation: &str) -> StarkResult {
+    error::set_detail(alloc::format!("{operation} stopped by its cancel token"));
+    StarkResult::Cancelled
+}
+
+/// Create a cancel token (not cancelled)
+///
+/// Writes an owned handle to `out_token`; release it with
+/// `stark_cancel_token_free` once no operation uses it.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_cancel_token_new")]
+pub unsafe extern "C" fn stark_cancel_token_new(
+    out_token: *mut *mut StarkCancelToken,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_token);
+        *out_token = StarkCancelToken {
+            cancelled: AtomicBool::new(false),
+        }
+        .into_handle();
+        StarkResult::Success
+    })
+}
+
+/// Cancel the operations using `token` (from any thread)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_cancel_token_cancel")]
+pub unsafe extern "C" fn stark_cancel_token_cancel(token: *const StarkCancelToken) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(token);
+        match StarkCancelToken::from_handle(token) {
+            Ok(token) => token.cancelled.store(true, Ordering::Relaxed),
+            Err(e) => return e,
+        }
+        StarkResult::Success
+    })
+}
+
+/// Clear the cancellation, so the token can be used again
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_cancel_token_reset")]
+pub unsafe extern "C" fn stark_cancel_token_reset(token: *const StarkCancelToken) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(token);
+        match StarkCancelToken::from_handle(token) {
+            Ok(token) => token.cancelled.store(false, Ordering::Relaxed),
+            Err(e) => return e,
+        }
+        StarkResult::Success
+    })
+}
+
+/// Whether `token` is cancelled (false for null)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_cancel_token_is_cancelled")]
+pub unsafe extern "C" fn stark_cancel_token_is_cancelled(token: *const StarkCancelToken) -> bool {
+    let mut cancelled = false;
+    ffi_guard(|| match optional_token(token) {
+        Ok(token) => {
+            cancelled = is_cancelled(token);
+            StarkResult::Success
+        }
+        Err(e) => e,
+    });
+    cancelled
+}
+
+/// Free a cancel token (null is a no-op)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_cancel_token_free")]
+pub unsafe extern "C" fn stark_cancel_token_free(token: *mut StarkCancelToken) {
+    ffi_guard_void(|| StarkCancelToken::free_handle(token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_token_lifecycle() {
+        let mut token = core::ptr::null_mut();
+        unsafe {
+            assert_eq!(stark_cancel_token_new(&mut token), StarkResult::Success);
+            assert!(!stark_cancel_token_is_cancelled(token));
+
+            assert_eq!(stark_cancel_token_cancel(token), StarkResult::Success);
+            assert!(stark_cancel_token_is_cancelled(token));
+            // Cancelling twice is harmless
+            assert_eq!(stark_cancel_token_cancel(token), StarkResult::Success);
+
+            assert_eq!(stark_cancel_token_reset(token), StarkResult::Success);
+            assert!(!stark_cancel_token_is_cancelled(token));
+
+            assert!(!stark_cancel_token_is_cancelled(core::ptr::null()));
+            assert_eq!(
+                stark_cancel_token_cancel(core::ptr::null()),
+                StarkResult::NullPointer
+            );
+            stark_cancel_token_free(token);
+        }
+    }
+}
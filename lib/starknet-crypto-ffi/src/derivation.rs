@@ -0,0 +1,534 @@
+//! Stark key derivation
+//!
+//! `grind_key` is StarkWare's `grindKey` (starkware-crypto-utils,
+//! @scure/starknet): it maps 32 bytes of seed material, typically the `r` of
+//! an Ethereum signature, to a private key with negligible bias. Candidates
+//! are `sha256(seed || i)` for i = 0, 1, ... (i as minimal big-endian bytes,
+//! `0x00` for zero); the first one below the largest multiple of n under
+//! 2^256 is reduced mod n.
+//!
+//! EIP-2645 derives the grinding seed from a BIP-32 (secp256k1) key at
+//! `m/2645'/layer'/application'/eth_address_1'/eth_address_2'/index`, where
+//! layer and application are the low 31 bits of `sha256(name)` and the
+//! Ethereum address is split into its low two 31-bit limbs.
+//!
+//! Derivation paths are strings such as `m/44'/9004'/0'/0/0`; hardened
+//! components take a `'` or `h` suffix. Starknet wallets (Argent X, Braavos)
+//! derive Stark keys the same way as EIP-2645: a secp256k1 BIP-32 key at the
+//! path, ground with `grind_key`. SLIP-10 defines no Stark curve variant, so
+//! there is no native Stark-curve child derivation.
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use hmac::{Hmac, Mac};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::elliptic_curve::PrimeField;
+use k256::{Scalar, SecretKey};
+use sha2::{Digest, Sha256, Sha512};
+use starknet_crypto::Felt;
+use starknet_types_core::felt::NonZeroFelt;
+use zeroize::Zeroizing;
+
+use crate::{
+    bytes_from_raw,
+    error::{invalid_input, null_pointer},
+    felt_to_bytes, ffi_guard, FeltBytes, StarkResult, EC_ORDER,
+};
+
+/// Hardened BIP-32 child index offset
+pub(crate) const HARDENED: u32 = 0x8000_0000;
+const EIP2645_PURPOSE: u32 = 2645;
+const LOW_31_BITS: u32 = 0x7fff_ffff;
+/// BIP-32 serializes the depth as one byte
+const MAX_PATH_DEPTH: usize = 255;
+
+/// 2^256 - (2^256 mod n)
+const GRIND_LIMIT: [u8; 32] = [
+    0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x0e, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xf7,
+    0x38, 0xa1, 0x3b, 0x4b, 0x92, 0x0e, 0x94, 0x11, 0xae, 0x6d, 0xa5, 0xf4, 0x0b, 0x03, 0x58, 0xb1,
+];
+
+/// Reduce a 256-bit big-endian integer mod n
+fn reduce_mod_order(bytes: &[u8; 32]) -> Felt {
+    let order = NonZeroFelt::from_felt_unchecked(EC_ORDER);
+    let high = Felt::from_bytes_be_slice(&bytes[..16]);
+    let low = Felt::from_bytes_be_slice(&bytes[16..]);
+    // (high·2^128 mod n) + low < n + 2^128 < p, so the sum does not wrap
+    (high.mul_mod(&Felt::TWO.pow(128u32), &order) + low).mod_floor(&order)
+}
+
+pub(crate) fn grind_key(seed: &[u8; 32]) -> Felt {
+    for i in 0u64.. {
+        let index = i.to_be_bytes();
+        let start = index.iter().position(|&b| b != 0).unwrap_or(7);
+        let candidate: [u8; 32] = Sha256::new()
+            .chain_update(seed)
+            .chain_update(&index[start..])
+            .finalize()
+            .into();
+        // Big-endian byte arrays compare like the integers they encode
+        if candidate < GRIND_LIMIT {
+            return reduce_mod_order(&candidate);
+        }
+    }
+    unreachable!("each candidate is accepted with probability > 0.96")
+}
+
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> Zeroizing<[u8; 64]> {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts any key length");
+    for part in data {
+        mac.update(part);
+    }
+    Zeroizing::new(mac.finalize().into_bytes().into())
+}
+
+/// BIP-32 secp256k1 private key derivation from a seed along `path`
+///
+/// Returns `None` in the (probability < 2^-127) cases where BIP-32 declares
+/// a key invalid.
+pub(crate) fn bip32_derive_secp256k1(seed: &[u8], path: &[u32]) -> Option<Zeroizing<[u8; 32]>> {
+    let master = hmac_sha512(b"Bitcoin seed", &[seed]);
+    let mut key = SecretKey::from_slice(&master[..32]).ok()?;
+    let mut chain_code = Zeroizing::new(<[u8; 32]>::try_from(&master[32..]).unwrap());
+
+    for &index in path {
+        let data = if index >= HARDENED {
+            hmac_sha512(
+                &chain_code[..],
+                &[&[0], &key.to_bytes(), &index.to_be_bytes()],
+            )
+        } else {
+            let public_key = key.public_key().to_encoded_point(true);
+            hmac_sha512(
+                &chain_code[..],
+                &[public_key.as_bytes(), &index.to_be_bytes()],
+            )
+        };
+        let tweak = Zeroizing::new(<[u8; 32]>::try_from(&data[..32]).unwrap());
+        let tweak = Zeroizing::new(Option::<Scalar>::from(Scalar::from_repr((*tweak).into()))?);
+        let child = Zeroizing::new(*tweak + key.to_nonzero_scalar().as_ref());
+        key = SecretKey::from_bytes(&child.to_repr()).ok()?;
+        chain_code.copy_from_slice(&data[32..]);
+    }
+    Some(Zeroizing::new(key.to_bytes().into()))
+}
+
+/// Parse a path such as `m/44'/9004'/0'/0/0` into child indices
+pub(crate) fn parse_derivation_path(path: &str) -> Option<Vec<u32>> {
+    let mut components = path.split('/');
+    if components.next()? != "m" {
+        return None;
+    }
+
+    let mut indices = Vec::new();
+    for component in components {
+        let (number, hardened) = match component.strip_suffix(['\'', 'h', 'H']) {
+            Some(number) => (number, true),
+            None => (component, false),
+        };
+        // Reject signs, whitespace and empty components that u32 parsing allows
+        if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let index: u32 = number.parse().ok()?;
+        if index >= HARDENED {
+            return None;
+        }
+        indices.push(if hardened { index | HARDENED } else { index });
+    }
+    (indices.len() <= MAX_PATH_DEPTH).then_some(indices)
+}
+
+unsafe fn seed_from_raw<'a>(seed: *const u8, len: usize) -> Result<&'a [u8], StarkResult> {
+    let seed = bytes_from_raw(seed, len).ok_or_else(|| null_pointer("seed"))?;
+    if !(16..=64).contains(&seed.len()) {
+        return Err(invalid_input(format!(
+            "`seed` must be 16 to 64 bytes, got {}",
+            seed.len()
+        )));
+    }
+    Ok(seed)
+}
+
+unsafe fn path_from_raw(path: *const u8, len: usize) -> Result<Vec<u32>, StarkResult> {
+    let path = bytes_from_raw(path, len).ok_or_else(|| null_pointer("path"))?;
+    core::str::from_utf8(path)
+        .ok()
+        .and_then(parse_derivation_path)
+        .ok_or_else(|| invalid_input("`path` is not a valid derivation path"))
+}
+
+/// Low 31 bits of `sha256(name)`, as EIP-2645 encodes layer and application
+fn eip2645_name_index(name: &[u8]) -> u32 {
+    let hash = Sha256::digest(name);
+    u32::from_be_bytes(hash[28..].try_into().unwrap()) & LOW_31_BITS
+}
+
+pub(crate) fn eip2645_path(
+    layer: &[u8],
+    application: &[u8],
+    eth_address: &[u8; 20],
+    index: u32,
+) -> [u32; 6] {
+    let low = u64::from_be_bytes(eth_address[12..].try_into().unwrap());
+    [
+        EIP2645_PURPOSE | HARDENED,
+        eip2645_name_index(layer) | HARDENED,
+        eip2645_name_index(application) | HARDENED,
+        (low as u32 & LOW_31_BITS) | HARDENED,
+        ((low >> 31) as u32 & LOW_31_BITS) | HARDENED,
+        index,
+    ]
+}
+
+/// Derive a Stark private key from 32 bytes of seed material (StarkWare
+/// `grindKey`)
+///
+/// For "derive your Starknet key from an Ethereum wallet" flows, the seed is
+/// the `r` value of the Ethereum signature over the key derivation message.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_grind_key")]
+pub unsafe extern "C" fn stark_grind_key(
+    seed: *const [u8; 32],
+    out_private_key: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(seed, out_private_key);
+        *out_private_key = felt_to_bytes(&grind_key(&*seed));
+        StarkResult::Success
+    })
+}
+
+/// Derive a Stark private key along an EIP-2645 path
+///
+/// `seed` is the BIP-39 seed (16 to 64 bytes), `layer` and `application` the
+/// path names (e.g. "starkex", "starkdeployement"), and `index` the
+/// non-hardened account index. The BIP-32 key at the path is ground into a
+/// Stark key with `stark_grind_key`. Returns `InvalidInput` for a seed of the
+/// wrong length or a hardened `index`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_derive_eip2645")]
+pub unsafe extern "C" fn stark_derive_eip2645(
+    seed: *const u8,
+    seed_len: usize,
+    layer: *const u8,
+    layer_len: usize,
+    application: *const u8,
+    application_len: usize,
+    eth_address: *const [u8; 20],
+    index: u32,
+    out_private_key: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(eth_address, out_private_key);
+        let seed = match seed_from_raw(seed, seed_len) {
+            Ok(s) => s,
+            Err(e) => return e,
+        };
+        let layer = match bytes_from_raw(layer, layer_len) {
+            Some(s) => s,
+            None => return null_pointer("layer"),
+        };
+        let application = match bytes_from_raw(application, application_len) {
+            Some(s) => s,
+            None => return null_pointer("application"),
+        };
+        if index >= HARDENED {
+            return StarkResult::InvalidInput;
+        }
+
+        let path = eip2645_path(layer, application, &*eth_address, index);
+        match bip32_derive_secp256k1(seed, &path) {
+            Some(key) => {
+                *out_private_key = felt_to_bytes(&grind_key(&key));
+                StarkResult::Success
+            }
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+/// Derive a secp256k1 private key from a BIP-39 seed along a BIP-32 path
+///
+/// `seed` is 16 to 64 bytes and `path` a UTF-8 path string (not
+/// NUL-terminated) such as `m/44'/60'/0'/0/0`. Returns `InvalidInput` for a
+/// malformed path or seed, or in the negligible case where BIP-32 declares
+/// the derived key invalid.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bip32_derive")]
+pub unsafe extern "C" fn bip32_derive(
+    seed: *const u8,
+    seed_len: usize,
+    path: *const u8,
+    path_len: usize,
+    out_private_key: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_private_key);
+        let seed = match seed_from_raw(seed, seed_len) {
+            Ok(s) => s,
+            Err(e) => return e,
+        };
+        let path = match path_from_raw(path, path_len) {
+            Ok(p) => p,
+            Err(e) => return e,
+        };
+
+        match bip32_derive_secp256k1(seed, &path) {
+            Some(key) => {
+                *out_private_key = *key;
+                StarkResult::Success
+            }
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+/// Derive a Stark private key from a BIP-39 seed along a BIP-32 path
+///
+/// The secp256k1 key at `path` is ground into a Stark key, which is how
+/// Argent X and Braavos derive accounts (`m/44'/9004'/0'/0/<index>`).
+/// Errors as `bip32_derive`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_derive_path")]
+pub unsafe extern "C" fn stark_derive_path(
+    seed: *const u8,
+    seed_len: usize,
+    path: *const u8,
+    path_len: usize,
+    out_private_key: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_private_key);
+        let mut key = Zeroizing::new([0u8; 32]);
+        let result = bip32_derive(seed, seed_len, path, path_len, &mut *key);
+        if result == StarkResult::Success {
+            *out_private_key = felt_to_bytes(&grind_key(&key));
+        }
+        result
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex32(s: &str) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn test_grind_key_starknet_js_vector() {
+        let seed = hex32("86f3e7293141f20a8baff320e8ee4accb9d4a4bf2b4d295e8cee784db46e0519");
+        let mut private_key = [0u8; 32];
+
+        unsafe {
+            let result = stark_grind_key(&seed, &mut private_key);
+            assert_eq!(result, StarkResult::Success);
+        }
+        assert_eq!(
+            Felt::from_bytes_be(&private_key),
+            Felt::from_hex_unchecked(
+                "0x5c8c8683596c732541a59e03007b2d30dbbbb873556fe65b5fb63c16688f941"
+            )
+        );
+    }
+
+    #[test]
+    fn test_grind_key_rejects_first_candidate() {
+        // sha256(seed || 0x00) is above the limit, so index 1 is used
+        let seed = hex32("8f11b05da785e43e713d03774c6bd3405d99cd3024af334ffd68db663aa37034");
+        assert_eq!(
+            grind_key(&seed),
+            Felt::from_hex_unchecked(
+                "0x64d3d7e2da2b916b965b1530561fce9e9be6c571dc8452fd87320421fd38f62"
+            )
+        );
+    }
+
+    #[test]
+    fn test_bip32_vector_1() {
+        // BIP-32 test vector 1, m/0'/1
+        let seed = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f,
+        ];
+        assert_eq!(
+            *bip32_derive_secp256k1(&seed, &[HARDENED, 1]).unwrap(),
+            hex32("3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368")
+        );
+    }
+
+    #[test]
+    fn test_bip32_derive_path_string() {
+        // BIP-32 test vector 1, deepest chain
+        let seed: Vec<u8> = (0..16).collect();
+        let path = "m/0'/1/2h/2/1000000000";
+        let mut key = [0u8; 32];
+
+        unsafe {
+            let result = bip32_derive(
+                seed.as_ptr(),
+                seed.len(),
+                path.as_ptr(),
+                path.len(),
+                &mut key,
+            );
+            assert_eq!(result, StarkResult::Success);
+        }
+        assert_eq!(
+            key,
+            hex32("471b76e389e528d6de6d816857e012c5455051cad6660850e58372a6c3e6e7c8")
+        );
+    }
+
+    #[test]
+    fn test_parse_derivation_path() {
+        assert_eq!(parse_derivation_path("m"), Some(vec![]));
+        assert_eq!(
+            parse_derivation_path("m/44'/9004'/0'/0/7"),
+            Some(vec![44 | HARDENED, 9004 | HARDENED, HARDENED, 0, 7])
+        );
+        assert_eq!(
+            parse_derivation_path("m/1H/2147483647"),
+            Some(vec![1 | HARDENED, 0x7fffffff])
+        );
+
+        for bad in [
+            "",
+            "44'/0",
+            "m/",
+            "m//0",
+            "m/+1",
+            "m/0''",
+            "m/2147483648",
+            "m/0x1",
+            "M/0",
+        ] {
+            assert_eq!(parse_derivation_path(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_stark_derive_path_matches_eip2645() {
+        // The EIP-2645 vector below, spelled out as a path string
+        let seed = [
+            hex32("ed43abe2785ca9dfe74a2a8b05feaf2cdd4eda6847914674f88f5f114c3b694d"),
+            hex32("4ae930dd3808088ce40143430157ed79a66d7ff8d4919bd833d31d4a0f16f441"),
+        ]
+        .concat();
+        let path = "m/2645'/579218131'/211006541'/1961790679'/2135936222'/1";
+        let mut private_key = [0u8; 32];
+
+        unsafe {
+            let result = stark_derive_path(
+                seed.as_ptr(),
+                seed.len(),
+                path.as_ptr(),
+                path.len(),
+                &mut private_key,
+            );
+            assert_eq!(result, StarkResult::Success);
+
+            let result =
+                stark_derive_path(seed.as_ptr(), 8, path.as_ptr(), path.len(), &mut [0u8; 32]);
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+        assert_eq!(
+            Felt::from_bytes_be(&private_key),
+            Felt::from_hex_unchecked(
+                "0x8ed06dd293624465d596ef595ef1334e69aed24fabbb619c0443d958272937"
+            )
+        );
+    }
+
+    #[test]
+    fn test_eip2645_path() {
+        let eth_address = [
+            0xa4, 0x86, 0x4d, 0x97, 0x7b, 0x94, 0x43, 0x15, 0x38, 0x9d, 0x17, 0x65, 0xff, 0xa7,
+            0xe6, 0x6f, 0x74, 0xee, 0x8c, 0xd7,
+        ];
+        let path = eip2645_path(b"starkex", b"immutablex", &eth_address, 1);
+        assert_eq!(
+            path,
+            [
+                2645 | HARDENED,
+                579218131 | HARDENED,
+                211006541 | HARDENED,
+                1961790679 | HARDENED,
+                2135936222 | HARDENED,
+                1
+            ]
+        );
+    }
+
+    #[test]
+    fn test_derive_eip2645() {
+        // BIP-39 seed of "range mountain blast problem vibrant void vivid doctor
+        // cluster enough melody salt layer language laptop boat major space
+        // monkey unit glimpse pause change vibrant" (empty passphrase)
+        let seed = [
+            hex32("ed43abe2785ca9dfe74a2a8b05feaf2cdd4eda6847914674f88f5f114c3b694d"),
+            hex32("4ae930dd3808088ce40143430157ed79a66d7ff8d4919bd833d31d4a0f16f441"),
+        ]
+        .concat();
+        let eth_address: [u8; 20] =
+            hex32("000000000000000000000000a4864d977b944315389d1765ffa7e66f74ee8cd7")[12..]
+                .try_into()
+                .unwrap();
+        let (layer, application) = (b"starkex", b"immutablex");
+        let mut private_key = [0u8; 32];
+
+        unsafe {
+            let result = stark_derive_eip2645(
+                seed.as_ptr(),
+                seed.len(),
+                layer.as_ptr(),
+                layer.len(),
+                application.as_ptr(),
+                application.len(),
+                &eth_address,
+                1,
+                &mut private_key,
+            );
+            assert_eq!(result, StarkResult::Success);
+
+            let result = stark_derive_eip2645(
+                seed.as_ptr(),
+                seed.len(),
+                layer.as_ptr(),
+                layer.len(),
+                application.as_ptr(),
+                application.len(),
+                &eth_address,
+                HARDENED,
+                &mut private_key.clone(),
+            );
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+        assert_eq!(
+            Felt::from_bytes_be(&private_key),
+            Felt::from_hex_unchecked(
+                "0x8ed06dd293624465d596ef595ef1334e69aed24fabbb619c0443d958272937"
+            )
+        );
+    }
+
+    #[test]
+    fn test_reduce_mod_order() {
+        assert_eq!(reduce_mod_order(&EC_ORDER.to_bytes_be()), Felt::ZERO);
+        // The limit is a multiple of n
+        assert_eq!(reduce_mod_order(&GRIND_LIMIT), Felt::ZERO);
+        assert_eq!(
+            reduce_mod_order(&[0xff; 32]),
+            Felt::from_hex_unchecked(
+                "0x7fffffffffffdf10000000000000008c75ec4b46df16bee51925a0bf4fca74e"
+            )
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! ECIES encryption to a Stark public key
+//!
+//! Encrypts to the same key pair as `starknet_sign`, so anything holding a
+//! Starknet account key can receive encrypted memos or order flow. The
+//! sender draws an ephemeral key `e` and computes the shared secret as the x
+//! coordinate of `e·P`. Public keys are x-only; both lifts of an x give the
+//! same shared x, so no sign bit is needed.
+//!
+//! ```text
+//! key        = HKDF-SHA256(ikm = shared_x, salt = ephemeral_x || recipient_x,
+//!                          info = "STARK_ECIES")
+//! ciphertext = ephemeral_x (32) || XChaCha20-Poly1305(key, nonce = 0) || tag (16)
+//! ```
+//!
+//! Every message has a fresh key, so the fixed nonce is never reused.
+
+use alloc::vec::Vec;
+
+use hkdf::Hkdf;
+use sha2::Sha256;
+use starknet_crypto::Felt;
+use starknet_types_core::curve::{AffinePoint, ProjectivePoint};
+use zeroize::Zeroizing;
+
+use crate::aead::{xchacha_decrypt, xchacha_encrypt, AEAD_TAG_LEN};
+use crate::point_table::generator_table;
+use crate::{
+    bytes_from_raw, error::null_pointer, felt_from_bytes, felt_to_bytes, ffi_guard, random,
+    write_bytes_out, FeltBytes, StarkResult, EC_ORDER,
+};
+
+/// Bytes a ciphertext adds to its plaintext (ephemeral key and tag)
+pub const STARK_ECIES_OVERHEAD: usize = 32 + AEAD_TAG_LEN;
+
+const INFO: &[u8] = b"STARK_ECIES";
+const NONCE: [u8; 24] = [0; 24];
+
+/// Lift an x-only public key to a curve point (either sign)
+fn lift(x: &Felt) -> Option<ProjectivePoint> {
+    let point = AffinePoint::new_from_x(x, false)?;
+    Some(ProjectivePoint::from_affine_unchecked(point.x(), point.y()))
+}
+
+fn message_key(
+    shared: &ProjectivePoint,
+    ephemeral: &Felt,
+    recipient: &Felt,
+) -> Option<Zeroizing<[u8; 32]>> {
+    let shared_x = Zeroizing::new(shared.to_affine().ok()?.x());
+    let mut salt = [0u8; 64];
+    salt[..32].copy_from_slice(&ephemeral.to_bytes_be());
+    salt[32..].copy_from_slice(&recipient.to_bytes_be());
+
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(&salt), &Zeroizing::new(shared_x.to_bytes_be())[..])
+        .expand(INFO, &mut key[..])
+        .ok()?;
+    Some(key)
+}
+
+/// Encrypt with a given ephemeral private key in `[1, n)`
+fn encrypt(recipient: &Felt, ephemeral_key: &Felt, plaintext: &[u8]) -> Option<Vec<u8>> {
+    let recipient_point = lift(recipient)?;
+    let ephemeral = generator_table().mul(ephemeral_key).to_affine().ok()?.x();
+    let key = message_key(&(&recipient_point * *ephemeral_key), &ephemeral, recipient)?;
+
+    let mut out = Vec::with_capacity(plaintext.len() + STARK_ECIES_OVERHEAD);
+    out.extend_from_slice(&felt_to_bytes(&ephemeral));
+    out.extend(xchacha_encrypt(&key, &NONCE, &[], plaintext));
+    Some(out)
+}
+
+fn decrypt(private_key: &Felt, ciphertext: &[u8]) -> Result<Vec<u8>, StarkResult> {
+    if ciphertext.len() < STARK_ECIES_OVERHEAD {
+        return Err(StarkResult::InvalidInput);
+    }
+    let (ephemeral, sealed) = ciphertext.split_at(32);
+    let ephemeral = Felt::from_bytes_be_slice(ephemeral);
+    let ephemeral_point = lift(&ephemeral).ok_or(StarkResult::InvalidInput)?;
+    let recipient = generator_table()
+        .mul(private_key)
+        .to_affine()
+        .map_err(|_| StarkResult::InvalidInput)?
+        .x();
+
+    let key = message_key(&(&ephemeral_point * *private_key), &ephemeral, &recipient)
+        .ok_or(StarkResult::InvalidInput)?;
+    xchacha_decrypt(&key, &NONCE, &[], sealed).ok_or(StarkResult::DecryptionFailed)
+}
+
+/// Encrypt a message to a Stark public key
+///
+/// The output is `STARK_ECIES_OVERHEAD` bytes longer than the plaintext and
+/// follows the usual `out_len` capacity convention. Returns `InvalidInput`
+/// if the public key is not the x coordinate of a curve point and
+/// `RngFailed` if the OS random number generator is unavailable.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_ecies_encrypt")]
+pub unsafe extern "C" fn stark_ecies_encrypt(
+    recipient_public_key: *const FeltBytes,
+    plaintext: *const u8,
+    len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(recipient_public_key, out_len);
+        let recipient = match felt_from_bytes(&*recipient_public_key) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let plaintext = match bytes_from_raw(plaintext, len) {
+            Some(p) => p,
+            None => return null_pointer("plaintext"),
+        };
+        let ephemeral_key = match random::random_private_key() {
+            Ok(k) => Zeroizing::new(k),
+            Err(_) => return StarkResult::RngFailed,
+        };
+
+        match encrypt(&recipient, &ephemeral_key, plaintext) {
+            Some(ciphertext) => write_bytes_out(&ciphertext, out, out_len),
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+/// Decrypt a message produced by `stark_ecies_encrypt`
+///
+/// Returns `InvalidPrivateKey` unless 0 < key < n, `InvalidInput` for a
+/// truncated ciphertext or malformed ephemeral key, and `DecryptionFailed`
+/// if authentication fails (wrong key or tampered ciphertext).
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_ecies_decrypt")]
+pub unsafe extern "C" fn stark_ecies_decrypt(
+    private_key: *const FeltBytes,
+    ciphertext: *const u8,
+    len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(private_key, out_len);
+        let pk = match felt_from_bytes(&*private_key) {
+            Some(f) => Zeroizing::new(f),
+            None => return StarkResult::InvalidInput,
+        };
+        if *pk == Felt::ZERO || *pk >= EC_ORDER {
+            return StarkResult::InvalidPrivateKey;
+        }
+        let ciphertext = match bytes_from_raw(ciphertext, len) {
+            Some(c) => c,
+            None => return null_pointer("ciphertext"),
+        };
+
+        match decrypt(&pk, ciphertext) {
+            Ok(plaintext) => write_bytes_out(&plaintext, out, out_len),
+            Err(e) => e,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet_crypto::get_public_key as public_key;
+
+    #[test]
+    fn test_ecies_roundtrip() {
+        let private_key = felt_to_bytes(&Felt::from(0x1234_5678u64));
+        let recipient = felt_to_bytes(&public_key(&Felt::from(0x1234_5678u64)));
+        let memo = b"limit order: 10 ETH @ 3200";
+        let mut ciphertext = [0u8; 26 + STARK_ECIES_OVERHEAD];
+        let mut plaintext = [0u8; 26];
+
+        unsafe {
+            let mut len = ciphertext.len();
+            let result = stark_ecies_encrypt(
+                &recipient,
+                memo.as_ptr(),
+                memo.len(),
+                ciphertext.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(len, ciphertext.len());
+
+            let mut len = plaintext.len();
+            let result = stark_ecies_decrypt(
+                &private_key,
+                ciphertext.as_ptr(),
+                ciphertext.len(),
+                plaintext.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(&plaintext, memo);
+
+            let wrong_key = felt_to_bytes(&Felt::from(0x1234_5679u64));
+            let mut len = plaintext.len();
+            let result = stark_ecies_decrypt(
+                &wrong_key,
+                ciphertext.as_ptr(),
+                ciphertext.len(),
+                plaintext.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::DecryptionFailed);
+        }
+    }
+
+    #[test]
+    fn test_ecies_ephemeral_key_prefix() {
+        let private_key = Felt::from(42u64);
+        let ephemeral_key = Felt::from(7u64);
+        let ciphertext = encrypt(&public_key(&private_key), &ephemeral_key, b"memo").unwrap();
+
+        assert_eq!(ciphertext.len(), 4 + STARK_ECIES_OVERHEAD);
+        assert_eq!(
+            Felt::from_bytes_be_slice(&ciphertext[..32]),
+            public_key(&ephemeral_key)
+        );
+        assert_eq!(decrypt(&private_key, &ciphertext).unwrap(), b"memo");
+
+        let mut tampered = ciphertext.clone();
+        tampered[40] ^= 1;
+        assert_eq!(
+            decrypt(&private_key, &tampered),
+            Err(StarkResult::DecryptionFailed)
+        );
+        assert_eq!(
+            decrypt(&private_key, &ciphertext[..STARK_ECIES_OVERHEAD - 1]),
+            Err(StarkResult::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn test_ecies_rejects_off_curve_recipient() {
+        let off_curve = (1u64..)
+            .map(Felt::from)
+            .find(|x| AffinePoint::new_from_x(x, false).is_none())
+            .unwrap();
+        assert!(encrypt(&off_curve, &Felt::from(7u64), b"memo").is_none());
+    }
+}
@@ -0,0 +1,179 @@
+//! Ed25519 signatures (RFC 8032)
+//!
+//! For peers outside Starknet (device attestation, libsodium). Private keys
+//! are the 32-byte RFC 8032 seed; libsodium's 64-byte secret key is
+//! `seed || public_key`. Messages are signed as-is, not prehashed.
+//!
+//! Verification uses the strict rules (canonical encodings, no small-order
+//! keys), which matches libsodium's `crypto_sign_verify_detached`.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use zeroize::Zeroizing;
+
+use crate::{bytes_from_raw, error::null_pointer, ffi_guard, random, StarkResult};
+
+/// Generate a random keypair (private seed, public key)
+///
+/// Returns `RngFailed` if the OS random number generator is unavailable.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_ed25519_keypair")]
+pub unsafe extern "C" fn ed25519_keypair(
+    out_private: *mut [u8; 32],
+    out_public: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_private, out_public);
+        let mut seed = Zeroizing::new([0u8; 32]);
+        if random::fill_random(&mut seed[..]).is_err() {
+            return StarkResult::RngFailed;
+        }
+
+        let key = SigningKey::from_bytes(&seed);
+        *out_private = *seed;
+        *out_public = key.verifying_key().to_bytes();
+        StarkResult::Success
+    })
+}
+
+/// Derive the public key from a private seed
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_ed25519_get_public_key")]
+pub unsafe extern "C" fn ed25519_get_public_key(
+    private_key: *const [u8; 32],
+    out: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(private_key, out);
+        let key = SigningKey::from_bytes(&*private_key);
+        *out = key.verifying_key().to_bytes();
+        StarkResult::Success
+    })
+}
+
+/// Sign a message (returns the 64-byte signature R || S)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_ed25519_sign")]
+pub unsafe extern "C" fn ed25519_sign(
+    private_key: *const [u8; 32],
+    msg: *const u8,
+    len: usize,
+    out: *mut [u8; 64],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(private_key, out);
+        let msg = match bytes_from_raw(msg, len) {
+            Some(m) => m,
+            None => return null_pointer("msg"),
+        };
+
+        let key = SigningKey::from_bytes(&*private_key);
+        *out = key.sign(msg).to_bytes();
+        StarkResult::Success
+    })
+}
+
+/// Verify a signature over a message
+///
+/// Returns `InvalidInput` if the public key is not a valid point encoding.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_ed25519_verify")]
+pub unsafe extern "C" fn ed25519_verify(
+    public_key: *const [u8; 32],
+    msg: *const u8,
+    len: usize,
+    signature: *const [u8; 64],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(public_key, signature);
+        let msg = match bytes_from_raw(msg, len) {
+            Some(m) => m,
+            None => return null_pointer("msg"),
+        };
+        let key = match VerifyingKey::from_bytes(&*public_key) {
+            Ok(k) => k,
+            Err(_) => return StarkResult::InvalidInput,
+        };
+
+        let signature = Signature::from_bytes(&*signature);
+        match key.verify_strict(msg, &signature) {
+            Ok(()) => StarkResult::Success,
+            Err(_) => StarkResult::InvalidSignature,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex<const N: usize>(s: &str) -> [u8; N] {
+        let mut out = [0u8; N];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    // RFC 8032 7.1, TEST 2 (one-byte message 0x72)
+    const PRIVATE_KEY: &str = "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb";
+    const PUBLIC_KEY: &str = "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c";
+    const SIGNATURE: &str = "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00";
+    const MESSAGE: [u8; 1] = [0x72];
+
+    #[test]
+    fn test_ed25519_sign_rfc8032() {
+        let mut public_key = [0u8; 32];
+        let mut signature = [0u8; 64];
+
+        unsafe {
+            let result = ed25519_get_public_key(&hex(PRIVATE_KEY), &mut public_key);
+            assert_eq!(result, StarkResult::Success);
+            let result = ed25519_sign(
+                &hex(PRIVATE_KEY),
+                MESSAGE.as_ptr(),
+                MESSAGE.len(),
+                &mut signature,
+            );
+            assert_eq!(result, StarkResult::Success);
+        }
+        assert_eq!(public_key, hex::<32>(PUBLIC_KEY));
+        assert_eq!(signature, hex::<64>(SIGNATURE));
+    }
+
+    #[test]
+    fn test_ed25519_verify() {
+        let public_key = hex::<32>(PUBLIC_KEY);
+        let signature = hex::<64>(SIGNATURE);
+
+        unsafe {
+            let result = ed25519_verify(&public_key, MESSAGE.as_ptr(), MESSAGE.len(), &signature);
+            assert_eq!(result, StarkResult::Success);
+
+            let result = ed25519_verify(&public_key, core::ptr::null(), 0, &signature);
+            assert_eq!(result, StarkResult::InvalidSignature);
+
+            let result = ed25519_verify(&public_key, core::ptr::null(), 1, &signature);
+            assert_eq!(result, StarkResult::NullPointer);
+        }
+    }
+
+    #[test]
+    fn test_ed25519_keypair() {
+        let mut private_key = [0u8; 32];
+        let mut public_key = [0u8; 32];
+        let mut expected_public = [0u8; 32];
+        let mut signature = [0u8; 64];
+        let msg = b"device attestation";
+
+        unsafe {
+            let result = ed25519_keypair(&mut private_key, &mut public_key);
+            assert_eq!(result, StarkResult::Success);
+            ed25519_get_public_key(&private_key, &mut expected_public);
+            assert_eq!(public_key, expected_public);
+
+            ed25519_sign(&private_key, msg.as_ptr(), msg.len(), &mut signature);
+            let result = ed25519_verify(&public_key, msg.as_ptr(), msg.len(), &signature);
+            assert_eq!(result, StarkResult::Success);
+        }
+    }
+}
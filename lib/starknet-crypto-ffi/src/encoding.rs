@@ -0,0 +1,404 @@
+//! Text encodings for byte buffers
+//!
+//! Encoded strings are ASCII and not NUL-terminated; output lengths follow
+//! the usual capacity convention, so a call with a zero capacity reports
+//! the size to allocate.
+//!
+//! - hex: lowercase on output, either case and an optional `0x` on input
+//! - base64: the standard alphabet with `=` padding, or the URL-safe
+//!   alphabet without padding (as in JWTs and WebAuthn); padding is
+//!   optional on input
+//! - base58: the Bitcoin alphabet, optionally with a Base58Check checksum
+//!   (the first 4 bytes of SHA-256(SHA-256(data)) appended to the data)
+
+use alloc::vec::Vec;
+
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+use crate::{bytes_from_raw, error::null_pointer, ffi_guard, write_bytes_out, StarkResult};
+
+/// Base58Check checksum length
+const BASE58_CHECKSUM_LEN: usize = 4;
+
+const BASE64_STANDARD: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+const BASE64_URL_SAFE: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new()
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+fn base64_engine(url_safe: bool) -> &'static GeneralPurpose {
+    if url_safe {
+        &BASE64_URL_SAFE
+    } else {
+        &BASE64_STANDARD
+    }
+}
+
+fn base58_checksum(data: &[u8]) -> [u8; BASE58_CHECKSUM_LEN] {
+    let digest = Sha256::digest(Sha256::digest(data));
+    let mut checksum = [0u8; BASE58_CHECKSUM_LEN];
+    checksum.copy_from_slice(&digest[..BASE58_CHECKSUM_LEN]);
+    checksum
+}
+
+/// Decode base58, then split off and verify the checksum if requested
+fn base58_decode_impl(str: &[u8], checksum: bool) -> Option<Vec<u8>> {
+    let mut bytes = bs58::decode(str).into_vec().ok()?;
+    if checksum {
+        let data_len = bytes.len().checked_sub(BASE58_CHECKSUM_LEN)?;
+        if bytes[data_len..] != base58_checksum(&bytes[..data_len]) {
+            return None;
+        }
+        bytes.truncate(data_len);
+    }
+    Some(bytes)
+}
+
+/// Encode bytes as lowercase hex, without a `0x` prefix
+///
+/// `data` may be null when `len == 0`. `out_len` holds the capacity of
+/// `out_str` on input and the string length (`2 * len`) on output; if the
+/// buffer is too small, the required length is written and `BufferTooSmall`
+/// is returned.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_hex_encode")]
+pub unsafe extern "C" fn hex_encode(
+    data: *const u8,
+    len: usize,
+    out_str: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_len);
+        let data = match bytes_from_raw(data, len) {
+            Some(d) => d,
+            None => return null_pointer("data"),
+        };
+
+        write_bytes_out(hex::encode(data).as_bytes(), out_str, out_len)
+    })
+}
+
+/// Decode a hex string, with or without a `0x` / `0X` prefix
+///
+/// Digits may be upper or lower case. `out_len` follows the usual capacity
+/// convention. Returns `InvalidInput` for an odd number of digits or a
+/// character that is not a hex digit.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_hex_decode")]
+pub unsafe extern "C" fn hex_decode(
+    str: *const u8,
+    str_len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_len);
+        let str = match bytes_from_raw(str, str_len) {
+            Some(s) => s,
+            None => return null_pointer("str"),
+        };
+        let digits = str
+            .strip_prefix(b"0x")
+            .or_else(|| str.strip_prefix(b"0X"))
+            .unwrap_or(str);
+
+        match hex::decode(digits) {
+            Ok(bytes) => write_bytes_out(&bytes, out, out_len),
+            Err(_) => StarkResult::InvalidInput,
+        }
+    })
+}
+
+/// Encode bytes as base64
+///
+/// With `url_safe`, uses the `-_` alphabet without padding; otherwise the
+/// standard `+/` alphabet with `=` padding. `data` may be null when
+/// `len == 0`; `out_len` follows the usual capacity convention.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_base64_encode")]
+pub unsafe extern "C" fn base64_encode(
+    data: *const u8,
+    len: usize,
+    url_safe: bool,
+    out_str: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_len);
+        let data = match bytes_from_raw(data, len) {
+            Some(d) => d,
+            None => return null_pointer("data"),
+        };
+
+        write_bytes_out(
+            base64_engine(url_safe).encode(data).as_bytes(),
+            out_str,
+            out_len,
+        )
+    })
+}
+
+/// Decode base64 in the standard or URL-safe alphabet
+///
+/// Padding is accepted but not required. Returns `InvalidInput` for
+/// characters outside the selected alphabet, whitespace, or an impossible
+/// length.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_base64_decode")]
+pub unsafe extern "C" fn base64_decode(
+    str: *const u8,
+    str_len: usize,
+    url_safe: bool,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_len);
+        let str = match bytes_from_raw(str, str_len) {
+            Some(s) => s,
+            None => return null_pointer("str"),
+        };
+
+        match base64_engine(url_safe).decode(str) {
+            Ok(bytes) => write_bytes_out(&bytes, out, out_len),
+            Err(_) => StarkResult::InvalidInput,
+        }
+    })
+}
+
+/// Encode bytes as base58 (Bitcoin alphabet)
+///
+/// With `checksum`, appends the 4-byte Base58Check checksum before
+/// encoding. Leading zero bytes become leading `1`s. `out_len` follows the
+/// usual capacity convention.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_base58_encode")]
+pub unsafe extern "C" fn base58_encode(
+    data: *const u8,
+    len: usize,
+    checksum: bool,
+    out_str: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_len);
+        let data = match bytes_from_raw(data, len) {
+            Some(d) => d,
+            None => return null_pointer("data"),
+        };
+
+        let mut payload = data.to_vec();
+        if checksum {
+            payload.extend(base58_checksum(data));
+        }
+        write_bytes_out(
+            bs58::encode(payload).into_string().as_bytes(),
+            out_str,
+            out_len,
+        )
+    })
+}
+
+/// Decode base58 (Bitcoin alphabet)
+///
+/// With `checksum`, verifies and strips the trailing 4-byte Base58Check
+/// checksum. Returns `InvalidInput` for characters outside the alphabet or
+/// a missing or wrong checksum.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_base58_decode")]
+pub unsafe extern "C" fn base58_decode(
+    str: *const u8,
+    str_len: usize,
+    checksum: bool,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_len);
+        let str = match bytes_from_raw(str, str_len) {
+            Some(s) => s,
+            None => return null_pointer("str"),
+        };
+
+        match base58_decode_impl(str, checksum) {
+            Some(bytes) => write_bytes_out(&bytes, out, out_len),
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn decode(str: &str) -> Result<Vec<u8>, StarkResult> {
+        let mut out = vec![0u8; str.len()];
+        let mut len = out.len();
+        match hex_decode(str.as_ptr(), str.len(), out.as_mut_ptr(), &mut len) {
+            StarkResult::Success => Ok(out[..len].to_vec()),
+            e => Err(e),
+        }
+    }
+
+    unsafe fn encode_with(
+        f: unsafe extern "C" fn(*const u8, usize, bool, *mut u8, *mut usize) -> StarkResult,
+        data: &[u8],
+        flag: bool,
+    ) -> String {
+        let mut len = 0;
+        f(
+            data.as_ptr(),
+            data.len(),
+            flag,
+            core::ptr::null_mut(),
+            &mut len,
+        );
+        let mut out = vec![0u8; len];
+        let result = f(data.as_ptr(), data.len(), flag, out.as_mut_ptr(), &mut len);
+        assert_eq!(result, StarkResult::Success);
+        String::from_utf8(out).unwrap()
+    }
+
+    unsafe fn decode_with(
+        f: unsafe extern "C" fn(*const u8, usize, bool, *mut u8, *mut usize) -> StarkResult,
+        str: &str,
+        flag: bool,
+    ) -> Result<Vec<u8>, StarkResult> {
+        let mut out = vec![0u8; str.len()];
+        let mut len = out.len();
+        match f(str.as_ptr(), str.len(), flag, out.as_mut_ptr(), &mut len) {
+            StarkResult::Success => Ok(out[..len].to_vec()),
+            e => Err(e),
+        }
+    }
+
+    #[test]
+    fn test_hex_encode() {
+        let data = [0x00, 0x01, 0xab, 0xff];
+        let mut out = [0u8; 8];
+
+        unsafe {
+            let mut len = 0;
+            let result = hex_encode(data.as_ptr(), data.len(), core::ptr::null_mut(), &mut len);
+            assert_eq!(result, StarkResult::BufferTooSmall);
+            assert_eq!(len, 8);
+
+            let result = hex_encode(data.as_ptr(), data.len(), out.as_mut_ptr(), &mut len);
+            assert_eq!(result, StarkResult::Success);
+
+            let mut len = 0;
+            let result = hex_encode(core::ptr::null(), 0, core::ptr::null_mut(), &mut len);
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(len, 0);
+        }
+        assert_eq!(&out, b"0001abff");
+    }
+
+    #[test]
+    fn test_hex_decode() {
+        unsafe {
+            assert_eq!(decode("0001abff"), Ok(vec![0x00, 0x01, 0xab, 0xff]));
+            assert_eq!(decode("0x0001ABff"), Ok(vec![0x00, 0x01, 0xab, 0xff]));
+            assert_eq!(decode("0XdeAD"), Ok(vec![0xde, 0xad]));
+            assert_eq!(decode("0x"), Ok(vec![]));
+            assert_eq!(decode(""), Ok(vec![]));
+
+            assert_eq!(decode("0x123"), Err(StarkResult::InvalidInput));
+            assert_eq!(decode("0xzz"), Err(StarkResult::InvalidInput));
+            assert_eq!(decode("0x0x12"), Err(StarkResult::InvalidInput));
+            assert_eq!(decode(" 12"), Err(StarkResult::InvalidInput));
+        }
+    }
+
+    #[test]
+    fn test_base64() {
+        // RFC 4648 section 10, plus bytes that differ between the alphabets
+        unsafe {
+            assert_eq!(encode_with(base64_encode, b"", false), "");
+            assert_eq!(encode_with(base64_encode, b"f", false), "Zg==");
+            assert_eq!(encode_with(base64_encode, b"foob", false), "Zm9vYg==");
+            assert_eq!(encode_with(base64_encode, b"foobar", false), "Zm9vYmFy");
+            assert_eq!(encode_with(base64_encode, b"foob", true), "Zm9vYg");
+            assert_eq!(encode_with(base64_encode, &[0xfb, 0xff], false), "+/8=");
+            assert_eq!(encode_with(base64_encode, &[0xfb, 0xff], true), "-_8");
+
+            assert_eq!(
+                decode_with(base64_decode, "Zm9vYg==", false),
+                Ok(b"foob".to_vec())
+            );
+            assert_eq!(
+                decode_with(base64_decode, "Zm9vYg", false),
+                Ok(b"foob".to_vec())
+            );
+            assert_eq!(
+                decode_with(base64_decode, "-_8", true),
+                Ok(vec![0xfb, 0xff])
+            );
+            assert_eq!(
+                decode_with(base64_decode, "-_8=", true),
+                Ok(vec![0xfb, 0xff])
+            );
+
+            let invalid = Err(StarkResult::InvalidInput);
+            assert_eq!(decode_with(base64_decode, "-_8", false), invalid);
+            assert_eq!(decode_with(base64_decode, "+/8", true), invalid);
+            assert_eq!(decode_with(base64_decode, "Zm9v Yg", false), invalid);
+            assert_eq!(decode_with(base64_decode, "Z", false), invalid);
+        }
+    }
+
+    #[test]
+    fn test_base58() {
+        unsafe {
+            assert_eq!(
+                encode_with(base58_encode, b"hello world", false),
+                "StV1DL6CwTryKyV"
+            );
+            assert_eq!(
+                encode_with(base58_encode, &[0, 0, 0x28, 0x7f], false),
+                "1145k"
+            );
+            assert_eq!(
+                decode_with(base58_decode, "StV1DL6CwTryKyV", false),
+                Ok(b"hello world".to_vec())
+            );
+            assert_eq!(
+                decode_with(base58_decode, "0OIl", false),
+                Err(StarkResult::InvalidInput)
+            );
+
+            // Bitcoin P2PKH address of hash160 = 0 (version byte 0x00)
+            let payload = [0u8; 21];
+            let address = encode_with(base58_encode, &payload, true);
+            assert_eq!(address, "1111111111111111111114oLvT2");
+            assert_eq!(
+                decode_with(base58_decode, &address, true),
+                Ok(payload.to_vec())
+            );
+
+            // Wrong checksum, or too short to hold one
+            let invalid = Err(StarkResult::InvalidInput);
+            assert_eq!(
+                decode_with(base58_decode, "1111111111111111111114oLvT3", true),
+                invalid
+            );
+            assert_eq!(decode_with(base58_decode, "2g", true), invalid);
+            // Without checksum verification the checksum bytes are returned
+            assert_eq!(
+                decode_with(base58_decode, &address, false).unwrap().len(),
+                25
+            );
+        }
+    }
+}
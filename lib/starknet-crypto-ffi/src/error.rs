@@ -0,0 +1,385 @@
+//! Thread-local description of the last failure
+//!
+//! Every extern function that returns a code other than `Success` also
+//! records a human-readable message for the calling thread, naming the
+//! argument at fault where the function knows it (e.g. "null pointer:
+//! `out_len` is null"). `stark_last_error_message` reads it back.
+//!
+//! A failing call overwrites the message; a successful call leaves it as it
+//! was, like `errno`. Failure sites call `set_detail` (or one of the
+//! shorthands below) and `ffi_guard` prefixes the detail with a description
+//! of the returned code when the outermost extern call returns.
+//!
+//! `stark_last_error_info` returns the same failure as a struct, for callers
+//! that branch on it: the code, the message, and the position of the
+//! argument at fault. A detail that starts with a backquoted name (as in
+//! "`out_len` is null") blames that parameter of the outermost extern
+//! function; the parameter lists come from the cbindgen pass in `build.rs`.
+
+use alloc::ffi::CString;
+use alloc::format;
+use alloc::string::String;
+#[cfg(feature = "std")]
+use core::any::Any;
+use core::cell::{Cell, RefCell};
+use core::ffi::{c_char, c_int, CStr};
+
+use crate::log::StarkLogLevel;
+use crate::{ffi_guard, StarkResult};
+
+include!(concat!(env!("OUT_DIR"), "/arguments.rs"));
+
+/// Capacity of `StarkErrorInfo::detail`, NUL terminator included
+pub const STARK_ERROR_DETAIL_LEN: usize = 128;
+
+/// The last failure on this thread, as filled by `stark_last_error_info`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct StarkErrorInfo {
+    /// Result code of the failed call (`Success` if no call failed yet)
+    pub code: StarkResult,
+    /// Zero-based position of the argument at fault, -1 if unknown
+    pub argument_index: i32,
+    /// Length in bytes of the full message; `detail` holds its first
+    /// `STARK_ERROR_DETAIL_LEN - 1` bytes when it is longer
+    pub detail_len: usize,
+    /// The message of `stark_last_error_message`, NUL-terminated
+    pub detail: [c_char; STARK_ERROR_DETAIL_LEN],
+}
+
+local! {
+    /// Message of the last failed call, returned by `stark_last_error_message`
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+    /// Code and argument index of the last failed call
+    static LAST_CODE: Cell<(StarkResult, i32)> = Cell::new((StarkResult::Success, -1));
+    /// Detail recorded by a failure site during the current extern call
+    static DETAIL: RefCell<Option<String>> = RefCell::new(None);
+    /// Outermost extern function of the current call (its closure's type
+    /// name, e.g. `kundera::starknet_sign::{{closure}}`)
+    static FUNCTION: Cell<&'static str> = Cell::new("");
+    /// Index of the argument blamed during the current call
+    static ARGUMENT: Cell<i32> = Cell::new(-1);
+    /// Extern calls in progress (extern functions call each other, and only
+    /// the outermost call publishes the message)
+    static DEPTH: Cell<usize> = Cell::new(0);
+}
+
+/// Every result code, indexed by its value
+const RESULTS: [StarkResult; 17] = [
+    StarkResult::Success,
+    StarkResult::InvalidInput,
+    StarkResult::InvalidSignature,
+    StarkResult::RecoveryFailed,
+    StarkResult::DivisionByZero,
+    StarkResult::NoInverse,
+    StarkResult::NoSquareRoot,
+    StarkResult::InvalidPrivateKey,
+    StarkResult::RngFailed,
+    StarkResult::DecryptionFailed,
+    StarkResult::MemoryLockFailed,
+    StarkResult::InternalPanic,
+    StarkResult::NullPointer,
+    StarkResult::InvalidHandle,
+    StarkResult::BufferTooSmall,
+    StarkResult::Cancelled,
+    StarkResult::SelfTestFailed,
+];
+
+/// Short description of a result code
+pub(crate) fn describe(result: StarkResult) -> &'static CStr {
+    match result {
+        StarkResult::Success => c"success",
+        StarkResult::InvalidInput => c"invalid input",
+        StarkResult::InvalidSignature => c"invalid signature",
+        StarkResult::RecoveryFailed => c"public key recovery failed",
+        StarkResult::DivisionByZero => c"division by zero",
+        StarkResult::NoInverse => c"no modular inverse",
+        StarkResult::NoSquareRoot => c"no square root",
+        StarkResult::InvalidPrivateKey => c"invalid private key",
+        StarkResult::RngFailed => c"random number generator failed",
+        StarkResult::DecryptionFailed => c"decryption failed",
+        StarkResult::MemoryLockFailed => c"memory lock failed",
+        StarkResult::InternalPanic => c"internal panic",
+        StarkResult::NullPointer => c"null pointer",
+        StarkResult::InvalidHandle => c"invalid handle",
+        StarkResult::BufferTooSmall => c"output buffer too small",
+        StarkResult::Cancelled => c"operation cancelled",
+        StarkResult::SelfTestFailed => c"self-test failed",
+    }
+}
+
+/// Position of parameter `arg` of the extern function whose body has the
+/// type name `function`, -1 if unknown
+fn argument_index(function: &str, arg: &str) -> i32 {
+    let name = function
+        .rsplit("::")
+        .find(|segment| !segment.starts_with("{{"))
+        .unwrap_or("");
+    ARGUMENTS
+        .binary_search_by_key(&name, |&(function, _)| function)
+        .ok()
+        .and_then(|i| ARGUMENTS[i].1.iter().position(|&a| a == arg))
+        .and_then(|i| i32::try_from(i).ok())
+        .unwrap_or(-1)
+}
+
+/// Record what went wrong in the current call (the last detail wins)
+pub(crate) fn set_detail(detail: impl Into<String>) {
+    let detail = detail.into();
+    // Inner extern calls blame their own parameters, not the outer call's
+    if DEPTH.with(Cell::get) == 1 {
+        let arg = detail
+            .strip_prefix('`')
+            .and_then(|rest| rest.split_once('`'))
+            .map(|(arg, _)| arg);
+        let index = arg.map_or(-1, |arg| FUNCTION.with(|f| argument_index(f.get(), arg)));
+        ARGUMENT.with(|a| a.set(index));
+    }
+    DETAIL.with(|d| *d.borrow_mut() = Some(detail));
+}
+
+/// Record that argument `arg` is null and return `NullPointer`
+pub(crate) fn null_pointer(arg: &str) -> StarkResult {
+    set_detail(format!("`{arg}` is null"));
+    StarkResult::NullPointer
+}
+
+/// Record `detail` and return `InvalidInput`
+pub(crate) fn invalid_input(detail: impl Into<String>) -> StarkResult {
+    set_detail(detail);
+    StarkResult::InvalidInput
+}
+
+/// Record `detail` and return `BufferTooSmall`
+pub(crate) fn buffer_too_small(detail: impl Into<String>) -> StarkResult {
+    set_detail(detail);
+    StarkResult::BufferTooSmall
+}
+
+/// Record the message of a caught panic
+#[cfg(feature = "std")]
+pub(crate) fn set_panic(payload: &(dyn Any + Send)) {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        set_detail(*msg);
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        set_detail(msg.as_str());
+    }
+}
+
+/// Start an extern call (see `ffi_guard`); `function` is the type name of
+/// its body
+pub(crate) fn enter(function: &'static str) {
+    let depth = DEPTH.with(|d| d.replace(d.get() + 1));
+    if depth == 0 {
+        DETAIL.with(|d| d.borrow_mut().take());
+        FUNCTION.with(|f| f.set(function));
+        ARGUMENT.with(|a| a.set(-1));
+    }
+}
+
+/// Finish an extern call, publishing the message if the outermost call failed
+pub(crate) fn leave(result: StarkResult) {
+    let depth = DEPTH.with(|d| {
+        d.set(d.get() - 1);
+        d.get()
+    });
+    if depth > 0 {
+        return;
+    }
+    let detail = DETAIL.with(|d| d.borrow_mut().take());
+    if result == StarkResult::Success {
+        return;
+    }
+    LAST_CODE.with(|c| c.set((result, ARGUMENT.with(Cell::get))));
+    let description = describe(result).to_string_lossy();
+    let message = match detail {
+        Some(detail) => format!("{description}: {detail}"),
+        None => description.into_owned(),
+    };
+    let level = match result {
+        StarkResult::InternalPanic | StarkResult::SelfTestFailed => StarkLogLevel::Error,
+        _ => StarkLogLevel::Debug,
+    };
+    crate::log::emit(level, format_args!("{message}"));
+    // Interior NULs (from a panic message) would truncate the C string
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Message describing the last failed call on this thread
+///
+/// Returns a NUL-terminated UTF-8 string, empty if no call on this thread
+/// has failed yet. The pointer stays valid until the next failing call on
+/// the same thread; copy the string to keep it. Never returns null.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_last_error_message")]
+pub extern "C" fn stark_last_error_message() -> *const c_char {
+    LAST_ERROR
+        .try_with(|e| e.borrow().as_ref().map(|message| message.as_ptr()))
+        .ok()
+        .flatten()
+        .unwrap_or(c"".as_ptr())
+}
+
+/// The last failed call on this thread as a struct (see `StarkErrorInfo`)
+///
+/// Before any failure, writes `Success`, index -1 and an empty detail. Like
+/// every function, a null `out_info` is itself a failure and replaces the
+/// last error.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_last_error_info")]
+pub unsafe extern "C" fn stark_last_error_info(out_info: *mut StarkErrorInfo) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_info);
+        let (code, argument_index) = LAST_CODE.with(Cell::get);
+        let mut info = StarkErrorInfo {
+            code,
+            argument_index,
+            detail_len: 0,
+            detail: [0; STARK_ERROR_DETAIL_LEN],
+        };
+        LAST_ERROR.with(|e| {
+            let last = e.borrow();
+            let message = last.as_deref().and_then(|m| m.to_str().ok()).unwrap_or("");
+            info.detail_len = message.len();
+            // Truncate on a character boundary
+            let mut len = message.len().min(STARK_ERROR_DETAIL_LEN - 1);
+            while !message.is_char_boundary(len) {
+                len -= 1;
+            }
+            for (dst, &src) in info.detail.iter_mut().zip(&message.as_bytes()[..len]) {
+                *dst = src as c_char;
+            }
+        });
+        *out_info = info;
+        StarkResult::Success
+    })
+}
+
+/// Static description of a result code (e.g. "invalid input")
+///
+/// Takes the code as an `int` so a value this build does not know (from a
+/// newer header) is safe to pass: it maps to "unknown error". The string is
+/// NUL-terminated, lives for the whole program and must not be freed.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_result_to_string")]
+pub extern "C" fn stark_result_to_string(result: c_int) -> *const c_char {
+    usize::try_from(result)
+        .ok()
+        .and_then(|code| RESULTS.get(code))
+        .map_or(c"unknown error", |&result| describe(result))
+        .as_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{felt_add, felt_inverse, write_bytes_out};
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(stark_last_error_message()) }
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    #[test]
+    fn test_result_to_string() {
+        for (code, &result) in RESULTS.iter().enumerate() {
+            assert_eq!(result as usize, code);
+        }
+        let to_string = |code| {
+            unsafe { CStr::from_ptr(stark_result_to_string(code)) }
+                .to_str()
+                .unwrap()
+        };
+        assert_eq!(to_string(0), "success");
+        assert_eq!(to_string(StarkResult::NullPointer as c_int), "null pointer");
+        assert_eq!(to_string(17), "unknown error");
+        assert_eq!(to_string(-1), "unknown error");
+    }
+
+    #[test]
+    fn test_last_error_names_argument() {
+        let a = [0u8; 32];
+        unsafe {
+            assert_eq!(
+                felt_add(&a, core::ptr::null(), core::ptr::null_mut()),
+                StarkResult::NullPointer
+            );
+        }
+        assert_eq!(last_error(), "null pointer: `b` is null");
+    }
+
+    fn last_error_info() -> StarkErrorInfo {
+        let mut info = core::mem::MaybeUninit::uninit();
+        unsafe {
+            assert_eq!(stark_last_error_info(info.as_mut_ptr()), StarkResult::Success);
+            info.assume_init()
+        }
+    }
+
+    #[test]
+    fn test_last_error_info() {
+        let a = [0u8; 32];
+        unsafe {
+            felt_add(&a, core::ptr::null(), core::ptr::null_mut());
+        }
+        let info = last_error_info();
+        assert_eq!(info.code, StarkResult::NullPointer);
+        assert_eq!(info.argument_index, 1);
+        assert_eq!(info.detail_len, "null pointer: `b` is null".len());
+        let detail = unsafe { CStr::from_ptr(info.detail.as_ptr()) };
+        assert_eq!(detail, c"null pointer: `b` is null");
+
+        // Not about an argument
+        let mut out = [0u8; 32];
+        unsafe { felt_inverse(&a, &mut out) };
+        let info = last_error_info();
+        assert_eq!(info.code, StarkResult::NoInverse);
+        assert_eq!(info.argument_index, -1);
+
+        // Truncated on a character boundary
+        crate::ffi_guard(|| invalid_input(format!("x{}", "é".repeat(100))));
+        let info = last_error_info();
+        let detail = unsafe { CStr::from_ptr(info.detail.as_ptr()) }.to_str().unwrap();
+        assert_eq!(info.detail_len, "invalid input: x".len() + 200);
+        assert_eq!(detail.len(), STARK_ERROR_DETAIL_LEN - 2);
+        assert!(detail.ends_with('é'));
+
+        assert_eq!(
+            unsafe { stark_last_error_info(core::ptr::null_mut()) },
+            StarkResult::NullPointer
+        );
+    }
+
+    #[test]
+    fn test_last_error_kept_on_success() {
+        let zero = [0u8; 32];
+        let mut out = [0u8; 32];
+        unsafe {
+            assert_eq!(felt_inverse(&zero, &mut out), StarkResult::NoInverse);
+            assert_eq!(last_error(), "no modular inverse");
+            assert_eq!(felt_add(&zero, &zero, &mut out), StarkResult::Success);
+        }
+        assert_eq!(last_error(), "no modular inverse");
+    }
+
+    #[test]
+    fn test_last_error_buffer_too_small() {
+        let mut out = [0u8; 2];
+        let mut out_len = out.len();
+        crate::ffi_guard(|| unsafe { write_bytes_out(b"abcd", out.as_mut_ptr(), &mut out_len) });
+        assert_eq!(
+            last_error(),
+            "output buffer too small: 4 bytes needed, capacity 2"
+        );
+    }
+
+    #[test]
+    fn test_last_error_panic_message() {
+        crate::ffi_guard(|| panic!("boom"));
+        assert_eq!(last_error(), "internal panic: boom");
+    }
+}
@@ -0,0 +1,191 @@
+//! Ethereum helpers
+//!
+//! EIP-191 `personal_sign` message hashing and address derivation with
+//! EIP-55 checksum encoding.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use sha3::{Digest, Keccak256};
+
+use crate::{error::null_pointer, ffi_guard, StarkResult};
+
+/// keccak256("\x19Ethereum Signed Message:\n" || decimal(len) || message)
+pub(crate) fn personal_message_hash(message: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(b"\x19Ethereum Signed Message:\n");
+    hasher.update(message.len().to_string().as_bytes());
+    hasher.update(message);
+    hasher.finalize().into()
+}
+
+/// EIP-191 personal message hash (the digest signed by `personal_sign`)
+///
+/// `msg` may be null when `len == 0`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_eth_personal_message_hash")]
+pub unsafe extern "C" fn eth_personal_message_hash(
+    msg: *const u8,
+    len: usize,
+    out: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out);
+        if msg.is_null() && len > 0 {
+            return null_pointer("msg");
+        }
+
+        let slice = if len > 0 {
+            core::slice::from_raw_parts(msg, len)
+        } else {
+            &[]
+        };
+
+        *out = personal_message_hash(slice);
+        StarkResult::Success
+    })
+}
+
+/// Address of an uncompressed secp256k1 public key (x || y):
+/// the last 20 bytes of keccak256(x || y)
+pub(crate) fn address_from_public_key(public_key: &[u8; 64]) -> [u8; 20] {
+    let hash = Keccak256::digest(public_key);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
+
+/// EIP-55 mixed-case hex encoding, `0x`-prefixed
+pub(crate) fn checksum_address(address: &[u8; 20]) -> String {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let lower: Vec<u8> = address
+        .iter()
+        .flat_map(|b| [HEX[(b >> 4) as usize], HEX[(b & 0x0f) as usize]])
+        .collect();
+    let hash = Keccak256::digest(&lower);
+
+    let mut out = String::with_capacity(42);
+    out.push_str("0x");
+    for (i, &c) in lower.iter().enumerate() {
+        let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+        if c.is_ascii_alphabetic() && nibble >= 8 {
+            out.push(c.to_ascii_uppercase() as char);
+        } else {
+            out.push(c as char);
+        }
+    }
+    out
+}
+
+/// Ethereum address of an uncompressed secp256k1 public key (x || y)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_eth_address_from_public_key")]
+pub unsafe extern "C" fn eth_address_from_public_key(
+    public_key: *const [u8; 64],
+    out: *mut [u8; 20],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(public_key, out);
+        *out = address_from_public_key(&*public_key);
+        StarkResult::Success
+    })
+}
+
+/// EIP-55 checksummed string of a 20-byte address
+///
+/// Writes `0x` followed by 40 hex digits and a NUL terminator (43 bytes).
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_eth_address_checksum")]
+pub unsafe extern "C" fn eth_address_checksum(
+    address: *const [u8; 20],
+    out: *mut [u8; 43],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(address, out);
+        let encoded = checksum_address(&*address);
+        let out = &mut *out;
+        out[..42].copy_from_slice(encoded.as_bytes());
+        out[42] = 0;
+        StarkResult::Success
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_personal_message_hash() {
+        let msg = b"Hello World";
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = eth_personal_message_hash(msg.as_ptr(), msg.len(), &mut out);
+            assert_eq!(result, StarkResult::Success);
+        }
+
+        // ethers.js hashMessage("Hello World")
+        let expected = [
+            0xa1, 0xde, 0x98, 0x86, 0x00, 0xa4, 0x2c, 0x4b, 0x4a, 0xb0, 0x89, 0xb6, 0x19, 0x29,
+            0x7c, 0x17, 0xd5, 0x3c, 0xff, 0xae, 0x5d, 0x51, 0x20, 0xd8, 0x2d, 0x8a, 0x92, 0xd0,
+            0xbb, 0x3b, 0x78, 0xf2,
+        ];
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_personal_message_hash_empty() {
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = eth_personal_message_hash(core::ptr::null(), 0, &mut out);
+            assert_eq!(result, StarkResult::Success);
+            let result = eth_personal_message_hash(core::ptr::null(), 1, &mut out);
+            assert_eq!(result, StarkResult::NullPointer);
+        }
+    }
+
+    #[test]
+    fn test_address_from_public_key() {
+        // Private key 1
+        let private_key = {
+            let mut k = [0u8; 32];
+            k[31] = 1;
+            k
+        };
+        let mut public_key = [0u8; 64];
+        let mut address = [0u8; 20];
+        let mut checksummed = [0u8; 43];
+
+        unsafe {
+            crate::secp256k1::secp256k1_get_public_key(&private_key, &mut public_key);
+            let result = eth_address_from_public_key(&public_key, &mut address);
+            assert_eq!(result, StarkResult::Success);
+            let result = eth_address_checksum(&address, &mut checksummed);
+            assert_eq!(result, StarkResult::Success);
+        }
+
+        assert_eq!(
+            &checksummed[..42],
+            b"0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+        );
+        assert_eq!(checksummed[42], 0);
+    }
+
+    #[test]
+    fn test_address_checksum() {
+        // EIP-55 test vectors
+        for expected in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let mut address = [0u8; 20];
+            for (i, byte) in address.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&expected[2 + 2 * i..4 + 2 * i], 16).unwrap();
+            }
+            assert_eq!(checksum_address(&address), expected);
+        }
+    }
+}
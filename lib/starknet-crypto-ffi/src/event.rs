@@ -0,0 +1,173 @@
+//! Cairo event selectors and keys
+//!
+//! A Cairo 1 contract emits a variant of its `Event` enum. The compiler
+//! puts one selector per enum level in the event keys, from the contract's
+//! enum down to the event struct, then the `#[key]` members:
+//!
+//! - the selector of a variant is `sn_keccak(variant name)`;
+//! - a variant holding another event enum nests by default, adding its own
+//!   selector before the inner variant's;
+//! - a `#[flat]` variant adds no selector, so the inner variant's selector
+//!   stands in for it (component events are usually flat).
+//!
+//! `starknet_event_keys` computes the selector keys of a variant path such
+//! as `ERC20Event::Transfer`; log filters append the `#[key]` members.
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use starknet_crypto::Felt;
+
+use crate::error::invalid_input;
+use crate::perf::{self, Op};
+use crate::{
+    bytes_from_raw, error::null_pointer, felt_to_bytes, ffi_guard, sn_keccak, write_felts_out,
+    FeltBytes, StarkResult,
+};
+
+/// Selector keys for the `::`-separated variant `path`, leaving out the
+/// segments whose bit is set in `flat_mask`
+fn event_keys(path: &str, flat_mask: u64) -> Result<Vec<Felt>, StarkResult> {
+    let segments: Vec<&str> = path.split("::").collect();
+    if segments.len() > 64 {
+        return Err(invalid_input(format!(
+            "`path` has {} segments (at most 64)",
+            segments.len()
+        )));
+    }
+    if segments.len() < 64 && flat_mask >> segments.len() != 0 {
+        return Err(invalid_input(format!(
+            "`flat_mask` has bits set past the {} segments of `path`",
+            segments.len()
+        )));
+    }
+    let mut keys = Vec::with_capacity(segments.len());
+    for (i, segment) in segments.into_iter().enumerate() {
+        if segment.is_empty() {
+            return Err(invalid_input(format!("`path` segment {i} is empty")));
+        }
+        if flat_mask & (1 << i) == 0 {
+            keys.push(sn_keccak(segment.as_bytes()));
+        }
+    }
+    Ok(keys)
+}
+
+/// Compute the selector of an event variant: `sn_keccak(event_name)`
+///
+/// `event_name` is the variant name (`Transfer`), not NUL-terminated.
+/// Returns `InvalidInput` if it is empty.
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_starknet_event_selector"
+)]
+pub unsafe extern "C" fn starknet_event_selector(
+    event_name: *const u8,
+    len: usize,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Keccak);
+        check_null!(event_name, out);
+        if len == 0 {
+            return invalid_input("`event_name` is empty");
+        }
+        let name = core::slice::from_raw_parts(event_name, len);
+        *out = felt_to_bytes(&sn_keccak(name));
+        StarkResult::Success
+    })
+}
+
+/// Compute the selector keys of an event, as the compiler emits them
+///
+/// `path` (UTF-8, not NUL-terminated) names the variants from the
+/// contract's event enum down to the event, separated by `::`
+/// (`ERC20Event::Transfer`); bit `i` of `flat_mask` marks segment `i` as a
+/// `#[flat]` variant, which adds no key. `*out_count` is the capacity of
+/// `out_keys` in felts, set to the number of keys as for every felt array
+/// output. Returns `InvalidInput` for an empty segment, more than 64
+/// segments or a mask bit past the last segment.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_event_keys")]
+pub unsafe extern "C" fn starknet_event_keys(
+    path: *const u8,
+    len: usize,
+    flat_mask: u64,
+    out_keys: *mut FeltBytes,
+    out_count: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Keccak);
+        check_null!(out_count);
+        let Some(path) = bytes_from_raw(path, len) else {
+            return null_pointer("path");
+        };
+        let Ok(path) = core::str::from_utf8(path) else {
+            return invalid_input("`path` is not UTF-8");
+        };
+        match event_keys(path, flat_mask) {
+            Ok(keys) => write_felts_out(&keys, out_keys, out_count),
+            Err(e) => e,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(path: &str, flat_mask: u64) -> (StarkResult, Vec<FeltBytes>) {
+        let mut out = [[0u8; 32]; 4];
+        let mut count = out.len();
+        let result = unsafe {
+            starknet_event_keys(
+                path.as_ptr(),
+                path.len(),
+                flat_mask,
+                out.as_mut_ptr(),
+                &mut count,
+            )
+        };
+        (result, out[..count.min(4)].to_vec())
+    }
+
+    #[test]
+    fn test_event_selector() {
+        let mut out = [0u8; 32];
+        unsafe {
+            assert_eq!(
+                starknet_event_selector(b"Transfer".as_ptr(), 8, &mut out),
+                StarkResult::Success
+            );
+            assert_eq!(
+                starknet_event_selector(b"Transfer".as_ptr(), 0, &mut out),
+                StarkResult::InvalidInput
+            );
+        }
+        // starknet.py get_selector_from_name("Transfer")
+        assert_eq!(
+            Felt::from_bytes_be(&out),
+            Felt::from_hex_unchecked(
+                "0x99cd8bde557814842a3121e8ddfd433a539b8c9f14bf31ebf108d12e6196e9"
+            )
+        );
+    }
+
+    #[test]
+    fn test_event_keys() {
+        let selector = |name: &str| felt_to_bytes(&sn_keccak(name.as_bytes()));
+
+        let (result, nested) = keys("ERC20Event::Transfer", 0);
+        assert_eq!(result, StarkResult::Success);
+        assert_eq!(nested, [selector("ERC20Event"), selector("Transfer")]);
+
+        let (result, flat) = keys("ERC20Event::Transfer", 0b01);
+        assert_eq!(result, StarkResult::Success);
+        assert_eq!(flat, [selector("Transfer")]);
+
+        for (path, mask) in [("", 0), ("A::", 0), ("A::B", 0b100)] {
+            assert_eq!(keys(path, mask).0, StarkResult::InvalidInput);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Felt arrays converted once and hashed many times
+//!
+//! Every function taking `FeltBytes` parses each 32-byte buffer into a
+//! `Felt` (a reduction mod P into Montgomery form) on every call. When the
+//! host hashes the same data again and again, for instance sliding windows
+//! over a trace, it can convert it once into a `StarkFeltArray` and hash
+//! ranges of that array with `starknet_poseidon_hash_felt_array`, which reads
+//! the felts in place. An array is immutable, so it can be shared between
+//! threads.
+
+use alloc::format;
+use alloc::vec::Vec;
+
+#[cfg(feature = "hashing")]
+use starknet_crypto::poseidon_hash_many;
+use starknet_crypto::Felt;
+
+use crate::error::invalid_input;
+use crate::handle::StarkHandle;
+#[cfg(feature = "hashing")]
+use crate::perf::{self, Op};
+use crate::{felt_from_bytes, felt_to_bytes, ffi_guard, ffi_guard_void, FeltBytes, StarkResult};
+
+/// Immutable array of converted felts
+pub struct StarkFeltArray {
+    felts: Vec<Felt>,
+}
+
+impl StarkHandle for StarkFeltArray {
+    const NAME: &'static str = "StarkFeltArray";
+}
+
+impl StarkFeltArray {
+    /// `felts[start..start + count]`, or `InvalidInput` if it is out of range
+    fn range(&self, start: usize, count: usize) -> Result<&[Felt], StarkResult> {
+        match start.checked_add(count) {
+            Some(end) if end <= self.felts.len() => Ok(&self.felts[start..end]),
+            _ => Err(invalid_input(format!(
+                "range {start}+{count} is out of bounds (array of {})",
+                self.felts.len()
+            ))),
+        }
+    }
+}
+
+/// Convert `count` felts into a new array
+///
+/// `felts` may be null when `count == 0`. Writes an owned handle to
+/// `out_array`; release it with `stark_felt_array_free`.
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_stark_felt_array_new"
+)]
+pub unsafe extern "C" fn stark_felt_array_new(
+    felts: *const FeltBytes,
+    count: usize,
+    out_array: *mut *mut StarkFeltArray,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_array);
+        let felts = if count == 0 {
+            Vec::new()
+        } else {
+            check_null!(felts);
+            let slice = core::slice::from_raw_parts(felts, count);
+            match slice
+                .iter()
+                .map(felt_from_bytes)
+                .collect::<Option<Vec<_>>>()
+            {
+                Some(felts) => felts,
+                None => return StarkResult::InvalidInput,
+            }
+        };
+        *out_array = StarkFeltArray { felts }.into_handle();
+        StarkResult::Success
+    })
+}
+
+/// Number of felts in the array (0 for null)
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_stark_felt_array_len"
+)]
+pub unsafe extern "C" fn stark_felt_array_len(array: *const StarkFeltArray) -> usize {
+    let mut len = 0;
+    ffi_guard(|| {
+        if array.is_null() {
+            return StarkResult::Success;
+        }
+        match StarkFeltArray::from_handle(array) {
+            Ok(array) => {
+                len = array.felts.len();
+                StarkResult::Success
+            }
+            Err(e) => e,
+        }
+    });
+    len
+}
+
+/// Read the felt at `index`
+///
+/// Returns `InvalidInput` if `index` is out of bounds.
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_stark_felt_array_get"
+)]
+pub unsafe extern "C" fn stark_felt_array_get(
+    array: *const StarkFeltArray,
+    index: usize,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(array, out);
+        match StarkFeltArray::from_handle(array).and_then(|array| array.range(index, 1)) {
+            Ok(felt) => {
+                *out = felt_to_bytes(&felt[0]);
+                StarkResult::Success
+            }
+            Err(e) => e,
+        }
+    })
+}
+
+/// Free an array (null is a no-op)
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_stark_felt_array_free"
+)]
+pub unsafe extern "C" fn stark_felt_array_free(array: *mut StarkFeltArray) {
+    ffi_guard_void(|| StarkFeltArray::free_handle(array))
+}
+
+/// Poseidon hash of `count` felts of `array` from index `start`
+///
+/// Same result as `starknet_poseidon_hash_many` over those felts, without
+/// converting them again. Returns `InvalidInput` if `count` is 0 or the
+/// range is out of bounds.
+#[cfg(feature = "hashing")]
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_starknet_poseidon_hash_felt_array"
+)]
+pub unsafe extern "C" fn starknet_poseidon_hash_felt_array(
+    array: *const StarkFeltArray,
+    start: usize,
+    count: usize,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Poseidon);
+        check_null!(array, out);
+        if count == 0 {
+            return invalid_input("`count` is 0");
+        }
+        match StarkFeltArray::from_handle(array).and_then(|array| array.range(start, count)) {
+            Ok(felts) => {
+                *out = felt_to_bytes(&poseidon_hash_many(felts));
+                StarkResult::Success
+            }
+            Err(e) => e,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn felts(count: u64) -> Vec<FeltBytes> {
+        (0..count)
+            .map(|i| felt_to_bytes(&Felt::from(i + 7).pow(0x1234_5678_9abc_def1u64)))
+            .collect()
+    }
+
+    #[test]
+    fn test_felt_array() {
+        let bytes = felts(6);
+        let mut array = core::ptr::null_mut();
+        let mut out = [0u8; 32];
+        unsafe {
+            assert_eq!(
+                stark_felt_array_new(bytes.as_ptr(), bytes.len(), &mut array),
+                StarkResult::Success
+            );
+            assert_eq!(stark_felt_array_len(array), 6);
+            assert_eq!(
+                stark_felt_array_get(array, 5, &mut out),
+                StarkResult::Success
+            );
+            assert_eq!(out, bytes[5]);
+            assert_eq!(
+                stark_felt_array_get(array, 6, &mut out),
+                StarkResult::InvalidInput
+            );
+            assert_eq!(stark_felt_array_len(core::ptr::null()), 0);
+            stark_felt_array_free(array);
+
+            assert_eq!(
+                stark_felt_array_new(core::ptr::null(), 0, &mut array),
+                StarkResult::Success
+            );
+            assert_eq!(stark_felt_array_len(array), 0);
+            stark_felt_array_free(array);
+        }
+    }
+
+    #[cfg(feature = "hashing")]
+    #[test]
+    fn test_poseidon_hash_felt_array() {
+        use crate::starknet_poseidon_hash_many;
+
+        let bytes = felts(10);
+        let mut array = core::ptr::null_mut();
+        let (mut expected, mut out) = ([0u8; 32], [0u8; 32]);
+        unsafe {
+            stark_felt_array_new(bytes.as_ptr(), bytes.len(), &mut array);
+            for (start, count) in [(0, 10), (3, 4), (9, 1)] {
+                starknet_poseidon_hash_many(bytes[start..].as_ptr(), count, &mut expected);
+                assert_eq!(
+                    starknet_poseidon_hash_felt_array(array, start, count, &mut out),
+                    StarkResult::Success
+                );
+                assert_eq!(out, expected);
+            }
+            for (start, count) in [(0, 0), (8, 3), (usize::MAX, 2)] {
+                assert_eq!(
+                    starknet_poseidon_hash_felt_array(array, start, count, &mut out),
+                    StarkResult::InvalidInput
+                );
+            }
+            stark_felt_array_free(array);
+        }
+    }
+}
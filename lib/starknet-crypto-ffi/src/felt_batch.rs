@@ -0,0 +1,443 @@
+//! Element-wise felt arithmetic over arrays
+//!
+//! `felt_vec_add`, `felt_vec_sub` and `felt_vec_mul` apply one operation to
+//! `count` pairs in a single call, and `felt_vec_scale` multiplies `count`
+//! felts by one scalar, for workloads (trace generation, host-side
+//! polynomial code) that would otherwise cross the FFI boundary once per
+//! element.
+//! The inner loop is compiled twice on x86: once for the baseline target
+//! and once with AVX2 enabled, picked at runtime by CPU feature detection
+//! (with `std`), so the compiler can vectorize the limb arithmetic and the
+//! byte conversions. NEON is part of the aarch64 baseline, so the single
+//! build already uses it there.
+//!
+//! `out` may be the same array as an input (in-place update), but must not
+//! partially overlap it.
+//!
+//! `felt_inner_product` sums the full 512-bit products of the canonical
+//! integers in a 576-bit accumulator and reduces mod P once at the end,
+//! instead of a Montgomery conversion and reduction per element;
+//! `felt_mat_vec_mul` does the same for each row of a matrix.
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use starknet_crypto::Felt;
+
+use crate::error::invalid_input;
+use crate::perf::{self, Op};
+use crate::{felt_to_bytes, ffi_guard, FeltBytes, StarkResult};
+
+/// `out[i] = op(a[i], b[i * b_step])`
+///
+/// `b_step` is 1 for an array and 0 for a scalar. Inputs are reduced mod P
+/// like `felt_from_bytes` does, without its arbitrary-length slice path.
+/// Each input element is read before its output is written, so `out` may
+/// alias `a` or `b` exactly.
+#[inline(always)]
+unsafe fn zip(
+    a: *const FeltBytes,
+    b: *const FeltBytes,
+    b_step: usize,
+    count: usize,
+    out: *mut FeltBytes,
+    op: impl Fn(Felt, Felt) -> Felt,
+) {
+    for i in 0..count {
+        let x = Felt::from_bytes_be(&a.add(i).read());
+        let y = Felt::from_bytes_be(&b.add(i * b_step).read());
+        out.add(i).write(felt_to_bytes(&op(x, y)));
+    }
+}
+
+#[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+#[target_feature(enable = "avx2")]
+unsafe fn zip_avx2(
+    a: *const FeltBytes,
+    b: *const FeltBytes,
+    b_step: usize,
+    count: usize,
+    out: *mut FeltBytes,
+    op: impl Fn(Felt, Felt) -> Felt,
+) {
+    zip(a, b, b_step, count, out, op)
+}
+
+/// `zip` with the fastest inner loop this CPU supports
+unsafe fn dispatch(
+    a: *const FeltBytes,
+    b: *const FeltBytes,
+    b_step: usize,
+    count: usize,
+    out: *mut FeltBytes,
+    op: impl Fn(Felt, Felt) -> Felt,
+) {
+    #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+    if std::is_x86_feature_detected!("avx2") {
+        return zip_avx2(a, b, b_step, count, out, op);
+    }
+    zip(a, b, b_step, count, out, op)
+}
+
+/// Checks shared by the batch functions, then `dispatch`
+unsafe fn batch(
+    a: *const FeltBytes,
+    b: *const FeltBytes,
+    b_step: usize,
+    count: usize,
+    out: *mut FeltBytes,
+    op: impl Fn(Felt, Felt) -> Felt,
+) -> StarkResult {
+    let _timer = perf::timer(Op::FeltArith);
+    if count == 0 {
+        return StarkResult::Success;
+    }
+    check_null!(a, b, out);
+    dispatch(a, b, b_step, count, out, op);
+    StarkResult::Success
+}
+
+/// Add `count` pairs of felts: `out[i] = (a[i] + b[i]) mod P`
+///
+/// The arrays may be null when `count == 0`. `out` may be `a` or `b`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_vec_add")]
+pub unsafe extern "C" fn felt_vec_add(
+    a: *const FeltBytes,
+    b: *const FeltBytes,
+    count: usize,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| batch(a, b, 1, count, out, |x, y| x + y))
+}
+
+/// Subtract `count` pairs of felts: `out[i] = (a[i] - b[i]) mod P`
+///
+/// The arrays may be null when `count == 0`. `out` may be `a` or `b`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_vec_sub")]
+pub unsafe extern "C" fn felt_vec_sub(
+    a: *const FeltBytes,
+    b: *const FeltBytes,
+    count: usize,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| batch(a, b, 1, count, out, |x, y| x - y))
+}
+
+/// Multiply `count` pairs of felts: `out[i] = (a[i] * b[i]) mod P`
+///
+/// The arrays may be null when `count == 0`. `out` may be `a` or `b`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_vec_mul")]
+pub unsafe extern "C" fn felt_vec_mul(
+    a: *const FeltBytes,
+    b: *const FeltBytes,
+    count: usize,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| batch(a, b, 1, count, out, |x, y| x * y))
+}
+
+/// Multiply `count` felts by one scalar: `out[i] = (a[i] * scalar) mod P`
+///
+/// The arrays may be null when `count == 0`. `out` may be `a`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_vec_scale")]
+pub unsafe extern "C" fn felt_vec_scale(
+    a: *const FeltBytes,
+    scalar: *const FeltBytes,
+    count: usize,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| batch(a, scalar, 0, count, out, |x, y| x * y))
+}
+
+/// 64-bit limbs of a big-endian 256-bit integer, least significant first
+#[inline(always)]
+fn limbs(bytes: &FeltBytes) -> [u64; 4] {
+    core::array::from_fn(|i| u64::from_be_bytes(bytes[24 - 8 * i..32 - 8 * i].try_into().unwrap()))
+}
+
+/// `Σ a[i]·b[i]` over the integers, least significant limb first
+///
+/// Each product is below 2^512 and there are fewer than 2^64 of them, so
+/// the sum fits in 576 bits.
+unsafe fn wide_inner_product(a: *const FeltBytes, b: *const FeltBytes, count: usize) -> [u64; 9] {
+    let mut acc = [0u64; 9];
+    for i in 0..count {
+        let (x, y) = (limbs(&*a.add(i)), limbs(&*b.add(i)));
+        let mut product = [0u64; 8];
+        for (j, &xj) in x.iter().enumerate() {
+            let mut carry = 0u128;
+            for (k, &yk) in y.iter().enumerate() {
+                let t = xj as u128 * yk as u128 + product[j + k] as u128 + carry;
+                product[j + k] = t as u64;
+                carry = t >> 64;
+            }
+            product[j + 4] = carry as u64;
+        }
+        let mut carry = false;
+        for (j, limb) in acc.iter_mut().enumerate() {
+            let (sum, c1) = limb.overflowing_add(product.get(j).copied().unwrap_or(0));
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 | c2;
+        }
+    }
+    acc
+}
+
+/// A 576-bit integer (least significant limb first) reduced mod P
+fn reduce_wide(limbs: [u64; 9]) -> Felt {
+    let mut bytes = [0u8; 72];
+    for (chunk, limb) in bytes.rchunks_exact_mut(8).zip(limbs) {
+        chunk.copy_from_slice(&limb.to_be_bytes());
+    }
+    Felt::from_bytes_be_slice(&bytes)
+}
+
+/// Inner product of `count` pairs of felts: `out = Σ a[i]·b[i] mod P`
+///
+/// One reduction for the whole sum. The arrays may be null when
+/// `count == 0` (the sum is then 0).
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_inner_product")]
+pub unsafe extern "C" fn felt_inner_product(
+    a: *const FeltBytes,
+    b: *const FeltBytes,
+    count: usize,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::FeltArith);
+        check_null!(out);
+        if count > 0 {
+            check_null!(a, b);
+        }
+        *out = felt_to_bytes(&reduce_wide(wide_inner_product(a, b, count)));
+        StarkResult::Success
+    })
+}
+
+/// Multiply a row-major `rows × cols` matrix by a vector of `cols` felts:
+/// `out[r] = Σ matrix[r·cols + c]·vector[c] mod P`
+///
+/// Each row is one `felt_inner_product`. `matrix` and `vector` may be null
+/// when `cols == 0` (the rows are then 0), `out` when `rows == 0`. The whole
+/// result is computed before `out` is written, so `out` may be `vector`.
+/// Returns `InvalidInput` if `rows × cols` overflows.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_mat_vec_mul")]
+pub unsafe extern "C" fn felt_mat_vec_mul(
+    matrix: *const FeltBytes,
+    rows: usize,
+    cols: usize,
+    vector: *const FeltBytes,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::FeltArith);
+        if rows.checked_mul(cols).is_none() {
+            return invalid_input(format!("`rows` × `cols` ({rows} × {cols}) overflows"));
+        }
+        if rows == 0 {
+            return StarkResult::Success;
+        }
+        check_null!(out);
+        if cols > 0 {
+            check_null!(matrix, vector);
+        }
+        let result: Vec<Felt> = (0..rows)
+            .map(|r| reduce_wide(wide_inner_product(matrix.add(r * cols), vector, cols)))
+            .collect();
+        for (r, felt) in result.iter().enumerate() {
+            out.add(r).write(felt_to_bytes(felt));
+        }
+        StarkResult::Success
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::{felt_add, felt_mul, felt_sub};
+
+    type Batch = unsafe extern "C" fn(
+        *const FeltBytes,
+        *const FeltBytes,
+        usize,
+        *mut FeltBytes,
+    ) -> StarkResult;
+    type Single =
+        unsafe extern "C" fn(*const FeltBytes, *const FeltBytes, *mut FeltBytes) -> StarkResult;
+
+    const OPS: [(Batch, Single); 3] = [
+        (felt_vec_add, felt_add),
+        (felt_vec_sub, felt_sub),
+        (felt_vec_mul, felt_mul),
+    ];
+
+    /// Felts spread over the field, including 0, P - 1 and an unreduced
+    /// 2^256 - 1
+    fn felts(seed: u64, count: usize) -> Vec<FeltBytes> {
+        (0..count as u64)
+            .map(|i| match i {
+                0 => [0u8; 32],
+                1 => felt_to_bytes(&Felt::MAX),
+                2 => [0xff; 32],
+                _ => felt_to_bytes(&Felt::from(seed + i).pow(0x1234_5678_9abc_def1u64)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_batch_matches_single() {
+        let (a, b) = (felts(7, 37), felts(11, 37));
+        for (batch, single) in OPS {
+            let mut out = [[0u8; 32]; 37];
+            unsafe {
+                assert_eq!(
+                    batch(a.as_ptr(), b.as_ptr(), a.len(), out.as_mut_ptr()),
+                    StarkResult::Success
+                );
+            }
+            for i in 0..a.len() {
+                let mut expected = [0u8; 32];
+                unsafe { single(&a[i], &b[i], &mut expected) };
+                assert_eq!(out[i], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_batch_in_place() {
+        let (a, b) = (felts(3, 9), felts(5, 9));
+        let mut expected = [[0u8; 32]; 9];
+        let mut in_place = a.clone();
+        unsafe {
+            felt_vec_mul(a.as_ptr(), b.as_ptr(), a.len(), expected.as_mut_ptr());
+            let out = in_place.as_mut_ptr();
+            assert_eq!(
+                felt_vec_mul(out, b.as_ptr(), a.len(), out),
+                StarkResult::Success
+            );
+        }
+        assert_eq!(in_place, expected);
+    }
+
+    #[test]
+    fn test_vec_scale() {
+        let a = felts(13, 11);
+        let scalar = felt_to_bytes(&Felt::from(0xdead_beefu64));
+        let mut out = a.clone();
+        unsafe {
+            let out = out.as_mut_ptr();
+            assert_eq!(
+                felt_vec_scale(out, &scalar, a.len(), out),
+                StarkResult::Success
+            );
+        }
+        for i in 0..a.len() {
+            let mut expected = [0u8; 32];
+            unsafe { felt_mul(&a[i], &scalar, &mut expected) };
+            assert_eq!(out[i], expected);
+        }
+    }
+
+    #[test]
+    fn test_inner_product() {
+        let (a, b) = (felts(17, 29), felts(19, 29));
+        // Worst case for the accumulator: every product near 2^512
+        let max = alloc::vec![[0xffu8; 32]; 64];
+        for (a, b) in [(&a, &b), (&max, &max)] {
+            let mut expected = Felt::ZERO;
+            for (x, y) in a.iter().zip(b) {
+                expected += Felt::from_bytes_be(x) * Felt::from_bytes_be(y);
+            }
+            let mut out = [0u8; 32];
+            unsafe {
+                assert_eq!(
+                    felt_inner_product(a.as_ptr(), b.as_ptr(), a.len(), &mut out),
+                    StarkResult::Success
+                );
+            }
+            assert_eq!(out, felt_to_bytes(&expected));
+        }
+
+        let mut out = [1u8; 32];
+        unsafe {
+            assert_eq!(
+                felt_inner_product(core::ptr::null(), core::ptr::null(), 0, &mut out),
+                StarkResult::Success
+            );
+        }
+        assert_eq!(out, [0u8; 32]);
+    }
+
+    #[test]
+    fn test_mat_vec_mul() {
+        let (rows, cols) = (3, 5);
+        let matrix = felts(23, rows * cols);
+        let mut vector = felts(29, cols);
+        let mut out = [[0u8; 32]; 3];
+        unsafe {
+            assert_eq!(
+                felt_mat_vec_mul(
+                    matrix.as_ptr(),
+                    rows,
+                    cols,
+                    vector.as_ptr(),
+                    out.as_mut_ptr()
+                ),
+                StarkResult::Success
+            );
+        }
+        for (r, out) in out.iter().enumerate() {
+            let mut expected = [0u8; 32];
+            let row = &matrix[r * cols..(r + 1) * cols];
+            unsafe { felt_inner_product(row.as_ptr(), vector.as_ptr(), cols, &mut expected) };
+            assert_eq!(*out, expected);
+        }
+
+        // In place with a square matrix
+        let square = felts(31, cols * cols);
+        let mut expected = [[0u8; 32]; 5];
+        unsafe {
+            let v = vector.as_mut_ptr();
+            felt_mat_vec_mul(square.as_ptr(), cols, cols, v, expected.as_mut_ptr());
+            assert_eq!(
+                felt_mat_vec_mul(square.as_ptr(), cols, cols, v, v),
+                StarkResult::Success
+            );
+            assert_eq!(vector, expected);
+            assert_eq!(
+                felt_mat_vec_mul(square.as_ptr(), usize::MAX, 2, v, v),
+                StarkResult::InvalidInput
+            );
+        }
+    }
+
+    #[test]
+    fn test_batch_empty_and_null() {
+        let a = felts(1, 2);
+        let mut out = [[0u8; 32]; 2];
+        unsafe {
+            assert_eq!(
+                felt_vec_add(
+                    core::ptr::null(),
+                    core::ptr::null(),
+                    0,
+                    core::ptr::null_mut()
+                ),
+                StarkResult::Success
+            );
+            assert_eq!(
+                felt_vec_add(a.as_ptr(), core::ptr::null(), 2, out.as_mut_ptr()),
+                StarkResult::NullPointer
+            );
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Deterministic felts derived from a secret seed
+//!
+//! `felt_derive` turns a master secret into any number of independent,
+//! reproducible felts (salts, blinding factors, nullifiers), one per domain
+//! tag: HKDF-SHA256 (RFC 5869) with the seed as input keying material, no
+//! salt and the tag as `info`, expanded to 64 bytes and reduced mod P. A
+//! 512-bit value reduces with a negligible bias (below 2^-250), where 32
+//! bytes would make some felts about 3% likelier (2^256 is close to 32·P).
+//! Anyone with HKDF-SHA256 can recompute a derived felt. It is built with
+//! the `hashing` feature.
+//!
+//! `felt_from_hash512` exposes that reduction for 64-byte digests from
+//! other hashes (SHA-512, BLAKE2b, Fiat–Shamir transcripts).
+
+#[cfg(feature = "hashing")]
+use hkdf::Hkdf;
+#[cfg(feature = "hashing")]
+use sha2::Sha256;
+use starknet_crypto::Felt;
+#[cfg(feature = "hashing")]
+use zeroize::Zeroizing;
+
+#[cfg(feature = "hashing")]
+use crate::{bytes_from_raw, error::invalid_input, error::null_pointer};
+use crate::{felt_to_bytes, ffi_guard, FeltBytes, StarkResult};
+
+/// Bytes reduced into one felt
+const WIDE_LEN: usize = 64;
+
+/// Big-endian `bytes` reduced mod P (near-uniform for uniform bytes)
+fn felt_from_wide_bytes(bytes: &[u8; WIDE_LEN]) -> Felt {
+    Felt::from_bytes_be_slice(bytes)
+}
+
+#[cfg(feature = "hashing")]
+fn derive(seed: &[u8], tag: &[u8]) -> Felt {
+    let mut okm = Zeroizing::new([0u8; WIDE_LEN]);
+    Hkdf::<Sha256>::new(None, seed)
+        .expand(tag, &mut okm[..])
+        .expect("64 bytes is a valid HKDF-SHA256 output length");
+    felt_from_wide_bytes(&okm)
+}
+
+/// Derive the felt for `domain_tag` from a secret seed
+///
+/// `seed` must not be empty; `domain_tag` may be (null when `tag_len` is 0).
+/// The same seed and tag always give the same felt; different tags give
+/// independent felts. Returns `InvalidInput` if `seed_len` is 0.
+#[cfg(feature = "hashing")]
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_derive")]
+pub unsafe extern "C" fn felt_derive(
+    seed: *const u8,
+    seed_len: usize,
+    domain_tag: *const u8,
+    tag_len: usize,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(seed, out);
+        if seed_len == 0 {
+            return invalid_input("`seed_len` is 0");
+        }
+        let seed = core::slice::from_raw_parts(seed, seed_len);
+        let Some(tag) = bytes_from_raw(domain_tag, tag_len) else {
+            return null_pointer("domain_tag");
+        };
+        *out = felt_to_bytes(&derive(seed, tag));
+        StarkResult::Success
+    })
+}
+
+/// Reduce a 64-byte big-endian digest mod P
+///
+/// For a uniform digest the felt is uniform up to a bias below 2^-250, where
+/// reducing 32 bytes would be biased by about 3%.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_from_hash512")]
+pub unsafe extern "C" fn felt_from_hash512(
+    digest: *const [u8; WIDE_LEN],
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(digest, out);
+        *out = felt_to_bytes(&felt_from_wide_bytes(&*digest));
+        StarkResult::Success
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "hashing")]
+    #[test]
+    fn test_felt_derive() {
+        let seed = [7u8; 32];
+        let (mut a, mut b, mut again) = ([0u8; 32], [0u8; 32], [0u8; 32]);
+        unsafe {
+            assert_eq!(
+                felt_derive(seed.as_ptr(), seed.len(), b"salt".as_ptr(), 4, &mut a),
+                StarkResult::Success
+            );
+            felt_derive(seed.as_ptr(), seed.len(), b"nullifier".as_ptr(), 9, &mut b);
+            felt_derive(seed.as_ptr(), seed.len(), b"salt".as_ptr(), 4, &mut again);
+            assert_eq!(a, again);
+            assert_ne!(a, b);
+            assert_eq!(
+                felt_derive(seed.as_ptr(), seed.len(), core::ptr::null(), 0, &mut b),
+                StarkResult::Success
+            );
+            assert_eq!(
+                felt_derive(seed.as_ptr(), 0, core::ptr::null(), 0, &mut b),
+                StarkResult::InvalidInput
+            );
+        }
+
+        // HKDF-SHA256 output reduced mod P
+        let mut okm = [0u8; 64];
+        Hkdf::<Sha256>::new(None, &seed)
+            .expand(b"salt", &mut okm)
+            .unwrap();
+        let expected = num_bigint::BigUint::from_bytes_be(&okm) % (Felt::MAX.to_biguint() + 1u32);
+        assert_eq!(Felt::from_bytes_be(&a).to_biguint(), expected);
+    }
+
+    #[test]
+    fn test_felt_from_hash512() {
+        let p = Felt::MAX.to_biguint() + 1u32;
+        let mut out = [0u8; 32];
+        for digest in [
+            [0u8; 64],
+            [0xff; 64],
+            core::array::from_fn(|i| (i as u8).wrapping_mul(37)),
+        ] {
+            unsafe {
+                assert_eq!(felt_from_hash512(&digest, &mut out), StarkResult::Success);
+            }
+            let expected = num_bigint::BigUint::from_bytes_be(&digest) % &p;
+            assert_eq!(Felt::from_bytes_be(&out).to_biguint(), expected);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Opaque handles: one ownership convention for every stateful object
+//!
+//! A stateful object (ABI, calldata builder/reader, secret buffer, verify
+//! context, ...) is boxed and handed to C as an opaque pointer by a `*_new`
+//! function (written to an `out_*` argument), borrowed by the functions that
+//! use it, and released by the matching `*_free` function, where null is a
+//! no-op. Every handle type implements `StarkHandle`, which does all three.
+//!
+//! Debug builds keep a registry of live handles with their type, so using a
+//! handle after it was freed, freeing it twice, or passing a handle of the
+//! wrong type is reported as `InvalidHandle` (and a bad free is ignored)
+//! instead of being undefined behaviour. The check is best effort: the
+//! allocator may hand a freed address to a new handle of the same type.
+//! Release builds trust the caller, as with any other pointer.
+
+use alloc::boxed::Box;
+
+use crate::StarkResult;
+
+#[cfg(debug_assertions)]
+mod registry {
+    use alloc::collections::BTreeMap;
+    use core::any::TypeId;
+
+    use crate::sync::{Mutex, MutexGuard};
+
+    /// Address -> type of every live handle
+    static LIVE: Mutex<BTreeMap<usize, TypeId>> = Mutex::new(BTreeMap::new());
+
+    fn live() -> MutexGuard<'static, BTreeMap<usize, TypeId>> {
+        LIVE.lock()
+    }
+
+    pub(super) fn insert<T: 'static>(addr: usize) {
+        live().insert(addr, TypeId::of::<T>());
+    }
+
+    pub(super) fn contains<T: 'static>(addr: usize) -> bool {
+        live().get(&addr) == Some(&TypeId::of::<T>())
+    }
+
+    pub(super) fn remove<T: 'static>(addr: usize) -> bool {
+        let mut live = live();
+        if live.get(&addr) != Some(&TypeId::of::<T>()) {
+            return false;
+        }
+        live.remove(&addr);
+        true
+    }
+}
+
+/// Record a dead or foreign handle of type `T` and return `InvalidHandle`
+#[cfg(debug_assertions)]
+fn invalid_handle<T: StarkHandle>() -> StarkResult {
+    crate::error::set_detail(alloc::format!(
+        "not a live `{}` handle (already freed, or not created by its `_new` function)",
+        T::NAME
+    ));
+    StarkResult::InvalidHandle
+}
+
+/// An object handed to C as an opaque pointer
+pub(crate) trait StarkHandle: Sized + 'static {
+    /// Type name used in error messages
+    #[cfg_attr(not(debug_assertions), allow(dead_code))]
+    const NAME: &'static str;
+
+    /// Box `self` and return the owned handle
+    fn into_handle(self) -> *mut Self {
+        let ptr = Box::into_raw(Box::new(self));
+        #[cfg(debug_assertions)]
+        registry::insert::<Self>(ptr as usize);
+        ptr
+    }
+
+    /// Borrow a live handle (`ptr` must be non-null; check with `check_null!`)
+    unsafe fn from_handle<'a>(ptr: *const Self) -> Result<&'a Self, StarkResult> {
+        #[cfg(debug_assertions)]
+        if !registry::contains::<Self>(ptr as usize) {
+            return Err(invalid_handle::<Self>());
+        }
+        Ok(&*ptr)
+    }
+
+    /// Mutably borrow a live handle (`ptr` must be non-null)
+    unsafe fn from_handle_mut<'a>(ptr: *mut Self) -> Result<&'a mut Self, StarkResult> {
+        #[cfg(debug_assertions)]
+        if !registry::contains::<Self>(ptr as usize) {
+            return Err(invalid_handle::<Self>());
+        }
+        Ok(&mut *ptr)
+    }
+
+    /// Release a handle (null is a no-op)
+    ///
+    /// In debug builds a handle that is not live is left alone and reported
+    /// as `InvalidHandle`.
+    unsafe fn free_handle(ptr: *mut Self) -> StarkResult {
+        if ptr.is_null() {
+            return StarkResult::Success;
+        }
+        #[cfg(debug_assertions)]
+        if !registry::remove::<Self>(ptr as usize) {
+            return invalid_handle::<Self>();
+        }
+        drop(Box::from_raw(ptr));
+        StarkResult::Success
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Probe(u64);
+
+    impl StarkHandle for Probe {
+        const NAME: &'static str = "Probe";
+    }
+
+    // Each test uses its own types: a freed address can be reused by an
+    // allocation of the same type in a test running in parallel
+    #[cfg(debug_assertions)]
+    struct Stale(u64);
+
+    #[cfg(debug_assertions)]
+    impl StarkHandle for Stale {
+        const NAME: &'static str = "Stale";
+    }
+
+    #[cfg(debug_assertions)]
+    struct Other(u64);
+
+    #[cfg(debug_assertions)]
+    impl StarkHandle for Other {
+        const NAME: &'static str = "Other";
+    }
+
+    #[test]
+    fn test_handle_lifecycle() {
+        let handle = Probe(7).into_handle();
+        unsafe {
+            Probe::from_handle_mut(handle).unwrap().0 += 1;
+            assert_eq!(Probe::from_handle(handle).unwrap().0, 8);
+            assert_eq!(Probe::free_handle(handle), StarkResult::Success);
+            assert_eq!(
+                Probe::free_handle(core::ptr::null_mut()),
+                StarkResult::Success
+            );
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_handle_misuse_detected() {
+        let handle = Stale(1).into_handle();
+        let other = Other(2).into_handle();
+        unsafe {
+            assert_eq!(Stale::from_handle(handle).unwrap().0, 1);
+            assert_eq!(Other::from_handle(other).unwrap().0, 2);
+
+            // Wrong type
+            assert_eq!(
+                Stale::from_handle(other.cast()).err(),
+                Some(StarkResult::InvalidHandle)
+            );
+            assert_eq!(Stale::free_handle(other.cast()), StarkResult::InvalidHandle);
+            assert_eq!(Other::free_handle(other), StarkResult::Success);
+
+            // Use after free and double free
+            assert_eq!(Stale::free_handle(handle), StarkResult::Success);
+            assert_eq!(
+                Stale::from_handle(handle).err(),
+                Some(StarkResult::InvalidHandle)
+            );
+            assert_eq!(Stale::free_handle(handle), StarkResult::InvalidHandle);
+        }
+    }
+}
@@ -0,0 +1,436 @@
+//! Web3 Secret Storage (keystore v3)
+//!
+//! The JSON key files written by geth, MetaMask exports and most Ethereum
+//! tooling. A key derived from the password (scrypt or PBKDF2-HMAC-SHA256)
+//! encrypts the private key with AES-128-CTR under its first 16 bytes; the
+//! MAC is `keccak256(derived_key[16..32] || ciphertext)`.
+//!
+//! New files use geth's standard scrypt parameters (n = 2^18, r = 8, p = 1).
+//! When decrypting, KDF costs (scrypt memory, parallelism and total work,
+//! PBKDF2 rounds) are capped so a hostile file cannot demand unbounded
+//! memory or time.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+
+use zeroize::Zeroizing;
+
+use crate::{bytes_from_raw, error::null_pointer, ffi_guard, random, write_bytes_out, StarkResult};
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+const SCRYPT_LOG_N: u8 = 18;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// Largest scrypt working set accepted when decrypting (128 * r * n bytes)
+const SCRYPT_MAX_MEMORY: u64 = 1 << 30;
+/// Largest scrypt parallelism accepted when decrypting (each lane is a
+/// sequential ROMix pass)
+const SCRYPT_MAX_P: u32 = 16;
+/// Largest scrypt work (n * r * p, 8 times geth's standard parameters)
+/// accepted when decrypting
+const SCRYPT_MAX_WORK: u64 = 1 << 24;
+/// Largest PBKDF2 iteration count accepted when decrypting
+const PBKDF2_MAX_ROUNDS: u32 = 10_000_000;
+
+/// Upper bound on the length of the JSON written by `keystore_encrypt`
+pub const KEYSTORE_JSON_MAX_LEN: usize = 512;
+
+enum Kdf {
+    Scrypt {
+        log_n: u8,
+        r: u32,
+        p: u32,
+        salt: Vec<u8>,
+    },
+    Pbkdf2 {
+        rounds: u32,
+        salt: Vec<u8>,
+    },
+}
+
+impl Kdf {
+    fn derive(&self, password: &[u8]) -> Option<Zeroizing<[u8; 32]>> {
+        let mut key = Zeroizing::new([0u8; 32]);
+        match self {
+            Kdf::Scrypt { log_n, r, p, salt } => {
+                let params = scrypt::Params::new(*log_n, *r, *p, key.len()).ok()?;
+                scrypt::scrypt(password, salt, &params, &mut key[..]).ok()?;
+            }
+            Kdf::Pbkdf2 { rounds, salt } => {
+                pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, *rounds, &mut key[..]);
+            }
+        }
+        Some(key)
+    }
+
+    fn to_json(&self) -> (&'static str, Value) {
+        match self {
+            Kdf::Scrypt { log_n, r, p, salt } => (
+                "scrypt",
+                json!({ "dklen": 32, "n": 1u64 << log_n, "r": r, "p": p, "salt": to_hex(salt) }),
+            ),
+            Kdf::Pbkdf2 { rounds, salt } => (
+                "pbkdf2",
+                json!({ "dklen": 32, "c": rounds, "prf": "hmac-sha256", "salt": to_hex(salt) }),
+            ),
+        }
+    }
+
+    fn from_json(kdf: &str, params: &Value) -> Option<Self> {
+        if params.get("dklen")?.as_u64()? != 32 {
+            return None;
+        }
+        let salt = from_hex(params.get("salt")?.as_str()?)?;
+        let u32_param = |name: &str| u32::try_from(params.get(name)?.as_u64()?).ok();
+
+        match kdf {
+            "scrypt" => {
+                let n = params.get("n")?.as_u64()?;
+                let (r, p) = (u32_param("r")?, u32_param("p")?);
+                if !n.is_power_of_two()
+                    || n < 2
+                    || n.checked_mul(128 * r as u64)? > SCRYPT_MAX_MEMORY
+                    || p == 0
+                    || p > SCRYPT_MAX_P
+                    || n.checked_mul(r as u64 * p as u64)? > SCRYPT_MAX_WORK
+                {
+                    return None;
+                }
+                Some(Kdf::Scrypt {
+                    log_n: n.trailing_zeros() as u8,
+                    r,
+                    p,
+                    salt,
+                })
+            }
+            "pbkdf2" => {
+                let rounds = u32_param("c")?;
+                if params.get("prf")?.as_str()? != "hmac-sha256"
+                    || rounds == 0
+                    || rounds > PBKDF2_MAX_ROUNDS
+                {
+                    return None;
+                }
+                Some(Kdf::Pbkdf2 { rounds, salt })
+            }
+            _ => None,
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn mac(derived_key: &[u8; 32], ciphertext: &[u8]) -> [u8; 32] {
+    Keccak256::new()
+        .chain_update(&derived_key[16..])
+        .chain_update(ciphertext)
+        .finalize()
+        .into()
+}
+
+/// RFC 4122 version 4 UUID from 16 random bytes
+fn uuid_v4(mut bytes: [u8; 16]) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = to_hex(&bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+fn encrypt(
+    private_key: &[u8; 32],
+    password: &[u8],
+    kdf: &Kdf,
+    iv: &[u8; 16],
+    id: [u8; 16],
+) -> Option<String> {
+    let derived_key = kdf.derive(password)?;
+    let mut ciphertext = *private_key;
+    Aes128Ctr::new(derived_key[..16].into(), iv.into()).apply_keystream(&mut ciphertext);
+
+    let (kdf_name, kdf_params) = kdf.to_json();
+    let keystore = json!({
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": { "iv": to_hex(iv) },
+            "ciphertext": to_hex(&ciphertext),
+            "kdf": kdf_name,
+            "kdfparams": kdf_params,
+            "mac": to_hex(&mac(&derived_key, &ciphertext)),
+        },
+        "id": uuid_v4(id),
+        "version": 3,
+    });
+    Some(keystore.to_string())
+}
+
+/// KDF, IV, ciphertext and MAC of a keystore holding a 32-byte key
+struct Keystore {
+    kdf: Kdf,
+    iv: [u8; 16],
+    ciphertext: [u8; 32],
+    mac: [u8; 32],
+}
+
+fn decrypt(json: &[u8], password: &[u8]) -> Result<Zeroizing<[u8; 32]>, StarkResult> {
+    let parse = || -> Option<Keystore> {
+        let root: Value = serde_json::from_slice(json).ok()?;
+        if root.get("version")?.as_u64()? != 3 {
+            return None;
+        }
+        // Early geth releases wrote "Crypto"
+        let crypto = root.get("crypto").or_else(|| root.get("Crypto"))?;
+        if crypto.get("cipher")?.as_str()? != "aes-128-ctr" {
+            return None;
+        }
+        let kdf = Kdf::from_json(crypto.get("kdf")?.as_str()?, crypto.get("kdfparams")?)?;
+        let iv = from_hex(crypto.get("cipherparams")?.get("iv")?.as_str()?)?;
+        let ciphertext = from_hex(crypto.get("ciphertext")?.as_str()?)?;
+        let mac = from_hex(crypto.get("mac")?.as_str()?)?;
+        Some(Keystore {
+            kdf,
+            iv: iv.try_into().ok()?,
+            ciphertext: ciphertext.try_into().ok()?,
+            mac: mac.try_into().ok()?,
+        })
+    };
+    let keystore = parse().ok_or(StarkResult::InvalidInput)?;
+
+    let derived_key = keystore
+        .kdf
+        .derive(password)
+        .ok_or(StarkResult::InvalidInput)?;
+    if mac(&derived_key, &keystore.ciphertext) != keystore.mac {
+        return Err(StarkResult::DecryptionFailed);
+    }
+    let mut private_key = Zeroizing::new(keystore.ciphertext);
+    Aes128Ctr::new(derived_key[..16].into(), (&keystore.iv).into())
+        .apply_keystream(&mut private_key[..]);
+    Ok(private_key)
+}
+
+/// Encrypt a 32-byte private key into a keystore v3 JSON file
+///
+/// Uses scrypt (n = 2^18, r = 8, p = 1) with a random salt, IV and id.
+/// `out_len` holds the capacity of `out_json` on input and the JSON length
+/// on output; if the buffer is too small, the required length is written and
+/// `BufferTooSmall` is returned. `KEYSTORE_JSON_MAX_LEN` bytes always suffice.
+/// Returns `RngFailed` if the OS random number generator is unavailable.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_keystore_encrypt")]
+pub unsafe extern "C" fn keystore_encrypt(
+    private_key: *const [u8; 32],
+    password: *const u8,
+    password_len: usize,
+    out_json: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(private_key, out_len);
+        let password = match bytes_from_raw(password, password_len) {
+            Some(p) => p,
+            None => return null_pointer("password"),
+        };
+        let mut random_bytes = [0u8; 64];
+        if random::fill_random(&mut random_bytes).is_err() {
+            return StarkResult::RngFailed;
+        }
+        let kdf = Kdf::Scrypt {
+            log_n: SCRYPT_LOG_N,
+            r: SCRYPT_R,
+            p: SCRYPT_P,
+            salt: random_bytes[..32].to_vec(),
+        };
+        let iv: [u8; 16] = random_bytes[32..48].try_into().unwrap();
+        let id: [u8; 16] = random_bytes[48..].try_into().unwrap();
+
+        let json = match encrypt(&*private_key, password, &kdf, &iv, id) {
+            Some(j) => j,
+            None => return StarkResult::InvalidInput,
+        };
+        write_bytes_out(json.as_bytes(), out_json, out_len)
+    })
+}
+
+/// Decrypt a keystore v3 JSON file holding a 32-byte private key
+///
+/// Accepts scrypt and PBKDF2 (hmac-sha256) files with AES-128-CTR. Returns
+/// `DecryptionFailed` on a MAC mismatch (wrong password or corrupted file)
+/// and `InvalidInput` for malformed JSON, unsupported algorithms or KDF
+/// costs above the limits.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_keystore_decrypt")]
+pub unsafe extern "C" fn keystore_decrypt(
+    json: *const u8,
+    json_len: usize,
+    password: *const u8,
+    password_len: usize,
+    out_private_key: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_private_key);
+        let json = match bytes_from_raw(json, json_len) {
+            Some(j) => j,
+            None => return null_pointer("json"),
+        };
+        let password = match bytes_from_raw(password, password_len) {
+            Some(p) => p,
+            None => return null_pointer("password"),
+        };
+
+        match decrypt(json, password) {
+            Ok(private_key) => {
+                *out_private_key = *private_key;
+                StarkResult::Success
+            }
+            Err(e) => e,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWORD: &[u8] = b"testpassword";
+    const PRIVATE_KEY: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+
+    // Web3 Secret Storage Definition test vectors
+    const PBKDF2_KEYSTORE: &str = r#"{
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": { "iv": "6087dab2f9fdbbfaddc31a909735c1e6" },
+            "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+            "kdf": "pbkdf2",
+            "kdfparams": {
+                "c": 262144,
+                "dklen": 32,
+                "prf": "hmac-sha256",
+                "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+            },
+            "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+        },
+        "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+        "version": 3
+    }"#;
+
+    // geth "light" scrypt parameters (n = 2^12, r = 8, p = 6). The spec's
+    // scrypt vector uses r = 1 with n = 2^18, which RFC 7914 forbids
+    // (n < 2^(16r)) and the scrypt crate rejects.
+    const SCRYPT_KEYSTORE: &str = r#"{
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": { "iv": "0f1e2d3c4b5a69788796a5b4c3d2e1f0" },
+            "ciphertext": "f406db52a1648594858cb05e271f32aaf31148f413815ee737b47e951f36dd5e",
+            "kdf": "scrypt",
+            "kdfparams": {
+                "dklen": 32,
+                "n": 4096,
+                "p": 6,
+                "r": 8,
+                "salt": "5c1a5d2dd4d5b3f8b7b0f8c0ae9e0e1f8b3c6d1e2f3a4b5c6d7e8f9011223344"
+            },
+            "mac": "71e12efbabd16722f71a1e63c8be4b87d3dc755d71ab0d45e451f4492f02d973"
+        },
+        "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+        "version": 3
+    }"#;
+
+    #[test]
+    fn test_keystore_decrypt_vectors() {
+        for keystore in [PBKDF2_KEYSTORE, SCRYPT_KEYSTORE] {
+            let mut private_key = [0u8; 32];
+            unsafe {
+                let result = keystore_decrypt(
+                    keystore.as_ptr(),
+                    keystore.len(),
+                    PASSWORD.as_ptr(),
+                    PASSWORD.len(),
+                    &mut private_key,
+                );
+                assert_eq!(result, StarkResult::Success);
+            }
+            assert_eq!(to_hex(&private_key), PRIVATE_KEY);
+        }
+    }
+
+    #[test]
+    fn test_keystore_wrong_password() {
+        assert_eq!(
+            decrypt(SCRYPT_KEYSTORE.as_bytes(), b"wrongpassword"),
+            Err(StarkResult::DecryptionFailed)
+        );
+    }
+
+    #[test]
+    fn test_keystore_rejects_excessive_cost() {
+        let keystore = SCRYPT_KEYSTORE.replace("4096", "1073741824");
+        assert_eq!(
+            decrypt(keystore.as_bytes(), PASSWORD),
+            Err(StarkResult::InvalidInput)
+        );
+        // A huge p: p ROMix passes and p * 128 * r bytes
+        let keystore = SCRYPT_KEYSTORE.replace(r#""p": 6"#, r#""p": 1000000"#);
+        assert_eq!(
+            decrypt(keystore.as_bytes(), PASSWORD),
+            Err(StarkResult::InvalidInput)
+        );
+        // Memory and p within bounds, n * r * p over the work cap
+        let keystore = SCRYPT_KEYSTORE
+            .replace("4096", "1048576")
+            .replace(r#""p": 6"#, r#""p": 16"#);
+        assert_eq!(
+            decrypt(keystore.as_bytes(), PASSWORD),
+            Err(StarkResult::InvalidInput)
+        );
+        let keystore = PBKDF2_KEYSTORE.replace("262144", "4294967295");
+        assert_eq!(
+            decrypt(keystore.as_bytes(), PASSWORD),
+            Err(StarkResult::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn test_keystore_roundtrip() {
+        // Cheap scrypt parameters; keystore_encrypt itself uses n = 2^18
+        let kdf = Kdf::Scrypt {
+            log_n: 10,
+            r: 8,
+            p: 1,
+            salt: vec![7; 32],
+        };
+        let private_key = [0x42; 32];
+        let json = encrypt(&private_key, PASSWORD, &kdf, &[1; 16], [2; 16]).unwrap();
+
+        assert!(json.len() <= KEYSTORE_JSON_MAX_LEN);
+        assert!(json.contains(r#""id":"02020202-0202-4202-8202-020202020202""#));
+        assert_eq!(decrypt(json.as_bytes(), PASSWORD).map(|k| *k), Ok(private_key));
+    }
+}
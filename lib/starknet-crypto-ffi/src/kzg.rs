@@ -0,0 +1,552 @@
+//! EIP-4844 KZG commitments for data-availability blobs
+//!
+//! Starknet posts its state diffs to Ethereum as EIP-4844 blobs: 4096
+//! BLS12-381 scalars (32 bytes big-endian each, below the curve order r),
+//! read as the evaluations of a polynomial over the 4096th roots of unity in
+//! bit-reversed order. A blob's KZG commitment (a compressed G1 point) is
+//! what the block header references; a proof shows the polynomial takes
+//! value `y` at point `z`. The functions follow the Deneb consensus
+//! specification (`polynomial-commitments.md`).
+//!
+//! Commitments and proofs need the Ethereum KZG ceremony's trusted setup,
+//! which the host loads once with `kzg_load_trusted_setup` from the
+//! `trusted_setup.txt` file distributed with c-kzg-4844 and the consensus
+//! clients (4096 G1 points in Lagrange form, then 65 G2 points in monomial
+//! form, then optional G1 monomial points, all hex). Loading decompresses
+//! and subgroup-checks every point, which takes a moment.
+//!
+//! The tests run on a 16-point setup generated from a known secret. The
+//! ceremony setup and the consensus-spec KZG vectors are not available to
+//! this build, so 4096-point blobs under the real setup are untested.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use bls12_381::{
+    multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar,
+};
+
+use crate::error::invalid_input;
+use crate::sync::Mutex;
+use crate::{bytes_from_raw, ffi_guard, StarkResult};
+
+/// Scalars in a blob
+pub const KZG_FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+/// Size of a blob in bytes
+pub const KZG_BYTES_PER_BLOB: usize = KZG_FIELD_ELEMENTS_PER_BLOB * 32;
+
+/// Generator of the multiplicative group used for the roots of unity
+const PRIMITIVE_ROOT_OF_UNITY: u64 = 7;
+
+/// Trusted setup over an evaluation domain of `n` points (4096 for blobs;
+/// any power of two from 2 for tests)
+struct Settings {
+    /// Lagrange basis `[L_i(s)]₁`, bit-reversed like `roots`
+    g1_lagrange: Vec<G1Affine>,
+    /// `[s]₂`
+    g2_s: G2Affine,
+    /// The `n`th roots of unity, bit-reversed
+    roots: Vec<Scalar>,
+}
+
+/// Loaded by `kzg_load_trusted_setup`
+static SETTINGS: Mutex<Option<Arc<Settings>>> = Mutex::new(None);
+
+fn bit_reverse<T>(values: &mut [T]) {
+    let bits = values.len().trailing_zeros();
+    for i in 0..values.len() {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+}
+
+fn hex_bytes<const N: usize>(s: &str) -> Option<[u8; N]> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if s.len() != 2 * N {
+        return None;
+    }
+    let mut out = [0u8; N];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(s.get(2 * i..2 * i + 2)?, 16).ok()?;
+    }
+    Some(out)
+}
+
+impl Settings {
+    /// `g1_lagrange` in natural order (as in `trusted_setup.txt`)
+    fn new(mut g1_lagrange: Vec<G1Affine>, g2_s: G2Affine) -> Self {
+        let n = g1_lagrange.len();
+        // root = 7^((r - 1) / n)
+        let mut exponent = [0u64; 4];
+        let r_minus_one = (-Scalar::one()).to_bytes();
+        for (limb, bytes) in exponent.iter_mut().zip(r_minus_one.chunks(8)) {
+            *limb = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        let shift = n.trailing_zeros();
+        for i in 0..4 {
+            exponent[i] =
+                (exponent[i] >> shift) | exponent.get(i + 1).map_or(0, |next| next << (64 - shift));
+        }
+        let root = Scalar::from(PRIMITIVE_ROOT_OF_UNITY).pow_vartime(&exponent);
+        let mut roots = Vec::with_capacity(n);
+        let mut power = Scalar::one();
+        for _ in 0..n {
+            roots.push(power);
+            power *= root;
+        }
+        bit_reverse(&mut roots);
+        bit_reverse(&mut g1_lagrange);
+        Self {
+            g1_lagrange,
+            g2_s,
+            roots,
+        }
+    }
+
+    /// Parse the `trusted_setup.txt` format
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut tokens = text.split_ascii_whitespace();
+        let mut count = |what: &str| -> Result<usize, String> {
+            tokens
+                .next()
+                .and_then(|t| t.parse().ok())
+                .ok_or(format!("missing the number of {what} points"))
+        };
+        let (n_g1, n_g2) = (count("G1")?, count("G2")?);
+        if n_g1 < 2 || !n_g1.is_power_of_two() {
+            return Err(format!("{n_g1} G1 points (expected a power of two)"));
+        }
+        if n_g2 < 2 {
+            return Err(format!("{n_g2} G2 points (expected at least 2)"));
+        }
+        let mut g1_lagrange = Vec::with_capacity(n_g1);
+        for i in 0..n_g1 {
+            let point = tokens
+                .next()
+                .and_then(hex_bytes::<48>)
+                .and_then(|bytes| Option::from(G1Affine::from_compressed(&bytes)))
+                .ok_or(format!("G1 point {i} is not a compressed G1 point"))?;
+            g1_lagrange.push(point);
+        }
+        let mut g2_s = G2Affine::identity();
+        for i in 0..n_g2 {
+            let token = tokens.next();
+            if i == 1 {
+                g2_s = token
+                    .and_then(hex_bytes::<96>)
+                    .and_then(|bytes| Option::from(G2Affine::from_compressed(&bytes)))
+                    .ok_or(format!("G2 point {i} is not a compressed G2 point"))?;
+            } else if token.is_none() {
+                return Err(format!("missing G2 point {i}"));
+            }
+        }
+        Ok(Self::new(g1_lagrange, g2_s))
+    }
+
+    /// Index of `z` in the domain, if it is a root of unity
+    fn root_index(&self, z: &Scalar) -> Option<usize> {
+        self.roots.iter().position(|root| root == z)
+    }
+
+    /// Value at `z` of the polynomial with evaluations `poly` over the domain
+    fn evaluate(&self, poly: &[Scalar], z: &Scalar) -> Scalar {
+        if let Some(i) = self.root_index(z) {
+            return poly[i];
+        }
+        let n = poly.len();
+        // p(z) = (z^n - 1) / n · Σ p_i·ω_i / (z - ω_i)
+        let mut denominators: Vec<Scalar> = self.roots.iter().map(|root| z - root).collect();
+        batch_invert(&mut denominators);
+        let sum = poly
+            .iter()
+            .zip(&self.roots)
+            .zip(&denominators)
+            .fold(Scalar::zero(), |acc, ((p, root), inv)| acc + p * root * inv);
+        let scale = (z.pow_vartime(&[n as u64, 0, 0, 0]) - Scalar::one())
+            * Scalar::from(n as u64).invert().unwrap();
+        sum * scale
+    }
+
+    /// Evaluations of `(p(x) - y) / (x - z)` over the domain
+    fn quotient(&self, poly: &[Scalar], z: &Scalar, y: &Scalar) -> Vec<Scalar> {
+        let within = self.root_index(z);
+        let mut denominators: Vec<Scalar> = self
+            .roots
+            .iter()
+            .map(|root| if root == z { Scalar::one() } else { root - z })
+            .collect();
+        batch_invert(&mut denominators);
+        let mut quotient: Vec<Scalar> = poly
+            .iter()
+            .zip(&denominators)
+            .map(|(p, inv)| (p - y) * inv)
+            .collect();
+        if let Some(m) = within {
+            // q(ω_m) = Σ_{i≠m} (p_i - y)·ω_i / (z·(z - ω_i))
+            let mut denominators: Vec<Scalar> = self
+                .roots
+                .iter()
+                .map(|root| {
+                    if root == z {
+                        Scalar::one()
+                    } else {
+                        z * (z - root)
+                    }
+                })
+                .collect();
+            batch_invert(&mut denominators);
+            quotient[m] = (0..poly.len())
+                .filter(|&i| i != m)
+                .fold(Scalar::zero(), |acc, i| {
+                    acc + (poly[i] - y) * self.roots[i] * denominators[i]
+                });
+        }
+        quotient
+    }
+
+    /// `[p(s)]₁` for the polynomial with evaluations `poly`
+    fn commit(&self, poly: &[Scalar]) -> G1Affine {
+        G1Affine::from(lincomb(&self.g1_lagrange, poly))
+    }
+
+    /// `(proof, y)` for `p(z) = y`
+    fn compute_proof(&self, poly: &[Scalar], z: &Scalar) -> (G1Affine, Scalar) {
+        let y = self.evaluate(poly, z);
+        (self.commit(&self.quotient(poly, z, &y)), y)
+    }
+
+    /// Check `e(C - [y]₁, -[1]₂) · e(proof, [s - z]₂) == 1`
+    fn verify_proof(
+        &self,
+        commitment: &G1Affine,
+        z: &Scalar,
+        y: &Scalar,
+        proof: &G1Affine,
+    ) -> bool {
+        let x_minus_z = G2Projective::from(self.g2_s) - G2Projective::generator() * z;
+        let p_minus_y = G1Projective::from(commitment) - G1Projective::generator() * y;
+        multi_miller_loop(&[
+            (
+                &G1Affine::from(p_minus_y),
+                &G2Prepared::from(-G2Affine::generator()),
+            ),
+            (proof, &G2Prepared::from(G2Affine::from(x_minus_z))),
+        ])
+        .final_exponentiation()
+            == Gt::identity()
+    }
+}
+
+/// Invert non-zero `values` in place with one inversion (Montgomery's trick)
+fn batch_invert(values: &mut [Scalar]) {
+    let mut prefix = Vec::with_capacity(values.len());
+    let mut product = Scalar::one();
+    for value in values.iter() {
+        prefix.push(product);
+        product *= value;
+    }
+    let mut inverse = product.invert().unwrap();
+    for (value, prefix) in values.iter_mut().zip(prefix).rev() {
+        let value_inverse = inverse * prefix;
+        inverse *= *value;
+        *value = value_inverse;
+    }
+}
+
+/// `Σ scalars[i]·points[i]` (Pippenger, 8-bit windows: one bucket pass per
+/// scalar byte)
+fn lincomb(points: &[G1Affine], scalars: &[Scalar]) -> G1Projective {
+    let digits: Vec<[u8; 32]> = scalars.iter().map(Scalar::to_bytes).collect();
+    let mut acc = G1Projective::identity();
+    let mut buckets = vec![G1Projective::identity(); 255];
+    for byte in (0..32).rev() {
+        for _ in 0..8 {
+            acc = acc.double();
+        }
+        buckets.fill(G1Projective::identity());
+        for (point, digits) in points.iter().zip(&digits) {
+            if digits[byte] != 0 {
+                buckets[digits[byte] as usize - 1] += point;
+            }
+        }
+        // Σ (i + 1)·buckets[i] as a running sum from the top
+        let (mut running, mut sum) = (G1Projective::identity(), G1Projective::identity());
+        for bucket in buckets.iter().rev() {
+            running += bucket;
+            sum += running;
+        }
+        acc += sum;
+    }
+    acc
+}
+
+fn scalar_from_bytes(bytes: &[u8; 32]) -> Option<Scalar> {
+    let mut le = *bytes;
+    le.reverse();
+    Option::from(Scalar::from_bytes(&le))
+}
+
+fn scalar_to_bytes(scalar: &Scalar) -> [u8; 32] {
+    let mut bytes = scalar.to_bytes();
+    bytes.reverse();
+    bytes
+}
+
+fn blob_to_polynomial(blob: &[u8; KZG_BYTES_PER_BLOB]) -> Result<Vec<Scalar>, StarkResult> {
+    blob.chunks_exact(32)
+        .enumerate()
+        .map(|(i, chunk)| {
+            scalar_from_bytes(chunk.try_into().unwrap())
+                .ok_or_else(|| invalid_input(format!("blob element {i} is not below r")))
+        })
+        .collect()
+}
+
+fn g1_from_bytes(bytes: &[u8; 48], what: &str) -> Result<G1Affine, StarkResult> {
+    Option::from(G1Affine::from_compressed(bytes))
+        .ok_or_else(|| invalid_input(format!("`{what}` is not a compressed G1 point")))
+}
+
+fn settings() -> Result<Arc<Settings>, StarkResult> {
+    SETTINGS
+        .lock()
+        .clone()
+        .ok_or_else(|| invalid_input("no trusted setup loaded (see kzg_load_trusted_setup)"))
+}
+
+/// Load the trusted setup the other `kzg_` functions use
+///
+/// `text` is the content of `trusted_setup.txt` (not NUL-terminated) and
+/// must hold 4096 G1 points. Replaces any setup loaded before; calls already
+/// running finish with the old one. Returns `InvalidInput` if the text is
+/// malformed or a point is invalid.
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_kzg_load_trusted_setup"
+)]
+pub unsafe extern "C" fn kzg_load_trusted_setup(text: *const u8, len: usize) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(text);
+        let text = match bytes_from_raw(text, len).map(core::str::from_utf8) {
+            Some(Ok(text)) => text,
+            _ => return invalid_input("`text` is not UTF-8"),
+        };
+        let settings = match Settings::parse(text) {
+            Ok(settings) => settings,
+            Err(e) => return invalid_input(format!("`text`: {e}")),
+        };
+        if settings.roots.len() != KZG_FIELD_ELEMENTS_PER_BLOB {
+            return invalid_input(format!(
+                "`text` has {} G1 points (expected {KZG_FIELD_ELEMENTS_PER_BLOB})",
+                settings.roots.len()
+            ));
+        }
+        *SETTINGS.lock() = Some(Arc::new(settings));
+        log!(Info, "KZG trusted setup loaded");
+        StarkResult::Success
+    })
+}
+
+/// Compute the KZG commitment of a blob (compressed G1 point)
+///
+/// Returns `InvalidInput` if no trusted setup is loaded or a blob element
+/// is not below r.
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_kzg_blob_to_commitment"
+)]
+pub unsafe extern "C" fn kzg_blob_to_commitment(
+    blob: *const [u8; KZG_BYTES_PER_BLOB],
+    out_commitment: *mut [u8; 48],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(blob, out_commitment);
+        let result = settings().and_then(|settings| {
+            let poly = blob_to_polynomial(&*blob)?;
+            Ok(settings.commit(&poly))
+        });
+        match result {
+            Ok(commitment) => {
+                *out_commitment = commitment.to_compressed();
+                StarkResult::Success
+            }
+            Err(e) => e,
+        }
+    })
+}
+
+/// Compute the KZG proof that a blob's polynomial takes value `y` at `z`
+///
+/// `z` and the written `y` are 32-byte big-endian scalars. Returns
+/// `InvalidInput` if no trusted setup is loaded, or `z` or a blob element is
+/// not below r.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_kzg_compute_proof")]
+pub unsafe extern "C" fn kzg_compute_proof(
+    blob: *const [u8; KZG_BYTES_PER_BLOB],
+    z: *const [u8; 32],
+    out_proof: *mut [u8; 48],
+    out_y: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(blob, z, out_proof, out_y);
+        let result = settings().and_then(|settings| {
+            let z = scalar_from_bytes(&*z).ok_or_else(|| invalid_input("`z` is not below r"))?;
+            let poly = blob_to_polynomial(&*blob)?;
+            Ok(settings.compute_proof(&poly, &z))
+        });
+        match result {
+            Ok((proof, y)) => {
+                *out_proof = proof.to_compressed();
+                *out_y = scalar_to_bytes(&y);
+                StarkResult::Success
+            }
+            Err(e) => e,
+        }
+    })
+}
+
+/// Verify a KZG proof that the polynomial committed to takes value `y` at
+/// `z`
+///
+/// Returns `Success` if the proof holds, `InvalidSignature` if it does not,
+/// and `InvalidInput` if no trusted setup is loaded, `commitment` or
+/// `proof` is not a compressed G1 point, or `z` or `y` is not below r.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_kzg_verify_proof")]
+pub unsafe extern "C" fn kzg_verify_proof(
+    commitment: *const [u8; 48],
+    z: *const [u8; 32],
+    y: *const [u8; 32],
+    proof: *const [u8; 48],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(commitment, z, y, proof);
+        let result = settings().and_then(|settings| {
+            let commitment = g1_from_bytes(&*commitment, "commitment")?;
+            let proof = g1_from_bytes(&*proof, "proof")?;
+            let z = scalar_from_bytes(&*z).ok_or_else(|| invalid_input("`z` is not below r"))?;
+            let y = scalar_from_bytes(&*y).ok_or_else(|| invalid_input("`y` is not below r"))?;
+            Ok(settings.verify_proof(&commitment, &z, &y, &proof))
+        });
+        match result {
+            Ok(true) => StarkResult::Success,
+            Ok(false) => StarkResult::InvalidSignature,
+            Err(e) => e,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Synthetic setup: the real `trusted_setup.txt` is not available to this
+    // build (see the module docs)
+    const SECRET: u64 = 1337;
+    const N: usize = 16;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    /// `trusted_setup.txt` for `N` points and the secret `SECRET`
+    fn setup_text() -> String {
+        let s = Scalar::from(SECRET);
+        let root =
+            Settings::new(vec![G1Affine::generator(); N], G2Affine::generator()).roots[N / 2];
+        // L_i(s) = ω^i / n · (s^n - 1) / (s - ω^i), natural order
+        let scale = (s.pow_vartime(&[N as u64, 0, 0, 0]) - Scalar::one())
+            * Scalar::from(N as u64).invert().unwrap();
+        let mut text = format!("{N}\n2\n");
+        let mut omega = Scalar::one();
+        for _ in 0..N {
+            let l = omega * scale * (s - omega).invert().unwrap();
+            text += &format!(
+                "{}\n",
+                hex(&G1Affine::from(G1Projective::generator() * l).to_compressed())
+            );
+            omega *= root;
+        }
+        for point in [G2Projective::generator(), G2Projective::generator() * s] {
+            text += &format!("{}\n", hex(&G2Affine::from(point).to_compressed()));
+        }
+        text
+    }
+
+    fn polynomial() -> Vec<Scalar> {
+        (0..N as u64)
+            .map(|i| Scalar::from(i * i + 3).pow_vartime(&[i + 1, 0, 0, 0]))
+            .collect()
+    }
+
+    #[test]
+    fn test_roots_of_unity() {
+        let settings = Settings::new(vec![G1Affine::generator(); N], G2Affine::generator());
+        // Bit-reversed: ω^0, ω^8 = -1, ω^4, ω^12, ...
+        assert_eq!(settings.roots[0], Scalar::one());
+        assert_eq!(settings.roots[1], -Scalar::one());
+        assert_eq!(settings.roots[2].square(), -Scalar::one());
+        assert_eq!(
+            settings.roots[8].pow_vartime(&[N as u64, 0, 0, 0]),
+            Scalar::one()
+        );
+        assert_ne!(
+            settings.roots[8].pow_vartime(&[N as u64 / 2, 0, 0, 0]),
+            Scalar::one()
+        );
+    }
+
+    #[test]
+    fn test_commitment_is_evaluation_at_secret() {
+        let settings = Settings::parse(&setup_text()).unwrap();
+        let poly = polynomial();
+        let expected = G1Projective::generator() * settings.evaluate(&poly, &Scalar::from(SECRET));
+        assert_eq!(settings.commit(&poly), G1Affine::from(expected));
+    }
+
+    #[test]
+    fn test_proofs() {
+        let settings = Settings::parse(&setup_text()).unwrap();
+        let poly = polynomial();
+        let commitment = settings.commit(&poly);
+        // Outside and inside the domain
+        for z in [Scalar::from(0xdead_beefu64), settings.roots[5]] {
+            let (proof, y) = settings.compute_proof(&poly, &z);
+            assert!(settings.verify_proof(&commitment, &z, &y, &proof));
+            assert!(!settings.verify_proof(&commitment, &z, &(y + Scalar::one()), &proof));
+        }
+        assert_eq!(settings.evaluate(&poly, &settings.roots[5]), poly[5]);
+    }
+
+    #[test]
+    fn test_load_trusted_setup_rejects_bad_input() {
+        let text = setup_text();
+        let wrong_size = text.as_bytes();
+        let truncated = &text.as_bytes()[..text.len() / 2];
+        unsafe {
+            for input in [wrong_size, truncated, b"4096 65 zz".as_slice()] {
+                assert_eq!(
+                    kzg_load_trusted_setup(input.as_ptr(), input.len()),
+                    StarkResult::InvalidInput
+                );
+            }
+        }
+        let z = [0xffu8; 32];
+        let mut proof = [0u8; 48];
+        let mut y = [0u8; 32];
+        unsafe {
+            assert_eq!(
+                kzg_compute_proof(&[0u8; KZG_BYTES_PER_BLOB], &z, &mut proof, &mut y),
+                StarkResult::InvalidInput
+            );
+        }
+    }
+}
@@ -2,13 +2,209 @@
 //!
 //! Exposes Felt252 arithmetic, Pedersen/Poseidon hashing, and STARK ECDSA
 //! as extern "C" functions for Zig FFI consumption.
-
-use starknet_crypto::{
-    pedersen_hash, poseidon_hash, poseidon_hash_many,
-    sign, verify, get_public_key, recover, Felt,
-    rfc6979_generate_k,
-};
+//!
+//! Every extern function shares the same safety contract: pointer arguments
+//! must be valid for reads (inputs) or writes (outputs) of their pointee type,
+//! and array pointers must cover `count`/`len` elements. Null pointers are
+//! checked: unless a function documents a pointer as optional (or it is a
+//! buffer with a zero length), null is reported as `NullPointer`.
+//!
+//! No extern function unwinds into the caller: a panic inside one is caught
+//! and reported as `InternalPanic` (on targets that unwind; WASM aborts).
+//!
+//! After a call fails, `stark_last_error_message` describes the failure
+//! (see `error`).
+//!
+//! Variable-length outputs follow one capacity convention: the caller passes
+//! a buffer and its capacity in `out_len` (`out_count` for felt arrays), and
+//! the function writes the output length there. If the output does not fit,
+//! nothing is written to the buffer, the required length is still written
+//! and `BufferTooSmall` is returned, so a call with a null buffer and a zero
+//! capacity queries the size to allocate. A `_alloc` variant, where one
+//! exists, allocates the buffer in the library instead (see `buffer`).
+//!
+//! Without the default `std` feature the crate is `no_std + alloc`, for
+//! bare-metal firmware: the host provides the heap with
+//! `stark_set_allocator`, must not call in concurrently (see `sync`), and a
+//! panic halts instead of returning `InternalPanic` (build with
+//! `panic = "abort"`). Randomness still comes from `getrandom`, whose
+//! custom backend the firmware registers on targets without an OS.
+//!
+//! Subsystems are default cargo features that size-constrained builds can
+//! leave out: `hashing` (Pedersen, Poseidon and keccak exports), `ecdsa`
+//! (STARK signing and verification, V3 transactions), `secp` (secp256k1,
+//! secp256r1 and everything built on them), `merkle` (sessions and
+//! `merkletree` typed data), `keystore`, `encoding`, and one feature per
+//! module for the other curves and ciphers (`bls`, `kzg`, `ed25519`,
+//! `x25519`, `aead`, `sealed_box`, `ecies`), `mnemonic`, `abi` and
+//! `typed_data`. Felt arithmetic and the Starknet primitives (addresses,
+//! calldata, block hashes) are always built.
+//!
+//! With the `symbol-prefix` feature every extern function is exported as
+//! `kundera_<name>` (`kundera_keccak256`, `kundera_starknet_sign`, ...), for
+//! static links next to libraries exporting the same generic names. The
+//! generated C header and Zig bindings follow the build.
+#![cfg_attr(not(feature = "std"), no_std)]
+// `#[no_mangle]` stays for cbindgen; the prefixed `export_name` overrides it
+#![cfg_attr(feature = "symbol-prefix", allow(unused_attributes))]
+#![allow(clippy::missing_safety_doc)]
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown", not(feature = "wasm")))]
+compile_error!("wasm32-unknown-unknown builds need the `wasm` feature (see `wasm`)");
+
+extern crate alloc;
+
+use alloc::{format, vec::Vec};
+#[cfg(feature = "std")]
+use std::panic::{self, AssertUnwindSafe};
+
+use starknet_crypto::Felt;
+#[cfg(feature = "hashing")]
+use starknet_crypto::{poseidon_hash, poseidon_hash_many, poseidon_permute_comp};
+#[cfg(feature = "ecdsa")]
+use starknet_crypto::{sign, verify, get_public_key, recover, rfc6979_generate_k};
 use sha3::{Keccak256, Digest};
+#[cfg(feature = "ecdsa")]
+use zeroize::Zeroizing;
+
+use pedersen::pedersen_hash;
+use perf::Op;
+
+/// Return `StarkResult::NullPointer` from the enclosing function (or
+/// `ffi_guard` body) if any of the pointers is null, naming the first null
+/// one in the last-error message
+macro_rules! check_null {
+    ($($ptr:expr),+ $(,)?) => {
+        $(
+            if $ptr.is_null() {
+                return $crate::error::null_pointer(stringify!($ptr));
+            }
+        )+
+    };
+}
+
+/// Send a formatted message at `$level` (a `StarkLogLevel` variant) to the
+/// host's log callback, if one is set (see `log`)
+macro_rules! log {
+    ($level:ident, $($arg:tt)+) => {
+        $crate::log::emit($crate::log::StarkLogLevel::$level, format_args!($($arg)+))
+    };
+}
+
+/// `thread_local!` with const initializers; without `std` each static is
+/// process-wide (see `sync`)
+macro_rules! local {
+    ($($(#[$attr:meta])* static $name:ident: $ty:ty = $init:expr;)+) => {
+        #[cfg(feature = "std")]
+        std::thread_local! {
+            $($(#[$attr])* static $name: $ty = const { $init };)+
+        }
+        $(
+            #[cfg(not(feature = "std"))]
+            $(#[$attr])*
+            static $name: $crate::sync::Local<$ty> = $crate::sync::Local::new($init);
+        )+
+    };
+}
+
+#[cfg(feature = "abi")]
+pub mod abi;
+pub mod address;
+#[cfg(feature = "aead")]
+pub mod aead;
+pub mod arena;
+#[cfg(not(feature = "std"))]
+pub mod allocator;
+#[cfg(feature = "secp")]
+pub mod bip340;
+pub mod block;
+#[cfg(feature = "bls")]
+pub mod bls;
+pub mod buffer;
+pub mod byte_array;
+pub mod call;
+pub mod calldata;
+pub mod cancel;
+#[cfg(feature = "secp")]
+pub mod derivation;
+#[cfg(feature = "ecies")]
+pub mod ecies;
+#[cfg(feature = "ed25519")]
+pub mod ed25519;
+#[cfg(feature = "encoding")]
+pub mod encoding;
+pub mod error;
+pub mod felt_array;
+pub mod felt_batch;
+pub mod felt_derive;
+#[cfg(feature = "hashing")]
+pub mod grind;
+#[cfg(feature = "secp")]
+pub mod eth;
+pub mod event;
+pub mod handle;
+#[cfg(feature = "keystore")]
+pub mod keystore;
+#[cfg(feature = "kzg")]
+pub mod kzg;
+pub mod log;
+#[cfg(feature = "hashing")]
+pub mod mimc;
+#[cfg(feature = "mnemonic")]
+pub mod mnemonic;
+pub mod multisig;
+#[cfg(feature = "napi")]
+pub mod napi;
+#[cfg(feature = "ecdsa")]
+pub mod nonce;
+#[cfg(feature = "typed_data")]
+pub mod outside_execution;
+pub mod pedersen;
+pub mod perf;
+pub(crate) mod point_table;
+pub mod pool;
+pub mod program;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod random;
+#[cfg(feature = "hashing")]
+pub mod rescue;
+pub mod schnorr;
+#[cfg(feature = "sealed_box")]
+pub mod sealed_box;
+pub mod secret;
+pub mod self_test;
+#[cfg(feature = "secp")]
+pub mod secp256k1;
+#[cfg(feature = "secp")]
+pub mod secp256r1;
+#[cfg(feature = "merkle")]
+pub mod session;
+pub mod signature;
+#[cfg(feature = "ecdsa")]
+pub mod sign_ct;
+pub mod stream;
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
+pub(crate) mod sync;
+#[cfg(feature = "ecdsa")]
+pub mod transaction;
+#[cfg(feature = "hashing")]
+pub mod transcript;
+#[cfg(feature = "typed_data")]
+pub mod typed_data;
+pub mod u256;
+#[cfg(feature = "hashing")]
+pub mod vector_commit;
+#[cfg(feature = "ecdsa")]
+pub mod verify;
+pub mod version;
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub mod wasm;
+#[cfg(feature = "secp")]
+pub mod webauthn;
+#[cfg(feature = "x25519")]
+pub mod x25519;
 
 /// Result codes for FFI functions
 #[repr(C)]
@@ -21,6 +217,16 @@ pub enum StarkResult {
     DivisionByZero = 4,
     NoInverse = 5,
     NoSquareRoot = 6,
+    InvalidPrivateKey = 7,
+    RngFailed = 8,
+    DecryptionFailed = 9,
+    MemoryLockFailed = 10,
+    InternalPanic = 11,
+    NullPointer = 12,
+    InvalidHandle = 13,
+    BufferTooSmall = 14,
+    Cancelled = 15,
+    SelfTestFailed = 16,
 }
 
 /// Felt252 as 32 bytes (big-endian)
@@ -28,152 +234,380 @@ pub type FeltBytes = [u8; 32];
 
 // ============ HELPERS ============
 
-fn felt_from_bytes(bytes: &FeltBytes) -> Option<Felt> {
+/// Order of the STARK curve generator
+pub(crate) const EC_ORDER: Felt =
+    Felt::from_hex_unchecked("0x0800000000000010ffffffffffffffffb781126dcae7b2321e66a241adc64d2f");
+
+pub(crate) fn felt_from_bytes(bytes: &FeltBytes) -> Option<Felt> {
     // Felt::from_bytes_be returns the felt directly (panics on invalid)
     // We use from_bytes_be_slice which is safer
     Some(Felt::from_bytes_be_slice(bytes))
 }
 
-fn felt_to_bytes(felt: &Felt) -> FeltBytes {
+pub(crate) fn felt_to_bytes(felt: &Felt) -> FeltBytes {
     felt.to_bytes_be()
 }
 
+/// Run the body of an extern function, reporting a panic as `InternalPanic`
+///
+/// Unwinding out of an `extern "C"` function aborts the host process. A
+/// handle the body was updating when it panicked may be left part-way
+/// through the update, but stays valid to free. A failure result is also
+/// recorded for `stark_last_error_message`.
+pub(crate) fn ffi_guard<F: FnOnce() -> StarkResult>(body: F) -> StarkResult {
+    error::enter(core::any::type_name::<F>());
+    #[cfg(feature = "std")]
+    let result = match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(result) => result,
+        Err(payload) => {
+            error::set_panic(&*payload);
+            StarkResult::InternalPanic
+        }
+    };
+    // Nothing to catch: a panic ends in `panic_halt`
+    #[cfg(not(feature = "std"))]
+    let result = body();
+    error::leave(result);
+    result
+}
+
+/// Panic handler of `no_std` builds: report the panic to the log callback,
+/// then halt (the firmware's watchdog or debugger takes over)
+#[cfg(all(not(feature = "std"), not(test)))]
+#[panic_handler]
+fn panic_halt(info: &core::panic::PanicInfo) -> ! {
+    log!(Error, "{info}");
+    loop {
+        core::hint::spin_loop();
+    }
+}
+
+/// `ffi_guard` for extern functions without a result (the `_free` functions)
+///
+/// The body's result is not returned, but a failure (e.g. `InvalidHandle`
+/// on a double free) is still recorded for `stark_last_error_message`.
+pub(crate) fn ffi_guard_void(body: impl FnOnce() -> StarkResult) {
+    let _ = ffi_guard(body);
+}
+
+/// Borrow `len` bytes from a raw pointer (null is accepted when `len == 0`)
+///
+/// `None` means a null pointer with a non-zero length: `NullPointer`.
+pub(crate) unsafe fn bytes_from_raw<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        return Some(&[]);
+    }
+    if ptr.is_null() {
+        return None;
+    }
+    Some(core::slice::from_raw_parts(ptr, len))
+}
+
+/// Copy `bytes` to a caller buffer whose capacity is `*out_len`
+///
+/// `*out_len` is set to `bytes.len()` either way, so a caller can retry with
+/// a larger buffer after `BufferTooSmall`, or query the size first with a
+/// null `out` and a zero capacity. `out` may be null when nothing is
+/// written; otherwise a null `out` or `out_len` is `NullPointer`.
+pub(crate) unsafe fn write_bytes_out(
+    bytes: &[u8],
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    check_null!(out_len);
+    let capacity = *out_len;
+    *out_len = bytes.len();
+    if capacity < bytes.len() {
+        return error::buffer_too_small(format!(
+            "{} bytes needed, capacity {}",
+            bytes.len(),
+            capacity
+        ));
+    }
+    if !bytes.is_empty() {
+        check_null!(out);
+        core::ptr::copy_nonoverlapping(bytes.as_ptr(), out, bytes.len());
+    }
+    StarkResult::Success
+}
+
+/// Copy `felts` to a caller array whose capacity is `*out_count` felts
+///
+/// Same convention as `write_bytes_out`: `*out_count` is set to
+/// `felts.len()` either way.
+pub(crate) unsafe fn write_felts_out(
+    felts: &[Felt],
+    out: *mut FeltBytes,
+    out_count: *mut usize,
+) -> StarkResult {
+    check_null!(out_count);
+    let capacity = *out_count;
+    *out_count = felts.len();
+    if capacity < felts.len() {
+        return error::buffer_too_small(format!(
+            "{} felts needed, capacity {}",
+            felts.len(),
+            capacity
+        ));
+    }
+    if !felts.is_empty() {
+        check_null!(out);
+    }
+    for (i, felt) in felts.iter().enumerate() {
+        *out.add(i) = felt_to_bytes(felt);
+    }
+    StarkResult::Success
+}
+
+/// Read `len` felts from a raw array (null is accepted when `len == 0`)
+///
+/// `None` means a null pointer with a non-zero length: `NullPointer`.
+pub(crate) unsafe fn felts_from_raw(ptr: *const FeltBytes, len: usize) -> Option<Vec<Felt>> {
+    if len == 0 {
+        return Some(Vec::new());
+    }
+    if ptr.is_null() {
+        return None;
+    }
+    core::slice::from_raw_parts(ptr, len)
+        .iter()
+        .map(felt_from_bytes)
+        .collect()
+}
+
+/// Keccak256 truncated to 250 bits (`sn_keccak`, used for selectors and type hashes)
+pub(crate) fn sn_keccak(data: &[u8]) -> Felt {
+    let mut hash: [u8; 32] = Keccak256::digest(data).into();
+    hash[0] &= 0x03;
+    Felt::from_bytes_be(&hash)
+}
+
+/// Pedersen hash chain over an array, terminated by its length
+/// (`compute_hash_on_elements` in starknet.py / starknet-rs)
+pub(crate) fn pedersen_hash_array(elements: &[Felt]) -> Felt {
+    let acc = elements
+        .iter()
+        .fold(Felt::ZERO, |acc, e| pedersen_hash(&acc, e));
+    pedersen_hash(&acc, &Felt::from(elements.len()))
+}
+
 // ============ FELT ARITHMETIC ============
 
 /// Add two felts: (a + b) mod P
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_add")]
 pub unsafe extern "C" fn felt_add(
     a: *const FeltBytes,
     b: *const FeltBytes,
     out: *mut FeltBytes,
 ) -> StarkResult {
-    let a = match felt_from_bytes(&*a) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    let b = match felt_from_bytes(&*b) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    *out = felt_to_bytes(&(a + b));
-    StarkResult::Success
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::FeltArith);
+        check_null!(a, b, out);
+        let a = match felt_from_bytes(&*a) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let b = match felt_from_bytes(&*b) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        *out = felt_to_bytes(&(a + b));
+        StarkResult::Success
+    })
 }
 
 /// Subtract two felts: (a - b) mod P
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_sub")]
 pub unsafe extern "C" fn felt_sub(
     a: *const FeltBytes,
     b: *const FeltBytes,
     out: *mut FeltBytes,
 ) -> StarkResult {
-    let a = match felt_from_bytes(&*a) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    let b = match felt_from_bytes(&*b) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    *out = felt_to_bytes(&(a - b));
-    StarkResult::Success
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::FeltArith);
+        check_null!(a, b, out);
+        let a = match felt_from_bytes(&*a) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let b = match felt_from_bytes(&*b) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        *out = felt_to_bytes(&(a - b));
+        StarkResult::Success
+    })
 }
 
 /// Multiply two felts: (a * b) mod P
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_mul")]
 pub unsafe extern "C" fn felt_mul(
     a: *const FeltBytes,
     b: *const FeltBytes,
     out: *mut FeltBytes,
 ) -> StarkResult {
-    let a = match felt_from_bytes(&*a) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    let b = match felt_from_bytes(&*b) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    *out = felt_to_bytes(&(a * b));
-    StarkResult::Success
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::FeltArith);
+        check_null!(a, b, out);
+        let a = match felt_from_bytes(&*a) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let b = match felt_from_bytes(&*b) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        *out = felt_to_bytes(&(a * b));
+        StarkResult::Success
+    })
 }
 
 /// Divide two felts: a * inverse(b) mod P
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_div")]
 pub unsafe extern "C" fn felt_div(
     a: *const FeltBytes,
     b: *const FeltBytes,
     out: *mut FeltBytes,
 ) -> StarkResult {
-    let a = match felt_from_bytes(&*a) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    let b = match felt_from_bytes(&*b) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    if b == Felt::ZERO {
-        return StarkResult::DivisionByZero;
-    }
-    // Field division: a * b^(-1)
-    let b_inv = b.inverse().unwrap();
-    *out = felt_to_bytes(&(a * b_inv));
-    StarkResult::Success
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::FeltArith);
+        check_null!(a, b, out);
+        let a = match felt_from_bytes(&*a) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let b = match felt_from_bytes(&*b) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        if b == Felt::ZERO {
+            return StarkResult::DivisionByZero;
+        }
+        // Field division: a * b^(-1)
+        let b_inv = b.inverse().unwrap();
+        *out = felt_to_bytes(&(a * b_inv));
+        StarkResult::Success
+    })
 }
 
 /// Negate a felt: -a mod P (equivalently P - a)
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_neg")]
 pub unsafe extern "C" fn felt_neg(
     a: *const FeltBytes,
     out: *mut FeltBytes,
 ) -> StarkResult {
-    let a = match felt_from_bytes(&*a) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    *out = felt_to_bytes(&(-a));
-    StarkResult::Success
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::FeltArith);
+        check_null!(a, out);
+        let a = match felt_from_bytes(&*a) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        *out = felt_to_bytes(&(-a));
+        StarkResult::Success
+    })
 }
 
 /// Multiplicative inverse: a^(-1) mod P
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_inverse")]
 pub unsafe extern "C" fn felt_inverse(
     a: *const FeltBytes,
     out: *mut FeltBytes,
 ) -> StarkResult {
-    let a = match felt_from_bytes(&*a) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    match a.inverse() {
-        Some(inv) => {
-            *out = felt_to_bytes(&inv);
-            StarkResult::Success
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::FeltArith);
+        check_null!(a, out);
+        let a = match felt_from_bytes(&*a) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        match a.inverse() {
+            Some(inv) => {
+                *out = felt_to_bytes(&inv);
+                StarkResult::Success
+            }
+            None => StarkResult::NoInverse, // Zero has no inverse
         }
-        None => StarkResult::NoInverse, // Zero has no inverse
-    }
+    })
 }
 
 /// Power: base^exp mod P
+///
+/// `exp` is read like every felt argument: as a 256-bit big-endian integer
+/// reduced mod P. The result is `base` raised to that integer in
+/// `[0, P)`, with `0^0 = 1`; an `exp` of P or more therefore is not the
+/// integer power (see `felt_pow_reduced`).
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_pow")]
 pub unsafe extern "C" fn felt_pow(
     base: *const FeltBytes,
     exp: *const FeltBytes,
     out: *mut FeltBytes,
 ) -> StarkResult {
-    let base = match felt_from_bytes(&*base) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    let exp = match felt_from_bytes(&*exp) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    // Convert exp to u128 for pow (Felt doesn't have pow_felt directly)
-    // For large exponents, we use the bits
-    let result = felt_pow_impl(base, exp);
-    *out = felt_to_bytes(&result);
-    StarkResult::Success
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::FeltArith);
+        check_null!(base, exp, out);
+        let base = match felt_from_bytes(&*base) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let exp = match felt_from_bytes(&*exp) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        // Convert exp to u128 for pow (Felt doesn't have pow_felt directly)
+        // For large exponents, we use the bits
+        let result = felt_pow_impl(base, exp);
+        *out = felt_to_bytes(&result);
+        StarkResult::Success
+    })
+}
+
+/// Power with the exponent reduced mod P - 1: base^(exp mod (P - 1)) mod P
+///
+/// `exp` is the full 256-bit big-endian integer (not reduced mod P first).
+/// By Fermat's little theorem this is the integer power `base^exp` for any
+/// non-zero `base`. For `base == 0` the result is 1 when `exp` is a multiple
+/// of P - 1 (0 included) and 0 otherwise.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_pow_reduced")]
+pub unsafe extern "C" fn felt_pow_reduced(
+    base: *const FeltBytes,
+    exp: *const FeltBytes,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::FeltArith);
+        check_null!(base, exp, out);
+        let base = Felt::from_bytes_be_slice(&*base);
+        *out = felt_to_bytes(&felt_pow_impl(base, reduce_exponent(&*exp)));
+        StarkResult::Success
+    })
+}
+
+/// A 256-bit big-endian integer mod P - 1 = 2^251 + 17·2^192
+///
+/// With `exp = hi·2^251 + lo` and `2^251 ≡ -17·2^192`, the residue is
+/// `lo - 17·hi·2^192`, plus P - 1 if negative; both terms are below P - 1.
+fn reduce_exponent(exp: &FeltBytes) -> Felt {
+    let mut lo = *exp;
+    lo[0] &= 0x07;
+    let lo = Felt::from_bytes_be(&lo);
+    let mut t = [0u8; 32];
+    t[6..8].copy_from_slice(&(17 * u16::from(exp[0] >> 3)).to_be_bytes());
+    let t = Felt::from_bytes_be(&t);
+    // Below zero, felt subtraction adds P instead of P - 1
+    if lo >= t {
+        lo - t
+    } else {
+        lo - t - Felt::ONE
+    }
 }
 
 // Helper for modular exponentiation using square-and-multiply
@@ -185,9 +619,9 @@ fn felt_pow_impl(base: Felt, exp: Felt) -> Felt {
     for byte in exp_bytes.iter().rev() {
         for bit in 0..8 {
             if (byte >> bit) & 1 == 1 {
-                result = result * current;
+                result *= current;
             }
-            current = current * current;
+            current *= current;
         }
     }
     result
@@ -195,241 +629,635 @@ fn felt_pow_impl(base: Felt, exp: Felt) -> Felt {
 
 /// Square root (Tonelli-Shanks): returns sqrt if exists
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_sqrt")]
 pub unsafe extern "C" fn felt_sqrt(
     a: *const FeltBytes,
     out: *mut FeltBytes,
 ) -> StarkResult {
-    let a = match felt_from_bytes(&*a) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    match a.sqrt() {
-        Some(root) => {
-            *out = felt_to_bytes(&root);
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::FeltArith);
+        check_null!(a, out);
+        let a = match felt_from_bytes(&*a) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        match a.sqrt() {
+            Some(root) => {
+                *out = felt_to_bytes(&root);
+                StarkResult::Success
+            }
+            None => StarkResult::NoSquareRoot, // Not a quadratic residue
+        }
+    })
+}
+
+/// `*a_inout = op(*a_inout, *b)`, the body of the `_assign` functions
+///
+/// `op` returns `Err` to leave `a_inout` untouched.
+unsafe fn assign(
+    a_inout: *mut FeltBytes,
+    b: *const FeltBytes,
+    op: impl FnOnce(Felt, Felt) -> Result<Felt, StarkResult>,
+) -> StarkResult {
+    let _timer = perf::timer(Op::FeltArith);
+    check_null!(a_inout, b);
+    let a = Felt::from_bytes_be(&*a_inout);
+    let b = Felt::from_bytes_be(&*b);
+    match op(a, b) {
+        Ok(result) => {
+            *a_inout = felt_to_bytes(&result);
             StarkResult::Success
         }
-        None => StarkResult::NoSquareRoot, // Not a quadratic residue
+        Err(e) => e,
     }
 }
 
+/// In-place addition: a_inout = (a_inout + b) mod P
+///
+/// `b` may point to `a_inout`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_add_assign")]
+pub unsafe extern "C" fn felt_add_assign(
+    a_inout: *mut FeltBytes,
+    b: *const FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| assign(a_inout, b, |a, b| Ok(a + b)))
+}
+
+/// In-place subtraction: a_inout = (a_inout - b) mod P
+///
+/// `b` may point to `a_inout`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_sub_assign")]
+pub unsafe extern "C" fn felt_sub_assign(
+    a_inout: *mut FeltBytes,
+    b: *const FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| assign(a_inout, b, |a, b| Ok(a - b)))
+}
+
+/// In-place multiplication: a_inout = (a_inout * b) mod P
+///
+/// `b` may point to `a_inout`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_mul_assign")]
+pub unsafe extern "C" fn felt_mul_assign(
+    a_inout: *mut FeltBytes,
+    b: *const FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| assign(a_inout, b, |a, b| Ok(a * b)))
+}
+
+/// In-place division: a_inout = a_inout * inverse(b) mod P
+///
+/// Returns `DivisionByZero` with `a_inout` untouched if `b` is 0. `b` may
+/// point to `a_inout`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_div_assign")]
+pub unsafe extern "C" fn felt_div_assign(
+    a_inout: *mut FeltBytes,
+    b: *const FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        assign(a_inout, b, |a, b| match b.inverse() {
+            Some(b_inv) => Ok(a * b_inv),
+            None => Err(StarkResult::DivisionByZero),
+        })
+    })
+}
+
+/// In-place negation: a_inout = -a_inout mod P
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_felt_neg_assign")]
+pub unsafe extern "C" fn felt_neg_assign(a_inout: *mut FeltBytes) -> StarkResult {
+    ffi_guard(|| assign(a_inout, a_inout, |a, _| Ok(-a)))
+}
+
 // ============ HASHING ============
 
 /// Pedersen hash of two felts
+#[cfg(feature = "hashing")]
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_pedersen_hash")]
 pub unsafe extern "C" fn starknet_pedersen_hash(
     a: *const FeltBytes,
     b: *const FeltBytes,
     out: *mut FeltBytes,
 ) -> StarkResult {
-    let a = match felt_from_bytes(&*a) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    let b = match felt_from_bytes(&*b) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-
-    let result = pedersen_hash(&a, &b);
-    *out = felt_to_bytes(&result);
-    StarkResult::Success
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Pedersen);
+        check_null!(a, b, out);
+        let a = match felt_from_bytes(&*a) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let b = match felt_from_bytes(&*b) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+
+        let result = pedersen_hash(&a, &b);
+        *out = felt_to_bytes(&result);
+        StarkResult::Success
+    })
 }
 
 /// Poseidon hash of two felts
+#[cfg(feature = "hashing")]
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_poseidon_hash")]
 pub unsafe extern "C" fn starknet_poseidon_hash(
     a: *const FeltBytes,
     b: *const FeltBytes,
     out: *mut FeltBytes,
 ) -> StarkResult {
-    let a = match felt_from_bytes(&*a) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    let b = match felt_from_bytes(&*b) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-
-    let result = poseidon_hash(a, b);
-    *out = felt_to_bytes(&result);
-    StarkResult::Success
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Poseidon);
+        check_null!(a, b, out);
+        let a = match felt_from_bytes(&*a) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let b = match felt_from_bytes(&*b) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+
+        let result = poseidon_hash(a, b);
+        *out = felt_to_bytes(&result);
+        StarkResult::Success
+    })
 }
 
 /// Poseidon hash of N felts
+#[cfg(feature = "hashing")]
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_poseidon_hash_many")]
 pub unsafe extern "C" fn starknet_poseidon_hash_many(
     inputs: *const FeltBytes,
     count: usize,
     out: *mut FeltBytes,
 ) -> StarkResult {
-    if count == 0 {
-        return StarkResult::InvalidInput;
-    }
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Poseidon);
+        check_null!(inputs, out);
+        if count == 0 {
+            return StarkResult::InvalidInput;
+        }
 
-    let slice = std::slice::from_raw_parts(inputs, count);
-    let felts: Vec<Felt> = slice
-        .iter()
-        .filter_map(|b| felt_from_bytes(b))
-        .collect();
+        let slice = core::slice::from_raw_parts(inputs, count);
+        let felts: Vec<Felt> = slice
+            .iter()
+            .filter_map(felt_from_bytes)
+            .collect();
+
+        if felts.len() != count {
+            return StarkResult::InvalidInput;
+        }
+
+        let result = poseidon_hash_many(&felts);
+        *out = felt_to_bytes(&result);
+        StarkResult::Success
+    })
+}
+
+/// Poseidon hash of N felts, converted in an arena's buffer
+///
+/// Same as `starknet_poseidon_hash_many`, without allocating once `arena`
+/// has grown to `count` felts. `arena` may be null.
+#[cfg(feature = "hashing")]
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_starknet_poseidon_hash_many_arena"
+)]
+pub unsafe extern "C" fn starknet_poseidon_hash_many_arena(
+    arena: *const arena::StarkArena,
+    inputs: *const FeltBytes,
+    count: usize,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Poseidon);
+        check_null!(inputs, out);
+        if count == 0 {
+            return StarkResult::InvalidInput;
+        }
+        let arena = match arena::optional_arena(arena) {
+            Ok(arena) => arena,
+            Err(e) => return e,
+        };
+
+        let slice = core::slice::from_raw_parts(inputs, count);
+        arena::with_felts(arena, |felts| {
+            felts.extend(slice.iter().map_while(felt_from_bytes));
+            if felts.len() != count {
+                return StarkResult::InvalidInput;
+            }
+            *out = felt_to_bytes(&poseidon_hash_many(&*felts));
+            StarkResult::Success
+        })
+    })
+}
 
-    if felts.len() != count {
-        return StarkResult::InvalidInput;
+/// `poseidon_hash_many` with the capacity element set to `domain`
+///
+/// The Poseidon paper's domain separation: the sponge starts from
+/// `[0, 0, domain]` instead of all zeros, then absorbs and pads exactly as
+/// `poseidon_hash_many` does, so domain 0 gives the plain hash.
+#[cfg(feature = "hashing")]
+fn poseidon_hash_many_with_domain(domain: Felt, inputs: &[Felt]) -> Felt {
+    let mut state = [Felt::ZERO, Felt::ZERO, domain];
+    let mut pairs = inputs.chunks_exact(2);
+    for pair in &mut pairs {
+        state[0] += pair[0];
+        state[1] += pair[1];
+        poseidon_permute_comp(&mut state);
+    }
+    match pairs.remainder() {
+        [last] => {
+            state[0] += *last;
+            state[1] += Felt::ONE;
+        }
+        _ => state[0] += Felt::ONE,
     }
+    poseidon_permute_comp(&mut state);
+    state[0]
+}
 
-    let result = poseidon_hash_many(&felts);
-    *out = felt_to_bytes(&result);
-    StarkResult::Success
+/// Poseidon hash of N felts in the hash domain `domain_tag`
+///
+/// Protocols hashing with distinct tags cannot collide with each other: the
+/// tag initializes the sponge capacity. Tag 0 is the plain
+/// `starknet_poseidon_hash_many` domain. `inputs` may be null when `count`
+/// is 0.
+#[cfg(feature = "hashing")]
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_poseidon_hash_with_domain")]
+pub unsafe extern "C" fn poseidon_hash_with_domain(
+    domain_tag: *const FeltBytes,
+    inputs: *const FeltBytes,
+    count: usize,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Poseidon);
+        check_null!(domain_tag, out);
+        let Some(domain) = felt_from_bytes(&*domain_tag) else {
+            return StarkResult::InvalidInput;
+        };
+        let Some(felts) = felts_from_raw(inputs, count) else {
+            return error::null_pointer("inputs");
+        };
+        *out = felt_to_bytes(&poseidon_hash_many_with_domain(domain, &felts));
+        StarkResult::Success
+    })
 }
 
 /// Standard Keccak256 hash of arbitrary data (full 32 bytes)
 ///
 /// Returns the full 256-bit Keccak256 hash without any truncation.
+#[cfg(feature = "hashing")]
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_keccak256")]
 pub unsafe extern "C" fn keccak256(
     data: *const u8,
     len: usize,
     out: *mut [u8; 32],
 ) -> StarkResult {
-    if data.is_null() && len > 0 {
-        return StarkResult::InvalidInput;
-    }
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Keccak);
+        check_null!(out);
+        if data.is_null() && len > 0 {
+            return error::null_pointer("data");
+        }
 
-    let slice = if len > 0 {
-        std::slice::from_raw_parts(data, len)
-    } else {
-        &[]
-    };
+        let slice = if len > 0 {
+            core::slice::from_raw_parts(data, len)
+        } else {
+            &[]
+        };
 
-    let mut hasher = Keccak256::new();
-    hasher.update(slice);
-    let hash = hasher.finalize();
+        let mut hasher = Keccak256::new();
+        hasher.update(slice);
+        let hash = hasher.finalize();
 
-    (*out).copy_from_slice(&hash);
-    StarkResult::Success
+        (*out).copy_from_slice(&hash);
+        StarkResult::Success
+    })
 }
 
 /// Keccak256 hash of arbitrary data, truncated to 250 bits (Starknet selector format)
 ///
 /// This is used for computing function/event selectors in Starknet.
 /// The output is keccak256(data) with the top 6 bits masked to zero.
+#[cfg(feature = "hashing")]
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_keccak256")]
 pub unsafe extern "C" fn starknet_keccak256(
     data: *const u8,
     len: usize,
     out: *mut FeltBytes,
 ) -> StarkResult {
-    if data.is_null() && len > 0 {
-        return StarkResult::InvalidInput;
-    }
-
-    let slice = if len > 0 {
-        std::slice::from_raw_parts(data, len)
-    } else {
-        &[]
-    };
-
-    // Compute keccak256
-    let mut hasher = Keccak256::new();
-    hasher.update(slice);
-    let hash = hasher.finalize();
-
-    // Copy to output, masking to 250 bits
-    let mut result = [0u8; 32];
-    result.copy_from_slice(&hash);
-    // Mask top 6 bits (250 = 256 - 6)
-    result[0] &= 0x03; // Keep only bottom 2 bits of first byte
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Keccak);
+        check_null!(out);
+        if data.is_null() && len > 0 {
+            return error::null_pointer("data");
+        }
 
-    *out = result;
-    StarkResult::Success
+        let slice = if len > 0 {
+            core::slice::from_raw_parts(data, len)
+        } else {
+            &[]
+        };
+
+        // Compute keccak256
+        let mut hasher = Keccak256::new();
+        hasher.update(slice);
+        let hash = hasher.finalize();
+
+        // Copy to output, masking to 250 bits
+        let mut result = [0u8; 32];
+        result.copy_from_slice(&hash);
+        // Mask top 6 bits (250 = 256 - 6)
+        result[0] &= 0x03; // Keep only bottom 2 bits of first byte
+
+        *out = result;
+        StarkResult::Success
+    })
 }
 
 // ============ ECDSA ============
 
 /// Get public key from private key
+#[cfg(feature = "ecdsa")]
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_get_public_key")]
 pub unsafe extern "C" fn starknet_get_public_key(
     private_key: *const FeltBytes,
     out: *mut FeltBytes,
 ) -> StarkResult {
-    let pk = match felt_from_bytes(&*private_key) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
+    ffi_guard(|| {
+        check_null!(private_key, out);
+        let pk = match felt_from_bytes(&*private_key) {
+            Some(f) => Zeroizing::new(f),
+            None => return StarkResult::InvalidInput,
+        };
+
+        let public_key = get_public_key(&pk);
+        *out = felt_to_bytes(&public_key);
+        StarkResult::Success
+    })
+}
 
-    let public_key = get_public_key(&pk);
-    *out = felt_to_bytes(&public_key);
-    StarkResult::Success
+/// Generate a random keypair from OS randomness
+///
+/// The private key is sampled uniformly from [1, n). Returns `RngFailed` if
+/// the OS random source is unavailable.
+#[cfg(feature = "ecdsa")]
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_keypair_generate")]
+pub unsafe extern "C" fn starknet_keypair_generate(
+    out_private: *mut FeltBytes,
+    out_public: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_private, out_public);
+        let pk = match random::random_private_key() {
+            Ok(f) => Zeroizing::new(f),
+            Err(_) => return StarkResult::RngFailed,
+        };
+
+        *out_private = felt_to_bytes(&pk);
+        *out_public = felt_to_bytes(&get_public_key(&pk));
+        StarkResult::Success
+    })
+}
+
+/// Check that a private key is usable for signing: 0 < key < curve order
+///
+/// Returns `InvalidPrivateKey` otherwise.
+#[cfg(feature = "ecdsa")]
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_private_key_is_valid")]
+pub unsafe extern "C" fn starknet_private_key_is_valid(
+    private_key: *const FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(private_key);
+        let pk = match felt_from_bytes(&*private_key) {
+            Some(f) => Zeroizing::new(f),
+            None => return StarkResult::InvalidInput,
+        };
+
+        if *pk == Felt::ZERO || *pk >= EC_ORDER {
+            return StarkResult::InvalidPrivateKey;
+        }
+        StarkResult::Success
+    })
 }
 
 /// Sign a message hash with private key (returns r, s)
+#[cfg(feature = "ecdsa")]
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_sign")]
 pub unsafe extern "C" fn starknet_sign(
     private_key: *const FeltBytes,
     message_hash: *const FeltBytes,
     out_r: *mut FeltBytes,
     out_s: *mut FeltBytes,
 ) -> StarkResult {
-    let pk = match felt_from_bytes(&*private_key) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    let msg = match felt_from_bytes(&*message_hash) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Sign);
+        check_null!(private_key, message_hash, out_r, out_s);
+        let pk = match felt_from_bytes(&*private_key) {
+            Some(f) => Zeroizing::new(f),
+            None => return StarkResult::InvalidInput,
+        };
+        let msg = match felt_from_bytes(&*message_hash) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+
+        // Derive k deterministically via RFC6979 (unique per message+key pair)
+        let k = Zeroizing::new(rfc6979_generate_k(&msg, &pk, None));
+        match sign(&pk, &msg, &k) {
+            Ok(sig) => {
+                *out_r = felt_to_bytes(&sig.r);
+                *out_s = felt_to_bytes(&sig.s);
+                StarkResult::Success
+            }
+            Err(_) => StarkResult::InvalidInput,
+        }
+    })
+}
 
-    // Derive k deterministically via RFC6979 (unique per message+key pair)
-    let k = rfc6979_generate_k(&msg, &pk, None);
-    match sign(&pk, &msg, &k) {
-        Ok(sig) => {
-            *out_r = felt_to_bytes(&sig.r);
-            *out_s = felt_to_bytes(&sig.s);
-            StarkResult::Success
+/// Sign a message hash with private key (returns r, s and the recovery id v)
+///
+/// `v` is the value `starknet_recover` expects to recover the public key.
+#[cfg(feature = "ecdsa")]
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_sign_recoverable")]
+pub unsafe extern "C" fn starknet_sign_recoverable(
+    private_key: *const FeltBytes,
+    message_hash: *const FeltBytes,
+    out_r: *mut FeltBytes,
+    out_s: *mut FeltBytes,
+    out_v: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Sign);
+        check_null!(private_key, message_hash, out_r, out_s, out_v);
+        let pk = match felt_from_bytes(&*private_key) {
+            Some(f) => Zeroizing::new(f),
+            None => return StarkResult::InvalidInput,
+        };
+        let msg = match felt_from_bytes(&*message_hash) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+
+        let k = Zeroizing::new(rfc6979_generate_k(&msg, &pk, None));
+        match sign(&pk, &msg, &k) {
+            Ok(sig) => {
+                *out_r = felt_to_bytes(&sig.r);
+                *out_s = felt_to_bytes(&sig.s);
+                *out_v = felt_to_bytes(&sig.v);
+                StarkResult::Success
+            }
+            Err(_) => StarkResult::InvalidInput,
         }
-        Err(_) => StarkResult::InvalidInput,
-    }
+    })
+}
+
+/// Sign a message hash with a caller-provided nonce k (returns r, s)
+///
+/// k must be uniformly random and never reused across messages, or the private
+/// key can be recovered from two signatures. Prefer `starknet_sign` unless you
+/// need control over nonce derivation.
+#[cfg(feature = "ecdsa")]
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_sign_with_k")]
+pub unsafe extern "C" fn starknet_sign_with_k(
+    private_key: *const FeltBytes,
+    message_hash: *const FeltBytes,
+    k: *const FeltBytes,
+    out_r: *mut FeltBytes,
+    out_s: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Sign);
+        check_null!(private_key, message_hash, k, out_r, out_s);
+        let pk = match felt_from_bytes(&*private_key) {
+            Some(f) => Zeroizing::new(f),
+            None => return StarkResult::InvalidInput,
+        };
+        let msg = match felt_from_bytes(&*message_hash) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let k = match felt_from_bytes(&*k) {
+            Some(f) => Zeroizing::new(f),
+            None => return StarkResult::InvalidInput,
+        };
+
+        match sign(&pk, &msg, &k) {
+            Ok(sig) => {
+                *out_r = felt_to_bytes(&sig.r);
+                *out_s = felt_to_bytes(&sig.s);
+                StarkResult::Success
+            }
+            Err(_) => StarkResult::InvalidInput,
+        }
+    })
+}
+
+/// Derive the RFC6979 nonce k used by `starknet_sign`
+///
+/// `seed` is optional extra entropy (may be null). With a null seed this is
+/// exactly the k `starknet_sign` uses for the same key and message.
+#[cfg(feature = "ecdsa")]
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_rfc6979_k")]
+pub unsafe extern "C" fn starknet_rfc6979_k(
+    message_hash: *const FeltBytes,
+    private_key: *const FeltBytes,
+    seed: *const FeltBytes,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(message_hash, private_key, out);
+        let msg = match felt_from_bytes(&*message_hash) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let pk = match felt_from_bytes(&*private_key) {
+            Some(f) => Zeroizing::new(f),
+            None => return StarkResult::InvalidInput,
+        };
+        let seed = if seed.is_null() {
+            None
+        } else {
+            match felt_from_bytes(&*seed) {
+                Some(f) => Some(f),
+                None => return StarkResult::InvalidInput,
+            }
+        };
+
+        let k = Zeroizing::new(rfc6979_generate_k(&msg, &pk, seed.as_ref()));
+        *out = felt_to_bytes(&k);
+        StarkResult::Success
+    })
 }
 
 /// Verify a signature
+#[cfg(feature = "ecdsa")]
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_verify")]
 pub unsafe extern "C" fn starknet_verify(
     public_key: *const FeltBytes,
     message_hash: *const FeltBytes,
     r: *const FeltBytes,
     s: *const FeltBytes,
 ) -> StarkResult {
-    let pk = match felt_from_bytes(&*public_key) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    let msg = match felt_from_bytes(&*message_hash) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    let r = match felt_from_bytes(&*r) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    let s = match felt_from_bytes(&*s) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-
-    match verify(&pk, &msg, &r, &s) {
-        Ok(valid) => {
-            if valid {
-                StarkResult::Success
-            } else {
-                StarkResult::InvalidSignature
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Verify);
+        check_null!(public_key, message_hash, r, s);
+        let pk = match felt_from_bytes(&*public_key) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let msg = match felt_from_bytes(&*message_hash) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let r = match felt_from_bytes(&*r) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let s = match felt_from_bytes(&*s) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+
+        match verify(&pk, &msg, &r, &s) {
+            Ok(valid) => {
+                if valid {
+                    StarkResult::Success
+                } else {
+                    StarkResult::InvalidSignature
+                }
             }
+            Err(_) => StarkResult::InvalidInput,
         }
-        Err(_) => StarkResult::InvalidInput,
-    }
+    })
 }
 
 /// Recover public key from signature
+#[cfg(feature = "ecdsa")]
 #[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_recover")]
 pub unsafe extern "C" fn starknet_recover(
     message_hash: *const FeltBytes,
     r: *const FeltBytes,
@@ -437,30 +1265,34 @@ pub unsafe extern "C" fn starknet_recover(
     v: *const FeltBytes,
     out: *mut FeltBytes,
 ) -> StarkResult {
-    let msg = match felt_from_bytes(&*message_hash) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    let r = match felt_from_bytes(&*r) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    let s = match felt_from_bytes(&*s) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-    let v = match felt_from_bytes(&*v) {
-        Some(f) => f,
-        None => return StarkResult::InvalidInput,
-    };
-
-    match recover(&msg, &r, &s, &v) {
-        Ok(public_key) => {
-            *out = felt_to_bytes(&public_key);
-            StarkResult::Success
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Recover);
+        check_null!(message_hash, r, s, v, out);
+        let msg = match felt_from_bytes(&*message_hash) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let r = match felt_from_bytes(&*r) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let s = match felt_from_bytes(&*s) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let v = match felt_from_bytes(&*v) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+
+        match recover(&msg, &r, &s, &v) {
+            Ok(public_key) => {
+                *out = felt_to_bytes(&public_key);
+                StarkResult::Success
+            }
+            Err(_) => StarkResult::RecoveryFailed,
         }
-        Err(_) => StarkResult::RecoveryFailed,
-    }
+    })
 }
 
 // ============ TESTS ============
@@ -614,6 +1446,46 @@ mod tests {
         assert_eq!(out, expected);
     }
 
+    #[test]
+    fn test_felt_pow_exponent_semantics() {
+        let two = felt_bytes_from_u64(2);
+        // P + 3 as a raw 256-bit integer (P < 2^252, no overflow)
+        let mut big = felt_to_bytes(&Felt::MAX);
+        big[31] += 4;
+        let (mut pow, mut reduced) = ([0u8; 32], [0u8; 32]);
+        unsafe {
+            // felt_pow reads P + 3 as the felt 3
+            felt_pow(&two, &big, &mut pow);
+            assert_eq!(pow, felt_bytes_from_u64(8));
+
+            // felt_pow_reduced: (P + 3) mod (P - 1) = 4
+            felt_pow_reduced(&two, &big, &mut reduced);
+            assert_eq!(reduced, felt_bytes_from_u64(16));
+
+            // 2^(P - 1) = 1 either way; 0^(P - 1) = 1 when reduced
+            let p_minus_1 = felt_to_bytes(&Felt::MAX);
+            felt_pow_reduced(&two, &p_minus_1, &mut reduced);
+            felt_pow(&two, &p_minus_1, &mut pow);
+            assert_eq!((reduced, pow), (felt_bytes_from_u64(1), felt_bytes_from_u64(1)));
+            felt_pow_reduced(&[0u8; 32], &p_minus_1, &mut reduced);
+            assert_eq!(reduced, felt_bytes_from_u64(1));
+            felt_pow_reduced(&[0u8; 32], &felt_bytes_from_u64(5), &mut reduced);
+            assert_eq!(reduced, [0u8; 32]);
+
+            let max = [0xffu8; 32];
+            assert_eq!(felt_pow_reduced(&two, &max, &mut reduced), StarkResult::Success);
+        }
+
+        // The reduction of 256-bit exponents, above and below 17·hi·2^192
+        let p_minus_1 = Felt::MAX.to_biguint();
+        let mut high_bit = [0u8; 32];
+        high_bit[0] = 0x80;
+        for exp in [[0xffu8; 32], high_bit, felt_to_bytes(&Felt::MAX)] {
+            let expected = num_bigint::BigUint::from_bytes_be(&exp) % &p_minus_1;
+            assert_eq!(reduce_exponent(&exp), Felt::from(expected));
+        }
+    }
+
     #[test]
     fn test_felt_sqrt() {
         // 9 is a perfect square
@@ -633,6 +1505,54 @@ mod tests {
         assert_eq!(squared, a);
     }
 
+    #[test]
+    fn test_felt_assign_matches_out_variants() {
+        type Assign = unsafe extern "C" fn(*mut FeltBytes, *const FeltBytes) -> StarkResult;
+        type Binary =
+            unsafe extern "C" fn(*const FeltBytes, *const FeltBytes, *mut FeltBytes) -> StarkResult;
+        let ops: [(Assign, Binary); 4] = [
+            (felt_add_assign, felt_add),
+            (felt_sub_assign, felt_sub),
+            (felt_mul_assign, felt_mul),
+            (felt_div_assign, felt_div),
+        ];
+        let (a, b) = (felt_bytes_from_u64(12345), felt_to_bytes(&Felt::MAX));
+
+        for (assign, binary) in ops {
+            let (mut inout, mut expected) = (a, [0u8; 32]);
+            unsafe {
+                assert_eq!(binary(&a, &b, &mut expected), StarkResult::Success);
+                assert_eq!(assign(&mut inout, &b), StarkResult::Success);
+            }
+            assert_eq!(inout, expected);
+
+            // b aliasing a_inout
+            unsafe {
+                binary(&a, &a, &mut expected);
+                inout = a;
+                let ptr: *mut FeltBytes = &mut inout;
+                assert_eq!(assign(ptr, ptr), StarkResult::Success);
+            }
+            assert_eq!(inout, expected);
+        }
+
+        let mut inout = a;
+        let mut expected = [0u8; 32];
+        unsafe {
+            felt_neg(&a, &mut expected);
+            assert_eq!(felt_neg_assign(&mut inout), StarkResult::Success);
+            assert_eq!(inout, expected);
+
+            inout = a;
+            assert_eq!(
+                felt_div_assign(&mut inout, &[0u8; 32]),
+                StarkResult::DivisionByZero
+            );
+            assert_eq!(inout, a);
+        }
+    }
+
+    #[cfg(feature = "hashing")]
     #[test]
     fn test_pedersen_hash() {
         let a = felt_bytes_from_u64(1);
@@ -648,6 +1568,7 @@ mod tests {
         assert_ne!(out, [0u8; 32]);
     }
 
+    #[cfg(feature = "hashing")]
     #[test]
     fn test_poseidon_hash() {
         let a = felt_bytes_from_u64(1);
@@ -663,6 +1584,7 @@ mod tests {
         assert_ne!(out, [0u8; 32]);
     }
 
+    #[cfg(feature = "hashing")]
     #[test]
     fn test_poseidon_hash_many() {
         let inputs = [
@@ -681,6 +1603,69 @@ mod tests {
         assert_ne!(out, [0u8; 32]);
     }
 
+    #[cfg(feature = "hashing")]
+    #[test]
+    fn test_poseidon_hash_with_domain() {
+        let inputs: Vec<FeltBytes> = (1..=5).map(felt_bytes_from_u64).collect();
+        let (zero, tag) = (felt_bytes_from_u64(0), felt_bytes_from_u64(0x5354524b));
+        let (mut expected, mut plain, mut tagged) = ([0u8; 32], [0u8; 32], [0u8; 32]);
+
+        unsafe {
+            for count in [1, 2, 5] {
+                starknet_poseidon_hash_many(inputs.as_ptr(), count, &mut expected);
+                assert_eq!(
+                    poseidon_hash_with_domain(&zero, inputs.as_ptr(), count, &mut plain),
+                    StarkResult::Success
+                );
+                assert_eq!(plain, expected);
+                poseidon_hash_with_domain(&tag, inputs.as_ptr(), count, &mut tagged);
+                assert_ne!(tagged, plain);
+            }
+            assert_eq!(
+                poseidon_hash_with_domain(&tag, core::ptr::null(), 0, &mut tagged),
+                StarkResult::Success
+            );
+        }
+
+        // The tag is the initial capacity
+        let mut state = [Felt::ZERO, Felt::ZERO, Felt::from(0x5354524bu64)];
+        state[0] += Felt::ONE;
+        poseidon_permute_comp(&mut state);
+        assert_eq!(tagged, felt_to_bytes(&state[0]));
+    }
+
+    #[cfg(feature = "hashing")]
+    #[test]
+    fn test_poseidon_hash_many_arena() {
+        let inputs: Vec<FeltBytes> = (1..=20).map(felt_bytes_from_u64).collect();
+        let mut arena = core::ptr::null_mut();
+        let (mut expected, mut out) = ([0u8; 32], [0u8; 32]);
+
+        unsafe {
+            assert_eq!(arena::stark_arena_new(0, &mut arena), StarkResult::Success);
+            for count in [20, 3, 20] {
+                starknet_poseidon_hash_many(inputs.as_ptr(), count, &mut expected);
+                assert_eq!(
+                    starknet_poseidon_hash_many_arena(arena, inputs.as_ptr(), count, &mut out),
+                    StarkResult::Success
+                );
+                assert_eq!(out, expected);
+            }
+            assert_eq!(
+                starknet_poseidon_hash_many_arena(
+                    core::ptr::null(),
+                    inputs.as_ptr(),
+                    20,
+                    &mut out
+                ),
+                StarkResult::Success
+            );
+            assert_eq!(out, expected);
+            arena::stark_arena_free(arena);
+        }
+    }
+
+    #[cfg(feature = "ecdsa")]
     #[test]
     fn test_get_public_key() {
         // Use a test private key
@@ -696,6 +1681,48 @@ mod tests {
         assert_ne!(public_key, [0u8; 32]);
     }
 
+    #[cfg(feature = "ecdsa")]
+    #[test]
+    fn test_keypair_generate() {
+        let mut private_a = [0u8; 32];
+        let mut public_a = [0u8; 32];
+        let mut private_b = [0u8; 32];
+        let mut public_b = [0u8; 32];
+        let mut expected_public = [0u8; 32];
+
+        unsafe {
+            let result = starknet_keypair_generate(&mut private_a, &mut public_a);
+            assert_eq!(result, StarkResult::Success);
+            let result = starknet_keypair_generate(&mut private_b, &mut public_b);
+            assert_eq!(result, StarkResult::Success);
+
+            assert_eq!(starknet_private_key_is_valid(&private_a), StarkResult::Success);
+            starknet_get_public_key(&private_a, &mut expected_public);
+            assert_eq!(public_a, expected_public);
+        }
+        assert_ne!(private_a, private_b);
+    }
+
+    #[cfg(feature = "ecdsa")]
+    #[test]
+    fn test_private_key_is_valid() {
+        let order_minus_one = felt_to_bytes(&(EC_ORDER - Felt::ONE));
+
+        unsafe {
+            assert_eq!(starknet_private_key_is_valid(&felt_bytes_from_u64(1)), StarkResult::Success);
+            assert_eq!(starknet_private_key_is_valid(&order_minus_one), StarkResult::Success);
+            assert_eq!(
+                starknet_private_key_is_valid(&felt_bytes_from_u64(0)),
+                StarkResult::InvalidPrivateKey
+            );
+            assert_eq!(
+                starknet_private_key_is_valid(&felt_to_bytes(&EC_ORDER)),
+                StarkResult::InvalidPrivateKey
+            );
+        }
+    }
+
+    #[cfg(feature = "ecdsa")]
     #[test]
     fn test_sign_and_verify_roundtrip() {
         // Generate a test keypair
@@ -725,6 +1752,104 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "ecdsa")]
+    #[test]
+    fn test_sign_recoverable() {
+        let private_key = felt_bytes_from_u64(12345);
+        let message_hash = felt_bytes_from_u64(67890);
+        let mut public_key = [0u8; 32];
+        let mut recovered = [0u8; 32];
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        let mut v = [0u8; 32];
+        let mut plain_r = [0u8; 32];
+        let mut plain_s = [0u8; 32];
+
+        unsafe {
+            let result =
+                starknet_sign_recoverable(&private_key, &message_hash, &mut r, &mut s, &mut v);
+            assert_eq!(result, StarkResult::Success);
+
+            // Same (r, s) as the plain signing path
+            starknet_sign(&private_key, &message_hash, &mut plain_r, &mut plain_s);
+            assert_eq!((r, s), (plain_r, plain_s));
+
+            starknet_get_public_key(&private_key, &mut public_key);
+            let result = starknet_recover(&message_hash, &r, &s, &v, &mut recovered);
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(recovered, public_key);
+        }
+    }
+
+    #[cfg(feature = "ecdsa")]
+    #[test]
+    fn test_sign_with_k() {
+        let private_key = felt_bytes_from_u64(1);
+        let message_hash = felt_bytes_from_u64(2);
+        let k = felt_bytes_from_u64(3);
+        let mut public_key = [0u8; 32];
+        let mut k_point_x = [0u8; 32];
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+
+        unsafe {
+            let result = starknet_sign_with_k(&private_key, &message_hash, &k, &mut r, &mut s);
+            assert_eq!(result, StarkResult::Success);
+
+            // r is the x coordinate of k*G
+            starknet_get_public_key(&k, &mut k_point_x);
+            assert_eq!(r, k_point_x);
+
+            starknet_get_public_key(&private_key, &mut public_key);
+            let result = starknet_verify(&public_key, &message_hash, &r, &s);
+            assert_eq!(result, StarkResult::Success);
+
+            // k = 0 is rejected
+            let zero = [0u8; 32];
+            let result = starknet_sign_with_k(&private_key, &message_hash, &zero, &mut r, &mut s);
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+
+    #[cfg(feature = "ecdsa")]
+    #[test]
+    fn test_rfc6979_k_with_seed() {
+        // cairo-lang test vector
+        let hex = |s: &str| felt_to_bytes(&Felt::from_hex(s).unwrap());
+        let message_hash = hex("0x0080977da1148412a7976215729d396b72aec9e955498757a7b859281354b4b1");
+        let private_key = hex("0x03fa56dcdbe2fb6769a83786469faf589a3d1e31c66db8b0432f741a38cdeed1");
+        let seed = hex("0x0776cc1aa4c66417a4923768b9d4a7cfca731e862e4972ed930d8f2ad45d352b");
+        let mut k = [0u8; 32];
+
+        unsafe {
+            let result = starknet_rfc6979_k(&message_hash, &private_key, &seed, &mut k);
+            assert_eq!(result, StarkResult::Success);
+        }
+        assert_eq!(k, hex("0x0013480c97bb5861404aa16e1f97a99411ba8f4039b2d54de839dea5c9f0af47"));
+    }
+
+    #[cfg(feature = "ecdsa")]
+    #[test]
+    fn test_rfc6979_k_matches_sign() {
+        let private_key = felt_bytes_from_u64(12345);
+        let message_hash = felt_bytes_from_u64(67890);
+        let mut k = [0u8; 32];
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        let mut r_k = [0u8; 32];
+        let mut s_k = [0u8; 32];
+
+        unsafe {
+            let result = starknet_rfc6979_k(&message_hash, &private_key, core::ptr::null(), &mut k);
+            assert_eq!(result, StarkResult::Success);
+
+            starknet_sign(&private_key, &message_hash, &mut r, &mut s);
+            starknet_sign_with_k(&private_key, &message_hash, &k, &mut r_k, &mut s_k);
+            assert_eq!((r, s), (r_k, s_k));
+        }
+    }
+
+    #[cfg(feature = "ecdsa")]
     #[test]
     fn test_verify_invalid_signature() {
         let private_key = felt_bytes_from_u64(12345);
@@ -746,6 +1871,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "hashing")]
     #[test]
     fn test_keccak256_standard() {
         let data = b"hello";
@@ -767,12 +1893,13 @@ mod tests {
         assert_eq!(out, expected);
     }
 
+    #[cfg(feature = "hashing")]
     #[test]
     fn test_keccak256_empty() {
         let mut out = [0u8; 32];
 
         unsafe {
-            let result = keccak256(std::ptr::null(), 0, &mut out);
+            let result = keccak256(core::ptr::null(), 0, &mut out);
             assert_eq!(result, StarkResult::Success);
         }
 
@@ -787,6 +1914,7 @@ mod tests {
         assert_eq!(out, expected);
     }
 
+    #[cfg(feature = "hashing")]
     #[test]
     fn test_starknet_keccak256() {
         let data = b"transfer";
@@ -804,16 +1932,70 @@ mod tests {
         assert_ne!(out, [0u8; 32]);
     }
 
+    #[cfg(feature = "hashing")]
     #[test]
     fn test_starknet_keccak256_empty() {
         let mut out = [0u8; 32];
 
         unsafe {
-            let result = starknet_keccak256(std::ptr::null(), 0, &mut out);
+            let result = starknet_keccak256(core::ptr::null(), 0, &mut out);
             assert_eq!(result, StarkResult::Success);
         }
 
         // Should produce the keccak of empty string, masked
         assert!(out[0] <= 0x03);
     }
+
+    #[test]
+    fn test_ffi_guard_catches_panic() {
+        assert_eq!(ffi_guard(|| panic!("boom")), StarkResult::InternalPanic);
+        assert_eq!(
+            ffi_guard(|| StarkResult::InvalidInput),
+            StarkResult::InvalidInput
+        );
+        // Returns normally instead of unwinding
+        ffi_guard_void(|| panic!("boom"));
+    }
+
+    #[test]
+    fn test_null_pointers() {
+        let a = felt_bytes_from_u64(5);
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let null = core::ptr::null_mut();
+            assert_eq!(felt_add(&a, &a, null), StarkResult::NullPointer);
+            assert_eq!(
+                felt_add(core::ptr::null(), &a, &mut out),
+                StarkResult::NullPointer
+            );
+            #[cfg(feature = "hashing")]
+            {
+                assert_eq!(
+                    starknet_poseidon_hash_many(core::ptr::null(), 2, &mut out),
+                    StarkResult::NullPointer
+                );
+                assert_eq!(
+                    keccak256(core::ptr::null(), 1, &mut out),
+                    StarkResult::NullPointer
+                );
+            }
+            // A null buffer is fine while nothing has to be written to it
+            let mut len = 0;
+            assert_eq!(
+                write_bytes_out(&[], core::ptr::null_mut(), &mut len),
+                StarkResult::Success
+            );
+            // Size query: a null buffer with a zero capacity
+            assert_eq!(
+                write_bytes_out(&[1, 2], core::ptr::null_mut(), &mut len),
+                StarkResult::BufferTooSmall
+            );
+            assert_eq!(len, 2);
+            assert_eq!(
+                write_bytes_out(&[1, 2], core::ptr::null_mut(), &mut len),
+                StarkResult::NullPointer
+            );
+        }
+    }
 }
@@ -0,0 +1,161 @@
+//! Diagnostics through a host-provided logging callback
+//!
+//! The library never writes to stdout or stderr. A host that wants
+//! diagnostics registers a callback with `stark_set_log_callback`; the
+//! library then reports failed calls (with the `stark_last_error_message`
+//! text), platform and feature fallbacks, and streaming progress through it.
+//! Without a callback, a log site costs one atomic load.
+//!
+//! The callback runs on the thread that made the call being logged, so it
+//! may be invoked from several threads at once. It may call back into the
+//! library; messages logged while it runs are dropped rather than
+//! delivered recursively.
+
+use core::cell::Cell;
+use core::ffi::c_void;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::sync::Mutex;
+use crate::{ffi_guard, StarkResult};
+
+/// Severity of a log message (lower is more severe)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StarkLogLevel {
+    /// Internal failures (a caught panic)
+    Error = 1,
+    /// Degraded operation (a platform fallback, the test RNG)
+    Warn = 2,
+    /// Configuration changes
+    Info = 3,
+    /// Failed calls and their reason
+    Debug = 4,
+    /// Progress of long operations
+    Trace = 5,
+}
+
+/// Receives one log message: `len` bytes of UTF-8 at `msg` (not
+/// NUL-terminated), only valid during the call
+///
+/// `ctx` is the pointer given to `stark_set_log_callback`.
+pub type StarkLogCallback = Option<
+    unsafe extern "C" fn(ctx: *mut c_void, level: StarkLogLevel, msg: *const u8, len: usize),
+>;
+
+#[derive(Clone, Copy)]
+struct Logger {
+    callback: unsafe extern "C" fn(*mut c_void, StarkLogLevel, *const u8, usize),
+    /// The host's context pointer (stored as an address so the logger can
+    /// live in a static)
+    ctx: usize,
+}
+
+static LOGGER: Mutex<Option<Logger>> = Mutex::new(None);
+/// Whether a callback is set, checked before formatting anything
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+local! {
+    /// Set while this thread runs the callback
+    static IN_CALLBACK: Cell<bool> = Cell::new(false);
+}
+
+/// Send a message to the host callback, if one is set (see `log!`)
+pub(crate) fn emit(level: StarkLogLevel, args: fmt::Arguments) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    // Copied out so the lock is not held while the callback runs
+    let logger = *LOGGER.lock();
+    let logger = match logger {
+        Some(l) => l,
+        None => return,
+    };
+    if IN_CALLBACK.with(|c| c.replace(true)) {
+        return;
+    }
+    let message = alloc::fmt::format(args);
+    unsafe {
+        (logger.callback)(
+            logger.ctx as *mut c_void,
+            level,
+            message.as_ptr(),
+            message.len(),
+        )
+    };
+    IN_CALLBACK.with(|c| c.set(false));
+}
+
+/// Route library diagnostics to `callback`, called with `ctx`
+///
+/// Replaces any previous callback; pass null to turn logging off. `ctx` must
+/// stay valid, and the callback must be safe to call from any thread, until
+/// the callback is replaced.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_set_log_callback")]
+pub unsafe extern "C" fn stark_set_log_callback(
+    callback: StarkLogCallback,
+    ctx: *mut c_void,
+) -> StarkResult {
+    ffi_guard(|| {
+        let logger = callback.map(|callback| Logger {
+            callback,
+            ctx: ctx as usize,
+        });
+        *LOGGER.lock() = logger;
+        ENABLED.store(logger.is_some(), Ordering::Relaxed);
+        StarkResult::Success
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Mutex;
+    use std::thread::{self, ThreadId};
+
+    use crate::felt_inverse;
+
+    static MESSAGES: Mutex<Vec<(ThreadId, StarkLogLevel, String)>> = Mutex::new(Vec::new());
+
+    unsafe extern "C" fn record(
+        ctx: *mut c_void,
+        level: StarkLogLevel,
+        msg: *const u8,
+        len: usize,
+    ) {
+        assert_eq!(ctx as usize, 0x1234);
+        let msg = core::str::from_utf8(core::slice::from_raw_parts(msg, len)).unwrap();
+        let entry = (thread::current().id(), level, msg.to_owned());
+        MESSAGES.lock().unwrap().push(entry);
+        // Reentrant calls are allowed, and their messages dropped
+        felt_inverse(&[0u8; 32], &mut [0u8; 32]);
+    }
+
+    #[test]
+    fn test_log_callback() {
+        // Other tests run in parallel: only look at this thread's messages
+        let own = || -> Vec<(StarkLogLevel, String)> {
+            let id = thread::current().id();
+            let messages = MESSAGES.lock().unwrap();
+            messages
+                .iter()
+                .filter(|(thread, ..)| *thread == id)
+                .map(|(_, level, msg)| (*level, msg.clone()))
+                .collect()
+        };
+        let zero = [0u8; 32];
+        let mut out = [0u8; 32];
+        unsafe {
+            stark_set_log_callback(Some(record), 0x1234 as *mut c_void);
+            assert_eq!(felt_inverse(&zero, &mut out), StarkResult::NoInverse);
+            stark_set_log_callback(None, core::ptr::null_mut());
+            felt_inverse(&zero, &mut out);
+        }
+        assert_eq!(
+            own(),
+            [(StarkLogLevel::Debug, "no modular inverse".to_owned())]
+        );
+    }
+}
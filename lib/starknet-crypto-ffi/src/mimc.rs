@@ -0,0 +1,348 @@
+//! MiMC sponge hash over the Stark field
+//!
+//! circomlib's `MiMCSponge` (the MiMC hash most circuits use), moved to the
+//! Stark field for circuits ported to it: a MiMC-Feistel permutation of 220
+//! rounds with the round function `(xL + k + c_i)^5`, key `k = 0`, absorbing
+//! one input per permutation and outputting one element:
+//!
+//! ```text
+//! R = C = 0
+//! for x in inputs: (R, C) = feistel(R + x, C)
+//! hash = R
+//! ```
+//!
+//! The round constants are circomlib's: `c_0 = c_219 = 0` and, in between,
+//! the chain `h_i = keccak256(h_{i-1})` from `h_0 = keccak256("mimcsponge")`,
+//! each reduced mod the Stark prime instead of BN254's. x^5 is not a
+//! bijection here (5 divides p - 1), which the Feistel construction does not
+//! need. There is no Cairo builtin for it.
+
+use alloc::vec::Vec;
+
+use starknet_crypto::Felt;
+
+use crate::error::{invalid_input, null_pointer};
+use crate::{felt_to_bytes, felts_from_raw, ffi_guard, FeltBytes, StarkResult};
+
+const ROUNDS: usize = 220;
+
+/// MiMC-Feistel permutation of `(xL, xR)` with key 0
+fn feistel(mut left: Felt, mut right: Felt) -> (Felt, Felt) {
+    for (i, c) in ROUND_CONSTANTS.iter().enumerate() {
+        let t = left + c;
+        let t2 = t.square();
+        let sbox = t2.square() * t;
+        if i < ROUNDS - 1 {
+            (left, right) = (right + sbox, left);
+        } else {
+            right += sbox;
+        }
+    }
+    (left, right)
+}
+
+pub(crate) fn mimc_hash_many(inputs: &[Felt]) -> Felt {
+    let (left, _) = inputs
+        .iter()
+        .fold((Felt::ZERO, Felt::ZERO), |(left, right), input| {
+            feistel(left + input, right)
+        });
+    left
+}
+
+/// MiMC sponge hash of `count` felts (see the module docs for the
+/// parameters)
+///
+/// Returns `InvalidInput` if `count` is 0.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_mimc_hash")]
+pub unsafe extern "C" fn mimc_hash(
+    inputs: *const FeltBytes,
+    count: usize,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out);
+        if count == 0 {
+            return invalid_input("`count` is 0");
+        }
+        let inputs: Vec<Felt> = match felts_from_raw(inputs, count) {
+            Some(inputs) => inputs,
+            None => return null_pointer("inputs"),
+        };
+        *out = felt_to_bytes(&mimc_hash_many(&inputs));
+        StarkResult::Success
+    })
+}
+
+/// `c_0` to `c_219`
+#[rustfmt::skip]
+const ROUND_CONSTANTS: [Felt; ROUNDS] = [
+    Felt::from_hex_unchecked("0x0000000000000000000000000000000000000000000000000000000000000000"),
+    Felt::from_hex_unchecked("0x07be43c36a80e35c7c7c584d4f8f3759fb51f0d66065d8a227b688d12488c5d3"),
+    Felt::from_hex_unchecked("0x0448cd3e00a61817253fc009e60f249456f802ff9baf6549210d201321efc1b9"),
+    Felt::from_hex_unchecked("0x07c0849dba2642c37c13eb42ffb97663cdcecd669bf10f756be30bab71b86cf4"),
+    Felt::from_hex_unchecked("0x03f7674473613290c68f7dfdd5b792681d415098554fd8280f00d11b172b80cd"),
+    Felt::from_hex_unchecked("0x03133eb4a1a1aadf037c8bdf9adbb2999baf06f20a9c95180dc4ccdcbec58562"),
+    Felt::from_hex_unchecked("0x008bb66012356d019b059ef1d792b563d6c18624dddecc3fe4583fd3551e9b25"),
+    Felt::from_hex_unchecked("0x01bc3e244e1b91a31fe7f53cf523e491fd6ff487d59337a1d92f92668c4f4c28"),
+    Felt::from_hex_unchecked("0x01808e2b039fce56c489768f78d7499938ccc0858f3295151787cfe8b7e40bc7"),
+    Felt::from_hex_unchecked("0x06978af3ded436e141b3faa40cd6bcfce94f27f4abcc3ed34be19abd2c4537c2"),
+    Felt::from_hex_unchecked("0x029baee798a3209fca5b1cf888386d1dc12c13b38e10225aa4e9f03069a099f4"),
+    Felt::from_hex_unchecked("0x079dbf6050a039a0c3ade8d77e11c767d2251af9cdbd6cdf9a8a0ee921b32c63"),
+    Felt::from_hex_unchecked("0x074bbcf5067f052baec2cce4b98d130d7927456f5c5f6c00e0f5406a24eb8b05"),
+    Felt::from_hex_unchecked("0x037ab080d4c40026da6ecc8bd67468bc4619ba12f25b0da879a639c758c88548"),
+    Felt::from_hex_unchecked("0x06a5b797c2bba60da873b37f5c41adc47765e9be4a1f0e0650e6a24ad2268747"),
+    Felt::from_hex_unchecked("0x0270ae87cf3d82039c0b5f428466c429d7b7cbe234cecff39969171af0060160"),
+    Felt::from_hex_unchecked("0x0151c9f6e76d622852f7600d979ca9f3b643dfbe9551c83b31542830321b2a53"),
+    Felt::from_hex_unchecked("0x0119469e44229c3c0c4ff555a2b6f6b39961088e741e3c20a3c9b47f130c5548"),
+    Felt::from_hex_unchecked("0x05795e02bbaf90441f384741e5f18f8b644a0080441315d0e5b3c8123452a0a5"),
+    Felt::from_hex_unchecked("0x001e90a515e640499177b4f297f8049ce3d4c3659423c48b3fd64e83596ff0fc"),
+    Felt::from_hex_unchecked("0x024185c60a21e7c170f7d32cacaa2725aa8a844caea7ed760d2b965af1bf3e75"),
+    Felt::from_hex_unchecked("0x016fcbc39606131f887da609187a5dada2e1b829f23309a6375212cea1f25bee"),
+    Felt::from_hex_unchecked("0x05e84026d7c292210af18f7712fc3662f43387ae8cf7fdda1f9a810f4b24bcd3"),
+    Felt::from_hex_unchecked("0x029d568575846a2fb8a890b3c237fd0447426db878e6e25333b8eb9b386195ba"),
+    Felt::from_hex_unchecked("0x05a2aa32c84a4c04196dd4094b685dd11757470a3be094d98eea73f02452aa2c"),
+    Felt::from_hex_unchecked("0x03c9481380978b80ebc5b0a8d4481cd2ef654ee800907adb3d38dc2fd9265f92"),
+    Felt::from_hex_unchecked("0x04e53af71ef06b68b76d3294c11223911e9d177ff09b7009febc484add0beb70"),
+    Felt::from_hex_unchecked("0x03d44e16108223ab6dac3e5fe7acbe9df519bbba97380e5e9437a90658f21378"),
+    Felt::from_hex_unchecked("0x06f434863c78ffa3b2c202331e04bccea2251c1ff6f191dc2afa23e6f6d28e36"),
+    Felt::from_hex_unchecked("0x0490eeb39a733ba88062d87f981ae65a8fccf25c448f4455d27db3915351b060"),
+    Felt::from_hex_unchecked("0x00b89830ff7addcf58a5361a24869130ce1fcce97211602962e34859525dac49"),
+    Felt::from_hex_unchecked("0x01bae21b579d07b575c1694da628d0ecfd83efc9c8468704f410300062f64ca4"),
+    Felt::from_hex_unchecked("0x0326499de0476c959915dd1c661ef4550723d4aee9ee9af224edd208790fce28"),
+    Felt::from_hex_unchecked("0x0445208b8baa6e263821415957088c0b7e72a465f460b09ece2d270aee2f1830"),
+    Felt::from_hex_unchecked("0x062ad454f451328026ce2cc7e7914aef3eb96e8f89a4619a1dc7d11f8401c333"),
+    Felt::from_hex_unchecked("0x0129db368ef2af1c29bca38845325b0b7a820a4889e44b5829bbe1ed47fd4d51"),
+    Felt::from_hex_unchecked("0x06531d424b0cbad7abbf2d2acde698462ea4555bf32ccf1bbd26697e905066f4"),
+    Felt::from_hex_unchecked("0x05c30d247f045df8d05cf0dd0a49c9823e7a24b0d751d3c721353b96f29d76d8"),
+    Felt::from_hex_unchecked("0x06b7a3614056c153c6f171370fdd9d1048bb00b2cdd1b2721d11bdda5023f479"),
+    Felt::from_hex_unchecked("0x06e9c4621642a261f710908707557498d25a6fdd51866da5d9f0d205355a6188"),
+    Felt::from_hex_unchecked("0x00ca1cb1c7f6c58a4d1cf94f327b8763be173151b6b06f99dfc6a944bb5a72e1"),
+    Felt::from_hex_unchecked("0x0524d0b1b304cf9811ce0f274b0d93746a4860ed5cdd8d4348de557ea7a5ee6f"),
+    Felt::from_hex_unchecked("0x07423dabd1a3ccee8691438fc5891e3fd49ac0f3e21aaf249791bfde1303d2e5"),
+    Felt::from_hex_unchecked("0x0642e8800a48cc04c0168232c6f542396597a67cf395ad622d947e98bb68697a"),
+    Felt::from_hex_unchecked("0x01e7d3cbbc4c341f490647d8402e56d334336943bda91fe2d34ca9727c0e3ddd"),
+    Felt::from_hex_unchecked("0x056fb17303351f2e38f85e408ac861e76f24349ab6ee0469c22e19350bb24fd0"),
+    Felt::from_hex_unchecked("0x07d0faf5f0db31d5b60e13dc4914246b9edac7990fb4990b19aa86815586440e"),
+    Felt::from_hex_unchecked("0x0605b9b909ded16c4971eae979027c4e0de57f3b6a60d5ed58aba619c34749ca"),
+    Felt::from_hex_unchecked("0x0276890b2c205bfe5f000d1f5111ed8f177e279cae3e52862780f04e846228b6"),
+    Felt::from_hex_unchecked("0x02c5905f9450a1c9f6905ed5951a91b3730e3a2e2d62b50bdeb810015d503766"),
+    Felt::from_hex_unchecked("0x02366839f0291ba64da674ac3f0e1e9aa8b687ba533926cb40268039e57b966b"),
+    Felt::from_hex_unchecked("0x07ab0f3466989b71bbe209c37ec272ba83984ba6e445be6d472b63e3ca7270d7"),
+    Felt::from_hex_unchecked("0x06786007d0ce7e17a90e31d3263887d40c556dec88fcb8b56bc9e9c05ecc0c28"),
+    Felt::from_hex_unchecked("0x03814ed99bd00eb9389b0022663dbfddfbfa15e321c19abcf1eaf9556075fb67"),
+    Felt::from_hex_unchecked("0x05c0321ba26fce18fdc35b4999b78ceb54dcaf9fec2e3bdea98e9f82925c0926"),
+    Felt::from_hex_unchecked("0x032d2a929601f85e520e0b14aaa6ba9f1e79821a5b768919670a4ea970722bdf"),
+    Felt::from_hex_unchecked("0x05d2e0744d4aefff1918de69ec12128a5871367303ff83ed764771cbbdf6500a"),
+    Felt::from_hex_unchecked("0x04527d0c0868f1fe628086b874fa66a7347d3d3b918d2e07a5f33e1067e8ac4a"),
+    Felt::from_hex_unchecked("0x046bf6ac0314cab7d23e357bfcbbaa17d670672ae3a475f80934c716f10aca22"),
+    Felt::from_hex_unchecked("0x044007e286f8dc07fd5d0eeb0e95d7aa6589361d128a0cccb17b554c851a642b"),
+    Felt::from_hex_unchecked("0x06468a86a5a7da17763a053eb09ac1a02809ce095258c88101ee319e12b06969"),
+    Felt::from_hex_unchecked("0x0333e3d052b7c64dcac1eb366f610f6f97852242b1317a87b80f3bbc5c8c2d0b"),
+    Felt::from_hex_unchecked("0x02ec1d675cf5348753f6b628414783ca6b7fc0fe01948ca206daad712296e38b"),
+    Felt::from_hex_unchecked("0x03ceeeb301572b2791076750e11ea7e7fcbfee454d90dc1763989004a1894f91"),
+    Felt::from_hex_unchecked("0x0505737e7e94928a08d8cda10b6fbbbf879b2141ae7eabc30fcd22405135fe54"),
+    Felt::from_hex_unchecked("0x0127db7ac52009552092b66ec2bfc63653f4dc8ac66c76008fef885258a258a9"),
+    Felt::from_hex_unchecked("0x02692a7d808f44c11d628dbcfea377eb073fb918d7beb8136ea47f8cf094c88a"),
+    Felt::from_hex_unchecked("0x060e384b1268e35f47c91d6987fd280fa0a275541a7c5be34bf126af35c962dc"),
+    Felt::from_hex_unchecked("0x008c3b01966d8f1c13aee8d482ceaa6925311d2342e1a5aca4fcd2f44b6dadc1"),
+    Felt::from_hex_unchecked("0x007e868affd919808a87fa75ac9332a6cf23587d94e20c3262db5e91f30bf034"),
+    Felt::from_hex_unchecked("0x05ba5f8acad1a7daa3bbf2201055cd3ea27056c0c53f0c4c97f33cda8dbfe8f3"),
+    Felt::from_hex_unchecked("0x01ca814b49e00cc03118c53a2986ded128584acd7428735e08ade6661c457f6a"),
+    Felt::from_hex_unchecked("0x07c4c0bea813a212fd510c07f7bbe337badd4bcf28649a0d378970c2a15b3aa4"),
+    Felt::from_hex_unchecked("0x0053f1ea6dd60e7a6db09a00be77549ff3d4ee3737be7fb42052ae1321f667c3"),
+    Felt::from_hex_unchecked("0x03937077bb10c71138716d4e38edc1f9e7b18c6414fef85fe7e9c5567baa49e7"),
+    Felt::from_hex_unchecked("0x02cb14c0f1508bfb8f7fd048d716b8044aec7f0fb48e85e717bf532db97251f0"),
+    Felt::from_hex_unchecked("0x04a0abb8beb7cf5c72a0c1e6f58e080e1bb243d497a3e74538442a4555ad40b5"),
+    Felt::from_hex_unchecked("0x029eefd411e58f0ce44592cce298af87b2c62aa3cc8bb137aa99ca8d4aa5519a"),
+    Felt::from_hex_unchecked("0x053dae43cef763c5a2fc9846f09a2973b0ad9c35894c220699bcc2954501c6bb"),
+    Felt::from_hex_unchecked("0x04ed2a09375811fafb504c7a9ba13110bdd8549a47349db82c15a434c090e861"),
+    Felt::from_hex_unchecked("0x0063a5c4c9c12dcf4bae72c69f3a225664469503d61d9eae5d9553bfb006095b"),
+    Felt::from_hex_unchecked("0x048a4d35ad28e3d2d3713b45985cd3b70e37ccc2be42086f1ea078fe2dc9d812"),
+    Felt::from_hex_unchecked("0x006ba219308f0beb7b22fcb4449f8855192536c01b8057904e81c1c7814f4832"),
+    Felt::from_hex_unchecked("0x04d9604140a1ac39db204285b9fe1b303c281af2fc5fb362f6577282b423bced"),
+    Felt::from_hex_unchecked("0x01681959ec4bc1cd6911db2b2f56aa4db709c26f1a0a25c879286e37f4374645"),
+    Felt::from_hex_unchecked("0x04d849f3b5f9e2278af75619fb27f2e335adbb9b44988f17c4d389fa751ad45b"),
+    Felt::from_hex_unchecked("0x05f7fc22ad64c6e9c1e005110e13f4f1c6b1f8f8cc59000db0e3bb38f9955487"),
+    Felt::from_hex_unchecked("0x01133b8a20fbace133ec5f82cb47a38ae1877d12d1febb23982c7c808aa53160"),
+    Felt::from_hex_unchecked("0x04827c5c7b61121ec31b75984bb3ed16ed579e5b72e32a1289b63ab55eaf8bf4"),
+    Felt::from_hex_unchecked("0x04a361819ffefc9550fe34c91a322c9405f4e5a168c1fc0a0a1883993e32c9db"),
+    Felt::from_hex_unchecked("0x0656088842bfc91725a532784d3362cecfa86f9c7b208a6b499836ebe48ff14b"),
+    Felt::from_hex_unchecked("0x00129c7cd00e42ed05a37dbceb80d47b65e1d750ef2148278a54723fdf42c4cc"),
+    Felt::from_hex_unchecked("0x005b235631b785935cd46f7768f6c71ae004ad267ae59bdf929ada149b195873"),
+    Felt::from_hex_unchecked("0x04df65a82686bda3c5b237911abf237a9887c1a418f279ac79b446d7d311f5e4"),
+    Felt::from_hex_unchecked("0x015a850c3989de8ca6231e0bdd9916fc0e076f2c6c7f0f260a846d0179f9c31d"),
+    Felt::from_hex_unchecked("0x00fb0940848a6704e83d348421fadd79aefc7a2adabeec6e64904ebe1bf63e73"),
+    Felt::from_hex_unchecked("0x02b63a1627359871b66895461e62a19ff0d103693be771d93e3691bba89cdd76"),
+    Felt::from_hex_unchecked("0x0131a091756e0aea09ebecfffba5038634c5b3d5d0c5876dd72aac67452db895"),
+    Felt::from_hex_unchecked("0x05559b8bb79db7d69c46ee627f1b5ce1d8e6d89bf94a9987a1407759d1ba8959"),
+    Felt::from_hex_unchecked("0x01a1a11b29790027b155914d09f1df19b7ffec241e8b2487b6f6272a56a449f5"),
+    Felt::from_hex_unchecked("0x003293400e7bcadb4bb86dcb0d5ca57fa2466e13a572d7d3531c6fa491cb0efc"),
+    Felt::from_hex_unchecked("0x07cb5742b6bc51c3624d3568a33c21f31b877f8396972582028da999abf249dc"),
+    Felt::from_hex_unchecked("0x056efb400f84feb7c5bf811c65c86c7ed2e965f14f1a69bca436c0c60b79f447"),
+    Felt::from_hex_unchecked("0x07c4427998d9c286f849dcd75b7157996eaad1b9a1d58cc2441931300e26eb08"),
+    Felt::from_hex_unchecked("0x025ed18ad53e3254c3ae8cf353ff3f6dd315f60060442b74f6b614b24ebd4caa"),
+    Felt::from_hex_unchecked("0x02d3e9376c97b051a0fbf43e22a3616981d777365c765ead09a1d033fdf536a4"),
+    Felt::from_hex_unchecked("0x06daeff5769a051a6fe3b8fef30df07b1387373a7814cef364fe1d6059eaf532"),
+    Felt::from_hex_unchecked("0x020a78398345c520cf439643dab96223bf879c302648293eaf496fee5c978c4e"),
+    Felt::from_hex_unchecked("0x009ca65b6cf0e84b53c06dddc057dc61ba2839974569051c98b43e8618716ef0"),
+    Felt::from_hex_unchecked("0x03064161f127d7b59fc73625957e21630dc6dc99e5443f6ce37ecd6bf28e69a7"),
+    Felt::from_hex_unchecked("0x06d0ba662b500f839a3af52052f68932feec1d12290b2033c4f49148893d8b9b"),
+    Felt::from_hex_unchecked("0x00dd55b4a83a53bfee578eb3e6d26f594824d44670fc3f4de80642344d15c097"),
+    Felt::from_hex_unchecked("0x07b5b594f48bc448345ab90ded705920a7274b8e070eee8ce8cf90c72c3604a3"),
+    Felt::from_hex_unchecked("0x0101d8f4f2c8445e28e00663978f2050f2eb1cd6acb60d9d09c57c5d46ee54fb"),
+    Felt::from_hex_unchecked("0x06c56789beab24347ee32f594d5fc561ec59dfeb93606dc7dcc6fe65133a7da9"),
+    Felt::from_hex_unchecked("0x01c0b2cbe4fa9877a3d08eb67c510e8630da0a8beda94a6d9283e6f70d268bc5"),
+    Felt::from_hex_unchecked("0x031d85acd9031a8007350eed946a25734e974799c5ba7cff13b15a5a623a25ef"),
+    Felt::from_hex_unchecked("0x004497d1d35954e505a2fe655f3d6f94926ea92d12cdaa6556ec26362f239f60"),
+    Felt::from_hex_unchecked("0x0075f7edc66318b17ffe33019f44fc91f286236d5a5f90f16de4791b72a2a5d4"),
+    Felt::from_hex_unchecked("0x043f46e353354212ab8fe0ca4e9230dfc330bc163e602dfeaf307c1d1a7264b5"),
+    Felt::from_hex_unchecked("0x0448ae5e09625de7fcfd732fc9cd8f06e4c33b81f0a9240c83da56f41e9eccd1"),
+    Felt::from_hex_unchecked("0x07312eef69a33d4aa753c08840275692a03432b3e6da67f9c59b9f9f4971cd51"),
+    Felt::from_hex_unchecked("0x07333996af231b1aa293137da91801e191a6f24eb532ad1a7e6e9a2ad0efbbf5"),
+    Felt::from_hex_unchecked("0x00f771e0383a81c8c8e2eaa8efabda300947acaf0684fabddf8b4abb0abd8a4d"),
+    Felt::from_hex_unchecked("0x07f0b3d7a4643453f651b70c1963cc4fa6c46018d78f05cb2c5f187e25df8396"),
+    Felt::from_hex_unchecked("0x04373b704838311348273734dcdf962d7c156f431f70380ba4855832c4a238a5"),
+    Felt::from_hex_unchecked("0x022afa02604b8911eb570f48a0e97a5e6bfe9613394b9a6b0026ecd6cec8c31d"),
+    Felt::from_hex_unchecked("0x00892258cd8eb35e71caa6d6837ec9959bfdfd72f25c9005ebaffc4011f8a7b2"),
+    Felt::from_hex_unchecked("0x02824f561f6f80e5c1232836b0d268fa3b1b5489edd39a5fe1503bfc7ca91f2b"),
+    Felt::from_hex_unchecked("0x064eda75fda285fd9d812f24e37ac938844fbe383c243b32b9f66ae2e76be717"),
+    Felt::from_hex_unchecked("0x00a6fc431f5beedf1cca93b8b65b3f72c91f0693e2c74be9243b15abb31afca2"),
+    Felt::from_hex_unchecked("0x068db66ba891ed30d527f09ec6fff3ec0a269cf3d891a35ec13c902f70334b33"),
+    Felt::from_hex_unchecked("0x0244a5b102f787c32b8630a3cae6e6db2e6e483bb7cfeb3492cbd91793ef5987"),
+    Felt::from_hex_unchecked("0x03939fe8ef789a4333cbf129ba8a3aa1bd61510a178022a05177c9c5a1c59be9"),
+    Felt::from_hex_unchecked("0x036fe3b66dfda08a5a7aae241b4db442858bd720c1d579c0c869f273cd55d762"),
+    Felt::from_hex_unchecked("0x0490fcaa8ffa378d5dc67ae006e81352c7103945417b8e4b142afcaefa344b7f"),
+    Felt::from_hex_unchecked("0x02e66096cff34321ca53ffe0e58aafeb468bd174f00d8abc425b2099c088185b"),
+    Felt::from_hex_unchecked("0x07d05783a41bbfb73c9a45384b6d5e2547c5b6a224c8316910b208f2718a7093"),
+    Felt::from_hex_unchecked("0x02c6b9ba94040c9b92b865b6677b60ef3201b5c2121699f70beb9f9b2528a01b"),
+    Felt::from_hex_unchecked("0x0102a3d4d9ecbe54b2c76fddf780554bf93cad97b244e805d3adb94e181628eb"),
+    Felt::from_hex_unchecked("0x01df91ffeeb084b7d26041c29dac6fca1d56a4d022fe34b38831267395b98d0a"),
+    Felt::from_hex_unchecked("0x062646f851d9197840ad9ee711f12ec13b3e8f980ff5ef5ee43ca4520d57dee7"),
+    Felt::from_hex_unchecked("0x00b7381c9725b97507816baf8524943a79cea135807c84cce0833485c11e0c28"),
+    Felt::from_hex_unchecked("0x06afc10c5cedaca9da99df79387397c9be74a5b50f3a0c04ccb68d4e0f3a988d"),
+    Felt::from_hex_unchecked("0x0543da80d10da1ebc548776fe907c4ef89993d62e0062ae5c0496fcb851c365b"),
+    Felt::from_hex_unchecked("0x05140fe26d8afea830fccd50a68e3e11c1163d63b6d8b7cc40bc6f3c1d0b1aea"),
+    Felt::from_hex_unchecked("0x06fdf1872e4473d9bbb0ef6fab7f561bff121314695c433bd4c29ec118060c77"),
+    Felt::from_hex_unchecked("0x03b8c9f3d57b180302df059db1e6a5d42ad566f153f18460774f68ac265093f3"),
+    Felt::from_hex_unchecked("0x0415122d50b26ea5ab5784004c56cf03f128f825ad2236f4b3d51f74737bd969"),
+    Felt::from_hex_unchecked("0x00e115c4a98efae6a3a5ecc873b0cef63ccd5b515710a3ab03ec52218f784dc9"),
+    Felt::from_hex_unchecked("0x027d525427bad6b088238657c21331245578bc76aa6240b7f972382537a20290"),
+    Felt::from_hex_unchecked("0x03332e8b34505a73010270dc795290a2f515b8f89c163acecdf4799df04c62e8"),
+    Felt::from_hex_unchecked("0x009ecb6033d19eeff17a61066cd737d0c3c5873b51c3ab0a285e26939e62aa02"),
+    Felt::from_hex_unchecked("0x04e65c718938c27537378e7435332174329730bde85a4185e37875824eb49855"),
+    Felt::from_hex_unchecked("0x00e41430ccd41be8e92a9f9acd2e955c1385b9f5ed8d3f133d767429484a8ead"),
+    Felt::from_hex_unchecked("0x0038fe9d0125a9f3e54545276f841274e414c596ed4c9eaa6919604603d1ff91"),
+    Felt::from_hex_unchecked("0x03248698612cd8a43234fcf5db9b6b225f4b0ba78d72ef13ea1edff5f0fb0294"),
+    Felt::from_hex_unchecked("0x02a9fa3d39c1b8d7eefa666a1db71c6e0e4e3b707626b0197a4e59e7110cf0ba"),
+    Felt::from_hex_unchecked("0x028931ee7dfa011e2872e0d937ba3dc5c637118273a1f1f0c4fc880905c82ee4"),
+    Felt::from_hex_unchecked("0x01cd399556445e3d7b201d6c5e56a5794e60be2cfd9a4643e7ead79bb4f60f79"),
+    Felt::from_hex_unchecked("0x04855cc58d5fb9a8ff91a79683eb0e914c1b7d8d0a540d416838a89f83a8311a"),
+    Felt::from_hex_unchecked("0x07798af7ccf369856705849f7dd40328bf9346657255b431446ec75a681717f8"),
+    Felt::from_hex_unchecked("0x052a24c92d3f049ff551eeaf98c62ba525e84882d7adad835fad8de72986b295"),
+    Felt::from_hex_unchecked("0x07c8682759a2bd0ed67c87a77c285467801f1c44fd78fa4eb5957a4832c9d70e"),
+    Felt::from_hex_unchecked("0x0482ac3e7e4f31f427850d95a13942aea6d2923402b913046856ff7e8aaf9afd"),
+    Felt::from_hex_unchecked("0x07332b4c7aac29e5ccfe954de7ad22ccf6fcb4c5fa15c130ed22a40ae9398f45"),
+    Felt::from_hex_unchecked("0x04be0546013f82e6d1e118b37589723b58e323983263616d1b036f8b3fdd8569"),
+    Felt::from_hex_unchecked("0x064ec737d31ddca539b184438ccdd3e1d3e667572857cd6c9c31a0d1d9b7b071"),
+    Felt::from_hex_unchecked("0x02d12fbc74117bde4743d674539c86548c6758710a07a6abe3715e4b53526d23"),
+    Felt::from_hex_unchecked("0x05a16435a2300b05a337561401f06682ba85019aa0af61b264a1177d38b5c13a"),
+    Felt::from_hex_unchecked("0x02616f306e7634de93a22ab6ee15509d9b108d4136b32fa7f9ed259793f3929d"),
+    Felt::from_hex_unchecked("0x019727b25560ca460ce0d3f911bd4356f907160ab5186da10a629c7ccae18514"),
+    Felt::from_hex_unchecked("0x07f39e77928ce7880118d50e29bc87e7f78b53ad51366359aa17f07902ae6379"),
+    Felt::from_hex_unchecked("0x07dead3bfa1968a544118023dead77cdbee22c5b7c2414f5a6bdf82fd94cf3ab"),
+    Felt::from_hex_unchecked("0x03ef0f8b22a1cf640100f4a552a9d02b772130123de8144a00c4d57497e1d7ef"),
+    Felt::from_hex_unchecked("0x075188713fef8f2c1c35243f92cfa4331ab076e30e24b355c79b01f41d1529fa"),
+    Felt::from_hex_unchecked("0x03aadd2fd92e3d9bfb371be0578941dc0a108fbca0a7d81b88316fb94d6b4df7"),
+    Felt::from_hex_unchecked("0x04f955742e20a0d338611bf9fc4a478c97b673a7cd40d0113a58a1efe338d990"),
+    Felt::from_hex_unchecked("0x041c3fe9a5f7cc5e4ad5a51a224b3f94775266d19c3733017e4920d7391ad63e"),
+    Felt::from_hex_unchecked("0x0372df6148abee0a6fda5461779a9651130c6c525df733852bcd929016768a6e"),
+    Felt::from_hex_unchecked("0x05098e848fb8531c5adb5a6364b5ab33c79fb08877f2cf3e0e160d9fcb3ebcb8"),
+    Felt::from_hex_unchecked("0x00c5fc90f2743161bfe496dfba14bb0dba71141eb5472a365fd13023f4fe628d"),
+    Felt::from_hex_unchecked("0x03988dfc0c4dfccc999bd34840adcb63fdbf501ccd622ca2ddf5064ad8cdebdd"),
+    Felt::from_hex_unchecked("0x05b068c942724bfe4ed5851c9575c22752c9bd25f91ebfa589de3d88ee7627f5"),
+    Felt::from_hex_unchecked("0x0598a1931e3618f0218de11ff7879bd7114cda19c24ddbe15b3b0190ce01e18d"),
+    Felt::from_hex_unchecked("0x000b5a7d63f6c28c42ad612023d3ffd6c684ce2eab837180addcb4decf51852b"),
+    Felt::from_hex_unchecked("0x02ef24bf47c51e5518c6ff96657dd3c6fdff7212d5c798d826455de77b4b70b1"),
+    Felt::from_hex_unchecked("0x007da812fd5a8243587e4860f87691d0a8d61d454c507d09e5562e1a5d0fcc64"),
+    Felt::from_hex_unchecked("0x0459abbc62bc5ed8cacdff597e97990de56edc51cc6643afb0f6789fef1bad4b"),
+    Felt::from_hex_unchecked("0x00d61f5e566855600d36ef0f0f1fefcd7c829bdd60d95e0ef1fb5b988562809d"),
+    Felt::from_hex_unchecked("0x03218626665c41eb3aa2b0fa49224a3dce8e08b8b56f8851bd9cb5e25cb3042b"),
+    Felt::from_hex_unchecked("0x07685fb152dba13e4d02422e237e246df73d7d711ae6d7d33983bae0f873e303"),
+    Felt::from_hex_unchecked("0x02de34719e249822e70e4571c40474475a4af706a3cb82ac18a7fa44c22d1c3c"),
+    Felt::from_hex_unchecked("0x020c3dc7a496aca9059cb95d9b173812a00f3c4d435e0b9e8116e0c4b5f56aba"),
+    Felt::from_hex_unchecked("0x016bc98252f63136ed79073ee091a0e8ed0b5af51017da143940c00bdb863706"),
+    Felt::from_hex_unchecked("0x0179bf70695d922defb552a413701918afec9e12dfe213f4d0c27cfa68fad6a7"),
+    Felt::from_hex_unchecked("0x0003072d02f54b9c7a3c6c4cc18eda6dce87a03c6819df54e4ed8aed6dc56d2f"),
+    Felt::from_hex_unchecked("0x06fcda9d986cdd9c431af4d59c6a7709d650885b7886cba70f0e7cd92b331cd9"),
+    Felt::from_hex_unchecked("0x03ca5f7859b8290b0b63da06d43aa68a6b685f0a60397638bbea173b3f604178"),
+    Felt::from_hex_unchecked("0x059d392c06673016d37a06be2d51aabe9e79bdef0013bc109985648a14c7e40b"),
+    Felt::from_hex_unchecked("0x042f5f0d214677b6396e2bed6cf15a20bc22cc4c8cf7dd4b3514ac00148dd092"),
+    Felt::from_hex_unchecked("0x07a993a6af068d50bc36f0e814d29fef3f97d7a72aa963889b16a84574098618"),
+    Felt::from_hex_unchecked("0x071bf99686550d2696f7f4e2df6fdaa090fbc272c8c76eb32a3c6791de5a07a8"),
+    Felt::from_hex_unchecked("0x0234d705e1ecdb49cc6ed40749069d4b45e63deb49b5b7d7f527abd31c072b0b"),
+    Felt::from_hex_unchecked("0x07e929a1fd6aabdd5c4012c45dd727d2c816119567450003913d882cb97bc471"),
+    Felt::from_hex_unchecked("0x055371215f2ab8e4026b2e48739c11b4d8ffbb24dd4a6e41b9763862af967865"),
+    Felt::from_hex_unchecked("0x00e704566c49dfe71edc2c128b2e07f36dc0c755468268f8fe4c4859b9fa5941"),
+    Felt::from_hex_unchecked("0x063e1195090d007a1d8fb37de17ccf3b66d180645efa0d831865cfaa8797769a"),
+    Felt::from_hex_unchecked("0x065c090eebde2b1e7f9c92cf75641c7683fb8e81f4a48f5b7a9c7eb26a850283"),
+    Felt::from_hex_unchecked("0x018971781c6854a2a9752912780bb9b719c14a677a4c6393d62d6e046b97a298"),
+    Felt::from_hex_unchecked("0x06fc1ef1bca8bcc255cc66edecc5dc99030fe78311a3f21d8cd624df4f89e607"),
+    Felt::from_hex_unchecked("0x024e4e2838501406d3296542cb47a59a1ca4326e947c9c874d88dccc8e37b98a"),
+    Felt::from_hex_unchecked("0x04d5a9e7353a506d54c9c1381b556b543897cc89153c3e3749f2021d8237225c"),
+    Felt::from_hex_unchecked("0x04bcedbd54d0c7a1a315cc7ca785e3c5995abbeeb3deb3ebaf02c7a9bf6cc829"),
+    Felt::from_hex_unchecked("0x077476211105b2d09cef009c51155ae93526c53a74973ecfce40754b3df1051e"),
+    Felt::from_hex_unchecked("0x00aefbd978440bd9b4b9fbd36e00e6e36caeacf82b0da0a6161d34c541a5a6d8"),
+    Felt::from_hex_unchecked("0x022cd6d61be77f0b3c77677bc6ba40307b597ed981db57cb485313eec2a5a47f"),
+    Felt::from_hex_unchecked("0x01ffc4fe0dc5f66ac8dcdc1e60b8f0b1637f32a809175371b94a057272b07472"),
+    Felt::from_hex_unchecked("0x06a5268541bc4a66ad0ade8f55dda3492604857a71c923662a214dd7e9c20bf2"),
+    Felt::from_hex_unchecked("0x0000000000000000000000000000000000000000000000000000000000000000"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sha3::{Digest, Keccak256};
+
+    #[test]
+    fn test_round_constants_follow_circomlib() {
+        let mut hash: [u8; 32] = Keccak256::digest(b"mimcsponge").into();
+        for constant in &ROUND_CONSTANTS[1..ROUNDS - 1] {
+            hash = Keccak256::digest(hash).into();
+            assert_eq!(Felt::from_bytes_be(&hash), *constant);
+        }
+        assert_eq!(ROUND_CONSTANTS[0], Felt::ZERO);
+        assert_eq!(ROUND_CONSTANTS[ROUNDS - 1], Felt::ZERO);
+    }
+
+    #[test]
+    fn test_mimc_hash() {
+        // circomlib's multiHash with the Stark prime
+        let expected = [
+            "0x56c865b1e80316f005e938bb1cc3a5ad587b4254983be4be556145673c302b7",
+            "0x36b641ca59ce327d43eeb98c1021fc4e216efcbd9d12d2d5ae74358ae390720",
+            "0x526ab3b5aad2bc912db245fa399229ef83f1b195f6a9ef5b556177671a41878",
+        ];
+        let mut out = [0u8; 32];
+        for (i, expected) in expected.iter().enumerate() {
+            let inputs: Vec<FeltBytes> = (1..=i as u64 + 1)
+                .map(|n| felt_to_bytes(&Felt::from(n)))
+                .collect();
+            unsafe {
+                assert_eq!(
+                    mimc_hash(inputs.as_ptr(), inputs.len(), &mut out),
+                    StarkResult::Success
+                );
+            }
+            assert_eq!(out, felt_to_bytes(&Felt::from_hex(expected).unwrap()));
+        }
+        unsafe {
+            assert_eq!(
+                mimc_hash(core::ptr::null(), 0, &mut out),
+                StarkResult::InvalidInput
+            );
+        }
+    }
+}
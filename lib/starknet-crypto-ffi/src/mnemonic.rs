@@ -0,0 +1,193 @@
+//! BIP-39 mnemonic phrases (English wordlist)
+//!
+//! Phrases cross the FFI as UTF-8 bytes with an explicit length, words
+//! separated by single spaces and no trailing NUL. Input phrases and
+//! passphrases are NFKD-normalized before use, as BIP-39 requires.
+//!
+//! The 64-byte seed from `bip39_to_seed` is the input to
+//! `stark_derive_eip2645` and to BIP-32 derivation.
+
+use alloc::format;
+use alloc::string::ToString;
+use core::str;
+
+use bip39::Mnemonic;
+
+use crate::{
+    bytes_from_raw,
+    error::{invalid_input, null_pointer},
+    ffi_guard, random, write_bytes_out, StarkResult,
+};
+
+/// Longest English phrase: 24 words of at most 8 letters, 23 spaces
+pub const BIP39_PHRASE_MAX_LEN: usize = 24 * 8 + 23;
+
+unsafe fn str_from_raw<'a>(ptr: *const u8, len: usize, name: &str) -> Result<&'a str, StarkResult> {
+    let bytes = bytes_from_raw(ptr, len).ok_or_else(|| null_pointer(name))?;
+    str::from_utf8(bytes).map_err(|_| invalid_input(format!("`{name}` is not valid UTF-8")))
+}
+
+unsafe fn parse_phrase(phrase: *const u8, len: usize) -> Result<Mnemonic, StarkResult> {
+    Mnemonic::parse(str_from_raw(phrase, len, "phrase")?)
+        .map_err(|e| invalid_input(format!("`phrase`: {e}")))
+}
+
+/// Generate a random mnemonic phrase
+///
+/// `entropy_bits` is 128, 160, 192, 224 or 256 (12 to 24 words). `out_len`
+/// holds the capacity of `out_phrase` on input and the phrase length on
+/// output; if the buffer is too small, the required length is written and
+/// `BufferTooSmall` is returned. `BIP39_PHRASE_MAX_LEN` bytes always suffice.
+/// Returns `RngFailed` if the OS random number generator is unavailable.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bip39_generate")]
+pub unsafe extern "C" fn bip39_generate(
+    entropy_bits: u32,
+    out_phrase: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_len);
+        if !matches!(entropy_bits, 128 | 160 | 192 | 224 | 256) {
+            return StarkResult::InvalidInput;
+        }
+        let mut entropy = [0u8; 32];
+        let entropy = &mut entropy[..entropy_bits as usize / 8];
+        if random::fill_random(entropy).is_err() {
+            return StarkResult::RngFailed;
+        }
+        let phrase = match Mnemonic::from_entropy(entropy) {
+            Ok(m) => m.to_string(),
+            Err(_) => return StarkResult::InvalidInput,
+        };
+
+        write_bytes_out(phrase.as_bytes(), out_phrase, out_len)
+    })
+}
+
+/// Check that a phrase has a valid word count, only wordlist words and a
+/// matching checksum
+///
+/// Returns `Success` or `InvalidInput` (`NullPointer` for a null phrase
+/// with a non-zero length).
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bip39_validate")]
+pub unsafe extern "C" fn bip39_validate(phrase: *const u8, len: usize) -> StarkResult {
+    ffi_guard(|| match parse_phrase(phrase, len) {
+        Ok(_) => StarkResult::Success,
+        Err(e) => e,
+    })
+}
+
+/// Derive the 64-byte seed from a phrase and optional passphrase
+///
+/// The seed is PBKDF2-HMAC-SHA512 (2048 rounds) of the phrase, salted with
+/// "mnemonic" || passphrase. `passphrase` may be null when `passphrase_len`
+/// is 0. The phrase must pass `bip39_validate`, otherwise `InvalidInput` is
+/// returned.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_bip39_to_seed")]
+pub unsafe extern "C" fn bip39_to_seed(
+    phrase: *const u8,
+    phrase_len: usize,
+    passphrase: *const u8,
+    passphrase_len: usize,
+    out: *mut [u8; 64],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out);
+        let mnemonic = match parse_phrase(phrase, phrase_len) {
+            Ok(m) => m,
+            Err(e) => return e,
+        };
+        let passphrase = match str_from_raw(passphrase, passphrase_len, "passphrase") {
+            Ok(p) => p,
+            Err(e) => return e,
+        };
+
+        *out = mnemonic.to_seed(passphrase);
+        StarkResult::Success
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex<const N: usize>(s: &str) -> [u8; N] {
+        let mut out = [0u8; N];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    // BIP-39 English test vector (entropy 00 * 16, passphrase "TREZOR")
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    const SEED: &str = "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04";
+
+    #[test]
+    fn test_bip39_to_seed_vector() {
+        let passphrase = b"TREZOR";
+        let mut seed = [0u8; 64];
+
+        unsafe {
+            let result = bip39_to_seed(
+                PHRASE.as_ptr(),
+                PHRASE.len(),
+                passphrase.as_ptr(),
+                passphrase.len(),
+                &mut seed,
+            );
+            assert_eq!(result, StarkResult::Success);
+        }
+        assert_eq!(seed, hex::<64>(SEED));
+    }
+
+    #[test]
+    fn test_bip39_validate() {
+        unsafe {
+            assert_eq!(
+                bip39_validate(PHRASE.as_ptr(), PHRASE.len()),
+                StarkResult::Success
+            );
+
+            // Wrong checksum word
+            let bad = PHRASE.replace("about", "abandon");
+            assert_eq!(
+                bip39_validate(bad.as_ptr(), bad.len()),
+                StarkResult::InvalidInput
+            );
+
+            let unknown = PHRASE.replace("about", "starknet");
+            assert_eq!(
+                bip39_validate(unknown.as_ptr(), unknown.len()),
+                StarkResult::InvalidInput
+            );
+        }
+    }
+
+    #[test]
+    fn test_bip39_generate() {
+        let mut phrase = [0u8; BIP39_PHRASE_MAX_LEN];
+
+        unsafe {
+            for (bits, words) in [(128, 12), (256, 24)] {
+                let mut len = phrase.len();
+                let result = bip39_generate(bits, phrase.as_mut_ptr(), &mut len);
+                assert_eq!(result, StarkResult::Success);
+                assert_eq!(bip39_validate(phrase.as_ptr(), len), StarkResult::Success);
+                assert_eq!(phrase[..len].split(|&b| b == b' ').count(), words);
+            }
+
+            let mut len = 4;
+            let result = bip39_generate(128, phrase.as_mut_ptr(), &mut len);
+            assert_eq!(result, StarkResult::BufferTooSmall);
+            assert!(len > 4);
+
+            let mut len = phrase.len();
+            let result = bip39_generate(100, phrase.as_mut_ptr(), &mut len);
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+}
@@ -0,0 +1,376 @@
+//! Signature calldata for k-of-n multisig accounts
+//!
+//! A multisig account takes the signatures of several Stark-curve signers in
+//! one transaction signature, in a layout its `__validate__` parses and
+//! checks strictly: signers in the wrong order, or a missing length prefix,
+//! make the transaction fail validation. The layouts supported:
+//!
+//! - `MULTISIG_LAYOUT_ARGENT` (Argent multisig 0.2 and later): a serialized
+//!   `Array<SignerSignature>`, `[count, (0, public_key, r, s)...]` (0 is the
+//!   `Starknet` signer variant), sorted by ascending signer GUID
+//!   `poseidon('Starknet Signer', public_key)`;
+//! - `MULTISIG_LAYOUT_ARGENT_LEGACY` (Argent multisig 0.1):
+//!   `[(public_key, r, s)...]` with no prefix, sorted by ascending public key.
+//!
+//! Encoding sorts the signatures as the layout requires; decoding checks the
+//! order, so a payload that decodes is one the account accepts the shape of.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use starknet_crypto::{poseidon_hash, Felt};
+
+use crate::error::{invalid_input, null_pointer};
+use crate::{felts_from_raw, ffi_guard, write_felts_out, FeltBytes, StarkResult};
+
+/// Argent multisig 0.2+: `[count, (0, public_key, r, s)...]`, by signer GUID
+pub const MULTISIG_LAYOUT_ARGENT: u32 = 0;
+/// Argent multisig 0.1: `[(public_key, r, s)...]`, by public key
+pub const MULTISIG_LAYOUT_ARGENT_LEGACY: u32 = 1;
+
+/// `SignerSignature::Starknet` variant index
+const STARKNET_SIGNER_VARIANT: u64 = 0;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    Argent,
+    ArgentLegacy,
+}
+
+impl Layout {
+    fn from_u32(layout: u32) -> Result<Self, StarkResult> {
+        match layout {
+            MULTISIG_LAYOUT_ARGENT => Ok(Self::Argent),
+            MULTISIG_LAYOUT_ARGENT_LEGACY => Ok(Self::ArgentLegacy),
+            _ => Err(invalid_input(format!("unknown `layout` {layout}"))),
+        }
+    }
+
+    /// Felts per signature
+    fn stride(self) -> usize {
+        match self {
+            Self::Argent => 4,
+            Self::ArgentLegacy => 3,
+        }
+    }
+
+    /// Key the account expects signatures sorted by (strictly ascending)
+    fn sort_key(self, public_key: &Felt) -> Felt {
+        match self {
+            Self::Argent => {
+                poseidon_hash(Felt::from_bytes_be_slice(b"Starknet Signer"), *public_key)
+            }
+            Self::ArgentLegacy => *public_key,
+        }
+    }
+}
+
+/// `(public_key, r, s)`
+type Signature = (Felt, Felt, Felt);
+
+fn encode(layout: Layout, mut signatures: Vec<Signature>) -> Result<Vec<Felt>, StarkResult> {
+    signatures.sort_by_key(|(public_key, _, _)| layout.sort_key(public_key));
+    if signatures.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+        return Err(invalid_input("a signer appears twice"));
+    }
+    let mut felts = Vec::with_capacity(1 + layout.stride() * signatures.len());
+    if layout == Layout::Argent {
+        felts.push(Felt::from(signatures.len()));
+    }
+    for (public_key, r, s) in signatures {
+        if layout == Layout::Argent {
+            felts.push(Felt::from(STARKNET_SIGNER_VARIANT));
+        }
+        felts.extend([public_key, r, s]);
+    }
+    Ok(felts)
+}
+
+fn decode(layout: Layout, felts: &[Felt]) -> Result<Vec<Signature>, String> {
+    let body = match layout {
+        Layout::Argent => {
+            let (count, body) = felts.split_first().ok_or("empty signature")?;
+            if !body.len().is_multiple_of(4) || *count != Felt::from(body.len() / 4) {
+                return Err(format!("count {count} does not match {} felts", body.len()));
+            }
+            body
+        }
+        Layout::ArgentLegacy => {
+            if !felts.len().is_multiple_of(3) {
+                return Err(format!(
+                    "{} felts is not a whole number of signatures",
+                    felts.len()
+                ));
+            }
+            felts
+        }
+    };
+    let mut signatures = Vec::with_capacity(body.len() / layout.stride());
+    let mut last = None;
+    for (i, chunk) in body.chunks_exact(layout.stride()).enumerate() {
+        let signature = match (layout, chunk) {
+            (Layout::Argent, [variant, public_key, r, s]) => {
+                if *variant != Felt::from(STARKNET_SIGNER_VARIANT) {
+                    return Err(format!("signature {i} is not from a Starknet signer"));
+                }
+                (*public_key, *r, *s)
+            }
+            (Layout::ArgentLegacy, [public_key, r, s]) => (*public_key, *r, *s),
+            _ => unreachable!("chunks have the layout's stride"),
+        };
+        let key = layout.sort_key(&signature.0);
+        if last.is_some_and(|last| key <= last) {
+            return Err(format!("signature {i} is out of order"));
+        }
+        last = Some(key);
+        signatures.push(signature);
+    }
+    Ok(signatures)
+}
+
+/// Encode `count` signatures `(r[i], s[i])` by `public_keys[i]` as the
+/// signature of a multisig account
+///
+/// `layout` is a `MULTISIG_LAYOUT_` constant; the signatures are sorted as
+/// it requires. `*out_count` is the capacity of `out` in felts, set to the
+/// payload length as for every felt array output. Returns `InvalidInput`
+/// for an unknown layout or a signer appearing twice.
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_multisig_signature_encode"
+)]
+pub unsafe extern "C" fn multisig_signature_encode(
+    layout: u32,
+    public_keys: *const FeltBytes,
+    r: *const FeltBytes,
+    s: *const FeltBytes,
+    count: usize,
+    out: *mut FeltBytes,
+    out_count: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_count);
+        let layout = match Layout::from_u32(layout) {
+            Ok(layout) => layout,
+            Err(e) => return e,
+        };
+        let (Some(public_keys), Some(r), Some(s)) = (
+            felts_from_raw(public_keys, count),
+            felts_from_raw(r, count),
+            felts_from_raw(s, count),
+        ) else {
+            return null_pointer("public_keys, r or s");
+        };
+        let signatures = public_keys
+            .into_iter()
+            .zip(r)
+            .zip(s)
+            .map(|((public_key, r), s)| (public_key, r, s))
+            .collect();
+        match encode(layout, signatures) {
+            Ok(felts) => write_felts_out(&felts, out, out_count),
+            Err(e) => e,
+        }
+    })
+}
+
+/// Split a multisig account signature into its signers and `(r, s)` pairs
+///
+/// `*out_count` is the capacity of each of `out_public_keys`, `out_r` and
+/// `out_s` in signatures, set to the number of signatures (`BufferTooSmall`
+/// if it does not fit). Returns `InvalidInput` for an unknown layout or a
+/// payload that does not follow it, signers out of order included.
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_multisig_signature_decode"
+)]
+pub unsafe extern "C" fn multisig_signature_decode(
+    layout: u32,
+    signature: *const FeltBytes,
+    len: usize,
+    out_public_keys: *mut FeltBytes,
+    out_r: *mut FeltBytes,
+    out_s: *mut FeltBytes,
+    out_count: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_count);
+        let layout = match Layout::from_u32(layout) {
+            Ok(layout) => layout,
+            Err(e) => return e,
+        };
+        let felts = match felts_from_raw(signature, len) {
+            Some(felts) => felts,
+            None => return null_pointer("signature"),
+        };
+        let signatures = match decode(layout, &felts) {
+            Ok(signatures) => signatures,
+            Err(e) => return invalid_input(format!("`signature`: {e}")),
+        };
+        let columns: [Vec<Felt>; 3] = [
+            signatures.iter().map(|signature| signature.0).collect(),
+            signatures.iter().map(|signature| signature.1).collect(),
+            signatures.iter().map(|signature| signature.2).collect(),
+        ];
+        let capacity = *out_count;
+        for (column, out) in columns.iter().zip([out_public_keys, out_r, out_s]) {
+            *out_count = capacity;
+            let result = write_felts_out(column, out, out_count);
+            if result != StarkResult::Success {
+                return result;
+            }
+        }
+        StarkResult::Success
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::felt_to_bytes;
+
+    fn felts(values: &[u64]) -> Vec<FeltBytes> {
+        values
+            .iter()
+            .map(|v| felt_to_bytes(&Felt::from(*v)))
+            .collect()
+    }
+
+    fn encode_ffi(
+        layout: u32,
+        keys: &[FeltBytes],
+        r: &[FeltBytes],
+        s: &[FeltBytes],
+    ) -> (StarkResult, Vec<FeltBytes>) {
+        let mut out = [[0u8; 32]; 16];
+        let mut out_count = out.len();
+        let result = unsafe {
+            multisig_signature_encode(
+                layout,
+                keys.as_ptr(),
+                r.as_ptr(),
+                s.as_ptr(),
+                keys.len(),
+                out.as_mut_ptr(),
+                &mut out_count,
+            )
+        };
+        (result, out[..out_count].to_vec())
+    }
+
+    fn decode_ffi(layout: u32, signature: &[FeltBytes]) -> (StarkResult, [Vec<FeltBytes>; 3]) {
+        let mut columns = [[[0u8; 32]; 4]; 3];
+        let mut count = 4;
+        let [keys, r, s] = &mut columns;
+        let result = unsafe {
+            multisig_signature_decode(
+                layout,
+                signature.as_ptr(),
+                signature.len(),
+                keys.as_mut_ptr(),
+                r.as_mut_ptr(),
+                s.as_mut_ptr(),
+                &mut count,
+            )
+        };
+        (result, columns.map(|column| column[..count].to_vec()))
+    }
+
+    #[test]
+    fn test_argent_layout() {
+        let (keys, r, s) = (
+            felts(&[30, 10, 20]),
+            felts(&[31, 11, 21]),
+            felts(&[32, 12, 22]),
+        );
+        let (result, payload) = encode_ffi(MULTISIG_LAYOUT_ARGENT, &keys, &r, &s);
+        assert_eq!(result, StarkResult::Success);
+        assert_eq!(payload.len(), 13);
+        assert_eq!(payload[0], felt_to_bytes(&Felt::THREE));
+
+        // Sorted by GUID, not by key
+        let guid = |key: &FeltBytes| Layout::Argent.sort_key(&Felt::from_bytes_be(key));
+        let order: Vec<FeltBytes> = payload[1..].chunks(4).map(|chunk| chunk[1]).collect();
+        assert!(order.windows(2).all(|pair| guid(&pair[0]) < guid(&pair[1])));
+        for chunk in payload[1..].chunks(4) {
+            assert_eq!(chunk[0], [0u8; 32]);
+            let i = keys.iter().position(|key| *key == chunk[1]).unwrap();
+            assert_eq!([chunk[2], chunk[3]], [r[i], s[i]]);
+        }
+
+        let (result, [decoded_keys, decoded_r, _]) = decode_ffi(MULTISIG_LAYOUT_ARGENT, &payload);
+        assert_eq!(result, StarkResult::Success);
+        assert_eq!(decoded_keys, order);
+        assert_eq!(decoded_r.len(), 3);
+
+        // Reordered or miscounted payloads are rejected
+        let mut swapped = payload.clone();
+        swapped[1..].rotate_left(4);
+        let mut miscounted = payload.clone();
+        miscounted[0] = felt_to_bytes(&Felt::TWO);
+        for bad in [&swapped, &miscounted, &payload[..12].to_vec()] {
+            assert_eq!(
+                decode_ffi(MULTISIG_LAYOUT_ARGENT, bad).0,
+                StarkResult::InvalidInput
+            );
+        }
+    }
+
+    #[test]
+    fn test_argent_legacy_layout() {
+        let (keys, r, s) = (felts(&[30, 10]), felts(&[31, 11]), felts(&[32, 12]));
+        let (result, payload) = encode_ffi(MULTISIG_LAYOUT_ARGENT_LEGACY, &keys, &r, &s);
+        assert_eq!(result, StarkResult::Success);
+        assert_eq!(payload, felts(&[10, 11, 12, 30, 31, 32]));
+
+        let (result, [decoded_keys, decoded_r, decoded_s]) =
+            decode_ffi(MULTISIG_LAYOUT_ARGENT_LEGACY, &payload);
+        assert_eq!(result, StarkResult::Success);
+        assert_eq!(
+            [decoded_keys, decoded_r, decoded_s],
+            [felts(&[10, 30]), felts(&[11, 31]), felts(&[12, 32])]
+        );
+        assert_eq!(
+            decode_ffi(
+                MULTISIG_LAYOUT_ARGENT_LEGACY,
+                &felts(&[30, 31, 32, 10, 11, 12])
+            )
+            .0,
+            StarkResult::InvalidInput
+        );
+    }
+
+    #[test]
+    fn test_multisig_rejects_bad_input() {
+        let (keys, rs) = (felts(&[5, 5]), felts(&[1, 2]));
+        assert_eq!(
+            encode_ffi(MULTISIG_LAYOUT_ARGENT, &keys, &rs, &rs).0,
+            StarkResult::InvalidInput
+        );
+        assert_eq!(
+            encode_ffi(7, &keys[..1], &rs[..1], &rs[..1]).0,
+            StarkResult::InvalidInput
+        );
+
+        // Capacity is checked before anything is written
+        let payload = encode_ffi(MULTISIG_LAYOUT_ARGENT_LEGACY, &felts(&[1, 2]), &rs, &rs).1;
+        let mut count = 1;
+        let mut out = [[0u8; 32]; 1];
+        let result = unsafe {
+            multisig_signature_decode(
+                MULTISIG_LAYOUT_ARGENT_LEGACY,
+                payload.as_ptr(),
+                payload.len(),
+                out.as_mut_ptr(),
+                out.as_mut_ptr(),
+                out.as_mut_ptr(),
+                &mut count,
+            )
+        };
+        assert_eq!(result, StarkResult::BufferTooSmall);
+        assert_eq!(count, 2);
+    }
+}
@@ -0,0 +1,286 @@
+//! Node.js addon exports (`napi` feature)
+//!
+//! Built with the feature, the shared library is also a Node-API addon:
+//! copied to a `.node` file, it can be `require`d and called without a
+//! subprocess or FFI layer. Each function calls the extern function of the
+//! same operation, so the results are identical to the C API.
+//!
+//! ```js
+//! const kundera = require("./starknet_crypto_ffi.node");
+//! const hash = kundera.invokeV3Hash(tx, chainId); // Buffer (32 bytes)
+//! ```
+//!
+//! Felts are 32-byte big-endian `Buffer`s (any `Uint8Array` is accepted),
+//! 64-bit amounts are `bigint`s. A failure throws an `Error` carrying the
+//! `stark_last_error_message` text.
+
+use core::ffi::CStr;
+
+use napi::bindgen_prelude::{BigInt, Buffer};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use zeroize::Zeroizing;
+
+use crate::address::starknet_contract_address;
+use crate::error::stark_last_error_message;
+use crate::transaction::{self, StarknetInvokeV3, StarknetResourceBounds};
+use crate::typed_data::starknet_typed_data_hash;
+use crate::{
+    felt_from_bytes, felt_to_bytes, ffi_guard, keccak256 as ffi_keccak256, starknet_get_public_key,
+    starknet_keccak256, starknet_pedersen_hash, starknet_poseidon_hash,
+    starknet_poseidon_hash_many, starknet_sign, starknet_verify, FeltBytes, StarkResult,
+};
+
+/// A STARK ECDSA signature
+#[napi(object)]
+pub struct Signature {
+    pub r: Buffer,
+    pub s: Buffer,
+}
+
+/// Bounds for one resource (`maxPricePerUnit` must fit in 128 bits)
+#[napi(object)]
+pub struct ResourceBounds {
+    pub max_amount: BigInt,
+    pub max_price_per_unit: Buffer,
+}
+
+/// Fields of an INVOKE v3 transaction, except the signature
+#[napi(object)]
+pub struct InvokeTransactionV3 {
+    pub sender_address: Buffer,
+    pub calldata: Vec<Buffer>,
+    pub nonce: Buffer,
+    pub tip: BigInt,
+    pub l1_gas: ResourceBounds,
+    pub l2_gas: ResourceBounds,
+    pub l1_data_gas: ResourceBounds,
+    pub paymaster_data: Vec<Buffer>,
+    pub account_deployment_data: Vec<Buffer>,
+    /// `0` for L1, `1` for L2
+    pub nonce_data_availability_mode: u32,
+    pub fee_data_availability_mode: u32,
+}
+
+fn invalid_arg(message: String) -> Error {
+    Error::new(Status::InvalidArg, message)
+}
+
+fn felt_arg(value: &[u8], name: &str) -> Result<FeltBytes> {
+    value
+        .try_into()
+        .map_err(|_| invalid_arg(format!("`{name}` must be 32 bytes, got {}", value.len())))
+}
+
+fn felts_arg(values: &[Buffer], name: &str) -> Result<Vec<FeltBytes>> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| felt_arg(value, &format!("{name}[{i}]")))
+        .collect()
+}
+
+fn u64_arg(value: &BigInt, name: &str) -> Result<u64> {
+    match value.get_u64() {
+        (false, value, true) => Ok(value),
+        _ => Err(invalid_arg(format!("`{name}` must fit in a u64"))),
+    }
+}
+
+fn bounds_arg(bounds: &ResourceBounds, name: &str) -> Result<StarknetResourceBounds> {
+    Ok(StarknetResourceBounds {
+        max_amount: u64_arg(&bounds.max_amount, &format!("{name}.maxAmount"))?,
+        max_price_per_unit: felt_arg(
+            &bounds.max_price_per_unit,
+            &format!("{name}.maxPricePerUnit"),
+        )?,
+    })
+}
+
+/// `Ok` on success, otherwise an error with the last-error message
+fn check(result: StarkResult) -> Result<()> {
+    if result == StarkResult::Success {
+        return Ok(());
+    }
+    let message = unsafe { CStr::from_ptr(stark_last_error_message()) };
+    Err(Error::from_reason(message.to_string_lossy()))
+}
+
+fn felt_out(felt: FeltBytes) -> Buffer {
+    felt.to_vec().into()
+}
+
+#[napi]
+pub fn pedersen_hash(a: Buffer, b: Buffer) -> Result<Buffer> {
+    let (a, b) = (felt_arg(&a, "a")?, felt_arg(&b, "b")?);
+    let mut out = [0u8; 32];
+    check(unsafe { starknet_pedersen_hash(&a, &b, &mut out) })?;
+    Ok(felt_out(out))
+}
+
+#[napi]
+pub fn poseidon_hash(a: Buffer, b: Buffer) -> Result<Buffer> {
+    let (a, b) = (felt_arg(&a, "a")?, felt_arg(&b, "b")?);
+    let mut out = [0u8; 32];
+    check(unsafe { starknet_poseidon_hash(&a, &b, &mut out) })?;
+    Ok(felt_out(out))
+}
+
+#[napi]
+pub fn poseidon_hash_many(inputs: Vec<Buffer>) -> Result<Buffer> {
+    let inputs = felts_arg(&inputs, "inputs")?;
+    let mut out = [0u8; 32];
+    check(unsafe { starknet_poseidon_hash_many(inputs.as_ptr(), inputs.len(), &mut out) })?;
+    Ok(felt_out(out))
+}
+
+/// Keccak-256 of `data` (all 256 bits)
+#[napi]
+pub fn keccak256(data: Buffer) -> Result<Buffer> {
+    let mut out = [0u8; 32];
+    check(unsafe { ffi_keccak256(data.as_ptr(), data.len(), &mut out) })?;
+    Ok(felt_out(out))
+}
+
+/// Starknet Keccak of `data` (Keccak-256 truncated to 250 bits, as used for
+/// selectors)
+#[napi]
+pub fn starknet_keccak(data: Buffer) -> Result<Buffer> {
+    let mut out = [0u8; 32];
+    check(unsafe { starknet_keccak256(data.as_ptr(), data.len(), &mut out) })?;
+    Ok(felt_out(out))
+}
+
+#[napi]
+pub fn get_public_key(private_key: Buffer) -> Result<Buffer> {
+    let private_key = Zeroizing::new(felt_arg(&private_key, "privateKey")?);
+    let mut out = [0u8; 32];
+    check(unsafe { starknet_get_public_key(&*private_key, &mut out) })?;
+    Ok(felt_out(out))
+}
+
+/// Sign `messageHash` (RFC 6979 deterministic nonce)
+#[napi]
+pub fn sign(private_key: Buffer, message_hash: Buffer) -> Result<Signature> {
+    let private_key = Zeroizing::new(felt_arg(&private_key, "privateKey")?);
+    let message_hash = felt_arg(&message_hash, "messageHash")?;
+    let (mut r, mut s) = ([0u8; 32], [0u8; 32]);
+    check(unsafe { starknet_sign(&*private_key, &message_hash, &mut r, &mut s) })?;
+    Ok(Signature {
+        r: felt_out(r),
+        s: felt_out(s),
+    })
+}
+
+/// Whether `(r, s)` is a valid signature of `messageHash` by `publicKey`
+#[napi]
+pub fn verify(public_key: Buffer, message_hash: Buffer, r: Buffer, s: Buffer) -> Result<bool> {
+    let public_key = felt_arg(&public_key, "publicKey")?;
+    let message_hash = felt_arg(&message_hash, "messageHash")?;
+    let (r, s) = (felt_arg(&r, "r")?, felt_arg(&s, "s")?);
+    match unsafe { starknet_verify(&public_key, &message_hash, &r, &s) } {
+        StarkResult::InvalidSignature => Ok(false),
+        result => check(result).map(|()| true),
+    }
+}
+
+/// Hash of an INVOKE v3 transaction on `chainId`
+#[napi]
+pub fn invoke_v3_hash(tx: InvokeTransactionV3, chain_id: Buffer) -> Result<Buffer> {
+    let calldata = felts_arg(&tx.calldata, "calldata")?;
+    let paymaster_data = felts_arg(&tx.paymaster_data, "paymasterData")?;
+    let account_deployment_data = felts_arg(&tx.account_deployment_data, "accountDeploymentData")?;
+    let raw = StarknetInvokeV3 {
+        sender_address: felt_arg(&tx.sender_address, "senderAddress")?,
+        calldata: calldata.as_ptr(),
+        calldata_len: calldata.len(),
+        nonce: felt_arg(&tx.nonce, "nonce")?,
+        tip: u64_arg(&tx.tip, "tip")?,
+        l1_gas: bounds_arg(&tx.l1_gas, "l1Gas")?,
+        l2_gas: bounds_arg(&tx.l2_gas, "l2Gas")?,
+        l1_data_gas: bounds_arg(&tx.l1_data_gas, "l1DataGas")?,
+        paymaster_data: paymaster_data.as_ptr(),
+        paymaster_data_len: paymaster_data.len(),
+        account_deployment_data: account_deployment_data.as_ptr(),
+        account_deployment_data_len: account_deployment_data.len(),
+        nonce_data_availability_mode: tx.nonce_data_availability_mode,
+        fee_data_availability_mode: tx.fee_data_availability_mode,
+    };
+    let chain_id = felt_arg(&chain_id, "chainId")?;
+
+    let mut out = [0u8; 32];
+    check(ffi_guard(|| {
+        let chain_id = match felt_from_bytes(&chain_id) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        match unsafe { transaction::invoke_v3_hash(&raw, &chain_id) } {
+            Ok(hash) => {
+                out = felt_to_bytes(&hash);
+                StarkResult::Success
+            }
+            Err(e) => e,
+        }
+    }))?;
+    Ok(felt_out(out))
+}
+
+/// SNIP-12 message hash of the typed data `json` for `accountAddress`
+#[napi]
+pub fn typed_data_hash(json: String, account_address: Buffer) -> Result<Buffer> {
+    let account_address = felt_arg(&account_address, "accountAddress")?;
+    let mut out = [0u8; 32];
+    check(unsafe {
+        starknet_typed_data_hash(json.as_ptr(), json.len(), &account_address, &mut out)
+    })?;
+    Ok(felt_out(out))
+}
+
+/// Address of a contract deployed by `deployer` (zero for a deploy account
+/// transaction)
+#[napi]
+pub fn contract_address(
+    deployer: Buffer,
+    salt: Buffer,
+    class_hash: Buffer,
+    constructor_calldata: Vec<Buffer>,
+) -> Result<Buffer> {
+    let deployer = felt_arg(&deployer, "deployer")?;
+    let salt = felt_arg(&salt, "salt")?;
+    let class_hash = felt_arg(&class_hash, "classHash")?;
+    let calldata = felts_arg(&constructor_calldata, "constructorCalldata")?;
+    let mut out = [0u8; 32];
+    check(unsafe {
+        starknet_contract_address(
+            &deployer,
+            &salt,
+            &class_hash,
+            calldata.as_ptr(),
+            calldata.len(),
+            &mut out,
+        )
+    })?;
+    Ok(felt_out(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_felt_arg_length() {
+        assert_eq!(felt_arg(&[7u8; 32], "a").unwrap(), [7u8; 32]);
+        let err = felt_arg(&[0u8; 31], "a").unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert_eq!(err.reason, "`a` must be 32 bytes, got 31");
+    }
+
+    #[test]
+    fn test_check_uses_last_error() {
+        let zero = [0u8; 32];
+        let mut out = [0u8; 32];
+        let result = unsafe { crate::felt_inverse(&zero, &mut out) };
+        assert_eq!(check(result).unwrap_err().reason, "no modular inverse");
+        assert!(check(StarkResult::Success).is_ok());
+    }
+}
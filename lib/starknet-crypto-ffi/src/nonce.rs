@@ -0,0 +1,221 @@
+//! RFC 6979 nonce sequences
+//!
+//! `starknet_rfc6979_k` gives the first valid nonce of the RFC 6979
+//! HMAC-DRBG (HMAC-SHA256) for a key and message, the one `starknet_sign`
+//! uses. A signing loop that rejects some nonces (to enforce a low `s` or a
+//! particular `R`) continues the same sequence with a
+//! `StarkRfc6979KIter`: each `next` runs the RFC's retry step (3.2.h.3,
+//! `K = HMAC_K(V || 0x00)`, `V = HMAC_K(V)`) and draws until a candidate is
+//! in `[1, n)`, so the first value is `starknet_rfc6979_k`'s.
+//!
+//! Candidates are the 256-bit DRBG output shifted right by 4 bits, as
+//! starknet-crypto (and cairo-lang) derive them. The DRBG state is wiped
+//! when the iterator is freed.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use starknet_crypto::Felt;
+use zeroize::Zeroizing;
+
+use crate::handle::StarkHandle;
+use crate::{
+    felt_from_bytes, felt_to_bytes, ffi_guard, ffi_guard_void, FeltBytes, StarkResult, EC_ORDER,
+};
+
+/// HMAC-DRBG state yielding successive RFC 6979 nonces (opaque)
+pub struct StarkRfc6979KIter {
+    key: Zeroizing<[u8; 32]>,
+    v: Zeroizing<[u8; 32]>,
+}
+
+impl StarkHandle for StarkRfc6979KIter {
+    const NAME: &'static str = "StarkRfc6979KIter";
+}
+
+fn hmac_sha256(key: &[u8; 32], data: &[&[u8]]) -> Zeroizing<[u8; 32]> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    for part in data {
+        mac.update(part);
+    }
+    Zeroizing::new(mac.finalize().into_bytes().into())
+}
+
+impl StarkRfc6979KIter {
+    /// Steps 3.2.b to 3.2.g, with `seed` as additional data (RFC 6979 3.6)
+    fn new(private_key: &[u8; 32], message_hash: &[u8; 32], seed: &[u8]) -> Self {
+        let mut key = Zeroizing::new([0u8; 32]);
+        let mut v = Zeroizing::new([1u8; 32]);
+        for i in 0..=1u8 {
+            key = hmac_sha256(&key, &[&v[..], &[i], private_key, message_hash, seed]);
+            v = hmac_sha256(&key, &[&v[..]]);
+        }
+        Self { key, v }
+    }
+
+    /// Next candidate, then the retry update
+    fn candidate(&mut self) -> Zeroizing<[u8; 32]> {
+        self.v = hmac_sha256(&self.key, &[&self.v[..]]);
+        let candidate = self.v.clone();
+        self.key = hmac_sha256(&self.key, &[&self.v[..], &[0]]);
+        self.v = hmac_sha256(&self.key, &[&self.v[..]]);
+        candidate
+    }
+
+    fn next_k(&mut self) -> Zeroizing<Felt> {
+        let order = EC_ORDER.to_bytes_be();
+        loop {
+            let candidate = self.candidate();
+            let mut k = Zeroizing::new([0u8; 32]);
+            for i in 0..32 {
+                k[i] = (candidate[i] >> 4) | if i > 0 { candidate[i - 1] << 4 } else { 0 };
+            }
+            if *k != [0u8; 32] && *k < order {
+                return Zeroizing::new(Felt::from_bytes_be(&k));
+            }
+        }
+    }
+}
+
+/// Start the RFC 6979 nonce sequence of `starknet_rfc6979_k`
+///
+/// `seed` is optional extra entropy (may be null), as for
+/// `starknet_rfc6979_k`. Writes an owned handle to `out_iter`; release it
+/// with `starknet_rfc6979_k_iter_free`.
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_starknet_rfc6979_k_iter_new"
+)]
+pub unsafe extern "C" fn starknet_rfc6979_k_iter_new(
+    message_hash: *const FeltBytes,
+    private_key: *const FeltBytes,
+    seed: *const FeltBytes,
+    out_iter: *mut *mut StarkRfc6979KIter,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(message_hash, private_key, out_iter);
+        let (Some(msg), Some(pk)) = (
+            felt_from_bytes(&*message_hash),
+            felt_from_bytes(&*private_key),
+        ) else {
+            return StarkResult::InvalidInput;
+        };
+        let pk = Zeroizing::new(pk.to_bytes_be());
+        let seed = if seed.is_null() {
+            [0u8; 32]
+        } else {
+            match felt_from_bytes(&*seed) {
+                Some(f) => f.to_bytes_be(),
+                None => return StarkResult::InvalidInput,
+            }
+        };
+        // starknet-crypto feeds the seed without its leading zero bytes
+        let start = seed.iter().position(|b| *b != 0).unwrap_or(32);
+
+        *out_iter = StarkRfc6979KIter::new(&pk, &msg.to_bytes_be(), &seed[start..]).into_handle();
+        StarkResult::Success
+    })
+}
+
+/// Write the next nonce of the sequence to `out_k`
+///
+/// The first call gives `starknet_rfc6979_k`'s nonce; every value is in
+/// `[1, n)`.
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_starknet_rfc6979_k_iter_next"
+)]
+pub unsafe extern "C" fn starknet_rfc6979_k_iter_next(
+    iter: *mut StarkRfc6979KIter,
+    out_k: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(iter, out_k);
+        let iter = match StarkRfc6979KIter::from_handle_mut(iter) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+
+        *out_k = felt_to_bytes(&iter.next_k());
+        StarkResult::Success
+    })
+}
+
+/// Free a nonce iterator, wiping its state (null is a no-op)
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_starknet_rfc6979_k_iter_free"
+)]
+pub unsafe extern "C" fn starknet_rfc6979_k_iter_free(iter: *mut StarkRfc6979KIter) {
+    ffi_guard_void(|| StarkRfc6979KIter::free_handle(iter))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet_crypto::rfc6979_generate_k;
+
+    #[test]
+    fn test_rfc6979_k_iter() {
+        let message_hash = Felt::from_hex_unchecked(
+            "0x1e542e2da71b3f5d7b4e9d329b4d30ac0b5d6f266ebef7364bf61c39aac35d0",
+        );
+        let private_key = Felt::from_hex_unchecked(
+            "0x2dccce1da22003777062ee0870e9881b460a8b7eca276870f57c601f182136c",
+        );
+        let seed = Felt::from(0x1234u64);
+
+        for seed in [None, Some(seed)] {
+            let mut iter = core::ptr::null_mut();
+            let (mut k0, mut k1) = ([0u8; 32], [0u8; 32]);
+            unsafe {
+                let seed_bytes = seed.map(|s| felt_to_bytes(&s));
+                assert_eq!(
+                    starknet_rfc6979_k_iter_new(
+                        &felt_to_bytes(&message_hash),
+                        &felt_to_bytes(&private_key),
+                        seed_bytes.as_ref().map_or(core::ptr::null(), |s| s),
+                        &mut iter
+                    ),
+                    StarkResult::Success
+                );
+                assert_eq!(
+                    starknet_rfc6979_k_iter_next(iter, &mut k0),
+                    StarkResult::Success
+                );
+                assert_eq!(
+                    starknet_rfc6979_k_iter_next(iter, &mut k1),
+                    StarkResult::Success
+                );
+                starknet_rfc6979_k_iter_free(iter);
+            }
+            let k0 = Felt::from_bytes_be(&k0);
+            assert_eq!(
+                k0,
+                rfc6979_generate_k(&message_hash, &private_key, seed.as_ref())
+            );
+
+            // The second value continues the upstream HMAC-DRBG, skipping
+            // out-of-range candidates (about half of them)
+            let seed_bytes = seed.unwrap_or(Felt::ZERO).to_bytes_be();
+            let start = seed_bytes.iter().position(|b| *b != 0).unwrap_or(32);
+            let mut drbg = rfc6979::HmacDrbg::<Sha256>::new(
+                &private_key.to_bytes_be(),
+                &message_hash.to_bytes_be(),
+                &seed_bytes[start..],
+            );
+            let order = EC_ORDER.to_biguint();
+            let mut valid = core::iter::repeat_with(|| {
+                let mut bytes = [0u8; 32];
+                drbg.fill_bytes(&mut bytes);
+                num_bigint::BigUint::from_bytes_be(&bytes) >> 4u32
+            })
+            .filter(|k| *k != 0u32.into() && *k < order);
+            assert_eq!(k0.to_biguint(), valid.next().unwrap());
+            assert_eq!(Felt::from_bytes_be(&k1).to_biguint(), valid.next().unwrap());
+            assert_ne!(Felt::from_bytes_be(&k1), k0);
+        }
+    }
+}
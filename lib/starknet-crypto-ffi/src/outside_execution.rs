@@ -0,0 +1,340 @@
+//! SNIP-9 outside execution message hash
+//!
+//! The `OutsideExecution` typed data signed by an account to authorize a
+//! relayer (paymaster, meta-transaction service) to submit `calls` on its
+//! behalf through `execute_from_outside` / `execute_from_outside_v2`.
+//!
+//! SNIP-9 version 1 signs a revision 0 (Pedersen) SNIP-12 message, version 2
+//! a revision 1 (Poseidon) message. Both use the domain name
+//! `Account.execute_from_outside` with the SNIP-9 version as domain version.
+
+use alloc::vec::Vec;
+
+use starknet_crypto::Felt;
+
+use crate::call::{calls_from_raw, Call, StarknetCall};
+use crate::typed_data::{message_hash, short_string_to_felt, starknet_domain_hash, Revision};
+use crate::{
+    error::null_pointer, felt_from_bytes, felt_to_bytes, ffi_guard, sn_keccak, FeltBytes,
+    StarkResult,
+};
+
+const OUTSIDE_EXECUTION_TYPE_V1: &str = "OutsideExecution(caller:felt,nonce:felt,execute_after:felt,execute_before:felt,calls_len:felt,calls:OutsideCall*)OutsideCall(to:felt,selector:felt,calldata_len:felt,calldata:felt*)";
+const OUTSIDE_CALL_TYPE_V1: &str =
+    "OutsideCall(to:felt,selector:felt,calldata_len:felt,calldata:felt*)";
+
+const OUTSIDE_EXECUTION_TYPE_V2: &str = "\"OutsideExecution\"(\"Caller\":\"ContractAddress\",\"Nonce\":\"felt\",\"Execute After\":\"u128\",\"Execute Before\":\"u128\",\"Calls\":\"Call*\")\"Call\"(\"To\":\"ContractAddress\",\"Selector\":\"selector\",\"Calldata\":\"felt*\")";
+const OUTSIDE_CALL_TYPE_V2: &str =
+    "\"Call\"(\"To\":\"ContractAddress\",\"Selector\":\"selector\",\"Calldata\":\"felt*\")";
+
+const DOMAIN_NAME: &str = "Account.execute_from_outside";
+
+pub(crate) struct OutsideExecution {
+    pub caller: Felt,
+    pub nonce: Felt,
+    pub execute_after: u64,
+    pub execute_before: u64,
+    pub calls: Vec<Call>,
+}
+
+impl OutsideExecution {
+    fn struct_hash(&self, revision: Revision) -> Felt {
+        let call_hashes: Vec<Felt> = self
+            .calls
+            .iter()
+            .map(|call| call_hash(call, revision))
+            .collect();
+
+        match revision {
+            Revision::V0 => revision.hash_many(&[
+                sn_keccak(OUTSIDE_EXECUTION_TYPE_V1.as_bytes()),
+                self.caller,
+                self.nonce,
+                Felt::from(self.execute_after),
+                Felt::from(self.execute_before),
+                Felt::from(call_hashes.len()),
+                revision.hash_many(&call_hashes),
+            ]),
+            Revision::V1 => revision.hash_many(&[
+                sn_keccak(OUTSIDE_EXECUTION_TYPE_V2.as_bytes()),
+                self.caller,
+                self.nonce,
+                Felt::from(self.execute_after),
+                Felt::from(self.execute_before),
+                revision.hash_many(&call_hashes),
+            ]),
+        }
+    }
+
+    /// Message hash the signing account must sign
+    pub(crate) fn message_hash(&self, revision: Revision, chain_id: Felt, account: Felt) -> Felt {
+        let name = short_string_to_felt(DOMAIN_NAME).expect("domain name is a valid short string");
+        // The SNIP-9 version doubles as the domain version
+        let version = match revision {
+            Revision::V0 => Felt::ONE,
+            Revision::V1 => Felt::TWO,
+        };
+        let domain = starknet_domain_hash(revision, name, version, chain_id);
+        message_hash(revision, domain, account, self.struct_hash(revision))
+    }
+}
+
+fn call_hash(call: &Call, revision: Revision) -> Felt {
+    let calldata_hash = revision.hash_many(&call.calldata);
+    match revision {
+        Revision::V0 => revision.hash_many(&[
+            sn_keccak(OUTSIDE_CALL_TYPE_V1.as_bytes()),
+            call.to,
+            call.selector,
+            Felt::from(call.calldata.len()),
+            calldata_hash,
+        ]),
+        Revision::V1 => revision.hash_many(&[
+            sn_keccak(OUTSIDE_CALL_TYPE_V2.as_bytes()),
+            call.to,
+            call.selector,
+            calldata_hash,
+        ]),
+    }
+}
+
+/// Compute the SNIP-9 outside execution message hash
+///
+/// `revision` is the SNIP-12 revision of the signed message: 0 for SNIP-9
+/// version 1 (`execute_from_outside`), 1 for version 2
+/// (`execute_from_outside_v2`). `caller` may be the short string
+/// `ANY_CALLER`. `account_address` is the account that signs and executes
+/// the calls. Returns `InvalidInput` for an unknown revision.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_outside_execution_hash")]
+pub unsafe extern "C" fn starknet_outside_execution_hash(
+    caller: *const FeltBytes,
+    nonce: *const FeltBytes,
+    execute_after: u64,
+    execute_before: u64,
+    calls: *const StarknetCall,
+    calls_len: usize,
+    chain_id: *const FeltBytes,
+    account_address: *const FeltBytes,
+    revision: u8,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(caller, nonce, chain_id, account_address, out);
+        let revision = match revision {
+            0 => Revision::V0,
+            1 => Revision::V1,
+            _ => return StarkResult::InvalidInput,
+        };
+        let caller = match felt_from_bytes(&*caller) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let nonce = match felt_from_bytes(&*nonce) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let chain_id = match felt_from_bytes(&*chain_id) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let account = match felt_from_bytes(&*account_address) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let calls = match calls_from_raw(calls, calls_len) {
+            Some(c) => c,
+            None => return null_pointer("calls"),
+        };
+
+        let execution = OutsideExecution {
+            caller,
+            nonce,
+            execute_after,
+            execute_before,
+            calls,
+        };
+        *out = felt_to_bytes(&execution.message_hash(revision, chain_id, account));
+        StarkResult::Success
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::typed_data::starknet_typed_data_hash;
+
+    const ACCOUNT: &str = "0x1234";
+
+    fn typed_data_hash(json: &str) -> Felt {
+        let account = felt_to_bytes(&Felt::from_hex_unchecked(ACCOUNT));
+        let mut out = [0u8; 32];
+        unsafe {
+            let result = starknet_typed_data_hash(json.as_ptr(), json.len(), &account, &mut out);
+            assert_eq!(result, StarkResult::Success);
+        }
+        Felt::from_bytes_be(&out)
+    }
+
+    fn outside_execution_hash(revision: u8) -> Felt {
+        let calldata = [
+            felt_to_bytes(&Felt::from(0x5678u64)),
+            felt_to_bytes(&Felt::from(100u64)),
+        ];
+        let calls = [
+            StarknetCall {
+                to: felt_to_bytes(&Felt::from_hex_unchecked("0x49d36570")),
+                selector: felt_to_bytes(&sn_keccak(b"transfer")),
+                calldata: calldata.as_ptr(),
+                calldata_len: calldata.len(),
+            },
+            StarknetCall {
+                to: felt_to_bytes(&Felt::from_hex_unchecked("0xabc")),
+                selector: felt_to_bytes(&sn_keccak(b"ping")),
+                calldata: core::ptr::null(),
+                calldata_len: 0,
+            },
+        ];
+        let caller = felt_to_bytes(&short_string_to_felt("ANY_CALLER").unwrap());
+        let nonce = felt_to_bytes(&Felt::from(7u64));
+        let chain_id = felt_to_bytes(&short_string_to_felt("SN_SEPOLIA").unwrap());
+        let account = felt_to_bytes(&Felt::from_hex_unchecked(ACCOUNT));
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = starknet_outside_execution_hash(
+                &caller,
+                &nonce,
+                100,
+                200,
+                calls.as_ptr(),
+                calls.len(),
+                &chain_id,
+                &account,
+                revision,
+                &mut out,
+            );
+            assert_eq!(result, StarkResult::Success);
+        }
+        Felt::from_bytes_be(&out)
+    }
+
+    // Both versions are checked against the generic typed data path on the
+    // equivalent document. No SNIP-9 known-answer hash from starknet.js,
+    // Argent or Braavos is available to this build.
+    #[test]
+    fn test_outside_execution_v1_matches_typed_data() {
+        let json = r#"{
+  "types": {
+    "StarkNetDomain": [
+      { "name": "name", "type": "felt" },
+      { "name": "version", "type": "felt" },
+      { "name": "chainId", "type": "felt" }
+    ],
+    "OutsideExecution": [
+      { "name": "caller", "type": "felt" },
+      { "name": "nonce", "type": "felt" },
+      { "name": "execute_after", "type": "felt" },
+      { "name": "execute_before", "type": "felt" },
+      { "name": "calls_len", "type": "felt" },
+      { "name": "calls", "type": "OutsideCall*" }
+    ],
+    "OutsideCall": [
+      { "name": "to", "type": "felt" },
+      { "name": "selector", "type": "felt" },
+      { "name": "calldata_len", "type": "felt" },
+      { "name": "calldata", "type": "felt*" }
+    ]
+  },
+  "primaryType": "OutsideExecution",
+  "domain": { "name": "Account.execute_from_outside", "version": "1", "chainId": "SN_SEPOLIA" },
+  "message": {
+    "caller": "ANY_CALLER",
+    "nonce": "7",
+    "execute_after": "100",
+    "execute_before": "200",
+    "calls_len": "2",
+    "calls": [
+      {
+        "to": "0x49d36570",
+        "selector": "$TRANSFER",
+        "calldata_len": "2",
+        "calldata": ["0x5678", "100"]
+      },
+      {
+        "to": "0xabc",
+        "selector": "$PING",
+        "calldata_len": "0",
+        "calldata": []
+      }
+    ]
+  }
+}"#;
+        // Revision 0 has no `selector` type, selectors are plain felts
+        let json = json
+            .replace("$TRANSFER", &sn_keccak(b"transfer").to_hex_string())
+            .replace("$PING", &sn_keccak(b"ping").to_hex_string());
+        assert_eq!(outside_execution_hash(0), typed_data_hash(&json));
+    }
+
+    #[test]
+    fn test_outside_execution_v2_matches_typed_data() {
+        let json = r#"{
+  "types": {
+    "StarknetDomain": [
+      { "name": "name", "type": "shortstring" },
+      { "name": "version", "type": "shortstring" },
+      { "name": "chainId", "type": "shortstring" },
+      { "name": "revision", "type": "shortstring" }
+    ],
+    "OutsideExecution": [
+      { "name": "Caller", "type": "ContractAddress" },
+      { "name": "Nonce", "type": "felt" },
+      { "name": "Execute After", "type": "u128" },
+      { "name": "Execute Before", "type": "u128" },
+      { "name": "Calls", "type": "Call*" }
+    ],
+    "Call": [
+      { "name": "To", "type": "ContractAddress" },
+      { "name": "Selector", "type": "selector" },
+      { "name": "Calldata", "type": "felt*" }
+    ]
+  },
+  "primaryType": "OutsideExecution",
+  "domain": { "name": "Account.execute_from_outside", "version": "2", "chainId": "SN_SEPOLIA", "revision": "1" },
+  "message": {
+    "Caller": "0x414e595f43414c4c4552",
+    "Nonce": "7",
+    "Execute After": 100,
+    "Execute Before": 200,
+    "Calls": [
+      { "To": "0x49d36570", "Selector": "transfer", "Calldata": ["0x5678", "100"] },
+      { "To": "0xabc", "Selector": "ping", "Calldata": [] }
+    ]
+  }
+}"#;
+        assert_eq!(outside_execution_hash(1), typed_data_hash(json));
+    }
+
+    #[test]
+    fn test_outside_execution_rejects_unknown_revision() {
+        let zero = [0u8; 32];
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = starknet_outside_execution_hash(
+                &zero,
+                &zero,
+                0,
+                0,
+                core::ptr::null(),
+                0,
+                &zero,
+                &zero,
+                2,
+                &mut out,
+            );
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//! Pedersen hash, with optional large lookup tables
+//!
+//! Every Pedersen hash in the crate goes through `pedersen_hash`. By default
+//! that is starknet-crypto's, which adds one precomputed point per 4 bits of
+//! input. With the `pedersen-tables` feature, `stark_set_pedersen_table_size`
+//! builds tables of `2^bits` multiples per `bits`-bit window, so a hash
+//! adds one point per window instead: 12-bit windows cut the additions from
+//! 126 to 44, for about 11 MB of tables.
+//!
+//! | bits | tables  |
+//! |------|---------|
+//! | 8    | 1 MB    |
+//! | 10   | 3.3 MB  |
+//! | 12   | 11 MB   |
+//! | 14   | 38 MB   |
+//! | 16   | 134 MB  |
+//!
+//! The hash is `(shift + a_low·P0 + a_high·P1 + b_low·P2 + b_high·P3).x`,
+//! with `low` the 248 low bits of an input and `high` its 4 high bits.
+//! Only the two `low` terms get large windows; the `high` terms keep 16
+//! entries.
+
+#[cfg(not(feature = "pedersen-tables"))]
+pub(crate) use starknet_crypto::pedersen_hash;
+#[cfg(feature = "pedersen-tables")]
+pub(crate) use tables::pedersen_hash;
+#[cfg(feature = "pedersen-tables")]
+pub use tables::{stark_get_pedersen_table_size, stark_set_pedersen_table_size};
+
+#[cfg(feature = "pedersen-tables")]
+mod tables {
+    use alloc::format;
+    use alloc::vec::Vec;
+    use std::sync::Arc;
+
+    use starknet_crypto::Felt;
+    use starknet_types_core::curve::{AffinePoint, ProjectivePoint};
+
+    use crate::error::invalid_input;
+    use crate::sync::Mutex;
+    use crate::{ffi_guard, StarkResult};
+
+    const SHIFT_POINT: [&str; 2] = [
+        "0x049ee3eba8c1600700ee1b87eb599f16716b0b1022947733551fde4050ca6804",
+        "0x03ca0cfe4b3bc6ddf346d49d06ea0ed34e621062c0e056c1d0405d266e10268a",
+    ];
+    /// P0 to P3
+    const POINTS: [[&str; 2]; 4] = [
+        [
+            "0x0234287dcbaffe7f969c748655fca9e58fa8120b6d56eb0c1080d17957ebe47b",
+            "0x03b056f100f96fb21e889527d41f4e39940135dd7a6c94cc6ed0268ee89e5615",
+        ],
+        [
+            "0x04fa56f376c83db33f9dab2656558f3399099ec1de5e3018b7a6932dba8aa378",
+            "0x03fa0984c931c9e38113e0c0e47e4401562761f92a7a23b45168f4e80ff5b54d",
+        ],
+        [
+            "0x04ba4cc166be8dec764910f75b45f74b40c690c74709e90f3aa372f0bd2d6997",
+            "0x0040301cf5c1751f4b971e46c4ede85fcac5c59a5ce5ae7c48151f27b24b219c",
+        ],
+        [
+            "0x054302dcb0e6cc1c6e44cca8f61a63bb2ca65048d53fb325d36ff12c49a58202",
+            "0x01b77b3e37d13504b348046268d8ae25ce98ad783c25561a879dcc77e99c2426",
+        ],
+    ];
+
+    const LOW_BITS: u32 = 248;
+    const HIGH_BITS: u32 = 4;
+    /// Window sizes `stark_set_pedersen_table_size` accepts
+    const MIN_BITS: u32 = 4;
+    const MAX_BITS: u32 = 16;
+
+    fn point([x, y]: [&str; 2]) -> AffinePoint {
+        AffinePoint::new_unchecked(Felt::from_hex_unchecked(x), Felt::from_hex_unchecked(y))
+    }
+
+    /// `v·2^(bits·w)·P` at `[w << bits | v]`, for `v` in `1..2^bits` (slot 0
+    /// of each window is unused)
+    struct WindowTable {
+        bits: u32,
+        points: Vec<AffinePoint>,
+    }
+
+    impl WindowTable {
+        fn new(point: &AffinePoint, bits: u32, scalar_bits: u32) -> Self {
+            let size = 1usize << bits;
+            let windows = scalar_bits.div_ceil(bits);
+            let mut points = Vec::with_capacity(windows as usize * size);
+            let mut base = ProjectivePoint::from_affine_unchecked(point.x(), point.y());
+            for _ in 0..windows {
+                let mut row = Vec::with_capacity(size);
+                row.push(ProjectivePoint::identity());
+                for v in 1..size {
+                    row.push(&row[v - 1] + &base);
+                }
+                base = &row[size - 1] + &base;
+                points.push(AffinePoint::identity());
+                points.extend(normalize(&row[1..]));
+            }
+            Self { bits, points }
+        }
+
+        /// `acc += scalar·P`, for `scalar < 2^scalar_bits`
+        fn add_mul(&self, acc: &mut ProjectivePoint, digits: &[u64; 4], scalar_bits: u32) {
+            let mut offset = 0;
+            let mut window = 0;
+            while offset < scalar_bits {
+                let bits = self.bits.min(scalar_bits - offset);
+                let v = extract(digits, offset, bits);
+                if v != 0 {
+                    *acc += &self.points[(window << self.bits) | v];
+                }
+                offset += self.bits;
+                window += 1;
+            }
+        }
+    }
+
+    /// Affine form of non-identity projective points, with one inversion
+    /// (Montgomery's trick)
+    fn normalize(points: &[ProjectivePoint]) -> Vec<AffinePoint> {
+        let mut prefix = Vec::with_capacity(points.len());
+        let mut product = Felt::ONE;
+        for point in points {
+            prefix.push(product);
+            product *= point.z();
+        }
+        let mut inverse = product.inverse().expect("table point at infinity");
+        let mut affine = Vec::with_capacity(points.len());
+        for (point, prefix) in points.iter().zip(prefix).rev() {
+            let z_inverse = inverse * prefix;
+            inverse *= point.z();
+            affine.push(AffinePoint::new_unchecked(
+                point.x() * z_inverse,
+                point.y() * z_inverse,
+            ));
+        }
+        affine.reverse();
+        affine
+    }
+
+    /// `bits` bits of `digits` (little-endian limbs) from bit `offset`
+    fn extract(digits: &[u64; 4], offset: u32, bits: u32) -> usize {
+        let (limb, shift) = ((offset / 64) as usize, offset % 64);
+        let mut value = digits[limb] >> shift;
+        if shift + bits > 64 && limb < 3 {
+            value |= digits[limb + 1] << (64 - shift);
+        }
+        (value & ((1u64 << bits) - 1)) as usize
+    }
+
+    struct Tables {
+        shift: ProjectivePoint,
+        /// a_low·P0 and b_low·P2
+        low: [WindowTable; 2],
+        /// a_high·P1 and b_high·P3
+        high: [WindowTable; 2],
+    }
+
+    impl Tables {
+        fn new(bits: u32) -> Self {
+            let shift = point(SHIFT_POINT);
+            Self {
+                shift: ProjectivePoint::from_affine_unchecked(shift.x(), shift.y()),
+                low: [0, 2].map(|i| WindowTable::new(&point(POINTS[i]), bits, LOW_BITS)),
+                high: [1, 3].map(|i| WindowTable::new(&point(POINTS[i]), HIGH_BITS, HIGH_BITS)),
+            }
+        }
+
+        fn hash(&self, a: &Felt, b: &Felt) -> Felt {
+            let mut acc = self.shift.clone();
+            for (i, input) in [a, b].into_iter().enumerate() {
+                let digits = input.to_le_digits();
+                self.low[i].add_mul(&mut acc, &digits, LOW_BITS);
+                let high = [digits[3] >> (LOW_BITS - 192), 0, 0, 0];
+                self.high[i].add_mul(&mut acc, &high, HIGH_BITS);
+            }
+            acc.to_affine().expect("pedersen hash at infinity").x()
+        }
+    }
+
+    /// Current tables (`None`: starknet-crypto's)
+    static TABLES: Mutex<Option<Arc<Tables>>> = Mutex::new(None);
+
+    pub(crate) fn pedersen_hash(a: &Felt, b: &Felt) -> Felt {
+        let tables = TABLES.lock().clone();
+        match tables {
+            Some(tables) => tables.hash(a, b),
+            None => starknet_crypto::pedersen_hash(a, b),
+        }
+    }
+
+    /// Use Pedersen lookup tables with `bits`-bit windows (0: the default
+    /// 4-bit tables)
+    ///
+    /// Builds the tables before returning (under a second for 12 bits in a
+    /// release build, see `pedersen` for their size) and frees the previous
+    /// ones; hashes already running finish with the old tables. Returns
+    /// `InvalidInput` unless `bits` is 0 or in `4..=16`. Only exported with
+    /// the `pedersen-tables` feature.
+    #[no_mangle]
+    #[cfg_attr(
+        feature = "symbol-prefix",
+        export_name = "kundera_stark_set_pedersen_table_size"
+    )]
+    pub extern "C" fn stark_set_pedersen_table_size(bits: u32) -> StarkResult {
+        ffi_guard(|| {
+            let tables = match bits {
+                0 => None,
+                MIN_BITS..=MAX_BITS => Some(Arc::new(Tables::new(bits))),
+                _ => {
+                    return invalid_input(format!(
+                        "`bits` is {bits} (expected 0 or {MIN_BITS} to {MAX_BITS})"
+                    ))
+                }
+            };
+            *TABLES.lock() = tables;
+            log!(Info, "pedersen table size set to {bits} bits");
+            StarkResult::Success
+        })
+    }
+
+    /// Window size of the current Pedersen tables (0: the default ones)
+    #[no_mangle]
+    #[cfg_attr(
+        feature = "symbol-prefix",
+        export_name = "kundera_stark_get_pedersen_table_size"
+    )]
+    pub extern "C" fn stark_get_pedersen_table_size() -> u32 {
+        let mut bits = 0;
+        ffi_guard(|| {
+            bits = TABLES
+                .lock()
+                .as_ref()
+                .map_or(0, |tables| tables.low[0].bits);
+            StarkResult::Success
+        });
+        bits
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn inputs() -> Vec<Felt> {
+            let mut inputs = vec![Felt::ZERO, Felt::ONE, Felt::MAX];
+            inputs.extend((1..6u64).map(|i| Felt::from(i * 0x1f3d).pow(0x9e37_79b9_7f4a_7c15u64)));
+            inputs
+        }
+
+        #[test]
+        fn test_tables_match_default_hash() {
+            let inputs = inputs();
+            for bits in [4, 7] {
+                let tables = Tables::new(bits);
+                for a in &inputs {
+                    for b in &inputs {
+                        assert_eq!(
+                            tables.hash(a, b),
+                            starknet_crypto::pedersen_hash(a, b),
+                            "{bits} bits"
+                        );
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn test_set_pedersen_table_size() {
+            assert_eq!(stark_set_pedersen_table_size(3), StarkResult::InvalidInput);
+            assert_eq!(stark_set_pedersen_table_size(17), StarkResult::InvalidInput);
+            assert_eq!(stark_set_pedersen_table_size(5), StarkResult::Success);
+            assert_eq!(stark_get_pedersen_table_size(), 5);
+
+            let (a, b) = (Felt::from(12345u64), Felt::MAX);
+            assert_eq!(
+                pedersen_hash(&a, &b),
+                starknet_crypto::pedersen_hash(&a, &b)
+            );
+
+            assert_eq!(stark_set_pedersen_table_size(0), StarkResult::Success);
+            assert_eq!(stark_get_pedersen_table_size(), 0);
+        }
+    }
+}
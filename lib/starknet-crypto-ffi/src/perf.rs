@@ -0,0 +1,216 @@
+//! Runtime performance counters (the `profiling` feature)
+//!
+//! With `profiling`, the hot exported functions count their calls and the
+//! time spent in them per operation class, and `stark_perf_counters` reads
+//! the totals: enough to see where a host spends its time in the library
+//! and whether a batch API would pay off. Without the feature the timers
+//! compile to nothing and the two functions are not exported.
+//!
+//! A call is timed from entry to return, argument checks included; a call
+//! that fails still counts. Counters are process-wide and only ever grow
+//! until `stark_perf_counters_reset`.
+
+#[cfg(feature = "profiling")]
+use core::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "profiling")]
+use std::time::Instant;
+
+#[cfg(feature = "profiling")]
+use crate::{ffi_guard, StarkResult};
+
+/// Calls and cumulative time of one operation class
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StarkPerfCounter {
+    pub calls: u64,
+    pub nanos: u64,
+}
+
+/// Counters per operation class, as read by `stark_perf_counters`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StarkPerfCounters {
+    /// felt_add, felt_sub, felt_mul, felt_div, felt_neg, felt_inverse,
+    /// felt_pow(_reduced), felt_sqrt, the `_assign` variants and the
+    /// `felt_batch` functions (one call per batch)
+    pub felt_arith: StarkPerfCounter,
+    pub pedersen: StarkPerfCounter,
+    /// starknet_poseidon_hash, starknet_poseidon_hash_many(_arena),
+    /// poseidon_hash_with_domain, starknet_poseidon_hash_felt_array, Poseidon
+    /// starknet_merkle_root, the vector commitment functions and the
+    /// transcript appends and challenges
+    pub poseidon: StarkPerfCounter,
+    /// keccak256, starknet_keccak256, stark_grind_nonce and the event
+    /// selector functions
+    pub keccak: StarkPerfCounter,
+    /// STARK ECDSA signing (starknet_sign and its variants)
+    pub sign: StarkPerfCounter,
+    /// Single STARK ECDSA verifications (starknet_verify, verify contexts,
+    /// starknet_verify_full)
+    pub verify: StarkPerfCounter,
+    /// starknet_verify_batch and starknet_verify_batch_soa (one call per
+    /// batch)
+    pub verify_batch: StarkPerfCounter,
+    pub recover: StarkPerfCounter,
+    pub typed_data: StarkPerfCounter,
+    /// starknet_sign_invoke_v3
+    pub transaction: StarkPerfCounter,
+}
+
+/// Operation classes, in `StarkPerfCounters` field order
+// Classes of subsystems left out of the build are never timed
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Op {
+    FeltArith,
+    Pedersen,
+    Poseidon,
+    Keccak,
+    Sign,
+    Verify,
+    VerifyBatch,
+    Recover,
+    TypedData,
+    Transaction,
+}
+
+#[cfg(feature = "profiling")]
+const OPS: usize = Op::Transaction as usize + 1;
+
+#[cfg(feature = "profiling")]
+struct Counter {
+    calls: AtomicU64,
+    nanos: AtomicU64,
+}
+
+#[cfg(feature = "profiling")]
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: Counter = Counter {
+    calls: AtomicU64::new(0),
+    nanos: AtomicU64::new(0),
+};
+
+#[cfg(feature = "profiling")]
+static COUNTERS: [Counter; OPS] = [ZERO; OPS];
+
+/// Adds the time until it is dropped to its class
+#[cfg(feature = "profiling")]
+pub(crate) struct Timer {
+    op: Op,
+    start: Instant,
+}
+
+#[cfg(feature = "profiling")]
+impl Drop for Timer {
+    fn drop(&mut self) {
+        let nanos = u64::try_from(self.start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+        let counter = &COUNTERS[self.op as usize];
+        counter.calls.fetch_add(1, Ordering::Relaxed);
+        counter.nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+/// Does nothing without `profiling`
+#[cfg(not(feature = "profiling"))]
+pub(crate) struct Timer;
+
+/// Time the rest of the calling scope as one `op` call:
+/// `let _timer = perf::timer(Op::Pedersen);`
+#[inline(always)]
+pub(crate) fn timer(op: Op) -> Timer {
+    #[cfg(feature = "profiling")]
+    return Timer {
+        op,
+        start: Instant::now(),
+    };
+    #[cfg(not(feature = "profiling"))]
+    {
+        let _ = op;
+        Timer
+    }
+}
+
+#[cfg(feature = "profiling")]
+fn read(op: Op) -> StarkPerfCounter {
+    let counter = &COUNTERS[op as usize];
+    StarkPerfCounter {
+        calls: counter.calls.load(Ordering::Relaxed),
+        nanos: counter.nanos.load(Ordering::Relaxed),
+    }
+}
+
+/// Read the call counts and cumulative time per operation class
+///
+/// Only exported by builds with the `profiling` feature. Counters of calls
+/// running on other threads may be read mid-update (calls and time are
+/// read separately).
+#[cfg(feature = "profiling")]
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_perf_counters")]
+pub unsafe extern "C" fn stark_perf_counters(out_counters: *mut StarkPerfCounters) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_counters);
+        *out_counters = StarkPerfCounters {
+            felt_arith: read(Op::FeltArith),
+            pedersen: read(Op::Pedersen),
+            poseidon: read(Op::Poseidon),
+            keccak: read(Op::Keccak),
+            sign: read(Op::Sign),
+            verify: read(Op::Verify),
+            verify_batch: read(Op::VerifyBatch),
+            recover: read(Op::Recover),
+            typed_data: read(Op::TypedData),
+            transaction: read(Op::Transaction),
+        };
+        StarkResult::Success
+    })
+}
+
+/// Set every counter back to zero
+///
+/// Only exported by builds with the `profiling` feature.
+#[cfg(feature = "profiling")]
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_stark_perf_counters_reset"
+)]
+pub extern "C" fn stark_perf_counters_reset() -> StarkResult {
+    ffi_guard(|| {
+        for counter in &COUNTERS {
+            counter.calls.store(0, Ordering::Relaxed);
+            counter.nanos.store(0, Ordering::Relaxed);
+        }
+        StarkResult::Success
+    })
+}
+
+#[cfg(all(test, feature = "profiling", feature = "hashing"))]
+mod tests {
+    use super::*;
+    use crate::starknet_pedersen_hash;
+
+    #[test]
+    fn test_perf_counters_count_calls() {
+        let mut before = StarkPerfCounters::default();
+        let mut after = StarkPerfCounters::default();
+        let (a, b, mut out) = ([0u8; 32], [1u8; 32], [0u8; 32]);
+        unsafe {
+            assert_eq!(stark_perf_counters(&mut before), StarkResult::Success);
+            for _ in 0..3 {
+                assert_eq!(
+                    starknet_pedersen_hash(&a, &b, &mut out),
+                    StarkResult::Success
+                );
+            }
+            assert_eq!(stark_perf_counters(&mut after), StarkResult::Success);
+            assert_eq!(
+                stark_perf_counters(core::ptr::null_mut()),
+                StarkResult::NullPointer
+            );
+        }
+        // Other tests may hash concurrently: at least our three calls
+        assert!(after.pedersen.calls >= before.pedersen.calls + 3);
+        assert!(after.pedersen.nanos > before.pedersen.nanos);
+    }
+}
@@ -0,0 +1,66 @@
+//! Fixed-window scalar multiplication tables
+//!
+//! Shared by STARK ECDSA verification, ECIES and Schnorr, which all multiply
+//! the generator (or a fixed key) by many scalars.
+
+use alloc::vec::Vec;
+
+use starknet_crypto::Felt;
+use starknet_types_core::curve::{AffinePoint, ProjectivePoint};
+
+use crate::sync::OnceLock;
+
+/// Multiples `0..16` of a point, for fixed 4-bit window scalar multiplication
+pub(crate) struct PointTable(Vec<ProjectivePoint>);
+
+impl PointTable {
+    pub(crate) fn new(point: &ProjectivePoint) -> Self {
+        let mut multiples = Vec::with_capacity(16);
+        multiples.push(ProjectivePoint::identity());
+        for i in 1..16 {
+            let next = &multiples[i - 1] + point;
+            multiples.push(next);
+        }
+        Self(multiples)
+    }
+
+    pub(crate) fn mul(&self, scalar: &Felt) -> ProjectivePoint {
+        let mut acc = ProjectivePoint::identity();
+        for byte in scalar.to_bytes_be() {
+            for nibble in [byte >> 4, byte & 0x0f] {
+                acc = acc.double().double().double().double();
+                if nibble != 0 {
+                    acc += &self.0[nibble as usize];
+                }
+            }
+        }
+        acc
+    }
+}
+
+pub(crate) fn generator_table() -> &'static PointTable {
+    static TABLE: OnceLock<PointTable> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let generator = AffinePoint::generator();
+        PointTable::new(&ProjectivePoint::from_affine_unchecked(
+            generator.x(),
+            generator.y(),
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_table_mul() {
+        let generator = AffinePoint::generator();
+        let point = ProjectivePoint::from_affine_unchecked(generator.x(), generator.y());
+        let scalar = Felt::from_hex_unchecked("0x7fffffffffffffffffffffff1234");
+
+        let expected = (&point * scalar).to_affine().unwrap();
+        let actual = PointTable::new(&point).mul(&scalar).to_affine().unwrap();
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,178 @@
+//! Thread pool for batch operations
+//!
+//! Batch functions (such as `starknet_verify_batch`) spread their items over
+//! a rayon pool owned by the library, never rayon's global pool, which the
+//! host may be using itself. The pool has, in order of precedence:
+//!
+//! - the count last given to `stark_set_thread_count`,
+//! - the `STARK_THREADS` environment variable, read when the pool is built,
+//! - one thread per core.
+//!
+//! A count of 1 runs batches on the calling thread without starting any
+//! thread. Without `std` there are no threads: batches always run on the
+//! calling thread.
+
+// Batch operations so far: ECDSA verification, nonce grinding
+#![cfg_attr(not(any(feature = "ecdsa", feature = "hashing")), allow(dead_code))]
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+#[cfg(feature = "std")]
+use rayon::prelude::*;
+#[cfg(feature = "std")]
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+#[cfg(feature = "std")]
+use crate::sync::Mutex;
+use crate::{ffi_guard, StarkResult};
+
+/// Environment variable giving the default thread count
+pub const THREADS_ENV: &str = "STARK_THREADS";
+
+/// Count set by `stark_set_thread_count` (0: not set)
+#[cfg(feature = "std")]
+static THREAD_COUNT: Mutex<usize> = Mutex::new(0);
+/// Built on first use; dropped when the count changes (batches still
+/// running keep their `Arc`)
+#[cfg(feature = "std")]
+static POOL: Mutex<Option<Arc<ThreadPool>>> = Mutex::new(None);
+
+#[cfg(feature = "std")]
+fn env_thread_count() -> Option<usize> {
+    let value = std::env::var(THREADS_ENV).ok()?;
+    match value.trim().parse() {
+        Ok(0) | Err(_) => {
+            log!(
+                Warn,
+                "ignoring {THREADS_ENV}={value:?} (expected a positive integer)"
+            );
+            None
+        }
+        Ok(count) => Some(count),
+    }
+}
+
+/// Number of threads batches run on
+#[cfg(feature = "std")]
+fn thread_count() -> usize {
+    match *THREAD_COUNT.lock() {
+        0 => env_thread_count()
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |count| count.get())),
+        count => count,
+    }
+}
+
+#[cfg(not(feature = "std"))]
+fn thread_count() -> usize {
+    1
+}
+
+/// The pool, or `None` to run on the calling thread
+#[cfg(feature = "std")]
+fn pool() -> Option<Arc<ThreadPool>> {
+    let mut pool = POOL.lock();
+    if pool.is_none() {
+        let count = thread_count();
+        if count == 1 {
+            return None;
+        }
+        match ThreadPoolBuilder::new()
+            .num_threads(count)
+            .thread_name(|i| format!("stark-{i}"))
+            .build()
+        {
+            Ok(built) => *pool = Some(Arc::new(built)),
+            Err(e) => {
+                log!(
+                    Warn,
+                    "cannot start {count} threads ({e}), running batches inline"
+                );
+                return None;
+            }
+        }
+    }
+    pool.clone()
+}
+
+/// `f` applied to each item, on the pool's threads
+///
+/// Without a pool the items are mapped sequentially: a rayon iterator
+/// outside `ThreadPool::install` would run on rayon's global pool.
+#[cfg_attr(not(feature = "ecdsa"), allow(dead_code))]
+pub(crate) fn map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    #[cfg(feature = "std")]
+    if let Some(pool) = pool() {
+        return pool.install(|| items.par_iter().map(f).collect());
+    }
+    items.iter().map(f).collect()
+}
+
+/// `f(i)` for each `i` in `0..count`, on the pool's threads (sequentially
+/// without a pool, as `map`)
+pub(crate) fn map_indices<R: Send>(count: usize, f: impl Fn(usize) -> R + Sync + Send) -> Vec<R> {
+    #[cfg(feature = "std")]
+    if let Some(pool) = pool() {
+        return pool.install(|| (0..count).into_par_iter().map(f).collect());
+    }
+    (0..count).map(f).collect()
+}
+
+/// Set the number of threads batch operations use
+///
+/// 0 restores the default (`STARK_THREADS`, else one per core). The pool is
+/// rebuilt on the next batch call; batches already running finish on the
+/// old one. Without `std` the count is ignored (batches run on the calling
+/// thread).
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_set_thread_count")]
+pub extern "C" fn stark_set_thread_count(count: usize) -> StarkResult {
+    ffi_guard(|| {
+        #[cfg(feature = "std")]
+        {
+            *THREAD_COUNT.lock() = count;
+            *POOL.lock() = None;
+            log!(Info, "batch thread count set to {}", thread_count());
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = count;
+        StarkResult::Success
+    })
+}
+
+/// Number of threads the next batch operation will use
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_get_thread_count")]
+pub extern "C" fn stark_get_thread_count() -> usize {
+    let mut count = 1;
+    ffi_guard(|| {
+        count = thread_count();
+        StarkResult::Success
+    });
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_thread_count() {
+        assert_eq!(stark_set_thread_count(3), StarkResult::Success);
+        assert_eq!(stark_get_thread_count(), 3);
+        assert_eq!(pool().unwrap().install(rayon::current_num_threads), 3);
+
+        // One thread: every item on the calling thread, none on rayon's
+        // global pool
+        assert_eq!(stark_set_thread_count(1), StarkResult::Success);
+        assert!(pool().is_none());
+        let threads = map_indices(64, |_| rayon::current_thread_index());
+        assert!(threads.iter().all(Option::is_none));
+        let threads = map(&[0u8; 64], |_| rayon::current_thread_index());
+        assert!(threads.iter().all(Option::is_none));
+
+        assert_eq!(stark_set_thread_count(0), StarkResult::Success);
+        assert!(stark_get_thread_count() >= 1);
+    }
+}
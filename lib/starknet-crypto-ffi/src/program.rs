@@ -0,0 +1,169 @@
+//! Cairo 0 program hash
+//!
+//! SHARP and the bootloader identify a compiled Cairo 0 program by
+//! `compute_program_hash_chain` (cairo-lang): the Pedersen hash chain
+//!
+//! `h(n, h(version, h(main, h(builtin_count, h(builtins..., h(data...))))))`
+//!
+//! where `n` is the number of elements after it, `version` the bootloader
+//! version (0 so far), `main` the pc of the program's `main` function,
+//! builtin names are read as short strings and `data` is the bytecode. A
+//! fact registered by the bootloader is tied to this hash.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use serde_json::Value;
+use starknet_crypto::Felt;
+
+use crate::error::invalid_input;
+use crate::pedersen::pedersen_hash;
+use crate::perf::{self, Op};
+use crate::{bytes_from_raw, felt_to_bytes, ffi_guard, FeltBytes, StarkResult};
+
+/// The Starknet field, as compiled programs state it
+const PRIME: &str = "0x800000000000011000000000000000000000000000000000000000000000001";
+
+fn field<'a>(program: &'a Value, name: &str) -> Result<&'a Value, String> {
+    program.get(name).ok_or(format!("missing `{name}`"))
+}
+
+/// Elements hashed after the bootloader version's length prefix
+fn hash_chain_data(program: &Value, version: u64) -> Result<Vec<Felt>, String> {
+    if let Some(prime) = program.get("prime") {
+        if prime.as_str() != Some(PRIME) {
+            return Err(format!("`prime` is {prime} (expected the Starknet prime)"));
+        }
+    }
+    let scope = match program.get("main_scope") {
+        Some(scope) => scope.as_str().ok_or("`main_scope` is not a string")?,
+        None => "__main__",
+    };
+    let main = field(program, "identifiers")?
+        .get(format!("{scope}.main"))
+        .and_then(|main| main.get("pc"))
+        .and_then(Value::as_u64)
+        .ok_or(format!("no `{scope}.main` function"))?;
+    let builtins = field(program, "builtins")?
+        .as_array()
+        .ok_or("`builtins` is not an array")?;
+    let data = field(program, "data")?
+        .as_array()
+        .ok_or("`data` is not an array")?;
+
+    let mut chain = Vec::with_capacity(3 + builtins.len() + data.len());
+    chain.extend([
+        Felt::from(version),
+        Felt::from(main),
+        Felt::from(builtins.len()),
+    ]);
+    for builtin in builtins {
+        match builtin.as_str() {
+            Some(name) if name.len() <= 31 && name.is_ascii() => {
+                chain.push(Felt::from_bytes_be_slice(name.as_bytes()))
+            }
+            _ => return Err(format!("builtin {builtin} is not a short string")),
+        }
+    }
+    for (i, word) in data.iter().enumerate() {
+        match word.as_str().map(Felt::from_hex) {
+            Some(Ok(word)) => chain.push(word),
+            _ => return Err(format!("`data[{i}]` is not a hex felt")),
+        }
+    }
+    Ok(chain)
+}
+
+/// `compute_hash_chain([len(chain)] + chain)`
+fn program_hash(program: &Value, version: u64) -> Result<Felt, String> {
+    let chain = hash_chain_data(program, version)?;
+    let last = chain.len() - 1;
+    let hash = chain[..last]
+        .iter()
+        .rfold(chain[last], |acc, element| pedersen_hash(element, &acc));
+    Ok(pedersen_hash(&Felt::from(chain.len()), &hash))
+}
+
+/// Compute the bootloader hash of a compiled Cairo 0 program
+///
+/// `program_json` is the `cairo-compile` output (not NUL-terminated) and
+/// `version` the bootloader version (0 for all current bootloaders).
+/// Returns `InvalidInput` if the JSON is malformed, has no `main`, or is
+/// compiled for another prime.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_cairo_program_hash")]
+pub unsafe extern "C" fn cairo_program_hash(
+    program_json: *const u8,
+    len: usize,
+    version: u64,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Pedersen);
+        check_null!(program_json, out);
+        let program: Value = match bytes_from_raw(program_json, len).map(serde_json::from_slice) {
+            Some(Ok(program)) => program,
+            _ => return invalid_input("`program_json` is not JSON"),
+        };
+        match program_hash(&program, version) {
+            Ok(hash) => {
+                *out = felt_to_bytes(&hash);
+                StarkResult::Success
+            }
+            Err(e) => invalid_input(format!("`program_json`: {e}")),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &str = r#"{
+        "prime": "0x800000000000011000000000000000000000000000000000000000000000001",
+        "builtins": ["output", "pedersen"],
+        "data": ["0x40780017fff7fff", "0x1", "0x208b7fff7fff7ffe"],
+        "identifiers": {
+            "__main__.main": {"decorators": [], "pc": 2, "type": "function"}
+        },
+        "main_scope": "__main__"
+    }"#;
+
+    fn hash(json: &str, version: u64) -> (StarkResult, FeltBytes) {
+        let mut out = [0u8; 32];
+        let result = unsafe { cairo_program_hash(json.as_ptr(), json.len(), version, &mut out) };
+        (result, out)
+    }
+
+    // The expected hash is the chain written out by hand, not
+    // `cairo-hash-program` output: neither cairo-lang nor a compiled program
+    // with a published hash is available to this build.
+    #[test]
+    fn test_program_hash() {
+        let h = |a: u64, b: Felt| pedersen_hash(&Felt::from(a), &b);
+        let output = Felt::from_bytes_be_slice(b"output");
+        let pedersen = Felt::from_bytes_be_slice(b"pedersen");
+        let data = pedersen_hash(
+            &Felt::from(0x40780017fff7fffu64),
+            &h(1, Felt::from(0x208b7fff7fff7ffeu64)),
+        );
+        let builtins = pedersen_hash(&output, &pedersen_hash(&pedersen, &data));
+        let expected = h(8, h(0, h(2, h(2, builtins))));
+
+        let (result, out) = hash(PROGRAM, 0);
+        assert_eq!(result, StarkResult::Success);
+        assert_eq!(out, felt_to_bytes(&expected));
+        assert_ne!(hash(PROGRAM, 1).1, out);
+    }
+
+    #[test]
+    fn test_program_hash_rejects_malformed_programs() {
+        let other_prime = PROGRAM.replace("0x8000", "0x9000");
+        let no_main = PROGRAM.replace("__main__.main", "__main__.start");
+        let bad_data = PROGRAM.replace("\"0x1\"", "\"one\"");
+        for json in ["{", "[]", &other_prime, &no_main, &bad_data] {
+            assert_eq!(hash(json, 0).0, StarkResult::InvalidInput);
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Python extension module (`python` feature)
+//!
+//! Built with the feature, the shared library is also a CPython extension:
+//! copied to `starknet_crypto_ffi.so` (`.pyd` on Windows), it can be
+//! imported directly. Each function calls the extern function of the same
+//! operation, so scripts reproduce exactly the hashes of the C API.
+//!
+//! ```python
+//! import starknet_crypto_ffi as sc
+//! h = sc.invoke_v3_hash(sender_address=..., calldata=[...], nonce=..., chain_id=...)
+//! ```
+//!
+//! Felts are 32-byte big-endian `bytes`, amounts and prices are `int`s and
+//! resource bounds are `(max_amount, max_price_per_unit)` tuples. Malformed
+//! arguments raise `ValueError`; a failure of the operation raises
+//! `StarkError` carrying the `stark_last_error_message` text.
+
+use alloc::vec::Vec;
+use core::ffi::CStr;
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use zeroize::Zeroizing;
+
+use crate::address::starknet_contract_address;
+use crate::error::stark_last_error_message;
+use crate::transaction::{self, StarknetInvokeV3, StarknetResourceBounds, DA_MODE_L1};
+use crate::typed_data::starknet_typed_data_hash;
+use crate::{
+    felt_from_bytes, felt_to_bytes, ffi_guard, keccak256 as ffi_keccak256, starknet_get_public_key,
+    starknet_keccak256, starknet_pedersen_hash, starknet_poseidon_hash,
+    starknet_poseidon_hash_many, starknet_sign, starknet_verify, FeltBytes, StarkResult,
+};
+
+create_exception!(
+    starknet_crypto_ffi,
+    StarkError,
+    PyException,
+    "A library call failed (the message is the last-error text)"
+);
+
+fn felt_arg(value: &[u8], name: &str) -> PyResult<FeltBytes> {
+    value.try_into().map_err(|_| {
+        PyValueError::new_err(format!("`{name}` must be 32 bytes, got {}", value.len()))
+    })
+}
+
+fn felts_arg(values: &[Vec<u8>], name: &str) -> PyResult<Vec<FeltBytes>> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| felt_arg(value, &format!("{name}[{i}]")))
+        .collect()
+}
+
+fn bounds_arg((max_amount, max_price_per_unit): (u64, u128)) -> StarknetResourceBounds {
+    let mut price = [0u8; 32];
+    price[16..].copy_from_slice(&max_price_per_unit.to_be_bytes());
+    StarknetResourceBounds {
+        max_amount,
+        max_price_per_unit: price,
+    }
+}
+
+/// `Ok` on success, otherwise a `StarkError` with the last-error message
+fn check(result: StarkResult) -> PyResult<()> {
+    if result == StarkResult::Success {
+        return Ok(());
+    }
+    let message = unsafe { CStr::from_ptr(stark_last_error_message()) };
+    Err(StarkError::new_err(message.to_string_lossy().into_owned()))
+}
+
+fn felt_out(py: Python<'_>, felt: FeltBytes) -> Bound<'_, PyBytes> {
+    PyBytes::new(py, &felt)
+}
+
+#[pyfunction]
+fn pedersen_hash<'py>(py: Python<'py>, a: &[u8], b: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let (a, b) = (felt_arg(a, "a")?, felt_arg(b, "b")?);
+    let mut out = [0u8; 32];
+    check(unsafe { starknet_pedersen_hash(&a, &b, &mut out) })?;
+    Ok(felt_out(py, out))
+}
+
+#[pyfunction]
+fn poseidon_hash<'py>(py: Python<'py>, a: &[u8], b: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let (a, b) = (felt_arg(a, "a")?, felt_arg(b, "b")?);
+    let mut out = [0u8; 32];
+    check(unsafe { starknet_poseidon_hash(&a, &b, &mut out) })?;
+    Ok(felt_out(py, out))
+}
+
+#[pyfunction]
+fn poseidon_hash_many(py: Python<'_>, inputs: Vec<Vec<u8>>) -> PyResult<Bound<'_, PyBytes>> {
+    let inputs = felts_arg(&inputs, "inputs")?;
+    let mut out = [0u8; 32];
+    check(unsafe { starknet_poseidon_hash_many(inputs.as_ptr(), inputs.len(), &mut out) })?;
+    Ok(felt_out(py, out))
+}
+
+/// Keccak-256 of `data` (all 256 bits)
+#[pyfunction]
+fn keccak256<'py>(py: Python<'py>, data: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let mut out = [0u8; 32];
+    check(unsafe { ffi_keccak256(data.as_ptr(), data.len(), &mut out) })?;
+    Ok(felt_out(py, out))
+}
+
+/// Starknet Keccak of `data` (Keccak-256 truncated to 250 bits, as used for
+/// selectors)
+#[pyfunction]
+fn starknet_keccak<'py>(py: Python<'py>, data: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let mut out = [0u8; 32];
+    check(unsafe { starknet_keccak256(data.as_ptr(), data.len(), &mut out) })?;
+    Ok(felt_out(py, out))
+}
+
+#[pyfunction]
+fn get_public_key<'py>(py: Python<'py>, private_key: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let private_key = Zeroizing::new(felt_arg(private_key, "private_key")?);
+    let mut out = [0u8; 32];
+    check(unsafe { starknet_get_public_key(&*private_key, &mut out) })?;
+    Ok(felt_out(py, out))
+}
+
+/// Sign `message_hash` (RFC 6979 deterministic nonce), returning `(r, s)`
+#[pyfunction]
+fn sign<'py>(
+    py: Python<'py>,
+    private_key: &[u8],
+    message_hash: &[u8],
+) -> PyResult<(Bound<'py, PyBytes>, Bound<'py, PyBytes>)> {
+    let private_key = Zeroizing::new(felt_arg(private_key, "private_key")?);
+    let message_hash = felt_arg(message_hash, "message_hash")?;
+    let (mut r, mut s) = ([0u8; 32], [0u8; 32]);
+    check(unsafe { starknet_sign(&*private_key, &message_hash, &mut r, &mut s) })?;
+    Ok((felt_out(py, r), felt_out(py, s)))
+}
+
+/// Whether `(r, s)` is a valid signature of `message_hash` by `public_key`
+#[pyfunction]
+fn verify(public_key: &[u8], message_hash: &[u8], r: &[u8], s: &[u8]) -> PyResult<bool> {
+    let public_key = felt_arg(public_key, "public_key")?;
+    let message_hash = felt_arg(message_hash, "message_hash")?;
+    let (r, s) = (felt_arg(r, "r")?, felt_arg(s, "s")?);
+    match unsafe { starknet_verify(&public_key, &message_hash, &r, &s) } {
+        StarkResult::InvalidSignature => Ok(false),
+        result => check(result).map(|()| true),
+    }
+}
+
+/// Hash of an INVOKE v3 transaction on `chain_id`
+#[pyfunction]
+#[pyo3(signature = (
+    *,
+    sender_address,
+    calldata,
+    nonce,
+    chain_id,
+    tip = 0,
+    l1_gas = (0, 0),
+    l2_gas = (0, 0),
+    l1_data_gas = (0, 0),
+    paymaster_data = Vec::new(),
+    account_deployment_data = Vec::new(),
+    nonce_data_availability_mode = DA_MODE_L1,
+    fee_data_availability_mode = DA_MODE_L1,
+))]
+#[allow(clippy::too_many_arguments)]
+fn invoke_v3_hash<'py>(
+    py: Python<'py>,
+    sender_address: &[u8],
+    calldata: Vec<Vec<u8>>,
+    nonce: &[u8],
+    chain_id: &[u8],
+    tip: u64,
+    l1_gas: (u64, u128),
+    l2_gas: (u64, u128),
+    l1_data_gas: (u64, u128),
+    paymaster_data: Vec<Vec<u8>>,
+    account_deployment_data: Vec<Vec<u8>>,
+    nonce_data_availability_mode: u32,
+    fee_data_availability_mode: u32,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let calldata = felts_arg(&calldata, "calldata")?;
+    let paymaster_data = felts_arg(&paymaster_data, "paymaster_data")?;
+    let account_deployment_data = felts_arg(&account_deployment_data, "account_deployment_data")?;
+    let raw = StarknetInvokeV3 {
+        sender_address: felt_arg(sender_address, "sender_address")?,
+        calldata: calldata.as_ptr(),
+        calldata_len: calldata.len(),
+        nonce: felt_arg(nonce, "nonce")?,
+        tip,
+        l1_gas: bounds_arg(l1_gas),
+        l2_gas: bounds_arg(l2_gas),
+        l1_data_gas: bounds_arg(l1_data_gas),
+        paymaster_data: paymaster_data.as_ptr(),
+        paymaster_data_len: paymaster_data.len(),
+        account_deployment_data: account_deployment_data.as_ptr(),
+        account_deployment_data_len: account_deployment_data.len(),
+        nonce_data_availability_mode,
+        fee_data_availability_mode,
+    };
+    let chain_id = felt_arg(chain_id, "chain_id")?;
+
+    let mut out = [0u8; 32];
+    check(ffi_guard(|| {
+        let chain_id = match felt_from_bytes(&chain_id) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        match unsafe { transaction::invoke_v3_hash(&raw, &chain_id) } {
+            Ok(hash) => {
+                out = felt_to_bytes(&hash);
+                StarkResult::Success
+            }
+            Err(e) => e,
+        }
+    }))?;
+    Ok(felt_out(py, out))
+}
+
+/// SNIP-12 message hash of the typed data `json` for `account_address`
+#[pyfunction]
+fn typed_data_hash<'py>(
+    py: Python<'py>,
+    json: &str,
+    account_address: &[u8],
+) -> PyResult<Bound<'py, PyBytes>> {
+    let account_address = felt_arg(account_address, "account_address")?;
+    let mut out = [0u8; 32];
+    check(unsafe {
+        starknet_typed_data_hash(json.as_ptr(), json.len(), &account_address, &mut out)
+    })?;
+    Ok(felt_out(py, out))
+}
+
+/// Address of a contract deployed by `deployer` (zero for a deploy account
+/// transaction)
+#[pyfunction]
+fn contract_address<'py>(
+    py: Python<'py>,
+    deployer: &[u8],
+    salt: &[u8],
+    class_hash: &[u8],
+    constructor_calldata: Vec<Vec<u8>>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let deployer = felt_arg(deployer, "deployer")?;
+    let salt = felt_arg(salt, "salt")?;
+    let class_hash = felt_arg(class_hash, "class_hash")?;
+    let calldata = felts_arg(&constructor_calldata, "constructor_calldata")?;
+    let mut out = [0u8; 32];
+    check(unsafe {
+        starknet_contract_address(
+            &deployer,
+            &salt,
+            &class_hash,
+            calldata.as_ptr(),
+            calldata.len(),
+            &mut out,
+        )
+    })?;
+    Ok(felt_out(py, out))
+}
+
+#[pymodule]
+fn starknet_crypto_ffi(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("StarkError", m.py().get_type::<StarkError>())?;
+    m.add_function(wrap_pyfunction!(pedersen_hash, m)?)?;
+    m.add_function(wrap_pyfunction!(poseidon_hash, m)?)?;
+    m.add_function(wrap_pyfunction!(poseidon_hash_many, m)?)?;
+    m.add_function(wrap_pyfunction!(keccak256, m)?)?;
+    m.add_function(wrap_pyfunction!(starknet_keccak, m)?)?;
+    m.add_function(wrap_pyfunction!(get_public_key, m)?)?;
+    m.add_function(wrap_pyfunction!(sign, m)?)?;
+    m.add_function(wrap_pyfunction!(verify, m)?)?;
+    m.add_function(wrap_pyfunction!(invoke_v3_hash, m)?)?;
+    m.add_function(wrap_pyfunction!(typed_data_hash, m)?)?;
+    m.add_function(wrap_pyfunction!(contract_address, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds_arg_packs_price() {
+        let bounds = bounds_arg((7, 0x0102));
+        assert_eq!(bounds.max_amount, 7);
+        assert_eq!(bounds.max_price_per_unit[..30], [0u8; 30]);
+        assert_eq!(bounds.max_price_per_unit[30..], [0x01, 0x02]);
+    }
+}
@@ -0,0 +1,195 @@
+//! OS randomness
+//!
+//! All randomness used by the library goes through `fill_random`, backed by
+//! the operating system CSPRNG (`getrandom`).
+//!
+//! With the `test-rng` feature, `stark_set_test_rng` swaps the OS CSPRNG for
+//! a seeded deterministic stream so integration tests get reproducible keys
+//! and salts. Never ship a build with this feature enabled.
+
+use starknet_crypto::Felt;
+
+use crate::{ffi_guard, StarkResult, EC_ORDER};
+
+/// Fill `dest` with bytes from the OS CSPRNG (or the test RNG, if set)
+pub(crate) fn fill_random(dest: &mut [u8]) -> Result<(), getrandom::Error> {
+    #[cfg(feature = "test-rng")]
+    if test_rng::fill(dest) {
+        return Ok(());
+    }
+    getrandom::fill(dest)
+}
+
+/// Fill `out` with `len` cryptographically secure random bytes
+///
+/// For salts, nonces and session tokens. `out` may be null when `len` is 0.
+/// Returns `RngFailed` if the OS random number generator is unavailable.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_random_bytes")]
+pub unsafe extern "C" fn stark_random_bytes(out: *mut u8, len: usize) -> StarkResult {
+    ffi_guard(|| {
+        if len == 0 {
+            return StarkResult::Success;
+        }
+        check_null!(out);
+
+        match fill_random(core::slice::from_raw_parts_mut(out, len)) {
+            Ok(()) => StarkResult::Success,
+            Err(_) => StarkResult::RngFailed,
+        }
+    })
+}
+
+/// Deterministic stream: block i is `sha256(seed || i)` (i as big-endian u64)
+#[cfg(feature = "test-rng")]
+mod test_rng {
+    use sha2::{Digest, Sha256};
+
+    use crate::sync::Mutex;
+
+    struct State {
+        seed: [u8; 32],
+        counter: u64,
+    }
+
+    static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+    pub(super) fn set(seed: Option<[u8; 32]>) {
+        let mut state = STATE.lock();
+        *state = seed.map(|seed| State { seed, counter: 0 });
+    }
+
+    /// Returns false (leaving `dest` untouched) when no seed is set
+    pub(super) fn fill(dest: &mut [u8]) -> bool {
+        let mut state = STATE.lock();
+        let state = match state.as_mut() {
+            Some(s) => s,
+            None => return false,
+        };
+        for chunk in dest.chunks_mut(32) {
+            let block = Sha256::new()
+                .chain_update(state.seed)
+                .chain_update(state.counter.to_be_bytes())
+                .finalize();
+            chunk.copy_from_slice(&block[..chunk.len()]);
+            state.counter += 1;
+        }
+        true
+    }
+}
+
+/// Make all library randomness deterministic, derived from `seed`
+///
+/// Pass null to go back to the OS CSPRNG. Only exported by builds with the
+/// `test-rng` feature.
+#[cfg(feature = "test-rng")]
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_set_test_rng")]
+pub unsafe extern "C" fn stark_set_test_rng(seed: *const [u8; 32]) -> StarkResult {
+    ffi_guard(|| {
+        if seed.is_null() {
+            test_rng::set(None);
+            log!(Info, "test RNG disabled, using the OS CSPRNG");
+        } else {
+            test_rng::set(Some(*seed));
+            log!(
+                Warn,
+                "deterministic test RNG enabled: all randomness is predictable"
+            );
+        }
+        StarkResult::Success
+    })
+}
+
+/// Sample a private key uniformly from `[1, n)` by rejection sampling.
+///
+/// Candidates are masked to 252 bits (n is just above 2^251), so on average
+/// fewer than two draws are needed.
+pub(crate) fn sample_private_key<E>(
+    mut fill: impl FnMut(&mut [u8]) -> Result<(), E>,
+) -> Result<Felt, E> {
+    let order = EC_ORDER.to_bytes_be();
+    loop {
+        let mut candidate = [0u8; 32];
+        fill(&mut candidate)?;
+        candidate[0] &= 0x0f;
+        // Big-endian byte arrays compare like the integers they encode
+        if candidate < order && candidate != [0u8; 32] {
+            return Ok(Felt::from_bytes_be(&candidate));
+        }
+    }
+}
+
+#[cfg_attr(not(any(feature = "ecdsa", feature = "ecies")), allow(dead_code))]
+pub(crate) fn random_private_key() -> Result<Felt, getrandom::Error> {
+    sample_private_key(fill_random)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_private_key_rejects_out_of_range() {
+        let draws = [
+            [0u8; 32],               // zero
+            EC_ORDER.to_bytes_be(),  // n
+            [0xff; 32],              // masked to 2^252 - 1 >= n
+            Felt::TWO.to_bytes_be(), // accepted
+        ];
+        let mut next = draws.iter();
+        let key = sample_private_key(|dest: &mut [u8]| -> Result<(), ()> {
+            dest.copy_from_slice(next.next().unwrap());
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(key, Felt::TWO);
+        assert!(next.next().is_none());
+    }
+
+    #[test]
+    fn test_random_bytes() {
+        let mut first = [0u8; 32];
+        let mut second = [0u8; 32];
+
+        unsafe {
+            assert_eq!(
+                stark_random_bytes(first.as_mut_ptr(), 32),
+                StarkResult::Success
+            );
+            assert_eq!(
+                stark_random_bytes(second.as_mut_ptr(), 32),
+                StarkResult::Success
+            );
+            assert_eq!(
+                stark_random_bytes(core::ptr::null_mut(), 0),
+                StarkResult::Success
+            );
+            assert_eq!(
+                stark_random_bytes(core::ptr::null_mut(), 1),
+                StarkResult::NullPointer
+            );
+        }
+        assert_ne!(first, second);
+    }
+
+    #[cfg(feature = "test-rng")]
+    #[test]
+    fn test_set_test_rng_is_reproducible() {
+        let seed = [7u8; 32];
+        let mut first = [0u8; 48];
+        let mut second = [0u8; 48];
+
+        unsafe {
+            stark_set_test_rng(&seed);
+            fill_random(&mut first).unwrap();
+            stark_set_test_rng(&seed);
+            fill_random(&mut second).unwrap();
+            stark_set_test_rng(core::ptr::null());
+        }
+        assert_eq!(first, second);
+        // Each 32-byte block uses the next counter value
+        assert_ne!(first[..16], first[32..]);
+    }
+}
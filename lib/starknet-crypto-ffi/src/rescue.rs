@@ -0,0 +1,306 @@
+//! Rescue-Prime hash over the Stark field
+//!
+//! An alternative algebraic hash to Poseidon, for comparing arithmetization
+//! costs behind the same ABI. It follows the Rescue-Prime specification
+//! (Szepieniec, Ashur and Dhooghe, "Rescue-Prime: a Standard
+//! Specification", 2020) with its reference parameter choices for the Stark
+//! prime p = 2^251 + 17·2^192 + 1, a state of 3 felts (rate 2, capacity 1)
+//! and 128-bit security:
+//!
+//! - S-box `x^3` (3 is the smallest α coprime to p - 1) and its inverse
+//!   `x^(1/3)`, with 1/3 taken mod p - 1;
+//! - 18 rounds (the Gröbner-basis bound of 12, plus 50%);
+//! - the MDS matrix from the systematic Vandermonde construction over the
+//!   primitive element 3;
+//! - round constants from SHAKE256 of `Rescue-XLIX(p,3,1,128)`, 33 bytes
+//!   little-endian per constant, reduced mod p.
+//!
+//! Hashing pads the inputs with a 1 then zeros to a multiple of the rate,
+//! absorbs them two at a time into a zero state, and outputs the first state
+//! element. There is no Cairo builtin for it: use it for measurements, not
+//! for anything a Starknet contract must recompute.
+
+use alloc::vec::Vec;
+
+use starknet_crypto::Felt;
+
+use crate::{
+    error::null_pointer, felt_to_bytes, felts_from_raw, ffi_guard, FeltBytes, StarkResult,
+};
+
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+const ROUNDS: usize = 18;
+
+/// 1/3 mod p - 1
+const ALPHA_INV: Felt =
+    Felt::from_hex_unchecked("0x0555555555555560aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaab");
+
+/// Small entries of the MDS matrix (the others are negative)
+const MDS: [[i64; WIDTH]; WIDTH] = [[27, -39, 13], [351, -480, 130], [3510, -4719, 1210]];
+
+/// `x^(1/3)`: 4-bit fixed windows over `ALPHA_INV` (a third faster than the
+/// generic `pow_felt`)
+fn inverse_sbox(x: Felt) -> Felt {
+    let mut powers = [Felt::ONE; 16];
+    for i in 1..16 {
+        powers[i] = powers[i - 1] * x;
+    }
+    ALPHA_INV.to_bytes_be().iter().fold(Felt::ONE, |acc, byte| {
+        let acc = acc.square().square().square().square() * powers[(byte >> 4) as usize];
+        acc.square().square().square().square() * powers[(byte & 15) as usize]
+    })
+}
+
+fn mds(state: &mut [Felt; WIDTH]) {
+    let input = *state;
+    for (out, row) in state.iter_mut().zip(MDS) {
+        *out = row
+            .iter()
+            .zip(&input)
+            .fold(Felt::ZERO, |acc, (m, x)| acc + Felt::from(*m) * x);
+    }
+}
+
+fn add_constants(state: &mut [Felt; WIDTH], constants: &[Felt]) {
+    for (x, c) in state.iter_mut().zip(constants) {
+        *x += c;
+    }
+}
+
+fn permute(state: &mut [Felt; WIDTH]) {
+    for constants in ROUND_CONSTANTS.chunks_exact(2 * WIDTH) {
+        for x in state.iter_mut() {
+            *x = *x * *x * *x;
+        }
+        mds(state);
+        add_constants(state, &constants[..WIDTH]);
+        for x in state.iter_mut() {
+            *x = inverse_sbox(*x);
+        }
+        mds(state);
+        add_constants(state, &constants[WIDTH..]);
+    }
+}
+
+pub(crate) fn rescue_prime_hash_many(inputs: &[Felt]) -> Felt {
+    let mut padded = inputs.to_vec();
+    padded.push(Felt::ONE);
+    padded.resize(padded.len().div_ceil(RATE) * RATE, Felt::ZERO);
+    let mut state = [Felt::ZERO; WIDTH];
+    for block in padded.chunks_exact(RATE) {
+        add_constants(&mut state, block);
+        permute(&mut state);
+    }
+    state[0]
+}
+
+/// Rescue-Prime hash of `count` felts (see the module docs for the
+/// parameters)
+///
+/// `inputs` may be null when `count == 0`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_rescue_prime_hash")]
+pub unsafe extern "C" fn rescue_prime_hash(
+    inputs: *const FeltBytes,
+    count: usize,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out);
+        let inputs: Vec<Felt> = match felts_from_raw(inputs, count) {
+            Some(inputs) => inputs,
+            None => return null_pointer("inputs"),
+        };
+        *out = felt_to_bytes(&rescue_prime_hash_many(&inputs));
+        StarkResult::Success
+    })
+}
+
+/// Two round constant vectors per round, in order
+#[rustfmt::skip]
+const ROUND_CONSTANTS: [Felt; 2 * WIDTH * ROUNDS] = [
+    // Round 0
+    Felt::from_hex_unchecked("0x075ab0351cbe1947f763ad432c55db3b10ef097a13ba142d826680cfb42453d4"),
+    Felt::from_hex_unchecked("0x0734a1a426866069e82ec89de5c1d63d67661ce52aae67b2679dfef67abf0cd2"),
+    Felt::from_hex_unchecked("0x053414656e83346ab03677ae63fe75de165e75fb33cbda3458cd536e5627397d"),
+    Felt::from_hex_unchecked("0x02d020c9f612545fa06aee21871a6f0f4a332b3397954a8b8fe26608cd6e2d10"),
+    Felt::from_hex_unchecked("0x01db31881c88669f60c69f670adb518f100d1c02fadbfa08510d31cc0e3faeb0"),
+    Felt::from_hex_unchecked("0x01a892a7077ff8baad0a0d57399f9697174846a7ccda778208ae7ea2344b10af"),
+    // Round 1
+    Felt::from_hex_unchecked("0x01d2e5dbd81bc9ce850237ff7008dc5ec27e00ed9fcc9431e62b261e0919d413"),
+    Felt::from_hex_unchecked("0x05d64b08f530a7d8d2323a78952eb6fb95d84e3e4f1e04997ab86c45b66d4039"),
+    Felt::from_hex_unchecked("0x002573112412ed9ee26e0f88f9f33fce132d15c705ba15b17a13d36313f7e6ae"),
+    Felt::from_hex_unchecked("0x060611fdef60b95e536e69e1d5412ddb1b2cc6fba9ab02c8f8dbb553788368a7"),
+    Felt::from_hex_unchecked("0x01a6d853a8c203ce20cbc4a0b8e15115566d5e3800ff937e817c4e3f3e44a3bb"),
+    Felt::from_hex_unchecked("0x008778d6fb3c69b009afd6e5c92d773d3692bf620b08c58e204d1bde212d3506"),
+    // Round 2
+    Felt::from_hex_unchecked("0x032221229a09419cf09a71087c408d57b76dc4400dc43291a3eb22d76c6ab6ad"),
+    Felt::from_hex_unchecked("0x03ed5194d4c67d1567e34a7b90dacb25d8e37f376851821ff2a69714a8ec77a6"),
+    Felt::from_hex_unchecked("0x034eda43c49537bf6890e1efe7945bfc60d6499dc450c1caf4e129d617573e62"),
+    Felt::from_hex_unchecked("0x057d9d489a991d7922febff9eb2e2addb87a08e0f1fbde4725b3e175d7505a60"),
+    Felt::from_hex_unchecked("0x01355008cee554d1bb56fcc207a50ecefea7ffb67a159f45d452bb9e937ed495"),
+    Felt::from_hex_unchecked("0x07a0ee58e26426644b4203288a698383ba02d05d0c7a2402b92dde7c06a2c7dc"),
+    // Round 3
+    Felt::from_hex_unchecked("0x079654dd1e9e17ab4f3552f61f7d1b08ab79deae19879a5fe210295318a6297f"),
+    Felt::from_hex_unchecked("0x021f243515791582f74d5d41d9b6a3d01ace833aae623c207b1d9bf8fee0df3a"),
+    Felt::from_hex_unchecked("0x015d5c4d4e73d1cb76c8dc7ec3eab185ff576880c0976438f053543b004d0281"),
+    Felt::from_hex_unchecked("0x02ec8c3f8e8383d7c760647fcfe6b4ac7e9b65f130814a17adf5fc7348bce20e"),
+    Felt::from_hex_unchecked("0x053a7057927ec1266afcdbdd0ccb340f175bee12750e713d9af688115df5cc18"),
+    Felt::from_hex_unchecked("0x0061b30b1307fd071071ecfb16c1138dc3e38ffff443782514f96bd83898fee1"),
+    // Round 4
+    Felt::from_hex_unchecked("0x01c1419fa7a7d88df133e7787551865e575c636ea0078963c55e41252140e573"),
+    Felt::from_hex_unchecked("0x050f5c8415468642a01664f405377cb10919a89a85fe5275a2dba385e7a71691"),
+    Felt::from_hex_unchecked("0x072e9c165036a3f778e53d24e88c0654c314e311d9102ac9b57a9ee618a1227a"),
+    Felt::from_hex_unchecked("0x052335d28ae8e0152cfbd8968b53981604c0ee918c8049ca5bca2fda9cdf1552"),
+    Felt::from_hex_unchecked("0x0155395a6adf343e12ac2e910adea99dd924f85f479231c6c7b66f2f15e3e50d"),
+    Felt::from_hex_unchecked("0x03de107a80955f3b6210fb92f0830b3c2d92d7659763b9a8323a4d12fd3d7743"),
+    // Round 5
+    Felt::from_hex_unchecked("0x00d5897e18f37a7d061d2f9481f1f9f13c5bb312cf52989cd0330aef1f9715a7"),
+    Felt::from_hex_unchecked("0x05ae778cd9e78111bac5c9b86a46017a7a6b624d6112c5294ab637d163edb703"),
+    Felt::from_hex_unchecked("0x0363c671ff31ae91bdb9d154b9d6f5b356531f80f73f0be808328799b5bec9a6"),
+    Felt::from_hex_unchecked("0x03c5022d70cc5c39e3255b733b96523abe3f0ef47fc0e211faeead1c28906349"),
+    Felt::from_hex_unchecked("0x065eaf60bf65a38d7df8228aa81459e7a48a1e256f69c47076b6da6b3285f620"),
+    Felt::from_hex_unchecked("0x067f4ca69964cf1b53e1e18435d8d17bfab8bfebc09d540ed5e559683a80ba70"),
+    // Round 6
+    Felt::from_hex_unchecked("0x018fdf5f5fb4ee1a78b82cc346d621c5a24c196eaf534379a69aac934798a0ef"),
+    Felt::from_hex_unchecked("0x01ca5f3ea015ebadb0511966b458f3bc69f2053386d79b92a4827c696eee055d"),
+    Felt::from_hex_unchecked("0x049980469a369fdaa5cd2cb87268b5fa7414df2757208d54af4edf2828a121d9"),
+    Felt::from_hex_unchecked("0x01c0454e1748b4496e3ee393f99b500f7717620efd8e156819abaa86e960ce0b"),
+    Felt::from_hex_unchecked("0x07095e6f28df117cf9467e6a8ec8bf3538b49afacab13be0415cb3e66cdf2bd4"),
+    Felt::from_hex_unchecked("0x015732ff40be4ce07187efad2e30ec85c08128b87811e8a384c4b35c23511084"),
+    // Round 7
+    Felt::from_hex_unchecked("0x07a6c241f7e745b9ca1a744e47f8308f7fce88a2559187e5803b1a78145362fa"),
+    Felt::from_hex_unchecked("0x03b09cf69d66e91d927c571b57a64850ca2b9c44f1f16e367885dd76b08cd79b"),
+    Felt::from_hex_unchecked("0x000034c09218bac7e1ffc6636c810f2a4f6b4b9cd58674e979aa81d946ee5902"),
+    Felt::from_hex_unchecked("0x02506bb730e39d639e291f99afa4f8aad468ea129cc67cb64033760403b1d338"),
+    Felt::from_hex_unchecked("0x03fc15cbf9e9a1ac7b5d93c0b958991bd52deb63982df0f74fe35e44fa2befad"),
+    Felt::from_hex_unchecked("0x04d337627597bffdb05bbe10b569d9880361e3af4056c118bd9fcd1d0239a1af"),
+    // Round 8
+    Felt::from_hex_unchecked("0x07d8c32a350a2b79b6f051fa65597926e2157ea1155e3be601ea77a9060329ac"),
+    Felt::from_hex_unchecked("0x0539da6cfa2a2e5375b16055a1e7cea4674e9ee6642a0897788cbccc126ed3dd"),
+    Felt::from_hex_unchecked("0x053f4f8299ea5d7b59cbf9335ced5b1a95c6265bb2562be7fdf0327e9a93d560"),
+    Felt::from_hex_unchecked("0x03222d8aac424bc4d061811b810e9c9592cd2b197afed0261bee835c4979f6b5"),
+    Felt::from_hex_unchecked("0x01d717d3638b75044018b6e59d5fa67004a337e8c112ff50139157693f952c06"),
+    Felt::from_hex_unchecked("0x023a763d40bfa8bb38d99c51deea25884c75d77f4da73f88c277b9226bb8be50"),
+    // Round 9
+    Felt::from_hex_unchecked("0x02d0950ea918130316454a47d38cb11c59f8aefc6fae7914965a140a5c258b29"),
+    Felt::from_hex_unchecked("0x05568e9d7bc54d95968e405ee68d38e95c425c4e9b9f2e3bc3ef51fe000ca3d0"),
+    Felt::from_hex_unchecked("0x0544b9fab5a871f1d25bcfcd31574e006982fd6c43dacf5b61c1f2959859e105"),
+    Felt::from_hex_unchecked("0x06eff38b91ffd24af4a913bcfc5ec5ae7faa31b596d1a2b7fc6bfd8e309301ae"),
+    Felt::from_hex_unchecked("0x032d060ceaf767f0f49a720982a865114e363f8899ae81783109a325ee46a4ba"),
+    Felt::from_hex_unchecked("0x05aff8a174ac87b29318225f599f2147b983436e9686b22eb94c93c65c40f408"),
+    // Round 10
+    Felt::from_hex_unchecked("0x0629f7c1e1a6fe2b74f58618334e652914874a001d5b700e076c962d38e95f9d"),
+    Felt::from_hex_unchecked("0x02fa6adb9888a898c0420e036b2f2de8ef3f3b643315a27c77fd3546061156b6"),
+    Felt::from_hex_unchecked("0x01e4957167b66b00e8d3fe02389e5ab5931bd7aee779300492e79126f86ca2b0"),
+    Felt::from_hex_unchecked("0x07fa3220307f9f8ffa96a232ffe78a8e55f6c10cedc7cc0ad64798a6a41ccb0f"),
+    Felt::from_hex_unchecked("0x0747b5002704b7fa281ff6be64bc427e541cf1fd5cf99c5d286de3f327803fec"),
+    Felt::from_hex_unchecked("0x0149d751ab3275f626000daa6b4db24b5d68ceb2d74342327c67ecaf36059906"),
+    // Round 11
+    Felt::from_hex_unchecked("0x047835c9481150ee6c093a91f66b9dfbadc8650f22c33850b023e487b56d3c12"),
+    Felt::from_hex_unchecked("0x07e8e0aa56a8998ad236e329b41be09392d6e5b3551c9e02776997225b45a9c1"),
+    Felt::from_hex_unchecked("0x0088d7c05ab982d80f8e7366c06a2a84827f8ca267b645309dca233e5fe17bb3"),
+    Felt::from_hex_unchecked("0x0387d7a4c9b3a0a8a26ad6dc2326b3c953116d67d36868236e35167bd5878142"),
+    Felt::from_hex_unchecked("0x02e90ace787eacbb6b3005a39e46b9aee7a9c3c74c16b4c7f9d40070cb94bb50"),
+    Felt::from_hex_unchecked("0x037f2b997f00e73c54ae0cdfc314a2e810fc6e44dd3c8ee204b05dec7dfceb7b"),
+    // Round 12
+    Felt::from_hex_unchecked("0x068c334794cefb4f3ec277dc992565dc34394d2a9ac04b08e352e7f0b1d91ffd"),
+    Felt::from_hex_unchecked("0x02871b838a2a9aa337db75ae9ff5aef30293a6c8341fbb4754b86db4744111fb"),
+    Felt::from_hex_unchecked("0x021ae383370e3abeb467bae7a3cad285ebc4e92ad01f9cfa25fc56d58966ba32"),
+    Felt::from_hex_unchecked("0x0314bcfb1e8697188207b85bcd76912627d71b1566fd5b652c162e1f21e63569"),
+    Felt::from_hex_unchecked("0x02f310e9d065780210190ac071291a733382cc7048b2dab6e7ffb7d0a262abe7"),
+    Felt::from_hex_unchecked("0x06a4a085a97af4ad4dd6345128c408b80be472bbecc214390c24ee9f1825cf33"),
+    // Round 13
+    Felt::from_hex_unchecked("0x04f4aa311de2c14d6969a57eea998e1644f7f0bf1c3345de4eb309f2dbe9b0c6"),
+    Felt::from_hex_unchecked("0x064c79e2c125bd4773edf7beed4adece0dcbbd38993c86fd05170572b30008c9"),
+    Felt::from_hex_unchecked("0x0772db2efd6f0674ae583ca19058577c1588db90c040c777c619a7bd102ebc0b"),
+    Felt::from_hex_unchecked("0x019a55df64d8f0f930fc5b01ed910f670248f12e3faffdd02459bf58cfb8d584"),
+    Felt::from_hex_unchecked("0x065d03a3b169566c73640c8ffb7c10f3eb988875c13882990cbfaff82ca71e56"),
+    Felt::from_hex_unchecked("0x02c1438539b54066ae86f4292b7ecc13d739a540128a3ac864a315ebcf3f7502"),
+    // Round 14
+    Felt::from_hex_unchecked("0x01a1d1a11bddf47fe2b123300f4da50c53fbfedf812cdf25f351d70be90b7202"),
+    Felt::from_hex_unchecked("0x0594e508345120e5f6645febcba031080ecabeb2e96e93ebc27e2581e34b03c2"),
+    Felt::from_hex_unchecked("0x025ad0268db046ab42e89def3d7a45faa40bb37d82bb7384ee1a9d6ccec5dce9"),
+    Felt::from_hex_unchecked("0x052ddf4c63c4c2bd9ea018cc48ab91a89f8b18176c430c069fd2be59b4fd65cc"),
+    Felt::from_hex_unchecked("0x0422f9b7d3f3508e9bbdd1e8e5b33f946eb8b97ee0c729f578618597e2d11e71"),
+    Felt::from_hex_unchecked("0x032fd5b8e895d67bc43661e0f77706aaa0adc2a27f9b73709908bbbcb19fd996"),
+    // Round 15
+    Felt::from_hex_unchecked("0x05a0dafe6d94510c42b3a7878bc183c85eb2df8f1c467db3f5410b0cc7ba3bdb"),
+    Felt::from_hex_unchecked("0x06a244d58208b6ce4d1df13be8174ee246f3fbc206c5abe577fc29297412bc2c"),
+    Felt::from_hex_unchecked("0x05dfeeb6d8ddadcd5802b0b788254a9be4cfcf04e1c17263838788f636aeccc8"),
+    Felt::from_hex_unchecked("0x03251a0484c7d779d1bf932e891d4d5a554e21d66e68b29988b65837c0c175da"),
+    Felt::from_hex_unchecked("0x025d589be11d5734be154e99682c808b719b265d1018bdf3718b150f44722ca0"),
+    Felt::from_hex_unchecked("0x01a0b711b1dbb95b9628724b00a1e4d957972fb96eb5095f002c05edc455128a"),
+    // Round 16
+    Felt::from_hex_unchecked("0x029c66f8101d3dde7c7859abc333440a05fd977a94265db854648a80a9bb3053"),
+    Felt::from_hex_unchecked("0x03b3b326491c998fc1e55f7ebaaec6227e8355e5dddf836c470c558ad03bf7c9"),
+    Felt::from_hex_unchecked("0x01a33ca26d39915c52d431f8ff8108a241d0fd0a0788700e19131905ee98510b"),
+    Felt::from_hex_unchecked("0x063b69d90e2e28089f25534d0609564c72ca89385f929012e8cbea67fecbda30"),
+    Felt::from_hex_unchecked("0x0558019b1c77efa3a6b5d1bbf237cbd47ab47a47a0791e99f97aaab05efb4db5"),
+    Felt::from_hex_unchecked("0x07a083962335ea8189c44c4cde77612d722bd8c6480a5eb491764ac6062c6962"),
+    // Round 17
+    Felt::from_hex_unchecked("0x0188f85d052a7cafcad07e3fdb27963a0357cd6fafb558d57842d37f624a72fa"),
+    Felt::from_hex_unchecked("0x014024a498dc2bbcbf41a1fc713ac5eeb2ca04883a02dfdcd0cba93171506de3"),
+    Felt::from_hex_unchecked("0x024f9fe08f3cbea5ef20aa7cd49a10f660ca468f0dd1a6c83418eed174954c1c"),
+    Felt::from_hex_unchecked("0x03a3fdacdee8fb23d20b1cecafa3785dd8aac2e51c247e0cc87283b917432b4c"),
+    Felt::from_hex_unchecked("0x06124caa60e237376744277416bb0d465e45806f36f38d24907f2879810d2b05"),
+    Felt::from_hex_unchecked("0x077c48794f32eac55b8162268a89e4c1a9e7f62abfa33aa8b959959026cc7f9b"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sha3::digest::{ExtendableOutput, Update, XofReader};
+    use sha3::Shake256;
+
+    #[test]
+    fn test_round_constants_follow_the_specification() {
+        let seed = alloc::format!("Rescue-XLIX({},3,1,128)", Felt::MAX.to_biguint() + 1u32);
+        let mut reader = Shake256::default().chain(seed.as_bytes()).finalize_xof();
+        for constant in ROUND_CONSTANTS {
+            let mut chunk = [0u8; 33];
+            reader.read(&mut chunk);
+            assert_eq!(Felt::from_bytes_le_slice(&chunk), constant);
+        }
+        assert_eq!(
+            ALPHA_INV.to_biguint() * 3u32 % Felt::MAX.to_biguint(),
+            1u32.into()
+        );
+    }
+
+    #[test]
+    fn test_rescue_prime_hash() {
+        // Computed with a port of the specification's reference implementation
+        let expected = [
+            "0x45006c6511871d0854ef21872ade7e1c3c758eca7e0645fa028dc44bae0d1e1",
+            "0x7f7432b9b6a752eb045ce9a0286501efae6f321abb19f986da0f09c02ed6f31",
+            "0x56cf691b422234c931a01319d264ae1c33c74cb1eb1d114068b226780971789",
+            "0x4506755bff1c9d78eb48a0239d597adfd5e2773df8d007475dea87ce1152ea1",
+        ];
+        let mut out = [0u8; 32];
+        for (count, expected) in expected.iter().enumerate() {
+            let inputs: Vec<FeltBytes> = (1..=count as u64)
+                .map(|i| felt_to_bytes(&Felt::from(i)))
+                .collect();
+            let inputs_ptr = if count == 0 {
+                core::ptr::null()
+            } else {
+                inputs.as_ptr()
+            };
+            unsafe {
+                assert_eq!(
+                    rescue_prime_hash(inputs_ptr, count, &mut out),
+                    StarkResult::Success
+                );
+            }
+            assert_eq!(out, felt_to_bytes(&Felt::from_hex(expected).unwrap()));
+        }
+        // Padding keeps [] and [0] apart
+        assert_ne!(
+            rescue_prime_hash_many(&[Felt::ZERO]),
+            rescue_prime_hash_many(&[])
+        );
+    }
+}
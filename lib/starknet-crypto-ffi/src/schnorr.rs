@@ -0,0 +1,244 @@
+//! Schnorr signatures over the STARK curve
+//!
+//! Keys are the same as for STARK ECDSA: the public key is the x coordinate
+//! returned by `starknet_get_public_key`. As in BIP-340, points are x-only
+//! and implicitly lifted to their even-y representative; the signer negates
+//! its key or nonce when the actual point has an odd y.
+//!
+//! A signature is `(r, s)` where `r` is the x coordinate of the nonce point
+//! `R` and
+//!
+//! ```text
+//! e = poseidon_hash_many([r, public_key, message_hash]) mod n
+//! s = k + e·d mod n
+//! ```
+//!
+//! Verification checks `s·G - e·P == R`. The equation is linear in `s`, so
+//! many signatures can be checked together with one multi-scalar
+//! multiplication.
+//!
+//! The nonce is RFC6979 with a fixed domain tag as extra entropy, so it never
+//! coincides with the ECDSA nonce for the same key and message.
+
+use starknet_crypto::{poseidon_hash_many, rfc6979_generate_k, Felt};
+use starknet_types_core::curve::{AffinePoint, ProjectivePoint};
+use starknet_types_core::felt::NonZeroFelt;
+use zeroize::Zeroizing;
+
+use crate::point_table::generator_table;
+use crate::{felt_from_bytes, felt_to_bytes, ffi_guard, FeltBytes, StarkResult, EC_ORDER};
+
+/// 'STARK_SCHNORR' as a short string, mixed into RFC6979
+const NONCE_TAG: Felt = Felt::from_hex_unchecked("0x535441524b5f5343484e4f5252");
+
+fn is_odd(y: &Felt) -> bool {
+    y.to_bytes_be()[31] & 1 == 1
+}
+
+/// `a + b mod n` for `a, b < n` (n < p < 2n, so the felt sum may wrap)
+fn add_mod_order(a: &Felt, b: &Felt) -> Felt {
+    let complement = EC_ORDER - b;
+    if *a >= complement {
+        a - complement
+    } else {
+        a + b
+    }
+}
+
+fn challenge(r: &Felt, public_key: &Felt, message: &Felt) -> Felt {
+    poseidon_hash_many(&[*r, *public_key, *message])
+        .mod_floor(&NonZeroFelt::from_felt_unchecked(EC_ORDER))
+}
+
+/// Sign with a private key in `[1, n)`; returns `None` in the negligible case
+/// where `s` would be zero.
+fn sign(private_key: &Felt, message: &Felt) -> Option<(Felt, Felt)> {
+    let public_point = generator_table().mul(private_key).to_affine().ok()?;
+    let d = Zeroizing::new(if is_odd(&public_point.y()) {
+        EC_ORDER - private_key
+    } else {
+        *private_key
+    });
+
+    let k = Zeroizing::new(rfc6979_generate_k(message, private_key, Some(&NONCE_TAG)));
+    let nonce_point = generator_table().mul(&k).to_affine().ok()?;
+    let k = Zeroizing::new(if is_odd(&nonce_point.y()) {
+        EC_ORDER - *k
+    } else {
+        *k
+    });
+
+    let r = nonce_point.x();
+    let e = challenge(&r, &public_point.x(), message);
+    let order = NonZeroFelt::from_felt_unchecked(EC_ORDER);
+    let s = add_mod_order(&k, &e.mul_mod(&d, &order));
+    if s == Felt::ZERO {
+        return None;
+    }
+    Some((r, s))
+}
+
+/// Returns `None` for out-of-range inputs or a public key that is not on the
+/// curve.
+fn verify(public_key: &Felt, message: &Felt, r: &Felt, s: &Felt) -> Option<bool> {
+    if *s == Felt::ZERO || *s >= EC_ORDER {
+        return None;
+    }
+    let public_point = AffinePoint::new_from_x(public_key, false)?;
+    let public_point = ProjectivePoint::from_affine_unchecked(public_point.x(), public_point.y());
+
+    let e = challenge(r, public_key, message);
+    let nonce_point = generator_table().mul(s) - &public_point * e;
+    Some(
+        nonce_point
+            .to_affine()
+            .is_ok_and(|p| p.x() == *r && !is_odd(&p.y())),
+    )
+}
+
+/// Sign a message hash with a Poseidon-challenge Schnorr signature
+/// (returns r, s)
+///
+/// The key pair is the same as for `starknet_sign`. Returns
+/// `InvalidPrivateKey` unless 0 < key < n.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_schnorr_sign")]
+pub unsafe extern "C" fn stark_schnorr_sign(
+    private_key: *const FeltBytes,
+    message_hash: *const FeltBytes,
+    out_r: *mut FeltBytes,
+    out_s: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(private_key, message_hash, out_r, out_s);
+        let pk = match felt_from_bytes(&*private_key) {
+            Some(f) => Zeroizing::new(f),
+            None => return StarkResult::InvalidInput,
+        };
+        let msg = match felt_from_bytes(&*message_hash) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        if *pk == Felt::ZERO || *pk >= EC_ORDER {
+            return StarkResult::InvalidPrivateKey;
+        }
+
+        match sign(&pk, &msg) {
+            Some((r, s)) => {
+                *out_r = felt_to_bytes(&r);
+                *out_s = felt_to_bytes(&s);
+                StarkResult::Success
+            }
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+/// Verify a Schnorr signature against a public key (x coordinate)
+///
+/// Returns `InvalidInput` if the public key is not on the curve or s is out of
+/// range.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_schnorr_verify")]
+pub unsafe extern "C" fn stark_schnorr_verify(
+    public_key: *const FeltBytes,
+    message_hash: *const FeltBytes,
+    r: *const FeltBytes,
+    s: *const FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(public_key, message_hash, r, s);
+        let pk = match felt_from_bytes(&*public_key) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let msg = match felt_from_bytes(&*message_hash) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let r = match felt_from_bytes(&*r) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let s = match felt_from_bytes(&*s) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+
+        match verify(&pk, &msg, &r, &s) {
+            Some(true) => StarkResult::Success,
+            Some(false) => StarkResult::InvalidSignature,
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet_crypto::get_public_key;
+
+    #[test]
+    fn test_schnorr_sign_verify() {
+        for i in 1..8u64 {
+            let private_key = Felt::from(0x1234_5678u64 * i);
+            let public_key = felt_to_bytes(&get_public_key(&private_key));
+            let message_hash = felt_to_bytes(&Felt::from(1000 + i));
+            let mut r = [0u8; 32];
+            let mut s = [0u8; 32];
+
+            unsafe {
+                let result =
+                    stark_schnorr_sign(&felt_to_bytes(&private_key), &message_hash, &mut r, &mut s);
+                assert_eq!(result, StarkResult::Success);
+
+                let result = stark_schnorr_verify(&public_key, &message_hash, &r, &s);
+                assert_eq!(result, StarkResult::Success);
+
+                let other_hash = felt_to_bytes(&Felt::from(2000 + i));
+                let result = stark_schnorr_verify(&public_key, &other_hash, &r, &s);
+                assert_eq!(result, StarkResult::InvalidSignature);
+            }
+        }
+    }
+
+    #[test]
+    fn test_schnorr_nonce_differs_from_ecdsa() {
+        let private_key = Felt::from(42u64);
+        let message_hash = Felt::from(7u64);
+        let (r, _) = sign(&private_key, &message_hash).unwrap();
+
+        let k = rfc6979_generate_k(&message_hash, &private_key, None);
+        let ecdsa_r = generator_table().mul(&k).to_affine().unwrap().x();
+        assert_ne!(r, ecdsa_r);
+    }
+
+    #[test]
+    fn test_schnorr_rejects_invalid_inputs() {
+        let private_key = felt_to_bytes(&Felt::from(42u64));
+        let public_key = felt_to_bytes(&get_public_key(&Felt::from(42u64)));
+        let message_hash = felt_to_bytes(&Felt::from(7u64));
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+
+        unsafe {
+            let result = stark_schnorr_sign(&[0u8; 32], &message_hash, &mut r, &mut s);
+            assert_eq!(result, StarkResult::InvalidPrivateKey);
+
+            stark_schnorr_sign(&private_key, &message_hash, &mut r, &mut s);
+            let result = stark_schnorr_verify(&public_key, &message_hash, &r, &[0u8; 32]);
+            assert_eq!(result, StarkResult::InvalidInput);
+
+            let result =
+                stark_schnorr_verify(&public_key, &message_hash, &r, &felt_to_bytes(&EC_ORDER));
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn test_add_mod_order() {
+        let a = EC_ORDER - Felt::ONE;
+        assert_eq!(add_mod_order(&a, &Felt::TWO), Felt::ONE);
+        assert_eq!(add_mod_order(&Felt::ONE, &Felt::TWO), Felt::THREE);
+    }
+}
@@ -0,0 +1,215 @@
+//! Anonymous public-key encryption (libsodium sealed boxes)
+//!
+//! Byte-compatible with libsodium's `crypto_box_seal` / `crypto_box_seal_open`
+//! and the keys of the X25519 module. The sender encrypts with a fresh
+//! ephemeral X25519 key and forgets it, so the recipient learns nothing about
+//! who sent the message:
+//!
+//! ```text
+//! nonce  = BLAKE2b-192(ephemeral_public || recipient_public)
+//! sealed = ephemeral_public (32) || tag (16) || XSalsa20(crypto_box key, nonce)
+//! ```
+
+use alloc::vec::Vec;
+
+use blake2::digest::consts::U24;
+use blake2::{Blake2b, Digest};
+use crypto_box::aead::Aead;
+use crypto_box::{Nonce, PublicKey, SalsaBox, SecretKey};
+use zeroize::Zeroizing;
+
+use crate::{bytes_from_raw, error::null_pointer, ffi_guard, random, write_bytes_out, StarkResult};
+
+/// Poly1305 tag length
+const TAG_LEN: usize = 16;
+
+/// Bytes a sealed box adds to its message (ephemeral public key and tag)
+pub const SEALED_BOX_OVERHEAD: usize = 32 + TAG_LEN;
+
+fn seal_nonce(ephemeral_public: &PublicKey, recipient_public: &PublicKey) -> Nonce {
+    Blake2b::<U24>::new()
+        .chain_update(ephemeral_public.as_bytes())
+        .chain_update(recipient_public.as_bytes())
+        .finalize()
+}
+
+fn seal(recipient_public: &[u8; 32], ephemeral_secret: [u8; 32], msg: &[u8]) -> Vec<u8> {
+    let recipient_public = PublicKey::from(*recipient_public);
+    let ephemeral_secret = SecretKey::from(ephemeral_secret);
+    let ephemeral_public = ephemeral_secret.public_key();
+
+    let nonce = seal_nonce(&ephemeral_public, &recipient_public);
+    let ciphertext = SalsaBox::new(&recipient_public, &ephemeral_secret)
+        .encrypt(&nonce, msg)
+        .expect("message length is within the XSalsa20 limit");
+
+    let mut sealed = Vec::with_capacity(msg.len() + SEALED_BOX_OVERHEAD);
+    sealed.extend_from_slice(ephemeral_public.as_bytes());
+    sealed.extend(ciphertext);
+    sealed
+}
+
+fn open(recipient_secret: &[u8; 32], sealed: &[u8]) -> Option<Vec<u8>> {
+    if sealed.len() < SEALED_BOX_OVERHEAD {
+        return None;
+    }
+    let recipient_secret = SecretKey::from(*recipient_secret);
+    let (ephemeral_public, ciphertext) = sealed.split_at(32);
+    let ephemeral_public = PublicKey::from_slice(ephemeral_public).ok()?;
+
+    let nonce = seal_nonce(&ephemeral_public, &recipient_secret.public_key());
+    SalsaBox::new(&ephemeral_public, &recipient_secret)
+        .decrypt(&nonce, ciphertext)
+        .ok()
+}
+
+/// Encrypt a message anonymously to an X25519 public key
+/// (libsodium `crypto_box_seal`)
+///
+/// The output is `SEALED_BOX_OVERHEAD` bytes longer than the message and
+/// follows the usual `out_len` capacity convention. Returns `RngFailed` if
+/// the OS random number generator is unavailable.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_sealed_box_seal")]
+pub unsafe extern "C" fn sealed_box_seal(
+    recipient_public_key: *const [u8; 32],
+    msg: *const u8,
+    len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(recipient_public_key, out_len);
+        let msg = match bytes_from_raw(msg, len) {
+            Some(m) => m,
+            None => return null_pointer("msg"),
+        };
+        let mut ephemeral_secret = Zeroizing::new([0u8; 32]);
+        if random::fill_random(&mut ephemeral_secret[..]).is_err() {
+            return StarkResult::RngFailed;
+        }
+
+        let sealed = seal(&*recipient_public_key, *ephemeral_secret, msg);
+        write_bytes_out(&sealed, out, out_len)
+    })
+}
+
+/// Open a sealed box with the recipient's X25519 private key
+/// (libsodium `crypto_box_seal_open`)
+///
+/// Returns `DecryptionFailed` if the box is truncated, was sealed to another
+/// key or has been tampered with.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_sealed_box_open")]
+pub unsafe extern "C" fn sealed_box_open(
+    recipient_private_key: *const [u8; 32],
+    sealed: *const u8,
+    len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(recipient_private_key, out_len);
+        let sealed = match bytes_from_raw(sealed, len) {
+            Some(s) => s,
+            None => return null_pointer("sealed"),
+        };
+
+        match open(&*recipient_private_key, sealed) {
+            Some(msg) => write_bytes_out(&msg, out, out_len),
+            None => StarkResult::DecryptionFailed,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::x25519::x25519_get_public_key;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn private_key() -> [u8; 32] {
+        core::array::from_fn(|i| i as u8 + 1)
+    }
+
+    #[test]
+    fn test_sealed_box_open_libsodium() {
+        // Sealed by libsodium 1.0.18 crypto_box_seal to the key above
+        let sealed = hex("1c2e93d9e9a435823298206666e4154a15b541a7b8e7c01c83bac17ec11b401cc582b505b7457a3eb98327fae25271549ec05cd2e0a501646c76e282b50f9c4481937c791d");
+        let mut msg = [0u8; 21];
+        let mut len = msg.len();
+
+        unsafe {
+            let result = sealed_box_open(
+                &private_key(),
+                sealed.as_ptr(),
+                sealed.len(),
+                msg.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::Success);
+        }
+        assert_eq!(&msg, b"relay notification #1");
+    }
+
+    #[test]
+    fn test_sealed_box_roundtrip() {
+        let mut public_key = [0u8; 32];
+        let msg = b"new deposit";
+        let mut sealed = [0u8; 11 + SEALED_BOX_OVERHEAD];
+        let mut opened = [0u8; 11];
+
+        unsafe {
+            x25519_get_public_key(&private_key(), &mut public_key);
+            let mut len = sealed.len();
+            let result = sealed_box_seal(
+                &public_key,
+                msg.as_ptr(),
+                msg.len(),
+                sealed.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::Success);
+
+            let mut len = opened.len();
+            let result = sealed_box_open(
+                &private_key(),
+                sealed.as_ptr(),
+                sealed.len(),
+                opened.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(&opened, msg);
+
+            sealed[SEALED_BOX_OVERHEAD] ^= 1;
+            let mut len = opened.len();
+            let result = sealed_box_open(
+                &private_key(),
+                sealed.as_ptr(),
+                sealed.len(),
+                opened.as_mut_ptr(),
+                &mut len,
+            );
+            assert_eq!(result, StarkResult::DecryptionFailed);
+        }
+    }
+
+    #[test]
+    fn test_sealed_box_fixed_ephemeral() {
+        // Opens with libsodium crypto_box_seal_open
+        let expected = hex("57db4b359f23ae5e146e4e2512056704722506348c150c14753d0c933d04d4215a592281b08d793fdbb2ddeb2d66d4268bfcf233c1ef03914c4123fa325322");
+        let mut public_key = [0u8; 32];
+        unsafe { x25519_get_public_key(&private_key(), &mut public_key) };
+
+        let sealed = seal(&public_key, [9u8; 32], b"hello from rust");
+        assert_eq!(sealed, expected);
+        assert!(open(&private_key(), &sealed[..SEALED_BOX_OVERHEAD - 1]).is_none());
+    }
+}
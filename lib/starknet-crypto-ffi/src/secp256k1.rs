@@ -0,0 +1,249 @@
+//! secp256k1 ECDSA (Ethereum signatures)
+//!
+//! Messages are 32-byte prehashes (e.g. `keccak256` or
+//! `eth_personal_message_hash`). Signing is deterministic (RFC6979) and always
+//! produces low-s signatures; verification rejects high-s signatures as
+//! Ethereum does since EIP-2.
+//!
+//! Public keys are 64 bytes: uncompressed `x || y` without the SEC1 `0x04`
+//! prefix. The recovery id `v` is 0 or 1 (27 and 28 are also accepted by
+//! `secp256k1_recover`).
+
+use k256::ecdsa::signature::hazmat::PrehashVerifier;
+use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+
+use crate::{ffi_guard, StarkResult};
+
+pub(crate) fn public_key_bytes(key: &VerifyingKey) -> [u8; 64] {
+    let point = key.to_encoded_point(false);
+    let mut out = [0u8; 64];
+    out.copy_from_slice(&point.as_bytes()[1..]);
+    out
+}
+
+pub(crate) fn verifying_key(public_key: &[u8; 64]) -> Option<VerifyingKey> {
+    let mut sec1 = [0u8; 65];
+    sec1[0] = 0x04;
+    sec1[1..].copy_from_slice(public_key);
+    VerifyingKey::from_sec1_bytes(&sec1).ok()
+}
+
+/// Derive the uncompressed public key (x || y) from a private key
+///
+/// Returns `InvalidPrivateKey` unless 0 < key < n.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_secp256k1_get_public_key")]
+pub unsafe extern "C" fn secp256k1_get_public_key(
+    private_key: *const [u8; 32],
+    out: *mut [u8; 64],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(private_key, out);
+        let key = match SigningKey::from_slice(&*private_key) {
+            Ok(k) => k,
+            Err(_) => return StarkResult::InvalidPrivateKey,
+        };
+
+        *out = public_key_bytes(key.verifying_key());
+        StarkResult::Success
+    })
+}
+
+/// Sign a 32-byte message hash (returns r, s and the recovery id v)
+///
+/// The signature is deterministic (RFC6979) and low-s normalized.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_secp256k1_sign_recoverable")]
+pub unsafe extern "C" fn secp256k1_sign_recoverable(
+    private_key: *const [u8; 32],
+    message_hash: *const [u8; 32],
+    out_r: *mut [u8; 32],
+    out_s: *mut [u8; 32],
+    out_v: *mut u8,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(private_key, message_hash, out_r, out_s, out_v);
+        let key = match SigningKey::from_slice(&*private_key) {
+            Ok(k) => k,
+            Err(_) => return StarkResult::InvalidPrivateKey,
+        };
+
+        match key.sign_prehash_recoverable(&*message_hash) {
+            Ok((signature, recovery_id)) => {
+                let (r, s) = signature.split_bytes();
+                *out_r = r.into();
+                *out_s = s.into();
+                *out_v = recovery_id.to_byte();
+                StarkResult::Success
+            }
+            Err(_) => StarkResult::InvalidInput,
+        }
+    })
+}
+
+/// Verify a signature over a 32-byte message hash
+///
+/// Returns `InvalidSignature` for a wrong or high-s signature and
+/// `InvalidInput` if the public key is not on the curve or r/s are out of
+/// range.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_secp256k1_verify")]
+pub unsafe extern "C" fn secp256k1_verify(
+    public_key: *const [u8; 64],
+    message_hash: *const [u8; 32],
+    r: *const [u8; 32],
+    s: *const [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(public_key, message_hash, r, s);
+        let key = match verifying_key(&*public_key) {
+            Some(k) => k,
+            None => return StarkResult::InvalidInput,
+        };
+        let signature = match Signature::from_scalars(*r, *s) {
+            Ok(sig) => sig,
+            Err(_) => return StarkResult::InvalidInput,
+        };
+
+        match key.verify_prehash(&*message_hash, &signature) {
+            Ok(()) => StarkResult::Success,
+            Err(_) => StarkResult::InvalidSignature,
+        }
+    })
+}
+
+/// Recover the public key (x || y) from a signature and its recovery id
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_secp256k1_recover")]
+pub unsafe extern "C" fn secp256k1_recover(
+    message_hash: *const [u8; 32],
+    r: *const [u8; 32],
+    s: *const [u8; 32],
+    v: u8,
+    out: *mut [u8; 64],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(message_hash, r, s, out);
+        let recovery_id = match v {
+            0 | 1 => RecoveryId::from_byte(v),
+            27 | 28 => RecoveryId::from_byte(v - 27),
+            _ => None,
+        };
+        let recovery_id = match recovery_id {
+            Some(id) => id,
+            None => return StarkResult::InvalidInput,
+        };
+        let signature = match Signature::from_scalars(*r, *s) {
+            Ok(sig) => sig,
+            Err(_) => return StarkResult::InvalidInput,
+        };
+
+        match VerifyingKey::recover_from_prehash(&*message_hash, &signature, recovery_id) {
+            Ok(key) => {
+                *out = public_key_bytes(&key);
+                StarkResult::Success
+            }
+            Err(_) => StarkResult::RecoveryFailed,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex32(s: &str) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn test_secp256k1_get_public_key() {
+        // Private key 1 maps to the generator
+        let private_key = hex32("0000000000000000000000000000000000000000000000000000000000000001");
+        let mut public_key = [0u8; 64];
+
+        unsafe {
+            let result = secp256k1_get_public_key(&private_key, &mut public_key);
+            assert_eq!(result, StarkResult::Success);
+        }
+
+        let mut expected = [0u8; 64];
+        expected[..32].copy_from_slice(&hex32(
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        ));
+        expected[32..].copy_from_slice(&hex32(
+            "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+        ));
+        assert_eq!(public_key, expected);
+
+        unsafe {
+            let result = secp256k1_get_public_key(&[0u8; 32], &mut public_key);
+            assert_eq!(result, StarkResult::InvalidPrivateKey);
+        }
+    }
+
+    #[test]
+    fn test_secp256k1_sign_verify_recover() {
+        let private_key = hex32("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318");
+        let message_hash =
+            hex32("a1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2");
+        let mut public_key = [0u8; 64];
+        let mut recovered = [0u8; 64];
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        let mut v = 0u8;
+
+        unsafe {
+            secp256k1_get_public_key(&private_key, &mut public_key);
+
+            let result =
+                secp256k1_sign_recoverable(&private_key, &message_hash, &mut r, &mut s, &mut v);
+            assert_eq!(result, StarkResult::Success);
+            assert!(v <= 1);
+
+            let result = secp256k1_verify(&public_key, &message_hash, &r, &s);
+            assert_eq!(result, StarkResult::Success);
+
+            let result = secp256k1_recover(&message_hash, &r, &s, v, &mut recovered);
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(recovered, public_key);
+
+            // Ethereum-style v
+            let result = secp256k1_recover(&message_hash, &r, &s, v + 27, &mut recovered);
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(recovered, public_key);
+
+            let mut other_hash = message_hash;
+            other_hash[0] ^= 1;
+            let result = secp256k1_verify(&public_key, &other_hash, &r, &s);
+            assert_eq!(result, StarkResult::InvalidSignature);
+        }
+    }
+
+    #[test]
+    fn test_secp256k1_verify_rejects_high_s() {
+        let private_key = hex32("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318");
+        let message_hash = [7u8; 32];
+        let mut public_key = [0u8; 64];
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        let mut v = 0u8;
+
+        unsafe {
+            secp256k1_get_public_key(&private_key, &mut public_key);
+            secp256k1_sign_recoverable(&private_key, &message_hash, &mut r, &mut s, &mut v);
+        }
+
+        let signature = Signature::from_scalars(r, s).unwrap();
+        let high_s: [u8; 32] = (-*signature.s()).to_bytes().into();
+
+        unsafe {
+            let result = secp256k1_verify(&public_key, &message_hash, &r, &high_s);
+            assert_eq!(result, StarkResult::InvalidSignature);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! secp256r1 (P-256) ECDSA
+//!
+//! Used by Starknet accounts with hardware or passkey signers. Messages are
+//! 32-byte prehashes and public keys are 64 bytes (`x || y`), as in the
+//! secp256k1 module. Signing is deterministic (RFC6979) and normalized to
+//! low-s so the result is accepted by verifiers that reject malleable
+//! signatures; verification accepts both forms.
+
+use p256::ecdsa::signature::hazmat::{PrehashSigner, PrehashVerifier};
+use p256::ecdsa::{Signature, SigningKey, VerifyingKey};
+
+use crate::{ffi_guard, StarkResult};
+
+pub(crate) fn public_key_bytes(key: &VerifyingKey) -> [u8; 64] {
+    let point = key.to_encoded_point(false);
+    let mut out = [0u8; 64];
+    out.copy_from_slice(&point.as_bytes()[1..]);
+    out
+}
+
+pub(crate) fn verifying_key(public_key: &[u8; 64]) -> Option<VerifyingKey> {
+    let mut sec1 = [0u8; 65];
+    sec1[0] = 0x04;
+    sec1[1..].copy_from_slice(public_key);
+    VerifyingKey::from_sec1_bytes(&sec1).ok()
+}
+
+/// Verify a P-256 signature over a 32-byte prehash (either s form)
+pub(crate) fn verify_prehash(
+    key: &VerifyingKey,
+    message_hash: &[u8; 32],
+    r: &[u8; 32],
+    s: &[u8; 32],
+) -> Option<bool> {
+    let signature = Signature::from_scalars(*r, *s).ok()?;
+    Some(key.verify_prehash(message_hash, &signature).is_ok())
+}
+
+/// Derive the uncompressed public key (x || y) from a private key
+///
+/// Returns `InvalidPrivateKey` unless 0 < key < n.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_secp256r1_get_public_key")]
+pub unsafe extern "C" fn secp256r1_get_public_key(
+    private_key: *const [u8; 32],
+    out: *mut [u8; 64],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(private_key, out);
+        let key = match SigningKey::from_slice(&*private_key) {
+            Ok(k) => k,
+            Err(_) => return StarkResult::InvalidPrivateKey,
+        };
+
+        *out = public_key_bytes(key.verifying_key());
+        StarkResult::Success
+    })
+}
+
+/// Sign a 32-byte message hash (returns r, s)
+///
+/// The signature is deterministic (RFC6979) and low-s normalized.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_secp256r1_sign")]
+pub unsafe extern "C" fn secp256r1_sign(
+    private_key: *const [u8; 32],
+    message_hash: *const [u8; 32],
+    out_r: *mut [u8; 32],
+    out_s: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(private_key, message_hash, out_r, out_s);
+        let key = match SigningKey::from_slice(&*private_key) {
+            Ok(k) => k,
+            Err(_) => return StarkResult::InvalidPrivateKey,
+        };
+
+        let signature: Signature = match key.sign_prehash(&*message_hash) {
+            Ok(sig) => sig,
+            Err(_) => return StarkResult::InvalidInput,
+        };
+        let signature = signature.normalize_s().unwrap_or(signature);
+        let (r, s) = signature.split_bytes();
+        *out_r = r.into();
+        *out_s = s.into();
+        StarkResult::Success
+    })
+}
+
+/// Verify a signature over a 32-byte message hash
+///
+/// Returns `InvalidInput` if the public key is not on the curve or r/s are
+/// out of range.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_secp256r1_verify")]
+pub unsafe extern "C" fn secp256r1_verify(
+    public_key: *const [u8; 64],
+    message_hash: *const [u8; 32],
+    r: *const [u8; 32],
+    s: *const [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(public_key, message_hash, r, s);
+        let key = match verifying_key(&*public_key) {
+            Some(k) => k,
+            None => return StarkResult::InvalidInput,
+        };
+
+        match verify_prehash(&key, &*message_hash, &*r, &*s) {
+            Some(true) => StarkResult::Success,
+            Some(false) => StarkResult::InvalidSignature,
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex<const N: usize>(s: &str) -> [u8; N] {
+        let mut out = [0u8; N];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    // RFC 6979 A.2.5, P-256 with SHA-256, message "sample"
+    const PRIVATE_KEY: &str = "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721";
+    const PUBLIC_KEY: &str = "60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb67903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299";
+    const SAMPLE_SHA256: &str = "af2bdbe1aa9b6ec1e2ade1d694f41fc71a831d0268e9891562113d8a62add1bf";
+    const R: &str = "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716";
+    const S: &str = "f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8";
+
+    #[test]
+    fn test_secp256r1_get_public_key() {
+        let mut public_key = [0u8; 64];
+
+        unsafe {
+            let result = secp256r1_get_public_key(&hex(PRIVATE_KEY), &mut public_key);
+            assert_eq!(result, StarkResult::Success);
+        }
+        assert_eq!(public_key, hex::<64>(PUBLIC_KEY));
+    }
+
+    #[test]
+    fn test_secp256r1_sign_rfc6979() {
+        let message_hash = hex(SAMPLE_SHA256);
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+
+        unsafe {
+            let result = secp256r1_sign(&hex(PRIVATE_KEY), &message_hash, &mut r, &mut s);
+            assert_eq!(result, StarkResult::Success);
+        }
+
+        // The RFC signature has a high s; we return its low-s twin n - s
+        let expected = Signature::from_scalars(hex::<32>(R), hex::<32>(S)).unwrap();
+        let expected = expected.normalize_s().unwrap();
+        let (expected_r, expected_s) = expected.split_bytes();
+        assert_eq!(r, <[u8; 32]>::from(expected_r));
+        assert_eq!(s, <[u8; 32]>::from(expected_s));
+    }
+
+    #[test]
+    fn test_secp256r1_verify() {
+        let public_key = hex::<64>(PUBLIC_KEY);
+        let message_hash = hex(SAMPLE_SHA256);
+        let (r, s) = (hex::<32>(R), hex::<32>(S));
+
+        unsafe {
+            // High-s RFC signature is accepted
+            let result = secp256r1_verify(&public_key, &message_hash, &r, &s);
+            assert_eq!(result, StarkResult::Success);
+
+            let mut other_hash = message_hash;
+            other_hash[0] ^= 1;
+            let result = secp256r1_verify(&public_key, &other_hash, &r, &s);
+            assert_eq!(result, StarkResult::InvalidSignature);
+
+            let result = secp256r1_verify(&[0u8; 64], &message_hash, &r, &s);
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Wiping secrets from memory
+//!
+//! `stark_secure_zero` lets callers wipe their own copies of keys, seeds and
+//! plaintexts with writes the optimizer cannot elide.
+//!
+//! Inside the library, private keys, nonces and derived symmetric keys are
+//! held in `Zeroizing` wrappers and wiped when the function returns. This is
+//! best effort: `Felt` is `Copy`, and the copies made inside dependencies
+//! (starknet-crypto's signer, for instance) or by moves between stack frames
+//! are out of reach.
+//!
+//! Long-lived processes can keep keys in a `SecretBuffer` instead of their
+//! own memory. Its pages are dedicated to the buffer, locked into RAM with
+//! `mlock` so they never reach swap, excluded from core dumps on Linux
+//! (`MADV_DONTDUMP`) and FreeBSD (`MADV_NOCORE`), and wiped before they are
+//! released. Locking is only available on Unix; elsewhere (WASM has no swap)
+//! the buffer is ordinary memory that is still wiped on free.
+
+use alloc::alloc::{alloc_zeroed, dealloc, Layout};
+#[cfg(unix)]
+use alloc::format;
+use core::ptr::NonNull;
+use core::slice;
+
+use zeroize::Zeroize;
+
+use crate::handle::StarkHandle;
+use crate::{bytes_from_raw, error::null_pointer, ffi_guard, ffi_guard_void, StarkResult};
+#[cfg(feature = "ecdsa")]
+use crate::{starknet_sign, FeltBytes};
+
+/// Overwrite `len` bytes at `ptr` with zeros
+///
+/// The writes are volatile, so they are not removed even when the buffer is
+/// never read again. `ptr` may be null when `len` is 0.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_secure_zero")]
+pub unsafe extern "C" fn stark_secure_zero(ptr: *mut u8, len: usize) -> StarkResult {
+    ffi_guard(|| {
+        if len == 0 {
+            return StarkResult::Success;
+        }
+        check_null!(ptr);
+
+        slice::from_raw_parts_mut(ptr, len).zeroize();
+        StarkResult::Success
+    })
+}
+
+/// Locked, non-dumpable memory for a secret (opaque)
+pub struct SecretBuffer {
+    ptr: NonNull<u8>,
+    len: usize,
+    layout: Layout,
+}
+
+impl StarkHandle for SecretBuffer {
+    const NAME: &'static str = "SecretBuffer";
+}
+
+fn page_size() -> usize {
+    #[cfg(unix)]
+    {
+        match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+            size if size > 0 => size as usize,
+            _ => 4096,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        4096
+    }
+}
+
+#[cfg(unix)]
+unsafe fn lock_pages(ptr: *mut u8, size: usize) -> bool {
+    if libc::mlock(ptr.cast(), size) != 0 {
+        #[cfg(feature = "std")]
+        let detail = format!(
+            "mlock of {size} bytes failed: {}",
+            std::io::Error::last_os_error()
+        );
+        #[cfg(not(feature = "std"))]
+        let detail = format!("mlock of {size} bytes failed");
+        crate::error::set_detail(detail);
+        return false;
+    }
+    // Best effort: older kernels reject the advice, the pages stay locked
+    #[cfg(target_os = "linux")]
+    let advised = libc::madvise(ptr.cast(), size, libc::MADV_DONTDUMP) == 0;
+    #[cfg(target_os = "freebsd")]
+    let advised = libc::madvise(ptr.cast(), size, libc::MADV_NOCORE) == 0;
+    #[cfg(not(any(target_os = "linux", target_os = "freebsd")))]
+    let advised = true;
+    if !advised {
+        log!(
+            Debug,
+            "secret pages not excluded from core dumps (madvise failed)"
+        );
+    }
+    true
+}
+
+#[cfg(not(unix))]
+unsafe fn lock_pages(_ptr: *mut u8, _size: usize) -> bool {
+    log!(
+        Warn,
+        "memory locking is not supported on this platform: secret pages are not locked"
+    );
+    true
+}
+
+#[cfg(unix)]
+unsafe fn unlock_pages(ptr: *mut u8, size: usize) {
+    #[cfg(target_os = "linux")]
+    libc::madvise(ptr.cast(), size, libc::MADV_DODUMP);
+    #[cfg(target_os = "freebsd")]
+    libc::madvise(ptr.cast(), size, libc::MADV_CORE);
+    libc::munlock(ptr.cast(), size);
+}
+
+#[cfg(not(unix))]
+unsafe fn unlock_pages(_ptr: *mut u8, _size: usize) {}
+
+impl SecretBuffer {
+    /// Allocate `len` zeroed bytes on pages of their own and lock them
+    fn new(len: usize) -> Result<Self, StarkResult> {
+        let page = page_size();
+        let layout = len
+            .checked_next_multiple_of(page)
+            .and_then(|size| Layout::from_size_align(size, page).ok())
+            .ok_or(StarkResult::InvalidInput)?;
+        let ptr = NonNull::new(unsafe { alloc_zeroed(layout) }).ok_or(StarkResult::InvalidInput)?;
+
+        // Dropping the buffer on failure unlocks and frees the pages
+        let buffer = SecretBuffer { ptr, len, layout };
+        if !unsafe { lock_pages(ptr.as_ptr(), layout.size()) } {
+            return Err(StarkResult::MemoryLockFailed);
+        }
+        Ok(buffer)
+    }
+
+    #[cfg(feature = "ecdsa")]
+    fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for SecretBuffer {
+    fn drop(&mut self) {
+        let size = self.layout.size();
+        unsafe {
+            slice::from_raw_parts_mut(self.ptr.as_ptr(), size).zeroize();
+            unlock_pages(self.ptr.as_ptr(), size);
+            dealloc(self.ptr.as_ptr(), self.layout);
+        }
+    }
+}
+
+/// Allocate a locked secret buffer of `len` zeroed bytes
+///
+/// Writes an owned handle to `out_secret`; release it with `secret_free`.
+/// Returns `InvalidInput` if `len` is 0 and `MemoryLockFailed` if the pages
+/// cannot be locked (on Linux, usually `RLIMIT_MEMLOCK`).
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_secret_new")]
+pub unsafe extern "C" fn secret_new(len: usize, out_secret: *mut *mut SecretBuffer) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_secret);
+        if len == 0 {
+            return StarkResult::InvalidInput;
+        }
+
+        match SecretBuffer::new(len) {
+            Ok(buffer) => {
+                *out_secret = buffer.into_handle();
+                StarkResult::Success
+            }
+            Err(e) => e,
+        }
+    })
+}
+
+/// Copy `len` bytes from `data` into the buffer at `offset`
+///
+/// Returns `InvalidInput` if the write does not fit. The caller should wipe
+/// its own copy of the data afterwards (`stark_secure_zero`).
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_secret_write")]
+pub unsafe extern "C" fn secret_write(
+    secret: *mut SecretBuffer,
+    offset: usize,
+    data: *const u8,
+    len: usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(secret);
+        let secret = match SecretBuffer::from_handle_mut(secret) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+        let data = match bytes_from_raw(data, len) {
+            Some(d) => d,
+            None => return null_pointer("data"),
+        };
+        let buffer = secret.as_mut_slice();
+        let end = match offset.checked_add(len) {
+            Some(end) if end <= buffer.len() => end,
+            _ => return StarkResult::InvalidInput,
+        };
+
+        buffer[offset..end].copy_from_slice(data);
+        StarkResult::Success
+    })
+}
+
+/// Sign a message hash with the Stark private key held in a secret buffer
+///
+/// The buffer must be 32 bytes long (a big-endian felt). Otherwise the same
+/// result codes as `starknet_sign`.
+#[cfg(feature = "ecdsa")]
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_secret_use_for_sign")]
+pub unsafe extern "C" fn secret_use_for_sign(
+    secret: *const SecretBuffer,
+    message_hash: *const FeltBytes,
+    out_r: *mut FeltBytes,
+    out_s: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(secret, message_hash, out_r, out_s);
+        let secret = match SecretBuffer::from_handle(secret) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+        let private_key: &FeltBytes = match secret.as_slice().try_into() {
+            Ok(k) => k,
+            Err(_) => return StarkResult::InvalidInput,
+        };
+
+        starknet_sign(private_key, message_hash, out_r, out_s)
+    })
+}
+
+/// Wipe, unlock and free a secret buffer (null is a no-op)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_secret_free")]
+pub unsafe extern "C" fn secret_free(secret: *mut SecretBuffer) {
+    ffi_guard_void(|| SecretBuffer::free_handle(secret))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secure_zero() {
+        let mut secret = [0xa5u8; 40];
+
+        unsafe {
+            assert_eq!(
+                stark_secure_zero(secret.as_mut_ptr().add(4), 32),
+                StarkResult::Success
+            );
+            assert_eq!(
+                stark_secure_zero(core::ptr::null_mut(), 0),
+                StarkResult::Success
+            );
+            assert_eq!(
+                stark_secure_zero(core::ptr::null_mut(), 1),
+                StarkResult::NullPointer
+            );
+        }
+        assert_eq!(secret[..4], [0xa5; 4]);
+        assert_eq!(secret[4..36], [0; 32]);
+        assert_eq!(secret[36..], [0xa5; 4]);
+    }
+
+    #[cfg(feature = "ecdsa")]
+    #[test]
+    fn test_secret_buffer_sign() {
+        let mut private_key = [0u8; 32];
+        private_key[28..].copy_from_slice(&0x1234_5678u32.to_be_bytes());
+        let mut message_hash = [0u8; 32];
+        message_hash[31] = 0x2a;
+        let (mut r, mut s) = ([0u8; 32], [0u8; 32]);
+        let (mut expected_r, mut expected_s) = ([0u8; 32], [0u8; 32]);
+
+        unsafe {
+            let mut secret = core::ptr::null_mut();
+            assert_eq!(secret_new(32, &mut secret), StarkResult::Success);
+            assert_eq!((*secret).as_slice(), [0u8; 32]);
+            assert_eq!((*secret).ptr.as_ptr() as usize % page_size(), 0);
+
+            // Written in two halves to exercise the offset
+            assert_eq!(
+                secret_write(secret, 0, private_key.as_ptr(), 16),
+                StarkResult::Success
+            );
+            assert_eq!(
+                secret_write(secret, 16, private_key[16..].as_ptr(), 16),
+                StarkResult::Success
+            );
+            assert_eq!(
+                secret_write(secret, 17, private_key.as_ptr(), 16),
+                StarkResult::InvalidInput
+            );
+            assert_eq!(
+                secret_write(secret, usize::MAX, private_key.as_ptr(), 2),
+                StarkResult::InvalidInput
+            );
+
+            let result = secret_use_for_sign(secret, &message_hash, &mut r, &mut s);
+            assert_eq!(result, StarkResult::Success);
+            starknet_sign(
+                &private_key,
+                &message_hash,
+                &mut expected_r,
+                &mut expected_s,
+            );
+            assert_eq!((r, s), (expected_r, expected_s));
+
+            secret_free(secret);
+            secret_free(core::ptr::null_mut());
+        }
+    }
+
+    #[cfg(feature = "ecdsa")]
+    #[test]
+    fn test_secret_buffer_rejects_wrong_length() {
+        let message_hash = [1u8; 32];
+        let (mut r, mut s) = ([0u8; 32], [0u8; 32]);
+
+        unsafe {
+            let mut secret = core::ptr::null_mut();
+            assert_eq!(secret_new(0, &mut secret), StarkResult::InvalidInput);
+            assert_eq!(secret_new(64, &mut secret), StarkResult::Success);
+            let result = secret_use_for_sign(secret, &message_hash, &mut r, &mut s);
+            assert_eq!(result, StarkResult::InvalidInput);
+            secret_free(secret);
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! Known-answer self-test
+//!
+//! `stark_self_test` runs published test vectors through the exported
+//! functions, for deployments (FIPS-style) that must check at startup that
+//! the binary was neither miscompiled nor corrupted. The vectors come from
+//! the StarkEx signature test data (Pedersen, STARK keys and signatures),
+//! cairo-lang (Poseidon), the Keccak reference and web3.js (secp256k1).
+
+#![cfg_attr(
+    not(any(feature = "hashing", feature = "ecdsa", feature = "secp")),
+    allow(dead_code)
+)]
+
+#[cfg(feature = "secp")]
+use crate::secp256k1::{secp256k1_get_public_key, secp256k1_sign_recoverable, secp256k1_verify};
+#[cfg(feature = "ecdsa")]
+use crate::{starknet_get_public_key, starknet_sign_with_k, starknet_verify, FeltBytes};
+#[cfg(feature = "hashing")]
+use crate::{keccak256, starknet_keccak256, starknet_pedersen_hash, starknet_poseidon_hash};
+use crate::{error, ffi_guard, StarkResult};
+
+/// Parse a 64-digit hex vector
+fn hex32(hex: &str) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).expect("malformed self-test vector");
+    }
+    out
+}
+
+/// One known-answer check: `Err` names what did not match
+type Check = fn() -> Result<(), &'static str>;
+
+fn expect(
+    result: StarkResult,
+    expected: StarkResult,
+    what: &'static str,
+) -> Result<(), &'static str> {
+    if result == expected {
+        Ok(())
+    } else {
+        Err(what)
+    }
+}
+
+fn expect_eq<const N: usize>(
+    actual: &[u8; N],
+    expected: &[u8; N],
+    what: &'static str,
+) -> Result<(), &'static str> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(what)
+    }
+}
+
+#[cfg(feature = "hashing")]
+fn pedersen() -> Result<(), &'static str> {
+    let a = hex32("03d937c035c878245caf64531a5756109c53068da139362728feb561405371cb");
+    let b = hex32("0208a0a10250e382e1e4bbe2880906c2791bf6275695e02fbbc6aeff9cd8b31a");
+    let mut out = [0u8; 32];
+    let result = unsafe { starknet_pedersen_hash(&a, &b, &mut out) };
+    expect(result, StarkResult::Success, "pedersen_hash")?;
+    expect_eq(
+        &out,
+        &hex32("030e480bed5fe53fa909cc0f8c4d99b8f9f2c016be4c41e13a4848797979c662"),
+        "pedersen_hash",
+    )
+}
+
+#[cfg(feature = "hashing")]
+fn poseidon() -> Result<(), &'static str> {
+    let a = hex32("000b662f9017fa7956fd70e26129b1833e10ad000fd37b4d9f4e0ce6884b7bbe");
+    let b = hex32("001fe356bf76102cdae1bfbdc173602ead228b12904c00dad9cf16e035468bea");
+    let mut out = [0u8; 32];
+    let result = unsafe { starknet_poseidon_hash(&a, &b, &mut out) };
+    expect(result, StarkResult::Success, "poseidon_hash")?;
+    expect_eq(
+        &out,
+        &hex32("075540825a6ecc5dc7d7c2f5f868164182742227f1367d66c43ee51ec7937a81"),
+        "poseidon_hash",
+    )
+}
+
+#[cfg(feature = "hashing")]
+fn keccak() -> Result<(), &'static str> {
+    let mut out = [0u8; 32];
+    let result = unsafe { keccak256(b"".as_ptr(), 0, &mut out) };
+    expect(result, StarkResult::Success, "keccak256")?;
+    expect_eq(
+        &out,
+        &hex32("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"),
+        "keccak256",
+    )?;
+
+    // selector!("transfer")
+    let result = unsafe { starknet_keccak256(b"transfer".as_ptr(), 8, &mut out) };
+    expect(result, StarkResult::Success, "starknet_keccak256")?;
+    expect_eq(
+        &out,
+        &hex32("0083afd3f4caedc6eebf44246fe54e38c95e3179a5ec9ea81740eca5b482d12e"),
+        "starknet_keccak256",
+    )
+}
+
+#[cfg(feature = "ecdsa")]
+fn stark_ecdsa() -> Result<(), &'static str> {
+    let private_key = hex32("0000000000000000000000000000000000000000000000000000000000000001");
+    let message_hash = hex32("0000000000000000000000000000000000000000000000000000000000000002");
+    let k = hex32("0000000000000000000000000000000000000000000000000000000000000003");
+    let expected_public_key =
+        hex32("01ef15c18599971b7beced415a40f0c7deacfd9b0d1819e03d723d8bc943cfca");
+    let expected_r = hex32("0411494b501a98abd8262b0da1351e17899a0c4ef23dd2f96fec5ba847310b20");
+    let expected_s = hex32("0405c3191ab3883ef2b763af35bc5f5d15b3b4e99461d70e84c654a351a7c81b");
+
+    let mut public_key = [0u8; 32];
+    let result = unsafe { starknet_get_public_key(&private_key, &mut public_key) };
+    expect(result, StarkResult::Success, "starknet_get_public_key")?;
+    expect_eq(&public_key, &expected_public_key, "starknet_get_public_key")?;
+
+    let (mut r, mut s): (FeltBytes, FeltBytes) = ([0u8; 32], [0u8; 32]);
+    let result = unsafe { starknet_sign_with_k(&private_key, &message_hash, &k, &mut r, &mut s) };
+    expect(result, StarkResult::Success, "starknet_sign_with_k")?;
+    expect_eq(&r, &expected_r, "starknet_sign_with_k (r)")?;
+    expect_eq(&s, &expected_s, "starknet_sign_with_k (s)")?;
+
+    let result = unsafe { starknet_verify(&public_key, &message_hash, &r, &s) };
+    expect(
+        result,
+        StarkResult::Success,
+        "starknet_verify (valid signature)",
+    )?;
+    let other_hash = hex32("0000000000000000000000000000000000000000000000000000000000000003");
+    let result = unsafe { starknet_verify(&public_key, &other_hash, &r, &s) };
+    expect(
+        result,
+        StarkResult::InvalidSignature,
+        "starknet_verify (wrong message)",
+    )
+}
+
+#[cfg(feature = "secp")]
+fn secp256k1() -> Result<(), &'static str> {
+    let private_key = hex32("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318");
+    // keccak256("\x19Ethereum Signed Message:\n9Some data")
+    let message_hash = hex32("1da44b586eb0729ff70a73c326926f6ed5a25f5b056e7f47fbc6e58d86871655");
+    let expected_r = hex32("b91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd");
+    let expected_s = hex32("6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a029");
+
+    let mut public_key = [0u8; 64];
+    let result = unsafe { secp256k1_get_public_key(&private_key, &mut public_key) };
+    expect(result, StarkResult::Success, "secp256k1_get_public_key")?;
+
+    let (mut r, mut s, mut v) = ([0u8; 32], [0u8; 32], 0u8);
+    let result =
+        unsafe { secp256k1_sign_recoverable(&private_key, &message_hash, &mut r, &mut s, &mut v) };
+    expect(result, StarkResult::Success, "secp256k1_sign_recoverable")?;
+    expect_eq(&r, &expected_r, "secp256k1_sign_recoverable (r)")?;
+    expect_eq(&s, &expected_s, "secp256k1_sign_recoverable (s)")?;
+    expect_eq(&[v], &[1], "secp256k1_sign_recoverable (v)")?;
+
+    let result = unsafe { secp256k1_verify(&public_key, &message_hash, &r, &s) };
+    expect(result, StarkResult::Success, "secp256k1_verify")
+}
+
+const CHECKS: &[Check] = &[
+    #[cfg(feature = "hashing")]
+    pedersen,
+    #[cfg(feature = "hashing")]
+    poseidon,
+    #[cfg(feature = "hashing")]
+    keccak,
+    #[cfg(feature = "ecdsa")]
+    stark_ecdsa,
+    #[cfg(feature = "secp")]
+    secp256k1,
+];
+
+/// Run the built-in known-answer tests
+///
+/// Returns `Success` if every vector matched, otherwise `SelfTestFailed`
+/// (the last-error message names the first failing function). Meant to be
+/// called once at startup; a failure means the library must not be used.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_self_test")]
+pub extern "C" fn stark_self_test() -> StarkResult {
+    ffi_guard(|| {
+        for check in CHECKS {
+            if let Err(what) = check() {
+                error::set_detail(alloc::format!("{what} did not match its test vector"));
+                return StarkResult::SelfTestFailed;
+            }
+        }
+        StarkResult::Success
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_passes() {
+        assert_eq!(stark_self_test(), StarkResult::Success);
+    }
+
+    #[test]
+    fn test_expect_eq_reports_mismatch() {
+        assert_eq!(expect_eq(&[1u8], &[2u8], "probe"), Err("probe"));
+        assert_eq!(
+            expect(StarkResult::InvalidInput, StarkResult::Success, "probe"),
+            Err("probe")
+        );
+    }
+}
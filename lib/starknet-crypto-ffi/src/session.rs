@@ -0,0 +1,306 @@
+//! Session key authorization hashing (Argent session module)
+//!
+//! A session authorizes `session_key` to call a fixed set of
+//! `(contract, selector)` pairs until `expires_at`. The owner signs a
+//! SNIP-12 revision 1 message over:
+//!
+//! `Session { Expires At, Allowed Methods (merkletree), Metadata, Session Key }`
+//!
+//! in the `SessionAccount.session` domain. The allowed methods are committed
+//! to as a Merkle root; each call made with the session later carries a proof
+//! against that root.
+//!
+//! Only Argent's format is implemented. Braavos session keys use a
+//! different message and are not supported.
+
+use alloc::vec::Vec;
+
+use starknet_crypto::{poseidon_hash, poseidon_hash_many, Felt};
+
+use crate::typed_data::{
+    byte_array_felts, merkle_root, message_hash, short_string_to_felt, starknet_domain_hash,
+    Revision,
+};
+use crate::{
+    error::null_pointer, felt_from_bytes, felt_to_bytes, ffi_guard, sn_keccak, FeltBytes,
+    StarkResult,
+};
+
+const SESSION_TYPE: &str = "\"Session\"(\"Expires At\":\"timestamp\",\"Allowed Methods\":\"merkletree\",\"Metadata\":\"string\",\"Session Key\":\"felt\")";
+const ALLOWED_METHOD_TYPE: &str =
+    "\"Allowed Method\"(\"Contract Address\":\"ContractAddress\",\"selector\":\"selector\")";
+
+const DOMAIN_NAME: &str = "SessionAccount.session";
+const DOMAIN_VERSION: &str = "1";
+
+/// A method the session key is allowed to call
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct StarknetSessionPolicy {
+    pub contract_address: FeltBytes,
+    pub selector: FeltBytes,
+}
+
+fn allowed_method_leaf(contract_address: Felt, selector: Felt) -> Felt {
+    poseidon_hash_many(&[
+        sn_keccak(ALLOWED_METHOD_TYPE.as_bytes()),
+        contract_address,
+        selector,
+    ])
+}
+
+unsafe fn policy_leaves(ptr: *const StarknetSessionPolicy, len: usize) -> Option<Vec<Felt>> {
+    if ptr.is_null() || len == 0 {
+        return None;
+    }
+    core::slice::from_raw_parts(ptr, len)
+        .iter()
+        .map(|policy| {
+            Some(allowed_method_leaf(
+                felt_from_bytes(&policy.contract_address)?,
+                felt_from_bytes(&policy.selector)?,
+            ))
+        })
+        .collect()
+}
+
+/// Merkle root of the allowed methods (`Allowed Methods` field)
+pub(crate) fn allowed_methods_root(mut leaves: Vec<Felt>) -> Felt {
+    merkle_root(Revision::V1, &mut leaves)
+}
+
+/// SNIP-12 message hash of a session, as signed by the account owner
+pub(crate) fn session_hash(
+    expires_at: u64,
+    allowed_methods_root: Felt,
+    metadata: &[u8],
+    session_key: Felt,
+    chain_id: Felt,
+    account: Felt,
+) -> Felt {
+    let struct_hash = poseidon_hash_many(&[
+        sn_keccak(SESSION_TYPE.as_bytes()),
+        Felt::from(expires_at),
+        allowed_methods_root,
+        poseidon_hash_many(&byte_array_felts(metadata)),
+        session_key,
+    ]);
+    let name = short_string_to_felt(DOMAIN_NAME).expect("domain name is a valid short string");
+    let version =
+        short_string_to_felt(DOMAIN_VERSION).expect("domain version is a valid short string");
+    let domain = starknet_domain_hash(Revision::V1, name, version, chain_id);
+    message_hash(Revision::V1, domain, account, struct_hash)
+}
+
+/// Compute the Merkle root committing to a session's allowed methods
+///
+/// Returns `InvalidInput` if `policies` is empty.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_session_policies_root")]
+pub unsafe extern "C" fn starknet_session_policies_root(
+    policies: *const StarknetSessionPolicy,
+    policies_len: usize,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(policies, out);
+        let leaves = match policy_leaves(policies, policies_len) {
+            Some(l) => l,
+            None => return StarkResult::InvalidInput,
+        };
+
+        *out = felt_to_bytes(&allowed_methods_root(leaves));
+        StarkResult::Success
+    })
+}
+
+/// Compute the session message hash the account owner signs
+///
+/// `metadata` is the UTF-8 `Metadata` string (may be null when
+/// `metadata_len == 0`). `session_key` is the session signer's GUID, see
+/// `starknet_session_signer_guid`. Returns `InvalidInput` if `policies` is
+/// empty.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_session_hash")]
+pub unsafe extern "C" fn starknet_session_hash(
+    expires_at: u64,
+    policies: *const StarknetSessionPolicy,
+    policies_len: usize,
+    metadata: *const u8,
+    metadata_len: usize,
+    session_key: *const FeltBytes,
+    chain_id: *const FeltBytes,
+    account_address: *const FeltBytes,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(policies, session_key, chain_id, account_address, out);
+        if metadata.is_null() && metadata_len > 0 {
+            return null_pointer("metadata");
+        }
+        let leaves = match policy_leaves(policies, policies_len) {
+            Some(l) => l,
+            None => return StarkResult::InvalidInput,
+        };
+        let session_key = match felt_from_bytes(&*session_key) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let chain_id = match felt_from_bytes(&*chain_id) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let account = match felt_from_bytes(&*account_address) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let metadata = if metadata_len > 0 {
+            core::slice::from_raw_parts(metadata, metadata_len)
+        } else {
+            &[]
+        };
+
+        let root = allowed_methods_root(leaves);
+        *out = felt_to_bytes(&session_hash(
+            expires_at,
+            root,
+            metadata,
+            session_key,
+            chain_id,
+            account,
+        ));
+        StarkResult::Success
+    })
+}
+
+/// GUID identifying a Stark-curve session key: `poseidon('Starknet Signer', public_key)`
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_session_signer_guid")]
+pub unsafe extern "C" fn starknet_session_signer_guid(
+    public_key: *const FeltBytes,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(public_key, out);
+        let public_key = match felt_from_bytes(&*public_key) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+
+        let signer_type = Felt::from_bytes_be_slice(b"Starknet Signer");
+        *out = felt_to_bytes(&poseidon_hash(signer_type, public_key));
+        StarkResult::Success
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::typed_data::starknet_typed_data_hash;
+
+    const ACCOUNT: &str = "0x1234";
+    const METADATA: &str = "{\"projectID\":\"test-dapp\"}";
+
+    fn policies() -> Vec<StarknetSessionPolicy> {
+        [
+            ("0x49d36570", "transfer"),
+            ("0x49d36570", "approve"),
+            ("0xabc", "ping"),
+        ]
+        .iter()
+        .map(|(to, name)| StarknetSessionPolicy {
+            contract_address: felt_to_bytes(&Felt::from_hex_unchecked(to)),
+            selector: felt_to_bytes(&sn_keccak(name.as_bytes())),
+        })
+        .collect()
+    }
+
+    // Checks the session hash against the generic typed data path on the
+    // same document. No known-answer hash from Argent's session SDK or
+    // contract tests is available to this build.
+    #[test]
+    fn test_session_hash_matches_typed_data() {
+        let json = r#"{
+  "types": {
+    "StarknetDomain": [
+      { "name": "name", "type": "shortstring" },
+      { "name": "version", "type": "shortstring" },
+      { "name": "chainId", "type": "shortstring" },
+      { "name": "revision", "type": "shortstring" }
+    ],
+    "Allowed Method": [
+      { "name": "Contract Address", "type": "ContractAddress" },
+      { "name": "selector", "type": "selector" }
+    ],
+    "Session": [
+      { "name": "Expires At", "type": "timestamp" },
+      { "name": "Allowed Methods", "type": "merkletree", "contains": "Allowed Method" },
+      { "name": "Metadata", "type": "string" },
+      { "name": "Session Key", "type": "felt" }
+    ]
+  },
+  "primaryType": "Session",
+  "domain": { "name": "SessionAccount.session", "version": "0x31", "chainId": "SN_SEPOLIA", "revision": "1" },
+  "message": {
+    "Expires At": 1700000000,
+    "Allowed Methods": [
+      { "Contract Address": "0x49d36570", "selector": "transfer" },
+      { "Contract Address": "0x49d36570", "selector": "approve" },
+      { "Contract Address": "0xabc", "selector": "ping" }
+    ],
+    "Metadata": "{\"projectID\":\"test-dapp\"}",
+    "Session Key": "0x5678"
+  }
+}"#;
+        let account = felt_to_bytes(&Felt::from_hex_unchecked(ACCOUNT));
+        let mut expected = [0u8; 32];
+        unsafe {
+            let result =
+                starknet_typed_data_hash(json.as_ptr(), json.len(), &account, &mut expected);
+            assert_eq!(result, StarkResult::Success);
+        }
+
+        let policies = policies();
+        let session_key = felt_to_bytes(&Felt::from_hex_unchecked("0x5678"));
+        let chain_id = felt_to_bytes(&short_string_to_felt("SN_SEPOLIA").unwrap());
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = starknet_session_hash(
+                1_700_000_000,
+                policies.as_ptr(),
+                policies.len(),
+                METADATA.as_ptr(),
+                METADATA.len(),
+                &session_key,
+                &chain_id,
+                &account,
+                &mut out,
+            );
+            assert_eq!(result, StarkResult::Success);
+        }
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_session_policies_root() {
+        let policies = policies();
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = starknet_session_policies_root(policies.as_ptr(), 1, &mut out);
+            assert_eq!(result, StarkResult::Success);
+        }
+        // A single leaf is its own root
+        let leaf = allowed_method_leaf(
+            Felt::from_hex_unchecked("0x49d36570"),
+            sn_keccak(b"transfer"),
+        );
+        assert_eq!(out, felt_to_bytes(&leaf));
+
+        unsafe {
+            let result = starknet_session_policies_root(policies.as_ptr(), 0, &mut out);
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+}
@@ -0,0 +1,562 @@
+//! Constant-time STARK ECDSA signing
+//!
+//! `starknet_sign` goes through starknet-crypto, which is fast but not
+//! written to hide its secrets from timing:
+//!
+//! - `k·G` is a double-and-add over the bits of `k` that skips the
+//!   additions for zero bits (starknet-types-core's point multiplication);
+//! - `k⁻¹ mod n` is an extended Euclid on heap big integers, whose number of
+//!   steps and allocations depend on `k`;
+//! - the field arithmetic reduces with data-dependent branches, and the
+//!   private key and nonce go through several `Felt` / big integer
+//!   conversions.
+//!
+//! `starknet_sign_ct` computes the same signature with code whose branches,
+//! memory accesses and loop counts depend only on public values:
+//!
+//! - field elements mod P and scalars mod n are 4×64-bit Montgomery
+//!   integers; every reduction is a masked conditional subtraction and
+//!   every choice goes through `subtle`'s conditional selection;
+//! - `k·G` uses 4-bit fixed windows (63 of them, always 4 doublings and 1
+//!   addition each) with the complete addition formulas of Renes, Costello
+//!   and Batina (2015), so adding the identity or a point to itself takes
+//!   the same path; each window entry is read by scanning the whole
+//!   16-entry table;
+//! - inversions (`Z⁻¹ mod P` for the affine `r`, `k⁻¹ mod n`) are Fermat
+//!   exponentiations by the public exponent `m - 2`;
+//! - `k` comes from RFC 6979 HMAC-DRBG (HMAC-SHA256) fed with the caller's
+//!   key bytes directly. Out-of-range candidates are drawn again, which
+//!   reveals how many were drawn but nothing about the one kept.
+//!
+//! What is not secret and may be branched on: the message hash, the output
+//! `r` and `s` (their range checks), whether the private key is in range,
+//! and the window and bit indices. Secrets are wiped before returning.
+//! Constant time here means no secret-dependent control flow or addressing
+//! in this code; it does not cover the hardware (variable-latency
+//! multipliers on some embedded cores) or what a compiler may do to code it
+//! cannot see through, which `subtle` guards against for the selections.
+
+use starknet_crypto::Felt;
+use starknet_types_core::curve::AffinePoint;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroize;
+
+use crate::perf::{self, Op};
+use crate::{felt_from_bytes, felt_to_bytes, ffi_guard, FeltBytes, StarkResult};
+
+/// 256-bit integer, little-endian 64-bit limbs
+type Limbs = [u64; 4];
+
+/// Field modulus P = 2^251 + 17·2^192 + 1
+const P: Limbs = [1, 0, 0, 0x0800_0000_0000_0011];
+/// Curve order n
+const N: Limbs = [
+    0x1e66_a241_adc6_4d2f,
+    0xb781_126d_cae7_b232,
+    0xffff_ffff_ffff_ffff,
+    0x0800_0000_0000_0010,
+];
+/// Curve coefficient β (y² = x³ + x + β)
+const BETA: Limbs = [
+    0xf4cd_fcb9_9cee_9e89,
+    0x609a_d26c_15c9_15c1,
+    0x150e_596d_72f7_a8c5,
+    0x06f2_1413_efbe_40de,
+];
+
+const fn geq(a: &Limbs, b: &Limbs) -> bool {
+    let mut i = 4;
+    while i > 0 {
+        i -= 1;
+        if a[i] != b[i] {
+            return a[i] > b[i];
+        }
+    }
+    true
+}
+
+/// `a - b` and the borrow (0 or 1)
+const fn sub_borrow(a: &Limbs, b: &Limbs) -> (Limbs, u64) {
+    let mut out = [0; 4];
+    let mut borrow = 0;
+    let mut i = 0;
+    while i < 4 {
+        let t = (a[i] as u128).wrapping_sub(b[i] as u128 + borrow as u128);
+        out[i] = t as u64;
+        borrow = (t >> 127) as u64;
+        i += 1;
+    }
+    (out, borrow)
+}
+
+/// `a + b` (no carry out: both operands are below 2^255)
+const fn add_limbs(a: &Limbs, b: &Limbs) -> Limbs {
+    let mut out = [0; 4];
+    let mut carry = 0;
+    let mut i = 0;
+    while i < 4 {
+        let t = a[i] as u128 + b[i] as u128 + carry as u128;
+        out[i] = t as u64;
+        carry = (t >> 64) as u64;
+        i += 1;
+    }
+    out
+}
+
+fn select(a: &Limbs, b: &Limbs, choice: Choice) -> Limbs {
+    core::array::from_fn(|i| u64::conditional_select(&a[i], &b[i], choice))
+}
+
+/// Integers mod an odd `m < 2^254`, in Montgomery form (`a·2^256 mod m`)
+struct Modulus {
+    m: Limbs,
+    /// -m⁻¹ mod 2^64
+    inv: u64,
+    /// 2^512 mod m
+    r2: Limbs,
+}
+
+impl Modulus {
+    const fn new(m: Limbs) -> Self {
+        // Newton's iteration doubles the correct low bits of m⁻¹ each step
+        let mut inv = 1u64;
+        let mut i = 0;
+        while i < 6 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(m[0].wrapping_mul(inv)));
+            i += 1;
+        }
+        let mut r2 = [1, 0, 0, 0];
+        let mut i = 0;
+        while i < 512 {
+            r2 = add_limbs(&r2, &r2);
+            if geq(&r2, &m) {
+                r2 = sub_borrow(&r2, &m).0;
+            }
+            i += 1;
+        }
+        Self {
+            m,
+            inv: inv.wrapping_neg(),
+            r2,
+        }
+    }
+
+    /// `a - m` if `a >= m`, else `a`
+    fn reduce_once(&self, a: &Limbs) -> Limbs {
+        let (reduced, borrow) = sub_borrow(a, &self.m);
+        select(&reduced, a, Choice::from(borrow as u8))
+    }
+
+    fn add(&self, a: &Limbs, b: &Limbs) -> Limbs {
+        self.reduce_once(&add_limbs(a, b))
+    }
+
+    fn sub(&self, a: &Limbs, b: &Limbs) -> Limbs {
+        let (diff, borrow) = sub_borrow(a, b);
+        select(
+            &diff,
+            &add_limbs(&diff, &self.m),
+            Choice::from(borrow as u8),
+        )
+    }
+
+    /// Montgomery product `a·b·2^-256 mod m` (CIOS)
+    fn mul(&self, a: &Limbs, b: &Limbs) -> Limbs {
+        let mac = |acc: u64, x: u64, y: u64, carry: u64| {
+            let t = acc as u128 + x as u128 * y as u128 + carry as u128;
+            (t as u64, (t >> 64) as u64)
+        };
+        let mut t = [0u64; 6];
+        for &b_i in b {
+            let mut carry = 0;
+            for j in 0..4 {
+                (t[j], carry) = mac(t[j], a[j], b_i, carry);
+            }
+            (t[4], t[5]) = mac(t[4], carry, 1, 0);
+
+            let mu = t[0].wrapping_mul(self.inv);
+            let (_, mut carry) = mac(t[0], mu, self.m[0], 0);
+            for j in 1..4 {
+                (t[j - 1], carry) = mac(t[j], mu, self.m[j], carry);
+            }
+            let (low, high) = mac(t[4], carry, 1, 0);
+            t[3] = low;
+            t[4] = t[5] + high;
+        }
+        // m < 2^254 keeps the result below 2m < 2^256, so t[4] is 0
+        self.reduce_once(&[t[0], t[1], t[2], t[3]])
+    }
+
+    fn to_mont(&self, a: &Limbs) -> Limbs {
+        self.mul(a, &self.r2)
+    }
+
+    fn canonical(&self, a: &Limbs) -> Limbs {
+        self.mul(a, &[1, 0, 0, 0])
+    }
+
+    /// `a^(m-2) = a⁻¹` (0 for 0), branching only on the bits of the public
+    /// exponent
+    fn invert(&self, a: &Limbs) -> Limbs {
+        let exponent = sub_borrow(&self.m, &[2, 0, 0, 0]).0;
+        let mut result = self.to_mont(&[1, 0, 0, 0]);
+        for i in (0..256).rev() {
+            result = self.mul(&result, &result);
+            if (exponent[i / 64] >> (i % 64)) & 1 == 1 {
+                result = self.mul(&result, a);
+            }
+        }
+        result
+    }
+}
+
+const FP: Modulus = Modulus::new(P);
+const FN: Modulus = Modulus::new(N);
+
+/// Projective point `(X : Y : Z)` (affine `(X/Z, Y/Z)`), coordinates in
+/// Montgomery form mod P
+#[derive(Clone, Copy, Default)]
+struct Point {
+    x: Limbs,
+    y: Limbs,
+    z: Limbs,
+}
+
+impl Zeroize for Point {
+    fn zeroize(&mut self) {
+        self.x.zeroize();
+        self.y.zeroize();
+        self.z.zeroize();
+    }
+}
+
+impl Point {
+    fn identity() -> Self {
+        Self {
+            x: [0; 4],
+            y: FP.to_mont(&[1, 0, 0, 0]),
+            z: [0; 4],
+        }
+    }
+
+    fn select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self {
+            x: select(&a.x, &b.x, choice),
+            y: select(&a.y, &b.y, choice),
+            z: select(&a.z, &b.z, choice),
+        }
+    }
+
+    /// `self + other`, complete for any two points including the identity
+    /// and `self == other` (Renes-Costello-Batina, algorithm 1, with a = 1)
+    fn add(&self, other: &Self, b3: &Limbs) -> Self {
+        let f = &FP;
+        let (x1, y1, z1) = (&self.x, &self.y, &self.z);
+        let (x2, y2, z2) = (&other.x, &other.y, &other.z);
+        let mut t0 = f.mul(x1, x2);
+        let mut t1 = f.mul(y1, y2);
+        let mut t2 = f.mul(z1, z2);
+        let mut t3 = f.mul(&f.add(x1, y1), &f.add(x2, y2));
+        let mut t4 = f.add(&t0, &t1);
+        t3 = f.sub(&t3, &t4);
+        t4 = f.mul(&f.add(x1, z1), &f.add(x2, z2));
+        let mut t5 = f.add(&t0, &t2);
+        t4 = f.sub(&t4, &t5);
+        t5 = f.mul(&f.add(y1, z1), &f.add(y2, z2));
+        let mut x3 = f.add(&t1, &t2);
+        t5 = f.sub(&t5, &x3);
+        let mut z3 = t4;
+        x3 = f.mul(b3, &t2);
+        z3 = f.add(&x3, &z3);
+        x3 = f.sub(&t1, &z3);
+        z3 = f.add(&t1, &z3);
+        let mut y3 = f.mul(&x3, &z3);
+        t1 = f.add(&t0, &t0);
+        t1 = f.add(&t1, &t0);
+        t4 = f.mul(b3, &t4);
+        t1 = f.add(&t1, &t2);
+        t2 = f.sub(&t0, &t2);
+        t4 = f.add(&t4, &t2);
+        t0 = f.mul(&t1, &t4);
+        y3 = f.add(&y3, &t0);
+        t0 = f.mul(&t5, &t4);
+        x3 = f.mul(&t3, &x3);
+        x3 = f.sub(&x3, &t0);
+        t0 = f.mul(&t3, &t1);
+        z3 = f.mul(&t5, &z3);
+        z3 = f.add(&z3, &t0);
+        Self {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    /// Affine coordinates (canonical, not Montgomery); `(0, 0)` for the
+    /// identity
+    fn affine(&self) -> (Limbs, Limbs) {
+        let z_inv = FP.invert(&self.z);
+        (
+            FP.canonical(&FP.mul(&self.x, &z_inv)),
+            FP.canonical(&FP.mul(&self.y, &z_inv)),
+        )
+    }
+}
+
+fn limbs_from_bytes(bytes: &[u8; 32]) -> Limbs {
+    core::array::from_fn(|i| {
+        let start = 24 - 8 * i;
+        u64::from_be_bytes(bytes[start..start + 8].try_into().unwrap())
+    })
+}
+
+fn limbs_to_bytes(limbs: &Limbs) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (i, limb) in limbs.iter().enumerate() {
+        bytes[24 - 8 * i..32 - 8 * i].copy_from_slice(&limb.to_be_bytes());
+    }
+    bytes
+}
+
+/// `k·G` for `k < 2^252`
+fn mul_generator(k: &Limbs) -> Point {
+    let b3 = FP.to_mont(&add_limbs(&add_limbs(&BETA, &BETA), &BETA));
+    let generator = AffinePoint::generator();
+    let generator = Point {
+        x: FP.to_mont(&limbs_from_bytes(&generator.x().to_bytes_be())),
+        y: FP.to_mont(&limbs_from_bytes(&generator.y().to_bytes_be())),
+        z: FP.to_mont(&[1, 0, 0, 0]),
+    };
+    // i·G for i in 0..16 (public)
+    let mut table = [Point::identity(); 16];
+    for i in 1..16 {
+        table[i] = table[i - 1].add(&generator, &b3);
+    }
+
+    let mut acc = Point::identity();
+    for window in (0..63).rev() {
+        for _ in 0..4 {
+            acc = acc.add(&acc, &b3);
+        }
+        let digit = (k[window / 16] >> (4 * (window % 16))) & 0xf;
+        let mut entry = Point::default();
+        for (i, point) in table.iter().enumerate() {
+            entry = Point::select(&entry, point, digit.ct_eq(&(i as u64)));
+        }
+        acc = acc.add(&entry, &b3);
+        entry.zeroize();
+    }
+    acc
+}
+
+/// RFC 6979 nonce for `private_key` (32 big-endian bytes) and the message
+/// hash, as `starknet_crypto::rfc6979_generate_k` derives it without a seed
+fn generate_k(private_key: &[u8; 32], message_hash: &[u8; 32]) -> Limbs {
+    let mut drbg = rfc6979::HmacDrbg::<sha2::Sha256>::new(private_key, message_hash, &[]);
+    loop {
+        let mut bytes = [0u8; 32];
+        drbg.fill_bytes(&mut bytes);
+        let mut k = limbs_from_bytes(&bytes);
+        bytes.zeroize();
+        for i in 0..4 {
+            k[i] = (k[i] >> 4) | k.get(i + 1).map_or(0, |next| next << 60);
+        }
+        if bool::from(!is_zero(&k) & lt(&k, &N)) {
+            return k;
+        }
+        k.zeroize();
+    }
+}
+
+fn is_zero(a: &Limbs) -> Choice {
+    (a[0] | a[1] | a[2] | a[3]).ct_eq(&0)
+}
+
+fn lt(a: &Limbs, b: &Limbs) -> Choice {
+    Choice::from(sub_borrow(a, b).1 as u8)
+}
+
+/// Whether `a < 2^251` (the signature component bound)
+fn below_2_251(a: &Limbs) -> bool {
+    a[3] >> 59 == 0
+}
+
+/// `(r, s, v)` of the signature of `message_hash` (`< 2^251`) by
+/// `private_key` (`0 < private_key < n`), or `None` if the nonce gives an
+/// out-of-range `r` or `s`
+fn sign(private_key: &Limbs, message_hash: &Limbs) -> Option<(Limbs, Limbs, u64)> {
+    let mut key_bytes = limbs_to_bytes(private_key);
+    let mut k = generate_k(&key_bytes, &limbs_to_bytes(message_hash));
+    key_bytes.zeroize();
+
+    let mut point = mul_generator(&k);
+    let (r, mut y) = point.affine();
+    point.zeroize();
+    let v = y[0] & 1;
+    y.zeroize();
+    if is_zero(&r).into() || !below_2_251(&r) {
+        k.zeroize();
+        return None;
+    }
+
+    // s = (r·d + z) / k mod n (r and z are below 2^251 < n)
+    let mut k_inv = FN.invert(&FN.to_mont(&k));
+    k.zeroize();
+    let mut d = FN.to_mont(private_key);
+    let mut rd = FN.mul(&FN.to_mont(&r), &d);
+    d.zeroize();
+    let mut sum = FN.add(&rd, &FN.to_mont(message_hash));
+    rd.zeroize();
+    let s = FN.canonical(&FN.mul(&sum, &k_inv));
+    sum.zeroize();
+    k_inv.zeroize();
+    if is_zero(&s).into() || !below_2_251(&s) {
+        return None;
+    }
+    Some((r, s, v))
+}
+
+/// Sign a message hash with private key in constant time (returns r, s)
+///
+/// Same signature as `starknet_sign` (RFC 6979 nonce), computed without
+/// branches or memory accesses that depend on the private key or the nonce
+/// (see `sign_ct`). Slower than `starknet_sign`. Returns
+/// `InvalidPrivateKey` unless `0 < private_key < n`, and `InvalidInput` if
+/// `message_hash >= 2^251` or the nonce gives an out-of-range signature.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_sign_ct")]
+pub unsafe extern "C" fn starknet_sign_ct(
+    private_key: *const FeltBytes,
+    message_hash: *const FeltBytes,
+    out_r: *mut FeltBytes,
+    out_s: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Sign);
+        check_null!(private_key, message_hash, out_r, out_s);
+        let mut key = limbs_from_bytes(&*private_key);
+        if !bool::from(!is_zero(&key) & lt(&key, &N)) {
+            key.zeroize();
+            return StarkResult::InvalidPrivateKey;
+        }
+        let message_hash = match felt_from_bytes(&*message_hash) {
+            Some(f) => limbs_from_bytes(&f.to_bytes_be()),
+            None => return StarkResult::InvalidInput,
+        };
+        if !below_2_251(&message_hash) {
+            key.zeroize();
+            return StarkResult::InvalidInput;
+        }
+
+        let signature = sign(&key, &message_hash);
+        key.zeroize();
+        match signature {
+            Some((r, s, _)) => {
+                *out_r = felt_to_bytes(&Felt::from_bytes_be(&limbs_to_bytes(&r)));
+                *out_s = felt_to_bytes(&Felt::from_bytes_be(&limbs_to_bytes(&s)));
+                StarkResult::Success
+            }
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet_types_core::curve::ProjectivePoint;
+
+    use crate::{starknet_sign, starknet_sign_recoverable};
+
+    fn felt_limbs(felt: &Felt) -> Limbs {
+        limbs_from_bytes(&felt.to_bytes_be())
+    }
+
+    #[test]
+    fn test_modular_arithmetic() {
+        let (a, b) = (
+            Felt::MAX - Felt::from(12345u64),
+            Felt::from(0xdead_beefu64).pow(9u64),
+        );
+        let (ma, mb) = (FP.to_mont(&felt_limbs(&a)), FP.to_mont(&felt_limbs(&b)));
+        for (got, expected) in [
+            (FP.add(&ma, &mb), a + b),
+            (FP.sub(&mb, &ma), b - a),
+            (FP.mul(&ma, &mb), a * b),
+            (FP.invert(&mb), b.inverse().unwrap()),
+        ] {
+            assert_eq!(FP.canonical(&got), felt_limbs(&expected));
+        }
+    }
+
+    #[test]
+    fn test_mul_generator_matches_projective() {
+        let g = AffinePoint::generator();
+        let g = ProjectivePoint::from_affine_unchecked(g.x(), g.y());
+        for k in [
+            Felt::ONE,
+            Felt::from(16u64),
+            Felt::from(0xffff_ffffu64).pow(7u64),
+        ] {
+            let expected = (&g * k).to_affine().unwrap();
+            let (x, y) = mul_generator(&felt_limbs(&k)).affine();
+            assert_eq!(x, felt_limbs(&expected.x()));
+            assert_eq!(y, felt_limbs(&expected.y()));
+        }
+        // Complete formulas: P + P and P + O
+        let b3 = FP.to_mont(&add_limbs(&add_limbs(&BETA, &BETA), &BETA));
+        let p = mul_generator(&[5, 0, 0, 0]);
+        let doubled = p.add(&p, &b3).affine();
+        assert_eq!(doubled, mul_generator(&[10, 0, 0, 0]).affine());
+        assert_eq!(p.add(&Point::identity(), &b3).affine(), p.affine());
+    }
+
+    #[test]
+    fn test_sign_ct_matches_sign() {
+        for i in 1..6u64 {
+            let private_key = felt_to_bytes(&Felt::from(i * 0x1234_5678).pow(5u64));
+            let message_hash = felt_to_bytes(&Felt::from(i * 1000 + 7));
+            let (mut r, mut s, mut v) = ([0u8; 32], [0u8; 32], [0u8; 32]);
+            let (mut ct_r, mut ct_s) = ([0u8; 32], [0u8; 32]);
+            unsafe {
+                assert_eq!(
+                    starknet_sign(&private_key, &message_hash, &mut r, &mut s),
+                    StarkResult::Success
+                );
+                assert_eq!(
+                    starknet_sign_ct(&private_key, &message_hash, &mut ct_r, &mut ct_s),
+                    StarkResult::Success
+                );
+                starknet_sign_recoverable(&private_key, &message_hash, &mut r, &mut s, &mut v);
+            }
+            assert_eq!((ct_r, ct_s), (r, s));
+
+            let (_, _, ct_v) = sign(
+                &limbs_from_bytes(&private_key),
+                &limbs_from_bytes(&message_hash),
+            )
+            .unwrap();
+            assert_eq!(ct_v, v[31] as u64);
+        }
+    }
+
+    #[test]
+    fn test_sign_ct_rejects_invalid_inputs() {
+        let message_hash = felt_to_bytes(&Felt::from(42u64));
+        let (mut r, mut s) = ([0u8; 32], [0u8; 32]);
+        let order = limbs_to_bytes(&N);
+        let too_large = felt_to_bytes(&Felt::from(2u64).pow(251u64));
+        unsafe {
+            for key in [[0u8; 32], order] {
+                assert_eq!(
+                    starknet_sign_ct(&key, &message_hash, &mut r, &mut s),
+                    StarkResult::InvalidPrivateKey
+                );
+            }
+            assert_eq!(
+                starknet_sign_ct(&felt_to_bytes(&Felt::ONE), &too_large, &mut r, &mut s),
+                StarkResult::InvalidInput
+            );
+        }
+    }
+}
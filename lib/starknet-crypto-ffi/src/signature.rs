@@ -0,0 +1,384 @@
+//! Signature encoding: STARK canonical form and ASN.1 DER
+//!
+//! A STARK signature `(r, s)` verifies if and only if `(r, n - s)` does, so
+//! signatures are malleable. The canonical form is the one with `s <= n / 2`
+//! (low-s), with `r`, `s` and `s^-1 mod n` inside the ranges accepted by the
+//! verifier (`[1, 2^251)`).
+//!
+//! Also converts `(r, s)` pairs to and from ASN.1 DER
+//! (`SEQUENCE { INTEGER r, INTEGER s }`) for HSM / PKCS#11 interop. The DER
+//! helpers work on any 32-byte big-endian scalars, so they apply equally to
+//! secp256k1 and secp256r1 signatures.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use starknet_crypto::Felt;
+use starknet_types_core::felt::NonZeroFelt;
+
+use crate::{
+    felt_from_bytes, felt_to_bytes, ffi_guard, write_bytes_out, FeltBytes, StarkResult, EC_ORDER,
+};
+
+/// `(n - 1) / 2`, the largest low-s value
+const EC_ORDER_HALF: Felt =
+    Felt::from_hex_unchecked("0x4000000000000087fffffffffffffffdbc08936e573d9190f335120d6e32697");
+
+fn in_verifier_range(x: &Felt) -> bool {
+    *x != Felt::ZERO && *x < Felt::ELEMENT_UPPER_BOUND
+}
+
+/// `r` and `s` are in range and `s` has an in-range inverse mod n
+fn is_well_formed(r: &Felt, s: &Felt) -> bool {
+    if !in_verifier_range(r) || !in_verifier_range(s) {
+        return false;
+    }
+    let order = NonZeroFelt::from_felt_unchecked(EC_ORDER);
+    match s.mod_inverse(&order) {
+        Some(w) => in_verifier_range(&w),
+        None => false,
+    }
+}
+
+/// Low-s form of a well-formed signature, if it is itself well-formed
+pub(crate) fn normalize(r: &Felt, s: &Felt) -> Option<Felt> {
+    if !is_well_formed(r, s) {
+        return None;
+    }
+    if *s <= EC_ORDER_HALF {
+        return Some(*s);
+    }
+    let low_s = EC_ORDER - s;
+    is_well_formed(r, &low_s).then_some(low_s)
+}
+
+pub(crate) fn is_canonical(r: &Felt, s: &Felt) -> bool {
+    is_well_formed(r, s) && *s <= EC_ORDER_HALF
+}
+
+/// Convert a signature to its canonical low-s form (writes s)
+///
+/// Returns `InvalidSignature` if `(r, s)` is outside the verifier's ranges or
+/// has no canonical equivalent.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_signature_normalize")]
+pub unsafe extern "C" fn starknet_signature_normalize(
+    r: *const FeltBytes,
+    s: *const FeltBytes,
+    out_s: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(r, s, out_s);
+        let r = match felt_from_bytes(&*r) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let s = match felt_from_bytes(&*s) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+
+        match normalize(&r, &s) {
+            Some(low_s) => {
+                *out_s = felt_to_bytes(&low_s);
+                StarkResult::Success
+            }
+            None => StarkResult::InvalidSignature,
+        }
+    })
+}
+
+/// Check that a signature is in canonical low-s form
+///
+/// Returns `Success` if canonical, `InvalidSignature` otherwise.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_signature_is_canonical")]
+pub unsafe extern "C" fn starknet_signature_is_canonical(
+    r: *const FeltBytes,
+    s: *const FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(r, s);
+        let r = match felt_from_bytes(&*r) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let s = match felt_from_bytes(&*s) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+
+        if is_canonical(&r, &s) {
+            StarkResult::Success
+        } else {
+            StarkResult::InvalidSignature
+        }
+    })
+}
+
+/// Largest DER signature: two 33-byte INTEGERs plus headers
+pub const SIGNATURE_DER_MAX_LEN: usize = 72;
+
+fn der_push_integer(out: &mut Vec<u8>, value: &[u8; 32]) {
+    let start = value.iter().position(|&b| b != 0).unwrap_or(31);
+    let digits = &value[start..];
+    let pad = digits[0] & 0x80 != 0;
+    out.push(0x02);
+    out.push((digits.len() + pad as usize) as u8);
+    if pad {
+        out.push(0x00);
+    }
+    out.extend_from_slice(digits);
+}
+
+pub(crate) fn signature_der(r: &[u8; 32], s: &[u8; 32]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(SIGNATURE_DER_MAX_LEN - 2);
+    der_push_integer(&mut body, r);
+    der_push_integer(&mut body, s);
+    let mut der = vec![0x30, body.len() as u8];
+    der.extend_from_slice(&body);
+    der
+}
+
+/// Parse one minimally-encoded non-negative INTEGER, returning it and the rest
+fn der_read_integer(input: &[u8]) -> Option<([u8; 32], &[u8])> {
+    let (&tag, rest) = input.split_first()?;
+    let (&len, rest) = rest.split_first()?;
+    let len = len as usize;
+    if tag != 0x02 || len == 0 || len > 33 || rest.len() < len {
+        return None;
+    }
+    let (digits, rest) = rest.split_at(len);
+    // Negative values and non-minimal encodings are not DER
+    if digits[0] & 0x80 != 0 {
+        return None;
+    }
+    if len > 1 && digits[0] == 0 && digits[1] & 0x80 == 0 {
+        return None;
+    }
+    let digits = if digits[0] == 0 && len > 1 {
+        &digits[1..]
+    } else {
+        digits
+    };
+    if digits.len() > 32 {
+        return None;
+    }
+    let mut value = [0u8; 32];
+    value[32 - digits.len()..].copy_from_slice(digits);
+    Some((value, rest))
+}
+
+pub(crate) fn signature_from_der_bytes(der: &[u8]) -> Option<([u8; 32], [u8; 32])> {
+    let (&tag, rest) = der.split_first()?;
+    let (&len, body) = rest.split_first()?;
+    // Short-form length only: a signature body never exceeds 70 bytes
+    if tag != 0x30 || len & 0x80 != 0 || body.len() != len as usize {
+        return None;
+    }
+    let (r, rest) = der_read_integer(body)?;
+    let (s, rest) = der_read_integer(rest)?;
+    rest.is_empty().then_some((r, s))
+}
+
+/// Encode a signature (r, s) as ASN.1 DER
+///
+/// `out_len` holds the capacity of `out` on input and the encoded length on
+/// output. If the buffer is too small, the required length is written to
+/// `out_len` and `BufferTooSmall` is returned. At most
+/// `SIGNATURE_DER_MAX_LEN` bytes are written.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_signature_to_der")]
+pub unsafe extern "C" fn signature_to_der(
+    r: *const [u8; 32],
+    s: *const [u8; 32],
+    out: *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(r, s, out_len);
+        let der = signature_der(&*r, &*s);
+        write_bytes_out(&der, out, out_len)
+    })
+}
+
+/// Decode an ASN.1 DER signature into (r, s)
+///
+/// Only strict DER is accepted (minimal integers, no trailing data) and each
+/// value must fit in 32 bytes.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_signature_from_der")]
+pub unsafe extern "C" fn signature_from_der(
+    der: *const u8,
+    der_len: usize,
+    out_r: *mut [u8; 32],
+    out_s: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(der, out_r, out_s);
+        let der = core::slice::from_raw_parts(der, der_len);
+
+        match signature_from_der_bytes(der) {
+            Some((r, s)) => {
+                *out_r = r;
+                *out_s = s;
+                StarkResult::Success
+            }
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+#[cfg(all(test, feature = "ecdsa"))]
+mod tests {
+    use super::*;
+    use crate::{starknet_get_public_key, starknet_sign_with_k, starknet_verify};
+
+    fn sign(k: u64) -> (FeltBytes, FeltBytes, FeltBytes, FeltBytes) {
+        let private_key = felt_to_bytes(&Felt::from(12345u64));
+        let message_hash = felt_to_bytes(&Felt::from(67890u64));
+        let k = felt_to_bytes(&Felt::from(k));
+        let mut public_key = [0u8; 32];
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+
+        unsafe {
+            starknet_get_public_key(&private_key, &mut public_key);
+            let result = starknet_sign_with_k(&private_key, &message_hash, &k, &mut r, &mut s);
+            assert_eq!(result, StarkResult::Success);
+        }
+        (public_key, message_hash, r, s)
+    }
+
+    #[test]
+    fn test_signature_normalize() {
+        // Find a high-s signature by trying successive nonces
+        let (public_key, message_hash, r, s) = (1..)
+            .map(sign)
+            .find(|(_, _, _, s)| Felt::from_bytes_be(s) > EC_ORDER_HALF)
+            .unwrap();
+        let mut low_s = [0u8; 32];
+
+        unsafe {
+            assert_eq!(
+                starknet_signature_is_canonical(&r, &s),
+                StarkResult::InvalidSignature
+            );
+
+            let result = starknet_signature_normalize(&r, &s, &mut low_s);
+            assert_eq!(result, StarkResult::Success);
+            assert_eq!(
+                Felt::from_bytes_be(&low_s),
+                EC_ORDER - Felt::from_bytes_be(&s)
+            );
+            assert_eq!(
+                starknet_signature_is_canonical(&r, &low_s),
+                StarkResult::Success
+            );
+
+            // Both forms verify
+            assert_eq!(
+                starknet_verify(&public_key, &message_hash, &r, &s),
+                StarkResult::Success
+            );
+            assert_eq!(
+                starknet_verify(&public_key, &message_hash, &r, &low_s),
+                StarkResult::Success
+            );
+
+            // Normalizing a canonical signature is a no-op
+            let mut again = [0u8; 32];
+            starknet_signature_normalize(&r, &low_s, &mut again);
+            assert_eq!(again, low_s);
+        }
+    }
+
+    #[test]
+    fn test_signature_out_of_range() {
+        let one = felt_to_bytes(&Felt::ONE);
+        let zero = [0u8; 32];
+        let too_big = felt_to_bytes(&Felt::ELEMENT_UPPER_BOUND);
+        let mut out = [0u8; 32];
+
+        unsafe {
+            assert_eq!(
+                starknet_signature_is_canonical(&zero, &one),
+                StarkResult::InvalidSignature
+            );
+            assert_eq!(
+                starknet_signature_is_canonical(&one, &too_big),
+                StarkResult::InvalidSignature
+            );
+            assert_eq!(
+                starknet_signature_normalize(&one, &zero, &mut out),
+                StarkResult::InvalidSignature
+            );
+        }
+    }
+
+    #[test]
+    fn test_signature_der_roundtrip() {
+        let mut r = [0u8; 32];
+        r[31] = 0x01;
+        let mut s = [0u8; 32];
+        s[0] = 0x80;
+        let mut der = [0u8; SIGNATURE_DER_MAX_LEN];
+        let mut der_len = der.len();
+
+        unsafe {
+            let result = signature_to_der(&r, &s, der.as_mut_ptr(), &mut der_len);
+            assert_eq!(result, StarkResult::Success);
+        }
+
+        // r = 0x01 is one byte, s has its high bit set and gets a 0x00 pad
+        let mut expected = vec![0x30, 0x26, 0x02, 0x01, 0x01, 0x02, 0x21, 0x00];
+        expected.extend_from_slice(&s);
+        assert_eq!(&der[..der_len], &expected[..]);
+
+        let mut decoded_r = [0u8; 32];
+        let mut decoded_s = [0u8; 32];
+        unsafe {
+            let result = signature_from_der(der.as_ptr(), der_len, &mut decoded_r, &mut decoded_s);
+            assert_eq!(result, StarkResult::Success);
+        }
+        assert_eq!((decoded_r, decoded_s), (r, s));
+    }
+
+    #[test]
+    fn test_signature_to_der_buffer_too_small() {
+        let value = [0xffu8; 32];
+        let mut der = [0u8; 8];
+        let mut der_len = der.len();
+
+        unsafe {
+            let result = signature_to_der(&value, &value, der.as_mut_ptr(), &mut der_len);
+            assert_eq!(result, StarkResult::BufferTooSmall);
+        }
+        assert_eq!(der_len, SIGNATURE_DER_MAX_LEN);
+    }
+
+    #[test]
+    fn test_signature_from_der_rejects_non_der() {
+        let cases: [&[u8]; 5] = [
+            // Non-minimal integer (superfluous leading zero)
+            &[0x30, 0x07, 0x02, 0x02, 0x00, 0x01, 0x02, 0x01, 0x01],
+            // Negative integer
+            &[0x30, 0x06, 0x02, 0x01, 0x81, 0x02, 0x01, 0x01],
+            // Trailing data
+            &[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x01, 0x00],
+            // Wrong sequence tag
+            &[0x31, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x01],
+            // Truncated
+            &[0x30, 0x06, 0x02, 0x01, 0x01, 0x02],
+        ];
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+
+        for der in cases {
+            unsafe {
+                let result = signature_from_der(der.as_ptr(), der.len(), &mut r, &mut s);
+                assert_eq!(result, StarkResult::InvalidInput, "{der:02x?}");
+            }
+        }
+    }
+}
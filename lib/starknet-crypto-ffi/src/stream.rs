@@ -0,0 +1,93 @@
+//! Streaming output through a caller callback
+//!
+//! The `_stream` variants of the large producers (calldata encoding) hand
+//! their output to a callback in chunks of at most `STREAM_CHUNK_MAX_LEN`
+//! bytes instead of writing one caller buffer, so a memory-constrained host
+//! can forward it to a file or socket as it arrives. Felt outputs are
+//! streamed as consecutive 32-byte big-endian felts and a chunk never splits
+//! a felt. Nothing is delivered if the call fails before producing output;
+//! an empty output makes no callback call.
+//!
+//! The callback may call other library functions, but must not free the
+//! handle being streamed from.
+
+use core::ffi::c_void;
+
+use starknet_crypto::Felt;
+
+use crate::{error::null_pointer, felt_to_bytes, StarkResult};
+
+/// Largest chunk passed to a `StarkChunkCallback`, in bytes (64 felts)
+pub const STREAM_CHUNK_MAX_LEN: usize = 2048;
+
+const CHUNK_FELTS: usize = STREAM_CHUNK_MAX_LEN / 32;
+
+/// Receives one chunk of output
+///
+/// `chunk` points to `len` bytes that are only valid during the call; `ctx`
+/// is the pointer given to the `_stream` function, passed through unchanged.
+pub type StarkChunkCallback =
+    Option<unsafe extern "C" fn(ctx: *mut c_void, chunk: *const u8, len: usize)>;
+
+/// Deliver `felts` to `callback` in chunks (a null callback is `NullPointer`)
+pub(crate) unsafe fn stream_felts(
+    felts: &[Felt],
+    callback: StarkChunkCallback,
+    ctx: *mut c_void,
+) -> StarkResult {
+    let callback = match callback {
+        Some(c) => c,
+        None => return null_pointer("callback"),
+    };
+
+    log!(
+        Trace,
+        "streaming {} felts in {} chunks",
+        felts.len(),
+        felts.len().div_ceil(CHUNK_FELTS)
+    );
+    let mut chunk = [0u8; STREAM_CHUNK_MAX_LEN];
+    for felts in felts.chunks(CHUNK_FELTS) {
+        for (bytes, felt) in chunk.chunks_exact_mut(32).zip(felts) {
+            bytes.copy_from_slice(&felt_to_bytes(felt));
+        }
+        callback(ctx, chunk.as_ptr(), felts.len() * 32);
+    }
+    StarkResult::Success
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Callback appending every chunk to the `Vec<Vec<u8>>` behind `ctx`
+    pub(crate) unsafe extern "C" fn collect_chunks(ctx: *mut c_void, chunk: *const u8, len: usize) {
+        let chunks = &mut *ctx.cast::<Vec<Vec<u8>>>();
+        chunks.push(core::slice::from_raw_parts(chunk, len).to_vec());
+    }
+
+    #[test]
+    fn test_stream_felts_chunks() {
+        let felts: Vec<Felt> = (0..CHUNK_FELTS as u64 + 1).map(Felt::from).collect();
+        let mut chunks: Vec<Vec<u8>> = Vec::new();
+        let ctx = (&mut chunks as *mut Vec<Vec<u8>>).cast();
+        unsafe {
+            assert_eq!(
+                stream_felts(&felts, Some(collect_chunks), ctx),
+                StarkResult::Success
+            );
+            assert_eq!(
+                stream_felts(&[], Some(collect_chunks), ctx),
+                StarkResult::Success
+            );
+            assert_eq!(stream_felts(&felts, None, ctx), StarkResult::NullPointer);
+        }
+
+        let lens: Vec<usize> = chunks.iter().map(Vec::len).collect();
+        assert_eq!(lens, [STREAM_CHUNK_MAX_LEN, 32]);
+        assert_eq!(chunks[1], felt_to_bytes(&Felt::from(CHUNK_FELTS as u64)));
+        let streamed: Vec<u8> = chunks.concat();
+        let expected: Vec<u8> = felts.iter().flat_map(felt_to_bytes).collect();
+        assert_eq!(streamed, expected);
+    }
+}
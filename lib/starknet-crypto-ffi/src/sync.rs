@@ -0,0 +1,103 @@
+//! Shared state, with and without `std`
+//!
+//! With `std`, `Mutex` is the standard mutex minus poisoning (no critical
+//! section in the crate can leave its data inconsistent), `OnceLock` is the
+//! standard one and `local!` statics are thread-locals.
+//!
+//! A `no_std` build has no threads to tell apart, so it requires the host to
+//! never call into the library from two threads (or an interrupt handler)
+//! at once. Under that contract, `local!` statics are process-wide, a
+//! `Mutex` is a `RefCell` (locking it twice panics instead of deadlocking)
+//! and `OnceLock` is a plain cell. None of them needs atomic
+//! read-modify-write instructions, which small microcontrollers lack.
+
+#[cfg(feature = "std")]
+pub(crate) use std::sync::{MutexGuard, OnceLock};
+
+#[cfg(not(feature = "std"))]
+pub(crate) use core::cell::RefMut as MutexGuard;
+
+/// Mutual exclusion for a `static` (see the module docs)
+pub(crate) struct Mutex<T> {
+    #[cfg(feature = "std")]
+    inner: std::sync::Mutex<T>,
+    #[cfg(not(feature = "std"))]
+    inner: core::cell::RefCell<T>,
+}
+
+// Without `std` the host never calls in concurrently (see the module docs)
+#[cfg(not(feature = "std"))]
+unsafe impl<T: Send> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            inner: std::sync::Mutex::new(value),
+            #[cfg(not(feature = "std"))]
+            inner: core::cell::RefCell::new(value),
+        }
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
+        #[cfg(feature = "std")]
+        return self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        #[cfg(not(feature = "std"))]
+        return self.inner.borrow_mut();
+    }
+}
+
+/// A value initialized on first use (see the module docs)
+#[cfg(not(feature = "std"))]
+pub(crate) struct OnceLock<T>(core::cell::UnsafeCell<Option<T>>);
+
+#[cfg(not(feature = "std"))]
+unsafe impl<T: Send + Sync> Sync for OnceLock<T> {}
+
+#[cfg(not(feature = "std"))]
+impl<T> OnceLock<T> {
+    pub(crate) const fn new() -> Self {
+        Self(core::cell::UnsafeCell::new(None))
+    }
+
+    pub(crate) fn get_or_init(&self, init: impl FnOnce() -> T) -> &T {
+        // Safety: calls are not concurrent, and the value is only written
+        // while no reference to it exists (before the first one is returned)
+        unsafe {
+            if (*self.0.get()).is_none() {
+                let value = init();
+                *self.0.get() = Some(value);
+            }
+            match &*self.0.get() {
+                Some(value) => value,
+                None => unreachable!(),
+            }
+        }
+    }
+}
+
+/// Storage of a `local!` static without `std`: one process-wide value
+#[cfg(not(feature = "std"))]
+pub(crate) struct Local<T>(T);
+
+#[cfg(not(feature = "std"))]
+unsafe impl<T> Sync for Local<T> {}
+
+#[cfg(not(feature = "std"))]
+impl<T> Local<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub(crate) fn with<R>(&'static self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.0)
+    }
+
+    /// Same as `with` (there is no thread teardown to fail on)
+    pub(crate) fn try_with<R>(
+        &'static self,
+        f: impl FnOnce(&T) -> R,
+    ) -> Result<R, core::convert::Infallible> {
+        Ok(f(&self.0))
+    }
+}
@@ -0,0 +1,407 @@
+//! Test vector export (the `test-vectors` feature)
+//!
+//! Emits JSON test vectors, inputs and expected outputs, for every hash,
+//! signature and transaction hash function, so reimplementations (the Zig
+//! wrappers, other languages) can check themselves against this library.
+//! Inputs are derived deterministically from a label and an index, so two
+//! builds of the same version emit identical files. The
+//! `kundera-test-vectors` binary writes the JSON to stdout.
+//!
+//! Each function has an array of cases under its C name. Felts are
+//! `0x`-prefixed 64-digit hex, byte strings are `0x`-prefixed hex.
+//!
+//! ```json
+//! { "version": "0.1.0",
+//!   "starknet_pedersen_hash": [{ "a": "0x…", "b": "0x…", "out": "0x…" }], … }
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use starknet_crypto::Felt;
+
+use crate::buffer::alloc_bytes_out;
+use crate::transaction::{
+    starknet_sign_invoke_v3, StarknetInvokeV3, StarknetResourceBounds, DA_MODE_L1, DA_MODE_L2,
+};
+use crate::{
+    ffi_guard, keccak256, sn_keccak, starknet_get_public_key, starknet_keccak256,
+    starknet_pedersen_hash, starknet_poseidon_hash, starknet_poseidon_hash_many,
+    starknet_rfc6979_k, starknet_sign, starknet_sign_recoverable, starknet_sign_with_k,
+    starknet_verify, FeltBytes, StarkResult,
+};
+
+/// Cases per function
+const CASES: u64 = 8;
+
+/// Deterministic input felt: `sn_keccak("<label>/<index>")`
+fn input(label: &str, index: u64) -> FeltBytes {
+    sn_keccak(format!("{label}/{index}").as_bytes()).to_bytes_be()
+}
+
+/// Deterministic input bytes of length `len`: `keccak256("<label>/<i>")`
+/// blocks (whole bytes, unlike felts)
+fn input_bytes(label: &str, len: usize) -> Vec<u8> {
+    (0..len.div_ceil(32))
+        .flat_map(|i| Keccak256::digest(format!("{label}/{i}").as_bytes()))
+        .take(len)
+        .collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut out = String::from("0x");
+    for byte in bytes {
+        out.push_str(&format!("{byte:02x}"));
+    }
+    out
+}
+
+fn felt(bytes: &FeltBytes) -> String {
+    hex(bytes)
+}
+
+/// Panic on a failed call: the inputs are chosen to be valid, so a failure
+/// is a bug in the library, not a vector to export
+fn expect_success(result: StarkResult, function: &str) {
+    assert_eq!(result, StarkResult::Success, "{function} failed");
+}
+
+fn pedersen() -> Value {
+    let cases = (0..CASES).map(|i| {
+        let (a, b) = (input("pedersen/a", i), input("pedersen/b", i));
+        let mut out = [0u8; 32];
+        expect_success(
+            unsafe { starknet_pedersen_hash(&a, &b, &mut out) },
+            "starknet_pedersen_hash",
+        );
+        json!({ "a": felt(&a), "b": felt(&b), "out": felt(&out) })
+    });
+    cases.collect()
+}
+
+fn poseidon() -> Value {
+    let cases = (0..CASES).map(|i| {
+        let (a, b) = (input("poseidon/a", i), input("poseidon/b", i));
+        let mut out = [0u8; 32];
+        expect_success(
+            unsafe { starknet_poseidon_hash(&a, &b, &mut out) },
+            "starknet_poseidon_hash",
+        );
+        json!({ "a": felt(&a), "b": felt(&b), "out": felt(&out) })
+    });
+    cases.collect()
+}
+
+/// One case per input count from 1 to `CASES`, across the sponge's rate
+/// boundaries
+fn poseidon_many() -> Value {
+    let cases = (1..=CASES).map(|count| {
+        let inputs: Vec<FeltBytes> = (0..count)
+            .map(|i| input(&format!("poseidon_many/{count}"), i))
+            .collect();
+        let mut out = [0u8; 32];
+        expect_success(
+            unsafe { starknet_poseidon_hash_many(inputs.as_ptr(), inputs.len(), &mut out) },
+            "starknet_poseidon_hash_many",
+        );
+        json!({ "inputs": inputs.iter().map(felt).collect::<Vec<_>>(), "out": felt(&out) })
+    });
+    cases.collect()
+}
+
+/// Byte lengths around keccak's 136-byte rate
+const KECCAK_LENGTHS: &[usize] = &[0, 1, 31, 32, 135, 136, 137, 300];
+
+fn keccak(
+    function: &str,
+    hash: unsafe extern "C" fn(*const u8, usize, *mut FeltBytes) -> StarkResult,
+) -> Value {
+    let cases = KECCAK_LENGTHS.iter().map(|&len| {
+        let data = input_bytes(&format!("{function}/{len}"), len);
+        let mut out = [0u8; 32];
+        expect_success(
+            unsafe { hash(data.as_ptr(), data.len(), &mut out) },
+            function,
+        );
+        json!({ "data": hex(&data), "out": felt(&out) })
+    });
+    cases.collect()
+}
+
+/// Private keys: small values, then random-looking ones
+fn private_key(index: u64) -> FeltBytes {
+    match index {
+        0..=2 => Felt::from(index + 1).to_bytes_be(),
+        _ => input("private_key", index),
+    }
+}
+
+fn get_public_key() -> Value {
+    let cases = (0..CASES).map(|i| {
+        let private_key = private_key(i);
+        let mut public_key = [0u8; 32];
+        expect_success(
+            unsafe { starknet_get_public_key(&private_key, &mut public_key) },
+            "starknet_get_public_key",
+        );
+        json!({ "private_key": felt(&private_key), "public_key": felt(&public_key) })
+    });
+    cases.collect()
+}
+
+/// `starknet_sign` (RFC 6979 nonce), with the nonce and the recovery id
+fn sign() -> Value {
+    let cases = (0..CASES).map(|i| {
+        let private_key = private_key(i);
+        let message_hash = input("sign/message_hash", i);
+        let (mut public_key, mut k) = ([0u8; 32], [0u8; 32]);
+        let (mut r, mut s, mut v) = ([0u8; 32], [0u8; 32], [0u8; 32]);
+        unsafe {
+            expect_success(
+                starknet_get_public_key(&private_key, &mut public_key),
+                "starknet_get_public_key",
+            );
+            expect_success(
+                starknet_rfc6979_k(&message_hash, &private_key, core::ptr::null(), &mut k),
+                "starknet_rfc6979_k",
+            );
+            expect_success(
+                starknet_sign(&private_key, &message_hash, &mut r, &mut s),
+                "starknet_sign",
+            );
+            let (mut r2, mut s2) = ([0u8; 32], [0u8; 32]);
+            expect_success(
+                starknet_sign_recoverable(&private_key, &message_hash, &mut r2, &mut s2, &mut v),
+                "starknet_sign_recoverable",
+            );
+            assert_eq!((r, s), (r2, s2), "starknet_sign_recoverable disagrees");
+            expect_success(
+                starknet_verify(&public_key, &message_hash, &r, &s),
+                "starknet_verify",
+            );
+        }
+        json!({
+            "private_key": felt(&private_key),
+            "message_hash": felt(&message_hash),
+            "public_key": felt(&public_key),
+            "k": felt(&k),
+            "r": felt(&r),
+            "s": felt(&s),
+            "v": felt(&v),
+        })
+    });
+    cases.collect()
+}
+
+fn sign_with_k() -> Value {
+    let cases = (0..CASES).map(|i| {
+        let private_key = private_key(i);
+        let message_hash = input("sign_with_k/message_hash", i);
+        let k = input("sign_with_k/k", i);
+        let (mut r, mut s) = ([0u8; 32], [0u8; 32]);
+        expect_success(
+            unsafe { starknet_sign_with_k(&private_key, &message_hash, &k, &mut r, &mut s) },
+            "starknet_sign_with_k",
+        );
+        json!({
+            "private_key": felt(&private_key),
+            "message_hash": felt(&message_hash),
+            "k": felt(&k),
+            "r": felt(&r),
+            "s": felt(&s),
+        })
+    });
+    cases.collect()
+}
+
+fn bounds_json(bounds: &StarknetResourceBounds) -> Value {
+    json!({
+        "max_amount": format!("{:#x}", bounds.max_amount),
+        "max_price_per_unit": felt(&bounds.max_price_per_unit),
+    })
+}
+
+/// INVOKE v3 hashes and signatures, with calldata lengths 0 to `CASES - 1`
+fn invoke_v3() -> Value {
+    let cases = (0..CASES).map(|i| {
+        let field = |name: &str| input(&format!("invoke_v3/{name}"), i);
+        let felts = |name: &str, len: u64| -> Vec<FeltBytes> {
+            (0..len)
+                .map(|j| input(&format!("invoke_v3/{name}/{i}"), j))
+                .collect()
+        };
+        let bounds = |name: &str| StarknetResourceBounds {
+            max_amount: u64::from_be_bytes(field(name)[24..].try_into().unwrap()),
+            max_price_per_unit: Felt::from(u128::from_be_bytes(
+                field(&format!("{name}/price"))[16..].try_into().unwrap(),
+            ))
+            .to_bytes_be(),
+        };
+        let private_key = private_key(i);
+        let chain_id = sn_keccak(b"SN_SEPOLIA").to_bytes_be();
+        let calldata = felts("calldata", i);
+        let paymaster_data = felts("paymaster_data", i % 2);
+        let account_deployment_data = felts("account_deployment_data", i % 3);
+        let tx = StarknetInvokeV3 {
+            sender_address: field("sender_address"),
+            calldata: calldata.as_ptr(),
+            calldata_len: calldata.len(),
+            nonce: Felt::from(i).to_bytes_be(),
+            tip: i * 1000,
+            l1_gas: bounds("l1_gas"),
+            l2_gas: bounds("l2_gas"),
+            l1_data_gas: bounds("l1_data_gas"),
+            paymaster_data: paymaster_data.as_ptr(),
+            paymaster_data_len: paymaster_data.len(),
+            account_deployment_data: account_deployment_data.as_ptr(),
+            account_deployment_data_len: account_deployment_data.len(),
+            nonce_data_availability_mode: if i % 2 == 0 { DA_MODE_L1 } else { DA_MODE_L2 },
+            fee_data_availability_mode: DA_MODE_L1,
+        };
+        let (mut r, mut s, mut tx_hash) = ([0u8; 32], [0u8; 32], [0u8; 32]);
+        expect_success(
+            unsafe {
+                starknet_sign_invoke_v3(&private_key, &tx, &chain_id, &mut r, &mut s, &mut tx_hash)
+            },
+            "starknet_sign_invoke_v3",
+        );
+        let list = |felts: &[FeltBytes]| felts.iter().map(felt).collect::<Vec<_>>();
+        json!({
+            "private_key": felt(&private_key),
+            "chain_id": felt(&chain_id),
+            "tx": {
+                "sender_address": felt(&tx.sender_address),
+                "calldata": list(&calldata),
+                "nonce": felt(&tx.nonce),
+                "tip": format!("{:#x}", tx.tip),
+                "l1_gas": bounds_json(&tx.l1_gas),
+                "l2_gas": bounds_json(&tx.l2_gas),
+                "l1_data_gas": bounds_json(&tx.l1_data_gas),
+                "paymaster_data": list(&paymaster_data),
+                "account_deployment_data": list(&account_deployment_data),
+                "nonce_data_availability_mode": tx.nonce_data_availability_mode,
+                "fee_data_availability_mode": tx.fee_data_availability_mode,
+            },
+            "tx_hash": felt(&tx_hash),
+            "r": felt(&r),
+            "s": felt(&s),
+        })
+    });
+    cases.collect()
+}
+
+#[cfg(feature = "secp")]
+fn secp256k1() -> Value {
+    use crate::secp256k1::{secp256k1_get_public_key, secp256k1_sign_recoverable};
+
+    let cases = (0..CASES).map(|i| {
+        let private_key = input("secp256k1/private_key", i);
+        let message_hash = input("secp256k1/message_hash", i);
+        let mut public_key = [0u8; 64];
+        let (mut r, mut s, mut v) = ([0u8; 32], [0u8; 32], 0u8);
+        unsafe {
+            expect_success(
+                secp256k1_get_public_key(&private_key, &mut public_key),
+                "secp256k1_get_public_key",
+            );
+            expect_success(
+                secp256k1_sign_recoverable(&private_key, &message_hash, &mut r, &mut s, &mut v),
+                "secp256k1_sign_recoverable",
+            );
+        }
+        json!({
+            "private_key": hex(&private_key),
+            "message_hash": hex(&message_hash),
+            "public_key": hex(&public_key),
+            "r": hex(&r),
+            "s": hex(&s),
+            "v": v,
+        })
+    });
+    cases.collect()
+}
+
+/// Every test vector, as a JSON object keyed by function name
+pub fn vectors() -> Value {
+    #[allow(unused_mut)]
+    let mut vectors = json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "starknet_pedersen_hash": pedersen(),
+        "starknet_poseidon_hash": poseidon(),
+        "starknet_poseidon_hash_many": poseidon_many(),
+        "keccak256": keccak("keccak256", keccak256),
+        "starknet_keccak256": keccak("starknet_keccak256", starknet_keccak256),
+        "starknet_get_public_key": get_public_key(),
+        "starknet_sign": sign(),
+        "starknet_sign_with_k": sign_with_k(),
+        "starknet_sign_invoke_v3": invoke_v3(),
+    });
+    #[cfg(feature = "secp")]
+    {
+        vectors["secp256k1_sign_recoverable"] = secp256k1();
+    }
+    vectors
+}
+
+/// Generate the test vectors as JSON into a library-allocated buffer
+///
+/// Writes the JSON (UTF-8, pretty-printed, not NUL-terminated) to a new
+/// buffer in `out_json` and its length to `out_len`; release it with
+/// `stark_buffer_free(*out_json, *out_len)`. Takes a few hundred
+/// milliseconds (it signs and hashes every case).
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_stark_test_vectors_json_alloc"
+)]
+pub unsafe extern "C" fn stark_test_vectors_json_alloc(
+    out_json: *mut *mut u8,
+    out_len: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_json, out_len);
+        match serde_json::to_string_pretty(&vectors()) {
+            Ok(json) => alloc_bytes_out(json.into_bytes(), out_json, out_len),
+            Err(_) => StarkResult::InternalPanic,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::stark_buffer_free;
+
+    #[test]
+    fn test_vectors_are_deterministic() {
+        let (mut ptr, mut len) = (core::ptr::null_mut(), 0);
+        let json = unsafe {
+            assert_eq!(
+                stark_test_vectors_json_alloc(&mut ptr, &mut len),
+                StarkResult::Success
+            );
+            let json = core::slice::from_raw_parts(ptr, len).to_vec();
+            stark_buffer_free(ptr, len);
+            json
+        };
+        let parsed: Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed, vectors());
+
+        let pedersen = &parsed["starknet_pedersen_hash"];
+        assert_eq!(pedersen.as_array().unwrap().len(), CASES as usize);
+        assert_eq!(pedersen[0]["out"].as_str().unwrap().len(), 66);
+        assert_eq!(parsed["keccak256"][0]["data"], "0x");
+        assert_eq!(
+            parsed["keccak256"][0]["out"],
+            "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        // Private key 1: the generator's x coordinate
+        assert_eq!(
+            parsed["starknet_get_public_key"][0]["public_key"],
+            "0x01ef15c18599971b7beced415a40f0c7deacfd9b0d1819e03d723d8bc943cfca"
+        );
+    }
+}
@@ -0,0 +1,323 @@
+//! V3 transaction hashing and signing (SNIP-8)
+//!
+//! Hashes follow the sequencer's `get_invoke_transaction_v3_hash`: a Poseidon
+//! hash chain over the transaction fields, with the tip and resource bounds
+//! folded into one hash. Each bound is packed as
+//! `0 (8) || resource_name (56) || max_amount (64) || max_price_per_unit (128)`.
+//!
+//! All three bounds (L1 gas, L2 gas, L1 data gas) are hashed, as required for
+//! transactions since Starknet 0.13.4.
+
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use starknet_crypto::{poseidon_hash_many, rfc6979_generate_k, sign, Felt};
+use zeroize::Zeroizing;
+
+use crate::perf::{self, Op};
+use crate::{
+    error::{invalid_input, null_pointer},
+    felt_from_bytes, felt_to_bytes, felts_from_raw, ffi_guard, FeltBytes, StarkResult,
+};
+
+/// 'invoke' as a short string
+const INVOKE_PREFIX: Felt = Felt::from_hex_unchecked("0x696e766f6b65");
+const TRANSACTION_VERSION_3: u64 = 3;
+
+const L1_GAS: &[u8; 7] = b"\0L1_GAS";
+const L2_GAS: &[u8; 7] = b"\0L2_GAS";
+const L1_DATA_GAS: &[u8; 7] = b"L1_DATA";
+
+/// Data availability mode: L1
+pub const DA_MODE_L1: u32 = 0;
+/// Data availability mode: L2
+pub const DA_MODE_L2: u32 = 1;
+
+/// Bounds for one resource. `max_price_per_unit` is a big-endian felt that
+/// must fit in 128 bits.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct StarknetResourceBounds {
+    pub max_amount: u64,
+    pub max_price_per_unit: FeltBytes,
+}
+
+/// Fields of an INVOKE v3 transaction, except the signature
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct StarknetInvokeV3 {
+    pub sender_address: FeltBytes,
+    pub calldata: *const FeltBytes,
+    pub calldata_len: usize,
+    pub nonce: FeltBytes,
+    pub tip: u64,
+    pub l1_gas: StarknetResourceBounds,
+    pub l2_gas: StarknetResourceBounds,
+    pub l1_data_gas: StarknetResourceBounds,
+    pub paymaster_data: *const FeltBytes,
+    pub paymaster_data_len: usize,
+    pub account_deployment_data: *const FeltBytes,
+    pub account_deployment_data_len: usize,
+    pub nonce_data_availability_mode: u32,
+    pub fee_data_availability_mode: u32,
+}
+
+/// Validated INVOKE v3 fields
+struct InvokeV3 {
+    sender_address: Felt,
+    calldata: Vec<Felt>,
+    nonce: Felt,
+    tip: u64,
+    resource_bounds: Vec<Felt>,
+    paymaster_data: Vec<Felt>,
+    account_deployment_data: Vec<Felt>,
+    nonce_data_availability_mode: u32,
+    fee_data_availability_mode: u32,
+}
+
+fn resource_bound(name: &[u8; 7], bounds: &StarknetResourceBounds) -> Option<Felt> {
+    if bounds.max_price_per_unit[..16] != [0u8; 16] {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    bytes[1..8].copy_from_slice(name);
+    bytes[8..16].copy_from_slice(&bounds.max_amount.to_be_bytes());
+    bytes[16..].copy_from_slice(&bounds.max_price_per_unit[16..]);
+    Some(Felt::from_bytes_be(&bytes))
+}
+
+/// `nonce_mode (32) || fee_mode (32)` in the low 64 bits
+fn data_availability_modes(nonce_mode: u32, fee_mode: u32) -> Felt {
+    Felt::from(((nonce_mode as u64) << 32) | fee_mode as u64)
+}
+
+impl InvokeV3 {
+    /// `NullPointer` for a null array with a non-zero length, `InvalidInput`
+    /// for an invalid field
+    unsafe fn from_raw(tx: &StarknetInvokeV3) -> Result<Self, StarkResult> {
+        for mode in [
+            tx.nonce_data_availability_mode,
+            tx.fee_data_availability_mode,
+        ] {
+            if mode != DA_MODE_L1 && mode != DA_MODE_L2 {
+                return Err(invalid_input(format!(
+                    "unknown data availability mode {mode} (expected 0 for L1 or 1 for L2)"
+                )));
+            }
+        }
+        let felts = |ptr, len, name| felts_from_raw(ptr, len).ok_or_else(|| null_pointer(name));
+        let invalid = StarkResult::InvalidInput;
+
+        Ok(Self {
+            sender_address: felt_from_bytes(&tx.sender_address).ok_or(invalid)?,
+            calldata: felts(tx.calldata, tx.calldata_len, "calldata")?,
+            nonce: felt_from_bytes(&tx.nonce).ok_or(invalid)?,
+            tip: tx.tip,
+            resource_bounds: vec![
+                resource_bound(L1_GAS, &tx.l1_gas).ok_or(invalid)?,
+                resource_bound(L2_GAS, &tx.l2_gas).ok_or(invalid)?,
+                resource_bound(L1_DATA_GAS, &tx.l1_data_gas).ok_or(invalid)?,
+            ],
+            paymaster_data: felts(tx.paymaster_data, tx.paymaster_data_len, "paymaster_data")?,
+            account_deployment_data: felts(
+                tx.account_deployment_data,
+                tx.account_deployment_data_len,
+                "account_deployment_data",
+            )?,
+            nonce_data_availability_mode: tx.nonce_data_availability_mode,
+            fee_data_availability_mode: tx.fee_data_availability_mode,
+        })
+    }
+
+    fn hash(&self, chain_id: &Felt) -> Felt {
+        let mut fee_fields = vec![Felt::from(self.tip)];
+        fee_fields.extend_from_slice(&self.resource_bounds);
+
+        poseidon_hash_many(&[
+            INVOKE_PREFIX,
+            Felt::from(TRANSACTION_VERSION_3),
+            self.sender_address,
+            poseidon_hash_many(&fee_fields),
+            poseidon_hash_many(&self.paymaster_data),
+            *chain_id,
+            self.nonce,
+            data_availability_modes(
+                self.nonce_data_availability_mode,
+                self.fee_data_availability_mode,
+            ),
+            poseidon_hash_many(&self.account_deployment_data),
+            poseidon_hash_many(&self.calldata),
+        ])
+    }
+}
+
+/// Hash of an INVOKE v3 transaction (errors as for
+/// `starknet_sign_invoke_v3`)
+#[cfg(any(feature = "napi", feature = "python"))]
+pub(crate) unsafe fn invoke_v3_hash(
+    tx: &StarknetInvokeV3,
+    chain_id: &Felt,
+) -> Result<Felt, StarkResult> {
+    Ok(InvokeV3::from_raw(tx)?.hash(chain_id))
+}
+
+/// Hash and sign an INVOKE v3 transaction in one call
+///
+/// Writes the transaction hash and its signature (r, s), produced exactly as
+/// `starknet_sign` would sign that hash. Returns `InvalidInput` if a field is
+/// not a valid felt, a `max_price_per_unit` exceeds 128 bits or a data
+/// availability mode is not `DA_MODE_L1`/`DA_MODE_L2`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_sign_invoke_v3")]
+pub unsafe extern "C" fn starknet_sign_invoke_v3(
+    private_key: *const FeltBytes,
+    tx: *const StarknetInvokeV3,
+    chain_id: *const FeltBytes,
+    out_r: *mut FeltBytes,
+    out_s: *mut FeltBytes,
+    out_tx_hash: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Transaction);
+        check_null!(private_key, tx, chain_id, out_r, out_s, out_tx_hash);
+        let pk = match felt_from_bytes(&*private_key) {
+            Some(f) => Zeroizing::new(f),
+            None => return StarkResult::InvalidInput,
+        };
+        let chain_id = match felt_from_bytes(&*chain_id) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let tx = match InvokeV3::from_raw(&*tx) {
+            Ok(tx) => tx,
+            Err(e) => return e,
+        };
+
+        let tx_hash = tx.hash(&chain_id);
+        let k = Zeroizing::new(rfc6979_generate_k(&tx_hash, &pk, None));
+        match sign(&pk, &tx_hash, &k) {
+            Ok(sig) => {
+                *out_r = felt_to_bytes(&sig.r);
+                *out_s = felt_to_bytes(&sig.s);
+                *out_tx_hash = felt_to_bytes(&tx_hash);
+                StarkResult::Success
+            }
+            Err(_) => StarkResult::InvalidInput,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{starknet_get_public_key, starknet_verify};
+
+    fn felt(hex: &str) -> Felt {
+        Felt::from_hex_unchecked(hex)
+    }
+
+    fn bounds(max_amount: u64, max_price_per_unit: u128) -> StarknetResourceBounds {
+        StarknetResourceBounds {
+            max_amount,
+            max_price_per_unit: felt_to_bytes(&Felt::from(max_price_per_unit)),
+        }
+    }
+
+    #[test]
+    fn test_invoke_v3_hash_mainnet() {
+        // Mainnet block 636864, before L1 data gas was part of the hash
+        let calldata = [
+            felt("0x1"),
+            felt("0x4c0a5193d58f74fbace4b74dcf65481e734ed1714121bdc571da345540efa05"),
+            felt("0x3943907ef0ef6f9d2e2408b05e520a66daaf74293dbf665e5a20b117676170e"),
+            felt("0x2"),
+            felt("0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7"),
+            felt("0x16345785d8a0000"),
+        ];
+        let tx = InvokeV3 {
+            sender_address: felt(
+                "0x69c0f9bcd79697bdceaf7748e3ff8f34aa39e4063ce44896af664c0c96f6c10",
+            ),
+            calldata: calldata.to_vec(),
+            nonce: felt("0x9d"),
+            tip: 0,
+            resource_bounds: vec![
+                resource_bound(L1_GAS, &bounds(0xa9e, 0x7f2a1ad4f2f1)).unwrap(),
+                resource_bound(L2_GAS, &bounds(0, 0)).unwrap(),
+            ],
+            paymaster_data: vec![],
+            account_deployment_data: vec![],
+            nonce_data_availability_mode: DA_MODE_L1,
+            fee_data_availability_mode: DA_MODE_L1,
+        };
+
+        let chain_id = Felt::from_bytes_be_slice(b"SN_MAIN");
+        assert_eq!(
+            tx.hash(&chain_id),
+            felt("0x1d4735f4ba73a67be2f648d9b21cab3783383b8c229566b46b027c46012219")
+        );
+    }
+
+    #[test]
+    fn test_resource_bound_packing() {
+        let packed = resource_bound(L1_DATA_GAS, &bounds(0x1234, 0x5678)).unwrap();
+        assert_eq!(
+            packed,
+            felt("0x4c315f44415441000000000000123400000000000000000000000000005678")
+        );
+
+        let mut too_expensive = bounds(1, 0);
+        too_expensive.max_price_per_unit[15] = 1;
+        assert!(resource_bound(L1_GAS, &too_expensive).is_none());
+    }
+
+    #[test]
+    fn test_sign_invoke_v3() {
+        let private_key = felt_to_bytes(&Felt::from(12345u64));
+        let chain_id = felt_to_bytes(&Felt::from_bytes_be_slice(b"SN_SEPOLIA"));
+        let calldata = [felt_to_bytes(&Felt::ONE), felt_to_bytes(&Felt::TWO)];
+        let mut tx = StarknetInvokeV3 {
+            sender_address: felt_to_bytes(&felt("0x1234")),
+            calldata: calldata.as_ptr(),
+            calldata_len: calldata.len(),
+            nonce: felt_to_bytes(&Felt::from(7u64)),
+            tip: 0,
+            l1_gas: bounds(0, 0),
+            l2_gas: bounds(0x2000000, 0x1000000000),
+            l1_data_gas: bounds(0x200, 0x2000),
+            paymaster_data: core::ptr::null(),
+            paymaster_data_len: 0,
+            account_deployment_data: core::ptr::null(),
+            account_deployment_data_len: 0,
+            nonce_data_availability_mode: DA_MODE_L1,
+            fee_data_availability_mode: DA_MODE_L1,
+        };
+        let mut public_key = [0u8; 32];
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        let mut tx_hash = [0u8; 32];
+
+        unsafe {
+            let result =
+                starknet_sign_invoke_v3(&private_key, &tx, &chain_id, &mut r, &mut s, &mut tx_hash);
+            assert_eq!(result, StarkResult::Success);
+
+            let expected = InvokeV3::from_raw(&tx)
+                .unwrap()
+                .hash(&felt_from_bytes(&chain_id).unwrap());
+            assert_eq!(tx_hash, felt_to_bytes(&expected));
+
+            starknet_get_public_key(&private_key, &mut public_key);
+            assert_eq!(
+                starknet_verify(&public_key, &tx_hash, &r, &s),
+                StarkResult::Success
+            );
+
+            tx.fee_data_availability_mode = 2;
+            let result =
+                starknet_sign_invoke_v3(&private_key, &tx, &chain_id, &mut r, &mut s, &mut tx_hash);
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! Fiat–Shamir transcripts over the Poseidon hash
+//!
+//! A `Transcript` is the channel of a non-interactive proof: the prover and
+//! the verifier append the same messages in the same order and draw the same
+//! challenges from it. It keeps a digest, initially 0, and the number of
+//! challenges drawn since the last message:
+//!
+//! - appending felt `x` sets `digest = poseidon_hash(digest, x)` and resets
+//!   the challenge count;
+//! - challenge `i` is `poseidon_hash(digest, i)`;
+//! - appending bytes sets `digest = poseidon_hash_many([digest, len,
+//!   chunks...])`, the chunks being the bytes in 31-byte big-endian pieces
+//!   (the last one possibly shorter), and resets the challenge count.
+//!
+//! Only the first two follow another implementation: stwo's
+//! `Poseidon252Channel` (`mix_root` and `draw_felt252`). The byte encoding
+//! is this library's own, and Stone's channel is not covered, so a
+//! transcript mixing bytes matches neither prover. The tests check the
+//! rules above; no vector from stwo's channel tests is available to this
+//! build.
+
+use alloc::vec::Vec;
+
+use starknet_crypto::{poseidon_hash, poseidon_hash_many, Felt};
+
+use crate::handle::StarkHandle;
+use crate::perf::{self, Op};
+use crate::{
+    bytes_from_raw, error::null_pointer, felt_from_bytes, felt_to_bytes, ffi_guard, ffi_guard_void,
+    FeltBytes, StarkResult,
+};
+
+/// Bytes packed into one felt
+const CHUNK_LEN: usize = 31;
+
+/// Fiat–Shamir transcript (opaque)
+pub struct Transcript {
+    digest: Felt,
+    challenges: u64,
+}
+
+impl StarkHandle for Transcript {
+    const NAME: &'static str = "Transcript";
+}
+
+impl Transcript {
+    fn mix(&mut self, digest: Felt) {
+        self.digest = digest;
+        self.challenges = 0;
+    }
+
+    fn append_bytes(&mut self, data: &[u8]) {
+        let mut felts = Vec::with_capacity(2 + data.len().div_ceil(CHUNK_LEN));
+        felts.extend([self.digest, Felt::from(data.len())]);
+        felts.extend(data.chunks(CHUNK_LEN).map(Felt::from_bytes_be_slice));
+        self.mix(poseidon_hash_many(&felts));
+    }
+
+    fn challenge(&mut self) -> Felt {
+        let challenge = poseidon_hash(self.digest, Felt::from(self.challenges));
+        self.challenges += 1;
+        challenge
+    }
+}
+
+/// Create an empty transcript (digest 0)
+///
+/// Writes an owned handle to `out_transcript`; release it with
+/// `transcript_free`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_transcript_new")]
+pub unsafe extern "C" fn transcript_new(out_transcript: *mut *mut Transcript) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_transcript);
+        *out_transcript = Transcript {
+            digest: Felt::ZERO,
+            challenges: 0,
+        }
+        .into_handle();
+        StarkResult::Success
+    })
+}
+
+/// Append a felt to the transcript
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_transcript_append_felt"
+)]
+pub unsafe extern "C" fn transcript_append_felt(
+    transcript: *mut Transcript,
+    value: *const FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Poseidon);
+        check_null!(transcript, value);
+        let transcript = match Transcript::from_handle_mut(transcript) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+        let value = match felt_from_bytes(&*value) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+
+        transcript.mix(poseidon_hash(transcript.digest, value));
+        StarkResult::Success
+    })
+}
+
+/// Append `len` bytes to the transcript
+///
+/// `data` may be null when `len == 0`; an empty message still changes the
+/// digest.
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_transcript_append_bytes"
+)]
+pub unsafe extern "C" fn transcript_append_bytes(
+    transcript: *mut Transcript,
+    data: *const u8,
+    len: usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Poseidon);
+        check_null!(transcript);
+        let transcript = match Transcript::from_handle_mut(transcript) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+        let Some(data) = bytes_from_raw(data, len) else {
+            return null_pointer("data");
+        };
+
+        transcript.append_bytes(data);
+        StarkResult::Success
+    })
+}
+
+/// Draw the next challenge felt
+///
+/// Successive challenges without an append in between are distinct.
+#[no_mangle]
+#[cfg_attr(
+    feature = "symbol-prefix",
+    export_name = "kundera_transcript_challenge_felt"
+)]
+pub unsafe extern "C" fn transcript_challenge_felt(
+    transcript: *mut Transcript,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Poseidon);
+        check_null!(transcript, out);
+        let transcript = match Transcript::from_handle_mut(transcript) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+
+        *out = felt_to_bytes(&transcript.challenge());
+        StarkResult::Success
+    })
+}
+
+/// Free a transcript (null is a no-op)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_transcript_free")]
+pub unsafe extern "C" fn transcript_free(transcript: *mut Transcript) {
+    ffi_guard_void(|| Transcript::free_handle(transcript))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn challenge(transcript: *mut Transcript) -> Felt {
+        let mut out = [0u8; 32];
+        unsafe {
+            assert_eq!(
+                transcript_challenge_felt(transcript, &mut out),
+                StarkResult::Success
+            );
+        }
+        Felt::from_bytes_be(&out)
+    }
+
+    #[test]
+    fn test_transcript() {
+        let data: Vec<u8> = (0..40).collect();
+        unsafe {
+            let mut transcript = core::ptr::null_mut();
+            assert_eq!(transcript_new(&mut transcript), StarkResult::Success);
+            assert_eq!(challenge(transcript), poseidon_hash(Felt::ZERO, Felt::ZERO));
+
+            let x = felt_to_bytes(&Felt::from(42u64));
+            assert_eq!(transcript_append_felt(transcript, &x), StarkResult::Success);
+            let digest = poseidon_hash(Felt::ZERO, Felt::from(42u64));
+            assert_eq!(challenge(transcript), poseidon_hash(digest, Felt::ZERO));
+            assert_eq!(challenge(transcript), poseidon_hash(digest, Felt::ONE));
+
+            assert_eq!(
+                transcript_append_bytes(transcript, data.as_ptr(), data.len()),
+                StarkResult::Success
+            );
+            let digest = poseidon_hash_many(&[
+                digest,
+                Felt::from(40u64),
+                Felt::from_bytes_be_slice(&data[..31]),
+                Felt::from_bytes_be_slice(&data[31..]),
+            ]);
+            assert_eq!(challenge(transcript), poseidon_hash(digest, Felt::ZERO));
+
+            assert_eq!(
+                transcript_append_bytes(transcript, core::ptr::null(), 0),
+                StarkResult::Success
+            );
+            let digest = poseidon_hash_many(&[digest, Felt::ZERO]);
+            assert_eq!(challenge(transcript), poseidon_hash(digest, Felt::ZERO));
+            transcript_free(transcript);
+        }
+    }
+
+    #[test]
+    fn test_transcript_rejects_invalid_input() {
+        let mut out = [0u8; 32];
+        unsafe {
+            assert_eq!(
+                transcript_new(core::ptr::null_mut()),
+                StarkResult::NullPointer
+            );
+            let mut transcript = core::ptr::null_mut();
+            transcript_new(&mut transcript);
+            assert_eq!(
+                transcript_append_bytes(transcript, core::ptr::null(), 1),
+                StarkResult::NullPointer
+            );
+            assert_eq!(
+                transcript_challenge_felt(core::ptr::null_mut(), &mut out),
+                StarkResult::NullPointer
+            );
+            transcript_free(transcript);
+            transcript_free(core::ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,1259 @@
+//! SNIP-12 typed data hashing (revisions 0 and 1)
+//!
+//! Parses a SNIP-12 JSON document (`types`, `primaryType`, `domain`,
+//! `message`) and computes the off-chain message hash signed by accounts:
+//!
+//! `H("StarkNet Message", domain_hash, account, struct_hash(message))`
+//!
+//! Revision 1 (`StarknetDomain`) hashes with Poseidon and JSON-escapes names
+//! in type signatures. The legacy revision 0 (`StarkNetDomain`) uses the
+//! Pedersen array hash, bare names and treats `string` as a short string.
+//!
+//! Follows starknet.js behaviour where it deviates from the spec (felt and
+//! shortstring values, enum type hashes, merkletree dependencies) since that
+//! is what wallets actually sign.
+
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde_json::{Map, Value};
+use starknet_crypto::{poseidon_hash_many, Felt};
+#[cfg(feature = "merkle")]
+use starknet_crypto::poseidon_hash;
+
+#[cfg(feature = "merkle")]
+use crate::arena::{self, optional_arena, StarkArena};
+#[cfg(feature = "merkle")]
+use crate::pedersen::pedersen_hash;
+use crate::perf::{self, Op};
+use crate::{
+    error::invalid_input, felt_from_bytes, felt_to_bytes, ffi_guard, pedersen_hash_array,
+    sn_keccak, FeltBytes, StarkResult,
+};
+
+const DOMAIN_TYPE_NAME_V0: &str = "StarkNetDomain";
+const DOMAIN_TYPE_NAME_V1: &str = "StarknetDomain";
+
+const DOMAIN_TYPE_SIGNATURE_V0: &str = "StarkNetDomain(name:felt,version:felt,chainId:felt)";
+const DOMAIN_TYPE_SIGNATURE_V1: &str = "\"StarknetDomain\"(\"name\":\"shortstring\",\"version\":\"shortstring\",\"chainId\":\"shortstring\",\"revision\":\"shortstring\")";
+
+#[derive(Debug)]
+pub(crate) struct TypedDataError(String);
+
+impl fmt::Display for TypedDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+type Result<T> = core::result::Result<T, TypedDataError>;
+
+fn error<T>(msg: impl Into<String>) -> Result<T> {
+    Err(TypedDataError(msg.into()))
+}
+
+/// SNIP-12 revision, selected by the domain type present in `types`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Revision {
+    V0,
+    V1,
+}
+
+impl Revision {
+    pub(crate) fn hash_many(self, elements: &[Felt]) -> Felt {
+        match self {
+            Self::V0 => pedersen_hash_array(elements),
+            Self::V1 => poseidon_hash_many(elements),
+        }
+    }
+
+    #[cfg(feature = "merkle")]
+    fn hash_pair(self, a: Felt, b: Felt) -> Felt {
+        match self {
+            Self::V0 => pedersen_hash(&a, &b),
+            Self::V1 => poseidon_hash(a, b),
+        }
+    }
+
+    /// Names are JSON-escaped in revision 1 signatures only
+    fn escape(self, name: &str) -> String {
+        match self {
+            Self::V0 => name.to_owned(),
+            Self::V1 => serde_json::to_string(name).expect("string serialization cannot fail"),
+        }
+    }
+}
+
+/// Type reference as written in a field or enum variant definition
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TypeRef {
+    Struct(String),
+    Enum(String),
+    Array(Box<TypeRef>),
+    MerkleTree(Box<TypeRef>),
+    Felt,
+    Bool,
+    String,
+    Selector,
+    U128,
+    I128,
+    ContractAddress,
+    ClassHash,
+    Timestamp,
+    ShortString,
+    U256,
+    TokenAmount,
+    NftId,
+}
+
+/// Preset struct types that are never declared in `types`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Preset {
+    U256,
+    TokenAmount,
+    NftId,
+}
+
+#[derive(Debug)]
+enum TypeDef {
+    Struct(Vec<(String, TypeRef)>),
+    Enum(Vec<(String, Vec<TypeRef>)>),
+}
+
+impl TypeRef {
+    /// Parse a type name that may appear inline (array elements, enum tuples,
+    /// merkletree leaves)
+    ///
+    /// The presets (`u256`, `TokenAmount`, `NftId`) exist in revision 1 only;
+    /// in revision 0 those names refer to types declared in `types`.
+    fn parse_inline(name: &str, revision: Revision) -> Result<Self> {
+        Ok(match name {
+            "felt" => Self::Felt,
+            "bool" => Self::Bool,
+            "string" => Self::String,
+            "selector" => Self::Selector,
+            "u128" => Self::U128,
+            "i128" => Self::I128,
+            "ContractAddress" => Self::ContractAddress,
+            "ClassHash" => Self::ClassHash,
+            "timestamp" => Self::Timestamp,
+            "shortstring" => Self::ShortString,
+            "u256" if revision == Revision::V1 => Self::U256,
+            "TokenAmount" if revision == Revision::V1 => Self::TokenAmount,
+            "NftId" if revision == Revision::V1 => Self::NftId,
+            "merkletree" | "enum" => return error(format!("`{name}` requires `contains`")),
+            _ => match name.strip_suffix('*') {
+                Some(element) if !element.ends_with('*') => {
+                    Self::Array(Box::new(Self::parse_inline(element, revision)?))
+                }
+                Some(_) => return error(format!("nested array type `{name}`")),
+                None if is_valid_type_name(name) => Self::Struct(name.to_owned()),
+                None => return error(format!("invalid type name `{name}`")),
+            },
+        })
+    }
+
+    /// Parse a struct field type (`type` plus optional `contains`)
+    fn parse_field(ty: &str, contains: Option<&str>, revision: Revision) -> Result<Self> {
+        match (ty, contains) {
+            ("enum", Some(name)) if is_valid_type_name(name) => Ok(Self::Enum(name.to_owned())),
+            ("merkletree", Some(leaf)) => {
+                let leaf = Self::parse_inline(leaf, revision)?;
+                if matches!(leaf, Self::Array(_)) {
+                    return error("merkletree leaves cannot be arrays");
+                }
+                Ok(Self::MerkleTree(Box::new(leaf)))
+            }
+            (_, Some(_)) => error(format!("unexpected `contains` on type `{ty}`")),
+            (_, None) => Self::parse_inline(ty, revision),
+        }
+    }
+
+    /// Name of the type as it appears inside a type signature
+    fn signature_name(&self) -> String {
+        match self {
+            Self::Struct(name) | Self::Enum(name) => name.clone(),
+            Self::Array(element) => format!("{}*", element.signature_name()),
+            Self::MerkleTree(_) => "merkletree".to_owned(),
+            Self::Felt => "felt".to_owned(),
+            Self::Bool => "bool".to_owned(),
+            Self::String => "string".to_owned(),
+            Self::Selector => "selector".to_owned(),
+            Self::U128 => "u128".to_owned(),
+            Self::I128 => "i128".to_owned(),
+            Self::ContractAddress => "ContractAddress".to_owned(),
+            Self::ClassHash => "ClassHash".to_owned(),
+            Self::Timestamp => "timestamp".to_owned(),
+            Self::ShortString => "shortstring".to_owned(),
+            Self::U256 => "u256".to_owned(),
+            Self::TokenAmount => "TokenAmount".to_owned(),
+            Self::NftId => "NftId".to_owned(),
+        }
+    }
+}
+
+fn is_valid_type_name(name: &str) -> bool {
+    !(name.is_empty() || name.contains(',') || name.contains('(') || name.contains(')'))
+}
+
+impl Preset {
+    fn name(self) -> &'static str {
+        match self {
+            Self::U256 => "u256",
+            Self::TokenAmount => "TokenAmount",
+            Self::NftId => "NftId",
+        }
+    }
+
+    fn signature(self, revision: Revision) -> &'static str {
+        match (self, revision) {
+            (Self::U256, Revision::V0) => "u256(low:u128,high:u128)",
+            (Self::U256, Revision::V1) => "\"u256\"(\"low\":\"u128\",\"high\":\"u128\")",
+            (Self::TokenAmount, Revision::V0) => {
+                "TokenAmount(token_address:ContractAddress,amount:u256)"
+            }
+            (Self::TokenAmount, Revision::V1) => {
+                "\"TokenAmount\"(\"token_address\":\"ContractAddress\",\"amount\":\"u256\")"
+            }
+            (Self::NftId, Revision::V0) => {
+                "NftId(collection_address:ContractAddress,token_id:u256)"
+            }
+            (Self::NftId, Revision::V1) => {
+                "\"NftId\"(\"collection_address\":\"ContractAddress\",\"token_id\":\"u256\")"
+            }
+        }
+    }
+
+    fn fields(self) -> [(&'static str, TypeRef); 2] {
+        match self {
+            Self::U256 => [("low", TypeRef::U128), ("high", TypeRef::U128)],
+            Self::TokenAmount => [
+                ("token_address", TypeRef::ContractAddress),
+                ("amount", TypeRef::U256),
+            ],
+            Self::NftId => [
+                ("collection_address", TypeRef::ContractAddress),
+                ("token_id", TypeRef::U256),
+            ],
+        }
+    }
+
+    fn type_hash(self, revision: Revision) -> Felt {
+        match self {
+            Self::U256 => sn_keccak(self.signature(revision).as_bytes()),
+            Self::TokenAmount | Self::NftId => {
+                let signature = format!(
+                    "{}{}",
+                    self.signature(revision),
+                    Self::U256.signature(revision)
+                );
+                sn_keccak(signature.as_bytes())
+            }
+        }
+    }
+}
+
+/// Signature source for the type hash: a user type or a preset
+enum Signature<'a> {
+    User(&'a TypeDef),
+    Preset(Preset),
+}
+
+struct TypedData {
+    revision: Revision,
+    types: BTreeMap<String, TypeDef>,
+    primary_type: String,
+    domain_hash: Felt,
+    message: Value,
+}
+
+impl TypedData {
+    fn parse(json: &[u8]) -> Result<Self> {
+        let root: Value = serde_json::from_slice(json)
+            .map_err(|e| TypedDataError(format!("invalid JSON: {e}")))?;
+        let root = root
+            .as_object()
+            .ok_or_else(|| TypedDataError("document must be an object".into()))?;
+
+        let raw_types = match root.get("types") {
+            Some(Value::Object(types)) => types,
+            _ => return error("missing `types` object"),
+        };
+        let primary_type = match root.get("primaryType") {
+            Some(Value::String(name)) => name.clone(),
+            _ => return error("missing `primaryType` string"),
+        };
+        let message = match root.get("message") {
+            Some(message) => message.clone(),
+            None => return error("missing `message`"),
+        };
+        let domain = match root.get("domain") {
+            Some(Value::Object(domain)) => domain,
+            _ => return error("missing `domain` object"),
+        };
+
+        let mut revision = None;
+        for name in raw_types.keys() {
+            let domain_revision = match name.as_str() {
+                DOMAIN_TYPE_NAME_V0 => Revision::V0,
+                DOMAIN_TYPE_NAME_V1 => Revision::V1,
+                _ => continue,
+            };
+            if revision.is_some() {
+                return error("conflicting domain type definitions");
+            }
+            revision = Some(domain_revision);
+        }
+        let revision = revision.ok_or_else(|| TypedDataError("missing domain type".into()))?;
+
+        let mut types = BTreeMap::new();
+        for (name, definition) in raw_types {
+            let definition = parse_type_def(definition, revision)
+                .map_err(|e| TypedDataError(format!("type `{name}`: {e}")))?;
+            match name.as_str() {
+                DOMAIN_TYPE_NAME_V0 | DOMAIN_TYPE_NAME_V1 => {
+                    if !is_domain_type(&definition, revision) {
+                        return error(format!("invalid {name} type definition"));
+                    }
+                }
+                _ => {
+                    types.insert(name.clone(), definition);
+                }
+            }
+        }
+
+        Ok(Self {
+            revision,
+            types,
+            primary_type,
+            domain_hash: domain_hash(domain, revision)?,
+            message,
+        })
+    }
+
+    fn message_hash(&self, account: Felt) -> Result<Felt> {
+        let message =
+            self.encode_value(&TypeRef::Struct(self.primary_type.clone()), &self.message)?;
+        Ok(message_hash(
+            self.revision,
+            self.domain_hash,
+            account,
+            message,
+        ))
+    }
+
+    fn type_def(&self, name: &str) -> Result<&TypeDef> {
+        self.types
+            .get(name)
+            .ok_or_else(|| TypedDataError(format!("type `{name}` is not defined")))
+    }
+
+    /// keccak of the type's signature followed by its dependencies sorted by name
+    fn type_hash(&self, name: &str) -> Result<Felt> {
+        let definition = self.type_def(name)?;
+        let mut dependencies = BTreeMap::new();
+        self.collect_dependencies(definition, &mut dependencies)?;
+
+        let mut signature = String::new();
+        write_signature(
+            name,
+            &Signature::User(definition),
+            self.revision,
+            &mut signature,
+        );
+        for (dep_name, dep) in &dependencies {
+            write_signature(dep_name, dep, self.revision, &mut signature);
+        }
+        Ok(sn_keccak(signature.as_bytes()))
+    }
+
+    fn collect_dependencies<'a>(
+        &'a self,
+        definition: &'a TypeDef,
+        out: &mut BTreeMap<&'a str, Signature<'a>>,
+    ) -> Result<()> {
+        match definition {
+            TypeDef::Struct(fields) => {
+                for (_, ty) in fields {
+                    self.collect_ref_dependencies(ty, out)?;
+                }
+            }
+            TypeDef::Enum(variants) => {
+                for (_, tuple) in variants {
+                    for ty in tuple {
+                        self.collect_ref_dependencies(ty, out)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn collect_ref_dependencies<'a>(
+        &'a self,
+        ty: &'a TypeRef,
+        out: &mut BTreeMap<&'a str, Signature<'a>>,
+    ) -> Result<()> {
+        match ty {
+            TypeRef::Struct(name) | TypeRef::Enum(name) => {
+                let definition = self.type_def(name)?;
+                if out.insert(name, Signature::User(definition)).is_none() {
+                    self.collect_dependencies(definition, out)?;
+                }
+            }
+            TypeRef::Array(element) => self.collect_ref_dependencies(element, out)?,
+            // starknet.js does not treat the merkletree leaf type as a dependency
+            TypeRef::MerkleTree(_) => {}
+            TypeRef::U256 => {
+                out.insert(Preset::U256.name(), Signature::Preset(Preset::U256));
+            }
+            TypeRef::TokenAmount | TypeRef::NftId => {
+                let preset = if *ty == TypeRef::TokenAmount {
+                    Preset::TokenAmount
+                } else {
+                    Preset::NftId
+                };
+                out.insert(preset.name(), Signature::Preset(preset));
+                out.insert(Preset::U256.name(), Signature::Preset(Preset::U256));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn encode_value(&self, ty: &TypeRef, value: &Value) -> Result<Felt> {
+        match ty {
+            TypeRef::Struct(name) | TypeRef::Enum(name) => {
+                let object = expect_object(value)?;
+                match (self.type_def(name)?, ty) {
+                    (TypeDef::Struct(fields), TypeRef::Struct(_)) => {
+                        let mut elements = vec![self.type_hash(name)?];
+                        elements.extend(
+                            self.encode_fields(
+                                fields.iter().map(|(n, t)| (n.as_str(), t)),
+                                object,
+                            )?,
+                        );
+                        Ok(self.revision.hash_many(&elements))
+                    }
+                    (TypeDef::Enum(variants), _) => self.encode_enum(variants, object),
+                    (TypeDef::Struct(_), _) => error(format!("`{name}` is not an enum")),
+                }
+            }
+            TypeRef::Array(element) => {
+                let items = expect_array(value)?;
+                let encoded = items
+                    .iter()
+                    .map(|item| self.encode_value(element, item))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(self.revision.hash_many(&encoded))
+            }
+            #[cfg(not(feature = "merkle"))]
+            TypeRef::MerkleTree(_) => error("merkletree values need the `merkle` feature"),
+            #[cfg(feature = "merkle")]
+            TypeRef::MerkleTree(leaf) => {
+                let items = expect_array(value)?;
+                if items.is_empty() {
+                    return error("empty merkletree");
+                }
+                let mut leaves = items
+                    .iter()
+                    .map(|item| self.encode_value(leaf, item))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(merkle_root(self.revision, &mut leaves))
+            }
+            TypeRef::Felt | TypeRef::ShortString => parse_felt_or_short_string(value),
+            TypeRef::Bool => match value {
+                Value::Bool(b) => Ok(if *b { Felt::ONE } else { Felt::ZERO }),
+                _ => error(format!("expected boolean, got {value}")),
+            },
+            TypeRef::String => {
+                let s = expect_str(value)?;
+                match self.revision {
+                    // Revision 0 treats `string` as a short string
+                    Revision::V0 => short_string_to_felt(s)
+                        .ok_or_else(|| TypedDataError(format!("invalid short string `{s}`"))),
+                    Revision::V1 => Ok(poseidon_hash_many(&byte_array_felts(s.as_bytes()))),
+                }
+            }
+            TypeRef::Selector => selector_from_name(expect_str(value)?),
+            TypeRef::U128 | TypeRef::Timestamp => parse_u128(value).map(Felt::from),
+            TypeRef::I128 => parse_i128(value).map(Felt::from),
+            TypeRef::ContractAddress | TypeRef::ClassHash => {
+                let s = expect_str(value)?;
+                parse_number_str(s).ok_or_else(|| TypedDataError(format!("invalid felt `{s}`")))
+            }
+            TypeRef::U256 => self.encode_preset(Preset::U256, value),
+            TypeRef::TokenAmount => self.encode_preset(Preset::TokenAmount, value),
+            TypeRef::NftId => self.encode_preset(Preset::NftId, value),
+        }
+    }
+
+    fn encode_fields<'a>(
+        &self,
+        fields: impl ExactSizeIterator<Item = (&'a str, &'a TypeRef)>,
+        object: &Map<String, Value>,
+    ) -> Result<Vec<Felt>> {
+        if fields.len() != object.len() {
+            return error(format!(
+                "expected {} fields, got {}",
+                fields.len(),
+                object.len()
+            ));
+        }
+        fields
+            .map(|(name, ty)| match object.get(name) {
+                Some(value) => self
+                    .encode_value(ty, value)
+                    .map_err(|e| TypedDataError(format!("field `{name}`: {e}"))),
+                None => error(format!("missing field `{name}`")),
+            })
+            .collect()
+    }
+
+    fn encode_preset(&self, preset: Preset, value: &Value) -> Result<Felt> {
+        let object = expect_object(value)?;
+        let fields = preset.fields();
+        let mut elements = vec![preset.type_hash(self.revision)];
+        elements.extend(self.encode_fields(fields.iter().map(|(n, t)| (*n, t)), object)?);
+        Ok(self.revision.hash_many(&elements))
+    }
+
+    /// Enums hash the variant index followed by the tuple elements. Like
+    /// starknet.js, the enum type hash is not included.
+    fn encode_enum(
+        &self,
+        variants: &[(String, Vec<TypeRef>)],
+        object: &Map<String, Value>,
+    ) -> Result<Felt> {
+        if object.len() != 1 {
+            return error("enum value must have exactly one variant");
+        }
+        let (variant_name, tuple) = object.iter().next().expect("length checked above");
+        let tuple = expect_array(tuple)?;
+        let (index, (_, tuple_types)) = variants
+            .iter()
+            .enumerate()
+            .find(|(_, (name, _))| name == variant_name)
+            .ok_or_else(|| TypedDataError(format!("unknown variant `{variant_name}`")))?;
+        if tuple_types.len() != tuple.len() {
+            return error(format!(
+                "variant `{variant_name}` expects {} elements, got {}",
+                tuple_types.len(),
+                tuple.len()
+            ));
+        }
+
+        let mut elements = vec![Felt::from(index)];
+        for (ty, item) in tuple_types.iter().zip(tuple) {
+            elements.push(self.encode_value(ty, item)?);
+        }
+        Ok(self.revision.hash_many(&elements))
+    }
+}
+
+fn write_signature(name: &str, source: &Signature<'_>, revision: Revision, out: &mut String) {
+    let escape = |name: &str| revision.escape(name);
+    match source {
+        Signature::Preset(preset) => out.push_str(preset.signature(revision)),
+        Signature::User(TypeDef::Struct(fields)) => {
+            out.push_str(&escape(name));
+            out.push('(');
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(field, ty)| format!("{}:{}", escape(field), escape(&ty.signature_name())))
+                .collect();
+            out.push_str(&fields.join(","));
+            out.push(')');
+        }
+        Signature::User(TypeDef::Enum(variants)) => {
+            out.push_str(&escape(name));
+            out.push('(');
+            let variants: Vec<String> = variants
+                .iter()
+                .map(|(variant, tuple)| {
+                    let tuple: Vec<String> = tuple
+                        .iter()
+                        .map(|ty| escape(&ty.signature_name()))
+                        .collect();
+                    format!("{}:({})", escape(variant), tuple.join(","))
+                })
+                .collect();
+            out.push_str(&variants.join(","));
+            out.push(')');
+        }
+    }
+}
+
+fn parse_type_def(value: &Value, revision: Revision) -> Result<TypeDef> {
+    let entries = expect_array(value)?;
+    if entries.is_empty() {
+        return error("at least one field or variant required");
+    }
+
+    let mut fields = Vec::new();
+    let mut variants = Vec::new();
+    for entry in entries {
+        let entry = expect_object(entry)?;
+        let name = match entry.get("name") {
+            Some(Value::String(name)) if !name.is_empty() => name.clone(),
+            _ => return error("missing field name"),
+        };
+        let ty = match entry.get("type") {
+            Some(Value::String(ty)) => ty.as_str(),
+            _ => return error(format!("missing type for `{name}`")),
+        };
+        let contains = match entry.get("contains") {
+            Some(Value::String(contains)) => Some(contains.as_str()),
+            Some(_) => return error(format!("invalid `contains` for `{name}`")),
+            None => None,
+        };
+
+        if let Some(inner) = ty.strip_prefix('(') {
+            let inner = inner
+                .strip_suffix(')')
+                .ok_or_else(|| TypedDataError(format!("unclosed variant type `{ty}`")))?;
+            if contains.is_some() {
+                return error(format!("unexpected `contains` on variant `{name}`"));
+            }
+            let tuple = if inner.is_empty() {
+                Vec::new()
+            } else {
+                inner
+                    .split(',')
+                    .map(|t| TypeRef::parse_inline(t.trim(), revision))
+                    .collect::<Result<Vec<_>>>()?
+            };
+            variants.push((name, tuple));
+        } else {
+            fields.push((name, TypeRef::parse_field(ty, contains, revision)?));
+        }
+    }
+
+    match (fields.is_empty(), variants.is_empty()) {
+        (false, true) => Ok(TypeDef::Struct(fields)),
+        (true, false) => Ok(TypeDef::Enum(variants)),
+        _ => error("mixes struct fields and enum variants"),
+    }
+}
+
+fn is_domain_type(definition: &TypeDef, revision: Revision) -> bool {
+    let expected: &[(&str, TypeRef)] = match revision {
+        Revision::V0 => &[
+            ("name", TypeRef::Felt),
+            ("version", TypeRef::Felt),
+            ("chainId", TypeRef::Felt),
+        ],
+        Revision::V1 => &[
+            ("name", TypeRef::ShortString),
+            ("version", TypeRef::ShortString),
+            ("chainId", TypeRef::ShortString),
+            ("revision", TypeRef::ShortString),
+        ],
+    };
+    match definition {
+        TypeDef::Struct(fields) => {
+            fields.len() == expected.len()
+                && fields
+                    .iter()
+                    .zip(expected)
+                    .all(|((name, ty), (exp_name, exp_ty))| name == exp_name && ty == exp_ty)
+        }
+        TypeDef::Enum(_) => false,
+    }
+}
+
+fn domain_hash(domain: &Map<String, Value>, revision: Revision) -> Result<Felt> {
+    let field = |name: &str| -> Result<Felt> {
+        match domain.get(name) {
+            Some(value) => parse_felt_or_short_string(value)
+                .map_err(|e| TypedDataError(format!("domain `{name}`: {e}"))),
+            None => error(format!("domain is missing `{name}`")),
+        }
+    };
+    // Revision 0 domains usually omit `revision`
+    let domain_revision = match domain.get("revision") {
+        None => Felt::ZERO,
+        Some(_) => field("revision")?,
+    };
+
+    let expected_revision = match revision {
+        Revision::V0 => Felt::ZERO,
+        Revision::V1 => Felt::ONE,
+    };
+    if domain_revision != expected_revision {
+        return error("domain revision does not match the domain type");
+    }
+    Ok(starknet_domain_hash(
+        revision,
+        field("name")?,
+        field("version")?,
+        field("chainId")?,
+    ))
+}
+
+/// Struct hash of a `StarkNetDomain` (revision 0) or `StarknetDomain`
+/// (revision 1) domain
+pub(crate) fn starknet_domain_hash(
+    revision: Revision,
+    name: Felt,
+    version: Felt,
+    chain_id: Felt,
+) -> Felt {
+    match revision {
+        Revision::V0 => pedersen_hash_array(&[
+            sn_keccak(DOMAIN_TYPE_SIGNATURE_V0.as_bytes()),
+            name,
+            version,
+            chain_id,
+        ]),
+        Revision::V1 => poseidon_hash_many(&[
+            sn_keccak(DOMAIN_TYPE_SIGNATURE_V1.as_bytes()),
+            name,
+            version,
+            chain_id,
+            Felt::ONE,
+        ]),
+    }
+}
+
+/// `H("StarkNet Message", domain_hash, account, struct_hash)`
+pub(crate) fn message_hash(
+    revision: Revision,
+    domain_hash: Felt,
+    account: Felt,
+    struct_hash: Felt,
+) -> Felt {
+    revision.hash_many(&[
+        Felt::from_bytes_be_slice(b"StarkNet Message"),
+        domain_hash,
+        account,
+        struct_hash,
+    ])
+}
+
+/// Merkle root with sorted pairs; odd nodes are hashed with zero
+///
+/// Reduces `layer` in place (each level overwrites the front of the
+/// previous one), so a reused buffer costs no allocation.
+#[cfg(feature = "merkle")]
+pub(crate) fn merkle_root(revision: Revision, layer: &mut Vec<Felt>) -> Felt {
+    while layer.len() > 1 {
+        let len = layer.len();
+        for i in (0..len).step_by(2) {
+            layer[i / 2] = match (layer[i], layer.get(i + 1).copied()) {
+                (a, Some(b)) if a <= b => revision.hash_pair(a, b),
+                (a, Some(b)) => revision.hash_pair(b, a),
+                (a, None) => revision.hash_pair(Felt::ZERO, a),
+            };
+        }
+        layer.truncate(len.div_ceil(2));
+    }
+    layer[0]
+}
+
+fn expect_object(value: &Value) -> Result<&Map<String, Value>> {
+    value
+        .as_object()
+        .ok_or_else(|| TypedDataError(format!("expected object, got {value}")))
+}
+
+fn expect_array(value: &Value) -> Result<&Vec<Value>> {
+    value
+        .as_array()
+        .ok_or_else(|| TypedDataError(format!("expected array, got {value}")))
+}
+
+fn expect_str(value: &Value) -> Result<&str> {
+    value
+        .as_str()
+        .ok_or_else(|| TypedDataError(format!("expected string, got {value}")))
+}
+
+/// Parse a `0x`-prefixed hex or decimal string
+fn parse_number_str(s: &str) -> Option<Felt> {
+    match s.strip_prefix("0x") {
+        Some(hex) if !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Felt::from_hex(s).ok()
+        }
+        Some(_) => None,
+        None if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) => {
+            Felt::from_dec_str(s).ok()
+        }
+        None => None,
+    }
+}
+
+/// Encode an ASCII string of at most 31 characters as a felt
+pub(crate) fn short_string_to_felt(s: &str) -> Option<Felt> {
+    if s.len() > 31 || !s.is_ascii() {
+        return None;
+    }
+    Some(Felt::from_bytes_be_slice(s.as_bytes()))
+}
+
+/// `felt`/`shortstring` values: numeric strings are taken as raw numbers,
+/// anything else as a Cairo short string (starknet.js compatible)
+fn parse_felt_or_short_string(value: &Value) -> Result<Felt> {
+    match value {
+        Value::String(s) => parse_number_str(s)
+            .or_else(|| short_string_to_felt(s))
+            .ok_or_else(|| TypedDataError(format!("invalid short string `{s}`"))),
+        Value::Number(n) => n
+            .as_u64()
+            .map(Felt::from)
+            .ok_or_else(|| TypedDataError(format!("invalid felt number {n}"))),
+        _ => error(format!("expected string or number, got {value}")),
+    }
+}
+
+fn parse_u128(value: &Value) -> Result<u128> {
+    let parsed = match value {
+        Value::Number(n) => n.as_u64().map(u128::from),
+        Value::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => u128::from_str_radix(hex, 16).ok(),
+            None => s.parse().ok(),
+        },
+        _ => None,
+    };
+    parsed.ok_or_else(|| TypedDataError(format!("invalid u128 {value}")))
+}
+
+fn parse_i128(value: &Value) -> Result<i128> {
+    let parsed = match value {
+        Value::Number(n) => n.as_i64().map(i128::from).or(n.as_u64().map(i128::from)),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    };
+    parsed.ok_or_else(|| TypedDataError(format!("invalid i128 {value}")))
+}
+
+fn selector_from_name(name: &str) -> Result<Felt> {
+    if !name.is_ascii() {
+        return error(format!("invalid selector name `{name}`"));
+    }
+    if name == "__default__" || name == "__l1_default__" {
+        return Ok(Felt::ZERO);
+    }
+    Ok(sn_keccak(name.as_bytes()))
+}
+
+/// Cairo `ByteArray` serialization:
+/// `[full_words_len, full_words..., pending_word, pending_word_len]`
+pub(crate) fn byte_array_felts(bytes: &[u8]) -> Vec<Felt> {
+    let chunks = bytes.chunks_exact(31);
+    let pending = chunks.remainder();
+    let mut felts = vec![Felt::from(bytes.len() / 31)];
+    felts.extend(chunks.map(Felt::from_bytes_be_slice));
+    felts.push(Felt::from_bytes_be_slice(pending));
+    felts.push(Felt::from(pending.len()));
+    felts
+}
+
+/// Compute the SNIP-12 message hash of a typed data document
+///
+/// The revision is taken from the domain type: `StarknetDomain` for
+/// revision 1, `StarkNetDomain` for the legacy revision 0.
+/// `json` is the UTF-8 typed data document (not NUL-terminated) and
+/// `account_address` the signer's account. Returns `InvalidInput` if the
+/// document is malformed or does not match its type definitions.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_typed_data_hash")]
+pub unsafe extern "C" fn starknet_typed_data_hash(
+    json: *const u8,
+    json_len: usize,
+    account_address: *const FeltBytes,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::TypedData);
+        check_null!(json, account_address, out);
+        let account = match felt_from_bytes(&*account_address) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let json = core::slice::from_raw_parts(json, json_len);
+
+        match TypedData::parse(json).and_then(|data| data.message_hash(account)) {
+            Ok(hash) => {
+                *out = felt_to_bytes(&hash);
+                StarkResult::Success
+            }
+            Err(e) => invalid_input(format!("`json`: {e}")),
+        }
+    })
+}
+
+/// `starknet_merkle_root` hash: Pedersen (SNIP-12 revision 0)
+#[cfg(feature = "merkle")]
+pub const MERKLE_HASH_PEDERSEN: u32 = 0;
+/// `starknet_merkle_root` hash: Poseidon (SNIP-12 revision 1)
+#[cfg(feature = "merkle")]
+pub const MERKLE_HASH_POSEIDON: u32 = 1;
+
+/// Merkle root of `count` leaves, as SNIP-12 `merkletree` values hash them
+///
+/// `hash` is `MERKLE_HASH_PEDERSEN` or `MERKLE_HASH_POSEIDON`. Pairs are
+/// sorted before hashing and an odd node is hashed with zero. `arena` is
+/// optional (null: allocate the tree in a new buffer). Returns
+/// `InvalidInput` for zero leaves or an unknown `hash`.
+#[cfg(feature = "merkle")]
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_merkle_root")]
+pub unsafe extern "C" fn starknet_merkle_root(
+    leaves: *const FeltBytes,
+    count: usize,
+    hash: u32,
+    arena: *const StarkArena,
+    out: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let (revision, op) = match hash {
+            MERKLE_HASH_PEDERSEN => (Revision::V0, Op::Pedersen),
+            MERKLE_HASH_POSEIDON => (Revision::V1, Op::Poseidon),
+            _ => return invalid_input(format!("unknown `hash` {hash}")),
+        };
+        let _timer = perf::timer(op);
+        check_null!(leaves, out);
+        if count == 0 {
+            return invalid_input("`count` is 0");
+        }
+        let arena = match optional_arena(arena) {
+            Ok(arena) => arena,
+            Err(e) => return e,
+        };
+        let leaves = core::slice::from_raw_parts(leaves, count);
+
+        arena::with_felts(arena, |layer| {
+            for (i, leaf) in leaves.iter().enumerate() {
+                match felt_from_bytes(leaf) {
+                    Some(felt) => layer.push(felt),
+                    None => return invalid_input(format!("`leaves[{i}]` is not a felt")),
+                }
+            }
+            *out = felt_to_bytes(&merkle_root(revision, layer));
+            StarkResult::Success
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The expected message hashes below are this implementation's output for
+    // documents modelled on the SNIP-12 specification examples, with a
+    // `Starknet Example` domain and account 0x1234. They are not the
+    // starknet.js `__mocks__/typedData` example files, and no starknet.js
+    // output for these documents is available to this build: they pin the
+    // current behaviour, not interop with starknet.js.
+    const DOMAIN: &str = r#""StarknetDomain": [
+      { "name": "name", "type": "shortstring" },
+      { "name": "version", "type": "shortstring" },
+      { "name": "chainId", "type": "shortstring" },
+      { "name": "revision", "type": "shortstring" }
+    ]"#;
+
+    fn document(types: &str, primary: &str, message: &str) -> String {
+        format!(
+            r#"{{
+  "types": {{ {DOMAIN}, {types} }},
+  "primaryType": "{primary}",
+  "domain": {{ "name": "Starknet Example", "version": "1", "chainId": "SN_MAIN", "revision": "1" }},
+  "message": {message}
+}}"#
+        )
+    }
+
+    fn hash(json: &str) -> core::result::Result<Felt, StarkResult> {
+        let account = felt_to_bytes(&Felt::from_hex_unchecked("0x1234"));
+        let mut out = [0u8; 32];
+        let result =
+            unsafe { starknet_typed_data_hash(json.as_ptr(), json.len(), &account, &mut out) };
+        match result {
+            StarkResult::Success => Ok(Felt::from_bytes_be(&out)),
+            err => Err(err),
+        }
+    }
+
+    #[test]
+    fn test_typed_data_struct() {
+        let json = document(
+            r#""Example Message": [
+      { "name": "Name", "type": "string" },
+      { "name": "Some Array", "type": "u128*" },
+      { "name": "Some Object", "type": "My Object" }
+    ],
+    "My Object": [
+      { "name": "Some Selector", "type": "selector" },
+      { "name": "Some Contract Address", "type": "ContractAddress" }
+    ]"#,
+            "Example Message",
+            r#"{
+    "Name": "some name",
+    "Some Array": [1, 2, 3, 4],
+    "Some Object": { "Some Selector": "transfer", "Some Contract Address": "0x0123" }
+  }"#,
+        );
+        assert_eq!(
+            hash(&json).unwrap(),
+            Felt::from_hex_unchecked(
+                "0x045bca39274d2b7fdf7dc7c4ecf75f6549f614ce44359cc62ec106f4e5cc87b4"
+            )
+        );
+    }
+
+    #[test]
+    fn test_typed_data_basic_types() {
+        let json = document(
+            r#""Example Message": [
+      { "name": "Bool", "type": "bool" },
+      { "name": "I128", "type": "i128" },
+      { "name": "Classhash", "type": "ClassHash" },
+      { "name": "Timestamp", "type": "timestamp" },
+      { "name": "Short1", "type": "shortstring" },
+      { "name": "Short2", "type": "shortstring" },
+      { "name": "Short3", "type": "shortstring" }
+    ]"#,
+            "Example Message",
+            r#"{
+    "Bool": true,
+    "I128": -123,
+    "Classhash": "0x1234",
+    "Timestamp": 1234,
+    "Short1": 123,
+    "Short2": "0x123",
+    "Short3": "hello"
+  }"#,
+        );
+        assert_eq!(
+            hash(&json).unwrap(),
+            Felt::from_hex_unchecked(
+                "0x0795c7e03a0ef83c4e3dee6942ef64d4126a91cafbda207356dae1de3bed4063"
+            )
+        );
+    }
+
+    #[test]
+    fn test_typed_data_presets() {
+        let json = document(
+            r#""Example Message": [
+      { "name": "Uint", "type": "u256" },
+      { "name": "Amount", "type": "TokenAmount" },
+      { "name": "Id", "type": "NftId" }
+    ]"#,
+            "Example Message",
+            r#"{
+    "Uint": { "low": "1234", "high": "0x5678" },
+    "Amount": { "token_address": "0x11223344", "amount": { "low": 1000000, "high": 0 } },
+    "Id": { "collection_address": "0x55667788", "token_id": { "low": "0x12345678", "high": 0 } }
+  }"#,
+        );
+        assert_eq!(
+            hash(&json).unwrap(),
+            Felt::from_hex_unchecked(
+                "0x068b85f4061d8155c0445f7e3c6bae1e7641b88b1d3b7c034c0b4f6c30eb5049"
+            )
+        );
+    }
+
+    #[test]
+    fn test_typed_data_enum() {
+        let json = document(
+            r#""Example Message": [
+      { "name": "Value", "type": "enum", "contains": "My Enum" }
+    ],
+    "My Enum": [
+      { "name": "Variant 1", "type": "()" },
+      { "name": "Variant 2", "type": "(string)" },
+      { "name": "Variant 3", "type": "(u128)" }
+    ]"#,
+            "Example Message",
+            r#"{ "Value": { "Variant 2": ["tuple element"] } }"#,
+        );
+        assert_eq!(
+            hash(&json).unwrap(),
+            Felt::from_hex_unchecked(
+                "0x05cb0569ef378e0c17c07c13cb86bc6e067f824ccffd79fd49d875ecc0296124"
+            )
+        );
+    }
+
+    #[cfg(feature = "merkle")]
+    #[test]
+    fn test_typed_data_merkletree() {
+        let json = document(
+            r#""Example Message": [
+      { "name": "Value", "type": "merkletree", "contains": "My Object" }
+    ],
+    "My Object": [
+      { "name": "Some Selector", "type": "selector" },
+      { "name": "Some Contract Address", "type": "ContractAddress" }
+    ]"#,
+            "Example Message",
+            r#"{ "Value": [
+      { "Some Selector": "selector1", "Some Contract Address": "0x1111" },
+      { "Some Selector": "selector2", "Some Contract Address": "0x2222" },
+      { "Some Selector": "selector3", "Some Contract Address": "0x3333" },
+      { "Some Selector": "selector4", "Some Contract Address": "0x4444" },
+      { "Some Selector": "selector5", "Some Contract Address": "0x5555" }
+    ] }"#,
+        );
+        assert_eq!(
+            hash(&json).unwrap(),
+            Felt::from_hex_unchecked(
+                "0x064bd27eb802de8c83ff1437394c142bbe771530a248c548fab27ac3bcd2a503"
+            )
+        );
+    }
+
+    #[test]
+    fn test_typed_data_revision_0() {
+        let json = r#"{
+  "types": {
+    "StarkNetDomain": [
+      { "name": "name", "type": "felt" },
+      { "name": "version", "type": "felt" },
+      { "name": "chainId", "type": "felt" }
+    ],
+    "Example Message": [
+      { "name": "Name", "type": "string" },
+      { "name": "Some Array", "type": "u128*" },
+      { "name": "Some Object", "type": "My Object" }
+    ],
+    "My Object": [
+      { "name": "Some Selector", "type": "selector" },
+      { "name": "Some Contract Address", "type": "ContractAddress" }
+    ]
+  },
+  "primaryType": "Example Message",
+  "domain": { "name": "Starknet Example", "version": "1", "chainId": "SN_MAIN" },
+  "message": {
+    "Name": "some name",
+    "Some Array": [1, 2, 3, 4],
+    "Some Object": { "Some Selector": "transfer", "Some Contract Address": "0x0123" }
+  }
+}"#;
+        assert_eq!(
+            hash(json).unwrap(),
+            Felt::from_hex_unchecked(
+                "0x0778d68fe2baf73ee78a6711c29bad4722680984c1553a8035c8cb3feb5310c9"
+            )
+        );
+    }
+
+    #[test]
+    fn test_typed_data_revision_0_no_presets() {
+        let document = |types: &str, message: &str| {
+            format!(
+                r#"{{
+  "types": {{
+    "StarkNetDomain": [
+      {{ "name": "name", "type": "felt" }},
+      {{ "name": "version", "type": "felt" }},
+      {{ "name": "chainId", "type": "felt" }}
+    ],
+    "Message": [ {{ "name": "a", "type": "u256" }} ]{types}
+  }},
+  "primaryType": "Message",
+  "domain": {{ "name": "Example", "version": "1", "chainId": "SN_MAIN" }},
+  "message": {{ "a": {message} }}
+}}"#
+            )
+        };
+
+        // `u256` is not a preset in revision 0: it must be declared, and the
+        // declaration is used as is
+        let undeclared = document("", r#"{ "low": 1, "high": 0 }"#);
+        assert_eq!(hash(&undeclared), Err(StarkResult::InvalidInput));
+        let declared = document(
+            r#", "u256": [ { "name": "value", "type": "felt" } ]"#,
+            r#"{ "value": 1 }"#,
+        );
+        assert!(hash(&declared).is_ok());
+    }
+
+    #[test]
+    fn test_typed_data_inconsistent_revision() {
+        let json = r#"{
+  "types": {
+    "StarkNetDomain": [
+      { "name": "name", "type": "felt" },
+      { "name": "version", "type": "felt" },
+      { "name": "chainId", "type": "felt" }
+    ],
+    "Message": [ { "name": "a", "type": "felt" } ]
+  },
+  "primaryType": "Message",
+  "domain": { "name": "Example", "version": "1", "chainId": "SN_MAIN", "revision": "1" },
+  "message": { "a": 1 }
+}"#;
+        assert_eq!(hash(json), Err(StarkResult::InvalidInput));
+    }
+
+    #[test]
+    fn test_typed_data_rejects_malformed() {
+        let missing_field = document(
+            r#""Example Message": [ { "name": "a", "type": "felt" } ]"#,
+            "Example Message",
+            r#"{ "b": 1 }"#,
+        );
+        assert_eq!(hash(&missing_field), Err(StarkResult::InvalidInput));
+        assert_eq!(hash("not json"), Err(StarkResult::InvalidInput));
+    }
+
+    #[cfg(feature = "merkle")]
+    #[test]
+    fn test_merkle_root() {
+        let felts: Vec<Felt> = (1..=5u64).map(|i| Felt::from(i * 0x1111)).collect();
+        let leaves: Vec<FeltBytes> = felts.iter().map(felt_to_bytes).collect();
+        let mut arena = core::ptr::null_mut();
+        let mut out = [0u8; 32];
+
+        unsafe {
+            assert_eq!(
+                crate::arena::stark_arena_new(0, &mut arena),
+                StarkResult::Success
+            );
+            for (hash, revision) in [
+                (MERKLE_HASH_PEDERSEN, Revision::V0),
+                (MERKLE_HASH_POSEIDON, Revision::V1),
+            ] {
+                // ((l1, l2), (l3, l4)), ((l5, 0), 0) with each pair sorted
+                let pair = |a: Felt, b: Felt| revision.hash_pair(a.min(b), a.max(b));
+                let expected = pair(
+                    pair(pair(felts[0], felts[1]), pair(felts[2], felts[3])),
+                    pair(Felt::ZERO, pair(Felt::ZERO, felts[4])),
+                );
+                for arena in [arena.cast_const(), core::ptr::null()] {
+                    assert_eq!(
+                        starknet_merkle_root(leaves.as_ptr(), 5, hash, arena, &mut out),
+                        StarkResult::Success
+                    );
+                    assert_eq!(out, felt_to_bytes(&expected));
+                }
+                assert_eq!(
+                    starknet_merkle_root(leaves.as_ptr(), 1, hash, arena, &mut out),
+                    StarkResult::Success
+                );
+                assert_eq!(out, leaves[0]);
+            }
+
+            assert_eq!(
+                starknet_merkle_root(leaves.as_ptr(), 0, MERKLE_HASH_POSEIDON, arena, &mut out),
+                StarkResult::InvalidInput
+            );
+            assert_eq!(
+                starknet_merkle_root(leaves.as_ptr(), 5, 2, arena, &mut out),
+                StarkResult::InvalidInput
+            );
+            crate::arena::stark_arena_free(arena);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Cairo `u256` as a pair of felts
+//!
+//! Cairo serializes a `u256` as two `u128` felts, `low` first: the value is
+//! `high·2^128 + low`. Across the FFI a `u256` is 32 big-endian bytes, so
+//! `high` is the first 16 bytes and `low` the last 16.
+
+use starknet_crypto::Felt;
+
+use crate::{felt_from_bytes, felt_to_bytes, ffi_guard, FeltBytes, StarkResult};
+
+/// Split a big-endian `u256` into its (low, high) halves
+pub(crate) fn u256_to_halves(value: &[u8; 32]) -> (Felt, Felt) {
+    (
+        Felt::from_bytes_be_slice(&value[16..]),
+        Felt::from_bytes_be_slice(&value[..16]),
+    )
+}
+
+/// Join (low, high) halves into a big-endian `u256`, or `None` if either
+/// half does not fit in 128 bits
+pub(crate) fn u256_from_halves(low: &Felt, high: &Felt) -> Option<[u8; 32]> {
+    let low = u128::try_from(*low).ok()?;
+    let high = u128::try_from(*high).ok()?;
+
+    let mut value = [0u8; 32];
+    value[..16].copy_from_slice(&high.to_be_bytes());
+    value[16..].copy_from_slice(&low.to_be_bytes());
+    Some(value)
+}
+
+/// Split a 32-byte big-endian `u256` into its Cairo (low, high) felts
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_u256_to_felts")]
+pub unsafe extern "C" fn u256_to_felts(
+    value: *const [u8; 32],
+    out_low: *mut FeltBytes,
+    out_high: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(value, out_low, out_high);
+        let (low, high) = u256_to_halves(&*value);
+        *out_low = felt_to_bytes(&low);
+        *out_high = felt_to_bytes(&high);
+        StarkResult::Success
+    })
+}
+
+/// Join Cairo (low, high) felts into a 32-byte big-endian `u256`
+///
+/// Returns `InvalidInput` unless both halves are below 2^128.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_u256_from_felts")]
+pub unsafe extern "C" fn u256_from_felts(
+    low: *const FeltBytes,
+    high: *const FeltBytes,
+    out: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(low, high, out);
+        let low = match felt_from_bytes(&*low) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let high = match felt_from_bytes(&*high) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+
+        match u256_from_halves(&low, &high) {
+            Some(value) => {
+                *out = value;
+                StarkResult::Success
+            }
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_u256_roundtrip() {
+        // 10^24 (one million tokens with 18 decimals) plus 2^200
+        let mut value = [0u8; 32];
+        value[..16].copy_from_slice(&(1u128 << 72).to_be_bytes());
+        value[16..].copy_from_slice(&10u128.pow(24).to_be_bytes());
+        let (mut low, mut high) = ([0u8; 32], [0u8; 32]);
+        let mut joined = [0u8; 32];
+
+        unsafe {
+            assert_eq!(
+                u256_to_felts(&value, &mut low, &mut high),
+                StarkResult::Success
+            );
+            assert_eq!(
+                Felt::from_bytes_be(&low),
+                Felt::from_dec_str("1000000000000000000000000").unwrap()
+            );
+            assert_eq!(Felt::from_bytes_be(&high), Felt::TWO.pow(72u32));
+
+            assert_eq!(
+                u256_from_felts(&low, &high, &mut joined),
+                StarkResult::Success
+            );
+        }
+        assert_eq!(joined, value);
+    }
+
+    #[test]
+    fn test_u256_from_felts_range() {
+        let max = felt_to_bytes(&Felt::from(u128::MAX));
+        let too_big = felt_to_bytes(&Felt::TWO.pow(128u32));
+        let mut out = [0u8; 32];
+
+        unsafe {
+            assert_eq!(u256_from_felts(&max, &max, &mut out), StarkResult::Success);
+            assert_eq!(out, [0xff; 32]);
+            assert_eq!(
+                u256_from_felts(&too_big, &max, &mut out),
+                StarkResult::InvalidInput
+            );
+            assert_eq!(
+                u256_from_felts(&max, &too_big, &mut out),
+                StarkResult::InvalidInput
+            );
+        }
+    }
+}
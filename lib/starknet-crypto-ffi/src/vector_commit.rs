@@ -0,0 +1,272 @@
+//! Poseidon vector commitments with positional openings
+//!
+//! A stable scheme for committing to a vector of felts and later proving the
+//! value at one position, for circuit inputs. Unlike `starknet_merkle_root`
+//! (SNIP-12 trees, sorted pairs), nodes are hashed in position order and
+//! the tree has a fixed shape given by the length alone:
+//!
+//! - the `count` values are the leaves, padded with zeros to `2^depth`,
+//!   `depth` being the smallest with `2^depth >= count`;
+//! - a node is `poseidon_hash(left, right)`;
+//! - the commitment is `poseidon_hash(tree_root, count)`, so a vector and its
+//!   zero-padded extension commit differently.
+//!
+//! An opening of position `index` is the `depth` sibling hashes from the
+//! leaf up. A circuit checks it by hashing up the path, taking the current
+//! node as the left input when bit `k` of `index` is 0 at level `k`.
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use starknet_crypto::{poseidon_hash, Felt};
+
+use crate::error::invalid_input;
+use crate::perf::{self, Op};
+use crate::{
+    error::null_pointer, felt_from_bytes, felt_to_bytes, felts_from_raw, ffi_guard,
+    write_felts_out, FeltBytes, StarkResult,
+};
+
+/// Levels above the leaves for `count` values
+fn depth(count: usize) -> u32 {
+    count.next_power_of_two().trailing_zeros()
+}
+
+/// Replace `layer` by its parent layer
+fn hash_layer(layer: &mut Vec<Felt>) {
+    for i in 0..layer.len() / 2 {
+        layer[i] = poseidon_hash(layer[2 * i], layer[2 * i + 1]);
+    }
+    layer.truncate(layer.len() / 2);
+}
+
+/// Commitment to `values` (not empty) and, for `open`, the opening of that
+/// position
+fn commit(values: &[Felt], open: Option<usize>) -> (Felt, Vec<Felt>) {
+    let mut layer = values.to_vec();
+    layer.resize(values.len().next_power_of_two(), Felt::ZERO);
+    let mut path = Vec::new();
+    let mut index = open;
+    while layer.len() > 1 {
+        if let Some(i) = index {
+            path.push(layer[i ^ 1]);
+            index = Some(i / 2);
+        }
+        hash_layer(&mut layer);
+    }
+    (poseidon_hash(layer[0], Felt::from(values.len())), path)
+}
+
+/// Commitment `path` opens `value` at `index` to
+fn root_from_path(value: Felt, index: usize, path: &[Felt], count: usize) -> Felt {
+    let (node, _) = path.iter().fold((value, index), |(node, i), sibling| {
+        let parent = if i % 2 == 0 {
+            poseidon_hash(node, *sibling)
+        } else {
+            poseidon_hash(*sibling, node)
+        };
+        (parent, i / 2)
+    });
+    poseidon_hash(node, Felt::from(count))
+}
+
+/// Read `count` (non-zero) values
+unsafe fn values_from_raw(
+    values: *const FeltBytes,
+    count: usize,
+) -> Result<Vec<Felt>, StarkResult> {
+    if count == 0 {
+        return Err(invalid_input("`count` is 0"));
+    }
+    felts_from_raw(values, count).ok_or_else(|| null_pointer("values"))
+}
+
+/// Commit to `count` values (see the module docs for the scheme)
+///
+/// Returns `InvalidInput` if `count` is 0.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_vector_commit")]
+pub unsafe extern "C" fn vector_commit(
+    values: *const FeltBytes,
+    count: usize,
+    out_root: *mut FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Poseidon);
+        check_null!(out_root);
+        match values_from_raw(values, count) {
+            Ok(values) => {
+                *out_root = felt_to_bytes(&commit(&values, None).0);
+                StarkResult::Success
+            }
+            Err(e) => e,
+        }
+    })
+}
+
+/// Write the opening of position `index` of `count` values: the sibling
+/// hashes from the leaf up
+///
+/// `*out_count` is the capacity of `out_path` in felts, set to the path
+/// length (the tree depth; 0 for a single value) as for every felt array
+/// output. Returns `InvalidInput` if `count` is 0 or `index` is not below
+/// it.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_vector_open")]
+pub unsafe extern "C" fn vector_open(
+    values: *const FeltBytes,
+    count: usize,
+    index: usize,
+    out_path: *mut FeltBytes,
+    out_count: *mut usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Poseidon);
+        check_null!(out_count);
+        let values = match values_from_raw(values, count) {
+            Ok(values) => values,
+            Err(e) => return e,
+        };
+        if index >= count {
+            return invalid_input(format!("`index` {index} is out of bounds ({count} values)"));
+        }
+        write_felts_out(&commit(&values, Some(index)).1, out_path, &mut *out_count)
+    })
+}
+
+/// Verify that `root` commits to `count` values with `value` at `index`
+///
+/// Returns `Success` if the opening holds, `InvalidSignature` if it does not,
+/// and `InvalidInput` if `index` is not below `count` or `path_len` is not
+/// the depth for `count` values.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_vector_verify")]
+pub unsafe extern "C" fn vector_verify(
+    root: *const FeltBytes,
+    count: usize,
+    index: usize,
+    value: *const FeltBytes,
+    path: *const FeltBytes,
+    path_len: usize,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Poseidon);
+        check_null!(root, value);
+        if index >= count {
+            return invalid_input(format!("`index` {index} is out of bounds ({count} values)"));
+        }
+        if path_len != depth(count) as usize {
+            return invalid_input(format!(
+                "`path_len` is {path_len} (expected {} for {count} values)",
+                depth(count)
+            ));
+        }
+        let path = match felts_from_raw(path, path_len) {
+            Some(path) => path,
+            None => return null_pointer("path"),
+        };
+        let (Some(root), Some(value)) = (felt_from_bytes(&*root), felt_from_bytes(&*value)) else {
+            return StarkResult::InvalidInput;
+        };
+        if root_from_path(value, index, &path, count) == root {
+            StarkResult::Success
+        } else {
+            StarkResult::InvalidSignature
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(count: u64) -> Vec<FeltBytes> {
+        (0..count)
+            .map(|i| felt_to_bytes(&Felt::from(i * 31 + 5)))
+            .collect()
+    }
+
+    #[test]
+    fn test_vector_commit_shape() {
+        let felts: Vec<Felt> = (1..=3u64).map(Felt::from).collect();
+        let tree = poseidon_hash(
+            poseidon_hash(felts[0], felts[1]),
+            poseidon_hash(felts[2], Felt::ZERO),
+        );
+        assert_eq!(commit(&felts, None).0, poseidon_hash(tree, Felt::from(3)));
+        assert_eq!(
+            commit(&felts[..1], None).0,
+            poseidon_hash(felts[0], Felt::ONE)
+        );
+
+        // Zero padding does not collide with explicit zeros
+        let mut padded = felts.clone();
+        padded.push(Felt::ZERO);
+        assert_ne!(commit(&padded, None).0, commit(&felts, None).0);
+    }
+
+    #[test]
+    fn test_vector_open_and_verify() {
+        let mut root = [0u8; 32];
+        let mut path = [[0u8; 32]; 8];
+        for count in [1, 2, 5, 8, 13] {
+            let values = values(count);
+            unsafe {
+                assert_eq!(
+                    vector_commit(values.as_ptr(), values.len(), &mut root),
+                    StarkResult::Success
+                );
+                for index in 0..values.len() {
+                    let mut path_len = path.len();
+                    assert_eq!(
+                        vector_open(
+                            values.as_ptr(),
+                            values.len(),
+                            index,
+                            path.as_mut_ptr(),
+                            &mut path_len
+                        ),
+                        StarkResult::Success
+                    );
+                    assert_eq!(path_len, depth(values.len()) as usize);
+                    let verify = |value: &FeltBytes, index| {
+                        vector_verify(&root, values.len(), index, value, path.as_ptr(), path_len)
+                    };
+                    assert_eq!(verify(&values[index], index), StarkResult::Success);
+                    if values.len() > 1 {
+                        let other = (index + 1) % values.len();
+                        assert_eq!(verify(&values[other], index), StarkResult::InvalidSignature);
+                        assert_eq!(verify(&values[index], other), StarkResult::InvalidSignature);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_vector_commit_rejects_bad_arguments() {
+        let values = values(4);
+        let mut out = [0u8; 32];
+        let mut path = [[0u8; 32]; 2];
+        let mut path_len = 1;
+        unsafe {
+            assert_eq!(
+                vector_commit(values.as_ptr(), 0, &mut out),
+                StarkResult::InvalidInput
+            );
+            assert_eq!(
+                vector_open(values.as_ptr(), 4, 4, path.as_mut_ptr(), &mut path_len),
+                StarkResult::InvalidInput
+            );
+            assert_eq!(
+                vector_open(values.as_ptr(), 4, 0, path.as_mut_ptr(), &mut path_len),
+                StarkResult::BufferTooSmall
+            );
+            assert_eq!(path_len, 2);
+            assert_eq!(
+                vector_verify(&out, 4, 0, &values[0], path.as_ptr(), 1),
+                StarkResult::InvalidInput
+            );
+        }
+    }
+}
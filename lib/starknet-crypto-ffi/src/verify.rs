@@ -0,0 +1,567 @@
+//! STARK ECDSA verification with precomputed point tables
+//!
+//! `starknet_verify` decompresses the public key from its x coordinate on
+//! every call. A verify context does it once and caches a 4-bit window table
+//! of the key's multiples, so each verification costs 63 additions per
+//! scalar multiplication instead of ~126. The generator table is shared by
+//! all contexts (see `point_table`).
+//!
+//! `starknet_verify_full` takes the full `(x, y)` point, which skips the
+//! reconstruction and removes the `±Q` ambiguity of x-only keys.
+
+use alloc::format;
+
+use starknet_crypto::Felt;
+use starknet_types_core::curve::{AffinePoint, ProjectivePoint};
+use starknet_types_core::felt::NonZeroFelt;
+
+use crate::cancel::{self, StarkCancelToken};
+use crate::error;
+use crate::handle::StarkHandle;
+use crate::perf::{self, Op};
+use crate::point_table::{generator_table, PointTable};
+use crate::pool;
+use crate::{felt_from_bytes, ffi_guard, ffi_guard_void, FeltBytes, StarkResult, EC_ORDER};
+
+fn in_verifier_range(x: &Felt) -> bool {
+    *x != Felt::ZERO && *x < Felt::ELEMENT_UPPER_BOUND
+}
+
+/// Same checks as `starknet_crypto::verify`, with the public key given as a
+/// precomputed table. Returns `None` for out-of-range inputs.
+///
+/// When the key was reconstructed from its x coordinate (`x_only`), its sign
+/// is unknown and both `±Q` are accepted like `starknet_crypto::verify` does.
+pub(crate) fn verify_with_table(
+    public_key: &PointTable,
+    x_only: bool,
+    message: &Felt,
+    r: &Felt,
+    s: &Felt,
+) -> Option<bool> {
+    if *message >= Felt::ELEMENT_UPPER_BOUND || !in_verifier_range(r) || !in_verifier_range(s) {
+        return None;
+    }
+    let order = NonZeroFelt::from_felt_unchecked(EC_ORDER);
+    let w = s.mod_inverse(&order)?;
+    if !in_verifier_range(&w) {
+        return None;
+    }
+
+    let zw_g = generator_table().mul(&message.mul_mod(&w, &order));
+    let rw_q = public_key.mul(&r.mul_mod(&w, &order));
+
+    let x_matches = |point: ProjectivePoint| point.to_affine().is_ok_and(|p| p.x() == *r);
+    Some(x_matches(&zw_g + &rw_q) || (x_only && x_matches(&zw_g - &rw_q)))
+}
+
+/// Verification context for a fixed public key
+pub struct StarkVerifyContext {
+    table: PointTable,
+}
+
+impl StarkHandle for StarkVerifyContext {
+    const NAME: &'static str = "StarkVerifyContext";
+}
+
+/// Create a verification context for `public_key` (x coordinate)
+///
+/// Writes an owned handle to `out_ctx`; release it with
+/// `starknet_verify_context_free`. Returns `InvalidInput` if `public_key` is
+/// not the x coordinate of a curve point.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_verify_context_new")]
+pub unsafe extern "C" fn starknet_verify_context_new(
+    public_key: *const FeltBytes,
+    out_ctx: *mut *mut StarkVerifyContext,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(public_key, out_ctx);
+        let x = match felt_from_bytes(&*public_key) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let point = match AffinePoint::new_from_x(&x, false) {
+            Some(p) => p,
+            None => return StarkResult::InvalidInput,
+        };
+
+        let table = PointTable::new(&ProjectivePoint::from_affine_unchecked(
+            point.x(),
+            point.y(),
+        ));
+        *out_ctx = StarkVerifyContext { table }.into_handle();
+        StarkResult::Success
+    })
+}
+
+/// Verify a signature with a verification context
+///
+/// Same result codes as `starknet_verify`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_verify_context_verify")]
+pub unsafe extern "C" fn starknet_verify_context_verify(
+    ctx: *const StarkVerifyContext,
+    message_hash: *const FeltBytes,
+    r: *const FeltBytes,
+    s: *const FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Verify);
+        check_null!(ctx, message_hash, r, s);
+        let ctx = match StarkVerifyContext::from_handle(ctx) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+        let msg = match felt_from_bytes(&*message_hash) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let r = match felt_from_bytes(&*r) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let s = match felt_from_bytes(&*s) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+
+        match verify_with_table(&ctx.table, true, &msg, &r, &s) {
+            Some(true) => StarkResult::Success,
+            Some(false) => StarkResult::InvalidSignature,
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+/// Free a verification context (null is a no-op)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_verify_context_free")]
+pub unsafe extern "C" fn starknet_verify_context_free(ctx: *mut StarkVerifyContext) {
+    ffi_guard_void(|| StarkVerifyContext::free_handle(ctx))
+}
+
+/// Verify a signature against a full (x, y) public key
+///
+/// Unlike `starknet_verify`, only the given point is accepted, not its
+/// negation. Returns `InvalidInput` if (x, y) is not on the curve, otherwise
+/// the same result codes as `starknet_verify`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_verify_full")]
+pub unsafe extern "C" fn starknet_verify_full(
+    public_key_x: *const FeltBytes,
+    public_key_y: *const FeltBytes,
+    message_hash: *const FeltBytes,
+    r: *const FeltBytes,
+    s: *const FeltBytes,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::Verify);
+        check_null!(public_key_x, public_key_y, message_hash, r, s);
+        let x = match felt_from_bytes(&*public_key_x) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let y = match felt_from_bytes(&*public_key_y) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let msg = match felt_from_bytes(&*message_hash) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let r = match felt_from_bytes(&*r) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let s = match felt_from_bytes(&*s) {
+            Some(f) => f,
+            None => return StarkResult::InvalidInput,
+        };
+        let point = match ProjectivePoint::from_affine(x, y) {
+            Ok(p) => p,
+            Err(_) => return StarkResult::InvalidInput,
+        };
+
+        match verify_with_table(&PointTable::new(&point), false, &msg, &r, &s) {
+            Some(true) => StarkResult::Success,
+            Some(false) => StarkResult::InvalidSignature,
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+/// One signature of a `starknet_verify_batch` call
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct StarknetVerifyItem {
+    pub public_key: FeltBytes,
+    pub message_hash: FeltBytes,
+    pub r: FeltBytes,
+    pub s: FeltBytes,
+}
+
+/// `starknet_verify` of one batch item
+fn verify_item(item: &StarknetVerifyItem) -> StarkResult {
+    verify_bytes([&item.public_key, &item.message_hash, &item.r, &item.s])
+}
+
+/// `starknet_verify` of `[public_key, message_hash, r, s]`
+fn verify_bytes(signature: [&FeltBytes; 4]) -> StarkResult {
+    let [public_key, message_hash, r, s] =
+        signature.map(|bytes| felt_from_bytes(bytes).unwrap_or(Felt::ZERO));
+    match starknet_crypto::verify(&public_key, &message_hash, &r, &s) {
+        Ok(true) => StarkResult::Success,
+        Ok(false) => StarkResult::InvalidSignature,
+        Err(_) => StarkResult::InvalidInput,
+    }
+}
+
+/// Verify `count` signatures on the library's thread pool (see `pool`)
+///
+/// Writes the `starknet_verify` result of each item to `out_results`.
+/// Returns `Success` if every signature is valid, otherwise
+/// `InvalidSignature`. `cancel` is optional (see `cancel`): once it is
+/// cancelled, the remaining items are skipped and `Cancelled` is returned
+/// with `out_results` left unwritten.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_verify_batch")]
+pub unsafe extern "C" fn starknet_verify_batch(
+    items: *const StarknetVerifyItem,
+    count: usize,
+    cancel: *const StarkCancelToken,
+    out_results: *mut StarkResult,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::VerifyBatch);
+        let cancel = match cancel::optional_token(cancel) {
+            Ok(t) => t,
+            Err(e) => return e,
+        };
+        if count == 0 {
+            return StarkResult::Success;
+        }
+        check_null!(items, out_results);
+        let items = core::slice::from_raw_parts(items, count);
+
+        let results = pool::map(items, |item| {
+            if cancel::is_cancelled(cancel) {
+                return StarkResult::Cancelled;
+            }
+            verify_item(item)
+        });
+        if cancel::is_cancelled(cancel) {
+            return cancel::cancelled("batch verification");
+        }
+        core::ptr::copy_nonoverlapping(results.as_ptr(), out_results, count);
+        match results.iter().position(|r| *r != StarkResult::Success) {
+            None => StarkResult::Success,
+            Some(i) => {
+                error::set_detail(format!("first failure: item {i} ({:?})", results[i]));
+                StarkResult::InvalidSignature
+            }
+        }
+    })
+}
+
+/// Verify `count` signatures stored as one array per field, on the
+/// library's thread pool (see `pool`)
+///
+/// Signature `i` is `(public_keys[i], message_hashes[i], r[i], s[i])`. Sets
+/// bit `i % 8` of `out_bitmap[i / 8]` if it is valid and clears it
+/// otherwise; `out_bitmap` holds `(count + 7) / 8` bytes and the unused bits
+/// of its last byte are cleared. Each task verifies the 8 signatures of one
+/// output byte. Returns `Success` if every signature is valid, otherwise
+/// `InvalidSignature`. `cancel` is optional, as for
+/// `starknet_verify_batch`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_starknet_verify_batch_soa")]
+pub unsafe extern "C" fn starknet_verify_batch_soa(
+    public_keys: *const FeltBytes,
+    message_hashes: *const FeltBytes,
+    r: *const FeltBytes,
+    s: *const FeltBytes,
+    count: usize,
+    cancel: *const StarkCancelToken,
+    out_bitmap: *mut u8,
+) -> StarkResult {
+    ffi_guard(|| {
+        let _timer = perf::timer(Op::VerifyBatch);
+        let cancel = match cancel::optional_token(cancel) {
+            Ok(t) => t,
+            Err(e) => return e,
+        };
+        if count == 0 {
+            return StarkResult::Success;
+        }
+        check_null!(public_keys, message_hashes, r, s, out_bitmap);
+        let fields = [public_keys, message_hashes, r, s]
+            .map(|field| core::slice::from_raw_parts(field, count));
+
+        let bitmap = pool::map_indices(count.div_ceil(8), |byte| {
+            let mut bits = 0u8;
+            for i in byte * 8..count.min(byte * 8 + 8) {
+                if cancel::is_cancelled(cancel) {
+                    break;
+                }
+                if verify_bytes(fields.map(|field| &field[i])) == StarkResult::Success {
+                    bits |= 1 << (i % 8);
+                }
+            }
+            bits
+        });
+        if cancel::is_cancelled(cancel) {
+            return cancel::cancelled("batch verification");
+        }
+        core::ptr::copy_nonoverlapping(bitmap.as_ptr(), out_bitmap, bitmap.len());
+        let first_failure = (0..count).find(|i| bitmap[i / 8] & (1 << (i % 8)) == 0);
+        match first_failure {
+            None => StarkResult::Success,
+            Some(i) => {
+                error::set_detail(format!("first failure: signature {i}"));
+                StarkResult::InvalidSignature
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ptr::{null, null_mut};
+
+    use crate::cancel::{
+        stark_cancel_token_cancel, stark_cancel_token_free, stark_cancel_token_new,
+        stark_cancel_token_reset,
+    };
+    use crate::{felt_to_bytes, starknet_get_public_key, starknet_sign, starknet_verify};
+
+    #[test]
+    fn test_verify_context_matches_verify() {
+        let private_key = felt_to_bytes(&Felt::from(12345u64));
+        let mut public_key = [0u8; 32];
+        let mut ctx = core::ptr::null_mut();
+
+        unsafe {
+            starknet_get_public_key(&private_key, &mut public_key);
+            let result = starknet_verify_context_new(&public_key, &mut ctx);
+            assert_eq!(result, StarkResult::Success);
+
+            for i in 0..4u64 {
+                let message_hash = felt_to_bytes(&Felt::from(1000 + i));
+                let other_hash = felt_to_bytes(&Felt::from(2000 + i));
+                let mut r = [0u8; 32];
+                let mut s = [0u8; 32];
+                starknet_sign(&private_key, &message_hash, &mut r, &mut s);
+
+                for hash in [&message_hash, &other_hash] {
+                    assert_eq!(
+                        starknet_verify_context_verify(ctx, hash, &r, &s),
+                        starknet_verify(&public_key, hash, &r, &s)
+                    );
+                }
+                assert_eq!(
+                    starknet_verify_context_verify(ctx, &message_hash, &r, &s),
+                    StarkResult::Success
+                );
+            }
+
+            // Out-of-range r and s
+            let zero = [0u8; 32];
+            assert_eq!(
+                starknet_verify_context_verify(ctx, &zero, &zero, &zero),
+                StarkResult::InvalidInput
+            );
+
+            starknet_verify_context_free(ctx);
+        }
+    }
+
+    #[test]
+    fn test_verify_context_rejects_off_curve_key() {
+        // Any x for which x^3 + ax + b has no square root
+        let mut ctx = core::ptr::null_mut();
+        let x = (0u64..)
+            .map(Felt::from)
+            .find(|x| AffinePoint::new_from_x(x, false).is_none())
+            .unwrap();
+
+        unsafe {
+            let result = starknet_verify_context_new(&felt_to_bytes(&x), &mut ctx);
+            assert_eq!(result, StarkResult::InvalidInput);
+            assert!(ctx.is_null());
+            starknet_verify_context_free(ctx);
+        }
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let private_key = felt_to_bytes(&Felt::from(12345u64));
+        let mut public_key = [0u8; 32];
+        let mut items = Vec::new();
+
+        unsafe {
+            starknet_get_public_key(&private_key, &mut public_key);
+            for i in 0..8u64 {
+                let message_hash = felt_to_bytes(&Felt::from(1000 + i));
+                let (mut r, mut s) = ([0u8; 32], [0u8; 32]);
+                starknet_sign(&private_key, &message_hash, &mut r, &mut s);
+                items.push(StarknetVerifyItem {
+                    public_key,
+                    message_hash,
+                    r,
+                    s,
+                });
+            }
+            let mut results = [StarkResult::InternalPanic; 8];
+            assert_eq!(
+                starknet_verify_batch(items.as_ptr(), items.len(), null(), results.as_mut_ptr()),
+                StarkResult::Success
+            );
+            assert_eq!(results, [StarkResult::Success; 8]);
+
+            items[2].message_hash = felt_to_bytes(&Felt::from(7u64));
+            items[5].r = [0u8; 32];
+            assert_eq!(
+                starknet_verify_batch(items.as_ptr(), items.len(), null(), results.as_mut_ptr()),
+                StarkResult::InvalidSignature
+            );
+            for (i, item) in items.iter().enumerate() {
+                let expected =
+                    starknet_verify(&item.public_key, &item.message_hash, &item.r, &item.s);
+                assert_eq!(results[i], expected);
+            }
+            assert_eq!(results[2], StarkResult::InvalidSignature);
+            assert_eq!(results[5], StarkResult::InvalidInput);
+
+            let mut token = null_mut();
+            stark_cancel_token_new(&mut token);
+            stark_cancel_token_cancel(token);
+            let mut untouched = [StarkResult::InternalPanic; 8];
+            assert_eq!(
+                starknet_verify_batch(items.as_ptr(), items.len(), token, untouched.as_mut_ptr()),
+                StarkResult::Cancelled
+            );
+            assert_eq!(untouched, [StarkResult::InternalPanic; 8]);
+            stark_cancel_token_reset(token);
+            assert_eq!(
+                starknet_verify_batch(items.as_ptr(), items.len(), token, results.as_mut_ptr()),
+                StarkResult::InvalidSignature
+            );
+            stark_cancel_token_free(token);
+
+            assert_eq!(
+                starknet_verify_batch(null(), 0, null(), null_mut()),
+                StarkResult::Success
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_batch_soa() {
+        let private_key = felt_to_bytes(&Felt::from(12345u64));
+        let mut public_key = [0u8; 32];
+        let count = 11;
+        let mut message_hashes = Vec::new();
+        let (mut rs, mut ss) = (Vec::new(), Vec::new());
+
+        unsafe {
+            starknet_get_public_key(&private_key, &mut public_key);
+            for i in 0..count as u64 {
+                let message_hash = felt_to_bytes(&Felt::from(1000 + i));
+                let (mut r, mut s) = ([0u8; 32], [0u8; 32]);
+                starknet_sign(&private_key, &message_hash, &mut r, &mut s);
+                message_hashes.push(message_hash);
+                rs.push(r);
+                ss.push(s);
+            }
+            let public_keys = vec![public_key; count];
+            let verify = |message_hashes: &[FeltBytes], rs: &[FeltBytes], bitmap: &mut [u8; 2]| {
+                starknet_verify_batch_soa(
+                    public_keys.as_ptr(),
+                    message_hashes.as_ptr(),
+                    rs.as_ptr(),
+                    ss.as_ptr(),
+                    count,
+                    null(),
+                    bitmap.as_mut_ptr(),
+                )
+            };
+
+            let mut bitmap = [0xaa; 2];
+            assert_eq!(verify(&message_hashes, &rs, &mut bitmap), StarkResult::Success);
+            assert_eq!(bitmap, [0xff, 0b111]);
+
+            message_hashes[2] = felt_to_bytes(&Felt::from(7u64));
+            rs[9] = [0u8; 32];
+            assert_eq!(
+                verify(&message_hashes, &rs, &mut bitmap),
+                StarkResult::InvalidSignature
+            );
+            assert_eq!(bitmap, [0b1111_1011, 0b101]);
+
+            let mut token = null_mut();
+            stark_cancel_token_new(&mut token);
+            stark_cancel_token_cancel(token);
+            let mut untouched = [0xaa; 2];
+            assert_eq!(
+                starknet_verify_batch_soa(
+                    public_keys.as_ptr(),
+                    message_hashes.as_ptr(),
+                    rs.as_ptr(),
+                    ss.as_ptr(),
+                    count,
+                    token,
+                    untouched.as_mut_ptr(),
+                ),
+                StarkResult::Cancelled
+            );
+            assert_eq!(untouched, [0xaa; 2]);
+            stark_cancel_token_free(token);
+
+            assert_eq!(
+                starknet_verify_batch_soa(null(), null(), null(), null(), 0, null(), null_mut()),
+                StarkResult::Success
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_full() {
+        let private_key = Felt::from(12345u64);
+        let public_point = &ProjectivePoint::from_affine_unchecked(
+            AffinePoint::generator().x(),
+            AffinePoint::generator().y(),
+        ) * private_key;
+        let public_point = public_point.to_affine().unwrap();
+        let x = felt_to_bytes(&public_point.x());
+        let y = felt_to_bytes(&public_point.y());
+        let neg_y = felt_to_bytes(&-public_point.y());
+        let message_hash = felt_to_bytes(&Felt::from(67890u64));
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+
+        unsafe {
+            starknet_sign(&felt_to_bytes(&private_key), &message_hash, &mut r, &mut s);
+
+            assert_eq!(
+                starknet_verify_full(&x, &y, &message_hash, &r, &s),
+                StarkResult::Success
+            );
+            // The negated key shares x but must not verify
+            assert_eq!(
+                starknet_verify_full(&x, &neg_y, &message_hash, &r, &s),
+                StarkResult::InvalidSignature
+            );
+            assert_eq!(
+                starknet_verify(&x, &message_hash, &r, &s),
+                StarkResult::Success
+            );
+            // Off-curve point
+            assert_eq!(
+                starknet_verify_full(&x, &x, &message_hash, &r, &s),
+                StarkResult::InvalidInput
+            );
+        }
+    }
+}
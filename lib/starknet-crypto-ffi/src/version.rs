@@ -0,0 +1,141 @@
+//! Library version and capability introspection
+//!
+//! For consumers that load the library dynamically: `stark_ffi_version`
+//! reports the crate version (the ABI follows semver: a minor bump only adds
+//! functions or result codes), and `stark_ffi_has_feature` tells whether a
+//! subsystem was compiled in.
+
+use core::ffi::{c_char, CStr};
+
+use crate::{ffi_guard, StarkResult};
+
+/// Subsystems compiled into this build, as accepted by `stark_ffi_has_feature`
+const FEATURES: &[&str] = &[
+    #[cfg(feature = "abi")]
+    "abi",
+    "address",
+    #[cfg(feature = "aead")]
+    "aead",
+    #[cfg(feature = "secp")]
+    "bip340",
+    #[cfg(feature = "mnemonic")]
+    "bip39",
+    #[cfg(feature = "bls")]
+    "bls",
+    "byte_array",
+    "calldata",
+    #[cfg(feature = "secp")]
+    "derivation",
+    #[cfg(feature = "ecies")]
+    "ecies",
+    #[cfg(feature = "ecdsa")]
+    "ecdsa",
+    #[cfg(feature = "ed25519")]
+    "ed25519",
+    #[cfg(feature = "encoding")]
+    "encoding",
+    #[cfg(feature = "secp")]
+    "eth",
+    #[cfg(feature = "hashing")]
+    "hashing",
+    #[cfg(feature = "keystore")]
+    "keystore",
+    #[cfg(feature = "kzg")]
+    "kzg",
+    #[cfg(feature = "merkle")]
+    "merkle",
+    #[cfg(feature = "typed_data")]
+    "outside_execution",
+    "schnorr",
+    #[cfg(feature = "sealed_box")]
+    "sealed_box",
+    "secret",
+    #[cfg(feature = "secp")]
+    "secp256k1",
+    #[cfg(feature = "secp")]
+    "secp256r1",
+    #[cfg(feature = "merkle")]
+    "session",
+    #[cfg(feature = "ecdsa")]
+    "transaction",
+    #[cfg(feature = "typed_data")]
+    "typed_data",
+    #[cfg(feature = "secp")]
+    "webauthn",
+    #[cfg(feature = "x25519")]
+    "x25519",
+    #[cfg(feature = "pedersen-tables")]
+    "pedersen-tables",
+    #[cfg(feature = "profiling")]
+    "profiling",
+    #[cfg(feature = "test-rng")]
+    "test-rng",
+    #[cfg(feature = "test-vectors")]
+    "test-vectors",
+];
+
+fn version_part(part: &str) -> u32 {
+    part.parse().unwrap_or(0)
+}
+
+/// Write the library version (`major.minor.patch`)
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_ffi_version")]
+pub unsafe extern "C" fn stark_ffi_version(
+    out_major: *mut u32,
+    out_minor: *mut u32,
+    out_patch: *mut u32,
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_major, out_minor, out_patch);
+        *out_major = version_part(env!("CARGO_PKG_VERSION_MAJOR"));
+        *out_minor = version_part(env!("CARGO_PKG_VERSION_MINOR"));
+        *out_patch = version_part(env!("CARGO_PKG_VERSION_PATCH"));
+        StarkResult::Success
+    })
+}
+
+/// Whether the subsystem `name` (a NUL-terminated string such as
+/// `"secp256k1"` or `"keystore"`) is compiled in
+///
+/// Returns false for unknown names and for a null `name`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_ffi_has_feature")]
+pub unsafe extern "C" fn stark_ffi_has_feature(name: *const c_char) -> bool {
+    if name.is_null() {
+        return false;
+    }
+    let name = CStr::from_ptr(name).to_bytes();
+    FEATURES.iter().any(|feature| feature.as_bytes() == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_and_features() {
+        let (mut major, mut minor, mut patch) = (u32::MAX, u32::MAX, u32::MAX);
+        unsafe {
+            assert_eq!(
+                stark_ffi_version(&mut major, &mut minor, &mut patch),
+                StarkResult::Success
+            );
+            assert_eq!(
+                stark_ffi_has_feature(c"secp256k1".as_ptr()),
+                cfg!(feature = "secp")
+            );
+            assert_eq!(
+                stark_ffi_has_feature(c"keystore".as_ptr()),
+                cfg!(feature = "keystore")
+            );
+            assert!(!stark_ffi_has_feature(c"quantum".as_ptr()));
+            assert!(!stark_ffi_has_feature(c"".as_ptr()));
+            assert!(!stark_ffi_has_feature(core::ptr::null()));
+        }
+        assert_eq!(
+            format!("{major}.{minor}.{patch}"),
+            env!("CARGO_PKG_VERSION")
+        );
+    }
+}
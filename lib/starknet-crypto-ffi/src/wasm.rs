@@ -0,0 +1,53 @@
+//! WebAssembly host interface (`wasm` feature, `wasm32-unknown-unknown`)
+//!
+//! The module exports the same extern functions as the native library, and
+//! every pointer argument is an offset into its linear memory (the `memory`
+//! export). A JavaScript host cannot hand over its own buffers: it reserves
+//! space with `stark_wasm_alloc`, copies the inputs in, calls the function,
+//! reads the outputs back and releases the space with `stark_buffer_free`.
+//! Handles are offsets as well, and stay opaque to the host. A panic cannot
+//! be caught on this target: it traps (`unreachable`), after which the
+//! instance must be discarded.
+//!
+//! There is no OS to draw randomness from, so the module imports
+//! `kundera.random_fill(ptr, len) -> i32` from the host, which fills `len`
+//! bytes at offset `ptr` (`crypto.getRandomValues` in a browser or Node) and
+//! returns 0, or non-zero on failure. `getrandom` reaches it through its
+//! custom backend, selected for this target in `.cargo/config.toml`.
+//!
+//! `examples/wasm-glue` is a minimal host.
+
+use alloc::vec;
+
+use crate::buffer::alloc_bytes_out;
+
+#[link(wasm_import_module = "kundera")]
+extern "C" {
+    /// Fill `len` bytes at `ptr` with secure random bytes (0 on success)
+    fn random_fill(ptr: *mut u8, len: usize) -> i32;
+}
+
+/// getrandom's custom backend: ask the host
+#[no_mangle]
+unsafe extern "Rust" fn __getrandom_v03_custom(
+    dest: *mut u8,
+    len: usize,
+) -> Result<(), getrandom::Error> {
+    match random_fill(dest, len) {
+        0 => Ok(()),
+        _ => Err(getrandom::Error::UNEXPECTED),
+    }
+}
+
+/// Reserve `len` zeroed bytes of linear memory and return their offset
+///
+/// The offset is never 0, even for `len == 0`. Release the space with
+/// `stark_buffer_free(offset, len)`.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_stark_wasm_alloc")]
+pub unsafe extern "C" fn stark_wasm_alloc(len: usize) -> *mut u8 {
+    let mut ptr = core::ptr::null_mut();
+    let mut out_len = 0;
+    alloc_bytes_out(vec![0; len], &mut ptr, &mut out_len);
+    ptr
+}
@@ -0,0 +1,292 @@
+//! WebAuthn (passkey) assertion helpers
+//!
+//! An authenticator signs `authenticatorData || sha256(clientDataJSON)` with
+//! P-256; the ECDSA prehash is the SHA-256 of that payload. Passkey account
+//! contracts rebuild the payload on-chain, so the host must check the same
+//! things before submitting:
+//!
+//! - `clientDataJSON.type` is `webauthn.get`
+//! - `clientDataJSON.challenge` is the base64url (unpadded) encoding of the
+//!   expected challenge bytes (typically the transaction hash)
+//! - the user-present flag is set in `authenticatorData`
+//!
+//! The relying party id hash and origin are not checked here.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::secp256r1;
+use crate::{error::null_pointer, ffi_guard, StarkResult};
+
+/// rpIdHash (32) || flags (1) || signCount (4)
+const AUTHENTICATOR_DATA_MIN_LEN: usize = 37;
+const FLAG_USER_PRESENT: u8 = 0x01;
+
+fn check_authenticator_data(authenticator_data: &[u8]) -> bool {
+    authenticator_data.len() >= AUTHENTICATOR_DATA_MIN_LEN
+        && authenticator_data[32] & FLAG_USER_PRESENT != 0
+}
+
+fn check_client_data(client_data_json: &[u8], challenge: &[u8]) -> bool {
+    let client_data: Value = match serde_json::from_slice(client_data_json) {
+        Ok(v) => v,
+        Err(_) => return false,
+    };
+    let expected_challenge = URL_SAFE_NO_PAD.encode(challenge);
+    client_data.get("type").and_then(Value::as_str) == Some("webauthn.get")
+        && client_data.get("challenge").and_then(Value::as_str) == Some(&expected_challenge)
+}
+
+/// `sha256(authenticatorData || sha256(clientDataJSON))`
+pub(crate) fn assertion_hash(authenticator_data: &[u8], client_data_json: &[u8]) -> [u8; 32] {
+    let client_data_hash = Sha256::digest(client_data_json);
+    let mut hasher = Sha256::new();
+    hasher.update(authenticator_data);
+    hasher.update(client_data_hash);
+    hasher.finalize().into()
+}
+
+/// Checked assertion hash: `NullPointer` for a null buffer, `InvalidInput` if
+/// the assertion does not match the expected challenge
+unsafe fn checked_assertion_hash(
+    authenticator_data: *const u8,
+    authenticator_data_len: usize,
+    client_data_json: *const u8,
+    client_data_json_len: usize,
+    challenge: *const u8,
+    challenge_len: usize,
+) -> Result<[u8; 32], StarkResult> {
+    if authenticator_data.is_null() {
+        return Err(null_pointer("authenticator_data"));
+    }
+    if client_data_json.is_null() {
+        return Err(null_pointer("client_data_json"));
+    }
+    if challenge.is_null() && challenge_len > 0 {
+        return Err(null_pointer("challenge"));
+    }
+    let authenticator_data =
+        core::slice::from_raw_parts(authenticator_data, authenticator_data_len);
+    let client_data_json = core::slice::from_raw_parts(client_data_json, client_data_json_len);
+    let challenge = if challenge_len > 0 {
+        core::slice::from_raw_parts(challenge, challenge_len)
+    } else {
+        &[]
+    };
+
+    if !check_authenticator_data(authenticator_data)
+        || !check_client_data(client_data_json, challenge)
+    {
+        return Err(StarkResult::InvalidInput);
+    }
+    Ok(assertion_hash(authenticator_data, client_data_json))
+}
+
+/// Compute the P-256 message hash signed by a WebAuthn assertion
+///
+/// Checks that `client_data_json` is a `webauthn.get` assertion over
+/// `challenge` and that the user-present flag is set, then writes
+/// `sha256(authenticator_data || sha256(client_data_json))`. Returns
+/// `InvalidInput` if a check fails.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_webauthn_assertion_hash")]
+pub unsafe extern "C" fn webauthn_assertion_hash(
+    authenticator_data: *const u8,
+    authenticator_data_len: usize,
+    client_data_json: *const u8,
+    client_data_json_len: usize,
+    challenge: *const u8,
+    challenge_len: usize,
+    out: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out);
+        match checked_assertion_hash(
+            authenticator_data,
+            authenticator_data_len,
+            client_data_json,
+            client_data_json_len,
+            challenge,
+            challenge_len,
+        ) {
+            Ok(hash) => {
+                *out = hash;
+                StarkResult::Success
+            }
+            Err(e) => e,
+        }
+    })
+}
+
+/// Verify a WebAuthn assertion signature against a P-256 public key (x || y)
+///
+/// Performs the checks of `webauthn_assertion_hash` (`InvalidInput` on
+/// failure), then verifies (r, s) over the assertion hash.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_webauthn_verify")]
+pub unsafe extern "C" fn webauthn_verify(
+    public_key: *const [u8; 64],
+    authenticator_data: *const u8,
+    authenticator_data_len: usize,
+    client_data_json: *const u8,
+    client_data_json_len: usize,
+    challenge: *const u8,
+    challenge_len: usize,
+    r: *const [u8; 32],
+    s: *const [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(public_key, r, s);
+        let key = match secp256r1::verifying_key(&*public_key) {
+            Some(k) => k,
+            None => return StarkResult::InvalidInput,
+        };
+        let hash = match checked_assertion_hash(
+            authenticator_data,
+            authenticator_data_len,
+            client_data_json,
+            client_data_json_len,
+            challenge,
+            challenge_len,
+        ) {
+            Ok(h) => h,
+            Err(e) => return e,
+        };
+
+        match secp256r1::verify_prehash(&key, &hash, &*r, &*s) {
+            Some(true) => StarkResult::Success,
+            Some(false) => StarkResult::InvalidSignature,
+            None => StarkResult::InvalidInput,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secp256r1::{secp256r1_get_public_key, secp256r1_sign};
+
+    const PRIVATE_KEY: [u8; 32] = [0x42; 32];
+    const CHALLENGE: [u8; 32] = [0xab; 32];
+
+    fn authenticator_data() -> Vec<u8> {
+        let mut data = Sha256::digest(b"example.com").to_vec();
+        data.push(0x05); // user present + user verified
+        data.extend_from_slice(&1u32.to_be_bytes());
+        data
+    }
+
+    fn client_data_json(challenge: &[u8]) -> String {
+        format!(
+            r#"{{"type":"webauthn.get","challenge":"{}","origin":"https://example.com","crossOrigin":false}}"#,
+            URL_SAFE_NO_PAD.encode(challenge)
+        )
+    }
+
+    fn sign(authenticator_data: &[u8], client_data_json: &str) -> ([u8; 64], [u8; 32], [u8; 32]) {
+        let hash = assertion_hash(authenticator_data, client_data_json.as_bytes());
+        let mut public_key = [0u8; 64];
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        unsafe {
+            secp256r1_get_public_key(&PRIVATE_KEY, &mut public_key);
+            secp256r1_sign(&PRIVATE_KEY, &hash, &mut r, &mut s);
+        }
+        (public_key, r, s)
+    }
+
+    unsafe fn verify(
+        public_key: &[u8; 64],
+        authenticator_data: &[u8],
+        client_data_json: &str,
+        r: &[u8; 32],
+        s: &[u8; 32],
+    ) -> StarkResult {
+        webauthn_verify(
+            public_key,
+            authenticator_data.as_ptr(),
+            authenticator_data.len(),
+            client_data_json.as_ptr(),
+            client_data_json.len(),
+            CHALLENGE.as_ptr(),
+            CHALLENGE.len(),
+            r,
+            s,
+        )
+    }
+
+    #[test]
+    fn test_webauthn_assertion_hash() {
+        let authenticator_data = authenticator_data();
+        let client_data_json = client_data_json(&CHALLENGE);
+        let mut out = [0u8; 32];
+
+        unsafe {
+            let result = webauthn_assertion_hash(
+                authenticator_data.as_ptr(),
+                authenticator_data.len(),
+                client_data_json.as_ptr(),
+                client_data_json.len(),
+                CHALLENGE.as_ptr(),
+                CHALLENGE.len(),
+                &mut out,
+            );
+            assert_eq!(result, StarkResult::Success);
+        }
+
+        let mut payload = authenticator_data.clone();
+        payload.extend_from_slice(&Sha256::digest(client_data_json.as_bytes()));
+        assert_eq!(out, <[u8; 32]>::from(Sha256::digest(&payload)));
+    }
+
+    #[test]
+    fn test_webauthn_verify() {
+        let authenticator_data = authenticator_data();
+        let client_data_json = client_data_json(&CHALLENGE);
+        let (public_key, r, s) = sign(&authenticator_data, &client_data_json);
+
+        unsafe {
+            let result = verify(&public_key, &authenticator_data, &client_data_json, &r, &s);
+            assert_eq!(result, StarkResult::Success);
+
+            // Tampered sign counter
+            let mut tampered = authenticator_data.clone();
+            tampered[36] ^= 1;
+            let result = verify(&public_key, &tampered, &client_data_json, &r, &s);
+            assert_eq!(result, StarkResult::InvalidSignature);
+        }
+    }
+
+    #[test]
+    fn test_webauthn_rejects_wrong_client_data() {
+        let authenticator_data = authenticator_data();
+
+        // Signed over a different challenge
+        let other_challenge = client_data_json(&[0u8; 32]);
+        let (public_key, r, s) = sign(&authenticator_data, &other_challenge);
+        unsafe {
+            let result = verify(&public_key, &authenticator_data, &other_challenge, &r, &s);
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+
+        // Registration ceremony instead of an assertion
+        let create = client_data_json(&CHALLENGE).replace("webauthn.get", "webauthn.create");
+        let (public_key, r, s) = sign(&authenticator_data, &create);
+        unsafe {
+            let result = verify(&public_key, &authenticator_data, &create, &r, &s);
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+
+        // User-present flag cleared
+        let mut not_present = authenticator_data.clone();
+        not_present[32] = 0x04;
+        let client_data_json = client_data_json(&CHALLENGE);
+        let (public_key, r, s) = sign(&not_present, &client_data_json);
+        unsafe {
+            let result = verify(&public_key, &not_present, &client_data_json, &r, &s);
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! X25519 Diffie-Hellman (RFC 7748)
+//!
+//! Keys and shared secrets are 32-byte little-endian u-coordinates, as used
+//! by libsodium and the platform crypto APIs. Private keys are clamped on
+//! use, so any 32 random bytes are a valid private key.
+//!
+//! The raw shared secret should go through a KDF before use as a symmetric
+//! key.
+
+use x25519_dalek::X25519_BASEPOINT_BYTES;
+use zeroize::Zeroizing;
+
+use crate::random;
+use crate::{ffi_guard, StarkResult};
+
+/// Generate a random keypair (private key, public key)
+///
+/// Returns `RngFailed` if the OS random number generator is unavailable.
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_x25519_keypair")]
+pub unsafe extern "C" fn x25519_keypair(
+    out_private: *mut [u8; 32],
+    out_public: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(out_private, out_public);
+        let mut private_key = Zeroizing::new([0u8; 32]);
+        if random::fill_random(&mut private_key[..]).is_err() {
+            return StarkResult::RngFailed;
+        }
+
+        *out_private = *private_key;
+        *out_public = x25519_dalek::x25519(*private_key, X25519_BASEPOINT_BYTES);
+        StarkResult::Success
+    })
+}
+
+/// Derive the public key from a private key
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_x25519_get_public_key")]
+pub unsafe extern "C" fn x25519_get_public_key(
+    private_key: *const [u8; 32],
+    out: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(private_key, out);
+        *out = x25519_dalek::x25519(*private_key, X25519_BASEPOINT_BYTES);
+        StarkResult::Success
+    })
+}
+
+/// Compute the shared secret between a private key and a peer's public key
+///
+/// Returns `InvalidInput` if the result is all zeros, i.e. the peer key is a
+/// low-order point (same check as libsodium's `crypto_scalarmult`).
+#[no_mangle]
+#[cfg_attr(feature = "symbol-prefix", export_name = "kundera_x25519")]
+pub unsafe extern "C" fn x25519(
+    private_key: *const [u8; 32],
+    peer_public: *const [u8; 32],
+    out_shared: *mut [u8; 32],
+) -> StarkResult {
+    ffi_guard(|| {
+        check_null!(private_key, peer_public, out_shared);
+        let shared = x25519_dalek::x25519(*private_key, *peer_public);
+        if shared == [0u8; 32] {
+            return StarkResult::InvalidInput;
+        }
+
+        *out_shared = shared;
+        StarkResult::Success
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex32(s: &str) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    // RFC 7748 6.1
+    const ALICE_PRIVATE: &str = "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a";
+    const ALICE_PUBLIC: &str = "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a";
+    const BOB_PRIVATE: &str = "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb";
+    const BOB_PUBLIC: &str = "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f";
+    const SHARED: &str = "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742";
+
+    #[test]
+    fn test_x25519_rfc7748() {
+        let mut alice_public = [0u8; 32];
+        let mut bob_public = [0u8; 32];
+        let mut alice_shared = [0u8; 32];
+        let mut bob_shared = [0u8; 32];
+
+        unsafe {
+            x25519_get_public_key(&hex32(ALICE_PRIVATE), &mut alice_public);
+            x25519_get_public_key(&hex32(BOB_PRIVATE), &mut bob_public);
+
+            let result = x25519(&hex32(ALICE_PRIVATE), &bob_public, &mut alice_shared);
+            assert_eq!(result, StarkResult::Success);
+            let result = x25519(&hex32(BOB_PRIVATE), &alice_public, &mut bob_shared);
+            assert_eq!(result, StarkResult::Success);
+        }
+        assert_eq!(alice_public, hex32(ALICE_PUBLIC));
+        assert_eq!(bob_public, hex32(BOB_PUBLIC));
+        assert_eq!(alice_shared, hex32(SHARED));
+        assert_eq!(bob_shared, hex32(SHARED));
+    }
+
+    #[test]
+    fn test_x25519_keypair_and_low_order_peer() {
+        let mut private_key = [0u8; 32];
+        let mut public_key = [0u8; 32];
+        let mut expected_public = [0u8; 32];
+        let mut shared = [0u8; 32];
+
+        unsafe {
+            let result = x25519_keypair(&mut private_key, &mut public_key);
+            assert_eq!(result, StarkResult::Success);
+            x25519_get_public_key(&private_key, &mut expected_public);
+            assert_eq!(public_key, expected_public);
+
+            // u = 0 is a low-order point
+            let result = x25519(&private_key, &[0u8; 32], &mut shared);
+            assert_eq!(result, StarkResult::InvalidInput);
+        }
+    }
+}
@@ -27,6 +27,16 @@ export enum StarkResult {
 	DivisionByZero = 4,
 	NoInverse = 5,
 	NoSquareRoot = 6,
+	InvalidPrivateKey = 7,
+	RngFailed = 8,
+	DecryptionFailed = 9,
+	MemoryLockFailed = 10,
+	InternalPanic = 11,
+	NullPointer = 12,
+	InvalidHandle = 13,
+	BufferTooSmall = 14,
+	Cancelled = 15,
+	SelfTestFailed = 16,
 }
 
 // ============ Backend Types ============
@@ -20,6 +20,16 @@ export enum StarkResult {
 	DivisionByZero = 4,
 	NoInverse = 5,
 	NoSquareRoot = 6,
+	InvalidPrivateKey = 7,
+	RngFailed = 8,
+	DecryptionFailed = 9,
+	MemoryLockFailed = 10,
+	InternalPanic = 11,
+	NullPointer = 12,
+	InvalidHandle = 13,
+	BufferTooSmall = 14,
+	Cancelled = 15,
+	SelfTestFailed = 16,
 }
 
 // Lazily loaded koffi module
@@ -175,6 +185,26 @@ function checkResult(result: number): void {
 			throw new Error("No multiplicative inverse");
 		case StarkResult.NoSquareRoot:
 			throw new Error("No square root exists");
+		case StarkResult.InvalidPrivateKey:
+			throw new Error("Invalid private key");
+		case StarkResult.RngFailed:
+			throw new Error("Random number generation failed");
+		case StarkResult.DecryptionFailed:
+			throw new Error("Decryption failed");
+		case StarkResult.MemoryLockFailed:
+			throw new Error("Memory lock failed");
+		case StarkResult.InternalPanic:
+			throw new Error("Internal panic");
+		case StarkResult.NullPointer:
+			throw new Error("Null pointer");
+		case StarkResult.InvalidHandle:
+			throw new Error("Invalid handle");
+		case StarkResult.BufferTooSmall:
+			throw new Error("Buffer too small");
+		case StarkResult.Cancelled:
+			throw new Error("Operation cancelled");
+		case StarkResult.SelfTestFailed:
+			throw new Error("Self-test failed");
 		default:
 			throw new Error(`Unknown error code: ${result}`);
 	}
@@ -172,6 +172,26 @@ function checkResult(code: number): void {
 			throw new Error("No multiplicative inverse");
 		case ErrorCode.NoSquareRoot:
 			throw new Error("No square root exists");
+		case ErrorCode.InvalidPrivateKey:
+			throw new Error("Invalid private key");
+		case ErrorCode.RngFailed:
+			throw new Error("Random number generation failed");
+		case ErrorCode.DecryptionFailed:
+			throw new Error("Decryption failed");
+		case ErrorCode.MemoryLockFailed:
+			throw new Error("Memory lock failed");
+		case ErrorCode.InternalPanic:
+			throw new Error("Internal panic");
+		case ErrorCode.NullPointer:
+			throw new Error("Null pointer");
+		case ErrorCode.InvalidHandle:
+			throw new Error("Invalid handle");
+		case ErrorCode.BufferTooSmall:
+			throw new Error("Buffer too small");
+		case ErrorCode.Cancelled:
+			throw new Error("Operation cancelled");
+		case ErrorCode.SelfTestFailed:
+			throw new Error("Self-test failed");
 		default:
 			throw new Error(`Unknown error code: ${code}`);
 	}